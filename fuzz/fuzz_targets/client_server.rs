@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wire_weaver_fuzz::check_round_trip;
+use ww_client_server::{Event, EventOwned, Request, RequestOwned};
+
+fuzz_target!(|data: &[u8]| {
+    check_round_trip!(Request<'_>, data);
+    check_round_trip!(RequestOwned, data);
+    check_round_trip!(Event<'_>, data);
+    check_round_trip!(EventOwned, data);
+});
@@ -0,0 +1,78 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use shrink_wrap::prelude::*;
+use wire_weaver_fuzz::check_round_trip;
+
+#[derive_shrink_wrap]
+#[owned = "std"]
+#[derive(Debug, Clone)]
+struct Inner<'i> {
+    label: &'i str,
+    enabled: bool,
+    #[flag]
+    gain: bool,
+    gain: Option<u16>,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(unib32)]
+#[owned = "std"]
+#[derive(Debug, Clone)]
+enum Command<'i> {
+    Stop,
+    Move { x: i32, y: Option<i32> },
+    Configure(Inner<'i>, RefVec<'i, u8>),
+}
+
+#[derive_shrink_wrap]
+#[owned = "std"]
+#[derive(Debug, Clone)]
+struct Everything<'i> {
+    a: u8,
+    flag: bool,
+    nib: Nibble,
+    b: UNib32,
+    c: Option<f32>,
+    name: &'i str,
+    status: Result<u16, &'i str>,
+    array: [Option<i8>; 3],
+    pair: (i64, &'i str),
+    list: RefVec<'i, Inner<'i>>,
+    names: RefVec<'i, &'i str>,
+    boxed: RefBox<'i, Command<'i>>,
+    range: Range<u16>,
+    range_inclusive: RangeInclusive<u8>,
+    bounded_name: BoundedString<8>,
+    bounded_list: BoundedVec<u16, 4>,
+    wide: (u128, i128),
+    double: f64,
+}
+
+#[derive_shrink_wrap]
+#[sized]
+#[derive(Debug, Clone)]
+struct Fixed {
+    a: u32,
+    b: bool,
+    c: Nibble,
+}
+
+fuzz_target!(|data: &[u8]| {
+    check_round_trip!(bool, data);
+    check_round_trip!(UNib32, data);
+    check_round_trip!(&str, data);
+    check_round_trip!(String, data);
+    check_round_trip!(RefVec<'_, u8>, data);
+    check_round_trip!(RefVec<'_, &str>, data);
+    check_round_trip!(Vec<String>, data);
+    check_round_trip!(Vec<Option<UNib32>>, data);
+    check_round_trip!(Option<(u8, &str)>, data);
+    check_round_trip!(Result<[u16; 2], &str>, data);
+    check_round_trip!(BoundedVec<BoundedString<4>, 3>, data);
+    check_round_trip!(Fixed, data);
+    check_round_trip!(Inner<'_>, data);
+    check_round_trip!(Command<'_>, data);
+    check_round_trip!(Everything<'_>, data);
+    check_round_trip!(EverythingOwned, data);
+});
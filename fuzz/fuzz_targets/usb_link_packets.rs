@@ -0,0 +1,68 @@
+#![no_main]
+
+use core::future::{Future, ready};
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+use libfuzzer_sys::fuzz_target;
+use wire_weaver_usb_link::{Error, PacketSink, PacketSource, WireWeaverUsbLink};
+use ww_version::{FullVersionOwned, VersionOwned};
+
+const PACKET_LEN: usize = 64;
+
+/// Feeds fuzzer input as a sequence of packets, first byte of each is its length.
+struct FuzzSource<'i> {
+    data: &'i [u8],
+}
+
+impl PacketSource for FuzzSource<'_> {
+    type Error = ();
+
+    fn read_packet(&mut self, packet: &mut [u8]) -> impl Future<Output = Result<usize, ()>> {
+        let Some((&len, rest)) = self.data.split_first() else {
+            return ready(Err(()));
+        };
+        let len = (len as usize).min(rest.len()).min(packet.len());
+        packet[..len].copy_from_slice(&rest[..len]);
+        self.data = &rest[len..];
+        ready(Ok(len))
+    }
+
+    async fn wait_usb_connection(&mut self) {}
+}
+
+struct NullSink;
+
+impl PacketSink for NullSink {
+    type Error = ();
+
+    async fn write_packet(&mut self, _data: &[u8]) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut tx_buf = [0u8; PACKET_LEN];
+    let mut rx_buf = [0u8; PACKET_LEN];
+    let mut link = WireWeaverUsbLink::new_host(
+        FullVersionOwned::new("fuzz".into(), VersionOwned::new(0, 1, 0)),
+        NullSink,
+        &mut tx_buf,
+        FuzzSource { data },
+        &mut rx_buf,
+    );
+    let mut message = [0u8; 512];
+    let mut cx = Context::from_waker(Waker::noop());
+    // several messages can be packed into one packet, but there are never more of them than input bytes
+    for _ in 0..=data.len() {
+        let result = {
+            let fut = pin!(link.receive_message(&mut message));
+            match fut.poll(&mut cx) {
+                Poll::Ready(result) => result,
+                Poll::Pending => break,
+            }
+        };
+        if let Err(Error::SourceError(())) = result {
+            break;
+        }
+    }
+});
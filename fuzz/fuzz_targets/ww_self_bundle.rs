@@ -0,0 +1,24 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use shrink_wrap::prelude::*;
+use wire_weaver_fuzz::check_round_trip;
+use ww_self::{ApiBundleOwned, TypeLocationOwned, ValueOwned};
+
+fuzz_target!(|data: &[u8]| {
+    check_round_trip!(ApiBundleOwned, data);
+
+    // first part is an ApiBundle, the rest is a value of one of its types, deserialized dynamically
+    let Some((&split, data)) = data.split_first() else {
+        return;
+    };
+    let (bundle_bytes, value_bytes) = data.split_at((split as usize).min(data.len()));
+    let Ok(bundle) = ApiBundleOwned::from_ww_bytes(bundle_bytes) else {
+        return;
+    };
+    for location in &bundle.types {
+        if let TypeLocationOwned::InLine { ty, .. } = location {
+            let _ = ValueOwned::des_shrink_wrap_dyn(value_bytes, ty, &bundle);
+        }
+    }
+});
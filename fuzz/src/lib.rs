@@ -0,0 +1,24 @@
+//! Helpers shared between fuzz targets.
+
+/// Deserialize `$ty` from `$data`, and if that succeeds, check that serializing it back and deserializing again
+/// reaches a fixed point: bytes produced from the first and the second values must be equal.
+///
+/// Values are compared in serialized form, because not all the types implement `PartialEq` and floats can be NaN.
+#[macro_export]
+macro_rules! check_round_trip {
+    ($ty:ty, $data:expr) => {{
+        use shrink_wrap::prelude::*;
+        let data: &[u8] = $data;
+        // decoded value can be bigger than the input, e.g., when trailing fields are defaulted
+        let mut buf = vec![0u8; data.len() * 8 + 1024];
+        // RefVec items are deserialized lazily, so malformed ones are only noticed when serializing
+        if let Ok(bytes) = <$ty>::from_ww_bytes(data).and_then(|value| value.to_ww_bytes(&mut buf))
+        {
+            let value_again =
+                <$ty>::from_ww_bytes(bytes).expect("serialized value must deserialize");
+            let mut buf_again = vec![0u8; bytes.len() * 8 + 1024];
+            let bytes_again = value_again.to_ww_bytes(&mut buf_again).unwrap();
+            assert_eq!(bytes, bytes_again);
+        }
+    }};
+}
@@ -84,6 +84,7 @@ impl DeserializeShrinkWrapOwned for String {
 
 impl<T: SerializeShrinkWrap> SerializeShrinkWrap for Box<T> {
     const ELEMENT_SIZE: ElementSize = ElementSize::Unsized;
+    const MAX_SERIALIZED_LEN: Option<usize> = T::MAX_SERIALIZED_LEN;
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         T::ser_shrink_wrap(self, wr)
@@ -107,3 +108,49 @@ impl<T: DeserializeShrinkWrapOwned> DeserializeShrinkWrapOwned for Box<T> {
         Ok(Box::new(value))
     }
 }
+
+/// Serialize a value into a Vec, growing the scratch buffer until the value fits, up to `max_len` bytes.
+pub fn to_ww_vec<T: SerializeShrinkWrap + ?Sized>(
+    value: &T,
+    max_len: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut scratch = Vec::new();
+    to_ww_scratch(value, &mut scratch, max_len).map(|bytes| bytes.to_vec())
+}
+
+/// Serialize a value into a reusable scratch buffer, growing it until the value fits, up to `max_len` bytes.
+pub fn to_ww_scratch<'s, T: SerializeShrinkWrap + ?Sized>(
+    value: &T,
+    scratch: &'s mut Vec<u8>,
+    max_len: usize,
+) -> Result<&'s [u8], Error> {
+    grow_to_fit(
+        scratch,
+        max_len,
+        |buf| value.to_ww_bytes(buf).map(|bytes| bytes.len()),
+        Error::is_write_eob,
+    )
+}
+
+/// Run `ser` on the scratch buffer, doubling it while `is_write_eob` reports that a value did not fit, up to
+/// `max_len` bytes. `ser` returns the serialized length.
+pub fn grow_to_fit<E>(
+    scratch: &mut Vec<u8>,
+    max_len: usize,
+    mut ser: impl FnMut(&mut [u8]) -> Result<usize, E>,
+    is_write_eob: impl Fn(&E) -> bool,
+) -> Result<&[u8], E> {
+    if scratch.is_empty() {
+        scratch.resize(256.min(max_len), 0);
+    }
+    loop {
+        let scratch_len = scratch.len();
+        match ser(scratch) {
+            Ok(len) => return Ok(&scratch[..len]),
+            Err(e) if is_write_eob(&e) && scratch_len < max_len => {
+                scratch.resize((scratch_len * 2).min(max_len), 0);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Owned fixed-capacity containers for `no_std` without allocator.
+//!
+//! [BoundedVec] and [BoundedString] are serialized exactly as `Vec<T>` / `RefVec<'i, T>` and `String` / `&'i str`,
+//! so a device can store them in its state, while a host can still use standard types.
+//! Deserializing more than `N` elements or bytes results in [Error::CapacityExceeded].
+
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::{Deref, DerefMut};
+
+use crate::{
+    BufReader, BufWriter, DeserializeShrinkWrap, DeserializeShrinkWrapOwned, ElementSize, Error,
+    SerializeShrinkWrap, max_len,
+};
+
+/// Vec-like container with a maximum capacity of `N` elements, stored inline.
+///
+/// ```
+/// use shrink_wrap::BoundedVec;
+///
+/// let mut values = BoundedVec::<u8, 2>::new();
+/// values.push(1).unwrap();
+/// values.push(2).unwrap();
+/// assert_eq!(values.push(3), Err(3));
+/// assert_eq!(values.as_slice(), &[1, 2]);
+/// ```
+pub struct BoundedVec<T, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+impl<T: Default, const N: usize> BoundedVec<T, N> {
+    pub fn new() -> Self {
+        BoundedVec {
+            items: core::array::from_fn(|_| T::default()),
+            len: 0,
+        }
+    }
+
+    /// Create a new vector from a slice, returns [Error::CapacityExceeded] if `slice.len() > N`.
+    pub fn from_slice(slice: &[T]) -> Result<Self, Error>
+    where
+        T: Clone,
+    {
+        if slice.len() > N {
+            return Err(Error::CapacityExceeded);
+        }
+        let mut vec = Self::new();
+        vec.items[..slice.len()].clone_from_slice(slice);
+        vec.len = slice.len();
+        Ok(vec)
+    }
+
+    /// Remove the last element and return it, or None if the vector is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(core::mem::take(&mut self.items[self.len]))
+    }
+
+    /// Shorten the vector, keeping only the first `len` elements. Has no effect if `len` is greater than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.pop();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T, const N: usize> BoundedVec<T, N> {
+    /// Append an element to the back, returns it back if the vector is already full.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if self.len == N {
+            return Err(item);
+        }
+        self.items[self.len] = item;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.items[..self.len]
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.items[..self.len]
+    }
+}
+
+impl<T: Default, const N: usize> Default for BoundedVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for BoundedVec<T, N> {
+    fn clone(&self) -> Self {
+        BoundedVec {
+            items: self.items.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for BoundedVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for BoundedVec<T, N> {}
+
+impl<T: Debug, const N: usize> Debug for BoundedVec<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<T: defmt::Format, const N: usize> defmt::Format for BoundedVec<T, N> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{}", self.as_slice())
+    }
+}
+
+impl<T, const N: usize> Deref for BoundedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for BoundedVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a BoundedVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl<T: Default + Clone, const N: usize> TryFrom<&[T]> for BoundedVec<T, N> {
+    type Error = Error;
+
+    fn try_from(slice: &[T]) -> Result<Self, Self::Error> {
+        Self::from_slice(slice)
+    }
+}
+
+impl<T: SerializeShrinkWrap, const N: usize> SerializeShrinkWrap for BoundedVec<T, N> {
+    const ELEMENT_SIZE: ElementSize = ElementSize::UnsizedFinalStructure;
+    const MAX_SERIALIZED_LEN: Option<usize> = max_len::root_len(max_len::add(
+        Some(max_len::unib32_nibbles(N) * 4),
+        max_len::mul(max_len::nested_bits::<T>(), N),
+    ));
+
+    fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
+        let Ok(len_u16) = u16::try_from(self.len) else {
+            return Err(Error::VecTooLong);
+        };
+        wr.write_u16_rev(len_u16)?;
+        for item in self.as_slice() {
+            wr.write(item)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'i, T: DeserializeShrinkWrap<'i> + Default, const N: usize> DeserializeShrinkWrap<'i>
+    for BoundedVec<T, N>
+{
+    const ELEMENT_SIZE: ElementSize = ElementSize::UnsizedFinalStructure;
+
+    fn des_shrink_wrap<'di>(rd: &'di mut BufReader<'i>) -> Result<Self, Error> {
+        let elements_count = rd.read_unib32_rev()?;
+        if elements_count as usize > N {
+            return Err(Error::CapacityExceeded);
+        }
+        let mut items = Self::new();
+        for _ in 0..elements_count {
+            items
+                .push(rd.read()?)
+                .map_err(|_| Error::CapacityExceeded)?;
+        }
+        Ok(items)
+    }
+}
+
+impl<T: DeserializeShrinkWrapOwned + Default, const N: usize> DeserializeShrinkWrapOwned
+    for BoundedVec<T, N>
+{
+    const ELEMENT_SIZE: ElementSize = ElementSize::UnsizedFinalStructure;
+
+    fn des_shrink_wrap_owned(rd: &mut BufReader<'_>) -> Result<Self, Error> {
+        let elements_count = rd.read_unib32_rev()?;
+        if elements_count as usize > N {
+            return Err(Error::CapacityExceeded);
+        }
+        let mut items = Self::new();
+        for _ in 0..elements_count {
+            items
+                .push(rd.read_owned()?)
+                .map_err(|_| Error::CapacityExceeded)?;
+        }
+        Ok(items)
+    }
+}
+
+/// UTF-8 string with a maximum length of `N` bytes, stored inline.
+///
+/// ```
+/// use shrink_wrap::BoundedString;
+///
+/// let mut name = BoundedString::<8>::try_from("abc").unwrap();
+/// name.push_str("def").unwrap();
+/// assert_eq!(name.as_str(), "abcdef");
+/// assert!(name.push_str("ghi").is_err());
+/// ```
+#[derive(Copy, Clone)]
+pub struct BoundedString<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> BoundedString<N> {
+    pub const fn new() -> Self {
+        BoundedString {
+            bytes: [0u8; N],
+            len: 0,
+        }
+    }
+
+    /// Append a string slice, returns [Error::CapacityExceeded] and leaves self unchanged if it doesn't fit.
+    pub fn push_str(&mut self, s: &str) -> Result<(), Error> {
+        let new_len = self.len + s.len();
+        if new_len > N {
+            return Err(Error::CapacityExceeded);
+        }
+        self.bytes[self.len..new_len].copy_from_slice(s.as_bytes());
+        self.len = new_len;
+        Ok(())
+    }
+
+    pub fn push(&mut self, ch: char) -> Result<(), Error> {
+        let mut buf = [0u8; 4];
+        self.push_str(ch.encode_utf8(&mut buf))
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only ever appended to from &str, so always valid
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const N: usize> Default for BoundedString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// bytes past len are left over after clear() and are not part of the value
+impl<const N: usize> PartialEq for BoundedString<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for BoundedString<N> {}
+
+impl<const N: usize> Hash for BoundedString<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<const N: usize> Deref for BoundedString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for BoundedString<N> {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let mut string = Self::new();
+        string.push_str(s)?;
+        Ok(string)
+    }
+}
+
+impl<const N: usize> Debug for BoundedString<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> Display for BoundedString<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const N: usize> defmt::Format for BoundedString<N> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{=str}", self.as_str())
+    }
+}
+
+impl<const N: usize> SerializeShrinkWrap for BoundedString<N> {
+    const ELEMENT_SIZE: ElementSize = ElementSize::Unsized;
+    const MAX_SERIALIZED_LEN: Option<usize> = Some(N);
+
+    fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
+        wr.write_raw_str(self.as_str())
+    }
+}
+
+impl<'i, const N: usize> DeserializeShrinkWrap<'i> for BoundedString<N> {
+    const ELEMENT_SIZE: ElementSize = ElementSize::Unsized;
+
+    fn des_shrink_wrap<'di>(rd: &'di mut BufReader<'i>) -> Result<Self, Error> {
+        Self::try_from(rd.read_raw_str()?)
+    }
+}
+
+impl<const N: usize> DeserializeShrinkWrapOwned for BoundedString<N> {
+    const ELEMENT_SIZE: ElementSize = ElementSize::Unsized;
+
+    fn des_shrink_wrap_owned(rd: &mut BufReader<'_>) -> Result<Self, Error> {
+        Self::try_from(rd.read_raw_str()?)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{BoundedString, BoundedVec};
+    use core::fmt::Formatter;
+    use core::marker::PhantomData;
+    use serde::de::{Error as _, SeqAccess, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    impl<T: Serialize, const N: usize> Serialize for BoundedVec<T, N> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.as_slice())
+        }
+    }
+
+    impl<'de, T: Deserialize<'de> + Default, const N: usize> Deserialize<'de> for BoundedVec<T, N> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct SeqVisitor<T, const N: usize>(PhantomData<T>);
+
+            impl<'de, T: Deserialize<'de> + Default, const N: usize> Visitor<'de> for SeqVisitor<T, N> {
+                type Value = BoundedVec<T, N>;
+
+                fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+                    write!(f, "a sequence of at most {N} elements")
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                    let mut items = BoundedVec::new();
+                    while let Some(item) = seq.next_element()? {
+                        items
+                            .push(item)
+                            .map_err(|_| A::Error::invalid_length(N + 1, &self))?;
+                    }
+                    Ok(items)
+                }
+            }
+
+            deserializer.deserialize_seq(SeqVisitor(PhantomData))
+        }
+    }
+
+    impl<const N: usize> Serialize for BoundedString<N> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(self.as_str())
+        }
+    }
+
+    impl<'de, const N: usize> Deserialize<'de> for BoundedString<N> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct StrVisitor<const N: usize>;
+
+            impl<const N: usize> Visitor<'_> for StrVisitor<N> {
+                type Value = BoundedString<N>;
+
+                fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+                    write!(f, "a string of at most {N} bytes")
+                }
+
+                fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                    BoundedString::try_from(v).map_err(|_| E::invalid_length(v.len(), &self))
+                }
+            }
+
+            deserializer.deserialize_str(StrVisitor)
+        }
+    }
+}
@@ -275,6 +275,30 @@ impl<'i> BufReader<'i> {
         }
     }
 
+    /// Advance past a value that was serialized with [write](crate::BufWriter::write).
+    ///
+    /// Unsized values are skipped using their size, without looking at the data. Other values do not carry their size,
+    /// so they are deserialized and discarded.
+    pub fn skip<T: DeserializeShrinkWrap<'i>>(&mut self) -> Result<(), Error> {
+        self.split_value::<T>().map(|_| ())
+    }
+
+    /// Advance past a value that was serialized with [write](crate::BufWriter::write) and return a reader from which
+    /// it can be deserialized with [des_shrink_wrap](DeserializeShrinkWrap::des_shrink_wrap).
+    ///
+    /// For Unsized values, returned reader only covers value's data. For other values, returned reader starts
+    /// at the value's position.
+    pub fn split_value<T: DeserializeShrinkWrap<'i>>(&mut self) -> Result<Self, Error> {
+        if matches!(T::ELEMENT_SIZE, ElementSize::Unsized) {
+            let size = self.read_unib32_rev()? as usize;
+            self.split(size)
+        } else {
+            let rd = *self;
+            let _value: T = T::des_shrink_wrap(self)?;
+            Ok(rd)
+        }
+    }
+
     /// Align to byte and split off a BufReader which can read up to the len bytes.
     /// This is the main mechanism used for forwards and backwards compatibility, as it allows for older code
     /// to ignore newer data by simply skipping additional bytes it doesn't know about.
@@ -1,8 +1,10 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 //#![cfg_attr(all(not(feature = "std"), not(test)), no_std)] ?
 
+pub mod bounded;
 pub mod buf_reader;
 
+pub use bounded::{BoundedString, BoundedVec};
 pub use buf_reader::BufReader;
 use core::fmt::{Display, Formatter};
 pub mod buf_writer;
@@ -21,8 +23,13 @@ pub use traits::{
 
 #[cfg(feature = "std")]
 pub mod alloc;
+#[cfg(feature = "std")]
+pub use alloc::{grow_to_fit, to_ww_scratch, to_ww_vec};
+pub mod max_len;
 pub mod nib;
 pub mod raw_slice;
+#[cfg(feature = "serde")]
+pub mod serde;
 pub mod stack_vec;
 pub mod un;
 
@@ -54,6 +61,7 @@ pub enum Error {
     EnumFutureVersionOrMalformedData,
     InvalidBitCount,
     SubtypeOutOfRange,
+    CapacityExceeded,
 }
 
 impl Display for Error {
@@ -65,6 +73,23 @@ impl Display for Error {
 #[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
+impl Error {
+    /// Whether a value did not fit into the provided buffer, i.e. serializing into a bigger buffer could succeed.
+    pub fn is_write_eob(&self) -> bool {
+        use Error::*;
+        matches!(
+            self,
+            OutOfBoundsWriteBool
+                | OutOfBoundsWriteU4
+                | OutOfBoundsWriteU8
+                | OutOfBoundsWriteRawSlice
+                | OutOfBoundsWriteUN(_)
+                | OutOfBoundsRev
+                | OutOfBoundsRevCompact
+        )
+    }
+}
+
 // impl Error {
 //     pub fn is_read_eob(&self) -> bool {
 //         use Error::*;
@@ -81,8 +106,10 @@ impl std::error::Error for Error {}
 // }
 
 pub mod prelude {
+    pub use crate::bounded::{BoundedString, BoundedVec};
     pub use crate::buf_reader::BufReader;
     pub use crate::buf_writer::BufWriter;
+    pub use crate::max_len;
     pub use crate::nib::Nibble;
     pub use crate::nib32::UNib32;
     pub use crate::ref_box::RefBox;
@@ -0,0 +1,140 @@
+//! Const helpers used to compute [SerializeShrinkWrap::MAX_SERIALIZED_LEN] in manual and generated implementations.
+//!
+//! Sizes of nested values are tracked in bits and include worst-case alignment padding, as well as size slots
+//! for Unsized values. The resulting upper bound is conservative, actual serialized size is usually smaller.
+//! `None` means that the size is not bounded (e.g., `&str`, `Vec<T>`, `RefVec<T>`) or not known.
+
+use crate::{ElementSize, SerializeShrinkWrap};
+
+/// Maximum number of nibbles a reverse or forward UNib32 number occupies.
+pub const UNIB32_MAX_NIBBLES: usize = 11;
+
+/// Number of nibbles required to encode `value` as UNib32.
+pub const fn unib32_nibbles(value: usize) -> usize {
+    if value == 0 {
+        1
+    } else {
+        (usize::BITS - value.leading_zeros()).div_ceil(3) as usize
+    }
+}
+
+/// Worst-case size in bits of `T` written into a parent with [write](crate::BufWriter::write).
+/// Includes alignment padding before the value and a size slot for Unsized types.
+pub const fn nested_bits<T: SerializeShrinkWrap>() -> Option<usize> {
+    if let ElementSize::Sized { size_bits: 1 } = T::ELEMENT_SIZE {
+        // bool and u1 are never aligned and cannot contain padding
+        return Some(1);
+    }
+    let Some(max_len) = T::MAX_SERIALIZED_LEN else {
+        return None;
+    };
+    if max_len == 0 {
+        return Some(0);
+    }
+    let slot_bits = if T::ELEMENT_SIZE.is_unsized() {
+        unib32_nibbles(max_len) * 4
+    } else {
+        0
+    };
+    Some(7 + max_len * 8 + slot_bits)
+}
+
+/// Sum of two bounds, None if any of them is unbounded.
+pub const fn add(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        _ => None,
+    }
+}
+
+/// Maximum of two bounds, None if any of them is unbounded.
+pub const fn max(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if a > b { a } else { b }),
+        _ => None,
+    }
+}
+
+/// Bound multiplied by `n` (for arrays).
+pub const fn mul(a: Option<usize>, n: usize) -> Option<usize> {
+    match a {
+        Some(a) => Some(a * n),
+        None => None,
+    }
+}
+
+/// Convert a worst-case size in bits of all the fields of a root object into its serialized length in bytes.
+/// Accounts for nibble alignment before reverse UNib32 sizes are encoded and byte alignment at the end.
+pub const fn root_len(bits: Option<usize>) -> Option<usize> {
+    match bits {
+        Some(bits) => Some((bits + 3).div_ceil(8)),
+        None => None,
+    }
+}
+
+/// Returns true if a buffer of `buf_len` bytes can hold a value of `max_len` bytes.
+/// Unbounded values never fit. Intended to be used in const asserts:
+/// ```
+/// use shrink_wrap::max_len::fits;
+/// const _: () = assert!(fits(<[u16; 4] as shrink_wrap::SerializeShrinkWrap>::MAX_SERIALIZED_LEN, 16));
+/// ```
+pub const fn fits(max_len: Option<usize>, buf_len: usize) -> bool {
+    match max_len {
+        Some(max_len) => max_len <= buf_len,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BufWriter, RefBox, UNib32};
+
+    fn check<T: SerializeShrinkWrap>(value: T) {
+        let mut buf = [0u8; 256];
+        let bytes = value.to_ww_bytes(&mut buf).unwrap();
+        assert!(bytes.len() <= T::MAX_SERIALIZED_LEN.unwrap());
+
+        // nested, after an unaligned bool
+        let mut buf = [0u8; 256];
+        let mut wr = BufWriter::new(&mut buf);
+        wr.write_bool(true).unwrap();
+        wr.write(&value).unwrap();
+        let bytes = wr.finish_and_take().unwrap();
+        assert!(bytes.len() * 8 <= nested_bits::<T>().unwrap() + 1 + 3 + 7);
+    }
+
+    #[test]
+    fn unib32_nibble_count() {
+        assert_eq!(unib32_nibbles(0), 1);
+        assert_eq!(unib32_nibbles(7), 1);
+        assert_eq!(unib32_nibbles(8), 2);
+        assert_eq!(unib32_nibbles(u32::MAX as usize), UNIB32_MAX_NIBBLES);
+        assert_eq!(UNib32(u32::MAX).len_nibbles(), UNIB32_MAX_NIBBLES);
+    }
+
+    #[test]
+    fn primitives_are_within_bounds() {
+        check(true);
+        check(0xAAu8);
+        check(u64::MAX);
+        check(UNib32(u32::MAX));
+        check(Some(u32::MAX));
+        check(Result::<u8, u16>::Err(0xFFFF));
+        check([UNib32(u32::MAX); 4]);
+        check((u8::MAX, Some(UNib32(u32::MAX)), [true; 3]));
+        check(RefBox::new(&u16::MAX));
+        check(0..u16::MAX);
+    }
+
+    #[test]
+    fn unbounded() {
+        assert_eq!(<&str as SerializeShrinkWrap>::MAX_SERIALIZED_LEN, None);
+        assert_eq!(
+            <Option<&str> as SerializeShrinkWrap>::MAX_SERIALIZED_LEN,
+            None
+        );
+        assert_eq!(<[&str; 2] as SerializeShrinkWrap>::MAX_SERIALIZED_LEN, None);
+        assert!(!fits(None, usize::MAX));
+    }
+}
@@ -104,6 +104,7 @@ impl UNib32 {
 
 impl SerializeShrinkWrap for UNib32 {
     const ELEMENT_SIZE: ElementSize = ElementSize::SelfDescribing;
+    const MAX_SERIALIZED_LEN: Option<usize> = Some(crate::max_len::UNIB32_MAX_NIBBLES.div_ceil(2));
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         self.write_forward(wr)
@@ -42,6 +42,7 @@ where
     T: SerializeShrinkWrap + DeserializeShrinkWrap<'i>,
 {
     const ELEMENT_SIZE: ElementSize = ElementSize::Unsized;
+    const MAX_SERIALIZED_LEN: Option<usize> = T::MAX_SERIALIZED_LEN;
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         match self {
@@ -0,0 +1,176 @@
+//! serde data format backed by [BufWriter] and [BufReader].
+//!
+//! Allows types that already derive `serde::Serialize` and `serde::Deserialize` to be serialized into shrink_wrap
+//! without rewriting them with `#[derive_shrink_wrap]`. Serde data model is mapped onto the same encoding that
+//! derive macros produce for equivalent types:
+//!
+//! | serde data model                          | shrink_wrap encoding                                                  |
+//! |-------------------------------------------|-----------------------------------------------------------------------|
+//! | `bool`                                    | 1 bit                                                                 |
+//! | `u8`..`u128`, `i8`..`i128`, `f32`, `f64`  | Little Endian, one byte alignment                                     |
+//! | `char`                                    | `UNib32`                                                              |
+//! | `str`, `String`                           | Unsized (`&str`)                                                      |
+//! | `bytes`                                   | `Vec<u8>`                                                             |
+//! | `Option<T>`                               | is_some flag followed by `T`                                          |
+//! | `()`, unit struct                         | nothing / empty Unsized struct                                        |
+//! | newtype struct                            | transparent                                                           |
+//! | sequence                                  | `Vec<T>` (UnsizedFinalStructure, up to `u16::MAX` elements)           |
+//! | tuple, `[T; N]`                           | tuple (UnsizedFinalStructure)                                         |
+//! | tuple struct, struct                      | Unsized struct                                                        |
+//! | map                                       | `Vec<(K, V)>`                                                         |
+//! | enum                                      | Unsized enum with `#[ww_repr(unib32)]` discriminant = variant index   |
+//!
+//! Limitations compared to `#[derive_shrink_wrap]`:
+//! * Format is not self-describing, `deserialize_any`, `deserialize_ignored_any` and `deserialize_identifier`
+//!   are not supported. So `#[serde(flatten)]`, untagged and internally tagged enums, and `skip_serializing_if`
+//!   cannot be used.
+//! * `#[sized]`, `#[final_structure]` and `#[self_describing]` cannot be expressed, all structs and enums are Unsized.
+//! * `Option` and `Result` flags are always placed right before the value, there is no `#[flag]` relocation.
+//! * `UNib32`, `Nibble` and `U1`..`U64` are `#[serde(transparent)]`, so they are encoded as their backing integers.
+//!
+//! Evolution guarantees are limited to what can be expressed with serde attributes:
+//! * New fields can only be added at the end of a struct or struct variant and must be `#[serde(default)]`.
+//!   Older data is read with all the missing trailing fields set to their default values.
+//!   Newer data is read by older code, ignoring additional fields, when a struct is nested in another object
+//!   (root object size is not stored and not checked).
+//! * New enum variants can be added at the end, older code will fail to deserialize them
+//!   with [EnumFutureVersionOrMalformedData](crate::Error::EnumFutureVersionOrMalformedData).
+//! * Fields and variants cannot be reordered, renamed fields are fine as names are not serialized.
+//!
+//! # Example
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Coord {
+//!     x: u8,
+//!     y: u8,
+//!     label: Option<String>,
+//! }
+//!
+//! let coord = Coord { x: 0xAA, y: 0xCC, label: None };
+//! let mut buf = [0u8; 64];
+//! let bytes = shrink_wrap::serde::to_ww_bytes(&coord, &mut buf).unwrap();
+//! assert_eq!(bytes, &[0xAA, 0xCC, 0x00]);
+//! let des: Coord = shrink_wrap::serde::from_ww_bytes(bytes).unwrap();
+//! assert_eq!(des, coord);
+//! ```
+
+mod de;
+mod ser;
+
+pub use de::Deserializer;
+pub use ser::Serializer;
+
+use crate::{BufReader, BufWriter};
+use core::fmt::{Display, Formatter};
+
+/// Serialize a value into the provided buffer, same as [to_ww_bytes](crate::SerializeShrinkWrap::to_ww_bytes)
+/// does for shrink_wrap types.
+pub fn to_ww_bytes<'i, T: ::serde::Serialize + ?Sized>(
+    value: &T,
+    buf: &'i mut [u8],
+) -> Result<&'i [u8], Error> {
+    let mut wr = BufWriter::new(buf);
+    value.serialize(&mut Serializer::new(&mut wr))?;
+    Ok(wr.finish_and_take()?)
+}
+
+/// Serialize a value into a Vec, growing the scratch buffer until the value fits, up to `max_len` bytes.
+#[cfg(feature = "std")]
+pub fn to_ww_vec<T: ::serde::Serialize + ?Sized>(
+    value: &T,
+    max_len: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut scratch = Vec::new();
+    let bytes = crate::grow_to_fit(
+        &mut scratch,
+        max_len,
+        |buf| to_ww_bytes(value, buf).map(|bytes| bytes.len()),
+        |e| matches!(e, Error::ShrinkWrap(e) if e.is_write_eob()),
+    )?;
+    Ok(bytes.to_vec())
+}
+
+/// Deserialize a value from the provided buffer, same as [from_ww_bytes](crate::DeserializeShrinkWrap::from_ww_bytes)
+/// does for shrink_wrap types.
+pub fn from_ww_bytes<'de, T: ::serde::Deserialize<'de>>(buf: &'de [u8]) -> Result<T, Error> {
+    T::deserialize(&mut Deserializer::new(BufReader::new(buf)))
+}
+
+/// Serialize a value as a part of a bigger object, same as [write](BufWriter::write) does for shrink_wrap types.
+///
+/// Values serialized with this function must be deserialized with [read].
+pub fn write<T: ::serde::Serialize + ?Sized>(wr: &mut BufWriter, value: &T) -> Result<(), Error> {
+    value.serialize(&mut Serializer::nested(wr))
+}
+
+/// Deserialize a value from a part of a bigger object, same as [read](BufReader::read) does for shrink_wrap types.
+pub fn read<'de, T: ::serde::Deserialize<'de>>(rd: &mut BufReader<'de>) -> Result<T, Error> {
+    let mut de = Deserializer::nested(*rd);
+    let value = T::deserialize(&mut de)?;
+    *rd = de.into_inner();
+    Ok(value)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Error from BufWriter or BufReader.
+    ShrinkWrap(crate::Error),
+    /// deserialize_any, deserialize_ignored_any or deserialize_identifier was called, but the format is not self-describing.
+    NotSelfDescribing,
+    /// Deserialized UNib32 is not a valid char.
+    InvalidChar,
+    /// Custom error message from Serialize or Deserialize implementation.
+    #[cfg(feature = "std")]
+    Custom(String),
+    /// Custom error from Serialize or Deserialize implementation, message is discarded on no_std.
+    #[cfg(not(feature = "std"))]
+    Custom,
+}
+
+impl From<crate::Error> for Error {
+    fn from(e: crate::Error) -> Self {
+        Error::ShrinkWrap(e)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::ShrinkWrap(e) => write!(f, "{e:?}"),
+            Error::NotSelfDescribing => f.write_str("shrink_wrap is not a self-describing format"),
+            Error::InvalidChar => f.write_str("invalid char"),
+            #[cfg(feature = "std")]
+            Error::Custom(msg) => f.write_str(msg),
+            #[cfg(not(feature = "std"))]
+            Error::Custom => f.write_str("custom error"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl ::serde::ser::Error for Error {
+    #[cfg(feature = "std")]
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn custom<T: Display>(_msg: T) -> Self {
+        Error::Custom
+    }
+}
+
+impl ::serde::de::Error for Error {
+    #[cfg(feature = "std")]
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn custom<T: Display>(_msg: T) -> Self {
+        Error::Custom
+    }
+}
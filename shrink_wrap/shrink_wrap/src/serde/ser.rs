@@ -0,0 +1,441 @@
+use crate::BufWriter;
+use crate::buf_writer::U16RevPos;
+use crate::serde::Error;
+use ::serde::ser::{
+    self, Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+};
+use core::fmt::{Display, Write};
+
+/// serde Serializer writing into a [BufWriter].
+///
+/// Root object is written without a size, same as [to_ww_bytes](crate::SerializeShrinkWrap::to_ww_bytes) does,
+/// all the nested Unsized objects are written with a size, same as [write](BufWriter::write) does.
+pub struct Serializer<'w, 'i> {
+    wr: &'w mut BufWriter<'i>,
+    nested: bool,
+}
+
+impl<'w, 'i> Serializer<'w, 'i> {
+    /// Create a Serializer for a root object.
+    pub fn new(wr: &'w mut BufWriter<'i>) -> Self {
+        Serializer { wr, nested: false }
+    }
+
+    /// Create a Serializer for an object that is part of a bigger one.
+    pub fn nested(wr: &'w mut BufWriter<'i>) -> Self {
+        Serializer { wr, nested: true }
+    }
+
+    /// Reserve a size slot if the Unsized object being written is nested, otherwise do nothing.
+    /// All the child objects are nested.
+    fn begin_unsized(&mut self) -> Result<Option<(U16RevPos, usize)>, Error> {
+        let unsized_info = if self.nested {
+            self.wr.align_byte();
+            let size_slot_pos = self.wr.write_u16_rev(0)?;
+            Some((size_slot_pos, self.wr.pos().0))
+        } else {
+            None
+        };
+        self.nested = true;
+        Ok(unsized_info)
+    }
+
+    /// Same as the second half of [write](BufWriter::write).
+    fn end_unsized(&mut self, unsized_info: Option<(U16RevPos, usize)>) -> Result<(), Error> {
+        if let Some((size_slot_pos, unsized_start_idx)) = unsized_info {
+            self.wr
+                .encode_nib16_rev(self.wr.u16_rev_pos(), size_slot_pos)?;
+            self.wr.align_byte();
+            let size_bytes = self.wr.pos().0 - unsized_start_idx;
+            let Ok(size_bytes) = u16::try_from(size_bytes) else {
+                return Err(crate::Error::ItemTooLong.into());
+            };
+            self.wr.update_u16_rev(size_slot_pos, size_bytes)?;
+        }
+        Ok(())
+    }
+
+    fn write_len(&mut self, len: usize) -> Result<U16RevPos, Error> {
+        let Ok(len) = u16::try_from(len) else {
+            return Err(crate::Error::VecTooLong.into());
+        };
+        Ok(self.wr.write_u16_rev(len)?)
+    }
+}
+
+/// State of a compound object being serialized.
+pub struct Compound<'a, 'w, 'i> {
+    ser: &'a mut Serializer<'w, 'i>,
+    unsized_info: Option<(U16RevPos, usize)>,
+    /// Position of the element count and actual count, if it wasn't known in advance.
+    len: Option<(U16RevPos, usize)>,
+}
+
+impl Compound<'_, '_, '_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        if let Some((_, count)) = &mut self.len {
+            *count += 1;
+        }
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        if let Some((pos, count)) = self.len {
+            let Ok(count) = u16::try_from(count) else {
+                return Err(crate::Error::VecTooLong.into());
+            };
+            self.ser.wr.update_u16_rev(pos, count)?;
+        }
+        self.ser.end_unsized(self.unsized_info)
+    }
+}
+
+impl<'a, 'w, 'i> ser::Serializer for &'a mut Serializer<'w, 'i> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a, 'w, 'i>;
+    type SerializeTuple = Compound<'a, 'w, 'i>;
+    type SerializeTupleStruct = Compound<'a, 'w, 'i>;
+    type SerializeTupleVariant = Compound<'a, 'w, 'i>;
+    type SerializeMap = Compound<'a, 'w, 'i>;
+    type SerializeStruct = Compound<'a, 'w, 'i>;
+    type SerializeStructVariant = Compound<'a, 'w, 'i>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        Ok(self.wr.write_bool(v)?)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        Ok(self.wr.write_i8(v)?)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        Ok(self.wr.write_i16(v)?)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        Ok(self.wr.write_i32(v)?)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        Ok(self.wr.write_i64(v)?)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> {
+        Ok(self.wr.write_i128(v)?)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        Ok(self.wr.write_u8(v)?)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        Ok(self.wr.write_u16(v)?)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        Ok(self.wr.write_u32(v)?)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        Ok(self.wr.write_u64(v)?)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> {
+        Ok(self.wr.write_u128(v)?)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        Ok(self.wr.write_f32(v)?)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        Ok(self.wr.write_f64(v)?)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        Ok(self.wr.write_unib32(v as u32)?)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        let unsized_info = self.begin_unsized()?;
+        self.wr.write_raw_str(v)?;
+        self.end_unsized(unsized_info)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.write_len(v.len())?;
+        Ok(self.wr.write_raw_slice(v)?)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(self.wr.write_bool(false)?)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        self.wr.write_bool(true)?;
+        self.nested = true;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        let unsized_info = self.begin_unsized()?;
+        self.end_unsized(unsized_info)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        let unsized_info = self.begin_unsized()?;
+        self.wr.write_unib32(variant_index)?;
+        self.end_unsized(unsized_info)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let unsized_info = self.begin_unsized()?;
+        self.wr.write_unib32(variant_index)?;
+        value.serialize(&mut *self)?;
+        self.end_unsized(unsized_info)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Compound<'a, 'w, 'i>, Error> {
+        let len = match len {
+            Some(len) => {
+                self.write_len(len)?;
+                None
+            }
+            None => Some((self.wr.write_u16_rev(0)?, 0)),
+        };
+        self.nested = true;
+        Ok(Compound {
+            ser: self,
+            unsized_info: None,
+            len,
+        })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a, 'w, 'i>, Error> {
+        self.nested = true;
+        Ok(Compound {
+            ser: self,
+            unsized_info: None,
+            len: None,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, 'w, 'i>, Error> {
+        let unsized_info = self.begin_unsized()?;
+        Ok(Compound {
+            ser: self,
+            unsized_info,
+            len: None,
+        })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, 'w, 'i>, Error> {
+        let unsized_info = self.begin_unsized()?;
+        self.wr.write_unib32(variant_index)?;
+        Ok(Compound {
+            ser: self,
+            unsized_info,
+            len: None,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'a, 'w, 'i>, Error> {
+        self.serialize_seq(len)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, 'w, 'i>, Error> {
+        let unsized_info = self.begin_unsized()?;
+        Ok(Compound {
+            ser: self,
+            unsized_info,
+            len: None,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, 'w, 'i>, Error> {
+        self.serialize_tuple_variant(name, variant_index, variant, len)
+    }
+
+    fn collect_str<T: Display + ?Sized>(self, value: &T) -> Result<(), Error> {
+        let unsized_info = self.begin_unsized()?;
+        let mut adapter = StrWriter {
+            wr: &mut *self.wr,
+            error: None,
+        };
+        if write!(adapter, "{value}").is_err() {
+            return Err(adapter
+                .error
+                .unwrap_or(crate::Error::OutOfBoundsWriteRawSlice)
+                .into());
+        }
+        self.end_unsized(unsized_info)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Writes formatted string directly into the buffer, without allocating.
+struct StrWriter<'a, 'i> {
+    wr: &'a mut BufWriter<'i>,
+    error: Option<crate::Error>,
+}
+
+impl Write for StrWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.wr.write_raw_str(s).map_err(|e| {
+            self.error = Some(e);
+            core::fmt::Error
+        })
+    }
+}
+
+impl SerializeSeq for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeTuple for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeTupleStruct for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeTupleVariant for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeMap for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        // (K, V) tuple is UnsizedFinalStructure, key and value are written one after another
+        self.element(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeStruct for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeStructVariant for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
@@ -1,9 +1,15 @@
+use crate::max_len;
 use crate::{BufReader, BufWriter, Error};
 use paste::paste;
 
 pub trait SerializeShrinkWrap {
     const ELEMENT_SIZE: ElementSize;
 
+    /// Upper bound of the serialized size in bytes, when serialized as a root object with [to_ww_bytes](Self::to_ww_bytes).
+    /// None if the size is not bounded (e.g., `&str`, `Vec<T>`, `RefVec<T>`) or is not known.
+    /// Can be used to statically size buffers, see [max_len](crate::max_len).
+    const MAX_SERIALIZED_LEN: Option<usize> = None;
+
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error>;
 
     fn to_ww_bytes<'i>(&self, buf: &'i mut [u8]) -> Result<&'i [u8], Error> {
@@ -155,6 +161,7 @@ impl ElementSize {
 
 impl SerializeShrinkWrap for ElementSize {
     const ELEMENT_SIZE: ElementSize = ElementSize::Sized { size_bits: 2 };
+    const MAX_SERIALIZED_LEN: Option<usize> = Some(1);
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         wr.write_un8(2, self.discriminant())
@@ -188,6 +195,7 @@ impl DeserializeShrinkWrapOwned for ElementSize {
 
 impl SerializeShrinkWrap for bool {
     const ELEMENT_SIZE: ElementSize = ElementSize::Sized { size_bits: 1 };
+    const MAX_SERIALIZED_LEN: Option<usize> = Some(1);
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         wr.write_bool(*self)
@@ -215,6 +223,7 @@ macro_rules! impl_serialize {
         paste! {
             impl SerializeShrinkWrap for [<$sign $bits>] {
                 const ELEMENT_SIZE: ElementSize = ElementSize::Sized { size_bits: $bits };
+                const MAX_SERIALIZED_LEN: Option<usize> = Some($bits / 8);
 
                 fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
                     wr.[<write_ $sign $bits>](*self)
@@ -288,6 +297,8 @@ impl<'i> DeserializeShrinkWrap<'i> for &'i str {
 
 impl<T: SerializeShrinkWrap> SerializeShrinkWrap for Option<T> {
     const ELEMENT_SIZE: ElementSize = ElementSize::SelfDescribing;
+    const MAX_SERIALIZED_LEN: Option<usize> =
+        max_len::root_len(max_len::add(Some(1), max_len::nested_bits::<T>()));
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         match self {
@@ -328,6 +339,10 @@ impl<T: DeserializeShrinkWrapOwned> DeserializeShrinkWrapOwned for Option<T> {
 
 impl<T: SerializeShrinkWrap, E: SerializeShrinkWrap> SerializeShrinkWrap for Result<T, E> {
     const ELEMENT_SIZE: ElementSize = ElementSize::SelfDescribing;
+    const MAX_SERIALIZED_LEN: Option<usize> = max_len::root_len(max_len::add(
+        Some(1),
+        max_len::max(max_len::nested_bits::<T>(), max_len::nested_bits::<E>()),
+    ));
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         match self {
@@ -379,6 +394,11 @@ macro_rules! impl_tuple {
             impl<$($types: SerializeShrinkWrap),*> SerializeShrinkWrap for ($($types),*) {
                 // const ELEMENT_SIZE: ElementSize = add_recursive!($($types),*);
                 const ELEMENT_SIZE: ElementSize = ElementSize::UnsizedFinalStructure;
+                const MAX_SERIALIZED_LEN: Option<usize> = {
+                    let bits = Some(0);
+                    $(let bits = max_len::add(bits, max_len::nested_bits::<$types>());)*
+                    max_len::root_len(bits)
+                };
 
                 fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
                     $(wr.write(&self.$indices)?;)*
@@ -427,6 +447,8 @@ impl_tuple!(A, B, C, D, E, F, G, H; 0, 1, 2, 3, 4, 5, 6, 7);
 
 impl<const N: usize, T: SerializeShrinkWrap> SerializeShrinkWrap for [T; N] {
     const ELEMENT_SIZE: ElementSize = ElementSize::UnsizedFinalStructure;
+    const MAX_SERIALIZED_LEN: Option<usize> =
+        max_len::root_len(max_len::mul(max_len::nested_bits::<T>(), N));
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         for elem in self {
@@ -477,6 +499,7 @@ impl<const N: usize, T: DeserializeShrinkWrapOwned + Default + Copy> Deserialize
 
 impl SerializeShrinkWrap for () {
     const ELEMENT_SIZE: ElementSize = ElementSize::Sized { size_bits: 0 };
+    const MAX_SERIALIZED_LEN: Option<usize> = Some(0);
 
     fn ser_shrink_wrap(&self, _wr: &mut BufWriter) -> Result<(), Error> {
         Ok(())
@@ -501,6 +524,8 @@ impl DeserializeShrinkWrapOwned for () {
 
 impl<T: SerializeShrinkWrap> SerializeShrinkWrap for core::ops::Range<T> {
     const ELEMENT_SIZE: ElementSize = T::ELEMENT_SIZE;
+    const MAX_SERIALIZED_LEN: Option<usize> =
+        max_len::root_len(max_len::mul(max_len::nested_bits::<T>(), 2));
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         wr.write(&self.start)?;
@@ -531,6 +556,8 @@ impl<T: DeserializeShrinkWrapOwned> DeserializeShrinkWrapOwned for core::ops::Ra
 
 impl<T: SerializeShrinkWrap> SerializeShrinkWrap for core::ops::RangeInclusive<T> {
     const ELEMENT_SIZE: ElementSize = T::ELEMENT_SIZE;
+    const MAX_SERIALIZED_LEN: Option<usize> =
+        max_len::root_len(max_len::mul(max_len::nested_bits::<T>(), 2));
 
     fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
         wr.write(self.start())?;
@@ -47,6 +47,7 @@ macro_rules! un {
 
             impl SerializeShrinkWrap for [<U $bits>] {
                 const ELEMENT_SIZE: ElementSize = ElementSize::Sized { size_bits: $bits };
+                const MAX_SERIALIZED_LEN: Option<usize> = Some(($bits as usize).div_ceil(8));
 
                 fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
                     wr.[<write_un $base_bits>]($bits, self.0)
@@ -221,6 +222,7 @@ macro_rules! signed_un {
 
             impl SerializeShrinkWrap for [<I $bits>] {
                 const ELEMENT_SIZE: ElementSize = ElementSize::Sized { size_bits: $bits };
+                const MAX_SERIALIZED_LEN: Option<usize> = Some(($bits as usize).div_ceil(8));
 
                 fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), Error> {
                     wr.[<write_un $base_bits>]($bits, self.0 as [<u $base_bits>])
@@ -314,7 +316,8 @@ impl<'i> DeserializeShrinkWrap<'i> for UN {
     const ELEMENT_SIZE: ElementSize = <UN as SerializeShrinkWrap>::ELEMENT_SIZE;
 
     fn des_shrink_wrap<'di>(_rd: &'di mut BufReader<'i>) -> Result<Self, Error> {
-        todo!()
+        // not supported yet, do not panic on data from newer versions or malformed data
+        Err(Error::EnumFutureVersionOrMalformedData)
     }
 }
 
@@ -338,7 +341,8 @@ impl<'i> DeserializeShrinkWrap<'i> for IN {
     const ELEMENT_SIZE: ElementSize = <UN as SerializeShrinkWrap>::ELEMENT_SIZE;
 
     fn des_shrink_wrap<'di>(_rd: &'di mut BufReader<'i>) -> Result<Self, Error> {
-        todo!()
+        // not supported yet, do not panic on data from newer versions or malformed data
+        Err(Error::EnumFutureVersionOrMalformedData)
     }
 }
 
@@ -62,3 +62,21 @@ fn array() {
 fn unit() {
     let _unit: () = DeserializeShrinkWrap::from_ww_bytes(&[]).unwrap();
 }
+
+#[test]
+fn to_ww_vec_grows_scratch() {
+    let value = vec![0xAA_u8; 1000];
+    let bytes = shrink_wrap::to_ww_vec(&value, 4096).unwrap();
+    let x: Vec<u8> = DeserializeShrinkWrap::from_ww_bytes(&bytes).unwrap();
+    assert_eq!(x, value);
+
+    let mut scratch = vec![0u8; 16];
+    assert_eq!(
+        shrink_wrap::to_ww_scratch(&value, &mut scratch, 4096).unwrap(),
+        bytes
+    );
+    assert!(scratch.len() >= bytes.len());
+
+    let e = shrink_wrap::to_ww_vec(&value, 512).unwrap_err();
+    assert!(e.is_write_eob());
+}
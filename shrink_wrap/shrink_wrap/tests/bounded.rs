@@ -0,0 +1,149 @@
+use shrink_wrap::prelude::*;
+
+#[derive_shrink_wrap]
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    name: BoundedString<8>,
+    samples: BoundedVec<u16, 4>,
+    labels: BoundedVec<BoundedString<4>, 2>,
+}
+
+#[derive_shrink_wrap]
+#[derive(Debug, PartialEq)]
+struct StateRef<'i> {
+    name: &'i str,
+    samples: RefVec<'i, u16>,
+    labels: RefVec<'i, &'i str>,
+}
+
+fn state() -> State {
+    State {
+        name: BoundedString::try_from("sensor").unwrap(),
+        samples: BoundedVec::from_slice(&[1, 2, 3]).unwrap(),
+        labels: BoundedVec::from_slice(&[
+            BoundedString::try_from("a").unwrap(),
+            BoundedString::try_from("bcd").unwrap(),
+        ])
+        .unwrap(),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let state = state();
+    let mut buf = [0u8; 64];
+    let bytes = state.to_ww_bytes(&mut buf).unwrap();
+    assert_eq!(State::from_ww_bytes(bytes).unwrap(), state);
+    assert_eq!(State::from_ww_bytes_owned(bytes).unwrap(), state);
+}
+
+#[test]
+fn same_as_unbounded() {
+    let state = state();
+    let mut buf = [0u8; 64];
+    let bytes = state.to_ww_bytes(&mut buf).unwrap();
+    let state_ref = StateRef::from_ww_bytes(bytes).unwrap();
+    assert_eq!(
+        state_ref,
+        StateRef {
+            name: "sensor",
+            samples: RefVec::Slice { slice: &[1, 2, 3] },
+            labels: RefVec::Slice {
+                slice: &["a", "bcd"]
+            },
+        }
+    );
+
+    let mut buf2 = [0u8; 64];
+    let bytes2 = state_ref.to_ww_bytes(&mut buf2).unwrap();
+    assert_eq!(bytes, bytes2);
+}
+
+#[test]
+fn capacity_exceeded() {
+    let mut buf = [0u8; 64];
+    let bytes = StateRef {
+        name: "too long for eight",
+        samples: RefVec::Slice { slice: &[] },
+        labels: RefVec::Slice { slice: &[] },
+    }
+    .to_ww_bytes(&mut buf)
+    .unwrap();
+    assert_eq!(
+        State::from_ww_bytes(bytes),
+        Err(ShrinkWrapError::CapacityExceeded)
+    );
+
+    let mut buf = [0u8; 64];
+    let bytes = StateRef {
+        name: "",
+        samples: RefVec::Slice {
+            slice: &[1, 2, 3, 4, 5],
+        },
+        labels: RefVec::Slice { slice: &[] },
+    }
+    .to_ww_bytes(&mut buf)
+    .unwrap();
+    assert_eq!(
+        State::from_ww_bytes(bytes),
+        Err(ShrinkWrapError::CapacityExceeded)
+    );
+
+    let mut buf = [0u8; 64];
+    let bytes = StateRef {
+        name: "",
+        samples: RefVec::Slice { slice: &[] },
+        labels: RefVec::Slice {
+            slice: &["a", "b", "c"],
+        },
+    }
+    .to_ww_bytes(&mut buf)
+    .unwrap();
+    assert_eq!(
+        State::from_ww_bytes_owned(bytes),
+        Err(ShrinkWrapError::CapacityExceeded)
+    );
+}
+
+#[test]
+fn push_and_pop() {
+    let mut samples = BoundedVec::<u16, 2>::new();
+    assert!(samples.is_empty());
+    samples.push(1).unwrap();
+    samples.push(2).unwrap();
+    assert!(samples.is_full());
+    assert_eq!(samples.push(3), Err(3));
+    assert_eq!(samples.pop(), Some(2));
+    assert_eq!(&*samples, &[1]);
+    assert!(BoundedVec::<u16, 2>::from_slice(&[1, 2, 3]).is_err());
+
+    let mut name = BoundedString::<4>::new();
+    name.push('a').unwrap();
+    name.push_str("bc").unwrap();
+    assert_eq!(name.push('ж'), Err(ShrinkWrapError::CapacityExceeded));
+    assert_eq!(name.as_str(), "abc");
+}
+
+#[test]
+fn clear_then_push() {
+    use std::hash::BuildHasher;
+
+    let mut name = BoundedString::<8>::try_from("abcdef").unwrap();
+    name.clear();
+    name.push_str("ab").unwrap();
+    let fresh = BoundedString::<8>::try_from("ab").unwrap();
+    assert_eq!(name, fresh);
+    let hasher = std::collections::hash_map::RandomState::new();
+    assert_eq!(hasher.hash_one(name), hasher.hash_one(fresh));
+}
+
+#[test]
+fn max_len() {
+    const LEN: usize = <State as SerializeShrinkWrap>::MAX_SERIALIZED_LEN.unwrap();
+    let mut state = state();
+    state.name = BoundedString::try_from("12345678").unwrap();
+    state.samples = BoundedVec::from_slice(&[u16::MAX; 4]).unwrap();
+    state.labels = BoundedVec::from_slice(&[BoundedString::try_from("abcd").unwrap(); 2]).unwrap();
+    let mut buf = [0u8; LEN];
+    assert!(state.to_ww_bytes(&mut buf).is_ok());
+}
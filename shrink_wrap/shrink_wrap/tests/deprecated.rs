@@ -0,0 +1,73 @@
+// Generated serdes code must not trigger deprecation warnings on deprecated fields and variants.
+#![deny(deprecated)]
+// Checks below that deprecated fields are still marked as such.
+#![deny(unfulfilled_lint_expectations)]
+
+use shrink_wrap::prelude::*;
+
+#[derive_shrink_wrap]
+#[view]
+#[derive(Debug, PartialEq, Clone)]
+struct Settings<'i> {
+    #[deprecated(since = "0.2.0", note = "use `label` instead")]
+    name: &'i str,
+    label: &'i str,
+    #[deprecated]
+    gain: Option<u16>,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(u4)]
+#[derive(Debug, PartialEq, Clone)]
+enum Mode {
+    Off,
+    #[deprecated = "use Mode::Fast"]
+    Slow,
+    Fast {
+        #[deprecated(note = "ignored by devices")]
+        boost: bool,
+    },
+}
+
+#[derive_shrink_wrap]
+#[derive(Debug, PartialEq, Clone)]
+struct Channel {
+    #[flag]
+    #[deprecated = "offset is calibrated on the device"]
+    offset: bool,
+    gain: u8,
+    offset: Option<i8>,
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_round_trip() {
+    let settings = Settings {
+        name: "old",
+        label: "new",
+        gain: Some(5),
+    };
+    let mut buf = [0u8; 64];
+    let bytes = settings.to_ww_bytes(&mut buf).unwrap();
+    assert_eq!(Settings::from_ww_bytes(bytes).unwrap(), settings);
+    assert_eq!(SettingsView::from_ww_bytes(bytes).name().unwrap(), "old");
+
+    for mode in [Mode::Off, Mode::Slow, Mode::Fast { boost: true }] {
+        let mut buf = [0u8; 64];
+        let bytes = mode.to_ww_bytes(&mut buf).unwrap();
+        assert_eq!(Mode::from_ww_bytes(bytes).unwrap(), mode);
+    }
+}
+
+// Flag is not part of the generated struct, so the attribute must end up on the Option it belongs to.
+#[test]
+#[expect(deprecated)]
+fn deprecated_flag() {
+    let channel = Channel {
+        gain: 2,
+        offset: Some(-1),
+    };
+    let mut buf = [0u8; 64];
+    let bytes = channel.to_ww_bytes(&mut buf).unwrap();
+    assert_eq!(Channel::from_ww_bytes(bytes).unwrap(), channel);
+}
@@ -0,0 +1,177 @@
+use shrink_wrap::prelude::*;
+
+trait Sample: Copy + PartialEq + core::fmt::Debug {}
+impl Sample for f32 {}
+impl Sample for i16 {}
+
+#[derive_shrink_wrap]
+#[sized]
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Measurement<T: Sample> {
+    value: T,
+    timestamp: u32,
+}
+
+#[derive_shrink_wrap]
+#[sized]
+struct MeasurementF32 {
+    value: f32,
+    timestamp: u32,
+}
+
+#[derive_shrink_wrap]
+#[sized]
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Extremes<T: Sample> {
+    min: Measurement<T>,
+    max: Measurement<T>,
+}
+
+#[derive_shrink_wrap]
+#[sized]
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Readings {
+    temperature: Measurement<f32>,
+    pressure: Measurement<i16>,
+}
+
+#[derive_shrink_wrap]
+#[derive(Debug, PartialEq, Clone)]
+#[owned = "std"]
+struct Response<'i, T>
+where
+    T: Clone,
+{
+    request_id: u8,
+    message: &'i str,
+    payload: Option<T>,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(u4)]
+#[derive(Debug, PartialEq, Clone)]
+enum Reply<T, E = u8> {
+    Ack,
+    Value(T),
+    Failed { code: E },
+}
+
+#[test]
+fn element_size_of_instances() {
+    assert_eq!(
+        <Measurement<f32> as SerializeShrinkWrap>::ELEMENT_SIZE,
+        ElementSize::Sized { size_bits: 64 }
+    );
+    assert_eq!(
+        <Measurement<i16> as SerializeShrinkWrap>::ELEMENT_SIZE,
+        ElementSize::Sized { size_bits: 48 }
+    );
+    assert_eq!(
+        <Extremes<i16> as SerializeShrinkWrap>::ELEMENT_SIZE,
+        ElementSize::Sized { size_bits: 96 }
+    );
+    assert_eq!(
+        <Readings as DeserializeShrinkWrap>::ELEMENT_SIZE,
+        ElementSize::Sized { size_bits: 112 }
+    );
+    assert_eq!(
+        <Response<u8> as SerializeShrinkWrap>::ELEMENT_SIZE,
+        ElementSize::Unsized
+    );
+    assert_eq!(
+        Measurement::<f32>::MAX_SERIALIZED_LEN,
+        MeasurementF32::MAX_SERIALIZED_LEN
+    );
+    assert!(Measurement::<i16>::MAX_SERIALIZED_LEN < Measurement::<f32>::MAX_SERIALIZED_LEN);
+    assert_eq!(Response::<u8>::MAX_SERIALIZED_LEN, None);
+}
+
+#[test]
+fn generic_struct_round_trip() {
+    let extremes = Extremes {
+        min: Measurement {
+            value: -3i16,
+            timestamp: 10,
+        },
+        max: Measurement {
+            value: 7,
+            timestamp: 20,
+        },
+    };
+    let mut buf = [0u8; 64];
+    let bytes = extremes.to_ww_bytes(&mut buf).unwrap();
+    assert_eq!(bytes.len(), 12);
+    assert_eq!(Extremes::from_ww_bytes(bytes).unwrap(), extremes);
+
+    let readings = Readings {
+        temperature: Measurement {
+            value: 21.5,
+            timestamp: 1,
+        },
+        pressure: Measurement {
+            value: 1013,
+            timestamp: 2,
+        },
+    };
+    let mut buf = [0u8; 64];
+    let bytes = readings.to_ww_bytes(&mut buf).unwrap();
+    assert_eq!(Readings::from_ww_bytes(bytes).unwrap(), readings);
+}
+
+#[test]
+fn generic_struct_with_lifetime_and_owned() {
+    let response = Response {
+        request_id: 1,
+        message: "ok",
+        payload: Some(Measurement {
+            value: 0.5f32,
+            timestamp: 100,
+        }),
+    };
+    let mut buf = [0u8; 64];
+    let bytes = response.to_ww_bytes(&mut buf).unwrap();
+    assert_eq!(Response::from_ww_bytes(bytes).unwrap(), response);
+
+    let owned = ResponseOwned::<Measurement<f32>>::from_ww_bytes_owned(bytes).unwrap();
+    assert_eq!(owned.message, "ok");
+    assert_eq!(owned.payload, response.payload);
+
+    let text: Response<&str> = Response {
+        request_id: 2,
+        message: "nested",
+        payload: Some("borrowed"),
+    };
+    let mut buf = [0u8; 64];
+    let bytes = text.to_ww_bytes(&mut buf).unwrap();
+    assert_eq!(Response::from_ww_bytes(bytes).unwrap(), text);
+}
+
+#[test]
+fn generic_enum_round_trip() {
+    let replies: [Reply<Measurement<f32>>; 3] = [
+        Reply::Ack,
+        Reply::Value(Measurement {
+            value: 1.0,
+            timestamp: 5,
+        }),
+        Reply::Failed { code: 3 },
+    ];
+    for reply in replies {
+        let mut buf = [0u8; 64];
+        let bytes = reply.to_ww_bytes(&mut buf).unwrap();
+        assert_eq!(Reply::from_ww_bytes(bytes).unwrap(), reply);
+        assert_eq!(
+            reply.discriminant(),
+            match reply {
+                Reply::Ack => 0,
+                Reply::Value(_) => 1,
+                Reply::Failed { .. } => 2,
+            }
+        );
+    }
+
+    let reply: Reply<&str, Option<u16>> = Reply::Failed { code: Some(500) };
+    let mut buf = [0u8; 64];
+    let bytes = reply.to_ww_bytes(&mut buf).unwrap();
+    assert_eq!(Reply::from_ww_bytes(bytes).unwrap(), reply);
+}
@@ -0,0 +1,120 @@
+use proptest::prelude::*;
+use shrink_wrap::prelude::*;
+
+#[derive_shrink_wrap]
+#[derive(Debug, Clone)]
+struct Inner {
+    a: u8,
+    b: [u16; 3],
+}
+
+#[derive_shrink_wrap]
+#[derive(Debug, Clone)]
+struct Bounded {
+    enabled: bool,
+    gain: Option<u16>,
+    inner: Inner,
+    coord: (u8, i16),
+    #[flag]
+    threshold: bool,
+    status: Result<u32, Inner>,
+    counter: UNib32,
+    threshold: Option<Inner>,
+    trailing: u8,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(unib32)]
+#[derive(Debug, Clone)]
+enum Command {
+    Stop,
+    Move { x: i32, y: i32 },
+    Configure(Bounded),
+}
+
+#[derive_shrink_wrap]
+#[derive(Debug)]
+struct Unbounded<'i> {
+    a: u8,
+    name: &'i str,
+}
+
+#[test]
+fn unbounded_types() {
+    assert_eq!(<Unbounded as SerializeShrinkWrap>::MAX_SERIALIZED_LEN, None);
+    assert_eq!(
+        <RefVec<u8> as SerializeShrinkWrap>::MAX_SERIALIZED_LEN,
+        None
+    );
+    assert!(<Bounded as SerializeShrinkWrap>::MAX_SERIALIZED_LEN.is_some());
+}
+
+#[test]
+fn const_assert() {
+    const _: () = assert!(max_len::fits(
+        <Command as SerializeShrinkWrap>::MAX_SERIALIZED_LEN,
+        128
+    ));
+    const LEN: usize = <Inner as SerializeShrinkWrap>::MAX_SERIALIZED_LEN.unwrap();
+    let mut buf = [0u8; LEN];
+    let inner = Inner {
+        a: 0xFF,
+        b: [0xFFFF; 3],
+    };
+    assert!(inner.to_ww_bytes(&mut buf).is_ok());
+}
+
+fn inner() -> impl Strategy<Value = Inner> {
+    (any::<u8>(), any::<[u16; 3]>()).prop_map(|(a, b)| Inner { a, b })
+}
+
+fn bounded() -> impl Strategy<Value = Bounded> {
+    (
+        any::<bool>(),
+        any::<Option<u16>>(),
+        inner(),
+        any::<(u8, i16)>(),
+        prop_oneof![any::<u32>().prop_map(Ok), inner().prop_map(Err)],
+        any::<u32>(),
+        proptest::option::of(inner()),
+        any::<u8>(),
+    )
+        .prop_map(
+            |(enabled, gain, inner, coord, status, counter, threshold, trailing)| Bounded {
+                enabled,
+                gain,
+                inner,
+                coord,
+                status,
+                counter: UNib32(counter),
+                threshold,
+                trailing,
+            },
+        )
+}
+
+fn command() -> impl Strategy<Value = Command> {
+    prop_oneof![
+        Just(Command::Stop),
+        any::<(i32, i32)>().prop_map(|(x, y)| Command::Move { x, y }),
+        bounded().prop_map(Command::Configure),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn serialized_len_within_bound(command in command()) {
+        let mut buf = [0u8; 512];
+        let bytes = command.to_ww_bytes(&mut buf).unwrap();
+        let max_len = <Command as SerializeShrinkWrap>::MAX_SERIALIZED_LEN.unwrap();
+        prop_assert!(bytes.len() <= max_len, "{} > {}", bytes.len(), max_len);
+
+        let mut buf = [0u8; 512];
+        let mut wr = BufWriter::new(&mut buf);
+        wr.write_bool(true).unwrap();
+        wr.write(&command).unwrap();
+        let nested_bits = max_len::nested_bits::<Command>().unwrap();
+        let bytes = wr.finish_and_take().unwrap();
+        prop_assert!(bytes.len() * 8 <= 1 + nested_bits + 3 + 7);
+    }
+}
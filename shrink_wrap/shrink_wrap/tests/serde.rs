@@ -0,0 +1,256 @@
+#![cfg(feature = "serde")]
+
+use hex_literal::hex;
+use serde::{Deserialize, Serialize};
+use shrink_wrap::prelude::*;
+
+#[derive_shrink_wrap]
+#[derive(Debug, PartialEq)]
+struct Inner<'i> {
+    a: u8,
+    name: &'i str,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(unib32)]
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Empty,
+    Circle(u16),
+    Rect { w: u8, h: u8 },
+}
+
+#[derive_shrink_wrap]
+#[derive(Debug, PartialEq)]
+struct Outer<'i> {
+    flag: bool,
+    maybe: Option<u16>,
+    inner: Inner<'i>,
+    shape: Shape,
+    items: RefVec<'i, u8>,
+    text: Option<&'i str>,
+    coord: (u8, i16),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct SerdeInner<'i> {
+    a: u8,
+    name: &'i str,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum SerdeShape {
+    Empty,
+    Circle(u16),
+    Rect { w: u8, h: u8 },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct SerdeOuter<'i> {
+    flag: bool,
+    maybe: Option<u16>,
+    #[serde(borrow)]
+    inner: SerdeInner<'i>,
+    shape: SerdeShape,
+    items: Vec<u8>,
+    text: Option<&'i str>,
+    coord: (u8, i16),
+}
+
+fn serde_to_vec<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut buf = [0u8; 256];
+    shrink_wrap::serde::to_ww_bytes(value, &mut buf)
+        .unwrap()
+        .to_vec()
+}
+
+#[test]
+fn same_as_derive() {
+    let items = [1, 2, 3];
+    let outer = Outer {
+        flag: true,
+        maybe: Some(0xAABB),
+        inner: Inner { a: 7, name: "abc" },
+        shape: Shape::Rect { w: 5, h: 6 },
+        items: RefVec::Slice { slice: &items },
+        text: None,
+        coord: (0xCC, -2),
+    };
+    let mut buf = [0u8; 256];
+    let derive_bytes = outer.to_ww_bytes(&mut buf).unwrap();
+
+    let serde_outer = SerdeOuter {
+        flag: true,
+        maybe: Some(0xAABB),
+        inner: SerdeInner { a: 7, name: "abc" },
+        shape: SerdeShape::Rect { w: 5, h: 6 },
+        items: vec![1, 2, 3],
+        text: None,
+        coord: (0xCC, -2),
+    };
+    let serde_bytes = serde_to_vec(&serde_outer);
+    assert_eq!(serde_bytes, derive_bytes);
+
+    let des: SerdeOuter = shrink_wrap::serde::from_ww_bytes(derive_bytes).unwrap();
+    assert_eq!(des, serde_outer);
+    let des = Outer::from_ww_bytes(&serde_bytes).unwrap();
+    assert_eq!(des, outer);
+}
+
+#[test]
+fn enum_variants() {
+    let cases = [
+        (Shape::Empty, SerdeShape::Empty),
+        (Shape::Circle(0x1234), SerdeShape::Circle(0x1234)),
+        (Shape::Rect { w: 1, h: 2 }, SerdeShape::Rect { w: 1, h: 2 }),
+    ];
+    for (shape, serde_shape) in cases {
+        let mut buf = [0u8; 16];
+        let derive_bytes = shape.to_ww_bytes(&mut buf).unwrap();
+        assert_eq!(serde_to_vec(&serde_shape), derive_bytes);
+        let des: SerdeShape = shrink_wrap::serde::from_ww_bytes(derive_bytes).unwrap();
+        assert_eq!(des, serde_shape);
+    }
+}
+
+#[test]
+fn unknown_variant() {
+    #[derive(Serialize)]
+    enum ShapeV2 {
+        _Empty,
+        _Circle(u16),
+        _Rect { w: u8, h: u8 },
+        Triangle,
+    }
+    let bytes = serde_to_vec(&ShapeV2::Triangle);
+    let err = shrink_wrap::serde::from_ww_bytes::<SerdeShape>(&bytes).unwrap_err();
+    assert_eq!(
+        err,
+        shrink_wrap::serde::Error::ShrinkWrap(ShrinkWrapError::EnumFutureVersionOrMalformedData)
+    );
+}
+
+#[test]
+fn strings_and_bytes() {
+    let bytes = serde_to_vec(&("abc", "de"));
+    assert_eq!(bytes, hex!("61 62 63 64 65 23"));
+    let des: (&str, String) = shrink_wrap::serde::from_ww_bytes(&bytes).unwrap();
+    assert_eq!(des, ("abc", "de".to_string()));
+
+    let bytes = serde_to_vec(&'ф');
+    let des: char = shrink_wrap::serde::from_ww_bytes(&bytes).unwrap();
+    assert_eq!(des, 'ф');
+}
+
+#[test]
+fn map() {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(1u8, "one".to_string());
+    map.insert(2u8, "two".to_string());
+    let bytes = serde_to_vec(&map);
+    let as_vec = serde_to_vec(&vec![(1u8, "one"), (2u8, "two")]);
+    assert_eq!(bytes, as_vec);
+    let des: std::collections::BTreeMap<u8, String> =
+        shrink_wrap::serde::from_ww_bytes(&bytes).unwrap();
+    assert_eq!(des, map);
+}
+
+#[test]
+fn evolution() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct V1 {
+        a: u8,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct V2 {
+        a: u8,
+        #[serde(default)]
+        b: Option<String>,
+        #[serde(default)]
+        c: u16,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Container<T> {
+        item: T,
+        tail: u8,
+    }
+
+    // older data, newer code
+    let bytes = serde_to_vec(&V1 { a: 1 });
+    let des: V2 = shrink_wrap::serde::from_ww_bytes(&bytes).unwrap();
+    assert_eq!(
+        des,
+        V2 {
+            a: 1,
+            b: None,
+            c: 0
+        }
+    );
+
+    // newer data, older code
+    let v2 = Container {
+        item: V2 {
+            a: 1,
+            b: Some("x".into()),
+            c: 0xAABB,
+        },
+        tail: 0xCC,
+    };
+    let bytes = serde_to_vec(&v2);
+    let des: Container<V1> = shrink_wrap::serde::from_ww_bytes(&bytes).unwrap();
+    assert_eq!(
+        des,
+        Container {
+            item: V1 { a: 1 },
+            tail: 0xCC
+        }
+    );
+}
+
+#[test]
+fn truncated_input() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Sample {
+        a: u8,
+        #[serde(default)]
+        b: u32,
+    }
+
+    let bytes = serde_to_vec(&Sample {
+        a: 1,
+        b: 0xAABBCCDD,
+    });
+    assert_eq!(bytes.len(), 5);
+    for len in 2..bytes.len() {
+        let err = shrink_wrap::serde::from_ww_bytes::<Sample>(&bytes[..len]).unwrap_err();
+        assert!(
+            matches!(err, shrink_wrap::serde::Error::ShrinkWrap(_)),
+            "{len}: {err:?}"
+        );
+    }
+    // buffer ending right before a field is valid, it was written by an older version
+    let des: Sample = shrink_wrap::serde::from_ww_bytes(&bytes[..1]).unwrap();
+    assert_eq!(des, Sample { a: 1, b: 0 });
+}
+
+#[test]
+fn nested_write_read() {
+    let mut buf = [0u8; 64];
+    let mut wr = BufWriter::new(&mut buf);
+    shrink_wrap::serde::write(&mut wr, &SerdeInner { a: 1, name: "a" }).unwrap();
+    wr.write_u8(0xAA).unwrap();
+    let bytes = wr.finish_and_take().unwrap();
+
+    let mut rd = BufReader::new(bytes);
+    let inner: SerdeInner = shrink_wrap::serde::read(&mut rd).unwrap();
+    assert_eq!(inner, SerdeInner { a: 1, name: "a" });
+    assert_eq!(rd.read_u8().unwrap(), 0xAA);
+}
+
+#[test]
+fn not_self_describing() {
+    let err = shrink_wrap::serde::from_ww_bytes::<serde::de::IgnoredAny>(&[0]).unwrap_err();
+    assert_eq!(err, shrink_wrap::serde::Error::NotSelfDescribing);
+}
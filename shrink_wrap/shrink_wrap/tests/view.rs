@@ -0,0 +1,106 @@
+use proptest::prelude::*;
+use shrink_wrap::prelude::*;
+
+#[derive_shrink_wrap]
+#[view]
+#[derive(Debug, PartialEq, Clone)]
+struct Inner<'i> {
+    a: u8,
+    name: &'i str,
+}
+
+#[derive_shrink_wrap]
+#[view]
+#[derive(Debug, PartialEq, Clone)]
+struct Config<'i> {
+    enabled: bool,
+    gain: Option<u16>,
+    label: &'i str,
+    inner: Inner<'i>,
+    #[flag]
+    threshold: bool,
+    items: RefVec<'i, u8>,
+    coord: (u8, i16),
+    threshold: Option<Inner<'i>>,
+    status: Result<u32, &'i str>,
+    trailing: u8,
+}
+
+#[test]
+fn view_accessors() {
+    let items = [1, 2, 3];
+    let config = Config {
+        enabled: true,
+        gain: Some(0xAABB),
+        label: "label",
+        inner: Inner { a: 7, name: "abc" },
+        items: RefVec::Slice { slice: &items },
+        coord: (1, -1),
+        threshold: None,
+        status: Err("error"),
+        trailing: 0xCC,
+    };
+    let mut buf = [0u8; 128];
+    let bytes = config.to_ww_bytes(&mut buf).unwrap();
+
+    let view = ConfigView::from_ww_bytes(bytes);
+    assert!(view.enabled().unwrap());
+    assert_eq!(view.gain().unwrap(), Some(0xAABB));
+    assert_eq!(view.label().unwrap(), "label");
+    assert_eq!(view.trailing().unwrap(), 0xCC);
+    assert_eq!(view.status().unwrap(), Err("error"));
+    assert_eq!(view.read_all().unwrap(), config);
+
+    let inner_view = InnerView::new(view.inner_reader().unwrap());
+    assert_eq!(inner_view.name().unwrap(), "abc");
+}
+
+fn inner() -> impl Strategy<Value = (u8, String)> {
+    (any::<u8>(), ".{0,8}")
+}
+
+proptest! {
+    #[test]
+    fn view_matches_full_decoder(
+        enabled in any::<bool>(),
+        gain in any::<Option<u16>>(),
+        label in ".{0,16}",
+        inner_val in inner(),
+        items in proptest::collection::vec(any::<u8>(), 0..16),
+        coord in any::<(u8, i16)>(),
+        threshold in proptest::option::of(inner()),
+        status in prop_oneof![any::<u32>().prop_map(Ok), ".{0,8}".prop_map(Err)],
+        trailing in any::<u8>(),
+    ) {
+        let config = Config {
+            enabled,
+            gain,
+            label: &label,
+            inner: Inner { a: inner_val.0, name: &inner_val.1 },
+            items: RefVec::Slice { slice: &items },
+            coord,
+            threshold: threshold.as_ref().map(|(a, name)| Inner { a: *a, name }),
+            status: status.as_ref().map(|v| *v).map_err(|e| e.as_str()),
+            trailing,
+        };
+        let mut buf = [0u8; 512];
+        let bytes = config.to_ww_bytes(&mut buf).unwrap();
+        let full = Config::from_ww_bytes(bytes).unwrap();
+        let view = ConfigView::from_ww_bytes(bytes);
+
+        prop_assert_eq!(view.enabled().unwrap(), full.enabled);
+        prop_assert_eq!(view.gain().unwrap(), full.gain);
+        prop_assert_eq!(view.label().unwrap(), full.label);
+        prop_assert_eq!(&view.inner().unwrap(), &full.inner);
+        let view_items = view.items().unwrap();
+        prop_assert_eq!(view_items.as_slice(), full.items.as_slice());
+        prop_assert_eq!(view.coord().unwrap(), full.coord);
+        prop_assert_eq!(&view.threshold().unwrap(), &full.threshold);
+        prop_assert_eq!(view.status().unwrap(), full.status);
+        prop_assert_eq!(view.trailing().unwrap(), full.trailing);
+
+        let inner_view = InnerView::new(view.inner_reader().unwrap());
+        prop_assert_eq!(inner_view.a().unwrap(), full.inner.a);
+        prop_assert_eq!(inner_view.name().unwrap(), full.inner.name);
+    }
+}
@@ -0,0 +1,58 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::{TypeParam, WhereClause};
+
+/// Type parameters of a generic struct or enum, e.g. `T: Copy` in `Measurement<T: Copy>`.
+/// Lifetime is not included, `'i` is added to the generated code if the item has potential lifetimes.
+#[derive(Clone, Debug, Default)]
+pub struct Generics {
+    pub params: Vec<TypeParam>,
+    pub where_clause: Option<WhereClause>,
+}
+
+impl Generics {
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// `<'i, T: Bound = Default>` for the struct or enum definition.
+    pub fn def(&self, lifetime: bool) -> TokenStream {
+        angle_bracketed(lifetime, self.params.iter().map(|p| p.to_token_stream()))
+    }
+
+    /// `<'i, T: Bound + extra_bounds>` for impl blocks, default types are not allowed there.
+    pub fn impl_params(&self, lifetime: bool, extra_bounds: TokenStream) -> TokenStream {
+        let params = self.params.iter().map(|p| {
+            let ident = &p.ident;
+            let mut bounds: Vec<TokenStream> =
+                p.bounds.iter().map(|b| b.to_token_stream()).collect();
+            if !extra_bounds.is_empty() {
+                bounds.push(extra_bounds.clone());
+            }
+            if bounds.is_empty() {
+                quote! { #ident }
+            } else {
+                quote! { #ident: #(#bounds)+* }
+            }
+        });
+        angle_bracketed(lifetime, params)
+    }
+
+    /// `<'i, T>` after the type name.
+    pub fn args(&self, lifetime: bool) -> TokenStream {
+        angle_bracketed(
+            lifetime,
+            self.params.iter().map(|p| p.ident.to_token_stream()),
+        )
+    }
+}
+
+fn angle_bracketed(lifetime: bool, params: impl Iterator<Item = TokenStream>) -> TokenStream {
+    let lifetime = lifetime.then(|| quote! { 'i });
+    let params: Vec<TokenStream> = lifetime.into_iter().chain(params).collect();
+    if params.is_empty() {
+        quote! {}
+    } else {
+        quote! { <#(#params),*> }
+    }
+}
@@ -1,10 +1,11 @@
 use crate::ast::docs::Docs;
+use crate::ast::generics::Generics;
 use crate::ast::item_struct::Field;
 use crate::ast::object_size::ObjectSize;
 use crate::ast::path::Path;
 use crate::ast::repr::Repr;
 use crate::ast::ty::Type;
-use crate::ast::util::{Cfg, CfgAttrDefmt, CfgAttrSerde, Version};
+use crate::ast::util::{Cfg, CfgAttrDefmt, CfgAttrSerde, Deprecated, Version};
 use proc_macro2::{Ident, Span};
 use syn::LitStr;
 
@@ -17,6 +18,7 @@ pub struct ItemEnum {
     pub repr: Repr,
     pub explicit_ww_repr: bool,
     pub ident: Ident,
+    pub generics: Generics,
     pub variants: Vec<Variant>,
     pub cfg: Option<Cfg>,
     pub defmt: Option<CfgAttrDefmt>,
@@ -37,6 +39,7 @@ pub struct Variant {
     pub fields: Fields,
     pub discriminant: u32,
     pub since: Option<Version>,
+    pub deprecated: Option<Deprecated>,
 }
 
 impl ItemEnum {
@@ -1,8 +1,9 @@
 use crate::ast::docs::Docs;
+use crate::ast::generics::Generics;
 use crate::ast::object_size::ObjectSize;
 use crate::ast::path::Path;
 use crate::ast::ty::Type;
-use crate::ast::util::{Cfg, CfgAttrDefmt, CfgAttrSerde, Version};
+use crate::ast::util::{Cfg, CfgAttrDefmt, CfgAttrSerde, Deprecated, Version};
 use crate::ast::value::Value;
 use proc_macro2::{Ident, Span};
 use syn::LitStr;
@@ -14,6 +15,7 @@ pub struct ItemStruct {
     pub derive_owned: Vec<Path>,
     pub size_assumption: Option<ObjectSize>,
     pub ident: Ident,
+    pub generics: Generics,
     pub fields: Vec<Field>,
     pub cfg: Option<Cfg>,
     pub defmt: Option<CfgAttrDefmt>,
@@ -27,6 +29,7 @@ pub struct Field {
     pub ident: Ident,
     pub ty: Type,
     pub since: Option<Version>,
+    pub deprecated: Option<Deprecated>,
     pub default: Option<Value>,
 }
 
@@ -61,6 +64,7 @@ impl Field {
             ident: Ident::new(ident, Span::call_site()),
             ty,
             since: None,
+            deprecated: None,
             default: None,
         }
     }
@@ -1,4 +1,5 @@
 pub(crate) mod docs;
+pub(crate) mod generics;
 pub(crate) mod item_enum;
 pub(crate) mod item_struct;
 pub(crate) mod object_size;
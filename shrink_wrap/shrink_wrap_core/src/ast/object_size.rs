@@ -29,7 +29,8 @@ impl ToTokens for ObjectSize {
 }
 
 impl ObjectSize {
-    pub fn sum_recursively(&self, sizes: Vec<Ident>) -> TokenStream {
+    /// Add `ELEMENT_SIZE` of user-defined types, e.g. `Point` or `Measurement<f32>`, to the known size.
+    pub fn sum_recursively(&self, sizes: Vec<TokenStream>) -> TokenStream {
         if sizes.is_empty() {
             quote! { #self }
         } else {
@@ -39,20 +40,7 @@ impl ObjectSize {
     }
 
     pub fn assert_element_size(&self, ident: &Ident, cfg: &Option<Cfg>) -> TokenStream {
-        let size_ts = match self {
-            ObjectSize::Unsized => quote! { Unsized },
-            ObjectSize::UnsizedFinalStructure => quote! { UnsizedFinalStructure },
-            ObjectSize::SelfDescribing => quote! { SelfDescribing },
-            ObjectSize::Sized { .. } => quote! { Sized { .. } },
-        };
-        let size = match self {
-            ObjectSize::Unsized => "Unsized",
-            ObjectSize::UnsizedFinalStructure => "UnsizedFinalStructure",
-            ObjectSize::SelfDescribing => "SelfDescribing",
-            ObjectSize::Sized { .. } => "Sized",
-        };
-        let err_msg = format!("{} must be {size}", ident);
-        let err_msg = LitStr::new(&err_msg, Span::call_site());
+        let (size_ts, err_msg) = self.assert_parts(ident);
         quote! {
             #cfg
             const _: () = assert!(
@@ -68,6 +56,37 @@ impl ObjectSize {
         }
     }
 
+    /// Same check as [assert_element_size](Self::assert_element_size) for generic items. Size of those depends on the
+    /// type arguments, so the check is placed into `ser_shrink_wrap` and is performed for each instance that is used.
+    pub fn assert_element_size_inline(&self, ident: &Ident) -> TokenStream {
+        let (size_ts, err_msg) = self.assert_parts(ident);
+        quote! {
+            const {
+                assert!(
+                    matches!(<Self as SerializeShrinkWrap>::ELEMENT_SIZE, ElementSize::#size_ts),
+                    #err_msg
+                );
+            }
+        }
+    }
+
+    fn assert_parts(&self, ident: &Ident) -> (TokenStream, LitStr) {
+        let size_ts = match self {
+            ObjectSize::Unsized => quote! { Unsized },
+            ObjectSize::UnsizedFinalStructure => quote! { UnsizedFinalStructure },
+            ObjectSize::SelfDescribing => quote! { SelfDescribing },
+            ObjectSize::Sized { .. } => quote! { Sized { .. } },
+        };
+        let size = match self {
+            ObjectSize::Unsized => "Unsized",
+            ObjectSize::UnsizedFinalStructure => "UnsizedFinalStructure",
+            ObjectSize::SelfDescribing => "SelfDescribing",
+            ObjectSize::Sized { .. } => "Sized",
+        };
+        let err_msg = format!("{} must be {size}", ident);
+        (size_ts, LitStr::new(&err_msg, Span::call_site()))
+    }
+
     /// IMPORTANT: this method must be a copy of the one in shrink_wrap
     pub fn add(&self, other: ObjectSize) -> ObjectSize {
         // Order is very important here, size requirement is bumped from Sized to SelfDescribing to Unsized.
@@ -94,13 +113,13 @@ impl ObjectSize {
     }
 }
 
-fn sum_unknown(mut sizes: Vec<Ident>) -> TokenStream {
-    if let Some(ident) = sizes.pop() {
+fn sum_unknown(mut sizes: Vec<TokenStream>) -> TokenStream {
+    if let Some(ty) = sizes.pop() {
         let inner = sum_unknown(sizes);
         if inner.is_empty() {
-            quote! { <#ident as SerializeShrinkWrap>::ELEMENT_SIZE }
+            quote! { <#ty as SerializeShrinkWrap>::ELEMENT_SIZE }
         } else {
-            quote! { <#ident as SerializeShrinkWrap>::ELEMENT_SIZE.add(#inner) }
+            quote! { <#ty as SerializeShrinkWrap>::ELEMENT_SIZE.add(#inner) }
         }
     } else {
         TokenStream::new()
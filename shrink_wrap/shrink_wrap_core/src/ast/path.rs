@@ -1,15 +1,19 @@
+use crate::ast::ty::Type;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{ToTokens, TokenStreamExt, quote};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Path {
     pub segments: Vec<Ident>,
+    /// Type arguments of the last segment, e.g. `f32` in `Measurement<f32>`. Lifetimes are not included.
+    pub generic_args: Vec<Type>,
 }
 
 impl Path {
     pub fn new_ident(ident: Ident) -> Self {
         Path {
             segments: vec![ident],
+            generic_args: vec![],
         }
     }
 
@@ -19,6 +23,7 @@ impl Path {
                 .split("::")
                 .map(|s| Ident::new(s, Span::call_site()))
                 .collect(),
+            generic_args: vec![],
         }
     }
 
@@ -34,6 +39,25 @@ impl Path {
     }
 }
 
+impl Path {
+    /// Path followed by the lifetime (if any) and type arguments, e.g. `Response<'i, f32>`.
+    pub(crate) fn def_with_args(
+        &self,
+        lifetime: Option<TokenStream>,
+        no_alloc: bool,
+    ) -> TokenStream {
+        let args: Vec<TokenStream> = lifetime
+            .into_iter()
+            .chain(self.generic_args.iter().map(|ty| ty.def(no_alloc)))
+            .collect();
+        if args.is_empty() {
+            quote! { #self }
+        } else {
+            quote! { #self<#(#args),*> }
+        }
+    }
+}
+
 impl ToTokens for &Path {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let segments = self.segments.iter();
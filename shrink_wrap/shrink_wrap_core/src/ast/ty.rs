@@ -40,6 +40,10 @@ pub enum Type {
     Array(usize, Box<Type>),
     Tuple(Vec<Type>),
     Vec(Box<Type>),
+    // max_len, item_ty
+    BoundedVec(usize, Box<Type>),
+    // max_len in bytes
+    BoundedString(usize),
     Range(Box<Type>),
     RangeInclusive(Box<Type>),
 
@@ -78,8 +82,10 @@ impl Type {
                 }
                 false
             }
-            Type::Array(_, ty) => ty.potential_lifetimes(),
-            Type::External(_, potential_lifetimes) => *potential_lifetimes,
+            Type::Array(_, ty) | Type::BoundedVec(_, ty) => ty.potential_lifetimes(),
+            Type::External(path, potential_lifetimes) => {
+                *potential_lifetimes || path.generic_args.iter().any(|ty| ty.potential_lifetimes())
+            }
             // Type::Sized(_, potential_lifetimes) => *potential_lifetimes,
             _ => false,
         }
@@ -93,13 +99,16 @@ impl Type {
                     path.make_owned();
                     *potential_lifetimes = false;
                 }
+                for ty in &mut path.generic_args {
+                    ty.make_owned();
+                }
             }
             Type::Option(_, some_ty) => some_ty.make_owned(),
             Type::Result(_, ok_err_ty) => {
                 ok_err_ty.0.make_owned();
                 ok_err_ty.1.make_owned();
             }
-            Type::Array(_, layout) => {
+            Type::Array(_, layout) | Type::BoundedVec(_, layout) => {
                 layout.make_owned();
             }
             Type::Tuple(types) => {
@@ -130,7 +139,7 @@ impl Type {
                 ok_ty.visit_external_types(f);
                 err_ty.visit_external_types(f);
             }
-            Type::Array(_, ty) => {
+            Type::Array(_, ty) | Type::BoundedVec(_, ty) => {
                 ty.visit_external_types(f);
             }
             Type::Tuple(types) => {
@@ -161,7 +170,7 @@ impl Type {
                 ok_ty.visit_external_types_mut(f);
                 err_ty.visit_external_types_mut(f);
             }
-            Type::Array(_, ty) => {
+            Type::Array(_, ty) | Type::BoundedVec(_, ty) => {
                 ty.visit_external_types_mut(f);
             }
             Type::Tuple(types) => {
@@ -212,7 +221,7 @@ impl Type {
             Type::ILeb128 => return Some(ObjectSize::SelfDescribing),
             Type::F32 => 32,
             Type::F64 => 64,
-            Type::String => return Some(ObjectSize::Unsized),
+            Type::String | Type::BoundedString(_) => return Some(ObjectSize::Unsized),
             Type::Array(len, ty) => {
                 let size = match ty.element_size()? {
                     ObjectSize::Unsized => ObjectSize::Unsized,
@@ -231,7 +240,9 @@ impl Type {
                 }
                 return Some(sum);
             }
-            Type::Vec(_) => return Some(ObjectSize::UnsizedFinalStructure),
+            Type::Vec(_) | Type::BoundedVec(_, _) => {
+                return Some(ObjectSize::UnsizedFinalStructure);
+            }
             Type::Range(ty) | Type::RangeInclusive(ty) => return ty.element_size(),
             Type::External(_, _) => return None, // cannot know if it's actually Unsized or not, const calculation will be performed instead
             Type::IsSome(_) | Type::IsOk(_) => return Some(ObjectSize::Sized { size_bits: 1 }),
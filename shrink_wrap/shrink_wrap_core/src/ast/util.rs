@@ -38,3 +38,23 @@ impl ToTokens for CfgAttrSerde {
         tokens.extend(quote! { #[cfg_attr(feature = #feature, derive(serde::Deserialize, serde::Serialize))] });
     }
 }
+
+/// `#[deprecated]` attribute of a field or enum variant, emitted back onto generated definitions.
+#[derive(Clone, Debug)]
+pub struct Deprecated {
+    pub since: Option<LitStr>,
+    pub note: Option<LitStr>,
+}
+
+impl ToTokens for Deprecated {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let since = self.since.as_ref().map(|since| quote! { since = #since });
+        let note = self.note.as_ref().map(|note| quote! { note = #note });
+        if since.is_none() && note.is_none() {
+            tokens.extend(quote! { #[deprecated] });
+        } else {
+            let args = since.into_iter().chain(note);
+            tokens.extend(quote! { #[deprecated(#(#args),*)] });
+        }
+    }
+}
@@ -17,6 +17,8 @@ impl ItemEnum {
             no_alloc,
         };
         let lifetime = enum_lifetime(self, no_alloc);
+        let generics = self.generics.def(lifetime);
+        let where_clause = &self.generics.where_clause;
         let derive = if no_alloc {
             strings_to_derive(&self.derive_borrowed)
         } else {
@@ -25,19 +27,18 @@ impl ItemEnum {
         let docs = &self.docs;
         let cfg = &self.cfg;
         let cfg_attr_defmt = &self.defmt;
-        let cfg_attr_serde = if lifetime.is_empty() {
-            &self.serde
-        } else {
-            &None
-        };
-        let assert_size = if let Some(size) = &self.size_assumption {
+        let cfg_attr_serde = if lifetime { &None } else { &self.serde };
+        // generic items are checked in ser_shrink_wrap instead
+        let assert_size = if let Some(size) = &self.size_assumption
+            && self.generics.is_empty()
+        {
             size.assert_element_size(&self.ident, &self.cfg)
         } else {
             quote! {}
         };
         // let base_ty = ww_discriminant_type(self);
         let native_repr = self.native_repr();
-        let enum_discriminant = enum_discriminant(self, lifetime.clone());
+        let enum_discriminant = enum_discriminant(self, lifetime);
         let ts = quote! {
             #cfg
             #docs
@@ -46,7 +47,7 @@ impl ItemEnum {
             #cfg_attr_serde
             #[repr(#native_repr)]
             // #[ww_repr(#base_ty)]
-            pub enum #enum_name #lifetime { #variants }
+            pub enum #enum_name #generics #where_clause { #variants }
 
             #assert_size
 
@@ -68,16 +69,16 @@ impl ItemEnum {
             owned: false,
         };
         let (lifetime, enum_des_owned) = if no_alloc && self.potential_lifetimes() {
-            (quote!(<'i>), None)
+            (true, None)
         } else if skip_owned {
-            (quote!(), None)
+            (false, None)
         } else {
             let enum_des_owned = CGEnumDes {
                 item_enum: self,
                 no_alloc,
                 owned: true,
             };
-            (quote!(), Some(enum_des_owned))
+            (false, Some(enum_des_owned))
         };
 
         let mut unknown_unsized = vec![];
@@ -96,10 +97,8 @@ impl ItemEnum {
                             if let Some(size) = f.ty.element_size() {
                                 sum = sum.add(size);
                             }
-                            if let Type::External(path, _) = &f.ty
-                                && let Some(ident) = path.segments.last()
-                            {
-                                unknown_unsized.push(ident.clone());
+                            if let Some(ty) = f.ty.unknown_size_ty(no_alloc) {
+                                unknown_unsized.push(ty);
                             }
                         }
                     }
@@ -108,10 +107,8 @@ impl ItemEnum {
                             if let Some(size) = ty.element_size() {
                                 sum = sum.add(size);
                             }
-                            if let Type::External(path, _) = ty
-                                && let Some(ident) = path.segments.last()
-                            {
-                                unknown_unsized.push(ident.clone());
+                            if let Some(ty) = ty.unknown_size_ty(no_alloc) {
+                                unknown_unsized.push(ty);
                             }
                         }
                     }
@@ -126,36 +123,89 @@ impl ItemEnum {
         } else {
             sum.sum_recursively(unknown_unsized)
         };
+        let max_len = self.max_len(no_alloc);
+        let enum_ser = match &self.size_assumption {
+            Some(size) if !self.generics.is_empty() => {
+                let assert_size = size.assert_element_size_inline(&self.ident);
+                quote! { #assert_size #enum_ser }
+            }
+            _ => enum_ser.to_token_stream(),
+        };
         serdes_scaffold(
             enum_name,
+            &self.generics,
             enum_ser,
             enum_des,
             enum_des_owned,
             lifetime,
             &self.cfg,
             element_size,
+            max_len,
         )
     }
-}
 
-pub fn enum_lifetime(item_enum: &ItemEnum, no_alloc: bool) -> TokenStream {
-    if no_alloc && item_enum.potential_lifetimes() {
-        quote!(<'i>)
-    } else {
-        quote!()
+    /// Worst-case discriminant size in bits + the biggest variant.
+    fn max_len(&self, no_alloc: bool) -> TokenStream {
+        let discriminant_bits: usize = match self.repr {
+            Repr::UNib32 => {
+                let max_discriminant = self
+                    .variants
+                    .iter()
+                    .map(|v| v.discriminant)
+                    .max()
+                    .unwrap_or(0);
+                let nibbles = if max_discriminant == 0 {
+                    1
+                } else {
+                    (32 - max_discriminant.leading_zeros() as usize).div_ceil(3)
+                };
+                nibbles * 4 + 3
+            }
+            Repr::Nibble => 4 + 3,
+            Repr::U(bits) => bits as usize,
+            Repr::U8 => 8 + 7,
+            Repr::U16 => 16 + 7,
+            Repr::U32 => 32 + 7,
+        };
+        let variants_max_bits = self.variants.iter().map(|v| {
+            let fields_max_bits: Vec<TokenStream> = match &v.fields {
+                Fields::Named(named) => named.iter().map(|f| f.ty.max_bits(no_alloc)).collect(),
+                Fields::Unnamed(unnamed) => {
+                    unnamed.iter().map(|ty| ty.max_bits(no_alloc)).collect()
+                }
+                Fields::Unit => vec![],
+            };
+            quote! {{
+                let bits = Some(0);
+                #(let bits = max_len::add(bits, #fields_max_bits);)*
+                bits
+            }}
+        });
+        quote! {{
+            let variant_bits = Some(0);
+            #(let variant_bits = max_len::max(variant_bits, #variants_max_bits);)*
+            max_len::root_len(max_len::add(Some(#discriminant_bits), variant_bits))
+        }}
     }
 }
 
+pub fn enum_lifetime(item_enum: &ItemEnum, no_alloc: bool) -> bool {
+    no_alloc && item_enum.potential_lifetimes()
+}
+
 // fn ww_discriminant_type(item_enum: &ItemEnum) -> Ident {
 //     let ty = format!("u{}", item_enum.repr.required_bits());
 //     Ident::new(ty.as_str(), Span::call_site())
 // }
 
-pub fn enum_discriminant(item_enum: &ItemEnum, lifetime: TokenStream) -> TokenStream {
+pub fn enum_discriminant(item_enum: &ItemEnum, lifetime: bool) -> TokenStream {
     let enum_name = &item_enum.ident;
     let native_repr = item_enum.native_repr();
+    let params = item_enum.generics.impl_params(lifetime, quote! {});
+    let args = item_enum.generics.args(lifetime);
+    let where_clause = &item_enum.generics.where_clause;
     quote! {
-        impl #lifetime #enum_name #lifetime {
+        impl #params #enum_name #args #where_clause {
             pub fn discriminant(&self) -> #native_repr {
                 unsafe { *<*const _>::from(self).cast::<#native_repr>() }
             }
@@ -174,6 +224,7 @@ impl ToTokens for CGEnumFieldsDef<'_> {
             let ident = &variant.ident;
             let discriminant = variant.discriminant_lit();
             let variant_docs = &variant.docs;
+            let variant_deprecated = &variant.deprecated;
             let variant = match &variant.fields {
                 Fields::Named(fields_named) => {
                     let fields_named = fields_named
@@ -181,13 +232,17 @@ impl ToTokens for CGEnumFieldsDef<'_> {
                         .filter(|f| !matches!(f.ty, Type::IsOk(_) | Type::IsSome(_)))
                         .collect::<Vec<_>>();
                     let fields_docs = fields_named.iter().map(|f| &f.docs).collect::<Vec<_>>();
+                    let fields_deprecated = fields_named
+                        .iter()
+                        .map(|f| &f.deprecated)
+                        .collect::<Vec<_>>();
                     let field_names: Vec<Ident> =
                         fields_named.iter().map(|f| f.ident.clone()).collect();
                     let field_types: Vec<TokenStream> = fields_named
                         .iter()
                         .map(|f| f.ty.def(self.no_alloc))
                         .collect();
-                    quote!(#variant_docs #ident { #(#fields_docs #field_names: #field_types),* } = #discriminant,)
+                    quote!(#variant_docs #variant_deprecated #ident { #(#fields_docs #fields_deprecated #field_names: #field_types),* } = #discriminant,)
                 }
                 Fields::Unnamed(fields_unnamed) => {
                     let fields_unnamed = fields_unnamed
@@ -198,9 +253,9 @@ impl ToTokens for CGEnumFieldsDef<'_> {
                         .iter()
                         .map(|ty| ty.def(self.no_alloc))
                         .collect();
-                    quote!(#variant_docs #ident ( #(#field_types),* ) = #discriminant,)
+                    quote!(#variant_docs #variant_deprecated #ident ( #(#field_types),* ) = #discriminant,)
                 }
-                Fields::Unit => quote!(#variant_docs #ident = #discriminant,),
+                Fields::Unit => quote!(#variant_docs #variant_deprecated #ident = #discriminant,),
             };
             tokens.append_all(variant);
         }
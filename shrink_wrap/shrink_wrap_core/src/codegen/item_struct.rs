@@ -4,8 +4,9 @@ use crate::ast::ty::Type;
 use crate::ast::ItemStruct;
 use crate::codegen::ty::FieldPath;
 use crate::codegen::util::{serdes_scaffold, strings_to_derive};
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens, TokenStreamExt};
+use syn::LitStr;
 
 impl ItemStruct {
     pub fn def_rust(&self, no_alloc: bool) -> TokenStream {
@@ -14,11 +15,9 @@ impl ItemStruct {
             fields: &self.fields,
             no_alloc,
         };
-        let lifetime = if no_alloc && self.potential_lifetimes() {
-            quote!(<'i>)
-        } else {
-            quote!()
-        };
+        let lifetime = no_alloc && self.potential_lifetimes();
+        let generics = self.generics.def(lifetime);
+        let where_clause = &self.generics.where_clause;
         let derive = if no_alloc {
             strings_to_derive(&self.derive_borrowed)
         } else {
@@ -27,12 +26,11 @@ impl ItemStruct {
         let docs = &self.docs;
         let cfg = &self.cfg;
         let cfg_attr_defmt = &self.defmt;
-        let cfg_attr_serde = if lifetime.is_empty() {
-            &self.serde
-        } else {
-            &None
-        };
-        let assert_size = if let Some(size) = &self.size_assumption {
+        let cfg_attr_serde = if lifetime { &None } else { &self.serde };
+        // generic items are checked in ser_shrink_wrap instead
+        let assert_size = if let Some(size) = &self.size_assumption
+            && self.generics.is_empty()
+        {
             size.assert_element_size(&self.ident, &self.cfg)
         } else {
             quote! {}
@@ -43,7 +41,7 @@ impl ItemStruct {
             #derive
             #cfg_attr_defmt
             #cfg_attr_serde
-            pub struct #ident #lifetime { #fields }
+            pub struct #ident #generics #where_clause { #fields }
             #assert_size
         };
         ts
@@ -61,16 +59,16 @@ impl ItemStruct {
             owned: false,
         };
         let (lifetime, struct_des_owned) = if no_alloc && self.potential_lifetimes() {
-            (quote!(<'i>), None)
+            (true, None)
         } else if skip_owned {
-            (quote!(), None)
+            (false, None)
         } else {
             let struct_des_owned = CGStructDes {
                 item_struct: self,
                 no_alloc,
                 owned: true,
             };
-            (quote!(), Some(struct_des_owned))
+            (false, Some(struct_des_owned))
         };
 
         let mut unknown_unsized = vec![];
@@ -83,10 +81,8 @@ impl ItemStruct {
                 if let Some(size) = f.ty.element_size() {
                     sum = sum.add(size);
                 }
-                if let Type::External(path, _) = &f.ty
-                    && let Some(ident) = path.segments.last()
-                {
-                    unknown_unsized.push(ident.clone());
+                if let Some(ty) = f.ty.unknown_size_ty(no_alloc) {
+                    unknown_unsized.push(ty);
                 }
             }
         }
@@ -97,18 +93,194 @@ impl ItemStruct {
         } else {
             sum.sum_recursively(unknown_unsized)
         };
+        let fields_max_bits = self.fields.iter().map(|f| f.ty.max_bits(no_alloc));
+        let max_len = quote! {{
+            let bits = Some(0);
+            #(let bits = max_len::add(bits, #fields_max_bits);)*
+            max_len::root_len(bits)
+        }};
+        let struct_ser = match &self.size_assumption {
+            Some(size) if !self.generics.is_empty() => {
+                let assert_size = size.assert_element_size_inline(&self.ident);
+                quote! { #assert_size #struct_ser }
+            }
+            _ => struct_ser.to_token_stream(),
+        };
         serdes_scaffold(
             struct_name,
+            &self.generics,
             struct_ser,
             struct_des,
             struct_des_owned,
             lifetime,
             &self.cfg,
             element_size,
+            max_len,
         )
     }
 }
 
+impl ItemStruct {
+    /// Generate `StructNameView<'i>` with an accessor method for each field, that only deserializes that field.
+    /// Fields before the requested one are skipped using their size if they are Unsized, or deserialized otherwise.
+    pub fn view_rust(&self, no_alloc: bool) -> TokenStream {
+        let struct_name = &self.ident;
+        let view_name = Ident::new(format!("{}View", self.ident).as_str(), self.ident.span());
+        let cfg = &self.cfg;
+        let doc = LitStr::new(
+            format!(" Lazy view into serialized [{struct_name}], each accessor deserializes only one field.")
+                .as_str(),
+            Span::call_site(),
+        );
+        let lifetime = if no_alloc && self.potential_lifetimes() {
+            quote!(<'i>)
+        } else {
+            quote!()
+        };
+        let mut accessors = TokenStream::new();
+        for (idx, struct_field) in self.fields.iter().enumerate() {
+            if matches!(struct_field.ty, Type::IsOk(_) | Type::IsSome(_)) {
+                continue;
+            }
+            let field_name = &struct_field.ident;
+            let ty = struct_field.ty.def(no_alloc);
+            let docs = &struct_field.docs;
+            let deprecated = &struct_field.deprecated;
+            let mut skip_preceding = TokenStream::new();
+            CGStructViewSkip {
+                item_struct: self,
+                up_to: idx,
+                no_alloc,
+            }
+            .to_tokens(&mut skip_preceding);
+
+            let mut read_field = TokenStream::new();
+            struct_field.ty.buf_read(
+                field_name,
+                no_alloc,
+                false,
+                struct_field.handle_eob(),
+                &quote! { _ },
+                &mut read_field,
+            );
+            accessors.append_all(quote! {
+                #docs
+                #deprecated
+                pub fn #field_name(&self) -> Result<#ty, ShrinkWrapError> {
+                    let mut rd = self.rd;
+                    #skip_preceding
+                    #read_field
+                    Ok(#field_name)
+                }
+            });
+
+            if let Type::External(_, _) = &struct_field.ty {
+                let reader_name =
+                    Ident::new(format!("{field_name}_reader").as_str(), field_name.span());
+                let doc = LitStr::new(
+                    format!(" Reader over `{field_name}` data only, can be used to create a view of the nested object.")
+                        .as_str(),
+                    Span::call_site(),
+                );
+                accessors.append_all(quote! {
+                    #[doc = #doc]
+                    pub fn #reader_name(&self) -> Result<BufReader<'i>, ShrinkWrapError> {
+                        let mut rd = self.rd;
+                        #skip_preceding
+                        rd.split_value::<#ty>()
+                    }
+                });
+            }
+        }
+        quote! {
+            #cfg
+            #[doc = #doc]
+            #[derive(Copy, Clone)]
+            pub struct #view_name<'i> {
+                rd: BufReader<'i>,
+            }
+
+            #cfg
+            impl<'i> #view_name<'i> {
+                /// Create a view from bytes produced by [to_ww_bytes](SerializeShrinkWrap::to_ww_bytes).
+                pub fn from_ww_bytes(buf: &'i [u8]) -> Self {
+                    Self { rd: BufReader::new(buf) }
+                }
+
+                /// Create a view from a reader positioned at the start of the object, e.g., from a nested object
+                /// reader accessor or a [split](BufReader::split).
+                pub fn new(rd: BufReader<'i>) -> Self {
+                    Self { rd }
+                }
+
+                /// Deserialize the whole object.
+                pub fn read_all(&self) -> Result<#struct_name #lifetime, ShrinkWrapError> {
+                    let mut rd = self.rd;
+                    #struct_name::des_shrink_wrap(&mut rd)
+                }
+
+                #accessors
+            }
+        }
+    }
+}
+
+struct CGStructViewSkip<'a> {
+    item_struct: &'a ItemStruct,
+    up_to: usize,
+    no_alloc: bool,
+}
+
+impl ToTokens for CGStructViewSkip<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let fields = &self.item_struct.fields;
+        // flags that are used to skip or read Option and Result fields up to and including the requested one
+        let used_flags: Vec<&Ident> = fields[..=self.up_to]
+            .iter()
+            .filter_map(|f| match &f.ty {
+                Type::Option(flag, _) | Type::Result(flag, _) => Some(flag),
+                _ => None,
+            })
+            .collect();
+        for struct_field in &fields[..self.up_to] {
+            let handle_eob = struct_field.handle_eob();
+            match &struct_field.ty {
+                Type::IsSome(_) | Type::IsOk(_) => {
+                    let flag = &struct_field.ident;
+                    if used_flags.contains(&flag) {
+                        tokens.append_all(quote! { let #flag = rd.read_bool() #handle_eob; });
+                    } else {
+                        tokens.append_all(quote! { let _ = rd.read_bool() #handle_eob; });
+                    }
+                }
+                Type::Option(flag, option_ty) => {
+                    let option_ty = option_ty.def(self.no_alloc);
+                    tokens.append_all(quote! {
+                        if #flag {
+                            rd.skip::<#option_ty>() #handle_eob;
+                        }
+                    });
+                }
+                Type::Result(flag, ok_err_ty) => {
+                    let ok_ty = ok_err_ty.0.def(self.no_alloc);
+                    let err_ty = ok_err_ty.1.def(self.no_alloc);
+                    tokens.append_all(quote! {
+                        if #flag {
+                            rd.skip::<#ok_ty>() #handle_eob;
+                        } else {
+                            rd.skip::<#err_ty>() #handle_eob;
+                        }
+                    });
+                }
+                ty => {
+                    let ty = ty.def(self.no_alloc);
+                    tokens.append_all(quote! { rd.skip::<#ty>() #handle_eob; });
+                }
+            }
+        }
+    }
+}
+
 struct CGStructFieldsDef<'a> {
     fields: &'a [Field],
     no_alloc: bool,
@@ -123,8 +295,10 @@ impl ToTokens for CGStructFieldsDef<'_> {
             let ident = &struct_field.ident;
             let ty = struct_field.ty.def(self.no_alloc);
             let docs = &struct_field.docs;
+            let deprecated = &struct_field.deprecated;
             tokens.append_all(quote! {
                 #docs
+                #deprecated
                 pub #ident: #ty,
             });
         }
@@ -88,16 +88,22 @@ impl Type {
                     quote! { Vec<#inner_ty> }
                 }
             }
+            Type::BoundedVec(len, inner_ty) => {
+                let inner_ty = inner_ty.def(no_alloc);
+                let len = Lit::Int(LitInt::new(format!("{}", len).as_str(), Span::call_site()));
+                quote! { BoundedVec<#inner_ty, #len> }
+            }
+            Type::BoundedString(len) => {
+                let len = Lit::Int(LitInt::new(format!("{}", len).as_str(), Span::call_site()));
+                quote! { BoundedString<#len> }
+            }
             // Type::User(user_layout) => {
             //     let path = user_layout.path();
             //     quote! { #path }
             // }
             Type::External(path, is_lifetime) => {
-                if *is_lifetime && no_alloc {
-                    quote! { #path<'i> }
-                } else {
-                    quote! { #path }
-                }
+                let lifetime = (*is_lifetime && no_alloc).then(|| quote! { 'i });
+                path.def_with_args(lifetime, no_alloc)
             }
             Type::Result(_, ok_err_ty) => {
                 let ok_ty = ok_err_ty.0.def(no_alloc);
@@ -128,6 +134,41 @@ impl Type {
         }
     }
 
+    /// Worst-case size in bits of this type serialized as a struct field or enum variant field,
+    /// including alignment padding. Generated code evaluates to `Option<usize>`, see shrink_wrap::max_len.
+    pub fn max_bits(&self, no_alloc: bool) -> TokenStream {
+        let bits: usize = match self {
+            Type::Bool | Type::IsSome(_) | Type::IsOk(_) => 1,
+            Type::Nibble | Type::I4 => 4 + 3,
+            Type::U8 | Type::I8 => 8 + 7,
+            Type::U16 | Type::I16 => 16 + 7,
+            Type::U32 | Type::I32 | Type::F32 => 32 + 7,
+            Type::U64 | Type::I64 | Type::F64 => 64 + 7,
+            Type::U128 | Type::I128 => 128 + 7,
+            Type::UNib32 => 11 * 4 + 3,
+            Type::ULeb32 | Type::ILeb32 => 5 * 8 + 7,
+            Type::ULeb64 | Type::ILeb64 => 10 * 8 + 7,
+            Type::ULeb128 | Type::ILeb128 => 19 * 8 + 7,
+            // flag is accounted for separately, value is written with wr.write()
+            Type::Option(_, option_ty) => {
+                let option_ty = option_ty.def(no_alloc);
+                return quote! { max_len::nested_bits::<#option_ty>() };
+            }
+            Type::Result(_, ok_err_ty) => {
+                let ok_ty = ok_err_ty.0.def(no_alloc);
+                let err_ty = ok_err_ty.1.def(no_alloc);
+                return quote! {
+                    max_len::max(max_len::nested_bits::<#ok_ty>(), max_len::nested_bits::<#err_ty>())
+                };
+            }
+            _ => {
+                let ty = self.def(no_alloc);
+                return quote! { max_len::nested_bits::<#ty>() };
+            }
+        };
+        quote! { Some(#bits) }
+    }
+
     // TODO: make arg_pos_def2 behavior default one
     pub fn arg_pos_def(&self, no_alloc: bool) -> TokenStream {
         match self {
@@ -147,11 +188,8 @@ impl Type {
                 }
             }
             Type::External(path, is_lifetime) => {
-                if *is_lifetime && no_alloc {
-                    quote! { #path<'_> }
-                } else {
-                    quote! { #path }
-                }
+                let lifetime = (*is_lifetime && no_alloc).then(|| quote! { '_ });
+                path.def_with_args(lifetime, no_alloc)
             }
             _ => self.def(no_alloc),
         }
@@ -299,6 +337,8 @@ impl Type {
             }
             Type::External(_, _)
             | Type::String
+            | Type::BoundedVec(_, _)
+            | Type::BoundedString(_)
             | Type::RefBox(_)
             | Type::Range(_)
             | Type::RangeInclusive(_) => {
@@ -368,6 +408,8 @@ impl Type {
             }
             Type::External(_, _)
             | Type::String
+            | Type::BoundedVec(_, _)
+            | Type::BoundedString(_)
             | Type::RefBox(_)
             | Type::Range(_)
             | Type::RangeInclusive(_) => {
@@ -403,6 +445,20 @@ impl Type {
         tokens.append_all(quote! { let #variable_name: #enforce_ty = rd.#read_fn() #handle_err; })
     }
 
+    /// Type to take `ELEMENT_SIZE` from when it is not known during code generation, i.e., for user-defined types.
+    pub(crate) fn unknown_size_ty(&self, no_alloc: bool) -> Option<TokenStream> {
+        let Type::External(path, _) = self else {
+            return None;
+        };
+        if path.generic_args.is_empty() {
+            let ident = path.segments.last()?;
+            Some(quote! { #ident })
+        } else {
+            // size of a generic type instance depends on the arguments
+            Some(self.def(no_alloc))
+        }
+    }
+
     pub fn is_byte_slice(&self) -> bool {
         let Type::Vec(inner) = self else {
             return false;
@@ -1,21 +1,32 @@
+use crate::ast::generics::Generics;
 use crate::ast::path::Path;
 use crate::ast::util::Cfg;
 use proc_macro2::{Ident, TokenStream};
 use quote::{ToTokens, quote};
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn serdes_scaffold(
     ty_name: &Ident,
+    generics: &Generics,
     ser: impl ToTokens,
     des: impl ToTokens,
     des_owned: Option<impl ToTokens>,
-    lifetime: TokenStream,
+    lifetime: bool,
     cfg: &Option<Cfg>,
     element_size: TokenStream,
+    max_len: TokenStream,
 ) -> TokenStream {
+    let where_clause = &generics.where_clause;
     let des_owned = if let Some(des_owned) = des_owned {
+        let owned_params = generics.impl_params(
+            false,
+            quote! { SerializeShrinkWrap + DeserializeShrinkWrapOwned },
+        );
+        let owned_args = generics.args(false);
         quote! {
             #cfg
-            impl DeserializeShrinkWrapOwned for #ty_name {
+            #[allow(deprecated)]
+            impl #owned_params DeserializeShrinkWrapOwned for #ty_name #owned_args #where_clause {
                 const ELEMENT_SIZE: ElementSize = #element_size;
 
                 fn des_shrink_wrap_owned(rd: &mut BufReader<'_>) -> Result<Self, ShrinkWrapError> {
@@ -26,10 +37,19 @@ pub(crate) fn serdes_scaffold(
     } else {
         quote! {}
     };
+    // ELEMENT_SIZE of type parameters is taken from SerializeShrinkWrap in all the impls
+    let ser_params = generics.impl_params(lifetime, quote! { SerializeShrinkWrap });
+    let des_params = generics.impl_params(
+        true,
+        quote! { SerializeShrinkWrap + DeserializeShrinkWrap<'i> },
+    );
+    let args = generics.args(lifetime);
     quote! {
         #cfg
-        impl #lifetime SerializeShrinkWrap for #ty_name #lifetime {
+        #[allow(deprecated)]
+        impl #ser_params SerializeShrinkWrap for #ty_name #args #where_clause {
             const ELEMENT_SIZE: ElementSize = #element_size;
+            const MAX_SERIALIZED_LEN: Option<usize> = #max_len;
 
             fn ser_shrink_wrap(&self, wr: &mut BufWriter) -> Result<(), ShrinkWrapError> {
                 #ser
@@ -37,7 +57,8 @@ pub(crate) fn serdes_scaffold(
         }
 
         #cfg
-        impl<'i> DeserializeShrinkWrap<'i> for #ty_name #lifetime {
+        #[allow(deprecated)]
+        impl #des_params DeserializeShrinkWrap<'i> for #ty_name #args #where_clause {
             const ELEMENT_SIZE: ElementSize = #element_size;
 
             fn des_shrink_wrap<'di>(rd: &'di mut BufReader<'i>) -> Result<Self, ShrinkWrapError> {
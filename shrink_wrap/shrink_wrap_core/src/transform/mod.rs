@@ -5,6 +5,9 @@ mod transform_struct;
 mod transform_ty;
 mod util;
 
-pub use syn_util::{collect_docs_attrs, collect_unknown_attributes, take_id_attr, take_owned_attr};
+pub use syn_util::{
+    collect_docs_attrs, collect_unknown_attributes, take_id_attr, take_owned_attr,
+    take_view_attr,
+};
 pub use transform_ty::{transform_return_type, transform_type};
 pub use util::{FieldPath, FieldPathRoot, create_flags};
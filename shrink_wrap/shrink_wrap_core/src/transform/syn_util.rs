@@ -2,7 +2,7 @@ use crate::ast::docs::Docs;
 use crate::ast::object_size::ObjectSize;
 use crate::ast::path::Path;
 use crate::ast::repr::Repr;
-use crate::ast::util::Version;
+use crate::ast::util::{Deprecated, Version};
 use crate::ast::value::Value;
 use syn::{Expr, Lit, LitStr, Meta};
 
@@ -45,6 +45,52 @@ pub fn take_since_attr(attrs: &mut Vec<syn::Attribute>) -> Result<Option<Version
     }
 }
 
+/// Take `#[deprecated]`, `#[deprecated = "note"]` or `#[deprecated(since = "x.y.z", note = "...")]` attribute
+pub fn take_deprecated_attr(
+    attrs: &mut Vec<syn::Attribute>,
+) -> Result<Option<Deprecated>, String> {
+    let attr_idx = attrs
+        .iter()
+        .enumerate()
+        .find(|(_, a)| a.path().is_ident("deprecated"))
+        .map(|(idx, _)| idx);
+    let Some(attr_idx) = attr_idx else {
+        return Ok(None);
+    };
+    let attr = attrs.remove(attr_idx);
+    let mut deprecated = Deprecated {
+        since: None,
+        note: None,
+    };
+    match &attr.meta {
+        Meta::Path(_) => {}
+        Meta::NameValue(name_value) => {
+            if let Expr::Lit(expr_lit) = &name_value.value
+                && let Lit::Str(lit_str) = &expr_lit.lit
+            {
+                deprecated.note = Some(lit_str.clone());
+            } else {
+                return Err("Expected #[deprecated = \"note\"]".into());
+            }
+        }
+        Meta::List(_) => {
+            attr.parse_nested_meta(|meta| {
+                let value: LitStr = meta.value()?.parse()?;
+                if meta.path.is_ident("since") {
+                    deprecated.since = Some(value);
+                } else if meta.path.is_ident("note") {
+                    deprecated.note = Some(value);
+                } else {
+                    return Err(meta.error("expected since or note"));
+                }
+                Ok(())
+            })
+            .map_err(|e| format!("Expected #[deprecated(since = \"x.y.z\", note = \"...\")]: {e}"))?;
+        }
+    }
+    Ok(Some(deprecated))
+}
+
 /// Take `#[default = lit]` attribute and return Value containing provided literal
 pub(crate) fn take_default_attr(attrs: &mut Vec<syn::Attribute>) -> Result<Option<Value>, String> {
     let attr_idx = attrs
@@ -200,6 +246,20 @@ pub fn take_owned_attr(attrs: &mut Vec<syn::Attribute>) -> Result<Option<LitStr>
     take_attr_inner(attrs, "owned")
 }
 
+/// Take `#[view]` attribute and return whether it was present
+pub fn take_view_attr(attrs: &mut Vec<syn::Attribute>) -> bool {
+    let attr_idx = attrs
+        .iter()
+        .enumerate()
+        .find(|(_, a)| a.path().is_ident("view"))
+        .map(|(idx, _)| idx);
+    let Some(attr_idx) = attr_idx else {
+        return false;
+    };
+    attrs.remove(attr_idx);
+    true
+}
+
 pub(crate) fn take_defmt_attr(attrs: &mut Vec<syn::Attribute>) -> Result<Option<LitStr>, String> {
     take_attr_inner(attrs, "defmt")
 }
@@ -275,7 +335,7 @@ pub fn collect_unknown_attributes(attrs: &mut Vec<syn::Attribute>) {
         if a.path().is_ident("derive_shrink_wrap") {
             continue;
         }
-        if a.path().is_ident("owned") {
+        if a.path().is_ident("owned") || a.path().is_ident("view") {
             continue;
         }
         println!("Unknown attribute: {:?}", a.meta.path());
@@ -3,13 +3,16 @@ use crate::ast::util::{CfgAttrDefmt, CfgAttrSerde};
 use crate::ast::ItemEnum;
 use crate::transform::docs_util::add_notes;
 use crate::transform::syn_util::{
-    collect_docs_attrs, collect_unknown_attributes, take_defmt_attr, take_derive_attr,
-    take_derive_borrowed_attr, take_derive_owned_attr, take_serde_attr, take_since_attr,
-    take_size_assumption, take_ww_repr_attr,
+    collect_docs_attrs, collect_unknown_attributes, take_defmt_attr, take_deprecated_attr,
+    take_derive_attr, take_derive_borrowed_attr, take_derive_owned_attr, take_serde_attr,
+    take_since_attr, take_size_assumption, take_ww_repr_attr,
+};
+use crate::transform::transform_struct::{
+    change_is_ok_to_is_some, move_deprecated_from_flags, propagate_default_to_flags,
 };
-use crate::transform::transform_struct::{change_is_ok_to_is_some, propagate_default_to_flags};
 use crate::transform::util::{
-    check_flag_order, create_flags, create_tuple_flags, transform_field, FieldPath, FieldPathRoot,
+    check_flag_order, create_flags, create_tuple_flags, transform_field, transform_generics,
+    FieldPath, FieldPathRoot,
 };
 use syn::{Expr, Lit};
 
@@ -35,6 +38,7 @@ impl ItemEnum {
             let fields = convert_fields(&variant.fields, &path)?;
             let mut attrs = variant.attrs.clone();
             let since = take_since_attr(&mut attrs)?;
+            let deprecated = take_deprecated_attr(&mut attrs)?;
             let docs = collect_docs_attrs(&mut attrs);
             collect_unknown_attributes(&mut attrs);
             variants.push(Variant {
@@ -43,6 +47,7 @@ impl ItemEnum {
                 fields,
                 discriminant,
                 since,
+                deprecated,
             });
         }
         let mut attrs = item_enum.attrs.clone();
@@ -70,6 +75,7 @@ impl ItemEnum {
             derive_borrowed,
             derive_owned,
             ident: item_enum.ident.clone(),
+            generics: transform_generics(&item_enum.generics)?,
             repr,
             explicit_ww_repr: true,
             variants,
@@ -117,6 +123,7 @@ fn convert_fields(fields: &syn::Fields, path: &FieldPath) -> Result<Fields, Stri
             check_flag_order(&named)?;
             propagate_default_to_flags(&mut named)?;
             change_is_ok_to_is_some(&mut named);
+            move_deprecated_from_flags(&mut named);
             Ok(Fields::Named(named))
         }
         syn::Fields::Unnamed(fields_unnamed) => {
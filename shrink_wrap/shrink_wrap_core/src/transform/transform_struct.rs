@@ -9,7 +9,7 @@ use crate::transform::syn_util::{
     take_derive_borrowed_attr, take_derive_owned_attr, take_serde_attr, take_size_assumption,
 };
 use crate::transform::util::{
-    check_flag_order, create_flags, transform_field, FieldPath, FieldPathRoot,
+    check_flag_order, create_flags, transform_field, transform_generics, FieldPath, FieldPathRoot,
 };
 
 impl ItemStruct {
@@ -45,11 +45,13 @@ impl ItemStruct {
         check_flag_order(&fields)?;
         propagate_default_to_flags(&mut fields)?;
         change_is_ok_to_is_some(&mut fields);
+        move_deprecated_from_flags(&mut fields);
         Ok(ItemStruct {
             docs,
             derive_borrowed,
             derive_owned,
             ident: item_struct.ident.clone(),
+            generics: transform_generics(&item_struct.generics)?,
             size_assumption,
             fields,
             cfg: None,
@@ -117,3 +119,23 @@ pub fn change_is_ok_to_is_some(fields: &mut [Field]) {
         }
     }
 }
+
+/// Move `#[deprecated]` from explicit flags to the Option or Result they belong to, flags are not part of the generated
+/// definitions and the attribute would be lost otherwise.
+pub fn move_deprecated_from_flags(fields: &mut [Field]) {
+    for idx in 0..fields.len() {
+        let (Type::IsOk(ident) | Type::IsSome(ident)) = &fields[idx].ty else {
+            continue;
+        };
+        let ident = ident.clone();
+        let Some(deprecated) = fields[idx].deprecated.take() else {
+            continue;
+        };
+        if let Some(flagged) = fields
+            .iter_mut()
+            .find(|f| f.ident == ident && matches!(f.ty, Type::Option(_, _) | Type::Result(_, _)))
+        {
+            flagged.deprecated.get_or_insert(deprecated);
+        }
+    }
+}
@@ -16,10 +16,12 @@ pub fn transform_type(
                 let ty = transform_path_segment(path_segment, path)?;
                 Ok(ty)
             } else {
+                let last_arguments = &type_path.path.segments.last().expect("").arguments;
                 let mut path = Path {
                     segments: Vec::new(),
+                    generic_args: generic_args(last_arguments, path)?,
                 };
-                let is_lifetime = is_lifetime(&type_path.path.segments.last().expect("").arguments);
+                let is_lifetime = is_lifetime(last_arguments);
                 for segment in type_path.path.segments {
                     path.segments.push(segment.ident);
                 }
@@ -89,6 +91,8 @@ fn transform_path_segment(
         "f64" => Type::F64,
         "String" | "str" => Type::String,
         "Vec" | "RefVec" => transform_type_vec(path_segment, field_path)?,
+        "BoundedVec" => transform_type_bounded_vec(path_segment, field_path)?,
+        "BoundedString" => transform_type_bounded_string(path_segment)?,
         "Result" => transform_type_result(path_segment, field_path)?,
         "Option" => transform_type_option(path_segment, field_path)?,
         "Range" => transform_type_range(path_segment, field_path)?,
@@ -113,10 +117,9 @@ fn transform_path_segment(
                 }
             }
 
-            return Ok(Type::External(
-                Path::new_ident(Ident::new(other_ty, path_segment.ident.span())),
-                is_lifetime(&path_segment.arguments),
-            ));
+            let mut path = Path::new_ident(Ident::new(other_ty, path_segment.ident.span()));
+            path.generic_args = generic_args(&path_segment.arguments, field_path)?;
+            return Ok(Type::External(path, is_lifetime(&path_segment.arguments)));
         }
     };
     Ok(ty)
@@ -132,6 +135,26 @@ fn is_lifetime(arguments: &PathArguments) -> bool {
     false
 }
 
+/// Type arguments of a user-defined generic type, lifetimes are skipped as they are handled separately.
+fn generic_args(arguments: &PathArguments, path: &FieldPath) -> Result<Vec<Type>, String> {
+    let PathArguments::AngleBracketed(args) = arguments else {
+        return Ok(vec![]);
+    };
+    let mut types = vec![];
+    for arg in &args.args {
+        match arg {
+            GenericArgument::Lifetime(_) => {}
+            GenericArgument::Type(ty) => types.push(transform_type(ty.clone(), None, path)?),
+            u => {
+                return Err(format!(
+                    "only lifetimes and types are supported as generic arguments, got {u:?}"
+                ));
+            }
+        }
+    }
+    Ok(types)
+}
+
 fn transform_type_result(path_segment: &PathSegment, path: &FieldPath) -> Result<Type, String> {
     let PathArguments::AngleBracketed(arg) = &path_segment.arguments else {
         return Err("expected Result<T, E>, got Result or Result()".into());
@@ -174,6 +197,46 @@ fn transform_type_vec(path_segment: &PathSegment, path: &FieldPath) -> Result<Ty
     Ok(Type::Vec(Box::new(inner_ty)))
 }
 
+fn transform_type_bounded_vec(
+    path_segment: &PathSegment,
+    path: &FieldPath,
+) -> Result<Type, String> {
+    let PathArguments::AngleBracketed(arg) = &path_segment.arguments else {
+        return Err("expected BoundedVec<T, N>, got BoundedVec or BoundedVec()".into());
+    };
+    let mut args = arg.args.iter();
+    let (Some(ty_arg), Some(len_arg)) = (args.next(), args.next()) else {
+        return Err("expected BoundedVec<T, N>".into());
+    };
+    let GenericArgument::Type(inner_ty) = ty_arg else {
+        return Err(format!("expected BoundedVec<T, N>, got {arg:?}"));
+    };
+    let inner_ty = transform_type(inner_ty.clone(), None, path)?;
+    let max_len = const_generic_len(len_arg)?;
+    Ok(Type::BoundedVec(max_len, Box::new(inner_ty)))
+}
+
+fn transform_type_bounded_string(path_segment: &PathSegment) -> Result<Type, String> {
+    let PathArguments::AngleBracketed(arg) = &path_segment.arguments else {
+        return Err("expected BoundedString<N>, got BoundedString or BoundedString()".into());
+    };
+    let Some(len_arg) = arg.args.first() else {
+        return Err("expected BoundedString<N>".into());
+    };
+    let max_len = const_generic_len(len_arg)?;
+    Ok(Type::BoundedString(max_len))
+}
+
+fn const_generic_len(arg: &GenericArgument) -> Result<usize, String> {
+    let GenericArgument::Const(Expr::Lit(lit)) = arg else {
+        return Err("only literals supported as max length".into());
+    };
+    let Lit::Int(lit_int) = &lit.lit else {
+        return Err("only integers supported as max length".into());
+    };
+    lit_int.base10_parse().map_err(|e| e.to_string())
+}
+
 fn transform_type_option(path_segment: &PathSegment, path: &FieldPath) -> Result<Type, String> {
     let PathArguments::AngleBracketed(arg) = &path_segment.arguments else {
         return Err("expected Option<T>, got Option or Option()".into());
@@ -1,9 +1,10 @@
 use crate::ast::docs::Docs;
+use crate::ast::generics::Generics;
 use crate::ast::item_struct::Field;
 use crate::ast::ty::Type;
 use crate::transform::syn_util::{
     collect_docs_attrs, collect_unknown_attributes, take_default_attr, take_flag_attr,
-    take_id_attr, take_since_attr,
+    take_deprecated_attr, take_id_attr, take_since_attr,
 };
 use crate::transform::transform_ty::transform_type;
 use proc_macro2::{Ident, Span};
@@ -96,6 +97,7 @@ pub fn create_flags(fields: &mut Vec<Field>, explicit_flags: &[Ident]) {
                 Type::IsSome(ident)
             },
             since: None,
+            deprecated: None,
             default: None,
         };
         fields.insert(pos + shift, flag);
@@ -188,6 +190,7 @@ pub fn transform_field(
     let default = take_default_attr(&mut field.attrs)?;
     let flag = take_flag_attr(&mut field.attrs);
     let id = take_id_attr(&mut field.attrs).unwrap_or(def_order_idx);
+    let deprecated = take_deprecated_attr(&mut field.attrs)?;
     let docs = collect_docs_attrs(&mut field.attrs);
     collect_unknown_attributes(&mut field.attrs);
 
@@ -212,6 +215,7 @@ pub fn transform_field(
                 ident,
                 ty: Type::IsOk(result_ident),
                 since: None,
+                deprecated,
                 default,
             },
             true,
@@ -224,9 +228,31 @@ pub fn transform_field(
                 ident,
                 ty,
                 since: take_since_attr(&mut field.attrs)?,
+                deprecated,
                 default,
             },
             false,
         ))
     }
 }
+
+/// Collect type parameters, lifetimes are skipped as generated code always uses `'i`.
+pub(crate) fn transform_generics(generics: &syn::Generics) -> Result<Generics, String> {
+    let mut params = vec![];
+    for param in &generics.params {
+        match param {
+            syn::GenericParam::Lifetime(_) => {}
+            syn::GenericParam::Type(type_param) => params.push(type_param.clone()),
+            syn::GenericParam::Const(const_param) => {
+                return Err(format!(
+                    "const generic parameter {} is not supported",
+                    const_param.ident
+                ));
+            }
+        }
+    }
+    Ok(Generics {
+        params,
+        where_clause: generics.where_clause.clone(),
+    })
+}
@@ -8,6 +8,8 @@ mod ww_repr;
 /// * Automatic generation of `MyTypeOwned` from `MyType<'i>` struct/enum definition and respective serdes code.
 /// * Support for `#[flag]` attributes to manually position where Option and Result flags are placed in the binary form
 ///   (for space savings and/or backwards compatibility).
+/// * `#[view]` on structs generates `MyTypeView<'i>` with an accessor method for each field, that deserializes only
+///   the requested field directly from the serialized bytes.
 ///
 /// See also [ShrinkWrap derive macro](ShrinkWrap).
 ///
@@ -1,7 +1,7 @@
 use proc_macro2::{Span, TokenStream};
 use quote::TokenStreamExt;
 use shrink_wrap_core::ast::{ItemEnum, ItemStruct};
-use shrink_wrap_core::transform::take_owned_attr;
+use shrink_wrap_core::transform::{take_owned_attr, take_view_attr};
 use syn::{File, Item};
 
 // TODO: move owned = "" to derive_shrink_warp attribute macro args?
@@ -33,11 +33,15 @@ fn shrink_wrap_attr_inner(mut file: File) -> Result<TokenStream, String> {
         _ => return Err("Expected enum or struct".into()),
     };
     let generate_owned = take_owned_attr(attrs)?;
+    let generate_view = take_view_attr(attrs);
     let no_alloc = has_lifetimes(&item);
 
     let mut ts = TokenStream::new();
     match &item {
         Item::Enum(item_enum) => {
+            if generate_view {
+                return Err("#[view] is only supported on structs".into());
+            }
             let ww_item_enum = ItemEnum::from_syn(item_enum, true)?;
             ts.append_all(ww_item_enum.def_rust(no_alloc));
             ts.append_all(ww_item_enum.serdes_rust(no_alloc, false));
@@ -49,8 +53,14 @@ fn shrink_wrap_attr_inner(mut file: File) -> Result<TokenStream, String> {
         }
         Item::Struct(item_struct) => {
             let ww_item_struct = ItemStruct::from_syn(item_struct, true)?;
+            if generate_view && !ww_item_struct.generics.is_empty() {
+                return Err("#[view] is not supported on generic structs".into());
+            }
             ts.append_all(ww_item_struct.def_rust(no_alloc));
             ts.append_all(ww_item_struct.serdes_rust(no_alloc, false));
+            if generate_view {
+                ts.append_all(ww_item_struct.view_rust(no_alloc));
+            }
             if let Some(feature) = &generate_owned {
                 let struct_owned = ww_item_struct.to_owned(feature.clone());
                 ts.append_all(struct_owned.def_rust(false));
@@ -66,17 +76,16 @@ fn shrink_wrap_derive_inner(mut file: File) -> Result<TokenStream, String> {
     let Some(item) = file.items.pop() else {
         return Err("Expected one item (enum or struct)".into());
     };
+    let no_alloc = has_lifetimes(&item);
 
     let mut ts = TokenStream::new();
     match &item {
         Item::Enum(item_enum) => {
             let ww_item_enum = ItemEnum::from_syn(item_enum, true)?;
-            let no_alloc = !item_enum.generics.params.is_empty();
             ts.append_all(ww_item_enum.serdes_rust(no_alloc, false));
         }
         Item::Struct(item_struct) => {
             let ww_item_struct = ItemStruct::from_syn(item_struct, true)?;
-            let no_alloc = !item_struct.generics.params.is_empty();
             ts.append_all(ww_item_struct.serdes_rust(no_alloc, false));
         }
         _ => {}
@@ -0,0 +1,327 @@
+#[cfg(test)]
+mod tests {
+    use dashboard_fixtures::{client_exchange, hex, value_cases};
+    use std::fmt::Write;
+    use std::path::{Path, PathBuf};
+    use std::process::Command as Process;
+
+    /// C++ value literals, in the same order as value_cases()
+    const VALUE_LITERALS: [&str; 10] = [
+        "Mode{Mode::Idle{}}",
+        "Mode{Mode::Run{1500}}",
+        "Mode{Mode::Hold{100, -7}}",
+        r#"Status{Mode{Mode::Run{3}}, true, 21.5f, "left", {1, 70000}, {0xde, 0xad}, {-5, 9}, {-1, 0, 300}, std::string("overheat"), UINT64_MAX, -2, 1000, 9}"#,
+        r#"Status{Mode{Mode::Idle{}}, false, std::nullopt, "\xce\xa9", {}, {}, {INT32_MAX, 0}, {0, 0, 0}, std::nullopt, 0, INT64_MIN, 0, 0}"#,
+        "Limits{{10, 2000}, -3.25, -128}",
+        r#"ConfigError{ConfigError::OutOfRange{"too high"}}"#,
+        "Telemetry{-100000, ww::Err<uint16_t>{513}}",
+        r#"Command{Command::Label{"go"}}"#,
+        "Command{Command::Sequence{{Mode{Mode::Idle{}}, Mode{Mode::Hold{1, 15}}}}}",
+    ];
+
+    /// Requests sent by the generated client are compared to the ones created in Rust and events created in Rust are
+    /// fed back to it.
+    fn client_program() -> String {
+        let exchange = client_exchange();
+        let mut cpp = String::new();
+        writeln!(
+            cpp,
+            r#"
+    std::vector<std::vector<uint8_t>> sent;
+    ww::Client ww_client([&](const std::vector<uint8_t>& bytes) {{ sent.push_back(bytes); }});
+    DashboardClient dashboard(ww_client);
+
+    auto stopped = dashboard.motor(1).stop(true);
+    check("stop request", sent.at(0), "{}");
+    feed(ww_client, "{}");
+    same("stop result", stopped.get() == std::optional<uint16_t>(300));
+
+    auto configured = dashboard.configure(2, Limits{{{{1, 5}}, 12.0, 3}});
+    check("configure request", sent.at(1), "{}");
+    feed(ww_client, "{}");
+    same("configure result", configured.get() == ww::Result<uint32_t, ConfigError>(ww::Err<ConfigError>{{ConfigError{{ConfigError::BadChannel{{}}}}}}));
+
+    auto speed = dashboard.read_speed();
+    check("read request", sent.at(2), "{}");
+    feed(ww_client, "{}");
+    try {{
+        speed.get();
+        same("read error", false);
+    }} catch (const ww::RemoteError& e) {{
+        same("read error", e.kind == "BadPath" && e.err_seq == 7);
+    }}
+
+    std::vector<Telemetry> received;
+    auto opened = dashboard.open_stream_telemetry([&](const Telemetry& value) {{ received.push_back(value); }});
+    check("open request", sent.at(3), "{}");
+    feed(ww_client, "{}");
+    opened.get();
+    feed(ww_client, "{}");
+    same("telemetry", received == std::vector<Telemetry>{{Telemetry{{1, ww::Ok<uint8_t>{{2}}}}}});
+
+    std::vector<std::vector<uint8_t>> logs;
+    auto log_opened = dashboard.open_stream_log([&](const std::vector<uint8_t>& value) {{ logs.push_back(value); }});
+    feed(ww_client, "{}");
+    log_opened.get();
+    feed(ww_client, "{}");
+    same("log", logs == std::vector<std::vector<uint8_t>>{{{{1, 2, 3}}}});
+
+    dashboard.write_commands(Command{{Command::Label{{"go"}}}});
+    check("sink write", sent.at(5), "{}");"#,
+            exchange.stop_request,
+            exchange.stop_return,
+            exchange.configure_request,
+            exchange.configure_return,
+            exchange.read_request,
+            exchange.read_error,
+            exchange.telemetry_open_request,
+            exchange.telemetry_opened,
+            exchange.telemetry_data,
+            exchange.log_opened,
+            exchange.log_data,
+            exchange.commands_write,
+        )
+        .unwrap();
+        cpp
+    }
+
+    fn snake_case(ty: &str) -> String {
+        match ty {
+            "ConfigError" => "config_error".into(),
+            ty => ty.to_lowercase(),
+        }
+    }
+
+    fn golden_program() -> String {
+        let mut cpp = String::new();
+        cpp.push_str(
+            r#"#define WW_NO_WEBSOCKET
+#include "dashboard.hpp"
+
+#include <iostream>
+
+using namespace typescript_api;
+
+static int failed = 0;
+
+static std::string hex(const std::vector<uint8_t>& bytes) {
+    std::string s;
+    char b[4];
+    for (size_t i = 0; i < bytes.size(); i++) {
+        std::snprintf(b, sizeof(b), i == 0 ? "%02x" : " %02x", bytes[i]);
+        s += b;
+    }
+    return s;
+}
+
+static std::vector<uint8_t> from_hex(const std::string& s) {
+    std::vector<uint8_t> bytes;
+    for (size_t i = 0; i + 1 < s.size(); i += 3) {
+        bytes.push_back(static_cast<uint8_t>(std::stoul(s.substr(i, 2), nullptr, 16)));
+    }
+    return bytes;
+}
+
+static void check(const char* name, const std::vector<uint8_t>& actual, const std::string& expected) {
+    if (hex(actual) != expected) {
+        std::cerr << name << ": expected [" << expected << "], got [" << hex(actual) << "]" << std::endl;
+        failed += 1;
+    }
+}
+
+static void same(const char* name, bool equal) {
+    if (!equal) {
+        std::cerr << name << ": values differ" << std::endl;
+        failed += 1;
+    }
+}
+
+static void feed(ww::Client& client, const std::string& event) {
+    std::vector<uint8_t> bytes = from_hex(event);
+    client.handle_event(bytes.data(), bytes.size());
+}
+
+int main() {
+"#,
+        );
+        let cases = value_cases();
+        assert_eq!(cases.len(), VALUE_LITERALS.len());
+        for (idx, ((ty, bytes), literal)) in cases.iter().zip(VALUE_LITERALS).enumerate() {
+            let bytes = hex(bytes);
+            let snake = snake_case(ty);
+            writeln!(
+                cpp,
+                "    check(\"{ty} #{idx}\", encode_{snake}({literal}), \"{bytes}\");"
+            )
+            .unwrap();
+            writeln!(
+                cpp,
+                "    same(\"{ty} #{idx} decoded\", decode_{snake}(from_hex(\"{bytes}\")) == {literal});"
+            )
+            .unwrap();
+        }
+        cpp.push_str(&client_program());
+        cpp.push_str(
+            r#"
+    return failed > 0 ? 1 : 0;
+}
+"#,
+        );
+        cpp
+    }
+
+    /// Program talking to a mock device over WebSocket.
+    fn ws_program(port: u16) -> String {
+        format!(
+            r#"#include "dashboard.hpp"
+
+#include <iostream>
+
+using namespace typescript_api;
+
+int main() {{
+    try {{
+        auto connection = ww::WebSocketConnection::connect("127.0.0.1", {port});
+        DashboardClient dashboard(connection->client());
+        dashboard.write_speed(-42).get();
+        if (dashboard.read_speed().get() != -42) {{
+            std::cerr << "speed was not written" << std::endl;
+            return 1;
+        }}
+        dashboard.status().get();
+        dashboard.motor(0).stop(true).get();
+        dashboard.set_mode(Mode{{Mode::Run{{100}}}}).get();
+    }} catch (const std::exception& e) {{
+        std::cerr << e.what() << std::endl;
+        return 1;
+    }}
+    return 0;
+}}
+"#
+        )
+    }
+
+    fn gen_dashboard() -> String {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../typescript_api");
+        let api_bundle =
+            wire_weaver_core::load(&crate_path, Some("Dashboard".into()), true).unwrap();
+        wire_weaver_core::codegen::cpp::gen_cpp(&api_bundle).unwrap()
+    }
+
+    /// Runs without a compiler, checks that everything golden and websocket_mock programs rely on is generated.
+    #[test]
+    fn generated_header() {
+        let hpp = gen_dashboard();
+        assert!(hpp.contains("#pragma once"));
+        assert!(hpp.contains("namespace typescript_api {"));
+        assert!(hpp.contains(
+            r#"inline constexpr const char* WW_API_NAME = "typescript_api::Dashboard v0.1.0";"#
+        ));
+        for (ty, _) in value_cases() {
+            let snake = snake_case(ty);
+            assert!(
+                hpp.contains(&format!(
+                    "inline std::vector<uint8_t> encode_{snake}(const {ty}& value) {{"
+                )),
+                "encode_{snake}"
+            );
+            assert!(
+                hpp.contains(&format!(
+                    "inline {ty} decode_{snake}(const std::vector<uint8_t>& bytes) {{"
+                )),
+                "decode_{snake}"
+            );
+        }
+        for line in [
+            "class DashboardClient {",
+            "    std::future<void> set_mode(const Mode& mode) const;",
+            "    std::future<Status> status() const;",
+            "    std::future<ww::Result<uint32_t, ConfigError>> configure(uint8_t channel, const Limits& limits) const;",
+            "    std::future<int16_t> read_speed() const;",
+            "    std::future<void> write_speed(int16_t value) const;",
+            "    std::future<uint64_t> read_serial() const;",
+            "    std::future<void> open_stream_telemetry(std::function<void(const Telemetry&)> on_value) const;",
+            "    std::future<void> close_stream_telemetry() const;",
+            "    std::future<void> open_stream_log(std::function<void(const std::vector<uint8_t>&)> on_value) const;",
+            "    std::future<void> open_sink_commands() const;",
+            "    void write_commands(const Command& value) const;",
+            "    std::future<void> close_sink_commands() const;",
+            "    MotorClient motor(uint32_t index) const;",
+            "class MotorClient {",
+            "    std::future<float> read_target() const;",
+            "    std::future<void> write_target(float value) const;",
+            "    std::future<std::optional<uint16_t>> stop(bool brake) const;",
+            // C ABI of wire_weaver_c_shim and a connection using it
+            "int32_t ww_shim_send(WwShim* shim, const uint8_t* bytes, size_t len);",
+            "class ShimConnection {",
+        ] {
+            assert!(hpp.contains(line), "missing: {line}");
+        }
+    }
+
+    /// Writes generated header and a program using it to a temporary directory, compiles and runs it.
+    fn compile_and_run(dir_name: &str, program: &str) {
+        let dashboard = gen_dashboard();
+
+        let dir = std::env::temp_dir().join(format!("{dir_name}_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("dashboard.hpp"), dashboard).unwrap();
+        std::fs::write(dir.join("main.cpp"), program).unwrap();
+
+        let cxx = std::env::var("CXX").unwrap_or("g++".into());
+        let exe: PathBuf = dir.join("main");
+        let output = Process::new(&cxx)
+            .args([
+                "-std=c++17",
+                "-pthread",
+                "-Wall",
+                "-Wextra",
+                "-Werror",
+                "-o",
+            ])
+            .arg(&exe)
+            .arg("main.cpp")
+            .current_dir(&dir)
+            .output()
+            .unwrap_or_else(|e| panic!("failed to run {cxx}: {e}"));
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let output = Process::new(&exe).current_dir(&dir).output().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(
+            output.status.success(),
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    #[ignore = "requires a C++17 compiler, set CXX to its path (default: g++) and run `just test-cpp`"]
+    fn golden() {
+        compile_and_run("ww_cpp_golden", &golden_program());
+    }
+
+    #[test]
+    #[ignore = "requires a C++17 compiler, set CXX to its path (default: g++) and run `just test-cpp`"]
+    fn websocket_mock() {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../typescript_api");
+        let server =
+            wire_weaver_mock::MockServer::from_crate(&crate_path, Some("Dashboard".into()))
+                .unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || runtime.block_on(wire_weaver_mock::serve_ws(listener, server)));
+
+        compile_and_run("ww_cpp_websocket", &ws_program(port));
+    }
+}
@@ -0,0 +1,248 @@
+//! Values and messages serialized in Rust, that the generated TypeScript and C++ clients of typescript_api are
+//! checked against.
+
+use typescript_api::{CommandOwned, ConfigErrorOwned, Limits, Mode, StatusOwned, Telemetry};
+use wire_weaver::prelude::*;
+use ww_client_server::{
+    Error, ErrorKind, Event, EventKind, PathKind, Request, RequestKind, StreamSidebandCommand,
+    StreamSidebandEvent,
+};
+
+#[derive_shrink_wrap]
+struct StopArgs {
+    brake: bool,
+}
+
+#[derive_shrink_wrap]
+struct ConfigureArgs {
+    channel: u8,
+    limits: Limits,
+}
+
+fn ww_bytes<T: SerializeShrinkWrap>(value: &T) -> Vec<u8> {
+    let mut buf = [0u8; 1024];
+    value.to_ww_bytes(&mut buf).unwrap().to_vec()
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn request(seq: u16, path: &[u32], kind: RequestKind) -> String {
+    let path: Vec<UNib32> = path.iter().map(|id| UNib32(*id)).collect();
+    hex(&ww_bytes(&Request {
+        seq,
+        path_kind: PathKind::absolute(&path),
+        kind,
+    }))
+}
+
+fn event(seq: u16, result: Result<EventKind, Error>) -> String {
+    hex(&ww_bytes(&Event { seq, result }))
+}
+
+fn status() -> StatusOwned {
+    StatusOwned {
+        mode: Mode::Run { rpm: 3 },
+        enabled: true,
+        temperature: Some(21.5),
+        name: "left".into(),
+        counters: vec![1, 70_000],
+        raw: vec![0xde, 0xad],
+        position: (-5, 9),
+        calibration: [-1, 0, 300],
+        fault: Some("overheat".into()),
+        uptime: u64::MAX,
+        offset: -2,
+        steps: UNib32(1000),
+        phase: Nibble::new(9).unwrap(),
+    }
+}
+
+/// (type name, bytes serialized with BufWriter), each client test provides value literals in the same order.
+pub fn value_cases() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("Mode", ww_bytes(&Mode::Idle)),
+        ("Mode", ww_bytes(&Mode::Run { rpm: 1500 })),
+        (
+            "Mode",
+            ww_bytes(&Mode::Hold(U7::new(100).unwrap(), I5::new(-7).unwrap())),
+        ),
+        ("Status", ww_bytes(&status())),
+        (
+            "Status",
+            ww_bytes(&StatusOwned {
+                mode: Mode::Idle,
+                enabled: false,
+                temperature: None,
+                name: "Ω".into(),
+                counters: vec![],
+                raw: vec![],
+                position: (i32::MAX, 0),
+                calibration: [0; 3],
+                fault: None,
+                uptime: 0,
+                offset: i64::MIN,
+                steps: UNib32(0),
+                phase: Nibble::new(0).unwrap(),
+            }),
+        ),
+        (
+            "Limits",
+            ww_bytes(&Limits {
+                current: 10..2000,
+                voltage: -3.25,
+                torque: -128,
+            }),
+        ),
+        (
+            "ConfigError",
+            ww_bytes(&ConfigErrorOwned::OutOfRange {
+                reason: "too high".into(),
+            }),
+        ),
+        (
+            "Telemetry",
+            ww_bytes(&Telemetry {
+                current: -100_000,
+                healthy: Err(513),
+            }),
+        ),
+        ("Command", ww_bytes(&CommandOwned::Label("go".into()))),
+        (
+            "Command",
+            ww_bytes(&CommandOwned::Sequence(vec![
+                Mode::Idle,
+                Mode::Hold(U7::new(1).unwrap(), I5::new(15).unwrap()),
+            ])),
+        ),
+    ]
+}
+
+/// Hex encoded requests expected from a generated client and events fed back to it, in the order they are exchanged.
+pub struct ClientExchange {
+    /// `motor(1).stop(true)`
+    pub stop_request: String,
+    /// `Some(300)`
+    pub stop_return: String,
+    /// `configure(2, Limits { current: 1..5, voltage: 12.0, torque: 3 })`
+    pub configure_request: String,
+    /// `Err(ConfigError::BadChannel)`
+    pub configure_return: String,
+    /// `read_speed()`
+    pub read_request: String,
+    /// `BadPath` with error sequence 7
+    pub read_error: String,
+    /// `open_stream_telemetry()`
+    pub telemetry_open_request: String,
+    pub telemetry_opened: String,
+    /// `Telemetry { current: 1, healthy: Ok(2) }`
+    pub telemetry_data: String,
+    /// Opened event for `open_stream_log()`, sent as the fifth request
+    pub log_opened: String,
+    /// `[1, 2, 3]`
+    pub log_data: String,
+    /// `write_commands(Command::Label("go"))`
+    pub commands_write: String,
+}
+
+pub fn client_exchange() -> ClientExchange {
+    let limits = Limits {
+        current: 1..5,
+        voltage: 12.0,
+        torque: 3,
+    };
+    let stop_args = ww_bytes(&StopArgs { brake: true });
+    let configure_args = ww_bytes(&ConfigureArgs { channel: 2, limits });
+    let stop_ret = ww_bytes(&Some(300u16));
+    let configure_ret = ww_bytes(&Err::<u32, _>(ConfigErrorOwned::BadChannel));
+    let telemetry = ww_bytes(&Telemetry {
+        current: 1,
+        healthy: Ok(2),
+    });
+    let command = ww_bytes(&CommandOwned::Label("go".into()));
+    let log = [1u8, 2, 3];
+    let telemetry_path = [UNib32(5)];
+    let log_path = [UNib32(6)];
+
+    ClientExchange {
+        stop_request: request(
+            1,
+            &[8, 1, 1],
+            RequestKind::Call {
+                args: RefVec::new_bytes(&stop_args),
+            },
+        ),
+        stop_return: event(
+            1,
+            Ok(EventKind::ReturnValue {
+                data: RefVec::new_bytes(&stop_ret),
+            }),
+        ),
+        configure_request: request(
+            2,
+            &[2],
+            RequestKind::Call {
+                args: RefVec::new_bytes(&configure_args),
+            },
+        ),
+        configure_return: event(
+            2,
+            Ok(EventKind::ReturnValue {
+                data: RefVec::new_bytes(&configure_ret),
+            }),
+        ),
+        read_request: request(3, &[3], RequestKind::Read),
+        read_error: event(3, Err(Error::new(7, ErrorKind::BadPath))),
+        telemetry_open_request: request(
+            4,
+            &[5],
+            RequestKind::StreamSideband {
+                sideband_cmd: StreamSidebandCommand::Open,
+            },
+        ),
+        telemetry_opened: event(
+            4,
+            Ok(EventKind::StreamSideband {
+                path: RefVec::Slice {
+                    slice: &telemetry_path,
+                },
+                sideband_event: StreamSidebandEvent::Opened,
+            }),
+        ),
+        telemetry_data: event(
+            0,
+            Ok(EventKind::StreamData {
+                path: RefVec::Slice {
+                    slice: &telemetry_path,
+                },
+                data: RefVec::new_bytes(&telemetry),
+            }),
+        ),
+        log_opened: event(
+            5,
+            Ok(EventKind::StreamSideband {
+                path: RefVec::Slice { slice: &log_path },
+                sideband_event: StreamSidebandEvent::Opened,
+            }),
+        ),
+        log_data: event(
+            0,
+            Ok(EventKind::StreamData {
+                path: RefVec::Slice { slice: &log_path },
+                data: RefVec::new_bytes(&log),
+            }),
+        ),
+        commands_write: request(
+            0,
+            &[7],
+            RequestKind::Write {
+                data: RefVec::new_bytes(&command),
+            },
+        ),
+    }
+}
@@ -0,0 +1,37 @@
+use wire_weaver::prelude::*;
+
+pub mod next;
+
+#[ww_trait]
+trait Sensor {
+    fn configure(settings: Settings<'i>);
+    #[deprecated(since = "0.2.0", note = "use configure instead")]
+    fn set_gain(gain: u8);
+    #[deprecated]
+    property!(rw mode: Mode);
+    #[deprecated = "samples are now sent with timestamps"]
+    stream!(raw_samples: u16);
+    fn calibrate();
+}
+
+#[derive_shrink_wrap]
+#[owned = "std"]
+pub struct Settings<'i> {
+    pub label: &'i str,
+    #[deprecated(since = "0.2.0", note = "gain is set per channel")]
+    pub gain: u8,
+    pub channel: u8,
+    #[flag]
+    #[deprecated = "offset is calibrated on the device"]
+    offset: bool,
+    pub offset: Option<i8>,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(u4)]
+#[derive(Copy, Clone)]
+pub enum Mode {
+    Continuous,
+    #[deprecated = "devices ignore it"]
+    Triggered,
+}
@@ -0,0 +1,25 @@
+//! Next major version of the Sensor API, with deprecated resources, fields and variants removed.
+
+use wire_weaver::prelude::*;
+
+#[ww_trait]
+trait SensorNext {
+    fn configure(settings: Settings<'i>);
+    reserved!(1..=1);
+    #[deprecated]
+    property!(rw mode: Mode);
+    reserved!(3..=4);
+}
+
+#[derive_shrink_wrap]
+#[owned = "std"]
+pub struct Settings<'i> {
+    pub label: &'i str,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(u4)]
+#[derive(Copy, Clone)]
+pub enum Mode {
+    Continuous,
+}
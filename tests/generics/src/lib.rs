@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use generics_api::{Measurement, Reply, Response};
+    use std::sync::{Arc, RwLock};
+    use std::time::Duration;
+    use tests_common::DummyTx;
+    use tokio::sync::mpsc;
+    use wire_weaver_client_common::ww_version::{FullVersionOwned, VersionOwned};
+    use wire_weaver_client_common::{CommandSender, DeviceFilter, OnError};
+
+    mod no_std_sync_server {
+        use super::*;
+        use tests_common::TestProcessEvents;
+        use wire_weaver::MessageSink;
+        use wire_weaver::prelude::*;
+
+        pub struct NoStdSyncServer {
+            pub offset: Arc<RwLock<Option<Measurement<i16>>>>,
+        }
+
+        impl NoStdSyncServer {
+            fn latest_temperature(&mut self, _msg_tx: &mut impl MessageSink) -> Measurement<f32> {
+                Measurement {
+                    value: 21.5,
+                    timestamp: 100,
+                }
+            }
+
+            fn set_offset(&mut self, _msg_tx: &mut impl MessageSink, offset: Measurement<i16>) {
+                *self.offset.write().unwrap() = Some(offset);
+            }
+
+            fn describe(
+                &mut self,
+                _msg_tx: &mut impl MessageSink,
+                channel: u8,
+            ) -> Response<&'_ str> {
+                Response {
+                    request_id: channel,
+                    payload: Some("thermocouple"),
+                }
+            }
+
+            fn last_reply(&mut self, _msg_tx: &mut impl MessageSink) -> Reply<Measurement<f32>> {
+                Reply::Value(Measurement {
+                    value: -1.0,
+                    timestamp: 7,
+                })
+            }
+        }
+
+        mod api_impl {
+            wire_weaver::ww_codegen!(
+                generics_api :: Telemetry for super::NoStdSyncServer,
+                server = true, no_alloc = true, use_async = false,
+                method_model = "_=immediate",
+                property_model = "_=get_set",
+                introspect = false,
+            );
+        }
+
+        impl TestProcessEvents for NoStdSyncServer {
+            fn process_request_bytes<'a>(
+                &mut self,
+                bytes: &[u8],
+                scratch_args: &'a mut [u8],
+                scratch_event: &'a mut [u8],
+                scratch_err: &'a mut [u8],
+                msg_tx: &mut impl MessageSink,
+            ) -> Result<&'a [u8], ShrinkWrapError> {
+                self.process_request_bytes(bytes, scratch_args, scratch_event, scratch_err, msg_tx)
+            }
+        }
+    }
+
+    mod std_async_client {
+        use wire_weaver_client_common::CommandSender;
+
+        pub struct StdAsyncClient {
+            pub cmd_tx: CommandSender,
+        }
+
+        mod api_client {
+            wire_weaver::ww_codegen!(
+                generics_api :: Telemetry for super::StdAsyncClient,
+                client = "full_client",
+            );
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn std_async_client_driving_no_std_sync_server() {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let offset = Arc::new(RwLock::new(None));
+        let server = no_std_sync_server::NoStdSyncServer {
+            offset: offset.clone(),
+        };
+        tokio::spawn(async move {
+            tests_common::test_event_loop(transport_cmd_rx, server, DummyTx {}).await;
+        });
+
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                FullVersionOwned::new("test".into(), VersionOwned::new(0, 1, 0)),
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+        let mut client = std_async_client::StdAsyncClient { cmd_tx };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let temperature = client.latest_temperature().call().await.unwrap();
+        assert_eq!(
+            temperature,
+            Measurement {
+                value: 21.5,
+                timestamp: 100
+            }
+        );
+
+        let new_offset = Measurement {
+            value: -12i16,
+            timestamp: 5,
+        };
+        client.set_offset(new_offset).call().await.unwrap();
+        assert_eq!(*offset.read().unwrap(), Some(new_offset));
+
+        let description = client.describe(3).call().await.unwrap();
+        assert_eq!(
+            description,
+            Response {
+                request_id: 3,
+                payload: Some("thermocouple".into())
+            }
+        );
+
+        let reply = client.last_reply().call().await.unwrap();
+        assert_eq!(
+            reply,
+            Reply::Value(Measurement {
+                value: -1.0,
+                timestamp: 7
+            })
+        );
+    }
+}
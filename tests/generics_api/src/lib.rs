@@ -0,0 +1,37 @@
+use wire_weaver::prelude::*;
+
+#[ww_trait]
+trait Telemetry {
+    fn latest_temperature() -> Measurement<f32>;
+    fn set_offset(offset: Measurement<i16>);
+    fn describe(channel: u8) -> Response<&'i str>;
+    fn last_reply() -> Reply<Measurement<f32>>;
+}
+
+pub trait Sample: Copy {}
+impl Sample for f32 {}
+impl Sample for i16 {}
+
+#[derive_shrink_wrap]
+#[sized]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Measurement<T: Sample> {
+    pub value: T,
+    pub timestamp: u32,
+}
+
+#[derive_shrink_wrap]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response<T> {
+    pub request_id: u8,
+    pub payload: Option<T>,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(u4)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reply<T, E = u8> {
+    Ack,
+    Value(T),
+    Failed { code: E },
+}
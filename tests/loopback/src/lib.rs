@@ -0,0 +1,957 @@
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+    use std::sync::{Arc, RwLock};
+    use std::time::{Duration, Instant};
+    use tokio::sync::{mpsc, oneshot};
+    use wire_weaver::prelude::*;
+    use wire_weaver_client_common::loopback::{Faults, Loopback, LoopbackServer, LoopbackSink};
+    use wire_weaver_client_common::metrics::Operation;
+    use wire_weaver_client_common::restore::ConnectionEvent;
+    use wire_weaver_client_common::ww_version::{FullVersionOwned, VersionOwned};
+    use wire_weaver_client_common::{
+        Command, CommandSender, DeviceFilter, Error, OnError, Stream, TypedStreamEvent,
+    };
+
+    #[derive(Default)]
+    struct SharedTestData {
+        one_plain_arg: u8,
+        plain_sink_rx: Vec<u8>,
+        cancelled_plain_return: Option<u16>,
+    }
+
+    mod methods_server {
+        use super::*;
+        use methods_api::UserDefined;
+
+        /// `plain_return` is deferred, it is answered on the next `one_plain_arg` call, unless cancelled.
+        pub struct MethodsServer {
+            pub data: Arc<RwLock<SharedTestData>>,
+            pub sink: LoopbackSink,
+            pub pending_plain_return: Option<u16>,
+        }
+
+        impl MethodsServer {
+            async fn no_args(&mut self, _msg_tx: &mut impl MessageSink) {}
+
+            async fn one_plain_arg(&mut self, _msg_tx: &mut impl MessageSink, value: u8) {
+                self.data.write().unwrap().one_plain_arg = value;
+                if let Some(seq) = self.pending_plain_return.take() {
+                    let mut s1 = [0u8; 64];
+                    let mut s2 = [0u8; 64];
+                    let event =
+                        Self::plain_return_ser_return_event(&mut s1, &mut s2, seq, value).unwrap();
+                    self.sink.send(event).await.unwrap();
+                }
+            }
+
+            async fn plain_return(
+                &mut self,
+                _msg_tx: &mut impl MessageSink,
+                seq: u16,
+            ) -> Option<u8> {
+                self.pending_plain_return = Some(seq);
+                None
+            }
+
+            async fn plain_return_cancel(&mut self, _msg_tx: &mut impl MessageSink, seq: u16) {
+                if self.pending_plain_return == Some(seq) {
+                    self.pending_plain_return = None;
+                }
+                self.data.write().unwrap().cancelled_plain_return = Some(seq);
+            }
+
+            async fn user_arg(&mut self, _msg_tx: &mut impl MessageSink, u: UserDefined<'_>) {
+                assert_eq!(u.a, 123);
+            }
+
+            async fn user_defined_return(
+                &mut self,
+                _msg_tx: &mut impl MessageSink,
+            ) -> UserDefined<'_> {
+                UserDefined {
+                    a: 37,
+                    b: RefVec::new_bytes(&[1, 2, 3]),
+                }
+            }
+
+            async fn added_later(&mut self, _msg_tx: &mut impl MessageSink) {}
+        }
+
+        mod api_impl {
+            wire_weaver::ww_codegen!(
+                methods_api :: Methods for super::MethodsServer,
+                server = true, no_alloc = true, use_async = true,
+                method_model = "plain_return=deferred+cancellable, _=immediate",
+                property_model = "_=get_set",
+                introspect = false,
+            );
+        }
+
+        impl LoopbackServer for MethodsServer {
+            async fn process_request_bytes<'a>(
+                &mut self,
+                bytes: &[u8],
+                scratch_args: &'a mut [u8],
+                scratch_event: &'a mut [u8],
+                scratch_err: &'a mut [u8],
+                msg_tx: &mut LoopbackSink,
+            ) -> Result<&'a [u8], ShrinkWrapError> {
+                self.process_request_bytes(bytes, scratch_args, scratch_event, scratch_err, msg_tx)
+                    .await
+            }
+        }
+    }
+
+    mod streams_server {
+        use super::*;
+        use ww_client_server::{StreamSidebandCommand, StreamSidebandEvent};
+
+        /// Sends one value to `plain_stream` when it is opened.
+        pub struct StreamsServer {
+            pub data: Arc<RwLock<SharedTestData>>,
+        }
+
+        impl StreamsServer {
+            async fn plain_stream_sideband(
+                &mut self,
+                msg_tx: &mut impl MessageSink,
+                cmd: StreamSidebandCommand,
+            ) -> Option<StreamSidebandEvent> {
+                if matches!(cmd, StreamSidebandCommand::Open) {
+                    let mut s1 = [0u8; 64];
+                    let mut s2 = [0u8; 64];
+                    let update = api_impl::stream_data_ser()
+                        .plain_stream(&0xAA, &mut s1, &mut s2)
+                        .unwrap();
+                    msg_tx.send(update).await.unwrap();
+                }
+                None
+            }
+
+            async fn plain_sink_sideband(
+                &mut self,
+                _msg_tx: &mut impl MessageSink,
+                _cmd: StreamSidebandCommand,
+            ) -> Option<StreamSidebandEvent> {
+                None
+            }
+
+            async fn plain_sink_write(&mut self, value: u8) {
+                self.data.write().unwrap().plain_sink_rx.push(value);
+            }
+
+            async fn vec_stream_sideband(
+                &mut self,
+                _msg_tx: &mut impl MessageSink,
+                _cmd: StreamSidebandCommand,
+            ) -> Option<StreamSidebandEvent> {
+                None
+            }
+
+            async fn array_of_streams_sideband(
+                &mut self,
+                _msg_tx: &mut impl MessageSink,
+                _idx: [UNib32; 1],
+                _cmd: StreamSidebandCommand,
+            ) -> Option<StreamSidebandEvent> {
+                None
+            }
+
+            fn valid_indices_root_array_of_streams(&self) -> ValidIndices<'_> {
+                ValidIndices::Range(0..255)
+            }
+
+            async fn finish(&mut self, _msg_tx: &mut impl MessageSink) {}
+        }
+
+        mod api_impl {
+            use super::StreamsServer;
+
+            wire_weaver::ww_codegen!(
+                streams_api :: Streams for StreamsServer,
+                server = true, no_alloc = true, use_async = true,
+                method_model = "_=immediate",
+                property_model = "_=get_set",
+            );
+        }
+
+        impl LoopbackServer for StreamsServer {
+            async fn process_request_bytes<'a>(
+                &mut self,
+                bytes: &[u8],
+                scratch_args: &'a mut [u8],
+                scratch_event: &'a mut [u8],
+                scratch_err: &'a mut [u8],
+                msg_tx: &mut LoopbackSink,
+            ) -> Result<&'a [u8], ShrinkWrapError> {
+                self.process_request_bytes(bytes, scratch_args, scratch_event, scratch_err, msg_tx)
+                    .await
+            }
+        }
+    }
+
+    /// Answers Open of any trait-addressed stream with Subscribed carrying its absolute path, like a device that
+    /// implements a trait at /3 would, and sends one stream update on every call.
+    mod trait_stream_server {
+        use super::*;
+        use ww_client_server::{Event, EventKind, Request, RequestKind, StreamSidebandCommand};
+
+        pub const ABSOLUTE_PATH: [UNib32; 2] = [UNib32(3), UNib32(5)];
+
+        pub struct TraitStreamServer;
+
+        impl LoopbackServer for TraitStreamServer {
+            async fn process_request_bytes<'a>(
+                &mut self,
+                bytes: &[u8],
+                scratch_args: &'a mut [u8],
+                scratch_event: &'a mut [u8],
+                _scratch_err: &'a mut [u8],
+                msg_tx: &mut LoopbackSink,
+            ) -> Result<&'a [u8], ShrinkWrapError> {
+                let path = RefVec::Slice {
+                    slice: &ABSOLUTE_PATH,
+                };
+                let request = Request::from_ww_bytes(bytes)?;
+                let kind = match request.kind {
+                    RequestKind::StreamSideband {
+                        sideband_cmd: StreamSidebandCommand::Open,
+                    } => EventKind::Subscribed { path },
+                    RequestKind::Call { .. } => {
+                        let update = Event {
+                            seq: 0,
+                            result: Ok(EventKind::StreamData {
+                                path: path.clone(),
+                                data: RefVec::new_bytes(&[0xDD]),
+                            }),
+                        };
+                        _ = msg_tx.send(update.to_ww_bytes(scratch_args)?).await;
+                        EventKind::ReturnValue {
+                            data: RefVec::new_bytes(&[]),
+                        }
+                    }
+                    _ => return Ok(&[]),
+                };
+                Event {
+                    seq: request.seq,
+                    result: Ok(kind),
+                }
+                .to_ww_bytes(scratch_event)
+            }
+        }
+    }
+
+    mod methods_client {
+        use wire_weaver_client_common::CommandSender;
+
+        pub struct StdAsyncClient {
+            pub cmd_tx: CommandSender,
+        }
+
+        mod api_client {
+            wire_weaver::ww_codegen!(
+                methods_api :: Methods for super::StdAsyncClient,
+                client = "full_client",
+            );
+        }
+    }
+
+    mod streams_client {
+        use wire_weaver_client_common::CommandSender;
+
+        pub struct StdAsyncClient {
+            pub cmd_tx: CommandSender,
+        }
+
+        mod api_client {
+            wire_weaver::ww_codegen!(
+                streams_api :: Streams for super::StdAsyncClient,
+                client = "full_client",
+            );
+        }
+    }
+
+    fn test_version() -> FullVersionOwned {
+        FullVersionOwned::new("test".into(), VersionOwned::new(0, 1, 0))
+    }
+
+    async fn connect(transport_cmd_tx: mpsc::UnboundedSender<Command>) -> CommandSender {
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                test_version(),
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+        cmd_tx
+    }
+
+    async fn methods_over(
+        loopback: Loopback,
+    ) -> (methods_client::StdAsyncClient, Arc<RwLock<SharedTestData>>) {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let data = Arc::new(RwLock::new(SharedTestData::default()));
+        let server = methods_server::MethodsServer {
+            data: data.clone(),
+            sink: loopback.sink(),
+            pending_plain_return: None,
+        };
+        tokio::spawn(loopback.run(transport_cmd_rx, server));
+        let cmd_tx = connect(transport_cmd_tx).await;
+        (methods_client::StdAsyncClient { cmd_tx }, data)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn immediate_and_deferred_methods() {
+        let (mut client, data) = methods_over(Loopback::default()).await;
+
+        client.no_args().call().await.unwrap();
+        client.one_plain_arg(0xCC).call().await.unwrap();
+        assert_eq!(data.read().unwrap().one_plain_arg, 0xCC);
+
+        // deferred return is sent through a sink, when the next one_plain_arg call arrives
+        let other_client = methods_client::StdAsyncClient {
+            cmd_tx: client.cmd_tx.clone(),
+        };
+        let deferred = tokio::spawn(async move { other_client.plain_return().call().await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        client.one_plain_arg(0x55).call().await.unwrap();
+        assert_eq!(deferred.await.unwrap().unwrap(), 0x55);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn dropped_deferred_call_is_cancelled_on_server() {
+        let (mut client, data) = methods_over(Loopback::default()).await;
+
+        let pending = client
+            .plain_return()
+            .notify_server_on_cancel()
+            .call_pending()
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let seq = pending.seq();
+        assert_ne!(seq, 0);
+        drop(pending);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(data.read().unwrap().cancelled_plain_return, Some(seq));
+
+        // cancelled call is not answered anymore
+        client.one_plain_arg(0x11).call().await.unwrap();
+        let pending = client.plain_return().call_pending().await.unwrap();
+        pending.cancel().unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(data.read().unwrap().cancelled_plain_return, Some(seq));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn in_flight_window_applies_backpressure() {
+        let (mut client, _data) = methods_over(Loopback::default()).await;
+        client.cmd_tx.set_max_in_flight(NonZeroUsize::MIN);
+
+        let pending = client.plain_return().call_pending().await.unwrap();
+        let blocked =
+            tokio::time::timeout(Duration::from_millis(50), client.no_args().call()).await;
+        assert!(blocked.is_err(), "second request must wait for a free slot");
+
+        // cancelling frees the slot
+        drop(pending);
+        tokio::time::timeout(Duration::from_millis(500), client.no_args().call())
+            .await
+            .expect("slot freed")
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn metrics_are_collected_per_resource() {
+        let (mut client, _data) = methods_over(Loopback::default()).await;
+        client.cmd_tx.set_client_introspect_bytes(
+            methods_client::StdAsyncClient::introspect_bytes(),
+            methods_client::StdAsyncClient::api_signature(),
+        );
+
+        client.no_args().call().await.unwrap();
+        client.no_args().call().await.unwrap();
+        // deferred and never answered
+        let r = client
+            .plain_return()
+            .with_timeout(Duration::from_millis(20))
+            .call()
+            .await;
+        assert!(matches!(r, Err(Error::Timeout)));
+
+        let metrics = client.cmd_tx.metrics();
+        let no_args = metrics
+            .requests
+            .iter()
+            .find(|m| m.name == "no_args")
+            .expect("no_args metrics");
+        assert_eq!(no_args.operation, Operation::Call);
+        assert_eq!(no_args.count, 2);
+        assert_eq!(no_args.latency.count, 2);
+        assert!(no_args.errors.is_empty());
+        let plain_return = metrics
+            .requests
+            .iter()
+            .find(|m| m.name == "plain_return")
+            .expect("plain_return metrics");
+        assert_eq!(plain_return.errors.get("Timeout"), Some(&1));
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains("ww_requests_total{resource=\"no_args\",op=\"call\"} 2\n"));
+        assert!(text.contains(
+            "ww_request_errors_total{resource=\"plain_return\",op=\"call\",kind=\"Timeout\"} 1\n"
+        ));
+        assert!(text.contains(
+            "ww_request_duration_seconds_bucket{resource=\"no_args\",op=\"call\",le=\"+Inf\"} 2\n"
+        ));
+    }
+
+    async fn wait_until(mut condition: impl FnMut() -> bool) {
+        let started = Instant::now();
+        while !condition() {
+            assert!(started.elapsed() < Duration::from_secs(1), "timed out");
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn metrics_are_recorded_on_reception_by_full_path() {
+        use wire_weaver_client_common::metrics::ResourcePath;
+        use wire_weaver_client_common::ww_version::{FullVersion, Version};
+        use ww_client_server::PathKind;
+
+        let (client, _data) = methods_over(Loopback::default()).await;
+
+        // response is counted when received, before it is awaited
+        let pending = client.no_args().call_pending().await.unwrap();
+        wait_until(|| !client.cmd_tx.metrics().requests.is_empty()).await;
+        pending.await.unwrap();
+
+        // same path from trait is counted separately for trait clients attached at different paths
+        const TRAIT: FullVersion = FullVersion::new("some_trait", Version::new(0, 1, 0));
+        for base in [7, 8] {
+            let mut cmd_tx = client.cmd_tx.clone();
+            cmd_tx.set_base_path(vec![UNib32(base)]);
+            let path = [UNib32(0)];
+            let r = cmd_tx
+                .prepare_call::<()>(PathKind::global(TRAIT, None, &path), None, Ok(vec![]))
+                .with_timeout(Duration::from_millis(50))
+                .call()
+                .await;
+            assert!(r.is_err());
+        }
+        let metrics = client.cmd_tx.metrics();
+        for (base, name) in [(7, "#7/#0"), (8, "#8/#0")] {
+            let m = metrics
+                .requests
+                .iter()
+                .find(|m| m.path == ResourcePath::Absolute(vec![base, 0]))
+                .expect("trait client metrics");
+            assert_eq!(m.name, name);
+            assert_eq!(m.count, 1);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn streams_and_sinks() {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let data = Arc::new(RwLock::new(SharedTestData::default()));
+        let server = streams_server::StreamsServer { data: data.clone() };
+        tokio::spawn(wire_weaver_client_common::loopback::loopback_worker(
+            transport_cmd_rx,
+            server,
+        ));
+        let cmd_tx = connect(transport_cmd_tx).await;
+        let client = streams_client::StdAsyncClient { cmd_tx };
+
+        let mut rx = client.plain_stream().unwrap();
+        rx.open().unwrap();
+        // stream data is counted when received, before it is consumed
+        wait_until(|| {
+            client
+                .cmd_tx
+                .metrics()
+                .streams
+                .first()
+                .is_some_and(|m| m.events == 1)
+        })
+        .await;
+        let value = loop {
+            if let TypedStreamEvent::Data(value) = rx.recv_any().await.unwrap() {
+                break value;
+            }
+        };
+        assert_eq!(value, 0xAA);
+
+        let mut sink = client.plain_sink().unwrap();
+        sink.send(5).unwrap();
+        sink.send(6).unwrap();
+        client.finish().call().await.unwrap();
+        assert_eq!(data.read().unwrap().plain_sink_rx, vec![5, 6]);
+
+        let metrics = client.cmd_tx.metrics();
+        assert_eq!(metrics.streams.len(), 1);
+        assert_eq!(metrics.streams[0].events, 1);
+        assert_eq!(metrics.streams[0].bytes, 1);
+        assert_eq!(metrics.streams[0].dropped, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn incompatible_version_is_rejected() {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let server = streams_server::StreamsServer {
+            data: Default::default(),
+        };
+        let loopback = Loopback::default().with_user_api_version(FullVersionOwned::new(
+            "other".into(),
+            VersionOwned::new(0, 1, 0),
+        ));
+        tokio::spawn(loopback.run(transport_cmd_rx, server));
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        let r = cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                test_version(),
+                OnError::ExitImmediately,
+            )
+            .await;
+        assert!(matches!(r, Err(Error::IncompatibleDeviceProtocol)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn items_newer_than_device_are_guarded() {
+        for (device_patch, is_available) in [(0, false), (1, true)] {
+            let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+            let loopback = Loopback::default().with_user_api_version(FullVersionOwned::new(
+                "methods_api".into(),
+                VersionOwned::new(0, 1, device_patch),
+            ));
+            let server = methods_server::MethodsServer {
+                data: Default::default(),
+                sink: loopback.sink(),
+                pending_plain_return: None,
+            };
+            tokio::spawn(loopback.run(transport_cmd_rx, server));
+            let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+            cmd_tx
+                .connect(
+                    DeviceFilter::vhrd_usb_can(),
+                    methods_api::METHODS_FULL_GID.make_owned(),
+                    OnError::ExitImmediately,
+                )
+                .await
+                .expect("connect");
+            let client = methods_client::StdAsyncClient { cmd_tx };
+
+            assert_eq!(client.added_later_is_available(), is_available);
+            let r = client.added_later().call().await;
+            if is_available {
+                r.unwrap();
+            } else {
+                assert!(matches!(r, Err(Error::OlderProtocol(..))));
+            }
+            // items without #[since] are always available
+            client.no_args().call().await.unwrap();
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn disconnect_fails_further_requests() {
+        let (client, _data) = methods_over(Loopback::default()).await;
+        client.no_args().call().await.unwrap();
+
+        let (disconnected_tx, disconnected_rx) = oneshot::channel();
+        client
+            .cmd_tx
+            .send(Command::DisconnectKeepStreams {
+                disconnected_tx: Some(disconnected_tx),
+            })
+            .unwrap();
+        disconnected_rx.await.unwrap();
+        assert!(matches!(
+            client.no_args().call().await,
+            Err(Error::Disconnected)
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn opened_streams_are_restored_on_reconnect() {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let server = streams_server::StreamsServer {
+            data: Default::default(),
+        };
+        tokio::spawn(wire_weaver_client_common::loopback::loopback_worker(
+            transport_cmd_rx,
+            server,
+        ));
+        let mut cmd_tx = connect(transport_cmd_tx).await;
+        let mut events = cmd_tx.connection_events().unwrap();
+        assert!(matches!(
+            events.recv().await,
+            Some(ConnectionEvent::Connected(_))
+        ));
+
+        let client = streams_client::StdAsyncClient {
+            cmd_tx: cmd_tx.clone(),
+        };
+        let mut rx = client.plain_stream().unwrap();
+        rx.open().unwrap();
+        let recv_data = async |rx: &mut Stream<u8>| loop {
+            if let TypedStreamEvent::Data(value) = rx.recv_any().await.unwrap() {
+                break value;
+            }
+        };
+        assert_eq!(recv_data(&mut rx).await, 0xAA);
+
+        let (disconnected_tx, disconnected_rx) = oneshot::channel();
+        cmd_tx
+            .send(Command::DisconnectKeepStreams {
+                disconnected_tx: Some(disconnected_tx),
+            })
+            .unwrap();
+        disconnected_rx.await.unwrap();
+        assert!(matches!(
+            events.recv().await,
+            Some(ConnectionEvent::Disconnected)
+        ));
+
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                test_version(),
+                OnError::ExitImmediately,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            events.recv().await,
+            Some(ConnectionEvent::Reconnected {
+                restored_requests: 1,
+                ..
+            })
+        ));
+        // server sends a stream update only in response to Open
+        assert_eq!(recv_data(&mut rx).await, 0xAA);
+    }
+
+    /// Filter unique to a test and a process, so that daemons of tests running in parallel do not collide.
+    #[cfg(unix)]
+    fn daemon_filter(vid: u16) -> DeviceFilter {
+        DeviceFilter::usb_vid_pid(vid, std::process::id() as u16).with_allow_ipc(true)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn shared_through_daemon() {
+        use wire_weaver_client_common::ipc;
+
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let data = Arc::new(RwLock::new(SharedTestData::default()));
+        let server = streams_server::StreamsServer { data: data.clone() };
+        tokio::spawn(wire_weaver_client_common::loopback::loopback_worker(
+            transport_cmd_rx,
+            server,
+        ));
+        let device = connect(transport_cmd_tx).await;
+        let filter = daemon_filter(0xd001);
+        let listener = ipc::bind(&filter).await.unwrap();
+        tokio::spawn(ipc::serve(listener, device));
+
+        let mut clients = vec![];
+        for _ in 0..2 {
+            let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+            tokio::spawn(ipc::ipc_worker(transport_cmd_rx));
+            let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+            cmd_tx
+                // daemon might not know about a device yet, right after it was started
+                .connect(filter.clone(), test_version(), OnError::retry_for_secs(2))
+                .await
+                .unwrap();
+            clients.push(streams_client::StdAsyncClient { cmd_tx });
+        }
+
+        // stream data is fanned out to all the clients
+        let mut rx_b = clients[1].plain_stream().unwrap();
+        let mut rx_a = clients[0].plain_stream().unwrap();
+        rx_a.open().unwrap();
+        for rx in [&mut rx_a, &mut rx_b] {
+            let value = loop {
+                if let TypedStreamEvent::Data(value) = rx.recv_any().await.unwrap() {
+                    break value;
+                }
+            };
+            assert_eq!(value, 0xAA);
+        }
+
+        // responses are delivered to the right client, even if seq numbers are the same
+        let mut sink_a = clients[0].plain_sink().unwrap();
+        let mut sink_b = clients[1].plain_sink().unwrap();
+        sink_a.send(5).unwrap();
+        clients[0].finish().call().await.unwrap();
+        sink_b.send(6).unwrap();
+        clients[1].finish().call().await.unwrap();
+        assert_eq!(data.read().unwrap().plain_sink_rx, vec![5, 6]);
+        _ = std::fs::remove_file(ipc::socket_path(&filter));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn daemon_applies_client_timeout() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::UnixStream;
+        use wire_weaver_client_common::ipc::{self, IpcFrame};
+        use ww_client_server::{Event, PathKind, Request, RequestKind};
+
+        async fn write_frame(stream: &mut UnixStream, frame: &IpcFrame) {
+            let mut scratch = [0u8; 512];
+            let bytes = frame.to_ww_bytes(&mut scratch).unwrap();
+            stream
+                .write_all(&(bytes.len() as u32).to_le_bytes())
+                .await
+                .unwrap();
+            stream.write_all(bytes).await.unwrap();
+        }
+
+        async fn read_frame(stream: &mut UnixStream) -> IpcFrame {
+            let mut len = [0u8; 4];
+            stream.read_exact(&mut len).await.unwrap();
+            let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+            stream.read_exact(&mut bytes).await.unwrap();
+            IpcFrame::from_ww_bytes(&bytes).unwrap()
+        }
+
+        let loopback = Loopback::default();
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let server = methods_server::MethodsServer {
+            data: Default::default(),
+            sink: loopback.sink(),
+            pending_plain_return: None,
+        };
+        tokio::spawn(loopback.run(transport_cmd_rx, server));
+        let device = connect(transport_cmd_tx).await;
+        let filter = daemon_filter(0xd003);
+        let listener = ipc::bind(&filter).await.unwrap();
+        tokio::spawn(ipc::serve(listener, device));
+
+        let mut stream = UnixStream::connect(ipc::socket_path(&filter))
+            .await
+            .unwrap();
+        write_frame(
+            &mut stream,
+            &IpcFrame::Hello {
+                client_version: test_version(),
+            },
+        )
+        .await;
+        assert!(matches!(
+            read_frame(&mut stream).await,
+            IpcFrame::Welcome {
+                device_info: Some(_)
+            }
+        ));
+
+        // plain_return is not answered, daemon gives up after the client timeout and reports it
+        let path = [UNib32(2)];
+        let request = Request {
+            seq: 1,
+            path_kind: PathKind::absolute(&path),
+            kind: RequestKind::Call {
+                args: RefVec::new_bytes(&[]),
+            },
+        };
+        let mut scratch = [0u8; 64];
+        let bytes = request.to_ww_bytes(&mut scratch).unwrap().to_vec();
+        let started = Instant::now();
+        write_frame(
+            &mut stream,
+            &IpcFrame::Request {
+                bytes,
+                timeout_ms: Some(50),
+            },
+        )
+        .await;
+        let IpcFrame::Event { bytes } =
+            tokio::time::timeout(Duration::from_secs(2), read_frame(&mut stream))
+                .await
+                .unwrap()
+        else {
+            panic!("expected an event");
+        };
+        assert!(started.elapsed() < Duration::from_secs(2));
+        let event = Event::from_ww_bytes(&bytes).unwrap();
+        assert_eq!(event.seq, 1);
+        let e = event.result.unwrap_err();
+        assert!(format!("{e:?}").contains("Timeout"), "{e:?}");
+        _ = std::fs::remove_file(ipc::socket_path(&filter));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn trait_stream_through_daemon() {
+        use trait_stream_server::{ABSOLUTE_PATH, TraitStreamServer};
+        use wire_weaver::ww_version::{FullVersion, Version};
+        use wire_weaver_client_common::ipc;
+        use ww_client_server::PathKind;
+
+        async fn request_update(cmd_tx: &CommandSender) {
+            cmd_tx
+                .prepare_call::<()>(PathKind::absolute(&ABSOLUTE_PATH), None, Ok(vec![]))
+                .call()
+                .await
+                .unwrap();
+        }
+
+        let loopback = Loopback::default();
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        tokio::spawn(loopback.run(transport_cmd_rx, TraitStreamServer));
+        let device = connect(transport_cmd_tx).await;
+        let filter = daemon_filter(0xd004);
+        let listener = ipc::bind(&filter).await.unwrap();
+        tokio::spawn(ipc::serve(listener, device));
+
+        let mut clients = vec![];
+        for _ in 0..2 {
+            let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+            tokio::spawn(ipc::ipc_worker(transport_cmd_rx));
+            let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+            cmd_tx
+                // daemon might not know about a device yet, right after it was started
+                .connect(filter.clone(), test_version(), OnError::retry_for_secs(2))
+                .await
+                .unwrap();
+            clients.push(cmd_tx);
+        }
+
+        // seq numbers of the clients and of the daemon diverge, so Subscribed is only matched if translated back
+        request_update(&clients[0]).await;
+        request_update(&clients[0]).await;
+
+        // both clients open the same trait-addressed stream, each is answered with its own seq number
+        let path_from_trait = [UNib32(5)];
+        let mut streams = vec![];
+        for cmd_tx in clients.iter().rev() {
+            let rx = cmd_tx
+                .prepare_stream::<u8>(
+                    PathKind::global(
+                        FullVersion::new("gpio_api", Version::new(1, 0, 0)),
+                        None,
+                        &path_from_trait,
+                    ),
+                    None,
+                )
+                .unwrap();
+            rx.open().unwrap();
+            streams.push(rx);
+        }
+        for rx in &mut streams {
+            // stream updates sent before Subscribed is delivered are not matched yet, ask for more until one is
+            let mut value = None;
+            for _ in 0..20 {
+                request_update(&clients[0]).await;
+                if let Ok(v) = tokio::time::timeout(Duration::from_millis(100), rx.recv()).await {
+                    value = Some(v.unwrap());
+                    break;
+                }
+            }
+            assert_eq!(value, Some(0xDD));
+        }
+        _ = std::fs::remove_file(ipc::socket_path(&filter));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn prefer_ipc_falls_back_to_direct_connection() {
+        use wire_weaver_client_common::ipc;
+
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let server = streams_server::StreamsServer {
+            data: Default::default(),
+        };
+        tokio::spawn(ipc::prefer_ipc(transport_cmd_rx, |cmd_rx| {
+            wire_weaver_client_common::loopback::loopback_worker(cmd_rx, server)
+        }));
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        // no daemon is running for this filter
+        cmd_tx
+            .connect(
+                daemon_filter(0xd002),
+                test_version(),
+                OnError::ExitImmediately,
+            )
+            .await
+            .unwrap();
+        let client = streams_client::StdAsyncClient { cmd_tx };
+        client.finish().call().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn injected_faults() {
+        // lost requests time out
+        let (mut client, data) = methods_over(Loopback::default().with_request_faults(Faults {
+            drop: 1.0,
+            ..Default::default()
+        }))
+        .await;
+        let r = client
+            .one_plain_arg(1)
+            .with_timeout(Duration::from_millis(20))
+            .call()
+            .await;
+        assert!(matches!(r, Err(Error::Timeout)));
+        assert_eq!(data.read().unwrap().one_plain_arg, 0);
+
+        // corrupted events are not silently accepted as valid returns
+        let (mut client, _data) = methods_over(Loopback::default().with_event_faults(Faults {
+            corrupt: 1.0,
+            ..Default::default()
+        }))
+        .await;
+        let mut intact = 0;
+        for _ in 0..8 {
+            let r = client
+                .one_plain_arg(1)
+                .with_timeout(Duration::from_millis(20))
+                .call()
+                .await;
+            if r.is_ok() {
+                intact += 1;
+            }
+        }
+        assert!(intact < 8);
+
+        // latency is added in both directions
+        let delay = Duration::from_millis(20);
+        let (client, _data) = methods_over(Loopback::default().with_faults(Faults {
+            delay,
+            ..Default::default()
+        }))
+        .await;
+        let started = Instant::now();
+        client.no_args().call().await.unwrap();
+        assert!(started.elapsed() >= delay * 2);
+
+        // reordered events are still matched with their requests
+        let (client, data) = methods_over(Loopback::default().with_event_faults(Faults {
+            reorder: 0.5,
+            ..Default::default()
+        }))
+        .await;
+        let mut calls = vec![];
+        for i in 0..8 {
+            let mut client = methods_client::StdAsyncClient {
+                cmd_tx: client.cmd_tx.clone(),
+            };
+            calls.push(tokio::spawn(
+                async move { client.one_plain_arg(i).call().await },
+            ));
+        }
+        for call in calls {
+            call.await.unwrap().unwrap();
+        }
+        assert!(data.read().unwrap().one_plain_arg < 8);
+    }
+}
@@ -53,6 +53,8 @@ mod tests {
                     b: RefVec::new_bytes(&[1, 2, 3]),
                 }
             }
+
+            fn added_later(&mut self, _msg_tx: &mut impl MessageSink) {}
         }
 
         mod api_impl {
@@ -109,6 +111,24 @@ mod tests {
     //     );
     // }
 
+    #[test]
+    fn max_len_consts() {
+        use no_std_sync_server::NoStdSyncServer;
+        const _: () = assert!(max_len::fits(
+            NoStdSyncServer::ONE_PLAIN_ARG_MAX_REQUEST_LEN,
+            16
+        ));
+        const _: () = assert!(max_len::fits(
+            NoStdSyncServer::PLAIN_RETURN_MAX_EVENT_LEN,
+            16
+        ));
+        assert_eq!(NoStdSyncServer::PLAIN_RETURN_MAX_RETURN_LEN, Some(1));
+        assert_eq!(NoStdSyncServer::NO_ARGS_MAX_RETURN_LEN, Some(0));
+        assert_eq!(NoStdSyncServer::USER_ARG_MAX_REQUEST_LEN, None);
+        assert_eq!(NoStdSyncServer::USER_DEFINED_RETURN_MAX_EVENT_LEN, None);
+        assert_eq!(NoStdSyncServer::MAX_CALL_REQUEST_LEN, None);
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn std_async_client_driving_no_std_sync_server() {
         tracing_subscriber::fmt::init();
@@ -173,4 +193,86 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn record_and_replay() {
+        use wire_weaver_client_common::capture::{Capture, CaptureWriter};
+        use wire_weaver_client_common::replay::{ReplayTiming, replay_worker};
+        use wire_weaver_client_common::{Command, TraceEvent};
+
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let data = Arc::new(RwLock::new(SharedTestData::default()));
+        let server = no_std_sync_server::NoStdSyncServer { data };
+        tokio::spawn(async move {
+            tests_common::test_event_loop(transport_cmd_rx, server, DummyTx {}).await;
+        });
+
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        let (trace_event_tx, mut trace_event_rx) = mpsc::unbounded_channel();
+        cmd_tx
+            .send(Command::RegisterTracer { trace_event_tx })
+            .unwrap();
+        let client_version = FullVersionOwned::new("test".into(), VersionOwned::new(0, 1, 0));
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                client_version.clone(),
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+        let mut client = std_async_client::StdAsyncClient { cmd_tx };
+        client.one_plain_arg(0xCC).call().await.unwrap();
+        assert_eq!(client.plain_return().call().await.unwrap(), 0xAA);
+
+        let mut writer = CaptureWriter::new(Vec::new()).unwrap();
+        while let Ok(event) = trace_event_rx.try_recv() {
+            writer.write_trace_event(&event).unwrap();
+        }
+        // GetDeviceInfo, as reported by a USB link tracer
+        writer
+            .write_trace_event(&TraceEvent::LinkPacket {
+                to_device: true,
+                bytes: vec![0x10, 0x00],
+            })
+            .unwrap();
+        let capture = Capture::from_bytes(&writer.into_inner()).unwrap();
+        assert_eq!(capture.records.len(), 6); // Connected + 2 requests + 2 events + link packet
+        assert!(capture.device_info().is_some());
+        assert!(capture.api_bundle().is_err());
+
+        let pcapng = capture.write_pcapng(Vec::new()).unwrap();
+        assert_eq!(pcapng[0..4], [0x0A, 0x0D, 0x0D, 0x0A]);
+        let mut blocks = 0;
+        let mut packet_interfaces = vec![];
+        let mut offset = 0;
+        while offset < pcapng.len() {
+            let block_type = u32::from_le_bytes(pcapng[offset..offset + 4].try_into().unwrap());
+            let len = u32::from_le_bytes(pcapng[offset + 4..offset + 8].try_into().unwrap());
+            if block_type == 6 {
+                let interface = &pcapng[offset + 8..offset + 12];
+                packet_interfaces.push(u32::from_le_bytes(interface.try_into().unwrap()));
+            }
+            offset += len as usize;
+            blocks += 1;
+        }
+        assert_eq!(offset, pcapng.len());
+        assert_eq!(blocks, 1 + 4 + 5); // section header, interfaces, 2 requests, 2 events and link packet
+        assert_eq!(packet_interfaces, [2, 3, 2, 3, 0]);
+
+        let (replay_cmd_tx, replay_cmd_rx) = mpsc::unbounded_channel();
+        tokio::spawn(replay_worker(replay_cmd_rx, capture, ReplayTiming::NoDelay));
+        let mut cmd_tx = CommandSender::new(replay_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                client_version,
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect to replay");
+        let mut client = std_async_client::StdAsyncClient { cmd_tx };
+        client.one_plain_arg(0xCC).call().await.unwrap();
+        assert_eq!(client.plain_return().call().await.unwrap(), 0xAA);
+    }
 }
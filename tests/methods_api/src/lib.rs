@@ -7,6 +7,8 @@ trait Methods {
     fn plain_return() -> u8;
     fn user_arg(u: UserDefined<'i>);
     fn user_defined_return() -> UserDefined<'i>;
+    #[since = "0.1.1"]
+    fn added_later();
 
     // user-defined
     // ()
@@ -0,0 +1,231 @@
+#[cfg(test)]
+mod tests {
+    use methods_api::UserDefinedOwned;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::path::Path;
+    use std::sync::{Arc, RwLock};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use wire_weaver::shrink_wrap::DeserializeShrinkWrapOwned;
+    use wire_weaver_client_common::device_filter::DeviceFilterKind;
+    use wire_weaver_client_common::ww_self::ValueOwned;
+    use wire_weaver_client_common::ww_version::FullVersionOwned;
+    use wire_weaver_client_common::{
+        CommandSender, DeviceFilter, OnError, Stream, TypedStreamEvent,
+    };
+    use wire_weaver_mock::{MockRequestKind, MockServer, Script, mock_worker};
+    use ww_numeric::NumericValue;
+
+    mod methods_client {
+        use wire_weaver_client_common::CommandSender;
+
+        pub struct StdAsyncClient {
+            pub cmd_tx: CommandSender,
+        }
+
+        mod api_client {
+            wire_weaver::ww_codegen!(
+                methods_api :: Methods for super::StdAsyncClient,
+                client = "full_client",
+            );
+        }
+    }
+
+    mod properties_client {
+        use wire_weaver_client_common::CommandSender;
+
+        pub struct StdAsyncClient {
+            pub cmd_tx: CommandSender,
+        }
+
+        mod api_client {
+            wire_weaver::ww_codegen!(
+                properties_api :: Properties for super::StdAsyncClient,
+                client = "full_client",
+            );
+        }
+    }
+
+    mod streams_client {
+        use wire_weaver_client_common::CommandSender;
+
+        pub struct StdAsyncClient {
+            pub cmd_tx: CommandSender,
+        }
+
+        mod api_client {
+            wire_weaver::ww_codegen!(
+                streams_api :: Streams for super::StdAsyncClient,
+                client = "full_client",
+            );
+        }
+    }
+
+    fn mock_server(api_crate: &str, trait_name: &str) -> MockServer {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(api_crate);
+        MockServer::from_crate(crate_path, Some(trait_name.into())).expect("load API")
+    }
+
+    async fn connect(server: MockServer) -> CommandSender {
+        let client_version: FullVersionOwned = server.user_api_version().unwrap().clone();
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        tokio::spawn(mock_worker(transport_cmd_rx, server));
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                client_version,
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+        cmd_tx
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn default_scripted_and_callback_responses() {
+        let script = Script::from_ron_str(
+            r#"(
+                responses: [
+                    (path: "plain_return", responses: [Value(Numeric(U8(7))), Value(Numeric(U8(8)))]),
+                    (path: "no_args", responses: [Error("not today")]),
+                ],
+            )"#,
+        )
+        .unwrap();
+        let one_plain_arg = Arc::new(RwLock::new(None));
+        let one_plain_arg_clone = one_plain_arg.clone();
+        let server = mock_server("methods_api", "Methods")
+            .with_script(script)
+            .on_request(move |request| {
+                if let MockRequestKind::Call { args } = &request.kind
+                    && request.path == "one_plain_arg"
+                {
+                    *one_plain_arg_clone.write().unwrap() = Some(args[0].clone());
+                }
+                None
+            });
+        let cmd_tx = connect(server).await;
+        let mut client = methods_client::StdAsyncClient { cmd_tx };
+
+        // scripted responses are given out in order, the last one is repeated
+        assert_eq!(client.plain_return().call().await.unwrap(), 7);
+        assert_eq!(client.plain_return().call().await.unwrap(), 8);
+        assert_eq!(client.plain_return().call().await.unwrap(), 8);
+
+        assert!(client.no_args().call().await.is_err());
+
+        client.one_plain_arg(0xCC).call().await.unwrap();
+        let (name, value) = one_plain_arg.read().unwrap().clone().unwrap();
+        assert_eq!(name, "value");
+        assert_eq!(value, ValueOwned::Numeric(NumericValue::U8(0xCC)));
+
+        client
+            .user_arg(UserDefinedOwned {
+                a: 123,
+                b: vec![1, 2, 3],
+            })
+            .call()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn written_property_is_read_back() {
+        let cmd_tx = connect(mock_server("properties_api", "Properties")).await;
+        let client = properties_client::StdAsyncClient { cmd_tx };
+
+        assert_eq!(client.read_plain().read().await.unwrap(), 0);
+        client.write_plain(0xAA).write().await.unwrap();
+        assert_eq!(client.read_plain().read().await.unwrap(), 0xAA);
+    }
+
+    /// Skip Connected and Opened events
+    async fn recv_data<T: DeserializeShrinkWrapOwned>(rx: &mut Stream<T>) -> T {
+        loop {
+            if let TypedStreamEvent::Data(data) = rx.recv_any().await.unwrap() {
+                return data;
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn scripted_streams() {
+        let script = Script::from_json_str(
+            r#"{
+                "streams": [
+                    { "path": "plain_stream", "values": [{ "Numeric": { "U8": 1 } }, { "Numeric": { "U8": 2 } }], "interval_ms": 1 },
+                    { "path": "array_of_streams[*]", "values": [{ "Vec": [{ "Numeric": { "U8": 170 } }] }] }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let sink_rx = Arc::new(RwLock::new(vec![]));
+        let sink_rx_clone = sink_rx.clone();
+        let server = mock_server("streams_api", "Streams")
+            .with_script(script)
+            .on_request(move |request| {
+                if let MockRequestKind::StreamWrite { value } = &request.kind {
+                    sink_rx_clone.write().unwrap().push(value.clone());
+                }
+                None
+            });
+        let cmd_tx = connect(server).await;
+        let client = streams_client::StdAsyncClient { cmd_tx };
+
+        let mut rx = client.plain_stream().expect("successful stream open");
+        rx.open().unwrap();
+        assert_eq!(recv_data(&mut rx).await, 1);
+        assert_eq!(recv_data(&mut rx).await, 2);
+
+        let mut rx_arr = client.array_of_streams(3).expect("successful stream open");
+        rx_arr.open().unwrap();
+        assert_eq!(recv_data(&mut rx_arr).await.0.as_slice(), &[0xAA]);
+
+        let mut sink = client.plain_sink().unwrap();
+        sink.send(5).unwrap();
+        client.finish().call().await.unwrap();
+        assert_eq!(
+            sink_rx.read().unwrap().as_slice(),
+            &[ValueOwned::Numeric(NumericValue::U8(5))]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn over_web_socket() {
+        let script = Script::from_ron_str(
+            r#"(responses: [(path: "plain_return", responses: [Value(Numeric(U8(42)))])])"#,
+        )
+        .unwrap();
+        let server = mock_server("methods_api", "Methods").with_script(script);
+        let client_version = server.user_api_version().unwrap().clone();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(wire_weaver_mock::serve_ws(listener, server));
+
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        tokio::spawn(wire_weaver_net_host::ws_worker(transport_cmd_rx));
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter {
+                    kind: DeviceFilterKind::WebSocket {
+                        addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                        port,
+                        path: "".into(),
+                    },
+                    allow_ipc: false,
+                },
+                client_version,
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+        let client = methods_client::StdAsyncClient { cmd_tx };
+        assert_eq!(client.plain_return().call().await.unwrap(), 42);
+        client.no_args().call().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use modules_api::{Direction, MotorOwned, Speed, StatusKind};
+    use std::path::Path;
+    use std::sync::{Arc, RwLock};
+    use std::time::Duration;
+    use tests_common::DummyTx;
+    use tokio::sync::mpsc;
+    use wire_weaver::ww_version::{FullVersionOwned, VersionOwned};
+    use wire_weaver_client_common::ww_self::{TypeLocationOwned, TypeOwned};
+    use wire_weaver_client_common::{CommandSender, DeviceFilter, OnError};
+
+    #[derive(Default)]
+    struct SharedTestData {
+        motor_name: String,
+        direction: Option<Direction>,
+    }
+
+    mod no_std_sync_server {
+        use super::*;
+        use modules_api::Motor;
+        use tests_common::TestProcessEvents;
+        use wire_weaver::MessageSink;
+        use wire_weaver::prelude::ShrinkWrapError;
+
+        pub struct NoStdSyncServer {
+            pub data: Arc<RwLock<SharedTestData>>,
+        }
+
+        impl NoStdSyncServer {
+            fn set_motor(&mut self, _msg_tx: &mut impl MessageSink, motor: Motor<'_>) {
+                assert_eq!(motor.speed, Speed { rpm: 1500 });
+                self.data.write().unwrap().motor_name = motor.name.to_string();
+            }
+
+            fn speed(&mut self, _msg_tx: &mut impl MessageSink) -> Speed {
+                Speed { rpm: 1500 }
+            }
+
+            fn status(&mut self, _msg_tx: &mut impl MessageSink) -> StatusKind {
+                StatusKind::Moving {
+                    direction: Direction::Backward,
+                }
+            }
+
+            fn set_direction(&mut self, value: Direction) {
+                self.data.write().unwrap().direction = Some(value);
+            }
+
+            fn get_direction(&mut self) -> Direction {
+                self.data
+                    .read()
+                    .unwrap()
+                    .direction
+                    .unwrap_or(Direction::Forward)
+            }
+        }
+
+        mod api_impl {
+            wire_weaver::ww_codegen!(
+                modules_api :: Modules for super::NoStdSyncServer,
+                server = true, no_alloc = true, use_async = false,
+                method_model = "_=immediate",
+                property_model = "_=get_set",
+                introspect = false,
+            );
+        }
+
+        impl TestProcessEvents for NoStdSyncServer {
+            fn process_request_bytes<'a>(
+                &mut self,
+                bytes: &[u8],
+                scratch_args: &'a mut [u8],
+                scratch_event: &'a mut [u8],
+                scratch_err: &'a mut [u8],
+                msg_tx: &mut impl MessageSink,
+            ) -> Result<&'a [u8], ShrinkWrapError> {
+                self.process_request_bytes(bytes, scratch_args, scratch_event, scratch_err, msg_tx)
+            }
+        }
+    }
+
+    mod std_async_client {
+        use wire_weaver_client_common::CommandSender;
+
+        pub struct StdAsyncClient {
+            pub cmd_tx: CommandSender,
+        }
+
+        mod api_client {
+            wire_weaver::ww_codegen!(
+                modules_api :: Modules for super::StdAsyncClient,
+                client = "full_client",
+            );
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn types_and_traits_from_submodules() {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let data = Arc::new(RwLock::new(SharedTestData::default()));
+
+        let server = no_std_sync_server::NoStdSyncServer { data: data.clone() };
+        tokio::spawn(async move {
+            tests_common::test_event_loop(transport_cmd_rx, server, DummyTx {}).await;
+        });
+
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                FullVersionOwned::new("test".into(), VersionOwned::new(0, 1, 0)),
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+        let mut client = std_async_client::StdAsyncClient { cmd_tx };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        client
+            .set_motor(MotorOwned {
+                name: "left".into(),
+                speed: Speed { rpm: 1500 },
+                direction: Direction::Forward,
+            })
+            .call()
+            .await
+            .unwrap();
+        assert_eq!(data.read().unwrap().motor_name, "left");
+
+        let speed = client.speed().call().await.unwrap();
+        assert_eq!(speed, Speed { rpm: 1500 });
+
+        let status = client.status().call().await.unwrap();
+        assert_eq!(
+            status,
+            StatusKind::Moving {
+                direction: Direction::Backward
+            }
+        );
+
+        client
+            .write_direction(Direction::Backward)
+            .write()
+            .await
+            .unwrap();
+        assert_eq!(data.read().unwrap().direction, Some(Direction::Backward));
+    }
+
+    #[test]
+    fn types_are_deduplicated_across_modules() {
+        let api_crate = Path::new(env!("CARGO_MANIFEST_DIR")).join("../modules_api");
+        let api_bundle = wire_weaver_core::load(&api_crate, None, true).unwrap();
+        assert_eq!(api_bundle.root.trait_name, "Modules");
+
+        let idents: Vec<&str> = api_bundle
+            .types
+            .iter()
+            .filter_map(|t| match t {
+                TypeLocationOwned::InLine {
+                    ty: TypeOwned::Struct(s),
+                    ..
+                } => Some(s.ident.as_str()),
+                TypeLocationOwned::InLine {
+                    ty: TypeOwned::Enum(e),
+                    ..
+                } => Some(e.ident.as_str()),
+                _ => None,
+            })
+            .collect();
+        // Direction is referred to as `motor::Direction`, `crate::types::motor::Direction` and through an alias
+        let mut sorted = idents.clone();
+        sorted.sort();
+        assert_eq!(sorted, ["Direction", "Motor", "Speed", "StatusKind"]);
+    }
+}
@@ -0,0 +1,19 @@
+// #[ww_trait] does not emit the trait itself, imports used only in it appear unused
+#[allow(unused_imports)]
+use super::StatusKind;
+#[allow(unused_imports)]
+use crate::types::Motor;
+use wire_weaver::prelude::*;
+
+#[ww_trait]
+pub trait Modules {
+    fn set_motor(motor: Motor<'i>);
+    fn speed() -> types::Speed;
+    fn status() -> StatusKind;
+    property!(rw direction: crate::types::motor::Direction);
+}
+
+mod types {
+    #[allow(unused_imports)]
+    pub use crate::types::motor::*;
+}
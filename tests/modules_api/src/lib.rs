@@ -0,0 +1,10 @@
+mod api;
+#[path = "status/status_kind.rs"]
+mod status;
+pub mod types;
+
+// generated code refers to types and traits from the crate root
+pub use api::*;
+pub use status::StatusKind;
+pub use types::motor::{Direction, Speed};
+pub use types::{Motor, MotorOwned};
@@ -0,0 +1,10 @@
+use super::types::motor::Direction as MotorDirection;
+use wire_weaver::prelude::*;
+
+#[derive_shrink_wrap]
+#[ww_repr(u4)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StatusKind {
+    Idle,
+    Moving { direction: MotorDirection },
+}
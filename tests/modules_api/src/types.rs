@@ -0,0 +1,13 @@
+pub mod motor;
+
+use motor::Speed;
+use wire_weaver::prelude::*;
+
+#[derive_shrink_wrap]
+#[owned = "std"]
+#[derive(Debug, PartialEq)]
+pub struct Motor<'i> {
+    pub name: &'i str,
+    pub speed: Speed,
+    pub direction: motor::Direction,
+}
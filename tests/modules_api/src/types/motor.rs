@@ -0,0 +1,15 @@
+use wire_weaver::prelude::*;
+
+#[derive_shrink_wrap]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Speed {
+    pub rpm: u16,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(u4)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
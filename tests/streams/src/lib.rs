@@ -2,12 +2,19 @@
 mod tests {
     use std::sync::{Arc, RwLock};
     use std::time::Duration;
-    use tests_common::DummyTx;
+    use tests_common::{ChannelTx, DummyTx};
     use tokio::sync::mpsc;
     use wire_weaver::MessageSink;
     use wire_weaver::prelude::*;
-    use wire_weaver::ww_version::{FullVersionOwned, VersionOwned};
+    use wire_weaver::ww_version::{
+        CompactVersion, FullVersion, FullVersionOwned, GlobalTypeId, Version, VersionOwned,
+    };
+    use wire_weaver_client_common::ww_self::{
+        self, ApiBundleOwned, ApiItemKindOwned, ApiItemOwned, ApiLevelLocationOwned, ApiLevelOwned,
+        Multiplicity, TypeOwned,
+    };
     use wire_weaver_client_common::{CommandSender, DeviceFilter, OnError, TypedStreamEvent};
+    use ww_client_server::{EventKindOwned, EventOwned, PathKind};
 
     #[derive(Default)]
     struct SharedTestData {
@@ -72,41 +79,41 @@ mod tests {
             fn finish(&mut self, _msg_tx: &mut impl MessageSink) {
                 println!("finish called");
             }
+        }
 
-            // pub fn send_updates(&mut self, stream_number: usize) -> Vec<Vec<u8>> {
-            //     println!("sending updates");
-            //     let mut updates = vec![];
-            //     let mut s1 = [0u8; 128];
-            //     let mut s2 = [0u8; 128];
-            //     match stream_number {
-            //         0 => {
-            //             updates.push(
-            //                 api_impl::stream_data_ser()
-            //                     .plain_stream(&0xAA, &mut s1, &mut s2)
-            //                     .unwrap()
-            //                     .to_vec(),
-            //             );
-            //         }
-            //         1 => {
-            //             updates.push(
-            //                 api_impl::stream_data_ser()
-            //                     .vec_stream(&[0xAAu8, 0xBB, 0xCC][..], &mut s1, &mut s2)
-            //                     .unwrap()
-            //                     .to_vec(),
-            //             );
-            //         }
-            //         2 => {
-            //             updates.push(
-            //                 api_impl::stream_data_ser()
-            //                     .array_of_streams(0, &[0xAAu8, 0xBB, 0xCC][..], &mut s1, &mut s2)
-            //                     .unwrap()
-            //                     .to_vec(),
-            //             );
-            //         }
-            //         _ => {}
-            //     }
-            //     updates
-            // }
+        /// Stream updates, as if they were sent by user code on the server side.
+        pub fn stream_updates(stream_number: usize) -> Vec<Vec<u8>> {
+            let mut updates = vec![];
+            let mut s1 = [0u8; 128];
+            let mut s2 = [0u8; 128];
+            match stream_number {
+                0 => {
+                    updates.push(
+                        api_impl::stream_data_ser()
+                            .plain_stream(&0xAA, &mut s1, &mut s2)
+                            .unwrap()
+                            .to_vec(),
+                    );
+                }
+                1 => {
+                    updates.push(
+                        api_impl::stream_data_ser()
+                            .vec_stream(&[0xAAu8, 0xBB, 0xCC][..], &mut s1, &mut s2)
+                            .unwrap()
+                            .to_vec(),
+                    );
+                }
+                2 => {
+                    updates.push(
+                        api_impl::stream_data_ser()
+                            .array_of_streams(0, &[0xAAu8, 0xBB, 0xCC][..], &mut s1, &mut s2)
+                            .unwrap()
+                            .to_vec(),
+                    );
+                }
+                _ => {}
+            }
+            updates
         }
 
         mod api_impl {
@@ -175,13 +182,27 @@ mod tests {
     async fn std_async_client_driving_no_std_sync_server() {
         tracing_subscriber::fmt::init();
         let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
-        let (notify_tx, _notify_rx) = mpsc::unbounded_channel::<usize>();
+        let (notify_tx, mut notify_rx) = mpsc::unbounded_channel::<usize>();
         let data = Arc::new(RwLock::new(SharedTestData::default()));
 
         let data_clone = data.clone();
         let server = no_std_sync_server::NoStdSyncServer { data: data_clone };
+        let (mut events_tx, events_rx) = ChannelTx::new();
         tokio::spawn(async move {
-            tests_common::test_event_loop(transport_cmd_rx, server, DummyTx {}).await;
+            tests_common::test_event_loop_with_events(
+                transport_cmd_rx,
+                server,
+                DummyTx {},
+                events_rx,
+            )
+            .await;
+        });
+        tokio::spawn(async move {
+            while let Some(stream_number) = notify_rx.recv().await {
+                for update in no_std_sync_server::stream_updates(stream_number) {
+                    events_tx.send(&update).await.unwrap();
+                }
+            }
         });
 
         let mut cmd_tx = CommandSender::new(transport_cmd_tx);
@@ -229,4 +250,220 @@ mod tests {
 
         tokio::time::sleep(Duration::from_millis(10)).await;
     }
+
+    fn trait_stream_update(path: &[u32], data: &[u8]) -> Vec<u8> {
+        let event = EventOwned {
+            seq: 0,
+            result: Ok(EventKindOwned::StreamData {
+                path: path.iter().map(|id| UNib32(*id)).collect(),
+                data: data.to_vec(),
+            }),
+        };
+        let mut buf = [0u8; 64];
+        event.to_ww_bytes(&mut buf).unwrap().to_vec()
+    }
+
+    fn subscribed(seq: u16, path: &[u32]) -> Vec<u8> {
+        let event = EventOwned {
+            seq,
+            result: Ok(EventKindOwned::Subscribed {
+                path: path.iter().map(|id| UNib32(*id)).collect(),
+            }),
+        };
+        let mut buf = [0u8; 64];
+        event.to_ww_bytes(&mut buf).unwrap().to_vec()
+    }
+
+    /// Root API with a LogDefmt-like trait implemented at /1, from a different crate.
+    fn api_bundle_with_trait() -> ApiBundleOwned {
+        let stream = |id: u32, ident: &str| ApiItemOwned {
+            id: UNib32(id),
+            kind: ApiItemKindOwned::Stream {
+                ty: TypeOwned::Bool,
+                is_up: true,
+            },
+            multiplicity: Multiplicity::Flat,
+            since: None,
+            ident: ident.into(),
+            docs: vec![],
+            deprecated: None,
+        };
+        ApiBundleOwned {
+            magic: ww_self::MAGIC,
+            ww_self_version: ww_self::VERSION,
+            root: ApiLevelOwned {
+                docs: vec![],
+                crate_idx: UNib32(0),
+                trait_name: "Device".into(),
+                items: vec![
+                    stream(0, "plain_stream"),
+                    ApiItemOwned {
+                        id: UNib32(1),
+                        kind: ApiItemKindOwned::Trait {
+                            trait_idx: UNib32(0),
+                        },
+                        multiplicity: Multiplicity::Flat,
+                        since: None,
+                        ident: "log".into(),
+                        docs: vec![],
+                        deprecated: None,
+                    },
+                ],
+            },
+            types: vec![],
+            traits: vec![ApiLevelLocationOwned::InLine {
+                level: ApiLevelOwned {
+                    docs: vec![],
+                    crate_idx: UNib32(1),
+                    trait_name: "LogDefmt".into(),
+                    items: vec![stream(0, "data")],
+                },
+                crate_idx: UNib32(1),
+            }],
+            ext_crates: vec![
+                FullVersionOwned::new("device_api".into(), VersionOwned::new(0, 1, 0)),
+                FullVersionOwned::new("log_api".into(), VersionOwned::new(0, 2, 1)),
+            ],
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn trait_addressed_streams() {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let data = Arc::new(RwLock::new(SharedTestData::default()));
+        let server = no_std_sync_server::NoStdSyncServer { data };
+        let (mut events_tx, events_rx) = ChannelTx::new();
+        tokio::spawn(async move {
+            tests_common::test_event_loop_with_events(
+                transport_cmd_rx,
+                server,
+                DummyTx {},
+                events_rx,
+            )
+            .await;
+        });
+
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                FullVersionOwned::new("test".into(), VersionOwned::new(0, 1, 0)),
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+
+        // resolved through ApiBundle
+        let log_gid = FullVersion::new("log_api", Version::new(0, 2, 0));
+        let mut log_rx = cmd_tx
+            .prepare_stream::<u8>(PathKind::global(log_gid, None, &[UNib32(0)]), None)
+            .unwrap();
+        assert_eq!(
+            log_rx.recv_any().await.unwrap(),
+            TypedStreamEvent::Connected
+        );
+        cmd_tx.set_api_bundle(api_bundle_with_trait()).unwrap();
+        events_tx
+            .send(&trait_stream_update(&[1, 0], &[0xBB]))
+            .await
+            .unwrap();
+        assert_eq!(log_rx.recv().await.unwrap(), 0xBB);
+
+        // opened after ApiBundle is already known
+        let mut log_rx2 = cmd_tx
+            .prepare_stream::<u8>(PathKind::global(log_gid, None, &[UNib32(0)]), None)
+            .unwrap();
+        events_tx
+            .send(&trait_stream_update(&[1, 0], &[0xCC]))
+            .await
+            .unwrap();
+        assert_eq!(log_rx.recv().await.unwrap(), 0xCC);
+        assert_eq!(log_rx2.recv().await.unwrap(), 0xCC);
+
+        // resolved through Subscribed event from a device
+        let compact_gid = CompactVersion::new(GlobalTypeId::new(7), 1, 0, 0);
+        let mut gpio_rx = cmd_tx
+            .prepare_stream::<u8>(
+                PathKind::global(
+                    FullVersion::new("gpio_api", Version::new(1, 0, 0)),
+                    Some(compact_gid),
+                    &[UNib32(5), UNib32(2)],
+                ),
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            gpio_rx.recv_any().await.unwrap(),
+            TypedStreamEvent::Connected
+        );
+        gpio_rx.open().unwrap();
+        while gpio_rx.open_seq() == 0 {
+            tokio::task::yield_now().await;
+        }
+        // Subscribed in response to some other request must not resolve the stream
+        events_tx
+            .send(&subscribed(gpio_rx.open_seq().wrapping_add(1), &[3, 5, 2]))
+            .await
+            .unwrap();
+        events_tx
+            .send(&trait_stream_update(&[3, 5, 2], &[0xD0]))
+            .await
+            .unwrap();
+        events_tx
+            .send(&subscribed(gpio_rx.open_seq(), &[3, 5, 2]))
+            .await
+            .unwrap();
+        events_tx
+            .send(&trait_stream_update(&[3, 5, 2], &[0xDD]))
+            .await
+            .unwrap();
+        assert_eq!(gpio_rx.recv().await.unwrap(), 0xDD);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn trait_addressed_compact_stream() {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let data = Arc::new(RwLock::new(SharedTestData::default()));
+        let server = no_std_sync_server::NoStdSyncServer { data };
+        let (mut events_tx, events_rx) = ChannelTx::new();
+        tokio::spawn(async move {
+            tests_common::test_event_loop_with_events(
+                transport_cmd_rx,
+                server,
+                DummyTx {},
+                events_rx,
+            )
+            .await;
+        });
+
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                FullVersionOwned::new("test".into(), VersionOwned::new(0, 1, 0)),
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+
+        // trait level is in-line, crate has a global type id assigned
+        let mut api_bundle = api_bundle_with_trait();
+        api_bundle.ext_crates[1] =
+            FullVersionOwned::new("ww_log_bare_metal".into(), VersionOwned::new(0, 2, 1));
+        cmd_tx.set_api_bundle(api_bundle).unwrap();
+
+        let log_gid = FullVersion::new("ww_log_bare_metal", Version::new(0, 2, 0));
+        let log_compact_gid = CompactVersion::new(GlobalTypeId::new(0), 0, 2, 0);
+        let mut log_rx = cmd_tx
+            .prepare_stream::<u8>(
+                PathKind::global(log_gid, Some(log_compact_gid), &[UNib32(0)]),
+                None,
+            )
+            .unwrap();
+        events_tx
+            .send(&trait_stream_update(&[1, 0], &[0xEE]))
+            .await
+            .unwrap();
+        assert_eq!(log_rx.recv().await.unwrap(), 0xEE);
+    }
 }
@@ -1,7 +1,11 @@
-use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use wire_weaver::prelude::*;
+use wire_weaver_client_common::event_loop_state::CommonState;
+use wire_weaver_client_common::rx_dispatcher::{
+    DispatcherCommand, DispatcherMessage, RxDispatcher,
+};
 use wire_weaver_client_common::{Command, DeviceInfoBundle};
-use ww_client_server::{Event, EventKind, Request};
+use ww_client_server::Request;
 
 pub struct DummyTx;
 
@@ -11,6 +15,25 @@ impl MessageSink for DummyTx {
     }
 }
 
+/// Forwards messages sent by a server outside of request processing (stream updates, etc.) back to [test_event_loop_with_events].
+#[derive(Clone)]
+pub struct ChannelTx {
+    tx: UnboundedSender<Vec<u8>>,
+}
+
+impl ChannelTx {
+    pub fn new() -> (Self, UnboundedReceiver<Vec<u8>>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (ChannelTx { tx }, rx)
+    }
+}
+
+impl MessageSink for ChannelTx {
+    fn send(&mut self, message: &[u8]) -> impl Future<Output = Result<(), ()>> {
+        core::future::ready(self.tx.send(message.to_vec()).map_err(|_| ()))
+    }
+}
+
 pub trait TestProcessEvents {
     fn process_request_bytes<'a>(
         &mut self,
@@ -23,52 +46,103 @@ pub trait TestProcessEvents {
 }
 
 pub async fn test_event_loop(
+    cmd_rx: UnboundedReceiver<Command>,
+    server: impl TestProcessEvents,
+    msg_tx: impl MessageSink,
+) {
+    let (_events_tx, events_rx) = ChannelTx::new();
+    test_event_loop_with_events(cmd_rx, server, msg_tx, events_rx).await;
+}
+
+/// Same as [test_event_loop], but also delivers messages from `events_rx` to the client, as if they were sent by a server.
+pub async fn test_event_loop_with_events(
     mut cmd_rx: UnboundedReceiver<Command>,
     mut server: impl TestProcessEvents,
     mut msg_tx: impl MessageSink,
+    mut events_rx: UnboundedReceiver<Vec<u8>>,
 ) {
     let mut s1 = [0u8; 512];
     let mut s2 = [0u8; 512];
     let mut se = [0u8; 128];
 
-    let mut seq = 1;
-    while let Some(cmd) = cmd_rx.recv().await {
+    let mut rx_dispatcher = RxDispatcher::default();
+    let mut common = CommonState::default();
+    loop {
+        let cmd = tokio::select! {
+            biased;
+            cmd = cmd_rx.recv() => {
+                let Some(cmd) = cmd else {
+                    break;
+                };
+                cmd
+            }
+            Some(event) = events_rx.recv() => {
+                common.trace_event(&event);
+                rx_dispatcher.handle_msg(DispatcherMessage::MessageBytes(&event));
+                continue;
+            }
+        };
         match cmd {
             Command::Connect { connected_tx, .. } => {
+                rx_dispatcher.handle_msg(DispatcherMessage::Connected);
                 if let Some(tx) = connected_tx {
                     tx.send(Ok(DeviceInfoBundle::empty())).unwrap();
                 }
-                continue;
+                common.device_info = Some(DeviceInfoBundle::empty());
+                _ = common.on_link_up();
+            }
+            Command::RegisterTracer { trace_event_tx } => {
+                common.tracers.push(trace_event_tx);
             }
             Command::SendMessage { mut bytes, done_tx } => {
-                Request::set_seq(&mut bytes, seq);
-                seq += 1;
+                if let Some((done_tx, timeout)) = done_tx {
+                    let seq = rx_dispatcher.next_seq().expect("next seq");
+                    Request::set_seq(&mut bytes, seq);
+                    rx_dispatcher.handle_cmd(DispatcherCommand::OnReturn {
+                        seq,
+                        done_tx,
+                        timeout,
+                    });
+                }
+                common.trace_request(&bytes);
                 let r = server
                     .process_request_bytes(&bytes, &mut s1, &mut s2, &mut se, &mut msg_tx)
                     .expect("process_request");
                 if r.is_empty() {
                     continue;
                 }
-                let event = Event::from_ww_bytes(r).unwrap();
-                let r = match event.result {
-                    Ok(event_kind) => {
-                        let data = match event_kind {
-                            EventKind::ReturnValue { data } => data.as_slice().to_vec(),
-                            EventKind::ReadValue { data } => data.as_slice().to_vec(),
-                            _ => vec![],
-                        };
-                        Ok(data)
+                common.trace_event(r);
+                rx_dispatcher.handle_msg(DispatcherMessage::MessageBytes(r));
+            }
+            Command::Cancel { request } => {
+                rx_dispatcher.cancel(request.seq.get());
+                if let Some(bytes) = request.notification() {
+                    common.trace_request(&bytes);
+                    let r = server
+                        .process_request_bytes(&bytes, &mut s1, &mut s2, &mut se, &mut msg_tx)
+                        .expect("process_request");
+                    if !r.is_empty() {
+                        common.trace_event(r);
+                        rx_dispatcher.handle_msg(DispatcherMessage::MessageBytes(r));
                     }
-                    Err(e) => Err(wire_weaver_client_common::Error::RemoteError(
-                        e.make_owned(),
-                    )),
-                };
-                if let Some((done_tx, _timeout)) = done_tx {
-                    done_tx.send(r).unwrap();
                 }
             }
-            Command::OnStreamEvent { .. } => {
-                // TODO: stream support in tests
+            Command::OnStreamEvent {
+                path_kind,
+                stream_event_tx,
+                subscribe_seq,
+            } => {
+                rx_dispatcher.handle_cmd(DispatcherCommand::OnStreamEvent {
+                    path_kind: *path_kind,
+                    stream_event_tx,
+                    subscribe_seq,
+                });
+            }
+            Command::SetApiBundle { api_bundle } => {
+                rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle { api_bundle });
+            }
+            Command::OnConnectionEvent { event_tx } => {
+                common.add_connection_event_tx(event_tx);
             }
             _ => panic!("not supported command"),
         }
@@ -84,6 +84,252 @@ mod tests {
         }
     }
 
+    /// Same API, but implemented through generated handler traits, split across several types.
+    mod handler_traits_server {
+        use super::*;
+        use api_impl::{G1Server, GpioServer, PeriphChannelServer, PeriphServer, TraitsServer};
+        use tests_common::TestProcessEvents;
+        use ww_client_server::ErrorKind;
+
+        pub struct Device {
+            pub gpio: GpioDriver,
+            pub channels: ChannelsDriver,
+        }
+
+        pub struct GpioDriver {
+            pub data: Arc<RwLock<SharedTestData>>,
+        }
+
+        pub struct ChannelsDriver {
+            pub data: Arc<RwLock<SharedTestData>>,
+        }
+
+        impl TraitsServer for Device {
+            type G1 = Self;
+            fn g1(&mut self) -> &mut Self::G1 {
+                self
+            }
+
+            type Gpio = GpioDriver;
+            fn gpio(&mut self) -> &mut Self::Gpio {
+                &mut self.gpio
+            }
+
+            type Periph = Self;
+            fn periph(&mut self) -> &mut Self::Periph {
+                self
+            }
+
+            fn valid_indices_gpio(&mut self) -> ValidIndices<'_> {
+                ValidIndices::Range(0..8)
+            }
+
+            fn valid_indices_periph(&mut self) -> ValidIndices<'_> {
+                ValidIndices::Range(0..4)
+            }
+        }
+
+        // m1 is not implemented yet
+        impl G1Server for Device {}
+
+        impl PeriphServer for Device {
+            type Channel = ChannelsDriver;
+            fn channel(&mut self) -> &mut Self::Channel {
+                &mut self.channels
+            }
+
+            fn valid_indices_channel(&mut self, _index: [UNib32; 1]) -> ValidIndices<'_> {
+                ValidIndices::Range(0..8)
+            }
+        }
+
+        impl GpioServer for GpioDriver {
+            fn set_high(
+                &mut self,
+                _msg_tx: &mut impl MessageSink,
+                index: [UNib32; 1],
+            ) -> Result<(), ErrorKind<'static>> {
+                self.data
+                    .write()
+                    .unwrap()
+                    .gpio_used_indices
+                    .push(index[0].0);
+                Ok(())
+            }
+        }
+
+        impl PeriphChannelServer for ChannelsDriver {
+            fn get_gain(&mut self, index: [UNib32; 2]) -> Result<f32, ErrorKind<'static>> {
+                let data = self.data.read().unwrap();
+                Ok(data.set_gain.get(&index).copied().unwrap_or(0.0))
+            }
+
+            fn set_gain(
+                &mut self,
+                index: [UNib32; 2],
+                value: f32,
+            ) -> Result<(), ErrorKind<'static>> {
+                self.data.write().unwrap().set_gain.insert(index, value);
+                Ok(())
+            }
+        }
+
+        mod api_impl {
+            wire_weaver::ww_codegen!(
+                traits_api :: Traits for super::Device,
+                server = true, no_alloc = true, use_async = false,
+                method_model = "_=immediate",
+                handler_traits = true,
+                introspect = false,
+                // debug_to_file = "../../target/tests_traits_handler_traits_server.rs"
+            );
+        }
+
+        impl TestProcessEvents for Device {
+            fn process_request_bytes<'a>(
+                &mut self,
+                bytes: &[u8],
+                scratch_args: &'a mut [u8],
+                scratch_event: &'a mut [u8],
+                scratch_err: &'a mut [u8],
+                msg_tx: &mut impl MessageSink,
+            ) -> Result<&'a [u8], ShrinkWrapError> {
+                TraitsServer::process_request_bytes(
+                    self,
+                    bytes,
+                    scratch_args,
+                    scratch_event,
+                    scratch_err,
+                    msg_tx,
+                )
+            }
+        }
+    }
+
+    /// Same as [handler_traits_server], but with async handlers, driven through a loopback link.
+    mod handler_traits_async_server {
+        use super::*;
+        use api_impl::{G1Server, GpioServer, PeriphChannelServer, PeriphServer, TraitsServer};
+        use wire_weaver_client_common::loopback::{LoopbackServer, LoopbackSink};
+        use ww_client_server::ErrorKind;
+
+        pub struct Device {
+            pub gpio: GpioDriver,
+            pub channels: ChannelsDriver,
+        }
+
+        pub struct GpioDriver {
+            pub data: Arc<RwLock<SharedTestData>>,
+        }
+
+        pub struct ChannelsDriver {
+            pub data: Arc<RwLock<SharedTestData>>,
+        }
+
+        impl TraitsServer for Device {
+            type G1 = Self;
+            fn g1(&mut self) -> &mut Self::G1 {
+                self
+            }
+
+            type Gpio = GpioDriver;
+            fn gpio(&mut self) -> &mut Self::Gpio {
+                &mut self.gpio
+            }
+
+            type Periph = Self;
+            fn periph(&mut self) -> &mut Self::Periph {
+                self
+            }
+
+            fn valid_indices_gpio(&mut self) -> ValidIndices<'_> {
+                ValidIndices::Range(0..8)
+            }
+
+            fn valid_indices_periph(&mut self) -> ValidIndices<'_> {
+                ValidIndices::Range(0..4)
+            }
+        }
+
+        // m1 is not implemented yet
+        impl G1Server for Device {}
+
+        impl PeriphServer for Device {
+            type Channel = ChannelsDriver;
+            fn channel(&mut self) -> &mut Self::Channel {
+                &mut self.channels
+            }
+
+            fn valid_indices_channel(&mut self, _index: [UNib32; 1]) -> ValidIndices<'_> {
+                ValidIndices::Range(0..8)
+            }
+        }
+
+        impl GpioServer for GpioDriver {
+            async fn set_high(
+                &mut self,
+                _msg_tx: &mut impl MessageSink,
+                index: [UNib32; 1],
+            ) -> Result<(), ErrorKind<'static>> {
+                tokio::task::yield_now().await;
+                self.data
+                    .write()
+                    .unwrap()
+                    .gpio_used_indices
+                    .push(index[0].0);
+                Ok(())
+            }
+        }
+
+        impl PeriphChannelServer for ChannelsDriver {
+            async fn get_gain(&mut self, index: [UNib32; 2]) -> Result<f32, ErrorKind<'static>> {
+                let data = self.data.read().unwrap();
+                Ok(data.set_gain.get(&index).copied().unwrap_or(0.0))
+            }
+
+            async fn set_gain(
+                &mut self,
+                index: [UNib32; 2],
+                value: f32,
+            ) -> Result<(), ErrorKind<'static>> {
+                self.data.write().unwrap().set_gain.insert(index, value);
+                Ok(())
+            }
+        }
+
+        mod api_impl {
+            wire_weaver::ww_codegen!(
+                traits_api :: Traits for super::Device,
+                server = true, no_alloc = true, use_async = true,
+                method_model = "_=immediate",
+                handler_traits = true,
+                introspect = false,
+                // debug_to_file = "../../target/tests_traits_handler_traits_async_server.rs"
+            );
+        }
+
+        impl LoopbackServer for Device {
+            async fn process_request_bytes<'a>(
+                &mut self,
+                bytes: &[u8],
+                scratch_args: &'a mut [u8],
+                scratch_event: &'a mut [u8],
+                scratch_err: &'a mut [u8],
+                msg_tx: &mut LoopbackSink,
+            ) -> Result<&'a [u8], ShrinkWrapError> {
+                TraitsServer::process_request_bytes(
+                    self,
+                    bytes,
+                    scratch_args,
+                    scratch_event,
+                    scratch_err,
+                    msg_tx,
+                )
+                .await
+            }
+        }
+    }
+
     mod std_async_client {
         use wire_weaver_client_common::CommandSender;
 
@@ -222,4 +468,101 @@ mod tests {
             .unwrap();
         assert!(value == 10.0);
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn handler_traits_split_across_types() {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let data = Arc::new(RwLock::new(SharedTestData::default()));
+        let server = handler_traits_server::Device {
+            gpio: handler_traits_server::GpioDriver { data: data.clone() },
+            channels: handler_traits_server::ChannelsDriver { data: data.clone() },
+        };
+        tokio::spawn(async move {
+            tests_common::test_event_loop(transport_cmd_rx, server, DummyTx {}).await;
+        });
+
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                FullVersionOwned::new("test".into(), VersionOwned::new(0, 1, 0)),
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+        let client = std_async_client::StdAsyncClient { cmd_tx };
+
+        client.gpio(5).set_high().call().await.unwrap();
+        assert_eq!(data.read().unwrap().gpio_used_indices, vec![5]);
+        assert!(client.gpio(8).set_high().call().await.is_err());
+
+        client
+            .periph(3)
+            .channel(7)
+            .write_gain(10.0)
+            .write()
+            .await
+            .unwrap();
+        let value = client
+            .periph(3)
+            .channel(7)
+            .read_gain()
+            .read()
+            .await
+            .unwrap();
+        assert_eq!(value, 10.0);
+
+        let err = client.g1().m1().call().await.unwrap_err();
+        assert!(format!("{err:?}").contains("OperationNotImplemented"));
+        assert!(!data.read().unwrap().subgroup_m1_called);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn handler_traits_async_split_across_types() {
+        let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+        let data = Arc::new(RwLock::new(SharedTestData::default()));
+        let server = handler_traits_async_server::Device {
+            gpio: handler_traits_async_server::GpioDriver { data: data.clone() },
+            channels: handler_traits_async_server::ChannelsDriver { data: data.clone() },
+        };
+        tokio::spawn(wire_weaver_client_common::loopback::loopback_worker(
+            transport_cmd_rx,
+            server,
+        ));
+
+        let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+        cmd_tx
+            .connect(
+                DeviceFilter::vhrd_usb_can(),
+                FullVersionOwned::new("test".into(), VersionOwned::new(0, 1, 0)),
+                OnError::ExitImmediately,
+            )
+            .await
+            .expect("connect");
+        let client = std_async_client::StdAsyncClient { cmd_tx };
+
+        client.gpio(5).set_high().call().await.unwrap();
+        assert_eq!(data.read().unwrap().gpio_used_indices, vec![5]);
+        assert!(client.gpio(8).set_high().call().await.is_err());
+
+        client
+            .periph(3)
+            .channel(7)
+            .write_gain(10.0)
+            .write()
+            .await
+            .unwrap();
+        let value = client
+            .periph(3)
+            .channel(7)
+            .read_gain()
+            .read()
+            .await
+            .unwrap();
+        assert_eq!(value, 10.0);
+
+        let err = client.g1().m1().call().await.unwrap_err();
+        assert!(format!("{err:?}").contains("OperationNotImplemented"));
+        assert!(!data.read().unwrap().subgroup_m1_called);
+    }
 }
@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod tests {
+    use dashboard_fixtures::{client_exchange, hex, value_cases};
+    use std::fmt::Write;
+    use std::path::Path;
+    use std::process::Command as Process;
+
+    /// TypeScript value literals, in the same order as value_cases()
+    const VALUE_LITERALS: [&str; 10] = [
+        r#"{ kind: "Idle" }"#,
+        r#"{ kind: "Run", rpm: 1500 }"#,
+        r#"{ kind: "Hold", _0: 100, _1: -7 }"#,
+        r#"{ mode: { kind: "Run", rpm: 3 }, enabled: true, temperature: 21.5, name: "left", counters: [1, 70000], raw: Uint8Array.of(0xde, 0xad), position: [-5, 9], calibration: [-1, 0, 300], fault: "overheat", uptime: 18446744073709551615n, offset: -2n, steps: 1000, phase: 9 }"#,
+        r#"{ mode: { kind: "Idle" }, enabled: false, temperature: null, name: "Ω", counters: [], raw: new Uint8Array(0), position: [2147483647, 0], calibration: [0, 0, 0], fault: null, uptime: 0n, offset: -9223372036854775808n, steps: 0, phase: 0 }"#,
+        "{ current: { start: 10, end: 2000 }, voltage: -3.25, torque: -128 }",
+        r#"{ kind: "OutOfRange", reason: "too high" }"#,
+        "{ current: -100000, healthy: { err: 513 } }",
+        r#"{ kind: "Label", _0: "go" }"#,
+        r#"{ kind: "Sequence", _0: [{ kind: "Idle" }, { kind: "Hold", _0: 1, _1: 15 }] }"#,
+    ];
+
+    /// Requests sent by the generated client are compared to the ones created in Rust and events created in Rust are
+    /// fed back to it.
+    fn client_script() -> String {
+        let exchange = client_exchange();
+        let mut ts = String::new();
+        writeln!(
+            ts,
+            r#"
+const sent: Uint8Array[] = [];
+const ww = new WwClient((bytes) => {{
+    sent.push(bytes);
+}});
+const dashboard = new DashboardClient(ww);
+
+const stopped = dashboard.motor(1).stop(true);
+check("stop request", sent[0], "{}");
+ww.handleEvent(fromHex("{}"));
+same("stop result", await stopped, 300);
+
+const configured = dashboard.configure(2, {{ current: {{ start: 1, end: 5 }}, voltage: 12.0, torque: 3 }});
+check("configure request", sent[1], "{}");
+ww.handleEvent(fromHex("{}"));
+same("configure result", await configured, {{ err: {{ kind: "BadChannel" }} }});
+
+const speed = dashboard.readSpeed();
+check("read request", sent[2], "{}");
+ww.handleEvent(fromHex("{}"));
+try {{
+    await speed;
+    same("read error", "resolved", "rejected");
+}} catch (e) {{
+    const err = e as WwRemoteError;
+    same("read error", [err.kind, err.errSeq], ["BadPath", 7]);
+}}
+
+const received: Telemetry[] = [];
+const opened = dashboard.openStreamTelemetry((value) => received.push(value));
+check("open request", sent[3], "{}");
+ww.handleEvent(fromHex("{}"));
+await opened;
+ww.handleEvent(fromHex("{}"));
+same("telemetry", received, [{{ current: 1, healthy: {{ ok: 2 }} }}]);
+
+const logs: Uint8Array[] = [];
+const logOpened = dashboard.openStreamLog((value) => logs.push(value));
+ww.handleEvent(fromHex("{}"));
+await logOpened;
+ww.handleEvent(fromHex("{}"));
+same("log", logs, [Uint8Array.of(1, 2, 3)]);
+
+dashboard.writeCommands({{ kind: "Label", _0: "go" }});
+check("sink write", sent[5], "{}");"#,
+            exchange.stop_request,
+            exchange.stop_return,
+            exchange.configure_request,
+            exchange.configure_return,
+            exchange.read_request,
+            exchange.read_error,
+            exchange.telemetry_open_request,
+            exchange.telemetry_opened,
+            exchange.telemetry_data,
+            exchange.log_opened,
+            exchange.log_data,
+            exchange.commands_write,
+        )
+        .unwrap();
+        ts
+    }
+
+    fn golden_script() -> String {
+        let mut ts = String::new();
+        ts.push_str(
+            r#"import {
+    DashboardClient,
+    WwClient,
+    WwRemoteError,
+    encodeMode, decodeMode,
+    encodeStatus, decodeStatus,
+    encodeLimits, decodeLimits,
+    encodeConfigError, decodeConfigError,
+    encodeTelemetry, decodeTelemetry,
+    encodeCommand, decodeCommand,
+} from "./dashboard.mts";
+import type { Telemetry } from "./dashboard.mts";
+
+let failed = 0;
+
+function hex(bytes: Uint8Array): string {
+    return Array.from(bytes, (b) => b.toString(16).padStart(2, "0")).join(" ");
+}
+
+function fromHex(s: string): Uint8Array {
+    return Uint8Array.from(s.split(" ").filter((b) => b.length > 0), (b) => parseInt(b, 16));
+}
+
+function show(value: unknown): string {
+    return JSON.stringify(value, (_, v) => {
+        if (typeof v === "bigint") {
+            return `${v}n`;
+        }
+        return v instanceof Uint8Array ? `bytes(${hex(v)})` : v;
+    });
+}
+
+function check(name: string, actual: Uint8Array, expected: string): void {
+    if (hex(actual) !== expected) {
+        console.error(`${name}: expected [${expected}], got [${hex(actual)}]`);
+        failed += 1;
+    }
+}
+
+function same(name: string, actual: unknown, expected: unknown): void {
+    if (show(actual) !== show(expected)) {
+        console.error(`${name}: expected ${show(expected)}, got ${show(actual)}`);
+        failed += 1;
+    }
+}
+"#,
+        );
+        let cases = value_cases();
+        assert_eq!(cases.len(), VALUE_LITERALS.len());
+        for (idx, ((ty, bytes), literal)) in cases.iter().zip(VALUE_LITERALS).enumerate() {
+            let bytes = hex(bytes);
+            writeln!(
+                ts,
+                "check(\"{ty} #{idx}\", encode{ty}({literal}), \"{bytes}\");"
+            )
+            .unwrap();
+            writeln!(
+                ts,
+                "same(\"{ty} #{idx} decoded\", decode{ty}(fromHex(\"{bytes}\")), {literal});"
+            )
+            .unwrap();
+        }
+        ts.push_str(&client_script());
+        ts.push_str(
+            r#"
+if (failed > 0) {
+    process.exit(1);
+}
+"#,
+        );
+        ts
+    }
+
+    fn gen_dashboard() -> String {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../typescript_api");
+        let api_bundle =
+            wire_weaver_core::load(&crate_path, Some("Dashboard".into()), true).unwrap();
+        wire_weaver_core::codegen::typescript::gen_typescript(&api_bundle).unwrap()
+    }
+
+    /// Runs without Node.js, checks that everything golden.mts relies on is generated.
+    #[test]
+    fn generated_source() {
+        let ts = gen_dashboard();
+        assert!(ts.contains(r#"export const WW_API_NAME = "typescript_api::Dashboard v0.1.0";"#));
+        for (ty, _) in value_cases() {
+            assert!(
+                ts.contains(&format!(
+                    "export function encode{ty}(value: {ty}): Uint8Array {{"
+                )),
+                "encode{ty}"
+            );
+            assert!(
+                ts.contains(&format!(
+                    "export function decode{ty}(bytes: Uint8Array): {ty} {{"
+                )),
+                "decode{ty}"
+            );
+        }
+        for line in [
+            "export class DashboardClient {",
+            "    static async connect(url: string, options: WwClientOptions = {}): Promise<DashboardClient> {",
+            "    async setMode(mode: Mode): Promise<void> {",
+            "    async status(): Promise<Status> {",
+            "    async configure(channel: number, limits: Limits): Promise<{ ok: number } | { err: ConfigError }> {",
+            "    async readSpeed(): Promise<number> {",
+            "    async writeSpeed(value: number): Promise<void> {",
+            "    async readSerial(): Promise<bigint> {",
+            "    openStreamTelemetry(onValue: (value: Telemetry) => void): Promise<void> {",
+            "    closeStreamTelemetry(): Promise<void> {",
+            "    openStreamLog(onValue: (value: Uint8Array) => void): Promise<void> {",
+            "    openSinkCommands(): Promise<void> {",
+            "    writeCommands(value: Command): void {",
+            "    closeSinkCommands(): Promise<void> {",
+            "    motor(index: number): MotorClient {",
+            "export class MotorClient {",
+            "    async readTarget(): Promise<number> {",
+            "    async writeTarget(value: number): Promise<void> {",
+            "    async stop(brake: boolean): Promise<number | null> {",
+        ] {
+            assert!(ts.contains(line), "missing: {line}");
+        }
+    }
+
+    #[test]
+    #[ignore = "requires Node.js 22.6+, set WW_NODE to its path (default: node) and run `just test-typescript`"]
+    fn golden() {
+        let dashboard = gen_dashboard();
+
+        let dir = std::env::temp_dir().join(format!("ww_typescript_golden_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("dashboard.mts"), dashboard).unwrap();
+        std::fs::write(dir.join("golden.mts"), golden_script()).unwrap();
+
+        // Node.js 22.6+ can run TypeScript directly
+        let node = std::env::var("WW_NODE").unwrap_or_else(|_| "node".into());
+        let output = Process::new(&node)
+            .args(["--experimental-strip-types", "--no-warnings", "golden.mts"])
+            .current_dir(&dir)
+            .output()
+            .unwrap_or_else(|e| panic!("failed to run {node}: {e}"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(
+            output.status.success(),
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
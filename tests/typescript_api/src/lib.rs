@@ -0,0 +1,90 @@
+use wire_weaver::prelude::*;
+
+/// Motor controller dashboard.
+#[ww_trait]
+trait Dashboard {
+    fn set_mode(mode: Mode);
+    fn status() -> Status<'i>;
+    fn configure(channel: u8, limits: Limits) -> Result<u32, ConfigError<'i>>;
+    property!(rw speed: i16);
+    property!(ro serial: u64);
+    stream!(telemetry: Telemetry);
+    stream!(log: [u8]);
+    sink!(commands: Command<'i>);
+    ww_impl!(motor[]: Motor);
+}
+
+#[ww_trait]
+trait Motor {
+    property!(rw target: f32);
+    fn stop(brake: bool) -> Option<u16>;
+}
+
+/// Operating mode.
+#[derive_shrink_wrap]
+#[ww_repr(u4)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mode {
+    Idle,
+    /// Spin at a constant speed.
+    Run {
+        rpm: u16,
+    },
+    Hold(U7, I5),
+}
+
+#[derive_shrink_wrap]
+#[derive(Clone, Debug, PartialEq)]
+#[owned = "std"]
+pub struct Status<'i> {
+    pub mode: Mode,
+    pub enabled: bool,
+    pub temperature: Option<f32>,
+    #[flag]
+    pub fault: bool,
+    pub name: &'i str,
+    pub counters: RefVec<'i, u32>,
+    pub raw: RefVec<'i, u8>,
+    pub position: (i32, u8),
+    pub calibration: [i16; 3],
+    pub fault: Option<&'i str>,
+    pub uptime: u64,
+    pub offset: i64,
+    pub steps: UNib32,
+    pub phase: Nibble,
+}
+
+#[derive_shrink_wrap]
+#[sized]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Limits {
+    pub current: Range<u16>,
+    pub voltage: f64,
+    pub torque: i8,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(unib32)]
+#[derive(Clone, Debug, PartialEq)]
+#[owned = "std"]
+pub enum ConfigError<'i> {
+    BadChannel,
+    OutOfRange { reason: &'i str },
+}
+
+#[derive_shrink_wrap]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Telemetry {
+    pub current: i32,
+    pub healthy: Result<u8, u16>,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(u8)]
+#[derive(Clone, Debug, PartialEq)]
+#[owned = "std"]
+pub enum Command<'i> {
+    Beep,
+    Label(&'i str),
+    Sequence(RefVec<'i, Mode>),
+}
@@ -0,0 +1,518 @@
+//! C ABI over wire_weaver_client_common transports, for C++ clients generated with `ww api cpp`.
+//!
+//! Generated code serializes ww_client_server requests itself, the shim only forwards them to a device event loop and
+//! calls back with serialized events. Requests are sent through the event loop with its own seq numbers and responses
+//! are passed back with the original ones, same as the IPC daemon does, so that they never mix with stream events.
+//!
+//! Declarations of these functions and a wrapper around them (`ww::ShimConnection`) are part of every generated header.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::net::ToSocketAddrs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use wire_weaver::prelude::*;
+use wire_weaver::shrink_wrap::to_ww_vec;
+use wire_weaver_client_common::device_filter::DeviceFilterKind;
+use wire_weaver_client_common::rx_dispatcher::response_channel;
+use wire_weaver_client_common::{Command, CommandSender, DeviceFilter, Error, OnError, TraceEvent};
+use ww_client_server::{Event, EventKindOwned, EventOwned, Request, RequestKind};
+use ww_version::{FullVersionOwned, VersionOwned};
+
+pub type WwShimEventFn = extern "C" fn(user_data: *mut c_void, bytes: *const u8, len: usize);
+pub type WwShimDisconnectFn = extern "C" fn(user_data: *mut c_void, reason: *const c_char);
+
+const MAX_EVENT_LEN: usize = 16 * 1024 * 1024;
+
+/// Connection to one device, created with [ww_shim_connect] and destroyed with [ww_shim_close].
+pub struct WwShim {
+    runtime: Runtime,
+    cmd_tx: CommandSender,
+    callbacks: Arc<Callbacks>,
+    timeout: Duration,
+}
+
+struct Callbacks {
+    on_event: WwShimEventFn,
+    on_disconnect: WwShimDisconnectFn,
+    user_data: *mut c_void,
+    /// Set before closing, no callbacks are made after that.
+    closed: AtomicBool,
+}
+
+// SAFETY: user_data is only passed back to the callbacks, which must be thread safe as documented in ww_shim_connect.
+unsafe impl Send for Callbacks {}
+unsafe impl Sync for Callbacks {}
+
+impl Callbacks {
+    fn event(&self, bytes: &[u8]) {
+        if !self.closed.load(Ordering::Acquire) {
+            (self.on_event)(self.user_data, bytes.as_ptr(), bytes.len());
+        }
+    }
+
+    fn disconnect(&self, reason: &str) {
+        if self.closed.load(Ordering::Acquire) {
+            return;
+        }
+        let reason = CString::new(reason.replace('\0', " ")).unwrap_or_default();
+        (self.on_disconnect)(self.user_data, reason.as_ptr());
+    }
+}
+
+/// Which ww_client_server event a device sends for a request, responses only carry data.
+enum Reply {
+    ReturnValue,
+    ReadValue,
+    Written,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    LAST_ERROR.with(|e| *e.borrow_mut() = message.into());
+}
+
+/// Parses "ws://host:port/path", "usb:vid:pid" or "usb:vid:pid:serial" (hexadecimal vid and pid).
+fn parse_filter(filter: &str) -> Result<DeviceFilter, String> {
+    let kind = if let Some(rest) = filter.strip_prefix("ws://") {
+        let (host_port, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let addr = host_port
+            .to_socket_addrs()
+            .map_err(|e| format!("{host_port}: {e}"))?
+            .next()
+            .ok_or_else(|| format!("{host_port}: no addresses"))?;
+        DeviceFilterKind::WebSocket {
+            addr: addr.ip(),
+            port: addr.port(),
+            path: path.to_string(),
+        }
+    } else if let Some(rest) = filter.strip_prefix("usb:") {
+        let mut parts = rest.splitn(3, ':');
+        let mut hex = |name: &str| {
+            let part = parts.next().unwrap_or_default();
+            u16::from_str_radix(part.trim_start_matches("0x"), 16)
+                .map_err(|_| format!("invalid {name} '{part}', expected hexadecimal u16"))
+        };
+        let vid = hex("vid")?;
+        let pid = hex("pid")?;
+        match parts.next() {
+            Some(serial) => DeviceFilterKind::UsbVidPidAndSerial {
+                vid,
+                pid,
+                serial: serial.to_string(),
+            },
+            None => DeviceFilterKind::UsbVidPid { vid, pid },
+        }
+    } else {
+        return Err(format!(
+            "unsupported filter '{filter}', expected ws://host:port/path or usb:vid:pid[:serial]"
+        ));
+    };
+    Ok(DeviceFilter {
+        kind,
+        allow_ipc: true,
+    })
+}
+
+fn start_worker(runtime: &Runtime, filter: &DeviceFilter) -> Result<CommandSender, String> {
+    let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+    match filter.kind {
+        DeviceFilterKind::WebSocket { .. } => {
+            runtime.spawn(wire_weaver_net_host::ws_worker(transport_cmd_rx));
+        }
+        #[cfg(feature = "usb")]
+        _ => {
+            runtime.spawn(wire_weaver_usb_host::usb_worker(transport_cmd_rx));
+        }
+        #[cfg(not(feature = "usb"))]
+        _ => return Err("USB support is not enabled (usb feature)".into()),
+    }
+    Ok(CommandSender::new(transport_cmd_tx))
+}
+
+fn connect(
+    filter: &str,
+    version: FullVersionOwned,
+    timeout: Duration,
+    callbacks: Arc<Callbacks>,
+) -> Result<WwShim, String> {
+    let filter = parse_filter(filter)?;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("ww_shim")
+        .enable_all()
+        .build()
+        .map_err(|e| format!("failed to start runtime: {e}"))?;
+    let mut cmd_tx = start_worker(&runtime, &filter)?;
+
+    let (trace_event_tx, mut trace_rx) = mpsc::unbounded_channel();
+    cmd_tx
+        .send(Command::RegisterTracer { trace_event_tx })
+        .map_err(|e| format!("{e:?}"))?;
+    runtime.block_on(async {
+        tokio::time::timeout(
+            timeout,
+            cmd_tx.connect(filter, version, OnError::ExitImmediately),
+        )
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|e| format!("{e:?}"))
+    })?;
+
+    // responses are passed back by forwarded requests, only stream and other events not tied to a request are passed here
+    let forward = callbacks.clone();
+    runtime.spawn(async move {
+        while let Some(trace_event) = trace_rx.recv().await {
+            match trace_event {
+                TraceEvent::Event { bytes } => {
+                    if matches!(Event::from_ww_bytes(&bytes), Ok(Event { seq: 0, .. })) {
+                        forward.event(&bytes);
+                    }
+                }
+                TraceEvent::Disconnected { reason, .. } => forward.disconnect(&reason),
+                _ => {}
+            }
+        }
+    });
+    Ok(WwShim {
+        runtime,
+        cmd_tx,
+        callbacks,
+        timeout,
+    })
+}
+
+impl WwShim {
+    fn send(&self, bytes: Vec<u8>) -> Result<(), String> {
+        let (seq, reply) = {
+            let request =
+                Request::from_ww_bytes(&bytes).map_err(|e| format!("malformed request: {e:?}"))?;
+            let reply = match request.kind {
+                RequestKind::Read => Reply::ReadValue,
+                RequestKind::Write { .. } => Reply::Written,
+                _ => Reply::ReturnValue,
+            };
+            (request.seq, reply)
+        };
+        if seq == 0 {
+            return self
+                .cmd_tx
+                .send(Command::SendMessage {
+                    bytes,
+                    done_tx: None,
+                })
+                .map_err(|e| format!("{e:?}"));
+        }
+        let (done_tx, done_rx, _) = response_channel();
+        self.cmd_tx
+            .send(Command::SendMessage {
+                bytes,
+                done_tx: Some((done_tx, self.timeout)),
+            })
+            .map_err(|e| format!("{e:?}"))?;
+        let callbacks = self.callbacks.clone();
+        self.runtime.spawn(async move {
+            let result = match done_rx.await {
+                Ok(Ok(data)) => Ok(match reply {
+                    Reply::ReturnValue => EventKindOwned::ReturnValue { data },
+                    Reply::ReadValue => EventKindOwned::ReadValue { data },
+                    Reply::Written => EventKindOwned::Written,
+                }),
+                Ok(Err(Error::RemoteError(e))) => Err(e),
+                // timeouts and disconnects are handled by a client itself
+                _ => return,
+            };
+            if let Ok(bytes) = to_ww_vec(&EventOwned { seq, result }, MAX_EVENT_LEN) {
+                callbacks.event(&bytes);
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Connect to a device, blocking for up to `timeout_ms`. Returns null on error, see [ww_shim_last_error].
+///
+/// Events are passed to `on_event` and a lost connection is reported to `on_disconnect`, both are called from a
+/// background thread until [ww_shim_close] returns.
+///
+/// # Safety
+/// `filter` and `crate_id` must be valid NUL-terminated strings, callbacks must be safe to call from another thread
+/// with `user_data`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ww_shim_connect(
+    filter: *const c_char,
+    crate_id: *const c_char,
+    major: u32,
+    minor: u32,
+    patch: u32,
+    timeout_ms: u32,
+    on_event: WwShimEventFn,
+    on_disconnect: WwShimDisconnectFn,
+    user_data: *mut c_void,
+) -> *mut WwShim {
+    if filter.is_null() || crate_id.is_null() {
+        set_last_error("filter and crate_id must not be null");
+        return std::ptr::null_mut();
+    }
+    // SAFETY: both are valid C strings as required by the caller
+    let (filter, crate_id) = unsafe { (CStr::from_ptr(filter), CStr::from_ptr(crate_id)) };
+    let version = FullVersionOwned::new(
+        crate_id.to_string_lossy().into_owned(),
+        VersionOwned::new(major, minor, patch),
+    );
+    let callbacks = Arc::new(Callbacks {
+        on_event,
+        on_disconnect,
+        user_data,
+        closed: AtomicBool::new(false),
+    });
+    match connect(
+        &filter.to_string_lossy(),
+        version,
+        Duration::from_millis(timeout_ms as u64),
+        callbacks,
+    ) {
+        Ok(shim) => Box::into_raw(Box::new(shim)),
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Send one serialized ww_client_server request. Returns 0 on success and -1 on error, see [ww_shim_last_error].
+///
+/// # Safety
+/// `shim` must be returned by [ww_shim_connect] and not closed, `bytes` must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ww_shim_send(shim: *mut WwShim, bytes: *const u8, len: usize) -> i32 {
+    if shim.is_null() || (bytes.is_null() && len > 0) {
+        set_last_error("shim and bytes must not be null");
+        return -1;
+    }
+    // SAFETY: guaranteed by the caller
+    let (shim, bytes) = unsafe {
+        let bytes = if len == 0 {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(bytes, len)
+        };
+        (&*shim, bytes)
+    };
+    match shim.send(bytes.to_vec()) {
+        Ok(()) => 0,
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Disconnect from a device and free the connection, no callbacks are made after this function returns.
+///
+/// # Safety
+/// `shim` must be returned by [ww_shim_connect] and not used afterward, must not be called from a callback.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ww_shim_close(shim: *mut WwShim) {
+    if shim.is_null() {
+        return;
+    }
+    // SAFETY: guaranteed by the caller
+    let shim = unsafe { Box::from_raw(shim) };
+    shim.callbacks.closed.store(true, Ordering::Release);
+    let WwShim {
+        runtime, cmd_tx, ..
+    } = *shim;
+    runtime.block_on(async {
+        _ = tokio::time::timeout(Duration::from_secs(1), cmd_tx.disconnect()).await;
+    });
+    runtime.shutdown_timeout(Duration::from_secs(1));
+}
+
+/// Copy the last error message of the calling thread into `buf` as a NUL-terminated string, truncating if needed.
+/// Returns the full length of the message, without the terminator.
+///
+/// # Safety
+/// `buf` must point to `len` writable bytes or be null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ww_shim_last_error(buf: *mut c_char, len: usize) -> usize {
+    LAST_ERROR.with(|e| {
+        let message = e.borrow();
+        if !buf.is_null() && len > 0 {
+            let n = message.len().min(len - 1);
+            // SAFETY: buf has at least len bytes as required by the caller and n < len
+            unsafe {
+                std::ptr::copy_nonoverlapping(message.as_ptr(), buf as *mut u8, n);
+                *buf.add(n) = 0;
+            }
+        }
+        message.len()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::mpsc as std_mpsc;
+
+    /// user_data is a `std_mpsc::Sender<Vec<u8>>` owned by a test
+    extern "C" fn on_event(user_data: *mut c_void, bytes: *const u8, len: usize) {
+        let events_tx = unsafe { &*(user_data as *const std_mpsc::Sender<Vec<u8>>) };
+        let bytes = unsafe { std::slice::from_raw_parts(bytes, len) }.to_vec();
+        _ = events_tx.send(bytes);
+    }
+
+    extern "C" fn on_disconnect(_user_data: *mut c_void, _reason: *const c_char) {}
+
+    /// Serve a mock device over WebSocket on a separate thread and connect a shim to it.
+    fn connect_to_mock(
+        crate_name: &str,
+        trait_name: &str,
+        script: wire_weaver_mock::Script,
+        events_tx: &std_mpsc::Sender<Vec<u8>>,
+    ) -> *mut WwShim {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests")
+            .join(crate_name);
+        let server = wire_weaver_mock::MockServer::from_crate(&crate_path, Some(trait_name.into()))
+            .unwrap()
+            .with_script(script);
+        let version = server.user_api_version().unwrap().clone();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || runtime.block_on(wire_weaver_mock::serve_ws(listener, server)));
+
+        let filter = CString::new(format!("ws://127.0.0.1:{port}/")).unwrap();
+        let crate_id = CString::new(version.crate_id.clone()).unwrap();
+        let v = &version.version;
+        let shim = unsafe {
+            ww_shim_connect(
+                filter.as_ptr(),
+                crate_id.as_ptr(),
+                v.major.0,
+                v.minor.0,
+                v.patch.0,
+                2000,
+                on_event,
+                on_disconnect,
+                events_tx as *const _ as *mut c_void,
+            )
+        };
+        assert!(!shim.is_null());
+        shim
+    }
+
+    fn send(shim: *mut WwShim, request: &Request) -> i32 {
+        let request = to_ww_vec(request, MAX_EVENT_LEN).unwrap();
+        unsafe { ww_shim_send(shim, request.as_ptr(), request.len()) }
+    }
+
+    #[test]
+    fn parse_filters() {
+        let filter = parse_filter("ws://127.0.0.1:8080/ws").unwrap();
+        assert!(matches!(
+            filter.kind,
+            DeviceFilterKind::WebSocket { port: 8080, ref path, .. } if path == "ws"
+        ));
+        let filter = parse_filter("ws://127.0.0.1:80").unwrap();
+        assert!(matches!(
+            filter.kind,
+            DeviceFilterKind::WebSocket { port: 80, ref path, .. } if path.is_empty()
+        ));
+        let filter = parse_filter("usb:0xc0de:cafe").unwrap();
+        assert!(matches!(
+            filter.kind,
+            DeviceFilterKind::UsbVidPid {
+                vid: 0xc0de,
+                pid: 0xcafe
+            }
+        ));
+        let filter = parse_filter("usb:c0de:CAFE:A1").unwrap();
+        assert!(matches!(
+            filter.kind,
+            DeviceFilterKind::UsbVidPidAndSerial { vid: 0xc0de, pid: 0xcafe, ref serial } if serial == "A1"
+        ));
+        assert!(filter.allow_ipc);
+        assert!(parse_filter("usb:c0de").is_err());
+        assert!(parse_filter("usb:c0de:xyz").is_err());
+        assert!(parse_filter("usb:1c0de:cafe").is_err());
+        assert!(parse_filter("tcp://127.0.0.1:1").is_err());
+    }
+
+    #[test]
+    fn call_over_web_socket() {
+        let (events_tx, events_rx) = std_mpsc::channel();
+        let shim = connect_to_mock("methods_api", "Methods", Default::default(), &events_tx);
+
+        // plain_return() with seq 7
+        let path = [UNib32(2)];
+        let request = Request {
+            seq: 7,
+            path_kind: ww_client_server::PathKind::absolute(&path),
+            kind: RequestKind::Call {
+                args: RefVec::new_bytes(&[]),
+            },
+        };
+        assert_eq!(send(shim, &request), 0);
+        let event = events_rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        let event = Event::from_ww_bytes(&event).unwrap();
+        assert_eq!(event.seq, 7);
+        assert!(event.result.is_ok());
+
+        assert_eq!(unsafe { ww_shim_send(shim, [0xff].as_ptr(), 1) }, -1);
+        let mut buf = [0 as c_char; 64];
+        let len = unsafe { ww_shim_last_error(buf.as_mut_ptr(), buf.len()) };
+        assert!(len > 0);
+        unsafe { ww_shim_close(shim) };
+    }
+
+    #[test]
+    fn stream_events_are_forwarded() {
+        let script = wire_weaver_mock::Script::from_ron_str(
+            r#"(streams: [(path: "plain_stream", values: [Numeric(U8(0xAA))], interval_ms: 1)])"#,
+        )
+        .unwrap();
+        let (events_tx, events_rx) = std_mpsc::channel();
+        let shim = connect_to_mock("streams_api", "Streams", script, &events_tx);
+
+        // opened without waiting for a response, Opened and stream updates are not tied to a request
+        let path = [UNib32(0)];
+        let request = Request {
+            seq: 0,
+            path_kind: ww_client_server::PathKind::absolute(&path),
+            kind: RequestKind::StreamSideband {
+                sideband_cmd: ww_client_server::StreamSidebandCommand::Open,
+            },
+        };
+        assert_eq!(send(shim, &request), 0);
+        let data = loop {
+            let event = events_rx.recv_timeout(Duration::from_secs(2)).unwrap();
+            let event = EventOwned::from_ww_bytes_owned(&event).unwrap();
+            assert_eq!(event.seq, 0);
+            match event.result {
+                Ok(EventKindOwned::StreamData { path, data }) => {
+                    assert_eq!(path, [UNib32(0)]);
+                    break data;
+                }
+                Ok(EventKindOwned::StreamSideband { .. }) => {}
+                other => panic!("unexpected event {other:?}"),
+            }
+        };
+        assert_eq!(data, [0xAA]);
+        unsafe { ww_shim_close(shim) };
+    }
+}
@@ -1,5 +1,7 @@
 use crate::cmd::api::ApiCommand;
+use crate::util::parse_hex_u16;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 #[command(propagate_version = true)]
@@ -40,6 +42,45 @@ pub(crate) enum Commands {
 
     Introspect,
 
+    /// Convert a capture file (see CommandSender::record_to_file) to pcapng, use 'ww api dissector' to view it in Wireshark
+    Pcapng {
+        /// Capture file
+        capture: PathBuf,
+
+        /// Output pcapng file
+        output: PathBuf,
+    },
+
+    /// Run a mock device, answering requests with default values or scripted responses, accessible over WebSocket
+    Mock {
+        /// Path to crate which defines ww_trait, API bundle RON file or a crate name to be found in ~/.wire_weaver
+        api: String,
+
+        /// Optional trait name if more than one is present
+        #[arg(long)]
+        name: Option<String>,
+
+        /// RON or JSON file with scripted responses, see wire_weaver_mock::Script
+        #[arg(short, long)]
+        script: Option<PathBuf>,
+
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+    },
+
+    /// Own a USB device and share it with other processes over a local socket.
+    /// Clients connect through it, when the same DeviceFilter is used with allow_ipc set.
+    #[cfg(unix)]
+    Daemon {
+        /// USB vendor ID (hex)
+        #[arg(long, default_value = "c0de", value_parser = parse_hex_u16)]
+        vid: u16,
+
+        /// USB product ID (hex)
+        #[arg(long, default_value = "cafe", value_parser = parse_hex_u16)]
+        pid: u16,
+    },
+
     /// Print udev rule to the stdout, run 'ww udev --help' for more information
     ///
     /// Create udev rule:
@@ -59,6 +100,10 @@ impl Cli {
             Commands::USBLoopback { .. } => true,
             Commands::Api(_) => false,
             Commands::Introspect => true,
+            Commands::Pcapng { .. } => false,
+            Commands::Mock { .. } => false,
+            #[cfg(unix)]
+            Commands::Daemon { .. } => false,
             #[cfg(target_os = "linux")]
             Commands::Udev => false,
         }
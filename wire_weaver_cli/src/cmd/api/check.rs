@@ -0,0 +1,18 @@
+use anyhow::{Result, anyhow};
+use std::path::PathBuf;
+use wire_weaver_core::evolution::check_api_evolution;
+use wire_weaver_core::load;
+
+pub(crate) fn check(old: PathBuf, new: PathBuf, trait_name: Option<String>) -> Result<()> {
+    let old_bundle = load(&old, trait_name.clone(), false)?;
+    let new_bundle = load(&new, trait_name, false)?;
+    let issues = check_api_evolution(&old_bundle, &new_bundle)?;
+    if issues.is_empty() {
+        println!("No evolution issues found");
+        return Ok(());
+    }
+    for issue in &issues {
+        println!("{issue}");
+    }
+    Err(anyhow!("{} evolution issue(s) found", issues.len()))
+}
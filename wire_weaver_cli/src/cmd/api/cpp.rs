@@ -0,0 +1,18 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use wire_weaver_core::codegen::cpp::gen_cpp;
+use wire_weaver_core::load;
+
+pub(crate) fn cpp(
+    crate_path: PathBuf,
+    trait_name: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let api_bundle = load(&crate_path, trait_name, false)?;
+    let cpp = gen_cpp(&api_bundle)?;
+    match output {
+        Some(output) => std::fs::write(output, cpp)?,
+        None => print!("{cpp}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use wire_weaver_core::codegen::wireshark::gen_wireshark_dissector;
+use wire_weaver_core::load;
+
+pub(crate) fn dissector(
+    crate_path: PathBuf,
+    trait_name: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let api_bundle = load(&crate_path, trait_name, false)?;
+    let lua = gen_wireshark_dissector(&api_bundle)?;
+    match output {
+        Some(output) => std::fs::write(output, lua)?,
+        None => print!("{lua}"),
+    }
+    Ok(())
+}
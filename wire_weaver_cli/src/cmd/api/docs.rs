@@ -0,0 +1,36 @@
+use anyhow::Result;
+use shrink_wrap::{UNib32, to_ww_vec};
+use std::path::PathBuf;
+use wire_weaver_core::codegen::docs_site::{ExampleRequest, write_docs_site};
+use wire_weaver_core::load;
+use ww_client_server::{PathKindOwned, RequestKindOwned, RequestOwned, StreamSidebandCommand};
+
+pub(crate) fn docs(crate_path: PathBuf, trait_name: Option<String>, output: PathBuf) -> Result<()> {
+    let api_bundle = load(&crate_path, trait_name, false)?;
+    write_docs_site(&api_bundle, ser_request, &output)?;
+    println!(
+        "API docs written to {}",
+        output.join("index.html").display()
+    );
+    Ok(())
+}
+
+fn ser_request(path: &[u32], request: ExampleRequest) -> Result<Vec<u8>> {
+    let kind = match request {
+        ExampleRequest::Call { args } => RequestKindOwned::Call { args },
+        ExampleRequest::Read => RequestKindOwned::Read,
+        ExampleRequest::Write { data } => RequestKindOwned::Write { data },
+        ExampleRequest::Subscribe => RequestKindOwned::Subscribe,
+        ExampleRequest::OpenStream => RequestKindOwned::StreamSideband {
+            sideband_cmd: StreamSidebandCommand::Open,
+        },
+    };
+    let request = RequestOwned {
+        seq: 1,
+        path_kind: PathKindOwned::Absolute {
+            path: path.iter().map(|id| UNib32(*id)).collect(),
+        },
+        kind,
+    };
+    Ok(to_ww_vec(&request, 1024)?)
+}
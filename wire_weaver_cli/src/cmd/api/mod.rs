@@ -1,12 +1,18 @@
-// mod server_methods;
-// mod tree_printer;
-
 mod ast;
+mod check;
+mod cpp;
+mod dissector;
+mod docs;
+mod report;
+mod server_methods;
+mod tree_printer;
+mod typescript;
 
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 
 use clap::Subcommand;
 use std::path::PathBuf;
+use wire_weaver_core::codegen::report::ReportFormat;
 
 #[derive(Subcommand)]
 pub enum ApiCommand {
@@ -27,6 +33,7 @@ pub enum ApiCommand {
         #[arg(short('d'), long)]
         skip_docs: bool,
     },
+    /// Print handler signatures that a server implementation has to provide
     ServerMethods {
         /// Path to crate which defines ww_trait
         path: PathBuf,
@@ -34,6 +41,116 @@ pub enum ApiCommand {
         /// Optional trait name if more than one is present
         #[arg(long)]
         name: Option<String>,
+
+        /// Which methods return immediately and which answer later, e.g. "move=deferred, _=immediate"
+        #[arg(long, default_value = "_=immediate")]
+        method_model: String,
+
+        /// Which properties use get/set handlers and which are struct fields, e.g. "_=value_on_changed".
+        /// Ignored with --handler-traits, properties always use get/set handlers then
+        #[arg(long, default_value = "_=get_set")]
+        property_model: String,
+
+        /// Generate no_std no-alloc compatible signatures
+        #[arg(long)]
+        no_alloc: bool,
+
+        /// Make all handlers async
+        #[arg(long)]
+        use_async: bool,
+
+        /// Print implementations of generated per-level handler traits instead of inherent methods
+        #[arg(long)]
+        handler_traits: bool,
+
+        /// Name of the user struct implementing the server
+        #[arg(long, default_value = "Server")]
+        server_struct: String,
+
+        /// Print signatures as JSON instead of Rust code
+        #[arg(long)]
+        json: bool,
+    },
+    /// Generate Wireshark Lua dissector, see `wire_weaver_client_common::pcapng`
+    Dissector {
+        /// Path to crate which defines ww_trait
+        path: PathBuf,
+
+        /// Optional trait name if more than one is present
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Write dissector to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export a report describing all resources, their IDs and types, see docs/api/addressing.md
+    Report {
+        /// Path to crate which defines ww_trait
+        path: PathBuf,
+
+        /// Optional trait name if more than one is present
+        #[arg(long)]
+        name: Option<String>,
+
+        /// md, html or json, guessed from the output file extension if not provided
+        #[arg(short, long)]
+        format: Option<ReportFormat>,
+
+        /// Write report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Generate a static HTML documentation site, with a page per trait, type definitions and example requests
+    Docs {
+        /// Path to crate which defines ww_trait
+        path: PathBuf,
+
+        /// Optional trait name if more than one is present
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Directory to put the site into, created if it does not exist
+        #[arg(short, long, default_value = "api_docs")]
+        output: PathBuf,
+    },
+    /// Generate a TypeScript module with types, codecs and a WebSocket client for browser dashboards
+    Typescript {
+        /// Path to crate which defines ww_trait
+        path: PathBuf,
+
+        /// Optional trait name if more than one is present
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Write TypeScript module to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Generate a header-only C++17 client with types and codecs, for C++ test software
+    Cpp {
+        /// Path to crate which defines ww_trait
+        path: PathBuf,
+
+        /// Optional trait name if more than one is present
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Write C++ header to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check that changes between two versions of an API follow the evolution rules, see docs/evolution/checker_tool.md
+    Check {
+        /// Path to the previously released version of the crate which defines ww_trait
+        old: PathBuf,
+
+        /// Path to the new version of the crate
+        new: PathBuf,
+
+        /// Optional trait name if more than one is present
+        #[arg(long)]
+        name: Option<String>,
     },
     /// Print AST
     Ast {
@@ -47,16 +164,46 @@ pub enum ApiCommand {
 }
 pub(crate) fn api(cmd: ApiCommand) -> Result<()> {
     match cmd {
-        // ApiCommand::Tree {
-        //     path,
-        //     name,
-        //     skip_reserved,
-        //     skip_docs,
-        // } => tree_printer::tree_printer(path, name, skip_reserved, skip_docs),
-        // ApiCommand::ServerMethods { path, name } => server_methods::server_methods(path, name),
-        ApiCommand::Tree { .. } | ApiCommand::ServerMethods { .. } => {
-            Err(anyhow!("Not implemented yet"))
-        }
+        ApiCommand::Tree {
+            path,
+            name,
+            skip_reserved,
+            skip_docs,
+        } => tree_printer::tree_printer(path, name, skip_reserved, skip_docs),
+        ApiCommand::ServerMethods {
+            path,
+            name,
+            method_model,
+            property_model,
+            no_alloc,
+            use_async,
+            handler_traits,
+            server_struct,
+            json,
+        } => server_methods::server_methods(
+            path,
+            name,
+            server_methods::ServerMethodsArgs {
+                method_model,
+                property_model,
+                no_alloc,
+                use_async,
+                handler_traits,
+                server_struct,
+                json,
+            },
+        ),
+        ApiCommand::Dissector { path, name, output } => dissector::dissector(path, name, output),
+        ApiCommand::Report {
+            path,
+            name,
+            format,
+            output,
+        } => report::report(path, name, format, output),
+        ApiCommand::Docs { path, name, output } => docs::docs(path, name, output),
+        ApiCommand::Typescript { path, name, output } => typescript::typescript(path, name, output),
+        ApiCommand::Cpp { path, name, output } => cpp::cpp(path, name, output),
+        ApiCommand::Check { old, new, name } => check::check(old, new, name),
         ApiCommand::Ast { path, name } => ast::print_ast(path, name),
     }
 }
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use wire_weaver_core::codegen::report::{ReportFormat, gen_api_report};
+use wire_weaver_core::load;
+
+pub(crate) fn report(
+    crate_path: PathBuf,
+    trait_name: Option<String>,
+    format: Option<ReportFormat>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let api_bundle = load(&crate_path, trait_name, false)?;
+    // guess format from the output file extension if not provided explicitly
+    let format = format
+        .or_else(|| {
+            output
+                .as_ref()
+                .and_then(|output| output.extension())
+                .and_then(|ext| ext.to_str()?.parse().ok())
+        })
+        .unwrap_or(ReportFormat::Markdown);
+    let report = gen_api_report(&api_bundle, format)?;
+    match output {
+        Some(output) => std::fs::write(output, report)?,
+        None => print!("{report}"),
+    }
+    Ok(())
+}
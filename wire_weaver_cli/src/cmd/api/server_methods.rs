@@ -1,41 +1,259 @@
-use crate::util;
+use anyhow::{Result, anyhow};
+use std::fmt::Write;
 use std::path::PathBuf;
-use wire_weaver_core::ast::api::{ApiItem, ApiItemKind};
-use wire_weaver_core::ast::visit::{Visit, visit_api_level};
-use wire_weaver_core::ast::visitors::IndexChain;
-
-pub fn server_methods(path: PathBuf, name: Option<String>) -> anyhow::Result<()> {
-    let level = util::load_level(path, name)?;
-    visit_api_level(
-        &level,
-        &mut ServerMethods {
-            ..Default::default()
-        },
-    );
+use wire_weaver_core::codegen::api_server::{HandlerKind, HandlerSignature, handler_signatures};
+use wire_weaver_core::load;
+use wire_weaver_core::prelude::{GenServerConfig, MethodModel, PropertyModel};
+
+pub(crate) struct ServerMethodsArgs {
+    pub(crate) method_model: String,
+    pub(crate) property_model: String,
+    pub(crate) no_alloc: bool,
+    pub(crate) use_async: bool,
+    pub(crate) handler_traits: bool,
+    pub(crate) server_struct: String,
+    pub(crate) json: bool,
+}
+
+pub(crate) fn server_methods(
+    crate_path: PathBuf,
+    trait_name: Option<String>,
+    args: ServerMethodsArgs,
+) -> Result<()> {
+    let api_bundle = load(&crate_path, trait_name, false)?;
+    let config = GenServerConfig {
+        no_alloc: args.no_alloc,
+        use_async: args.use_async,
+        method_model: MethodModel::parse(&args.method_model)
+            .map_err(|e| anyhow!("Invalid method model: {e}"))?,
+        property_model: PropertyModel::parse(&args.property_model)
+            .map_err(|e| anyhow!("Invalid property model: {e}"))?,
+        server_struct_path: args.server_struct.clone(),
+        generate_introspect: false,
+        handler_traits: args.handler_traits,
+    };
+    let signatures = handler_signatures(&api_bundle, &config)?;
+    if args.json {
+        println!("{}", render_json(&signatures)?);
+    } else {
+        print!("{}", render_rust(&signatures, &args.server_struct)?);
+    }
     Ok(())
 }
 
-#[derive(Default)]
-struct ServerMethods {
-    index_chain: IndexChain,
+fn render_json(signatures: &[HandlerSignature]) -> Result<String> {
+    let signatures: Vec<_> = signatures
+        .iter()
+        .map(|s| {
+            let kind = match s.kind {
+                HandlerKind::Handler => "handler",
+                HandlerKind::ValidIndices => "valid_indices",
+                HandlerKind::Field => "field",
+                HandlerKind::LevelType => "level_type",
+                HandlerKind::LevelAccessor => "level_accessor",
+            };
+            serde_json::json!({
+                "resource_path": s.resource_path,
+                "impl_target": s.impl_target,
+                "kind": kind,
+                "name": s.name,
+                "signature": s.signature,
+                "docs": s.docs,
+            })
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&signatures)?)
 }
 
-impl Visit for ServerMethods {
-    fn hook(&mut self) -> Option<&mut dyn Visit> {
-        Some(&mut self.index_chain)
+/// Handlers grouped into impl blocks, with `todo!()` bodies, ready to be pasted into a server implementation.
+fn render_rust(signatures: &[HandlerSignature], server_struct: &str) -> Result<String> {
+    let mut out = String::new();
+    let fields: Vec<_> = signatures
+        .iter()
+        .filter(|s| s.kind == HandlerKind::Field)
+        .collect();
+    if !fields.is_empty() {
+        writeln!(out, "struct {server_struct} {{")?;
+        for s in fields {
+            for line in &s.docs {
+                writeln!(out, "    ///{line}")?;
+            }
+            writeln!(out, "    {},", s.signature)?;
+        }
+        writeln!(out, "}}\n")?;
     }
 
-    fn visit_api_item(&mut self, item: &ApiItem) {
-        if !matches!(
-            item.kind,
-            ApiItemKind::Method { .. } | ApiItemKind::Property { .. } | ApiItemKind::Stream { .. }
-        ) {
-            return;
+    let mut impl_targets: Vec<&str> = vec![];
+    for s in signatures {
+        if s.kind != HandlerKind::Field && !impl_targets.contains(&s.impl_target.as_str()) {
+            impl_targets.push(&s.impl_target);
         }
-        println!(
-            "{} {:?}",
-            self.index_chain.flattened_name(),
-            self.index_chain.array_indices()
+    }
+    for impl_target in impl_targets {
+        if impl_target == server_struct {
+            writeln!(out, "impl {server_struct} {{")?;
+        } else {
+            writeln!(out, "impl {impl_target} for {server_struct} {{")?;
+        }
+        let mut items: Vec<String> = vec![];
+        for s in signatures.iter().filter(|s| s.impl_target == impl_target) {
+            let docs = s.docs.iter().map(|line| format!("    ///{line}\n"));
+            let docs: String = docs.collect();
+            match s.kind {
+                HandlerKind::Handler | HandlerKind::ValidIndices => {
+                    items.push(format!(
+                        "{docs}    {} {{\n        todo!()\n    }}",
+                        s.signature
+                    ));
+                }
+                HandlerKind::Field => {}
+                // all the traits are implemented on the same struct
+                HandlerKind::LevelType => items.push(format!("    type {} = Self;", s.name)),
+                HandlerKind::LevelAccessor => {
+                    let accessor = format!("\n    {} {{\n        self\n    }}", s.signature);
+                    match items.last_mut() {
+                        Some(level_type) => level_type.push_str(&accessor),
+                        None => items.push(accessor),
+                    }
+                }
+            }
+        }
+        writeln!(out, "{}", items.join("\n\n"))?;
+        writeln!(out, "}}\n")?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signatures(
+        method_model: &str,
+        property_model: &str,
+        handler_traits: bool,
+    ) -> Result<Vec<HandlerSignature>> {
+        let crate_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../tests/traits_api");
+        let api_bundle = load(&crate_path, Some("Traits".into()), true)?;
+        let config = GenServerConfig {
+            no_alloc: false,
+            use_async: false,
+            method_model: MethodModel::parse(method_model).map_err(|e| anyhow!(e))?,
+            property_model: PropertyModel::parse(property_model).map_err(|e| anyhow!(e))?,
+            server_struct_path: "Server".into(),
+            generate_introspect: false,
+            handler_traits,
+        };
+        handler_signatures(&api_bundle, &config)
+    }
+
+    #[test]
+    fn traits_api_json() {
+        let signatures = signatures(
+            "periph_channel_run=deferred, _=immediate",
+            "_=get_set",
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            render_json(&signatures).unwrap(),
+            r#"[
+  {
+    "docs": [
+      " Indices of `gpio` resources that can be accessed."
+    ],
+    "impl_target": "Server",
+    "kind": "valid_indices",
+    "name": "valid_indices_root_gpio",
+    "resource_path": "gpio[]",
+    "signature": "fn valid_indices_root_gpio(&mut self) -> wire_weaver::ValidIndices<'_>"
+  },
+  {
+    "docs": [
+      " Indices of `periph` resources that can be accessed."
+    ],
+    "impl_target": "Server",
+    "kind": "valid_indices",
+    "name": "valid_indices_root_periph",
+    "resource_path": "periph[]",
+    "signature": "fn valid_indices_root_periph(&mut self) -> wire_weaver::ValidIndices<'_>"
+  },
+  {
+    "docs": [],
+    "impl_target": "Server",
+    "kind": "handler",
+    "name": "g1_m1",
+    "resource_path": "g1.m1",
+    "signature": "fn g1_m1(&mut self, msg_tx: &mut impl wire_weaver::MessageSink)"
+  },
+  {
+    "docs": [],
+    "impl_target": "Server",
+    "kind": "handler",
+    "name": "gpio_set_high",
+    "resource_path": "gpio[].set_high",
+    "signature": "fn gpio_set_high(&mut self, msg_tx: &mut impl wire_weaver::MessageSink, index_chain: [UNib32; 1])"
+  },
+  {
+    "docs": [
+      " Indices of `channel` resources that can be accessed."
+    ],
+    "impl_target": "Server",
+    "kind": "valid_indices",
+    "name": "valid_indices_root_periph_channel",
+    "resource_path": "periph[].channel[]",
+    "signature": "fn valid_indices_root_periph_channel(&mut self, index_chain: [UNib32; 1]) -> wire_weaver::ValidIndices<'_>"
+  },
+  {
+    "docs": [],
+    "impl_target": "Server",
+    "kind": "handler",
+    "name": "get_periph_channel_gain",
+    "resource_path": "periph[].channel[].gain",
+    "signature": "fn get_periph_channel_gain(&mut self, index_chain: [UNib32; 2]) -> f32"
+  },
+  {
+    "docs": [],
+    "impl_target": "Server",
+    "kind": "handler",
+    "name": "set_periph_channel_gain",
+    "resource_path": "periph[].channel[].gain",
+    "signature": "fn set_periph_channel_gain(&mut self, index_chain: [UNib32; 2], value: f32)"
+  },
+  {
+    "docs": [
+      " Return None to answer later using request `seq`."
+    ],
+    "impl_target": "Server",
+    "kind": "handler",
+    "name": "periph_channel_run",
+    "resource_path": "periph[].channel[].run",
+    "signature": "fn periph_channel_run(&mut self, msg_tx: &mut impl wire_weaver::MessageSink, index_chain: [UNib32; 2], seq: u16) -> Option<()>"
+  }
+]"#
         );
     }
+
+    #[test]
+    fn value_on_changed_fields_and_handler_traits() {
+        let fields = signatures("_=immediate", "_=value_on_changed", false).unwrap();
+        let rust = render_rust(&fields, "Server").unwrap();
+        assert!(rust.starts_with(
+            "struct Server {\n    periph_channel_gain: Vec<f32>,\n}\n\nimpl Server {\n"
+        ));
+
+        let traits = signatures("_=immediate", "_=get_set", true).unwrap();
+        let rust = render_rust(&traits, "Server").unwrap();
+        assert!(rust.starts_with("impl TraitsServer for Server {\n"));
+        assert!(rust.contains("    type Periph = Self;\n    fn periph(&mut self) -> &mut Self::Periph {\n        self\n    }\n"));
+        assert!(rust.contains("impl PeriphChannelServer for Server {\n    fn get_gain(&mut self, index_chain: [UNib32; 2]) -> Result<f32, ww_client_server::ErrorKind<'static>> {\n        todo!()\n    }\n"));
+    }
+
+    #[test]
+    fn model_without_default_is_an_error() {
+        let e = signatures("_=immediate", "foo=get_set", false).unwrap_err();
+        assert!(e.to_string().contains("no match for gain"), "{e}");
+        let e = signatures("g1_m1=deferred", "_=get_set", false).unwrap_err();
+        assert!(e.to_string().contains("no match for gpio_set_high"), "{e}");
+    }
 }
@@ -1,167 +1,239 @@
-use crate::util;
+use anyhow::Result;
 use console::{StyledObject, style};
-use proc_macro2::Ident;
-use shrink_wrap_core::ast::Type;
 use std::fmt::Write;
 use std::path::PathBuf;
-use wire_weaver_core::ast::api::{ApiItem, ApiItemKind, ApiLevel, Argument, PropertyAccess};
-use wire_weaver_core::ast::trait_macro_args::ImplTraitMacroArgs;
-use wire_weaver_core::ast::visit::{Visit, visit_api_level};
-use wire_weaver_core::ast::visitors::IdStack;
+use wire_weaver_core::load;
+use ww_self::{
+    ApiBundleOwned, ApiItemKindOwned, ApiItemOwned, ApiLevelOwned, Multiplicity, PropertyAccess,
+    TypeOwned,
+};
 
-pub fn tree_printer(
-    path: PathBuf,
-    name: Option<String>,
+pub(crate) fn tree_printer(
+    crate_path: PathBuf,
+    trait_name: Option<String>,
     skip_reserved: bool,
     skip_docs: bool,
-) -> anyhow::Result<()> {
-    let level = util::load_level(path, name)?;
-    println!(
-        "{} {}:",
-        style("trait").true_color(0xCF, 0x8E, 0x6D),
-        style(&level.name).true_color(0x8D, 0x91, 0xDC)
-    );
-    visit_api_level(
-        &level,
-        &mut TreePrinter {
-            skip_reserved,
-            skip_docs,
-            ..Default::default()
-        },
-    );
+) -> Result<()> {
+    let api_bundle = load(&crate_path, trait_name, false)?;
+    print!("{}", render_tree(&api_bundle, skip_reserved, skip_docs)?);
     Ok(())
 }
 
-#[derive(Default)]
-struct TreePrinter {
-    id_stack: IdStack,
+fn render_tree(
+    api_bundle: &ApiBundleOwned,
     skip_reserved: bool,
     skip_docs: bool,
+) -> Result<String> {
+    let root = &api_bundle.root;
+    let mut printer = TreePrinter {
+        api_bundle,
+        skip_reserved,
+        skip_docs,
+        out: String::new(),
+    };
+    writeln!(
+        printer.out,
+        "{} {}::{}:",
+        style("trait").true_color(0xCF, 0x8E, 0x6D),
+        style(root.crate_name(api_bundle)?).true_color(0x8D, 0x91, 0xDC),
+        style(&root.trait_name).true_color(0x8D, 0x91, 0xDC)
+    )?;
+    printer.print_docs(0, &root.docs)?;
+    printer.print_level(root, &[], "")?;
+    Ok(printer.out)
 }
 
-impl Visit for TreePrinter {
-    fn hook(&mut self) -> Option<&mut dyn Visit> {
-        Some(&mut self.id_stack)
-    }
+struct TreePrinter<'i> {
+    api_bundle: &'i ApiBundleOwned,
+    skip_reserved: bool,
+    skip_docs: bool,
+    out: String,
+}
 
-    fn visit_method(&mut self, ident: &Ident, args: &[Argument], return_type: &Option<Type>) {
-        self.id_stack.print_indent_and_path();
-        print!("{} {ident}(", style("fn").blue());
-        for (idx, arg) in args.iter().enumerate() {
-            print!(
-                "{}: {}",
-                arg.ident,
-                style(arg.ty.arg_pos_def2(true).to_string()).true_color(0xA6, 0xBB, 0x77)
-            );
-            if idx + 1 < args.len() {
-                print!(", ");
+impl TreePrinter<'_> {
+    fn print_level(&mut self, level: &ApiLevelOwned, ids: &[u32], path: &str) -> Result<()> {
+        let depth = ids.len() + 1;
+        let mut items: Vec<&ApiItemOwned> = level.items.iter().collect();
+        items.sort_by_key(|item| item.id.0);
+        let mut next_id = 0;
+        for item in items {
+            // ids that are not used anymore are not present in the bundle
+            for reserved_id in next_id..item.id.0 {
+                if !self.skip_reserved {
+                    writeln!(
+                        self.out,
+                        "{}{} {}",
+                        indent(depth),
+                        style_ids(ids, reserved_id),
+                        style("reserved").dim()
+                    )?;
+                }
             }
+            next_id = item.id.0 + 1;
+            self.print_item(item, ids, path, depth)?;
         }
-        print!(")");
-        if let Some(ret) = return_type {
-            print!(
-                " -> {}",
-                style(ret.arg_pos_def2(true).to_string()).true_color(0xA6, 0xBB, 0x77)
-            );
-        }
-        println!();
+        Ok(())
     }
 
-    fn visit_property(
+    fn print_item(
         &mut self,
-        ident: &Ident,
-        ty: &Type,
-        access: PropertyAccess,
-        user_result_ty: &Option<Type>,
-    ) {
-        self.id_stack.print_indent_and_path();
-        let access = match access {
-            PropertyAccess::Const => "const",
-            PropertyAccess::ReadOnly => "ro",
-            PropertyAccess::ReadWrite => "rw",
-            PropertyAccess::WriteOnly => "wo",
+        item: &ApiItemOwned,
+        ids: &[u32],
+        path: &str,
+        depth: usize,
+    ) -> Result<()> {
+        let multiplicity = match item.multiplicity {
+            Multiplicity::Flat => "",
+            Multiplicity::Array { .. } => "[]",
         };
-        print!(
-            "{} {} {ident}: {}",
-            style(access).true_color(0xC7, 0x7D, 0xBB),
-            style("property").true_color(0xC7, 0x7D, 0xBB),
-            style_ty(ty),
-        );
-        if let Some(ty) = user_result_ty {
-            print!(", on_set_err: Result<(), {}>", style_ty(ty))
-        }
-        println!();
-    }
-
-    fn visit_stream(&mut self, ident: &Ident, ty: &Type, is_up: bool) {
-        self.id_stack.print_indent_and_path();
-        if is_up {
-            println!(
-                "{} {ident}: {}",
-                style("stream").true_color(0x8C, 0xC8, 0xD4),
-                style_ty(ty)
-            )
+        let item_path = if path.is_empty() {
+            format!("{}{multiplicity}", item.ident)
         } else {
-            println!(
-                "{} {ident}: {}",
-                style("sink").true_color(0x8C, 0xC8, 0xD4),
-                style_ty(ty)
-            )
+            format!("{path}.{}{multiplicity}", item.ident)
+        };
+        write!(
+            self.out,
+            "{}{} {item_path}: ",
+            indent(depth),
+            style_ids(ids, item.id.0)
+        )?;
+        match &item.kind {
+            ApiItemKindOwned::Method { args, return_ty } => {
+                write!(self.out, "{}(", style("fn").blue())?;
+                for (idx, arg) in args.iter().enumerate() {
+                    let ty = self.style_ty(&arg.ty)?;
+                    write!(self.out, "{}: {ty}", arg.ident)?;
+                    if idx + 1 < args.len() {
+                        write!(self.out, ", ")?;
+                    }
+                }
+                write!(self.out, ")")?;
+                if let Some(ty) = return_ty {
+                    let ty = self.style_ty(ty)?;
+                    write!(self.out, " -> {ty}")?;
+                }
+            }
+            ApiItemKindOwned::Property {
+                ty,
+                access,
+                write_err_ty,
+            } => {
+                let (access, observe) = match access {
+                    PropertyAccess::Const => ("const", false),
+                    PropertyAccess::ReadOnly { observe } => ("ro", *observe),
+                    PropertyAccess::ReadWrite { observe } => ("rw", *observe),
+                    PropertyAccess::WriteOnly => ("wo", false),
+                };
+                let ty = self.style_ty(ty)?;
+                write!(
+                    self.out,
+                    "{} {} {ty}",
+                    style(access).true_color(0xC7, 0x7D, 0xBB),
+                    style("property").true_color(0xC7, 0x7D, 0xBB),
+                )?;
+                if observe {
+                    write!(self.out, ", observe")?;
+                }
+                if let Some(ty) = write_err_ty {
+                    let ty = self.style_ty(ty)?;
+                    write!(self.out, ", on_set_err: Result<(), {ty}>")?;
+                }
+            }
+            ApiItemKindOwned::Stream { ty, is_up } => {
+                let kind = if *is_up { "stream" } else { "sink" };
+                let ty = self.style_ty(ty)?;
+                write!(
+                    self.out,
+                    "{} {ty}",
+                    style(kind).true_color(0x8C, 0xC8, 0xD4),
+                )?;
+            }
+            ApiItemKindOwned::Trait { .. } => {
+                let level = item.get_as_level(self.api_bundle)?;
+                write!(
+                    self.out,
+                    "{} {}::{}",
+                    style("impl").true_color(0xCF, 0x8E, 0x6D),
+                    style(level.crate_name(self.api_bundle)?).true_color(0x8D, 0x91, 0xDC),
+                    style(&level.trait_name).true_color(0x8D, 0x91, 0xDC),
+                )?;
+            }
         }
-    }
+        if let Some(since) = &item.since {
+            write!(self.out, " {}", style(format!("since {since:?}")).dim())?;
+        }
+        writeln!(self.out)?;
+        self.print_docs(depth + 1, &item.docs)?;
 
-    fn visit_impl_trait(&mut self, args: &ImplTraitMacroArgs, level: &ApiLevel) {
-        self.id_stack.print_indent_and_path();
-        println!(
-            "{} {} {}::{}",
-            style("impl").true_color(0xCF, 0x8E, 0x6D),
-            args.resource_name,
-            style(level.source_location.crate_name()).true_color(0x8D, 0x91, 0xDC),
-            style(&args.trait_name).true_color(0x8D, 0x91, 0xDC),
-        );
+        if let ApiItemKindOwned::Trait { .. } = &item.kind {
+            let level = item.get_as_level(self.api_bundle)?;
+            if item.docs.is_empty() {
+                self.print_docs(depth + 1, &level.docs)?;
+            }
+            let mut ids = ids.to_vec();
+            ids.push(item.id.0);
+            self.print_level(level, &ids, &item_path)?;
+        }
+        Ok(())
     }
 
-    fn after_visit_impl_trait(&mut self, _args: &ImplTraitMacroArgs, level: &ApiLevel) {
+    fn print_docs(&mut self, depth: usize, docs: &[String]) -> Result<()> {
         if self.skip_docs {
-            return;
+            return Ok(());
         }
-        if level.docs.is_empty() {
-            return;
+        for line in docs {
+            writeln!(
+                self.out,
+                "{}{}",
+                indent(depth),
+                style(format!("//{line}")).dim()
+            )?;
         }
-        self.id_stack.print_indented(|w| {
-            write!(w, "{}", style(level.docs.to_string()).dim())?;
-            Ok(())
-        });
+        Ok(())
     }
 
-    fn visit_reserved(&mut self) {
-        if !self.skip_reserved {
-            self.id_stack.print_indent_and_path();
-            println!("{}", style("reserved").dim());
-        }
+    fn style_ty(&self, ty: &TypeOwned) -> Result<StyledObject<String>> {
+        Ok(style(ty.human_name(false, self.api_bundle)?).true_color(0xA6, 0xBB, 0x77))
     }
+}
 
-    fn after_visit_api_item(&mut self, item: &ApiItem) {
-        if self.skip_docs {
-            return;
-        }
-        if matches!(item.kind, ApiItemKind::ImplTrait { .. }) {
-            // printed in after_visit_impl_trait instead, if printed here - it appears after all child levels
-            return;
-        }
-        if self.skip_reserved && matches!(item.kind, ApiItemKind::Reserved) {
-            return;
-        }
-        if item.docs.is_empty() {
-            return;
-        }
-        self.id_stack.print_indented(|w| {
-            write!(w, "{}", style(item.docs.to_string()).dim())?;
-            Ok(())
-        });
-    }
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+fn style_ids(ids: &[u32], id: u32) -> StyledObject<String> {
+    let ids = ids
+        .iter()
+        .chain(std::iter::once(&id))
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    style(format!("[{ids}]")).dim()
 }
 
-fn style_ty(ty: &Type) -> StyledObject<String> {
-    style(ty.arg_pos_def2(false).to_string()).true_color(0xA6, 0xBB, 0x77)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn traits_api_tree() {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/traits_api");
+        let api_bundle = load(&crate_path, Some("Traits".into()), true).unwrap();
+        let tree = render_tree(&api_bundle, false, false).unwrap();
+        assert_eq!(
+            console::strip_ansi_codes(&tree),
+            "\
+trait traits_api::Traits:
+  [0] g1: impl traits_api::Subgroup
+    [0, 0] g1.m1: fn()
+  [1] gpio[]: impl traits_api::Gpio
+    [1, 0] gpio[].set_high: fn()
+  [2] periph[]: impl traits_api::Peripheral
+    [2, 0] periph[].channel[]: impl traits_api::Channel
+      [2, 0, 0] periph[].channel[].gain: rw property f32
+      [2, 0, 1] periph[].channel[].run: fn()
+"
+        );
+    }
 }
@@ -0,0 +1,18 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use wire_weaver_core::codegen::typescript::gen_typescript;
+use wire_weaver_core::load;
+
+pub(crate) fn typescript(
+    crate_path: PathBuf,
+    trait_name: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let api_bundle = load(&crate_path, trait_name, false)?;
+    let ts = gen_typescript(&api_bundle)?;
+    match output {
+        Some(output) => std::fs::write(output, ts)?,
+        None => print!("{ts}"),
+    }
+    Ok(())
+}
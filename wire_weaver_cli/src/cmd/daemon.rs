@@ -0,0 +1,38 @@
+use anyhow::Result;
+use tokio::sync::mpsc;
+use wire_weaver_usb_host::usb_worker;
+use wire_weaver_usb_host::wire_weaver_client_common::device_filter::DeviceFilterKind;
+use wire_weaver_usb_host::wire_weaver_client_common::ipc;
+use wire_weaver_usb_host::wire_weaver_client_common::ww_version::{FullVersionOwned, VersionOwned};
+use wire_weaver_usb_host::wire_weaver_client_common::{
+    Command, CommandSender, DeviceFilter, OnError,
+};
+
+/// Own a USB device and share it with other processes, that use the same filter with allow_ipc set.
+pub(crate) async fn daemon(vid: u16, pid: u16, serial: Option<String>) -> Result<()> {
+    let filter = match serial {
+        Some(serial) => DeviceFilter {
+            kind: DeviceFilterKind::UsbVidPidAndSerial { vid, pid, serial },
+            allow_ipc: false,
+        },
+        None => DeviceFilter::usb_vid_pid(vid, pid),
+    };
+    let listener = ipc::bind(&filter).await?;
+    println!(
+        "Serving {filter:02x?} on {}",
+        ipc::socket_path(&filter).display()
+    );
+
+    let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+    tokio::spawn(usb_worker(transport_cmd_rx));
+    let device = CommandSender::new(transport_cmd_tx);
+    // do not wait for a device, clients are notified when it is connected
+    device.send(Command::Connect {
+        filter: Box::new(filter),
+        client_version: Box::new(FullVersionOwned::new("".into(), VersionOwned::new(0, 1, 0))),
+        on_error: OnError::KeepRetrying,
+        connected_tx: None,
+    })?;
+    ipc::serve(listener, device).await?;
+    Ok(())
+}
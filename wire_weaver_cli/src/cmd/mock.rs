@@ -0,0 +1,33 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use tokio::net::TcpListener;
+use wire_weaver_mock::{MockServer, Script, find_in_registry, serve_ws};
+
+/// `api` is a path to a crate which defines ww_trait, a RON file or a crate name to be found in the local registry.
+pub(crate) async fn mock(
+    api: String,
+    name: Option<String>,
+    script: Option<PathBuf>,
+    port: u16,
+) -> Result<()> {
+    let path = Path::new(&api);
+    let server = if path.is_dir() {
+        MockServer::from_crate(path, name)?
+    } else if path.extension().is_some_and(|ext| ext == "ron") {
+        MockServer::from_ron_file(path)?
+    } else {
+        let path = find_in_registry(&api)?;
+        println!("Using {}", path.display());
+        MockServer::from_ron_file(path)?
+    };
+    let server = match script {
+        Some(script) => server.with_script(Script::from_file(script)?),
+        None => server,
+    };
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    println!(
+        "Mock device is listening on ws://{}",
+        listener.local_addr()?
+    );
+    serve_ws(listener, server).await
+}
@@ -1,3 +1,7 @@
 pub(crate) mod api;
+#[cfg(unix)]
+pub(crate) mod daemon;
 pub(crate) mod introspect;
+pub(crate) mod mock;
+pub(crate) mod pcapng;
 pub(crate) mod usb_loopback;
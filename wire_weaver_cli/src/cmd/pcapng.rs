@@ -0,0 +1,15 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use wire_weaver_usb_host::wire_weaver_client_common::capture::Capture;
+
+pub(crate) fn capture_to_pcapng(capture: PathBuf, output: PathBuf) -> Result<()> {
+    let capture = Capture::from_file(capture)?;
+    let file = std::fs::File::create(&output)?;
+    capture.write_pcapng(std::io::BufWriter::new(file))?;
+    println!(
+        "Written {} records to {}",
+        capture.records.len(),
+        output.display()
+    );
+    Ok(())
+}
@@ -33,6 +33,15 @@ async fn main() -> Result<()> {
         }
         Commands::Api(api_cmd) => cmd::api::api(api_cmd)?,
         Commands::Introspect => cmd::introspect::introspect(device.as_mut().unwrap()).await?,
+        Commands::Pcapng { capture, output } => cmd::pcapng::capture_to_pcapng(capture, output)?,
+        Commands::Mock {
+            api,
+            name,
+            script,
+            port,
+        } => cmd::mock::mock(api, name, script, port).await?,
+        #[cfg(unix)]
+        Commands::Daemon { vid, pid } => cmd::daemon::daemon(vid, pid, cli.serial).await?,
 
         #[cfg(target_os = "linux")]
         Commands::Udev => {
@@ -1 +1,5 @@
-
+/// Parse a hex number with or without 0x prefix, e.g., USB VID or PID.
+pub(crate) fn parse_hex_u16(s: &str) -> Result<u16, String> {
+    let s = s.trim_start_matches("0x");
+    u16::from_str_radix(s, 16).map_err(|e| format!("expected a hex number: {e}"))
+}
@@ -0,0 +1,324 @@
+//! Capture files with all the traffic between a client and a device.
+//!
+//! File starts with a length prefixed [CaptureHeader], followed by length prefixed [CaptureRecord]'s, all serialized
+//! with shrink_wrap. Length is u32 little endian. Device info and ApiBundle are recorded as well,
+//! so that a capture can be decoded offline or replayed later (see [replay](crate::replay)).
+
+use crate::tracing::TraceEvent;
+use crate::{CommandSender, DeviceInfoBundle, Error, UserApiSignature};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+use wire_weaver::prelude::*;
+use ww_self::ApiBundleOwned;
+use ww_version::{FullVersionOwned, VersionTriplet};
+
+/// "WWCF" - WireWeaver Capture File
+pub const CAPTURE_MAGIC: u32 = 0x4643_5757;
+pub const CAPTURE_VERSION: VersionTriplet = VersionTriplet::new(0, 1, 0);
+const MAX_RECORD_LEN: usize = 16 * 1024 * 1024;
+
+#[derive_shrink_wrap]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureHeader {
+    /// [CAPTURE_MAGIC] value
+    pub magic: u32,
+    /// [CAPTURE_VERSION] used when writing this file
+    pub version: VersionTriplet,
+    /// Capture start time, microseconds since UNIX epoch
+    pub started_at_us: u64,
+}
+
+#[derive_shrink_wrap]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureRecord {
+    /// Microseconds since capture start
+    pub timestamp_us: u64,
+    pub kind: RecordKind,
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(nib)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordKind {
+    /// Serialized ww_client_server::Request sent to a device
+    Request {
+        bytes: Vec<u8>,
+    },
+    /// Serialized ww_client_server::Event received from a device
+    Event {
+        bytes: Vec<u8>,
+    },
+    Connected {
+        device_info: Option<CapturedDeviceInfo>,
+    },
+    Disconnected {
+        reason: String,
+        keep_streams: bool,
+    },
+    Error {
+        reason: String,
+    },
+    /// Serialized ApiBundle of a connected device
+    ApiBundle {
+        ww_self_bytes: Vec<u8>,
+    },
+    /// Link-level packet, messages carried in it are also recorded as Request or Event
+    LinkPacket {
+        to_device: bool,
+        bytes: Vec<u8>,
+    },
+}
+
+/// Same as [DeviceInfoBundle], in a serializable form.
+#[derive_shrink_wrap]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedDeviceInfo {
+    pub link_version: FullVersionOwned,
+    pub max_message_size: u32,
+    pub api_model_version: FullVersionOwned,
+    pub user_api_version: FullVersionOwned,
+    pub user_api_signature: Vec<u8>,
+}
+
+/// Writes capture file header and records into any [Write] implementation.
+pub struct CaptureWriter<W: Write> {
+    wr: W,
+    started_at: Instant,
+    scratch: Vec<u8>,
+}
+
+/// Capture file loaded into memory.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    pub header: CaptureHeader,
+    pub records: Vec<CaptureRecord>,
+}
+
+impl<W: Write> CaptureWriter<W> {
+    pub fn new(mut wr: W) -> Result<Self, Error> {
+        let started_at_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0);
+        let header = CaptureHeader {
+            magic: CAPTURE_MAGIC,
+            version: CAPTURE_VERSION,
+            started_at_us,
+        };
+        let mut scratch = vec![0u8; 1024];
+        write_framed(&mut wr, &header, &mut scratch)?;
+        Ok(CaptureWriter {
+            wr,
+            started_at: Instant::now(),
+            scratch,
+        })
+    }
+
+    /// Write a record, timestamped with the time elapsed since capture start.
+    pub fn write(&mut self, kind: RecordKind) -> Result<(), Error> {
+        let record = CaptureRecord {
+            timestamp_us: self.started_at.elapsed().as_micros() as u64,
+            kind,
+        };
+        write_framed(&mut self.wr, &record, &mut self.scratch)
+    }
+
+    pub fn write_trace_event(&mut self, event: &TraceEvent) -> Result<(), Error> {
+        let kind = match event {
+            TraceEvent::Request { bytes } => RecordKind::Request {
+                bytes: bytes.clone(),
+            },
+            TraceEvent::Connected { info } => RecordKind::Connected {
+                device_info: info.device_info.as_ref().map(CapturedDeviceInfo::from),
+            },
+            TraceEvent::Event { bytes } => RecordKind::Event {
+                bytes: bytes.clone(),
+            },
+            TraceEvent::Disconnected {
+                reason,
+                keep_streams,
+            } => RecordKind::Disconnected {
+                reason: reason.clone(),
+                keep_streams: *keep_streams,
+            },
+            TraceEvent::Error { reason } => RecordKind::Error {
+                reason: reason.clone(),
+            },
+            TraceEvent::ApiBundle { api_bundle } => RecordKind::ApiBundle {
+                ww_self_bytes: shrink_wrap::to_ww_vec(api_bundle.as_ref(), MAX_RECORD_LEN)?,
+            },
+            TraceEvent::LinkPacket { to_device, bytes } => RecordKind::LinkPacket {
+                to_device: *to_device,
+                bytes: bytes.clone(),
+            },
+        };
+        self.write(kind)
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.wr.flush().map_err(io_err)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.wr
+    }
+}
+
+impl CaptureWriter<std::io::BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = File::create(path).map_err(io_err)?;
+        Self::new(std::io::BufWriter::new(file))
+    }
+}
+
+impl CommandSender {
+    /// Record all the traffic of this connection into a capture file.
+    /// Recording is done in a separate thread, until the event loop exits.
+    ///
+    /// ApiBundle is not downloaded automatically, call [set_api_bundle](CommandSender::set_api_bundle) after this method
+    /// for it to be recorded as well. Without it, trait-addressed streams are only resolved during replay if a device
+    /// replied with a Subscribed event.
+    pub fn record_to_file(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = CaptureWriter::create(path)?;
+        let (trace_event_tx, mut trace_event_rx) = mpsc::unbounded_channel();
+        self.send(crate::Command::RegisterTracer { trace_event_tx })?;
+        std::thread::spawn(move || {
+            while let Some(event) = trace_event_rx.blocking_recv() {
+                if let Err(e) = writer.write_trace_event(&event) {
+                    warn!("capture write failed, stopping: {e:?}");
+                    return;
+                }
+                if matches!(event, TraceEvent::Disconnected { .. })
+                    && let Err(e) = writer.flush()
+                {
+                    warn!("capture flush failed, stopping: {e:?}");
+                    return;
+                }
+            }
+            _ = writer.flush();
+            debug!("capture recording finished");
+        });
+        Ok(())
+    }
+}
+
+impl Capture {
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, Error> {
+        let header: CaptureHeader =
+            read_framed(&mut bytes)?.ok_or_else(|| Error::Other("empty capture file".into()))?;
+        if header.magic != CAPTURE_MAGIC {
+            return Err(Error::Other("not a capture file".into()));
+        }
+        if header.version.major != CAPTURE_VERSION.major {
+            return Err(Error::Other(format!(
+                "unsupported capture file version: {:?}",
+                header.version
+            )));
+        }
+        let mut records = vec![];
+        while let Some(record) = read_framed(&mut bytes)? {
+            records.push(record);
+        }
+        Ok(Capture { header, records })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut bytes = vec![];
+        File::open(path)
+            .and_then(|mut f| f.read_to_end(&mut bytes))
+            .map_err(io_err)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Info of the first connected device, if recorded.
+    pub fn device_info(&self) -> Option<DeviceInfoBundle> {
+        self.records.iter().find_map(|r| match &r.kind {
+            RecordKind::Connected { device_info } => {
+                device_info.as_ref().map(DeviceInfoBundle::from)
+            }
+            _ => None,
+        })
+    }
+
+    /// First ApiBundle recorded. It is only recorded if [set_api_bundle](CommandSender::set_api_bundle) was called
+    /// during recording, an error is returned otherwise.
+    pub fn api_bundle(&self) -> Result<ApiBundleOwned, Error> {
+        let ww_self_bytes = self
+            .records
+            .iter()
+            .find_map(|r| match &r.kind {
+                RecordKind::ApiBundle { ww_self_bytes } => Some(ww_self_bytes),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                Error::Other("no ApiBundle in the capture, set_api_bundle was not called".into())
+            })?;
+        Ok(ApiBundleOwned::from_ww_bytes_owned(ww_self_bytes)?)
+    }
+
+    /// Whether an ApiBundle was recorded.
+    pub fn has_api_bundle(&self) -> bool {
+        self.records
+            .iter()
+            .any(|r| matches!(r.kind, RecordKind::ApiBundle { .. }))
+    }
+}
+
+impl From<&DeviceInfoBundle> for CapturedDeviceInfo {
+    fn from(info: &DeviceInfoBundle) -> Self {
+        CapturedDeviceInfo {
+            link_version: info.link_version.clone(),
+            max_message_size: info.max_message_size as u32,
+            api_model_version: info.api_model_version.clone(),
+            user_api_version: info.user_api_version.clone(),
+            user_api_signature: info.user_api_signature.0.clone(),
+        }
+    }
+}
+
+impl From<&CapturedDeviceInfo> for DeviceInfoBundle {
+    fn from(info: &CapturedDeviceInfo) -> Self {
+        DeviceInfoBundle {
+            link_version: info.link_version.clone(),
+            max_message_size: info.max_message_size as usize,
+            api_model_version: info.api_model_version.clone(),
+            user_api_version: info.user_api_version.clone(),
+            user_api_signature: UserApiSignature(info.user_api_signature.clone()),
+        }
+    }
+}
+
+fn write_framed<T: SerializeShrinkWrap>(
+    wr: &mut impl Write,
+    value: &T,
+    scratch: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let bytes = shrink_wrap::to_ww_scratch(value, scratch, MAX_RECORD_LEN)?;
+    wr.write_all(&(bytes.len() as u32).to_le_bytes())
+        .and_then(|_| wr.write_all(bytes))
+        .map_err(io_err)
+}
+
+fn read_framed<T: DeserializeShrinkWrapOwned>(bytes: &mut &[u8]) -> Result<Option<T>, Error> {
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    let Some((len, rest)) = bytes.split_first_chunk::<4>() else {
+        return Err(Error::Other("truncated capture file".into()));
+    };
+    let len = u32::from_le_bytes(*len) as usize;
+    if rest.len() < len {
+        return Err(Error::Other("truncated capture file".into()));
+    }
+    let (frame, rest) = rest.split_at(len);
+    *bytes = rest;
+    Ok(Some(T::from_ww_bytes_owned(frame)?))
+}
+
+pub(crate) fn io_err(e: std::io::Error) -> Error {
+    Error::Other(format!("capture file io: {e}"))
+}
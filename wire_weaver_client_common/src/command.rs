@@ -1,9 +1,11 @@
-use crate::rx_dispatcher::{ResponseSender, StreamUpdateSender};
+use crate::rx_dispatcher::{RequestSeq, ResponseSender, StreamUpdateSender};
 use crate::{DeviceFilter, Error, OnError};
 use std::fmt::{Debug, Formatter};
 use std::time::Duration;
 use tokio::sync::{mpsc, oneshot};
-use ww_client_server::PathKindOwned;
+use wire_weaver::shrink_wrap::SerializeShrinkWrap;
+use ww_client_server::{PathKindOwned, RequestKindOwned, RequestOwned};
+use ww_self::ApiBundleOwned;
 use ww_version::{FullVersionOwned, VersionOwned};
 
 /// Command for the transport event loop host (USB host, WebSocket client, UDP client).
@@ -41,9 +43,22 @@ pub enum Command {
         /// TODO: Timeout per each update or in total for multipart?
         done_tx: Option<(ResponseSender, Duration)>,
     },
+    /// Forget a request sent earlier with [SendMessage](Command::SendMessage), a late response to it will be ignored.
+    /// Optionally notify the device, so that it can abort deferred work.
+    Cancel { request: Box<CancelRequest> },
     OnStreamEvent {
         path_kind: Box<PathKindOwned>,
         stream_event_tx: StreamUpdateSender,
+        /// Seq number of a request that opens a trait-addressed stream, device replies with
+        /// [Subscribed](ww_client_server::EventKind::Subscribed) event carrying it and an absolute path of the stream.
+        subscribe_seq: RequestSeq,
+    },
+    /// Provide ApiBundle of a connected device (e.g., downloaded through [Introspect](crate::CommandSender::introspect)),
+    /// so that trait-addressed streams can be matched with stream events, that always carry an absolute path.
+    SetApiBundle { api_bundle: Box<ApiBundleOwned> },
+    /// Connection state changes (including automatic reconnections and device firmware changes) will be sent to this channel.
+    OnConnectionEvent {
+        event_tx: mpsc::UnboundedSender<crate::restore::ConnectionEvent>,
     },
     // RecycleBuffer(Vec<u8>),
     // GetStats,
@@ -58,6 +73,13 @@ const _: () = {
     assert!(size_of::<Command>() <= 64); // was 56
 };
 
+pub struct CancelRequest {
+    /// Seq number assigned to the request by an event loop.
+    pub seq: RequestSeq,
+    /// If Some, [Cancel](ww_client_server::RequestKind::Cancel) is sent to the device with this path.
+    pub notify_path: Option<PathKindOwned>,
+}
+
 #[derive(Debug)]
 pub enum TestProgress {
     TestStarted(&'static str),
@@ -88,6 +110,27 @@ impl Command {
     }
 }
 
+impl CancelRequest {
+    /// Serialized Cancel request to be sent to the device, if it should be notified.
+    pub fn notification(&self) -> Option<Vec<u8>> {
+        let path_kind = self.notify_path.clone()?;
+        let seq = self.seq.get();
+        if seq == 0 {
+            // request was never sent out
+            return None;
+        }
+        let req = RequestOwned {
+            seq: 0,
+            path_kind,
+            kind: RequestKindOwned::Cancel { seq },
+        };
+        let mut scratch = [0u8; 1024];
+        req.to_ww_bytes(&mut scratch)
+            .ok()
+            .map(|bytes| bytes.to_vec())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct DeviceInfoBundle {
     /// Link carries API model messages.
@@ -102,7 +145,7 @@ pub struct DeviceInfoBundle {
 }
 
 /// First 8 bytes for SHA256 of ww_self bytes without doc comments
-#[derive(Clone, Default)]
+#[derive(Clone, Default, PartialEq, Eq)]
 pub struct UserApiSignature(pub Vec<u8>);
 
 impl DeviceInfoBundle {
@@ -1,23 +1,31 @@
 use crate::introspect::Introspect;
+use crate::metrics::{
+    MeasuredResource, Metrics, MetricsSnapshot, Operation, RequestProbe, ResourcePath, StreamProbe,
+};
 use crate::prepared_call::PreparedCall;
-use crate::rx_dispatcher::{ResponseReceiver, ResponseSender, StreamUpdateReceiver};
+use crate::restore::ConnectionEvent;
+use crate::rx_dispatcher::{
+    RequestSeq, ResponseReceiver, StreamUpdateReceiver, response_channel_with_seq,
+    stream_update_channel,
+};
 use crate::stream::Stream;
 use crate::{
-    Command, DeviceFilter, DeviceInfoBundle, Error, OnError, PreparedRead, PreparedWrite,
-    Sink, DEFAULT_REQUEST_TIMEOUT,
+    CancelRequest, Command, DEFAULT_REQUEST_TIMEOUT, DeviceFilter, DeviceInfoBundle, Error,
+    OnError, PreparedRead, PreparedWrite, Sink,
 };
 use std::collections::HashMap;
 use std::marker::PhantomData;
-use std::time::Duration;
-use tokio::sync::{mpsc, oneshot};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc, oneshot};
 use wire_weaver::prelude::{DeserializeShrinkWrapOwned, UNib32};
 use wire_weaver::shrink_wrap::SerializeShrinkWrap;
 use ww_client_server::{PathKind, PathKindOwned, RequestKindOwned, StreamSidebandCommand};
 use ww_self::ApiBundleOwned;
-use ww_version::{CompactVersion, FullVersionOwned, VersionOwned};
+use ww_version::{CompactVersion, FullVersion, FullVersionOwned, VersionOwned};
 
 // TODO: in tests dispatcher command can arrive later than event with an answer (fixed with delay?), even though cmd are sent first, happens on real hw?
-// TODO: CommandSender outstanding request limit?
 /// Entry point for an API root or API trait implementation. Inside - wrapper over a channel sender half (currently tokio::mpsc::UnboundedSender).
 ///
 /// Commands sent through this channel are received by a worker thread (e.g., USB or WebSocket clients) and forwarded to a connected device.
@@ -42,11 +50,18 @@ pub struct CommandSender {
         Result<ApiBundleOwned, wire_weaver::shrink_wrap::Error>,
         Vec<u8>,
     )>,
+    /// Limits the number of outstanding async requests, shared between clones.
+    in_flight: Option<Arc<Semaphore>>,
+    metrics: Metrics,
 }
 
 pub(crate) struct TransportCommander {
     cmd_tx: mpsc::UnboundedSender<Command>,
     default_timeout: Duration,
+    in_flight: Option<Arc<Semaphore>>,
+    metrics: Metrics,
+    /// Resource requests are sent to, None if not measured (e.g., introspect or an invalid path)
+    resource: Option<Arc<MeasuredResource>>,
 }
 
 impl CommandSender {
@@ -58,6 +73,8 @@ impl CommandSender {
             default_timeout: DEFAULT_REQUEST_TIMEOUT,
             connected_device: DeviceInfoBundle::empty(),
             client_api: None,
+            in_flight: None,
+            metrics: Metrics::default(),
         }
     }
 
@@ -68,6 +85,14 @@ impl CommandSender {
         self.default_timeout = timeout;
     }
 
+    /// Limit the number of async calls, reads and writes awaiting a response at the same time.
+    /// When the limit is reached, new requests wait for earlier ones to complete, instead of failing when a device is slow
+    /// or when request IDs run out. The window is shared with clones of this CommandSender made afterward.
+    /// Blocking, promise and forget variants are not counted.
+    pub fn set_max_in_flight(&mut self, max: NonZeroUsize) {
+        self.in_flight = Some(Arc::new(Semaphore::new(max.get())));
+    }
+
     pub async fn connect(
         &mut self,
         filter: DeviceFilter,
@@ -123,10 +148,10 @@ impl CommandSender {
     pub fn prepare_call<T: DeserializeShrinkWrapOwned>(
         &self,
         path: PathKind<'_>,
+        since: Option<FullVersion<'_>>,
         args: Result<Vec<u8>, Error>,
     ) -> PreparedCall<T> {
         // postpone error return to have a better syntax (one ? instead of two)
-        let since = None; // TODO: fix
         let (postpone_err, args) = match (self.check_version(since), args) {
             (Ok(_), Ok(args)) => (Ok(()), args),
             (Err(e), _) => (Err(e), vec![]),
@@ -140,15 +165,14 @@ impl CommandSender {
         } else {
             (postpone_err, PathKindOwned::Absolute { path: vec![] })
         };
+        let transport_cmd_tx = self.commander_for(&path_kind);
         PreparedCall {
             postpone_err,
-            transport_cmd_tx: TransportCommander::new(
-                self.transport_cmd_tx.clone(),
-                self.default_timeout,
-            ),
+            transport_cmd_tx,
             path_kind,
             args,
             timeout_override: None,
+            notify_cancel: false,
             _phantom: PhantomData,
         }
     }
@@ -156,15 +180,16 @@ impl CommandSender {
     pub fn prepare_read<T: DeserializeShrinkWrapOwned>(
         &self,
         path: PathKind<'_>,
+        since: Option<FullVersion<'_>>,
     ) -> PreparedRead<T> {
-        let since = None; // TODO: fix
         let version_check = self.check_version(since);
         let path_kind = self.to_ww_client_server_path(path); // postpone error return to have a better syntax
+        let transport_cmd_tx = match &path_kind {
+            Ok(path_kind) => self.commander_for(path_kind),
+            Err(_) => self.commander(),
+        };
         PreparedRead {
-            transport_cmd_tx: TransportCommander::new(
-                self.transport_cmd_tx.clone(),
-                self.default_timeout,
-            ),
+            transport_cmd_tx,
             version_check,
             path_kind,
             timeout_override: None,
@@ -175,9 +200,9 @@ impl CommandSender {
     pub fn prepare_write<E: DeserializeShrinkWrapOwned>(
         &self,
         path: PathKind<'_>,
+        since: Option<FullVersion<'_>>,
         value: Result<Vec<u8>, Error>,
     ) -> PreparedWrite<E> {
-        let since = None; // TODO: fix
         let (postpone_err, value) = match (self.check_version(since), value) {
             (Ok(_), Ok(value)) => (Ok(()), value),
             (Err(e), _) => (Err(e), vec![]),
@@ -191,12 +216,10 @@ impl CommandSender {
         } else {
             (postpone_err, PathKindOwned::Absolute { path: vec![] })
         };
+        let transport_cmd_tx = self.commander_for(&path_kind);
         PreparedWrite {
             postpone_err,
-            transport_cmd_tx: TransportCommander::new(
-                self.transport_cmd_tx.clone(),
-                self.default_timeout,
-            ),
+            transport_cmd_tx,
             path_kind,
             value,
             timeout_override: None,
@@ -207,23 +230,24 @@ impl CommandSender {
     pub fn prepare_stream<T: DeserializeShrinkWrapOwned>(
         &self,
         path: PathKind<'_>,
+        since: Option<FullVersion<'_>>,
     ) -> Result<Stream<T>, Error> {
-        let since = None; // TODO: fix
         self.check_version(since)?;
         let path_kind = self.to_ww_client_server_path(path)?;
-        let (tx, rx) = mpsc::unbounded_channel();
+        let transport_cmd_tx = self.commander_for(&path_kind);
+        let (tx, rx) = stream_update_channel(transport_cmd_tx.stream_probe());
+        let subscribe_seq = RequestSeq::default();
         self.transport_cmd_tx
             .send(Command::OnStreamEvent {
                 path_kind: Box::new(path_kind.clone()),
                 stream_event_tx: tx,
+                subscribe_seq: subscribe_seq.clone(),
             })
             .map_err(|_| Error::RxDispatcherNotRunning)?;
         Ok(Stream {
-            transport_cmd_tx: TransportCommander::new(
-                self.transport_cmd_tx.clone(),
-                self.default_timeout,
-            ),
+            transport_cmd_tx,
             path_kind,
+            subscribe_seq,
             rx,
             _phantom: PhantomData,
         })
@@ -232,22 +256,20 @@ impl CommandSender {
     pub fn prepare_sink<T: DeserializeShrinkWrapOwned>(
         &self,
         path: PathKind<'_>,
+        since: Option<FullVersion<'_>>,
     ) -> Result<Sink<T>, Error> {
-        let since = None; // TODO: fix
         self.check_version(since)?;
         let path_kind = self.to_ww_client_server_path(path)?;
-        let (tx, rx) = mpsc::unbounded_channel();
+        let (tx, rx) = stream_update_channel(None);
         self.transport_cmd_tx
             .send(Command::OnStreamEvent {
                 path_kind: Box::new(path_kind.clone()),
                 stream_event_tx: tx,
+                subscribe_seq: RequestSeq::default(),
             })
             .map_err(|_| Error::RxDispatcherNotRunning)?;
         Ok(Sink {
-            transport_cmd_tx: TransportCommander::new(
-                self.transport_cmd_tx.clone(),
-                self.default_timeout,
-            ),
+            transport_cmd_tx: self.commander(),
             path_kind,
             _sideband_rx: rx,
             _phantom: PhantomData,
@@ -255,11 +277,28 @@ impl CommandSender {
         })
     }
 
+    /// Send ApiBundle of a connected device to the event loop, so that streams opened through trait clients
+    /// (GlobalCompact or GlobalFull addressing) can be resolved to absolute paths and receive data.
+    pub fn set_api_bundle(&self, api_bundle: ApiBundleOwned) -> Result<(), Error> {
+        self.transport_cmd_tx
+            .send(Command::SetApiBundle {
+                api_bundle: Box::new(api_bundle),
+            })
+            .map_err(|_| Error::EventLoopNotRunning)
+    }
+
+    /// Receive connection state changes: (re)connections, device firmware changes and disconnections.
+    /// If a device is already connected, [ConnectionEvent::Connected] is sent right away.
+    pub fn connection_events(&self) -> Result<mpsc::UnboundedReceiver<ConnectionEvent>, Error> {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        self.transport_cmd_tx
+            .send(Command::OnConnectionEvent { event_tx })
+            .map_err(|_| Error::EventLoopNotRunning)?;
+        Ok(event_rx)
+    }
+
     pub fn introspect(&self) -> Introspect {
-        Introspect::new(TransportCommander::new(
-            self.transport_cmd_tx.clone(),
-            self.default_timeout,
-        ))
+        Introspect::new(self.commander())
     }
 
     pub async fn disconnect(&self) {
@@ -297,6 +336,16 @@ impl CommandSender {
         ));
     }
 
+    /// Snapshot of per resource request and stream metrics collected by this CommandSender and its clones.
+    /// Resource names are resolved using client introspect data (see [Self::set_client_introspect_bytes]).
+    pub fn metrics(&self) -> MetricsSnapshot {
+        let api_bundle = self
+            .client_api
+            .as_ref()
+            .and_then(|(api_bundle, _)| api_bundle.as_ref().ok());
+        self.metrics.snapshot(api_bundle)
+    }
+
     pub fn print_version_report(&self) {
         let Some((_api_bundle, api_signature)) = &self.client_api else {
             println!("No client introspect data available");
@@ -359,25 +408,83 @@ impl CommandSender {
         Ok(path_kind)
     }
 
-    fn check_version(&self, since: Option<(u32, u32, u32)>) -> Result<(), Error> {
+    fn commander(&self) -> TransportCommander {
+        TransportCommander {
+            cmd_tx: self.transport_cmd_tx.clone(),
+            default_timeout: self.default_timeout,
+            in_flight: self.in_flight.clone(),
+            metrics: self.metrics.clone(),
+            resource: None,
+        }
+    }
+
+    /// Commander for a particular resource, metrics are keyed by a full path as sent to a device.
+    fn commander_for(&self, path_kind: &PathKindOwned) -> TransportCommander {
+        let ids = |path: &[UNib32]| path.iter().map(|id| id.0).collect::<Vec<_>>();
+        let (path, relative) = match path_kind {
+            PathKindOwned::Absolute { path } => {
+                let base_len = self.base_path.as_ref().map(|base| base.len()).unwrap_or(0);
+                let relative = ids(path.get(base_len..).unwrap_or_default());
+                (ResourcePath::Absolute(ids(path)), relative)
+            }
+            PathKindOwned::GlobalCompact {
+                gid,
+                path_from_trait,
+            } => (
+                ResourcePath::Trait {
+                    gid: format!("{gid:?}"),
+                    path_from_trait: ids(path_from_trait),
+                },
+                ids(path_from_trait),
+            ),
+            PathKindOwned::GlobalFull {
+                gid,
+                path_from_trait,
+            } => (
+                ResourcePath::Trait {
+                    gid: format!("{gid:?}"),
+                    path_from_trait: ids(path_from_trait),
+                },
+                ids(path_from_trait),
+            ),
+        };
+        // root path is not a resource (e.g., introspect stream)
+        let resource =
+            (!relative.is_empty()).then(|| Arc::new(MeasuredResource { path, relative }));
+        TransportCommander {
+            resource,
+            ..self.commander()
+        }
+    }
+
+    /// Returns false if a resource was added in a newer API version than the one reported by a connected device.
+    /// `since` is a version from `#[since = "x.y.z"]` attribute and a name of the crate that defines the resource.
+    /// Resources from crates other than the connected device's user API, and any resources before connection,
+    /// are assumed to be available, a device will respond with an error if they are not.
+    pub fn is_available(&self, since: Option<FullVersion<'_>>) -> bool {
+        self.check_version(since).is_ok()
+    }
+
+    fn check_version(&self, since: Option<FullVersion<'_>>) -> Result<(), Error> {
         let Some(since) = since else { return Ok(()) };
-        // assuming the protocol name matches, a device will refuse to connect if it is not, host as well checks it and won't try to connect
-        let dev_user_api = &self.connected_device.user_api_version.version;
+        let dev_user_api = &self.connected_device.user_api_version;
+        if dev_user_api.crate_id != since.crate_id {
+            return Ok(());
+        }
         // #[since = ""] only makes sense within compatible protocols, if a user annotated a resource with a different major version, it must be a mistake
-        // this is checked in ww_trait macro
-        let is_compatible = if dev_user_api.major.0 == 0 {
-            dev_user_api.patch.0 >= since.2
-        } else {
-            dev_user_api.minor.0 >= since.1
-        };
-        if is_compatible {
+        // this is checked in ww_trait macro, and a device with incompatible protocol is refused on connect
+        let dev = &dev_user_api.version;
+        let dev = (dev.major.0, dev.minor.0, dev.patch.0);
+        let required = &since.version;
+        let required = (required.major.0, required.minor.0, required.patch.0);
+        if dev >= required {
             Ok(())
         } else {
             Err(Error::OlderProtocol(
-                Box::new(self.connected_device.user_api_version.clone()),
+                Box::new(dev_user_api.clone()),
                 Box::new(FullVersionOwned::new(
-                    self.connected_device.user_api_version.crate_id.clone(),
-                    VersionOwned::new(since.0, since.1, since.2),
+                    dev_user_api.crate_id.clone(),
+                    VersionOwned::new(required.0, required.1, required.2),
                 )),
             ))
         }
@@ -385,24 +492,84 @@ impl CommandSender {
 }
 
 impl TransportCommander {
-    fn new(cmd_tx: mpsc::UnboundedSender<Command>, default_timeout: Duration) -> Self {
-        Self {
-            cmd_tx,
-            default_timeout,
-        }
+    /// Wait for a free slot in the in-flight window, if one was configured.
+    pub(crate) async fn acquire_in_flight(&self) -> Result<Option<OwnedSemaphorePermit>, Error> {
+        let Some(in_flight) = &self.in_flight else {
+            return Ok(None);
+        };
+        let permit = in_flight
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| Error::Other("in-flight window closed".into()))?;
+        Ok(Some(permit))
     }
 
+    /// Response is counted in metrics under `operation`, if provided.
     fn send_message_expect_response(
         &self,
         bytes: Vec<u8>,
-        done_tx: ResponseSender,
         timeout: Option<Duration>,
-    ) -> Result<(), Error> {
+        operation: Option<Operation>,
+    ) -> Result<(ResponseReceiver, RequestSeq), Error> {
+        let seq = RequestSeq::default();
+        let probe = operation.and_then(|operation| self.request_probe(operation));
+        let done_rx = self.send_message_with_seq(bytes, timeout, seq.clone(), probe)?;
+        Ok((done_rx, seq))
+    }
+
+    /// Same as [Self::send_message_expect_response], but a seq number assigned by an event loop is stored into `seq`.
+    fn send_message_with_seq(
+        &self,
+        bytes: Vec<u8>,
+        timeout: Option<Duration>,
+        seq: RequestSeq,
+        probe: Option<RequestProbe>,
+    ) -> Result<ResponseReceiver, Error> {
+        let (done_tx, done_rx) = response_channel_with_seq(seq);
+        let done_tx = done_tx.with_probe(probe);
         self.cmd_tx
             .send(Command::SendMessage {
                 bytes,
                 done_tx: Some((done_tx, timeout.unwrap_or(self.default_timeout))),
             })
+            .map_err(|_| Error::EventLoopNotRunning)?;
+        Ok(done_rx)
+    }
+
+    fn request_probe(&self, operation: Operation) -> Option<RequestProbe> {
+        Some(RequestProbe {
+            metrics: self.metrics.clone(),
+            resource: self.resource.clone()?,
+            operation,
+            started: Instant::now(),
+        })
+    }
+
+    pub(crate) fn stream_probe(&self) -> Option<StreamProbe> {
+        Some(StreamProbe {
+            metrics: self.metrics.clone(),
+            resource: self.resource.clone()?,
+        })
+    }
+
+    /// Count stream data events, that were received, but not delivered to an application.
+    pub(crate) fn record_stream_dropped(&self, count: u64) {
+        if let Some(probe) = self.stream_probe() {
+            probe.record_dropped(count);
+        }
+    }
+
+    /// Forget a pending request and optionally notify the device.
+    pub(crate) fn send_cancel(
+        &self,
+        seq: RequestSeq,
+        notify_path: Option<PathKindOwned>,
+    ) -> Result<(), Error> {
+        self.cmd_tx
+            .send(Command::Cancel {
+                request: Box::new(CancelRequest { seq, notify_path }),
+            })
             .map_err(|_| Error::EventLoopNotRunning)
     }
 
@@ -411,7 +578,7 @@ impl TransportCommander {
         path_kind: PathKindOwned,
         args: Vec<u8>,
         timeout: Option<Duration>,
-    ) -> Result<ResponseReceiver, Error> {
+    ) -> Result<(ResponseReceiver, RequestSeq), Error> {
         let req = ww_client_server::RequestOwned {
             seq: 0,
             path_kind,
@@ -419,9 +586,7 @@ impl TransportCommander {
         };
         let mut scratch = [0u8; 1024]; // TODO: use Vec flavor or recycle?
         let req = req.to_ww_bytes(&mut scratch)?;
-        let (done_tx, done_rx) = oneshot::channel();
-        self.send_message_expect_response(req.to_vec(), done_tx, timeout)?;
-        Ok(done_rx)
+        self.send_message_expect_response(req.to_vec(), timeout, Some(Operation::Call))
     }
 
     pub(crate) fn send_call_request_forget(
@@ -457,8 +622,8 @@ impl TransportCommander {
         };
         let mut scratch = [0u8; 1024]; // TODO: use Vec flavor or recycle?
         let req = req.to_ww_bytes(&mut scratch)?;
-        let (done_tx, done_rx) = oneshot::channel();
-        self.send_message_expect_response(req.to_vec(), done_tx, timeout)?;
+        let (done_rx, _seq) =
+            self.send_message_expect_response(req.to_vec(), timeout, Some(Operation::Read))?;
         Ok(done_rx)
     }
 
@@ -475,8 +640,8 @@ impl TransportCommander {
         };
         let mut scratch = [0u8; 1024]; // TODO: use Vec flavor or recycle?
         let req = req.to_ww_bytes(&mut scratch)?;
-        let (done_tx, done_rx) = oneshot::channel();
-        self.send_message_expect_response(req.to_vec(), done_tx, timeout)?;
+        let (done_rx, _seq) =
+            self.send_message_expect_response(req.to_vec(), timeout, Some(Operation::Write))?;
         Ok(done_rx)
     }
 
@@ -515,8 +680,7 @@ impl TransportCommander {
         };
         let mut scratch = [0u8; 1024]; // TODO: use Vec flavor or recycle?
         let req = req.to_ww_bytes(&mut scratch)?;
-        let (done_tx, done_rx) = oneshot::channel();
-        self.send_message_expect_response(req.to_vec(), done_tx, timeout)?;
+        let (done_rx, _seq) = self.send_message_expect_response(req.to_vec(), timeout, None)?;
         Ok(done_rx)
     }
 
@@ -541,6 +705,24 @@ impl TransportCommander {
         Ok(())
     }
 
+    /// Open a trait-addressed stream, device replies with Subscribed event carrying seq number stored into `subscribe_seq`.
+    pub(crate) fn send_stream_open(
+        &self,
+        path_kind: PathKindOwned,
+        subscribe_seq: RequestSeq,
+    ) -> Result<ResponseReceiver, Error> {
+        let req = ww_client_server::RequestOwned {
+            seq: 0,
+            path_kind,
+            kind: RequestKindOwned::StreamSideband {
+                sideband_cmd: StreamSidebandCommand::Open,
+            },
+        };
+        let mut scratch = [0u8; 1024]; // TODO: use Vec flavor or recycle?
+        let req = req.to_ww_bytes(&mut scratch)?;
+        self.send_message_with_seq(req.to_vec(), None, subscribe_seq, None)
+    }
+
     pub(crate) fn send_introspect(
         &self,
         _timeout: Option<Duration>,
@@ -552,11 +734,12 @@ impl TransportCommander {
         };
         let mut scratch = [0u8; 1024]; // TODO: use Vec flavor or recycle?
         let req = req.to_ww_bytes(&mut scratch)?;
-        let (stream_event_tx, stream_event_rx) = mpsc::unbounded_channel();
+        let (stream_event_tx, stream_event_rx) = stream_update_channel(None);
         self.cmd_tx
             .send(Command::OnStreamEvent {
                 path_kind: Box::new(PathKindOwned::Absolute { path: vec![] }),
                 stream_event_tx,
+                subscribe_seq: RequestSeq::default(),
             })
             .map_err(|_| Error::EventLoopNotRunning)?;
         self.cmd_tx
@@ -3,8 +3,9 @@ use std::net::IpAddr;
 #[derive(Clone, Debug)]
 pub struct DeviceFilter {
     pub kind: DeviceFilterKind,
-    // /// If a server process dedicated to the targeted device is already running, connect through it, instead of directly
-    // pub allow_ipc: bool,
+    /// If a server process dedicated to the targeted device is already running, connect through it, instead of directly
+    /// (see `ww daemon`). Daemon must be started with the same filter.
+    pub allow_ipc: bool,
     // /// Spawn an IPC process, that will establish an actual connection to the selected device and connect through it
     // pub require_ipc: bool,
 }
@@ -48,6 +49,7 @@ impl DeviceFilter {
     pub fn usb_vid_pid(vid: u16, pid: u16) -> DeviceFilter {
         Self {
             kind: DeviceFilterKind::UsbVidPid { vid, pid },
+            allow_ipc: false,
         }
     }
 
@@ -59,6 +61,7 @@ impl DeviceFilter {
                 manufacturer_contains: Some("vhrd"),
                 product_contains: Some("can"),
             },
+            allow_ipc: false,
         }
     }
 
@@ -70,9 +73,58 @@ impl DeviceFilter {
                 manufacturer_contains: Some("vhrd"),
                 product_contains: Some("io"),
             },
+            allow_ipc: false,
         }
     }
 
+    pub fn with_allow_ipc(mut self, allow_ipc: bool) -> Self {
+        self.allow_ipc = allow_ipc;
+        self
+    }
+
+    /// Short name identifying devices selected by this filter, used to find a daemon serving them.
+    pub fn ipc_name(&self) -> String {
+        let hex_or_any = |id: Option<u16>| id.map(|id| format!("{id:04x}")).unwrap_or("any".into());
+        let name = match &self.kind {
+            DeviceFilterKind::WebSocket { addr, port, path } => format!("ws-{addr}-{port}-{path}"),
+            DeviceFilterKind::UDP { addr, port } => format!("udp-{addr}-{port}"),
+            DeviceFilterKind::UsbFlexible {
+                vid,
+                pid,
+                manufacturer_contains,
+                product_contains,
+            } => format!(
+                "usb-{}-{}-{}-{}",
+                hex_or_any(*vid),
+                hex_or_any(*pid),
+                manufacturer_contains.unwrap_or("any"),
+                product_contains.unwrap_or("any")
+            ),
+            DeviceFilterKind::UsbVidPid { vid, pid } => format!("usb-{vid:04x}-{pid:04x}"),
+            DeviceFilterKind::UsbVidPidAndSerial { vid, pid, serial } => {
+                format!("usb-{vid:04x}-{pid:04x}-{serial}")
+            }
+            DeviceFilterKind::UsbPath { bus_id, port_chain } => {
+                let port_chain = port_chain
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                format!("usb-{bus_id}-{port_chain}")
+            }
+            DeviceFilterKind::Serial { serial } => format!("serial-{serial}"),
+        };
+        name.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
     pub fn as_web_socket(&self) -> Option<(IpAddr, u16, String)> {
         if let DeviceFilterKind::WebSocket { addr, port, path } = self.kind.clone() {
             Some((addr, port, path))
@@ -96,6 +148,7 @@ impl DeviceFilter {
                 bus_id: device_info.bus_id().to_string(),
                 port_chain: device_info.port_chain().to_vec(),
             },
+            allow_ipc: false,
         }
     }
 
@@ -1,7 +1,10 @@
+use crate::restore::{ActiveResources, ConnectionEvent, is_device_changed};
 use crate::tracing::TraceEvent;
-use crate::{DeviceInfoBundle, Error, OnError};
+use crate::{DeviceFilter, DeviceInfoBundle, Error, OnError};
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+use ww_self::ApiBundleOwned;
 use ww_version::FullVersionOwned;
 
 pub struct CommonState {
@@ -18,6 +21,14 @@ pub struct CommonState {
     // pub remote_api_model_version: Option<CompactVersion>,
     pub device_info: Option<DeviceInfoBundle>,
     pub tracers: Vec<mpsc::UnboundedSender<TraceEvent>>,
+    /// Filter from the last Connect command, used to reconnect automatically with [OnError::KeepRetrying]
+    pub filter: Option<DeviceFilter>,
+    /// Device info from the previous connection, used to detect firmware changes
+    pub previous_device_info: Option<DeviceInfoBundle>,
+    pub active_resources: ActiveResources,
+    pub connection_event_txs: Vec<mpsc::UnboundedSender<ConnectionEvent>>,
+    /// ApiBundle provided while disconnected, handed over to RxDispatcher once link is up
+    pub pending_api_bundle: Option<Box<ApiBundleOwned>>,
 }
 
 impl Default for CommonState {
@@ -32,12 +43,20 @@ impl Default for CommonState {
             client_version: None,
             device_info: None,
             tracers: vec![],
+            filter: None,
+            previous_device_info: None,
+            active_resources: ActiveResources::default(),
+            connection_event_txs: vec![],
+            pending_api_bundle: None,
         }
     }
 }
 
 impl CommonState {
     pub fn on_disconnect(&mut self) {
+        if self.link_up {
+            self.send_connection_event(ConnectionEvent::Disconnected);
+        }
         self.link_up = false;
         self.packet_started_instant = None;
         self.connected_tx = None;
@@ -45,23 +64,103 @@ impl CommonState {
 
     pub fn on_connect(
         &mut self,
+        filter: &DeviceFilter,
         on_error: OnError,
         connected_tx: Option<oneshot::Sender<Result<DeviceInfoBundle, Error>>>,
         client_version: FullVersionOwned,
     ) {
+        self.filter = Some(filter.clone());
         self.exit_on_error = on_error != OnError::KeepRetrying;
         self.connected_tx = connected_tx;
         self.client_version = Some(client_version);
     }
 
-    pub fn on_link_up(&mut self) {
+    /// Call after link setup is complete and [CommonState::device_info] is known.
+    ///
+    /// Returns requests that must be sent to a device to restore streams, subscriptions and rate limits from the
+    /// previous connection. Nothing is restored if a device reports a different user API.
+    pub fn on_link_up(&mut self) -> Vec<Vec<u8>> {
         self.link_up = true;
         self.tracers.retain_mut(|tx| {
             tx.send(TraceEvent::Connected {
-                info: Box::new(crate::tracing::ConnectionInfo {}),
+                info: Box::new(crate::tracing::ConnectionInfo {
+                    device_info: self.device_info.clone(),
+                }),
             })
             .is_ok()
         });
+        let current = self
+            .device_info
+            .clone()
+            .unwrap_or_else(DeviceInfoBundle::empty);
+        let (event, restore) = match self.previous_device_info.replace(current.clone()) {
+            Some(previous) if is_device_changed(&previous, &current) => {
+                warn!(
+                    "device changed: {previous:?} -> {current:?}, not restoring streams and subscriptions"
+                );
+                self.active_resources.clear();
+                let event = ConnectionEvent::DeviceChanged {
+                    previous: Box::new(previous),
+                    current: Box::new(current),
+                };
+                (event, vec![])
+            }
+            Some(_) => {
+                let restore = self.active_resources.requests();
+                let event = ConnectionEvent::Reconnected {
+                    info: Box::new(current),
+                    restored_requests: restore.len(),
+                };
+                (event, restore)
+            }
+            None => (
+                ConnectionEvent::Connected(Box::new(current)),
+                self.active_resources.requests(),
+            ),
+        };
+        self.send_connection_event(event);
+        restore
+    }
+
+    /// Filter to reconnect to after the link was lost, only available with [OnError::KeepRetrying].
+    pub fn reconnect_filter(&self) -> Option<DeviceFilter> {
+        if self.exit_on_error {
+            None
+        } else {
+            self.filter.clone()
+        }
+    }
+
+    /// Do not reconnect automatically, e.g., after a client requested disconnect.
+    pub fn stop_reconnecting(&mut self) {
+        self.filter = None;
+    }
+
+    /// Remember stream opens, subscriptions and rate limits and trace the request.
+    pub fn on_request(&mut self, bytes: &[u8]) {
+        self.active_resources.on_request(bytes);
+        self.trace_request(bytes);
+    }
+
+    pub fn add_connection_event_tx(&mut self, event_tx: mpsc::UnboundedSender<ConnectionEvent>) {
+        if self.link_up {
+            let info = self
+                .device_info
+                .clone()
+                .unwrap_or_else(DeviceInfoBundle::empty);
+            if event_tx
+                .send(ConnectionEvent::Connected(Box::new(info)))
+                .is_err()
+            {
+                return;
+            }
+        }
+        self.connection_event_txs.push(event_tx);
+    }
+
+    fn send_connection_event(&mut self, event: ConnectionEvent) {
+        self.connection_event_txs
+            .retain(|tx| tx.send(event.clone()).is_ok());
     }
 
     pub fn trace_request(&mut self, bytes: &[u8]) {
@@ -92,6 +191,15 @@ impl CommonState {
         });
     }
 
+    pub fn trace_api_bundle(&mut self, api_bundle: &ApiBundleOwned) {
+        self.tracers.retain_mut(|tx| {
+            tx.send(TraceEvent::ApiBundle {
+                api_bundle: Box::new(api_bundle.clone()),
+            })
+            .is_ok()
+        });
+    }
+
     pub fn trace_error(&mut self, reason: String) {
         self.tracers.retain_mut(|tx| {
             tx.send(TraceEvent::Error {
@@ -21,6 +21,7 @@ impl Introspect {
         let mut stream = Stream {
             transport_cmd_tx: self.transport_cmd_tx,
             path_kind: PathKindOwned::Absolute { path: vec![] },
+            subscribe_seq: Default::default(),
             rx,
             _phantom: Default::default(),
         };
@@ -38,6 +39,7 @@ impl Introspect {
         let mut stream = Stream {
             transport_cmd_tx: self.transport_cmd_tx,
             path_kind: PathKindOwned::Absolute { path: vec![] },
+            subscribe_seq: Default::default(),
             rx,
             _phantom: Default::default(),
         };
@@ -0,0 +1,895 @@
+//! Shared access to one device from several processes through a local daemon (see `ww daemon`).
+//!
+//! Daemon owns a device connection and listens on a Unix socket, its path is derived from a [DeviceFilter]
+//! (see [socket_path]). Clients with [DeviceFilter::allow_ipc] set connect through it transparently, if it is running
+//! (see [prefer_ipc]).
+//!
+//! Each client uses its own request seq numbers, daemon maps them onto the device connection and back. Stream data,
+//! stream sideband and subscription events are fanned out to all the clients. Streams and subscriptions are opened on
+//! a device once and closed after the last client closes them or goes away. Cancelled requests are cancelled on the device
+//! connection as well, including the ones left behind by a client that went away.
+//!
+//! Messages are length prefixed [IpcFrame]'s serialized with shrink_wrap. Length is u32 little endian.
+
+use crate::capture::CapturedDeviceInfo;
+use crate::event_loop_state::CommonState;
+use crate::restore::ConnectionEvent;
+use crate::rx_dispatcher::{
+    DispatcherCommand, DispatcherMessage, RequestSeq, RxDispatcher, response_channel,
+};
+use crate::tracing::TraceEvent;
+use crate::{
+    CancelRequest, Command, CommandSender, DeviceFilter, DeviceInfoBundle, Error, SeqTy,
+    TestProgress,
+};
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tracing::{debug, info, trace, warn};
+use wire_weaver::prelude::*;
+use ww_client_server::{
+    Event, EventKind, EventKindOwned, EventOwned, PathKind, Request, RequestKind,
+    StreamSidebandCommand,
+};
+use ww_version::{FullVersionOwned, VersionOwned};
+
+/// How long the daemon waits for a device to answer a client request, if a client did not send its own timeout.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often a client tries to reach a daemon again after it went away, with [OnError::KeepRetrying](crate::OnError::KeepRetrying).
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+#[derive_shrink_wrap]
+#[ww_repr(nib)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum IpcFrame {
+    /// Client to daemon, sent once after connecting
+    Hello { client_version: FullVersionOwned },
+    /// Daemon to client, Hello was accepted. None if a device is not connected at the moment.
+    Welcome {
+        device_info: Option<CapturedDeviceInfo>,
+    },
+    /// Daemon to client, client API is not compatible with a device. Connection is closed afterward.
+    Rejected { reason: String },
+    /// Client to daemon, serialized ww_client_server::Request, with how long a client waits for a response in ms.
+    Request {
+        bytes: Vec<u8>,
+        timeout_ms: Option<u32>,
+    },
+    /// Daemon to client, serialized ww_client_server::Event
+    Event { bytes: Vec<u8> },
+    /// Daemon to client, device connected or reconnected
+    DeviceConnected { device_info: CapturedDeviceInfo },
+    /// Daemon to client, device disconnected
+    DeviceDisconnected,
+}
+
+/// Path of a Unix socket of a daemon serving devices selected by filter: `$XDG_RUNTIME_DIR/ww-<filter>.sock`,
+/// or in a temporary directory.
+pub fn socket_path(filter: &DeviceFilter) -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    dir.join(format!("ww-{}.sock", filter.ipc_name()))
+}
+
+/// Whether a daemon serving devices selected by filter is running.
+pub async fn is_daemon_running(filter: &DeviceFilter) -> bool {
+    UnixStream::connect(socket_path(filter)).await.is_ok()
+}
+
+/// Bind a daemon socket for devices selected by filter, removing a stale socket file left by a previous daemon.
+pub async fn bind(filter: &DeviceFilter) -> Result<UnixListener, Error> {
+    let path = socket_path(filter);
+    if path.exists() {
+        if UnixStream::connect(&path).await.is_ok() {
+            return Err(Error::Transport(format!(
+                "daemon is already running at {}",
+                path.display()
+            )));
+        }
+        std::fs::remove_file(&path).map_err(ipc_err)?;
+    }
+    UnixListener::bind(&path).map_err(ipc_err)
+}
+
+/// Serve a device to local clients. `device` is a command sender of any transport event loop, it might not be connected yet,
+/// clients are notified whenever a device is connected or disconnected.
+///
+/// Returns if the device event loop exits or on socket error.
+pub async fn serve(listener: UnixListener, device: CommandSender) -> Result<(), Error> {
+    let (trace_event_tx, mut trace_rx) = mpsc::unbounded_channel();
+    device.send(Command::RegisterTracer { trace_event_tx })?;
+    let mut connection_events = device.connection_events()?;
+    let (frames_tx, mut frames_rx) = mpsc::unbounded_channel();
+    let (completed_tx, mut completed_rx) = mpsc::unbounded_channel();
+    let mut daemon = Daemon {
+        device,
+        device_info: None,
+        clients: HashMap::new(),
+        next_client_id: 0,
+        pending: HashMap::new(),
+        completed_tx,
+    };
+    loop {
+        tokio::select! {
+            r = listener.accept() => {
+                let (stream, _) = r.map_err(ipc_err)?;
+                daemon.on_client(stream, frames_tx.clone());
+            }
+            Some((client_id, frame)) = frames_rx.recv() => {
+                daemon.on_frame(client_id, frame);
+            }
+            Some(key) = completed_rx.recv() => {
+                daemon.pending.remove(&key);
+            }
+            event = trace_rx.recv() => {
+                match event {
+                    Some(TraceEvent::Event { bytes }) => daemon.on_device_event(bytes),
+                    Some(_) => {}
+                    None => return Err(Error::EventLoopNotRunning),
+                }
+            }
+            Some(event) = connection_events.recv() => {
+                daemon.on_connection_event(event);
+            }
+        }
+    }
+}
+
+type ClientId = u32;
+
+struct Daemon {
+    device: CommandSender,
+    device_info: Option<DeviceInfoBundle>,
+    clients: HashMap<ClientId, Client>,
+    next_client_id: ClientId,
+    /// Requests awaiting a response from a device, by client and client's seq, with seq used on the device connection
+    pending: HashMap<(ClientId, SeqTy), RequestSeq>,
+    completed_tx: mpsc::UnboundedSender<(ClientId, SeqTy)>,
+}
+
+struct Client {
+    tx: mpsc::UnboundedSender<IpcFrame>,
+    /// Hello was received and accepted
+    accepted: bool,
+    /// Streams opened and properties subscribed to by this client
+    resources: HashSet<(Resource, Vec<u8>)>,
+}
+
+/// Kind of resource shared between clients, with serialized PathKind
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Resource {
+    Stream,
+    Property,
+}
+
+/// Event a client expects in response to a request, device event loop only gives back its payload.
+#[derive(Copy, Clone)]
+enum Reply {
+    ReturnValue,
+    ReadValue,
+    Written,
+    /// Absolute path of a subscribed resource, e.g., of a trait-addressed stream
+    Subscribed,
+}
+
+impl Daemon {
+    fn on_client(
+        &mut self,
+        stream: UnixStream,
+        frames_tx: mpsc::UnboundedSender<(ClientId, Option<IpcFrame>)>,
+    ) {
+        let client_id = self.next_client_id;
+        self.next_client_id = self.next_client_id.wrapping_add(1);
+        debug!("IPC client {client_id} connected");
+        let (rd, wr) = stream.into_split();
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(write_frames(wr, rx));
+        tokio::spawn(read_frames(rd, frames_tx, move |frame| (client_id, frame)));
+        self.clients.insert(
+            client_id,
+            Client {
+                tx,
+                accepted: false,
+                resources: HashSet::new(),
+            },
+        );
+    }
+
+    fn on_frame(&mut self, client_id: ClientId, frame: Option<IpcFrame>) {
+        match frame {
+            Some(IpcFrame::Hello { client_version }) => self.on_hello(client_id, client_version),
+            Some(IpcFrame::Request { bytes, timeout_ms }) => {
+                let timeout = timeout_ms.map(|ms| Duration::from_millis(ms as u64));
+                self.on_request(client_id, bytes, timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT))
+            }
+            Some(frame) => warn!("unexpected frame from IPC client {client_id}: {frame:?}"),
+            None => self.on_client_gone(client_id),
+        }
+    }
+
+    fn on_hello(&mut self, client_id: ClientId, client_version: FullVersionOwned) {
+        let Some(client) = self.clients.get_mut(&client_id) else {
+            return;
+        };
+        if let Some(info) = &self.device_info
+            && !client_version.crate_id.is_empty()
+            && !info.user_api_version.crate_id.is_empty()
+            && !client_version.is_protocol_compatible(&info.user_api_version)
+        {
+            let reason = format!(
+                "client API {client_version:?} is not compatible with device API {:?}",
+                info.user_api_version
+            );
+            debug!("IPC client {client_id} rejected: {reason}");
+            _ = client.tx.send(IpcFrame::Rejected { reason });
+            self.clients.remove(&client_id);
+            return;
+        }
+        client.accepted = true;
+        _ = client.tx.send(IpcFrame::Welcome {
+            device_info: self.device_info.as_ref().map(CapturedDeviceInfo::from),
+        });
+    }
+
+    fn on_request(&mut self, client_id: ClientId, bytes: Vec<u8>, timeout: Duration) {
+        let (seq, reply, forward) = {
+            let Ok(request) = Request::from_ww_bytes(&bytes) else {
+                warn!("malformed request from IPC client {client_id}, ignoring");
+                return;
+            };
+            if let RequestKind::Cancel { seq } = request.kind {
+                self.on_cancel(client_id, seq, request.path_kind.make_owned().ok());
+                return;
+            }
+            let is_open = matches!(
+                request.kind,
+                RequestKind::Subscribe
+                    | RequestKind::StreamSideband {
+                        sideband_cmd: StreamSidebandCommand::Open
+                    }
+            );
+            let reply = match request.kind {
+                RequestKind::Read => Reply::ReadValue,
+                RequestKind::Write { .. } => Reply::Written,
+                RequestKind::Subscribe => Reply::Subscribed,
+                _ if is_open && !matches!(request.path_kind, PathKind::Absolute { .. }) => {
+                    Reply::Subscribed
+                }
+                _ => Reply::ReturnValue,
+            };
+            // an open with a seq number is answered by a device, e.g., with a Subscribed event carrying the absolute
+            // path of a trait-addressed stream, so it is forwarded even if the resource is already opened
+            let forward = self.track(client_id, &request) || (request.seq != 0 && is_open);
+            (request.seq, reply, forward)
+        };
+        if !forward {
+            trace!(
+                "not forwarding request from IPC client {client_id}, resource is already in the same state"
+            );
+            return;
+        }
+        if seq == 0 {
+            _ = self.device.send(Command::SendMessage {
+                bytes,
+                done_tx: None,
+            });
+            return;
+        }
+        let Some(client) = self.clients.get(&client_id) else {
+            return;
+        };
+        let (done_tx, done_rx, device_seq) = response_channel();
+        if self
+            .device
+            .send(Command::SendMessage {
+                bytes,
+                done_tx: Some((done_tx, timeout)),
+            })
+            .is_err()
+        {
+            return;
+        }
+        self.pending.insert((client_id, seq), device_seq);
+        let tx = client.tx.clone();
+        let completed_tx = self.completed_tx.clone();
+        tokio::spawn(async move {
+            let response = done_rx.await;
+            _ = completed_tx.send((client_id, seq));
+            let result = match response {
+                Ok(Ok(data)) => match reply {
+                    Reply::ReturnValue => Ok(EventKindOwned::ReturnValue { data }),
+                    Reply::ReadValue => Ok(EventKindOwned::ReadValue { data }),
+                    Reply::Written => Ok(EventKindOwned::Written),
+                    Reply::Subscribed => match Vec::<UNib32>::from_ww_bytes_owned(&data) {
+                        Ok(path) => Ok(EventKindOwned::Subscribed { path }),
+                        Err(e) => {
+                            debug!("request {seq} from IPC client {client_id}, bad path: {e:?}");
+                            Err(daemon_error("daemon: malformed Subscribed path"))
+                        }
+                    },
+                },
+                Ok(Err(Error::RemoteError(e))) => Err(e),
+                Ok(Err(e)) => {
+                    debug!("request {seq} from IPC client {client_id} failed: {e:?}");
+                    Err(daemon_error(&format!("daemon: {e}")))
+                }
+                Err(_) => Err(daemon_error("daemon: request was dropped")),
+            };
+            let event = EventOwned { seq, result };
+            match shrink_wrap::to_ww_vec(&event, MAX_FRAME_LEN) {
+                Ok(bytes) => {
+                    _ = tx.send(IpcFrame::Event { bytes });
+                }
+                Err(e) => warn!("failed to serialize event for IPC client {client_id}: {e:?}"),
+            }
+        });
+    }
+
+    /// Cancel a request on the device connection, notifying a device if a client did so.
+    fn on_cancel(
+        &mut self,
+        client_id: ClientId,
+        client_seq: SeqTy,
+        notify_path: Option<ww_client_server::PathKindOwned>,
+    ) {
+        let Some(seq) = self.pending.remove(&(client_id, client_seq)) else {
+            trace!("IPC client {client_id} cancelled {client_seq}, which is not pending");
+            return;
+        };
+        _ = self.device.send(Command::Cancel {
+            request: Box::new(CancelRequest { seq, notify_path }),
+        });
+    }
+
+    /// Keep track of streams and subscriptions of each client, returns false if a request does not have to be sent to
+    /// a device, because another client is already holding the same resource open.
+    fn track(&mut self, client_id: ClientId, request: &Request) -> bool {
+        let (resource, open) = match request.kind {
+            RequestKind::StreamSideband {
+                sideband_cmd: StreamSidebandCommand::Open,
+            } => (Resource::Stream, true),
+            RequestKind::StreamSideband {
+                sideband_cmd: StreamSidebandCommand::Close,
+            } => (Resource::Stream, false),
+            RequestKind::Subscribe => (Resource::Property, true),
+            RequestKind::Unsubscribe => (Resource::Property, false),
+            _ => return true,
+        };
+        let path = match shrink_wrap::to_ww_vec(&request.path_kind, MAX_FRAME_LEN) {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("not tracking {:?}: {e:?}", request.path_kind);
+                return true;
+            }
+        };
+        let key = (resource, path);
+        let held_by_others = self.is_held_by_others(client_id, &key);
+        let Some(client) = self.clients.get_mut(&client_id) else {
+            return false;
+        };
+        if open {
+            client.resources.insert(key);
+        } else {
+            client.resources.remove(&key);
+        }
+        !held_by_others
+    }
+
+    fn is_held_by_others(&self, client_id: ClientId, key: &(Resource, Vec<u8>)) -> bool {
+        self.clients
+            .iter()
+            .any(|(id, c)| *id != client_id && c.resources.contains(key))
+    }
+
+    /// Close streams and unsubscribe from properties, that no one else is using.
+    fn on_client_gone(&mut self, client_id: ClientId) {
+        let Some(client) = self.clients.remove(&client_id) else {
+            return;
+        };
+        debug!("IPC client {client_id} disconnected");
+        let abandoned: Vec<SeqTy> = self
+            .pending
+            .keys()
+            .filter(|(id, _)| *id == client_id)
+            .map(|(_, seq)| *seq)
+            .collect();
+        for seq in abandoned {
+            self.on_cancel(client_id, seq, None);
+        }
+        for key in client.resources {
+            if self.is_held_by_others(client_id, &key) {
+                continue;
+            }
+            let (resource, path) = key;
+            let Ok(path_kind) = PathKind::from_ww_bytes(&path) else {
+                continue;
+            };
+            let kind = match resource {
+                Resource::Stream => RequestKind::StreamSideband {
+                    sideband_cmd: StreamSidebandCommand::Close,
+                },
+                Resource::Property => RequestKind::Unsubscribe,
+            };
+            let request = Request {
+                seq: 0,
+                path_kind,
+                kind,
+            };
+            match shrink_wrap::to_ww_vec(&request, MAX_FRAME_LEN) {
+                Ok(bytes) => {
+                    _ = self.device.send(Command::SendMessage {
+                        bytes,
+                        done_tx: None,
+                    });
+                }
+                Err(e) => warn!("failed to serialize close request: {e:?}"),
+            }
+        }
+    }
+
+    /// Responses are sent to their clients with original seq numbers in [Self::on_request], only events not tied to
+    /// a particular request are fanned out. Subscribed events are responses as well, sent only to a client that
+    /// opened a stream or subscribed.
+    fn on_device_event(&mut self, bytes: Vec<u8>) {
+        let is_shared = matches!(
+            Event::from_ww_bytes(&bytes),
+            Ok(Event {
+                result: Ok(EventKind::StreamData { .. } | EventKind::StreamSideband { .. }),
+                ..
+            })
+        );
+        if is_shared {
+            self.broadcast(IpcFrame::Event { bytes });
+        }
+    }
+
+    fn on_connection_event(&mut self, event: ConnectionEvent) {
+        match event {
+            ConnectionEvent::Connected(info) | ConnectionEvent::Reconnected { info, .. } => {
+                info!("device connected: {:?}", info.user_api_version);
+                self.on_device_connected(*info);
+            }
+            ConnectionEvent::DeviceChanged { current, .. } => {
+                info!("device changed: {:?}", current.user_api_version);
+                // nothing was restored on a device, clients will open streams and subscribe again
+                for client in self.clients.values_mut() {
+                    client.resources.clear();
+                }
+                self.on_device_connected(*current);
+            }
+            ConnectionEvent::Disconnected => {
+                info!("device disconnected");
+                self.device_info = None;
+                self.broadcast(IpcFrame::DeviceDisconnected);
+            }
+        }
+    }
+
+    fn on_device_connected(&mut self, info: DeviceInfoBundle) {
+        self.broadcast(IpcFrame::DeviceConnected {
+            device_info: CapturedDeviceInfo::from(&info),
+        });
+        self.device_info = Some(info);
+    }
+
+    fn broadcast(&mut self, frame: IpcFrame) {
+        self.clients
+            .retain(|_, c| !c.accepted || c.tx.send(frame.clone()).is_ok());
+    }
+}
+
+/// Error reported to a client, when a request failed on the device connection (e.g., timed out or device disconnected).
+fn daemon_error(msg: &str) -> ww_client_server::ErrorOwned {
+    ww_client_server::Error::new(0, ww_client_server::ErrorKind::UserStr(msg)).make_owned()
+}
+
+/// Connect through a daemon if [DeviceFilter::allow_ipc] is set and a daemon serving the same filter is running,
+/// otherwise run `direct` event loop (e.g., USB host) as usual.
+///
+/// Commands sent before Connect are handed over to whichever event loop is used.
+pub async fn prefer_ipc<F: Future<Output = ()>>(
+    mut cmd_rx: mpsc::UnboundedReceiver<Command>,
+    direct: impl FnOnce(mpsc::UnboundedReceiver<Command>) -> F,
+) {
+    let mut early = vec![];
+    while let Some(cmd) = cmd_rx.recv().await {
+        let use_ipc = match &cmd {
+            Command::Connect { filter, .. } => {
+                Some(filter.allow_ipc && is_daemon_running(filter).await)
+            }
+            _ => None,
+        };
+        early.push(cmd);
+        match use_ipc {
+            Some(true) => {
+                debug!("connecting through daemon");
+                run_client(early, cmd_rx).await;
+                return;
+            }
+            Some(false) => break,
+            None => {}
+        }
+    }
+    let (direct_tx, direct_rx) = mpsc::unbounded_channel();
+    for cmd in early {
+        _ = direct_tx.send(cmd);
+    }
+    let direct = direct(direct_rx);
+    tokio::pin!(direct);
+    let relay = async move {
+        while let Some(cmd) = cmd_rx.recv().await {
+            if direct_tx.send(cmd).is_err() {
+                break;
+            }
+        }
+    };
+    tokio::select! {
+        _ = &mut direct => return,
+        _ = relay => {}
+    }
+    direct.await;
+}
+
+/// Client event loop connecting to a device through a daemon only.
+pub async fn ipc_worker(cmd_rx: mpsc::UnboundedReceiver<Command>) {
+    run_client(vec![], cmd_rx).await;
+}
+
+async fn run_client(early: Vec<Command>, mut cmd_rx: mpsc::UnboundedReceiver<Command>) {
+    let mut state = ClientState {
+        common: CommonState::default(),
+        link: None,
+        scratch: vec![],
+    };
+    let mut rx_dispatcher = RxDispatcher::default();
+    for cmd in early {
+        if state
+            .handle_command(cmd, &mut rx_dispatcher)
+            .await
+            .is_break()
+        {
+            return;
+        }
+    }
+    loop {
+        let prune_in = rx_dispatcher.prune_next_timeout();
+        let reconnect = state.link.is_none() && state.common.reconnect_filter().is_some();
+        tokio::select! {
+            // stream handlers registered before a shared stream was opened by another client must see its data
+            biased;
+            cmd = cmd_rx.recv() => {
+                let Some(cmd) = cmd else {
+                    debug!("IPC client exiting, because all command senders were dropped");
+                    break;
+                };
+                if state.handle_command(cmd, &mut rx_dispatcher).await.is_break() {
+                    break;
+                }
+            }
+            frame = recv_frame(&mut state.link) => {
+                state.handle_frame(frame, &mut rx_dispatcher).await;
+            }
+            _ = tokio::time::sleep(RECONNECT_INTERVAL), if reconnect => {
+                if let Err(e) = state.open_link().await {
+                    trace!("daemon is still not reachable: {e:?}");
+                }
+            }
+            _ = tokio::time::sleep(prune_in) => {}
+        }
+    }
+    debug!("IPC client exited");
+}
+
+struct ClientState {
+    common: CommonState,
+    link: Option<ClientLink>,
+    scratch: Vec<u8>,
+}
+
+struct ClientLink {
+    wr: OwnedWriteHalf,
+    frames_rx: mpsc::UnboundedReceiver<Option<IpcFrame>>,
+}
+
+async fn recv_frame(link: &mut Option<ClientLink>) -> Option<IpcFrame> {
+    match link {
+        Some(link) => link.frames_rx.recv().await.flatten(),
+        None => std::future::pending().await,
+    }
+}
+
+impl ClientState {
+    async fn handle_command(
+        &mut self,
+        cmd: Command,
+        rx_dispatcher: &mut RxDispatcher,
+    ) -> ControlFlow<()> {
+        match cmd {
+            Command::Connect {
+                filter,
+                client_version,
+                on_error,
+                connected_tx,
+            } => {
+                if self.link.is_some() {
+                    warn!("Ignoring Connect while already connected");
+                    return ControlFlow::Continue(());
+                }
+                self.common
+                    .on_connect(&filter, on_error, connected_tx, *client_version);
+                if let Err(e) = self.open_link().await {
+                    if self.common.exit_on_error {
+                        if let Some(tx) = self.common.connected_tx.take() {
+                            _ = tx.send(Err(e));
+                        }
+                    } else {
+                        debug!("daemon is not reachable: {e:?}, will keep trying");
+                    }
+                }
+            }
+            Command::RegisterTracer { trace_event_tx } => {
+                self.common.tracers.push(trace_event_tx);
+            }
+            Command::DisconnectKeepStreams { disconnected_tx } => {
+                self.common.trace_disconnect("client request", true);
+                self.common.stop_reconnecting();
+                self.close_link(rx_dispatcher);
+                if let Some(tx) = disconnected_tx {
+                    _ = tx.send(());
+                }
+            }
+            Command::DisconnectAndExit { disconnected_tx } => {
+                self.common.trace_disconnect("client request", false);
+                self.close_link(rx_dispatcher);
+                if let Some(tx) = disconnected_tx {
+                    _ = tx.send(());
+                }
+                return ControlFlow::Break(());
+            }
+            Command::SendMessage {
+                mut bytes,
+                mut done_tx,
+            } => {
+                let timeout = done_tx.as_ref().map(|(_, timeout)| *timeout);
+                if let Some((done_tx, timeout)) = done_tx.take() {
+                    if let Some(seq) = rx_dispatcher.next_seq() {
+                        Request::set_seq(&mut bytes, seq);
+                        rx_dispatcher.handle_cmd(DispatcherCommand::OnReturn {
+                            seq,
+                            done_tx,
+                            timeout,
+                        });
+                    } else {
+                        _ = done_tx.send(Err(Error::Other("No more request IDs available".into())));
+                    }
+                }
+                if !self.common.link_up {
+                    warn!("ignoring send message while disconnected");
+                    // streams opened while disconnected are opened after connection
+                    self.common.active_resources.on_request(&bytes);
+                    return ControlFlow::Continue(());
+                }
+                self.send_request(bytes, timeout, rx_dispatcher).await;
+            }
+            Command::Cancel { request } => {
+                rx_dispatcher.cancel(request.seq.get());
+                if !self.common.link_up {
+                    return ControlFlow::Continue(());
+                }
+                if let Some(bytes) = request.notification() {
+                    self.send_request(bytes, None, rx_dispatcher).await;
+                }
+            }
+            Command::OnStreamEvent {
+                path_kind,
+                stream_event_tx,
+                subscribe_seq,
+            } => {
+                rx_dispatcher.handle_cmd(DispatcherCommand::OnStreamEvent {
+                    path_kind: *path_kind,
+                    stream_event_tx,
+                    subscribe_seq,
+                });
+            }
+            Command::SetApiBundle { api_bundle } => {
+                self.common.trace_api_bundle(&api_bundle);
+                rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle { api_bundle });
+            }
+            Command::OnConnectionEvent { event_tx } => {
+                self.common.add_connection_event_tx(event_tx);
+            }
+            Command::LoopbackTest { progress_tx, .. } => {
+                _ = progress_tx.send(TestProgress::FatalError(
+                    "Not supported through IPC daemon".into(),
+                ));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    async fn handle_frame(&mut self, frame: Option<IpcFrame>, rx_dispatcher: &mut RxDispatcher) {
+        match frame {
+            Some(IpcFrame::Welcome {
+                device_info: Some(info),
+            })
+            | Some(IpcFrame::DeviceConnected { device_info: info }) => {
+                self.on_device_connected((&info).into(), rx_dispatcher)
+                    .await;
+            }
+            Some(IpcFrame::Welcome { device_info: None }) => {
+                if self.common.exit_on_error
+                    && let Some(tx) = self.common.connected_tx.take()
+                {
+                    _ = tx.send(Err(Error::DeviceNotFound));
+                    self.link = None;
+                } else {
+                    debug!("daemon is waiting for a device to connect");
+                }
+            }
+            Some(IpcFrame::Rejected { reason }) => {
+                warn!("daemon rejected connection: {reason}");
+                if let Some(tx) = self.common.connected_tx.take() {
+                    _ = tx.send(Err(Error::IncompatibleDeviceProtocol));
+                }
+                self.common.stop_reconnecting();
+                self.link = None;
+            }
+            Some(IpcFrame::Event { bytes }) => {
+                self.common.trace_event(&bytes);
+                rx_dispatcher.handle_msg(DispatcherMessage::MessageBytes(&bytes));
+            }
+            Some(IpcFrame::DeviceDisconnected) => {
+                self.on_device_disconnected("device disconnected from daemon", rx_dispatcher);
+            }
+            Some(frame) => warn!("unexpected frame from daemon: {frame:?}"),
+            None => {
+                if let Some(tx) = self.common.connected_tx.take() {
+                    _ = tx.send(Err(Error::Transport("daemon closed connection".into())));
+                }
+                self.on_device_disconnected("daemon closed connection", rx_dispatcher);
+                self.link = None;
+            }
+        }
+    }
+
+    async fn open_link(&mut self) -> Result<(), Error> {
+        let filter = self.common.filter.as_ref().ok_or(Error::Disconnected)?;
+        let stream = UnixStream::connect(socket_path(filter))
+            .await
+            .map_err(ipc_err)?;
+        let (rd, wr) = stream.into_split();
+        let (frames_tx, frames_rx) = mpsc::unbounded_channel();
+        tokio::spawn(read_frames(rd, frames_tx, |frame| frame));
+        self.link = Some(ClientLink { wr, frames_rx });
+        let client_version = self
+            .common
+            .client_version
+            .clone()
+            .unwrap_or_else(|| FullVersionOwned::new("".into(), VersionOwned::new(0, 0, 0)));
+        self.send_frame(&IpcFrame::Hello { client_version }).await
+    }
+
+    fn close_link(&mut self, rx_dispatcher: &mut RxDispatcher) {
+        self.common.on_disconnect();
+        self.link = None;
+        rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+    }
+
+    async fn on_device_connected(
+        &mut self,
+        device_info: DeviceInfoBundle,
+        rx_dispatcher: &mut RxDispatcher,
+    ) {
+        self.common.device_info = Some(device_info.clone());
+        rx_dispatcher.handle_msg(DispatcherMessage::Connected);
+        if let Some(tx) = self.common.connected_tx.take() {
+            _ = tx.send(Ok(device_info));
+        }
+        // daemon does not send requests to a device again, if they are already in effect
+        for request in self.common.on_link_up() {
+            self.send_request(request, None, rx_dispatcher).await;
+        }
+    }
+
+    fn on_device_disconnected(&mut self, reason: &str, rx_dispatcher: &mut RxDispatcher) {
+        if !self.common.link_up {
+            return;
+        }
+        self.common.trace_disconnect(reason, true);
+        self.common.on_disconnect();
+        rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+    }
+
+    async fn send_request(
+        &mut self,
+        bytes: Vec<u8>,
+        timeout: Option<Duration>,
+        rx_dispatcher: &mut RxDispatcher,
+    ) {
+        self.common.on_request(&bytes);
+        let timeout_ms = timeout.map(|t| u32::try_from(t.as_millis()).unwrap_or(u32::MAX));
+        if let Err(e) = self
+            .send_frame(&IpcFrame::Request { bytes, timeout_ms })
+            .await
+        {
+            warn!("failed to send request to daemon: {e:?}");
+            self.on_device_disconnected("daemon connection lost", rx_dispatcher);
+            self.link = None;
+        }
+    }
+
+    async fn send_frame(&mut self, frame: &IpcFrame) -> Result<(), Error> {
+        let Some(link) = &mut self.link else {
+            return Err(Error::Disconnected);
+        };
+        write_frame(&mut link.wr, frame, &mut self.scratch).await
+    }
+}
+
+async fn write_frame(
+    wr: &mut OwnedWriteHalf,
+    frame: &IpcFrame,
+    scratch: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let bytes = shrink_wrap::to_ww_scratch(frame, scratch, MAX_FRAME_LEN)?;
+    wr.write_all(&(bytes.len() as u32).to_le_bytes())
+        .await
+        .map_err(ipc_err)?;
+    wr.write_all(bytes).await.map_err(ipc_err)
+}
+
+async fn write_frames(mut wr: OwnedWriteHalf, mut rx: mpsc::UnboundedReceiver<IpcFrame>) {
+    let mut scratch = vec![];
+    while let Some(frame) = rx.recv().await {
+        if let Err(e) = write_frame(&mut wr, &frame, &mut scratch).await {
+            debug!("IPC write failed: {e:?}");
+            break;
+        }
+    }
+}
+
+async fn read_frame(rd: &mut OwnedReadHalf, buf: &mut Vec<u8>) -> Result<IpcFrame, Error> {
+    let mut len = [0u8; 4];
+    rd.read_exact(&mut len).await.map_err(ipc_err)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(Error::Transport(format!("IPC frame is too big: {len}")));
+    }
+    buf.resize(len, 0);
+    rd.read_exact(buf).await.map_err(ipc_err)?;
+    Ok(IpcFrame::from_ww_bytes_owned(buf)?)
+}
+
+/// Read frames until a socket is closed or a malformed frame is received, then send None.
+async fn read_frames<T: Send>(
+    mut rd: OwnedReadHalf,
+    tx: mpsc::UnboundedSender<T>,
+    map: impl Fn(Option<IpcFrame>) -> T + Send,
+) {
+    let mut buf = vec![];
+    loop {
+        match read_frame(&mut rd, &mut buf).await {
+            Ok(frame) => {
+                if tx.send(map(Some(frame))).is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                trace!("IPC read stopped: {e:?}");
+                _ = tx.send(map(None));
+                return;
+            }
+        }
+    }
+}
+
+fn ipc_err(e: std::io::Error) -> Error {
+    Error::Transport(format!("IPC: {e}"))
+}
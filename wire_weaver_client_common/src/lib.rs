@@ -1,13 +1,21 @@
 pub mod attachment;
+pub mod capture;
 mod command;
 pub mod command_sender;
 pub mod device_filter;
 pub mod event_loop_state;
 mod introspect;
+#[cfg(unix)]
+pub mod ipc;
+pub mod loopback;
+pub mod metrics;
+pub mod pcapng;
 mod prepared_call;
 mod prepared_read;
 mod prepared_write;
 pub mod promise;
+pub mod replay;
+pub mod restore;
 pub mod rx_dispatcher;
 mod sink;
 pub mod stream;
@@ -15,11 +23,12 @@ mod tracing;
 pub mod ww;
 
 // TODO: remove
+pub use crate::tracing::{ConnectionInfo, TraceEvent};
 pub use attachment::Attachment;
-pub use command::{Command, DeviceInfoBundle, TestProgress};
+pub use command::{CancelRequest, Command, DeviceInfoBundle, TestProgress, UserApiSignature};
 pub use command_sender::CommandSender;
 pub use device_filter::DeviceFilter;
-pub use prepared_call::PreparedCall;
+pub use prepared_call::{PendingCall, PreparedCall};
 pub use prepared_read::PreparedRead;
 pub use prepared_write::PreparedWrite;
 pub use sink::Sink;
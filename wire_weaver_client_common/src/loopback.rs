@@ -0,0 +1,481 @@
+//! In-process transport, connects a generated client to a generated server running in the same tokio runtime.
+//!
+//! Can be used to unit-test device logic without any hardware: requests sent by a client are processed by the server
+//! right away and events are routed back through the same [RxDispatcher] as with USB or WebSocket, so return values,
+//! streams, sinks and deferred method returns work exactly the same.
+//!
+//! Messages in both directions can optionally be dropped, delayed, reordered or corrupted, see [Faults].
+//!
+//! ```ignore
+//! let (transport_cmd_tx, transport_cmd_rx) = mpsc::unbounded_channel();
+//! let loopback = Loopback::default().with_event_faults(Faults { drop: 0.1, ..Default::default() });
+//! // server can keep a sink to send stream updates and deferred method returns later on
+//! let server = MyServer { sink: loopback.sink() };
+//! tokio::spawn(loopback.run(transport_cmd_rx, server));
+//! let mut cmd_tx = CommandSender::new(transport_cmd_tx);
+//! ```
+
+use crate::event_loop_state::CommonState;
+use crate::rx_dispatcher::{DispatcherCommand, DispatcherMessage, RxDispatcher};
+use crate::{Command, DeviceInfoBundle, Error, TestProgress};
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::{debug, trace, warn};
+use wire_weaver::MessageSink;
+use wire_weaver::shrink_wrap::Error as ShrinkWrapError;
+use ww_client_server::Request;
+use ww_version::FullVersionOwned;
+
+/// Server side of a loopback link.
+///
+/// Implement it by forwarding to the `process_request_bytes` method of a generated server (sync or async):
+/// ```ignore
+/// impl LoopbackServer for MyServer {
+///     async fn process_request_bytes<'a>(
+///         &mut self,
+///         bytes: &[u8],
+///         scratch_args: &'a mut [u8],
+///         scratch_event: &'a mut [u8],
+///         scratch_err: &'a mut [u8],
+///         msg_tx: &mut LoopbackSink,
+///     ) -> Result<&'a [u8], ShrinkWrapError> {
+///         self.process_request_bytes(bytes, scratch_args, scratch_event, scratch_err, msg_tx)
+///     }
+/// }
+/// ```
+pub trait LoopbackServer: Send {
+    fn process_request_bytes<'a>(
+        &mut self,
+        bytes: &[u8],
+        scratch_args: &'a mut [u8],
+        scratch_event: &'a mut [u8],
+        scratch_err: &'a mut [u8],
+        msg_tx: &mut LoopbackSink,
+    ) -> impl Future<Output = Result<&'a [u8], ShrinkWrapError>> + Send;
+}
+
+/// Delivers messages sent by a server (stream updates, deferred method returns, etc.) to a client.
+#[derive(Clone)]
+pub struct LoopbackSink {
+    tx: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+impl MessageSink for LoopbackSink {
+    fn send(&mut self, message: &[u8]) -> impl Future<Output = Result<(), ()>> {
+        core::future::ready(self.tx.send(message.to_vec()).map_err(|_| ()))
+    }
+}
+
+/// Faults injected into one direction of a loopback link, all of them are disabled by default.
+#[derive(Clone, Debug)]
+pub struct Faults {
+    /// Probability of a message being lost, from 0.0 to 1.0
+    pub drop: f32,
+    /// Probability of one bit in a message being flipped
+    pub corrupt: f32,
+    /// Probability of a message being held back for an additional [Faults::reorder_by], letting the following ones overtake it
+    pub reorder: f32,
+    pub reorder_by: Duration,
+    /// Latency added to every message
+    pub delay: Duration,
+}
+
+impl Default for Faults {
+    fn default() -> Self {
+        Faults {
+            drop: 0.0,
+            corrupt: 0.0,
+            reorder: 0.0,
+            reorder_by: Duration::from_millis(10),
+            delay: Duration::ZERO,
+        }
+    }
+}
+
+/// Loopback link configuration, call [Loopback::run] to start it.
+pub struct Loopback {
+    user_api_version: Option<FullVersionOwned>,
+    request_faults: Faults,
+    event_faults: Faults,
+    seed: u64,
+    scratch_len: usize,
+    sink: LoopbackSink,
+    sink_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+}
+
+impl Default for Loopback {
+    fn default() -> Self {
+        let (tx, sink_rx) = mpsc::unbounded_channel();
+        Loopback {
+            user_api_version: None,
+            request_faults: Faults::default(),
+            event_faults: Faults::default(),
+            seed: 0x5EED,
+            scratch_len: 4096,
+            sink: LoopbackSink { tx },
+            sink_rx,
+        }
+    }
+}
+
+/// Run loopback event loop with default configuration, see [Loopback::run].
+pub async fn loopback_worker(
+    cmd_rx: mpsc::UnboundedReceiver<Command>,
+    server: impl LoopbackServer,
+) {
+    Loopback::default().run(cmd_rx, server).await
+}
+
+impl Loopback {
+    /// Report this version to a client on connect. Compatibility is checked the same way as with a real device.
+    /// Empty version is reported and no checks are done if not set.
+    pub fn with_user_api_version(mut self, version: FullVersionOwned) -> Self {
+        self.user_api_version = Some(version);
+        self
+    }
+
+    /// Inject the same faults into requests and events.
+    pub fn with_faults(self, faults: Faults) -> Self {
+        self.with_request_faults(faults.clone())
+            .with_event_faults(faults)
+    }
+
+    /// Inject faults into requests sent from a client to a server.
+    pub fn with_request_faults(mut self, faults: Faults) -> Self {
+        self.request_faults = faults;
+        self
+    }
+
+    /// Inject faults into events sent from a server to a client.
+    pub fn with_event_faults(mut self, faults: Faults) -> Self {
+        self.event_faults = faults;
+        self
+    }
+
+    /// Seed for fault injection, the same seed and sequence of messages result in the same faults.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Size of each of the scratch buffers given to a server.
+    pub fn with_scratch_len(mut self, len: usize) -> Self {
+        self.scratch_len = len;
+        self
+    }
+
+    /// Sink that can be stored in a server to send messages outside of request processing.
+    pub fn sink(&self) -> LoopbackSink {
+        self.sink.clone()
+    }
+
+    /// Run loopback event loop until [Command::DisconnectAndExit] is received or all the command senders are dropped.
+    pub async fn run(
+        self,
+        mut cmd_rx: mpsc::UnboundedReceiver<Command>,
+        server: impl LoopbackServer,
+    ) {
+        let Loopback {
+            user_api_version,
+            request_faults,
+            event_faults,
+            seed,
+            scratch_len,
+            sink,
+            mut sink_rx,
+        } = self;
+        let mut state = State {
+            common: CommonState::default(),
+            server,
+            user_api_version,
+            msg_tx: sink,
+            requests: Link::new(request_faults),
+            events: Link::new(event_faults),
+            rng: XorShift(seed.max(1)),
+            scratch: [
+                vec![0u8; scratch_len],
+                vec![0u8; scratch_len],
+                vec![0u8; scratch_len],
+            ],
+        };
+        let mut rx_dispatcher = RxDispatcher::default();
+        loop {
+            state.process_due(&mut rx_dispatcher).await;
+            let prune_in = rx_dispatcher.prune_next_timeout();
+            let mut wake_at = Instant::now() + prune_in;
+            for due in [state.requests.next_due(), state.events.next_due()]
+                .into_iter()
+                .flatten()
+            {
+                wake_at = wake_at.min(due);
+            }
+            tokio::select! {
+                cmd = cmd_rx.recv() => {
+                    let Some(cmd) = cmd else {
+                        debug!("loopback worker exiting, because all command senders were dropped");
+                        break;
+                    };
+                    if state.handle_command(cmd, &mut rx_dispatcher).is_break() {
+                        break;
+                    }
+                }
+                Some(message) = sink_rx.recv() => {
+                    if state.common.link_up {
+                        state.events.push(message, &mut state.rng);
+                    } else {
+                        trace!("dropping server message while disconnected");
+                    }
+                }
+                _ = tokio::time::sleep_until(wake_at) => {}
+            }
+        }
+        debug!("loopback worker exited");
+    }
+}
+
+struct State<S> {
+    common: CommonState,
+    server: S,
+    user_api_version: Option<FullVersionOwned>,
+    msg_tx: LoopbackSink,
+    /// Requests in flight from a client to a server
+    requests: Link,
+    /// Events in flight from a server to a client
+    events: Link,
+    rng: XorShift,
+    scratch: [Vec<u8>; 3],
+}
+
+impl<S: LoopbackServer> State<S> {
+    fn handle_command(
+        &mut self,
+        cmd: Command,
+        rx_dispatcher: &mut RxDispatcher,
+    ) -> ControlFlow<()> {
+        match cmd {
+            Command::Connect {
+                filter,
+                on_error,
+                connected_tx,
+                client_version,
+            } => {
+                if self.common.link_up {
+                    warn!("Ignoring Connect while already connected");
+                    return ControlFlow::Continue(());
+                }
+                let mut device_info = DeviceInfoBundle::empty();
+                device_info.api_model_version = ww_client_server::FULL_VERSION.make_owned();
+                if let Some(version) = &self.user_api_version {
+                    device_info.user_api_version = version.clone();
+                }
+                self.common
+                    .on_connect(&filter, on_error, connected_tx, *client_version.clone());
+                if self.user_api_version.is_some()
+                    && !client_version.crate_id.is_empty()
+                    && !client_version.is_protocol_compatible(&device_info.user_api_version)
+                {
+                    if let Some(tx) = self.common.connected_tx.take() {
+                        _ = tx.send(Err(Error::IncompatibleDeviceProtocol));
+                    }
+                    return ControlFlow::Continue(());
+                }
+                self.common.device_info = Some(device_info.clone());
+                rx_dispatcher.handle_msg(DispatcherMessage::Connected);
+                if let Some(tx) = self.common.connected_tx.take() {
+                    _ = tx.send(Ok(device_info));
+                }
+                for request in self.common.on_link_up() {
+                    self.common.trace_request(&request);
+                    self.requests.push(request, &mut self.rng);
+                }
+            }
+            Command::RegisterTracer { trace_event_tx } => {
+                self.common.tracers.push(trace_event_tx);
+            }
+            Command::DisconnectKeepStreams { disconnected_tx } => {
+                self.common.trace_disconnect("client request", true);
+                self.common.on_disconnect();
+                self.requests.clear();
+                self.events.clear();
+                rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+                if let Some(tx) = disconnected_tx {
+                    _ = tx.send(());
+                }
+            }
+            Command::DisconnectAndExit { disconnected_tx } => {
+                self.common.trace_disconnect("client request", false);
+                rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+                if let Some(tx) = disconnected_tx {
+                    _ = tx.send(());
+                }
+                return ControlFlow::Break(());
+            }
+            Command::SendMessage {
+                mut bytes,
+                mut done_tx,
+            } => {
+                if let Some((done_tx, timeout)) = done_tx.take() {
+                    if let Some(seq) = rx_dispatcher.next_seq() {
+                        Request::set_seq(&mut bytes, seq);
+                        rx_dispatcher.handle_cmd(DispatcherCommand::OnReturn {
+                            seq,
+                            done_tx,
+                            timeout,
+                        });
+                    } else {
+                        _ = done_tx.send(Err(Error::Other("No more request IDs available".into())));
+                    }
+                }
+                if !self.common.link_up {
+                    warn!("ignoring send message while disconnected");
+                    return ControlFlow::Continue(());
+                }
+                self.common.on_request(&bytes);
+                self.requests.push(bytes, &mut self.rng);
+            }
+            Command::Cancel { request } => {
+                rx_dispatcher.cancel(request.seq.get());
+                if !self.common.link_up {
+                    return ControlFlow::Continue(());
+                }
+                if let Some(bytes) = request.notification() {
+                    self.common.on_request(&bytes);
+                    self.requests.push(bytes, &mut self.rng);
+                }
+            }
+            Command::OnStreamEvent {
+                path_kind,
+                stream_event_tx,
+                subscribe_seq,
+            } => {
+                rx_dispatcher.handle_cmd(DispatcherCommand::OnStreamEvent {
+                    path_kind: *path_kind,
+                    stream_event_tx,
+                    subscribe_seq,
+                });
+            }
+            Command::SetApiBundle { api_bundle } => {
+                self.common.trace_api_bundle(&api_bundle);
+                rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle { api_bundle });
+            }
+            Command::OnConnectionEvent { event_tx } => {
+                self.common.add_connection_event_tx(event_tx);
+            }
+            Command::LoopbackTest { progress_tx, .. } => {
+                _ = progress_tx.send(TestProgress::FatalError(
+                    "Not supported by loopback transport".into(),
+                ));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Process requests and deliver events that are due, until there are none left.
+    async fn process_due(&mut self, rx_dispatcher: &mut RxDispatcher) {
+        loop {
+            let now = Instant::now();
+            if let Some(request) = self.requests.pop_due(now) {
+                let [scratch_args, scratch_event, scratch_err] = &mut self.scratch;
+                match self
+                    .server
+                    .process_request_bytes(
+                        &request,
+                        scratch_args,
+                        scratch_event,
+                        scratch_err,
+                        &mut self.msg_tx,
+                    )
+                    .await
+                {
+                    Ok(event) if !event.is_empty() => {
+                        let event = event.to_vec();
+                        self.events.push(event, &mut self.rng);
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("server failed to process request: {e:?}"),
+                }
+            } else if let Some(event) = self.events.pop_due(now) {
+                self.common.trace_event(&event);
+                rx_dispatcher.handle_msg(DispatcherMessage::MessageBytes(&event));
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// Messages in flight in one direction, ordered by delivery time.
+struct Link {
+    faults: Faults,
+    in_flight: BTreeMap<(Instant, u64), Vec<u8>>,
+    counter: u64,
+}
+
+impl Link {
+    fn new(faults: Faults) -> Self {
+        Link {
+            faults,
+            in_flight: BTreeMap::new(),
+            counter: 0,
+        }
+    }
+
+    fn push(&mut self, mut message: Vec<u8>, rng: &mut XorShift) {
+        if rng.chance(self.faults.drop) {
+            trace!("dropping message: {}", hex::encode(&message));
+            return;
+        }
+        if !message.is_empty() && rng.chance(self.faults.corrupt) {
+            let bit = rng.next() as usize % (message.len() * 8);
+            message[bit / 8] ^= 1 << (bit % 8);
+            trace!("corrupted message: {}", hex::encode(&message));
+        }
+        let mut due = Instant::now() + self.faults.delay;
+        if rng.chance(self.faults.reorder) {
+            due += self.faults.reorder_by;
+        }
+        self.in_flight.insert((due, self.counter), message);
+        self.counter += 1;
+    }
+
+    fn pop_due(&mut self, now: Instant) -> Option<Vec<u8>> {
+        let entry = self.in_flight.first_entry()?;
+        if entry.key().0 > now {
+            return None;
+        }
+        Some(entry.remove())
+    }
+
+    fn next_due(&self) -> Option<Instant> {
+        self.in_flight.keys().next().map(|(due, _)| *due)
+    }
+
+    fn clear(&mut self) {
+        self.in_flight.clear();
+    }
+}
+
+/// Small deterministic PRNG, good enough for fault injection.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn chance(&mut self, probability: f32) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+        let sample = (self.next() >> 40) as f32 / (1u64 << 24) as f32;
+        sample < probability
+    }
+}
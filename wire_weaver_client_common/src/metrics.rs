@@ -0,0 +1,458 @@
+//! Per resource counters, error kinds and latency histograms for calls, reads and writes, as well as stream throughput.
+//!
+//! Collected by [CommandSender](crate::CommandSender) and shared between its clones, see
+//! [CommandSender::metrics](crate::CommandSender::metrics). Responses and stream data are counted when an event loop
+//! receives them, not when an application consumes them. Resources are keyed by a full path as sent to a device
+//! (absolute path or trait and path from it) and resolved into names when a snapshot is taken.
+//! Forget variants are not measured.
+
+use crate::Error;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use ww_client_server::ErrorKindOwned;
+use ww_self::{ApiBundleOwned, ApiItemKindOwned};
+
+/// Upper bounds of latency histogram buckets, the last bucket is unbounded.
+pub const LATENCY_BUCKETS: [Duration; 12] = [
+    Duration::from_micros(500),
+    Duration::from_millis(1),
+    Duration::from_micros(2500),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Operation {
+    Call,
+    Read,
+    Write,
+}
+
+/// Full path of a resource, as sent to a device.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourcePath {
+    Absolute(Vec<u32>),
+    /// Trait-addressed resource, `gid` is a trait crate name and version (`crate@x.y.z`) or a global type ID and
+    /// version (`G<id>@x.y.z`)
+    Trait {
+        gid: String,
+        path_from_trait: Vec<u32>,
+    },
+}
+
+/// Resource measured by [Metrics].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct MeasuredResource {
+    pub(crate) path: ResourcePath,
+    /// Path relative to the client API root, used to resolve a name
+    pub(crate) relative: Vec<u32>,
+}
+
+/// Shared collector, cheap to clone.
+#[derive(Clone, Default)]
+pub(crate) struct Metrics(Arc<Mutex<MetricsInner>>);
+
+#[derive(Default)]
+struct MetricsInner {
+    requests: BTreeMap<(MeasuredResource, Operation), RequestCounters>,
+    streams: BTreeMap<MeasuredResource, StreamCounters>,
+}
+
+/// Records a response into [Metrics] when it is received by an event loop, attached to a
+/// [ResponseSender](crate::rx_dispatcher::ResponseSender).
+#[derive(Debug)]
+pub(crate) struct RequestProbe {
+    pub(crate) metrics: Metrics,
+    pub(crate) resource: Arc<MeasuredResource>,
+    pub(crate) operation: Operation,
+    pub(crate) started: Instant,
+}
+
+/// Records stream data into [Metrics] when it is received by an event loop, attached to a
+/// [StreamUpdateSender](crate::rx_dispatcher::StreamUpdateSender).
+#[derive(Clone, Debug)]
+pub(crate) struct StreamProbe {
+    pub(crate) metrics: Metrics,
+    pub(crate) resource: Arc<MeasuredResource>,
+}
+
+#[derive(Default)]
+struct RequestCounters {
+    count: u64,
+    errors: BTreeMap<&'static str, u64>,
+    latency: LatencyHistogram,
+}
+
+#[derive(Default)]
+struct StreamCounters {
+    events: u64,
+    bytes: u64,
+    dropped: u64,
+    first_event: Option<Instant>,
+    last_event: Option<Instant>,
+}
+
+/// Point in time copy of all the collected metrics.
+#[derive(Clone, Debug, Default)]
+pub struct MetricsSnapshot {
+    pub requests: Vec<RequestMetrics>,
+    pub streams: Vec<StreamMetrics>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestMetrics {
+    pub path: ResourcePath,
+    /// Resource name, e.g. `motor[1]/speed`, or path IDs (`#2/#1/#0`) if a client has no introspect data.
+    /// Prefixed with a trait (`crate@x.y.z/`) or with base path IDs for trait clients.
+    pub name: String,
+    pub operation: Operation,
+    /// Number of completed requests, including failed ones
+    pub count: u64,
+    /// Failed requests by error kind, e.g. `Timeout`, `Disconnected` or remote [ErrorKind](ww_client_server::ErrorKind) name
+    pub errors: BTreeMap<&'static str, u64>,
+    pub latency: LatencyHistogram,
+}
+
+#[derive(Clone, Debug)]
+pub struct StreamMetrics {
+    pub path: ResourcePath,
+    pub name: String,
+    /// Number of data events received
+    pub events: u64,
+    /// Number of data bytes received
+    pub bytes: u64,
+    /// Data events that were received from a device, but not delivered: failed to deserialize or still buffered when
+    /// a Stream was dropped
+    pub dropped: u64,
+    /// Average over the time between the first and the last data event, None if less than two events were received
+    pub bytes_per_s: Option<f32>,
+}
+
+/// Latency histogram with [LATENCY_BUCKETS] bounds.
+#[derive(Clone, Debug, Default)]
+pub struct LatencyHistogram {
+    /// Number of samples in each bucket (not cumulative), one more than [LATENCY_BUCKETS] for the unbounded bucket
+    pub buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    pub sum: Duration,
+    pub count: u64,
+    pub max: Duration,
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, latency: Duration) {
+        let idx = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[idx] += 1;
+        self.sum += latency;
+        self.count += 1;
+        self.max = self.max.max(latency);
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(self.sum / self.count as u32)
+    }
+
+    /// Upper bound of a bucket containing q-th quantile (0.0..=1.0), None for no samples.
+    /// Max latency is returned instead, if it falls into the unbounded bucket.
+    pub fn quantile(&self, q: f32) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((self.count as f32 * q.clamp(0.0, 1.0)).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (idx, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Some(LATENCY_BUCKETS.get(idx).copied().unwrap_or(self.max));
+            }
+        }
+        Some(self.max)
+    }
+}
+
+impl Debug for Metrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Metrics")
+    }
+}
+
+impl RequestProbe {
+    pub(crate) fn record(self, result: Result<(), &Error>) {
+        let latency = self.started.elapsed();
+        let Ok(mut inner) = self.metrics.0.lock() else {
+            return;
+        };
+        let counters = inner
+            .requests
+            .entry((self.resource.as_ref().clone(), self.operation))
+            .or_default();
+        counters.count += 1;
+        match result {
+            Ok(()) => counters.latency.record(latency),
+            Err(e) => *counters.errors.entry(error_kind(e)).or_default() += 1,
+        }
+    }
+}
+
+impl StreamProbe {
+    pub(crate) fn record_data(&self, len: usize) {
+        let Ok(mut inner) = self.metrics.0.lock() else {
+            return;
+        };
+        let counters = inner
+            .streams
+            .entry(self.resource.as_ref().clone())
+            .or_default();
+        let now = Instant::now();
+        counters.events += 1;
+        counters.bytes += len as u64;
+        counters.first_event.get_or_insert(now);
+        counters.last_event = Some(now);
+    }
+
+    pub(crate) fn record_dropped(&self, count: u64) {
+        if count == 0 {
+            return;
+        }
+        let Ok(mut inner) = self.metrics.0.lock() else {
+            return;
+        };
+        inner
+            .streams
+            .entry(self.resource.as_ref().clone())
+            .or_default()
+            .dropped += count;
+    }
+}
+
+impl Metrics {
+    pub(crate) fn snapshot(&self, api_bundle: Option<&ApiBundleOwned>) -> MetricsSnapshot {
+        let Ok(inner) = self.0.lock() else {
+            return MetricsSnapshot::default();
+        };
+        let requests = inner
+            .requests
+            .iter()
+            .map(|((resource, operation), c)| RequestMetrics {
+                path: resource.path.clone(),
+                name: resource_name(api_bundle, resource),
+                operation: *operation,
+                count: c.count,
+                errors: c.errors.clone(),
+                latency: c.latency.clone(),
+            })
+            .collect();
+        let streams = inner
+            .streams
+            .iter()
+            .map(|(resource, c)| {
+                let bytes_per_s = match (c.first_event, c.last_event) {
+                    (Some(first), Some(last)) if last > first => {
+                        Some(c.bytes as f32 / (last - first).as_secs_f32())
+                    }
+                    _ => None,
+                };
+                StreamMetrics {
+                    path: resource.path.clone(),
+                    name: resource_name(api_bundle, resource),
+                    events: c.events,
+                    bytes: c.bytes,
+                    dropped: c.dropped,
+                    bytes_per_s,
+                }
+            })
+            .collect();
+        MetricsSnapshot { requests, streams }
+    }
+}
+
+impl MetricsSnapshot {
+    /// Render in Prometheus text exposition format, metric names are prefixed with `ww_`.
+    pub fn to_prometheus(&self) -> String {
+        let mut s = String::new();
+        let labels = |m: &RequestMetrics| {
+            format!(
+                "resource=\"{}\",op=\"{}\"",
+                escape_label(&m.name),
+                m.operation.as_str()
+            )
+        };
+
+        s += "# HELP ww_requests_total Completed requests, including failed ones.\n";
+        s += "# TYPE ww_requests_total counter\n";
+        for m in &self.requests {
+            _ = writeln!(s, "ww_requests_total{{{}}} {}", labels(m), m.count);
+        }
+
+        s += "# HELP ww_request_errors_total Failed requests by error kind.\n";
+        s += "# TYPE ww_request_errors_total counter\n";
+        for m in &self.requests {
+            for (kind, n) in &m.errors {
+                _ = writeln!(
+                    s,
+                    "ww_request_errors_total{{{},kind=\"{kind}\"}} {n}",
+                    labels(m)
+                );
+            }
+        }
+
+        s += "# HELP ww_request_duration_seconds Latency of successful requests.\n";
+        s += "# TYPE ww_request_duration_seconds histogram\n";
+        for m in &self.requests {
+            let labels = labels(m);
+            let mut cumulative = 0;
+            for (bound, n) in LATENCY_BUCKETS.iter().zip(m.latency.buckets.iter()) {
+                cumulative += n;
+                _ = writeln!(
+                    s,
+                    "ww_request_duration_seconds_bucket{{{labels},le=\"{}\"}} {cumulative}",
+                    bound.as_secs_f64()
+                );
+            }
+            _ = writeln!(
+                s,
+                "ww_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                m.latency.count
+            );
+            _ = writeln!(
+                s,
+                "ww_request_duration_seconds_sum{{{labels}}} {}",
+                m.latency.sum.as_secs_f64()
+            );
+            _ = writeln!(
+                s,
+                "ww_request_duration_seconds_count{{{labels}}} {}",
+                m.latency.count
+            );
+        }
+
+        let streams: [(&str, &str, StreamCounter); 3] = [
+            ("events", "Stream data events received.", |m| m.events),
+            ("bytes", "Stream data bytes received.", |m| m.bytes),
+            (
+                "dropped",
+                "Stream data events received, but not delivered.",
+                |m| m.dropped,
+            ),
+        ];
+        for (name, help, value) in streams {
+            _ = writeln!(s, "# HELP ww_stream_{name}_total {help}");
+            _ = writeln!(s, "# TYPE ww_stream_{name}_total counter");
+            for m in &self.streams {
+                _ = writeln!(
+                    s,
+                    "ww_stream_{name}_total{{resource=\"{}\"}} {}",
+                    escape_label(&m.name),
+                    value(m)
+                );
+            }
+        }
+        s
+    }
+}
+
+type StreamCounter = fn(&StreamMetrics) -> u64;
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Call => "call",
+            Operation::Read => "read",
+            Operation::Write => "write",
+        }
+    }
+}
+
+fn error_kind(e: &Error) -> &'static str {
+    match e {
+        Error::Timeout => "Timeout",
+        Error::Disconnected => "Disconnected",
+        Error::EventLoopNotRunning | Error::RxDispatcherNotRunning => "EventLoopNotRunning",
+        Error::ShrinkWrap(_) => "ShrinkWrap",
+        Error::OlderProtocol(..) => "OlderProtocol",
+        Error::RemoteError(e) => match &e.kind {
+            ErrorKindOwned::OperationNotSupported => "OperationNotSupported",
+            ErrorKindOwned::BadPath => "BadPath",
+            ErrorKindOwned::BadIndex => "BadIndex",
+            ErrorKindOwned::ExpectedArrayIndexGotNone => "ExpectedArrayIndexGotNone",
+            ErrorKindOwned::ArrayIndexDesFailed => "ArrayIndexDesFailed",
+            ErrorKindOwned::ArgsDesFailed => "ArgsDesFailed",
+            ErrorKindOwned::PathDesFailed => "PathDesFailed",
+            ErrorKindOwned::PropertyDesFailed => "PropertyDesFailed",
+            ErrorKindOwned::ResponseSerFailed => "ResponseSerFailed",
+            ErrorKindOwned::OperationNotImplemented => "OperationNotImplemented",
+            ErrorKindOwned::ReadPropertyWithSeqZero => "ReadPropertyWithSeqZero",
+            ErrorKindOwned::PathKindNotSupported => "PathKindNotSupported",
+            ErrorKindOwned::UserBytes(_) => "UserBytes",
+            ErrorKindOwned::UserStr(_) => "UserStr",
+        },
+        Error::RemoteErrorDes(_) => "UserBytes",
+        _ => "Other",
+    }
+}
+
+/// Walk the client API tree following resource IDs, array items consume one more ID as an index.
+/// Falls back to path IDs if a path cannot be resolved. Trait and base path of trait clients are kept as a prefix.
+fn resource_name(api_bundle: Option<&ApiBundleOwned>, resource: &MeasuredResource) -> String {
+    let relative = &resource.relative;
+    let name = api_bundle
+        .and_then(|api_bundle| resolve_name(api_bundle, relative))
+        .unwrap_or_else(|| path_ids(relative));
+    match &resource.path {
+        ResourcePath::Absolute(path) if path.len() > relative.len() => {
+            format!("{}/{name}", path_ids(&path[..path.len() - relative.len()]))
+        }
+        ResourcePath::Absolute(_) => name,
+        ResourcePath::Trait { gid, .. } => format!("{gid}/{name}"),
+    }
+}
+
+fn path_ids(path: &[u32]) -> String {
+    path.iter()
+        .map(|id| format!("#{id}"))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn resolve_name(api_bundle: &ApiBundleOwned, path: &[u32]) -> Option<String> {
+    let mut level = &api_bundle.root;
+    let mut names = vec![];
+    let mut ids = path.iter();
+    while let Some(id) = ids.next() {
+        let item = level.items.iter().find(|item| item.id.0 == *id)?;
+        if item.is_array() {
+            let index = ids.next()?;
+            names.push(format!("{}[{index}]", item.ident));
+        } else {
+            names.push(item.ident.clone());
+        }
+        if ids.len() == 0 {
+            return Some(names.join("/"));
+        }
+        let ApiItemKindOwned::Trait { trait_idx } = &item.kind else {
+            return None;
+        };
+        level = api_bundle.get_trait(trait_idx.0).ok()?;
+    }
+    None
+}
+
+fn escape_label(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,199 @@
+//! pcapng writer for USB link packets and ww_client_server messages, to be analyzed in Wireshark.
+//!
+//! Each [PcapngInterface] gets its own link type from the user range (LINKTYPE_USER0..3),
+//! dissector generated by `ww api dissector` is registered for all of them.
+
+use crate::Error;
+use crate::capture::{Capture, RecordKind, io_err};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+const BLOCK_SHB: u32 = 0x0A0D_0D0A;
+const BLOCK_IDB: u32 = 0x0000_0001;
+const BLOCK_EPB: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+const OPT_END: u16 = 0;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_IF_NAME: u16 = 2;
+const OPT_IF_TSRESOL: u16 = 9;
+const OPT_EPB_FLAGS: u16 = 2;
+
+const EPB_FLAGS_INBOUND: u32 = 0b01;
+const EPB_FLAGS_OUTBOUND: u32 = 0b10;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PcapngInterface {
+    /// USB link packets from host to device, see `wire_weaver_usb_link::Op`
+    LinkToDevice,
+    /// USB link packets from device to host
+    LinkFromDevice,
+    /// Serialized ww_client_server::Request
+    Request,
+    /// Serialized ww_client_server::Event
+    Event,
+}
+
+impl PcapngInterface {
+    pub const ALL: [PcapngInterface; 4] = [
+        PcapngInterface::LinkToDevice,
+        PcapngInterface::LinkFromDevice,
+        PcapngInterface::Request,
+        PcapngInterface::Event,
+    ];
+
+    /// LINKTYPE_USER0 to LINKTYPE_USER3
+    pub fn link_type(&self) -> u16 {
+        147 + self.id() as u16
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PcapngInterface::LinkToDevice => "ww_link host->device",
+            PcapngInterface::LinkFromDevice => "ww_link device->host",
+            PcapngInterface::Request => "ww_client_server requests",
+            PcapngInterface::Event => "ww_client_server events",
+        }
+    }
+
+    fn id(&self) -> u32 {
+        match self {
+            PcapngInterface::LinkToDevice => 0,
+            PcapngInterface::LinkFromDevice => 1,
+            PcapngInterface::Request => 2,
+            PcapngInterface::Event => 3,
+        }
+    }
+
+    fn is_outbound(&self) -> bool {
+        matches!(
+            self,
+            PcapngInterface::LinkToDevice | PcapngInterface::Request
+        )
+    }
+}
+
+/// Writes section header and all [PcapngInterface] descriptions on creation, followed by packets.
+pub struct PcapngWriter<W: Write> {
+    wr: W,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    pub fn new(wr: W) -> Result<Self, Error> {
+        let mut writer = PcapngWriter { wr };
+
+        let mut body = vec![];
+        body.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes()); // major version
+        body.extend_from_slice(&0u16.to_le_bytes()); // minor version
+        body.extend_from_slice(&(-1i64).to_le_bytes()); // section length is not known
+        push_option(&mut body, OPT_SHB_USERAPPL, b"wire_weaver");
+        push_option(&mut body, OPT_END, &[]);
+        writer.write_block(BLOCK_SHB, &body)?;
+
+        for interface in PcapngInterface::ALL {
+            let mut body = vec![];
+            body.extend_from_slice(&interface.link_type().to_le_bytes());
+            body.extend_from_slice(&0u16.to_le_bytes()); // reserved
+            body.extend_from_slice(&0u32.to_le_bytes()); // no snap length limit
+            push_option(&mut body, OPT_IF_NAME, interface.name().as_bytes());
+            push_option(&mut body, OPT_IF_TSRESOL, &[6]); // microseconds
+            push_option(&mut body, OPT_END, &[]);
+            writer.write_block(BLOCK_IDB, &body)?;
+        }
+        Ok(writer)
+    }
+
+    /// Write one packet or message, timestamp is in microseconds since UNIX epoch.
+    pub fn write_packet(
+        &mut self,
+        interface: PcapngInterface,
+        timestamp_us: u64,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let mut body = Vec::with_capacity(data.len() + 48);
+        body.extend_from_slice(&interface.id().to_le_bytes());
+        body.extend_from_slice(&((timestamp_us >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(timestamp_us as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes()); // captured length
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes()); // original length
+        body.extend_from_slice(data);
+        pad32(&mut body);
+        let flags = if interface.is_outbound() {
+            EPB_FLAGS_OUTBOUND
+        } else {
+            EPB_FLAGS_INBOUND
+        };
+        push_option(&mut body, OPT_EPB_FLAGS, &flags.to_le_bytes());
+        push_option(&mut body, OPT_END, &[]);
+        self.write_block(BLOCK_EPB, &body)
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.wr.flush().map_err(io_err)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.wr
+    }
+
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> Result<(), Error> {
+        let total_len = (body.len() + 12) as u32;
+        self.wr
+            .write_all(&block_type.to_le_bytes())
+            .and_then(|_| self.wr.write_all(&total_len.to_le_bytes()))
+            .and_then(|_| self.wr.write_all(body))
+            .and_then(|_| self.wr.write_all(&total_len.to_le_bytes()))
+            .map_err(io_err)
+    }
+}
+
+impl PcapngWriter<std::io::BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = File::create(path).map_err(io_err)?;
+        Self::new(std::io::BufWriter::new(file))
+    }
+}
+
+impl Capture {
+    /// Write all link packets, requests and events from this capture as pcapng packets, other records are skipped.
+    pub fn write_pcapng<W: Write>(&self, wr: W) -> Result<W, Error> {
+        let mut writer = PcapngWriter::new(wr)?;
+        for record in &self.records {
+            let timestamp_us = self.header.started_at_us + record.timestamp_us;
+            match &record.kind {
+                RecordKind::Request { bytes } => {
+                    writer.write_packet(PcapngInterface::Request, timestamp_us, bytes)?
+                }
+                RecordKind::Event { bytes } => {
+                    writer.write_packet(PcapngInterface::Event, timestamp_us, bytes)?
+                }
+                RecordKind::LinkPacket { to_device, bytes } => {
+                    let interface = if *to_device {
+                        PcapngInterface::LinkToDevice
+                    } else {
+                        PcapngInterface::LinkFromDevice
+                    };
+                    writer.write_packet(interface, timestamp_us, bytes)?
+                }
+                _ => {}
+            }
+        }
+        writer.flush()?;
+        Ok(writer.into_inner())
+    }
+}
+
+fn push_option(body: &mut Vec<u8>, code: u16, value: &[u8]) {
+    body.extend_from_slice(&code.to_le_bytes());
+    body.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    body.extend_from_slice(value);
+    pad32(body);
+}
+
+fn pad32(body: &mut Vec<u8>) {
+    while !body.len().is_multiple_of(4) {
+        body.push(0);
+    }
+}
@@ -1,9 +1,13 @@
 use crate::command_sender::TransportCommander;
 use crate::promise::Promise;
-use crate::Error;
+use crate::rx_dispatcher::{RequestSeq, ResponseReceiver};
+use crate::{Error, SeqTy};
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::time::Duration;
+use tokio::sync::OwnedSemaphorePermit;
 use wire_weaver::prelude::DeserializeShrinkWrapOwned;
 use ww_client_server::PathKindOwned;
 
@@ -15,6 +19,7 @@ use ww_client_server::PathKindOwned;
 ///
 /// When obtained, the user can choose how to actually execute the call:
 /// * async: `call()`
+/// * async, with an option to cancel: `call_pending()`
 /// * blocking: `blocking_call()`
 /// * call-ignoring-return value: `call_forget()`
 /// * turn into a `Promise<T>` useful in immediate mode UI
@@ -25,39 +30,69 @@ pub struct PreparedCall<T> {
     pub(crate) path_kind: PathKindOwned,
     pub(crate) args: Vec<u8>,
     pub(crate) timeout_override: Option<Duration>,
+    pub(crate) notify_cancel: bool,
     pub(crate) _phantom: PhantomData<T>,
 }
 
+/// Call request that was sent to a device and is awaiting a response.
+///
+/// Dropping it before the response arrives or calling [cancel](PendingCall::cancel) removes the response entry
+/// right away instead of waiting for a timeout, and notifies the server if [notify_server_on_cancel](PreparedCall::notify_server_on_cancel) was used.
+#[must_use = "PendingCall is cancelled when dropped"]
+pub struct PendingCall<T> {
+    transport_cmd_tx: TransportCommander,
+    done_rx: Option<ResponseReceiver>,
+    seq: RequestSeq,
+    notify_path: Option<PathKindOwned>,
+    _permit: Option<OwnedSemaphorePermit>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
 impl<T: DeserializeShrinkWrapOwned + Debug> PreparedCall<T> {
     /// Use a provided timeout instead of the default one propagated from CommandSender
     pub fn with_timeout(self, timeout: Duration) -> Self {
         Self {
-            postpone_err: self.postpone_err,
-            transport_cmd_tx: self.transport_cmd_tx,
-            path_kind: self.path_kind,
-            args: self.args,
             timeout_override: Some(timeout),
-            _phantom: PhantomData,
+            ..self
+        }
+    }
+
+    /// Send [Cancel](ww_client_server::RequestKind::Cancel) to the server when a pending call is cancelled or dropped,
+    /// so that deferred work can be aborted. Only useful for methods marked as `deferred+cancellable`.
+    pub fn notify_server_on_cancel(self) -> Self {
+        Self {
+            notify_cancel: true,
+            ..self
         }
     }
 
     /// Send a call request, await a response (or timeout) and return it.
     pub async fn call(self) -> Result<T, Error> {
+        self.call_pending().await?.await
+    }
+
+    /// Send a call request and return a future resolving to the response, which can also be cancelled.
+    /// Waits for a free slot first if the in-flight window is limited (see [set_max_in_flight](crate::CommandSender::set_max_in_flight)).
+    pub async fn call_pending(self) -> Result<PendingCall<T>, Error> {
         // late error return, to have more ergonomic dev.fn_name().call()?; instead of dev.fn_name()?.call()?;
         self.postpone_err?;
+        let permit = self.transport_cmd_tx.acquire_in_flight().await?;
+        let notify_path = self.notify_cancel.then(|| self.path_kind.clone());
 
         // send call to a remote device through transport layer
-        let done_rx = self.transport_cmd_tx.send_call_request(
+        let (done_rx, seq) = self.transport_cmd_tx.send_call_request(
             self.path_kind,
             self.args,
             self.timeout_override,
         )?;
-
-        // await return value from a remote device (routed through rx dispatcher)
-        let rx_or_recv_err = done_rx.await.map_err(|_| Error::RxDispatcherNotRunning)?;
-        let response = rx_or_recv_err?; // timeout is handled by rx dispatcher
-        let reply: T = T::from_ww_bytes_owned(&response)?;
-        Ok(reply)
+        Ok(PendingCall {
+            transport_cmd_tx: self.transport_cmd_tx,
+            done_rx: Some(done_rx),
+            seq,
+            notify_path,
+            _permit: permit,
+            _phantom: PhantomData,
+        })
     }
 
     /// Send a call request, block the thread until a response is received (or timeout) and return it.
@@ -65,7 +100,7 @@ impl<T: DeserializeShrinkWrapOwned + Debug> PreparedCall<T> {
         self.postpone_err?;
 
         // send call to a remote device through transport layer
-        let done_rx = self.transport_cmd_tx.send_call_request(
+        let (done_rx, _seq) = self.transport_cmd_tx.send_call_request(
             self.path_kind,
             self.args,
             self.timeout_override,
@@ -104,3 +139,49 @@ impl<T: DeserializeShrinkWrapOwned + Debug> PreparedCall<T> {
         )
     }
 }
+
+impl<T> PendingCall<T> {
+    /// Seq number assigned to the request, 0 if it was not yet processed by an event loop.
+    pub fn seq(&self) -> SeqTy {
+        self.seq.get()
+    }
+
+    /// Stop waiting for a response and notify the server if requested.
+    pub fn cancel(mut self) -> Result<(), Error> {
+        self.cancel_inner()
+    }
+
+    fn cancel_inner(&mut self) -> Result<(), Error> {
+        if self.done_rx.take().is_none() {
+            return Ok(());
+        }
+        self.transport_cmd_tx
+            .send_cancel(self.seq.clone(), self.notify_path.take())
+    }
+}
+
+impl<T: DeserializeShrinkWrapOwned> Future for PendingCall<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Some(done_rx) = &mut self.done_rx else {
+            return Poll::Ready(Err(Error::Other("polled after completion".into())));
+        };
+        // await return value from a remote device (routed through rx dispatcher)
+        let rx_or_recv_err = match Pin::new(done_rx).poll(cx) {
+            Poll::Ready(r) => r,
+            Poll::Pending => return Poll::Pending,
+        };
+        self.done_rx = None;
+        self._permit = None;
+        let response = rx_or_recv_err.map_err(|_| Error::RxDispatcherNotRunning)??; // timeout is handled by rx dispatcher
+        let reply: T = T::from_ww_bytes_owned(&response)?;
+        Poll::Ready(Ok(reply))
+    }
+}
+
+impl<T> Drop for PendingCall<T> {
+    fn drop(&mut self) {
+        _ = self.cancel_inner();
+    }
+}
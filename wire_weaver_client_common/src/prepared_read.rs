@@ -42,6 +42,7 @@ impl<T: DeserializeShrinkWrapOwned + Debug> PreparedRead<T> {
         // late error return, to have more ergonomic dev.fn_name().call()?; instead of dev.fn_name()?.call()?;
         self.version_check?;
         let path_kind = self.path_kind?;
+        let _permit = self.transport_cmd_tx.acquire_in_flight().await?;
 
         // send call to a remote device through transport layer
         let done_rx = self
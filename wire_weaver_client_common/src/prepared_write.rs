@@ -44,6 +44,7 @@ impl<E: DeserializeShrinkWrapOwned + Debug> PreparedWrite<E> {
     pub async fn write(self) -> Result<(), Error> {
         // late error return, to have more ergonomic dev.fn_name().call()?; instead of dev.fn_name()?.call()?;
         self.postpone_err?;
+        let _permit = self.transport_cmd_tx.acquire_in_flight().await?;
 
         // send call to a remote device through transport layer
         let done_rx = self.transport_cmd_tx.send_write_request(
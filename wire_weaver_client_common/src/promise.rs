@@ -341,7 +341,7 @@ impl<T: DeserializeShrinkWrapOwned + Debug> Promise<T> {
             };
             // send call to a remote device through transport layer
             match transport_cmd_tx.send_call_request(path_kind, args, *timeout) {
-                Ok(done_rx) => {
+                Ok((done_rx, _seq)) => {
                     self.state = StateInner::WaitingForReply(done_rx);
                 }
                 Err(e) => {
@@ -0,0 +1,308 @@
+//! Replay transport, feeds a [Capture] back to a client through the [Command] interface, as if a device was connected.
+//!
+//! Recorded events are delivered with the original or accelerated timing. Recorded requests act as synchronization points:
+//! replay waits until a client sends the next request, and continues from there. Request IDs (seq) of the recorded
+//! events are replaced with the ones assigned to the actual requests.
+
+use crate::capture::{Capture, CaptureRecord, RecordKind};
+use crate::event_loop_state::CommonState;
+use crate::rx_dispatcher::{DispatcherCommand, DispatcherMessage, RxDispatcher};
+use crate::{Command, DeviceInfoBundle, Error, SeqTy, TestProgress};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+use ww_client_server::{Event, Request};
+use ww_self::ApiBundleOwned;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReplayTiming {
+    /// Delays between events are the same as recorded.
+    Original,
+    /// Delays between events are divided by the provided factor.
+    Accelerated(f32),
+    /// Events are delivered as soon as possible.
+    NoDelay,
+}
+
+struct State {
+    common: CommonState,
+    records: VecDeque<CaptureRecord>,
+    timing: ReplayTiming,
+    /// Instant at which the record with the provided timestamp was replayed, used to schedule the following ones.
+    sync: (Instant, u64),
+    /// Requests sent by a client, not yet matched with recorded ones.
+    pending_requests: VecDeque<Vec<u8>>,
+    /// Recorded seq to the one used by a client.
+    seq_map: HashMap<SeqTy, SeqTy>,
+    requests_matched: usize,
+}
+
+/// Run replay event loop until [Command::DisconnectAndExit] is received or all the command senders are dropped.
+pub async fn replay_worker(
+    mut cmd_rx: mpsc::UnboundedReceiver<Command>,
+    capture: Capture,
+    timing: ReplayTiming,
+) {
+    if !capture.has_api_bundle() {
+        warn!(
+            "no ApiBundle in the capture, trait-addressed streams are only resolved through Subscribed events"
+        );
+    }
+    let mut state = State {
+        common: CommonState::default(),
+        records: capture.records.into(),
+        timing,
+        sync: (Instant::now(), 0),
+        pending_requests: VecDeque::new(),
+        seq_map: HashMap::new(),
+        requests_matched: 0,
+    };
+    let mut rx_dispatcher = RxDispatcher::default();
+    loop {
+        let next_due = if state.common.link_up {
+            state.replay_due_records(&mut rx_dispatcher)
+        } else {
+            None
+        };
+        let prune_in = rx_dispatcher.prune_next_timeout();
+        let sleep_for = next_due.map(|d| d.min(prune_in)).unwrap_or(prune_in);
+        tokio::select! {
+            cmd = cmd_rx.recv() => {
+                let Some(cmd) = cmd else {
+                    debug!("replay worker exiting, because all command senders were dropped");
+                    break;
+                };
+                if state.handle_command(cmd, &mut rx_dispatcher).is_break() {
+                    break;
+                }
+            }
+            _ = tokio::time::sleep(sleep_for) => {}
+        }
+    }
+    debug!("replay worker exited");
+}
+
+impl State {
+    fn handle_command(
+        &mut self,
+        cmd: Command,
+        rx_dispatcher: &mut RxDispatcher,
+    ) -> std::ops::ControlFlow<()> {
+        match cmd {
+            Command::Connect {
+                filter,
+                on_error,
+                connected_tx,
+                client_version,
+            } => {
+                if self.common.link_up {
+                    warn!("Ignoring Connect while already connected");
+                    return std::ops::ControlFlow::Continue(());
+                }
+                // skip everything before the first connection
+                let mut device_info = None;
+                if let Some(idx) = self
+                    .records
+                    .iter()
+                    .position(|r| matches!(r.kind, RecordKind::Connected { .. }))
+                {
+                    let record = self.records.drain(..=idx).next_back().unwrap();
+                    if let RecordKind::Connected {
+                        device_info: Some(info),
+                    } = &record.kind
+                    {
+                        device_info = Some(DeviceInfoBundle::from(info));
+                    }
+                    self.sync = (Instant::now(), record.timestamp_us);
+                } else {
+                    self.sync = (Instant::now(), 0);
+                }
+                let device_info = device_info.unwrap_or_else(DeviceInfoBundle::empty);
+                info!("Replaying capture of: {device_info:?}");
+                self.common
+                    .on_connect(&filter, on_error, connected_tx, *client_version.clone());
+                if !client_version.crate_id.is_empty()
+                    && !device_info.user_api_version.crate_id.is_empty()
+                    && !client_version.is_protocol_compatible(&device_info.user_api_version)
+                {
+                    if let Some(tx) = self.common.connected_tx.take() {
+                        _ = tx.send(Err(Error::IncompatibleDeviceProtocol));
+                    }
+                    return std::ops::ControlFlow::Continue(());
+                }
+                self.common.device_info = Some(device_info.clone());
+                rx_dispatcher.handle_msg(DispatcherMessage::Connected);
+                if let Some(tx) = self.common.connected_tx.take() {
+                    _ = tx.send(Ok(device_info));
+                }
+                // requests restoring streams are not part of a recording, so they are not sent
+                _ = self.common.on_link_up();
+            }
+            Command::RegisterTracer { trace_event_tx } => {
+                self.common.tracers.push(trace_event_tx);
+            }
+            Command::DisconnectKeepStreams { disconnected_tx } => {
+                self.common.trace_disconnect("client request", true);
+                self.common.on_disconnect();
+                rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+                if let Some(tx) = disconnected_tx {
+                    _ = tx.send(());
+                }
+            }
+            Command::DisconnectAndExit { disconnected_tx } => {
+                self.common.trace_disconnect("client request", false);
+                rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+                if let Some(tx) = disconnected_tx {
+                    _ = tx.send(());
+                }
+                return std::ops::ControlFlow::Break(());
+            }
+            Command::SendMessage {
+                mut bytes,
+                mut done_tx,
+            } => {
+                if let Some((done_tx, timeout)) = done_tx.take() {
+                    if let Some(seq) = rx_dispatcher.next_seq() {
+                        Request::set_seq(&mut bytes, seq);
+                        rx_dispatcher.handle_cmd(DispatcherCommand::OnReturn {
+                            seq,
+                            done_tx,
+                            timeout,
+                        });
+                    } else {
+                        _ = done_tx.send(Err(Error::Other("No more request IDs available".into())));
+                    }
+                }
+                if self.common.link_up {
+                    self.common.trace_request(&bytes);
+                    self.pending_requests.push_back(bytes);
+                }
+            }
+            Command::Cancel { request } => {
+                rx_dispatcher.cancel(request.seq.get());
+                if !self.common.link_up {
+                    return std::ops::ControlFlow::Continue(());
+                }
+                if let Some(bytes) = request.notification() {
+                    self.common.trace_request(&bytes);
+                    self.pending_requests.push_back(bytes);
+                }
+            }
+            Command::OnStreamEvent {
+                path_kind,
+                stream_event_tx,
+                subscribe_seq,
+            } => {
+                rx_dispatcher.handle_cmd(DispatcherCommand::OnStreamEvent {
+                    path_kind: *path_kind,
+                    stream_event_tx,
+                    subscribe_seq,
+                });
+            }
+            Command::SetApiBundle { api_bundle } => {
+                self.common.trace_api_bundle(&api_bundle);
+                rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle { api_bundle });
+            }
+            Command::OnConnectionEvent { event_tx } => {
+                self.common.add_connection_event_tx(event_tx);
+            }
+            Command::LoopbackTest { progress_tx, .. } => {
+                _ = progress_tx.send(TestProgress::FatalError(
+                    "Not supported when replaying a capture".into(),
+                ));
+            }
+        }
+        std::ops::ControlFlow::Continue(())
+    }
+
+    /// Replay all the records that are due, returns time until the next one, if it is not a request.
+    fn replay_due_records(&mut self, rx_dispatcher: &mut RxDispatcher) -> Option<Duration> {
+        while let Some(record) = self.records.front() {
+            if matches!(record.kind, RecordKind::Request { .. }) {
+                let Some(live) = self.pending_requests.pop_front() else {
+                    // wait for client to send the next request
+                    return None;
+                };
+                let record = self.records.pop_front().unwrap();
+                if let RecordKind::Request { bytes } = &record.kind {
+                    self.match_request(bytes, &live);
+                }
+                self.sync = (Instant::now(), record.timestamp_us);
+                continue;
+            }
+            let due = self.sync.0 + self.scale(record.timestamp_us.saturating_sub(self.sync.1));
+            let now = Instant::now();
+            if due > now {
+                return Some(due - now);
+            }
+            let record = self.records.pop_front().unwrap();
+            self.replay(record, rx_dispatcher);
+        }
+        None
+    }
+
+    fn replay(&mut self, record: CaptureRecord, rx_dispatcher: &mut RxDispatcher) {
+        match record.kind {
+            RecordKind::Request { .. } | RecordKind::LinkPacket { .. } => {}
+            RecordKind::Event { mut bytes } => {
+                if let Some(seq) = Event::seq(&bytes)
+                    && seq != 0
+                {
+                    if let Some(live_seq) = self.seq_map.get(&seq) {
+                        Event::set_seq(&mut bytes, *live_seq);
+                    } else {
+                        warn!("recorded event with seq {seq} does not match any request");
+                    }
+                }
+                self.common.trace_event(&bytes);
+                rx_dispatcher.handle_msg(DispatcherMessage::MessageBytes(&bytes));
+            }
+            RecordKind::Connected { .. } => {
+                rx_dispatcher.handle_msg(DispatcherMessage::Connected);
+            }
+            RecordKind::Disconnected { reason, .. } => {
+                info!("recorded disconnect: {reason}");
+                rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+            }
+            RecordKind::Error { reason } => {
+                info!("recorded error: {reason}");
+            }
+            RecordKind::ApiBundle { ww_self_bytes } => {
+                use wire_weaver::shrink_wrap::DeserializeShrinkWrapOwned;
+                match ApiBundleOwned::from_ww_bytes_owned(&ww_self_bytes) {
+                    Ok(api_bundle) => {
+                        rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle {
+                            api_bundle: Box::new(api_bundle),
+                        });
+                    }
+                    Err(e) => warn!("failed to deserialize recorded ApiBundle: {e:?}"),
+                }
+            }
+        }
+    }
+
+    fn match_request(&mut self, recorded: &[u8], live: &[u8]) {
+        self.requests_matched += 1;
+        if recorded.get(2..) != live.get(2..) {
+            warn!(
+                "request #{} differs from the recorded one: {:02x?} vs {:02x?}",
+                self.requests_matched, live, recorded
+            );
+        }
+        if let (Some(recorded_seq), Some(live_seq)) = (Request::seq(recorded), Request::seq(live))
+            && recorded_seq != 0
+        {
+            self.seq_map.insert(recorded_seq, live_seq);
+        }
+    }
+
+    fn scale(&self, us: u64) -> Duration {
+        let d = Duration::from_micros(us);
+        match self.timing {
+            ReplayTiming::Original => d,
+            ReplayTiming::Accelerated(factor) if factor > 0.0 => d.div_f32(factor),
+            ReplayTiming::Accelerated(_) | ReplayTiming::NoDelay => Duration::ZERO,
+        }
+    }
+}
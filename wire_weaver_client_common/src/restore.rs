@@ -0,0 +1,151 @@
+//! Keeps track of the device side state requested by a client (opened streams, property subscriptions and rate limits),
+//! so that it can be re-established after a device is reconnected or rebooted.
+
+use crate::DeviceInfoBundle;
+use tracing::warn;
+use wire_weaver::shrink_wrap::{self, DeserializeShrinkWrap};
+use ww_client_server::{Request, RequestKind, StreamSidebandCommand};
+
+const MAX_PATH_LEN: usize = 64 * 1024;
+
+/// Connection state changes, see [CommandSender::connection_events](crate::CommandSender::connection_events).
+#[derive(Debug, Clone)]
+pub enum ConnectionEvent {
+    /// Link is up for the first time.
+    Connected(Box<DeviceInfoBundle>),
+    /// Link is up again, after it was lost. Opened streams, subscriptions and rate limits were restored on the device side.
+    Reconnected {
+        info: Box<DeviceInfoBundle>,
+        restored_requests: usize,
+    },
+    /// Link is up again, but a device reports a different user API version or signature (e.g., after a firmware update).
+    /// Nothing was restored, because resource paths might have changed.
+    DeviceChanged {
+        previous: Box<DeviceInfoBundle>,
+        current: Box<DeviceInfoBundle>,
+    },
+    /// Link was lost or closed.
+    Disconnected,
+}
+
+/// Requests that changed the state of a device resource, with seq set to 0, in the order they were first seen.
+#[derive(Default)]
+pub struct ActiveResources {
+    resources: Vec<Resource>,
+}
+
+struct Resource {
+    /// Serialized PathKind
+    path: Vec<u8>,
+    stream_open: Option<Vec<u8>>,
+    stream_change_rate: Option<Vec<u8>>,
+    subscribe: Option<Vec<u8>>,
+    change_rate: Option<Vec<u8>>,
+}
+
+impl ActiveResources {
+    /// Look at a request sent to a device and remember it if it opens or closes a stream, (un)subscribes from a property
+    /// or changes their update rate. Other requests are ignored.
+    pub fn on_request(&mut self, bytes: &[u8]) {
+        let Ok(request) = Request::from_ww_bytes(bytes) else {
+            return;
+        };
+        let is_relevant = matches!(
+            request.kind,
+            RequestKind::Subscribe
+                | RequestKind::Unsubscribe
+                | RequestKind::ChangeRate { .. }
+                | RequestKind::StreamSideband {
+                    sideband_cmd: StreamSidebandCommand::Open
+                        | StreamSidebandCommand::Close
+                        | StreamSidebandCommand::ChangeRate(_)
+                }
+        );
+        if !is_relevant {
+            return;
+        }
+        let path = match shrink_wrap::to_ww_vec(&request.path_kind, MAX_PATH_LEN) {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("not tracking {:?} for restore: {e:?}", request.path_kind);
+                return;
+            }
+        };
+        let mut request_bytes = bytes.to_vec();
+        Request::set_seq(&mut request_bytes, 0);
+        let resource = match self.resources.iter().position(|r| r.path == path) {
+            Some(idx) => &mut self.resources[idx],
+            None => {
+                self.resources.push(Resource {
+                    path,
+                    stream_open: None,
+                    stream_change_rate: None,
+                    subscribe: None,
+                    change_rate: None,
+                });
+                self.resources.last_mut().unwrap()
+            }
+        };
+        match request.kind {
+            RequestKind::Subscribe => resource.subscribe = Some(request_bytes),
+            RequestKind::Unsubscribe => {
+                resource.subscribe = None;
+                resource.change_rate = None;
+            }
+            RequestKind::ChangeRate { .. } => resource.change_rate = Some(request_bytes),
+            RequestKind::StreamSideband { sideband_cmd } => match sideband_cmd {
+                StreamSidebandCommand::Open => resource.stream_open = Some(request_bytes),
+                StreamSidebandCommand::Close => {
+                    resource.stream_open = None;
+                    resource.stream_change_rate = None;
+                }
+                StreamSidebandCommand::ChangeRate(_) => {
+                    resource.stream_change_rate = Some(request_bytes)
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        self.resources.retain(|r| !r.is_idle());
+    }
+
+    /// Requests to send to a device to bring it to the same state, streams are opened before their rate is changed.
+    pub fn requests(&self) -> Vec<Vec<u8>> {
+        self.resources
+            .iter()
+            .flat_map(|r| {
+                [
+                    &r.stream_open,
+                    &r.stream_change_rate,
+                    &r.subscribe,
+                    &r.change_rate,
+                ]
+            })
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.resources.clear();
+    }
+}
+
+impl Resource {
+    fn is_idle(&self) -> bool {
+        self.stream_open.is_none()
+            && self.stream_change_rate.is_none()
+            && self.subscribe.is_none()
+            && self.change_rate.is_none()
+    }
+}
+
+/// Whether a device reconnected with a different user API, so that previously used paths cannot be trusted anymore.
+pub fn is_device_changed(previous: &DeviceInfoBundle, current: &DeviceInfoBundle) -> bool {
+    previous.user_api_version != current.user_api_version
+        || previous.user_api_signature != current.user_api_signature
+}
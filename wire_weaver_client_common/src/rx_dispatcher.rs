@@ -1,18 +1,39 @@
+use crate::metrics::{RequestProbe, StreamProbe};
 use crate::{Error, SeqTy, StreamEvent};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot};
 use tracing::{debug, trace, warn};
-use wire_weaver::shrink_wrap::{DeserializeShrinkWrap, UNib32};
+use wire_weaver::shrink_wrap::{self, DeserializeShrinkWrap, UNib32};
 use ww_client_server::{EventKind, PathKindOwned};
+use ww_self::{ApiBundleOwned, ApiItemKindOwned, ApiLevelLocationOwned, ApiLevelOwned};
 
-pub(crate) type ResponseSender = oneshot::Sender<Result<Vec<u8>, Error>>;
-pub(crate) type ResponseReceiver = oneshot::Receiver<Result<Vec<u8>, Error>>;
+pub type ResponseReceiver = oneshot::Receiver<Result<Vec<u8>, Error>>;
 
-pub(crate) type StreamUpdateSender = mpsc::UnboundedSender<StreamEvent>;
+/// Sender half of a request response channel, also carrying a seq number assigned to the request by an event loop.
+#[derive(Debug)]
+pub struct ResponseSender {
+    tx: oneshot::Sender<Result<Vec<u8>, Error>>,
+    seq: RequestSeq,
+    probe: Option<Box<RequestProbe>>,
+}
+
+/// Seq number assigned to a request by an event loop, 0 until then.
+#[derive(Clone, Debug, Default)]
+pub struct RequestSeq(Arc<AtomicU16>);
+
+/// Sender half of a stream event channel, data events are counted in metrics as they are received, if a probe is attached.
+#[derive(Clone, Debug)]
+pub struct StreamUpdateSender {
+    tx: mpsc::UnboundedSender<StreamEvent>,
+    probe: Option<StreamProbe>,
+}
 pub(crate) type StreamUpdateReceiver = mpsc::UnboundedReceiver<StreamEvent>;
 
 const IGNORE_TIMER_DURATION: Duration = Duration::from_millis(1);
+const MAX_PATH_LEN: usize = 64 * 1024;
 
 pub enum DispatcherMessage<'i> {
     Connected,
@@ -30,7 +51,11 @@ pub enum DispatcherCommand {
     OnStreamEvent {
         path_kind: PathKindOwned,
         stream_event_tx: StreamUpdateSender,
+        /// Seq number of a request that opened a trait-addressed stream, matched with a Subscribed event.
+        subscribe_seq: RequestSeq,
     },
+    /// ApiBundle of a connected device, used to resolve trait-addressed streams into absolute paths.
+    SetApiBundle { api_bundle: Box<ApiBundleOwned> },
 }
 
 // pub async fn rx_dispatcher(
@@ -70,11 +95,21 @@ pub struct RxDispatcher {
     is_connected: bool,
     response_map: HashMap<SeqTy, (ResponseSenderWrapper, Instant)>,
     stream_handlers: HashMap<Vec<UNib32>, Vec<StreamUpdateSender>>,
+    /// Trait-addressed (GlobalCompact or GlobalFull) streams, for which an absolute path is not yet known.
+    /// Resolved once ApiBundle is provided or when Subscribed event with an absolute path is received.
+    unresolved_stream_handlers: Vec<UnresolvedStream>,
+    api_bundle: Option<Box<ApiBundleOwned>>,
     next_seq: SeqTy,
 }
 
 struct ResponseSenderWrapper(Option<ResponseSender>);
 
+struct UnresolvedStream {
+    path_kind: PathKindOwned,
+    subscribe_seq: RequestSeq,
+    stream_event_tx: StreamUpdateSender,
+}
+
 impl ResponseSenderWrapper {
     fn send(&mut self, r: Result<Vec<u8>, Error>) -> Result<(), ()> {
         if let Some(tx) = self.0.take() {
@@ -83,6 +118,70 @@ impl ResponseSenderWrapper {
             Err(())
         }
     }
+    fn is_closed(&self) -> bool {
+        self.0.as_ref().map(|tx| tx.tx.is_closed()).unwrap_or(true)
+    }
+}
+
+impl ResponseSender {
+    pub fn send(self, r: Result<Vec<u8>, Error>) -> Result<(), Result<Vec<u8>, Error>> {
+        if let Some(probe) = self.probe {
+            probe.record(r.as_ref().map(|_| ()));
+        }
+        self.tx.send(r)
+    }
+
+    pub(crate) fn with_probe(mut self, probe: Option<RequestProbe>) -> Self {
+        self.probe = probe.map(Box::new);
+        self
+    }
+}
+
+impl StreamUpdateSender {
+    pub fn send(&self, event: StreamEvent) -> Result<(), mpsc::error::SendError<StreamEvent>> {
+        if let (Some(probe), StreamEvent::Data(bytes)) = (&self.probe, &event) {
+            probe.record_data(bytes.len());
+        }
+        self.tx.send(event)
+    }
+}
+
+/// Channel for stream events, see [Command::OnStreamEvent](crate::Command::OnStreamEvent).
+pub(crate) fn stream_update_channel(
+    probe: Option<StreamProbe>,
+) -> (StreamUpdateSender, StreamUpdateReceiver) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    (StreamUpdateSender { tx, probe }, rx)
+}
+
+impl RequestSeq {
+    pub fn get(&self) -> SeqTy {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, seq: SeqTy) {
+        self.0.store(seq, Ordering::Relaxed);
+    }
+}
+
+/// Channel to pass to [Command::SendMessage](crate::Command::SendMessage), for when requests are forwarded as bytes.
+pub fn response_channel() -> (ResponseSender, ResponseReceiver, RequestSeq) {
+    let seq = RequestSeq::default();
+    let (done_tx, rx) = response_channel_with_seq(seq.clone());
+    (done_tx, rx, seq)
+}
+
+/// Same as [response_channel], but a seq number assigned by an event loop is stored into an existing `seq`.
+pub(crate) fn response_channel_with_seq(seq: RequestSeq) -> (ResponseSender, ResponseReceiver) {
+    let (tx, rx) = oneshot::channel();
+    (
+        ResponseSender {
+            tx,
+            seq,
+            probe: None,
+        },
+        rx,
+    )
 }
 
 impl RxDispatcher {
@@ -99,14 +198,46 @@ impl RxDispatcher {
             DispatcherCommand::OnStreamEvent {
                 path_kind,
                 stream_event_tx,
+                subscribe_seq,
             } => {
-                if let PathKindOwned::Absolute { path } = path_kind {
-                    // TODO: send Connected/Disconnected only on actual connect/disconnect, send status here instead
-                    _ = stream_event_tx.send(self.is_connected_as_stream_event());
-                    let listeners = self.stream_handlers.entry(path).or_default();
-                    listeners.push(stream_event_tx);
+                // TODO: send Connected/Disconnected only on actual connect/disconnect, send status here instead
+                _ = stream_event_tx.send(self.is_connected_as_stream_event());
+                let path = match path_kind {
+                    PathKindOwned::Absolute { path } => path,
+                    path_kind => {
+                        let resolved = self
+                            .api_bundle
+                            .as_ref()
+                            .and_then(|api_bundle| resolve_trait_path(api_bundle, &path_kind));
+                        let Some(path) = resolved else {
+                            debug!("waiting for {path_kind:?} to be resolved");
+                            self.unresolved_stream_handlers.push(UnresolvedStream {
+                                path_kind,
+                                subscribe_seq,
+                                stream_event_tx,
+                            });
+                            return;
+                        };
+                        debug!("resolved {path_kind:?} to {path:?}");
+                        path
+                    }
+                };
+                let listeners = self.stream_handlers.entry(path).or_default();
+                listeners.push(stream_event_tx);
+            }
+            DispatcherCommand::SetApiBundle { api_bundle } => {
+                let mut unresolved = vec![];
+                for stream in self.unresolved_stream_handlers.drain(..) {
+                    if let Some(path) = resolve_trait_path(&api_bundle, &stream.path_kind) {
+                        debug!("resolved {:?} to {path:?}", stream.path_kind);
+                        let listeners = self.stream_handlers.entry(path).or_default();
+                        listeners.push(stream.stream_event_tx);
+                    } else {
+                        unresolved.push(stream);
+                    }
                 }
-                // TODO: other path kinds
+                self.unresolved_stream_handlers = unresolved;
+                self.api_bundle = Some(api_bundle);
             }
         }
     }
@@ -137,6 +268,7 @@ impl RxDispatcher {
             _ = done_tx.send(Err(Error::Disconnected));
             return;
         }
+        done_tx.seq.set(seq);
         let prune_at = Instant::now() + timeout;
         let replaced = self
             .response_map
@@ -148,6 +280,19 @@ impl RxDispatcher {
         }
     }
 
+    /// Forget a pending request, a late response to it will be ignored.
+    /// Returns true if the request was still pending.
+    pub fn cancel(&mut self, seq: SeqTy) -> bool {
+        if seq == 0 {
+            return false;
+        }
+        let removed = self.response_map.remove(&seq).is_some();
+        if removed {
+            trace!("cancelled {seq:?}");
+        }
+        removed
+    }
+
     pub fn prune_next_timeout(&mut self) -> Duration {
         let now = Instant::now();
         let mut min: Option<Duration> = None;
@@ -160,6 +305,10 @@ impl RxDispatcher {
                 trace!("pruned {seq:?}");
                 return false;
             }
+            if done_tx.is_closed() {
+                trace!("pruned {seq:?}, receiver was dropped");
+                return false;
+            }
             if let Some(prev_min) = &min {
                 if till_prune < *prev_min {
                     min = Some(till_prune);
@@ -240,7 +389,10 @@ impl RxDispatcher {
                         }
                         _ => unreachable!(),
                     };
-                    let path = path.iter().map(|p| p.unwrap()).collect::<Vec<_>>();
+                    let Ok(path) = path.iter().collect::<Result<Vec<_>, _>>() else {
+                        warn!("malformed stream path in event: {:?}", &event.seq);
+                        return;
+                    };
                     if let Some(listeners) = self.stream_handlers.get_mut(&path) {
                         listeners.retain(|tx| {
                             let keep = tx.send(ev.clone()).is_ok();
@@ -254,6 +406,29 @@ impl RxDispatcher {
                         }
                     }
                 }
+                EventKind::Subscribed { path } => {
+                    let path = path.iter().collect::<Result<Vec<_>, _>>();
+                    let done_tx = self.response_map.remove(&event.seq);
+                    match path {
+                        Ok(path) => {
+                            // response is the serialized absolute path, for when a request was forwarded on
+                            // behalf of another client (e.g., by ww daemon)
+                            if let Some((mut done_tx, _)) = done_tx {
+                                _ = done_tx.send(
+                                    shrink_wrap::to_ww_vec(&path, MAX_PATH_LEN)
+                                        .map_err(Error::ShrinkWrap),
+                                );
+                            }
+                            self.resolve_from_subscribed(event.seq, path);
+                        }
+                        Err(e) => {
+                            warn!("malformed path in Subscribed event: {:?} {e:?}", &event.seq);
+                            if let Some((mut done_tx, _)) = done_tx {
+                                _ = done_tx.send(Err(Error::ShrinkWrap(e)));
+                            }
+                        }
+                    }
+                }
                 _ => {}
             },
             Err(e) => {
@@ -266,6 +441,36 @@ impl RxDispatcher {
         }
     }
 
+    /// Device replied with an absolute path for a trait-addressed subscription.
+    /// Match it with a pending subscription by the seq number of a request that opened it, other pending subscriptions
+    /// to the same trait stream are resolved as well.
+    fn resolve_from_subscribed(&mut self, seq: SeqTy, path: Vec<UNib32>) {
+        if seq == 0 {
+            debug!("ignoring Subscribed event with seq == 0 for {path:?}");
+            return;
+        }
+        let Some(subscribed) = self
+            .unresolved_stream_handlers
+            .iter()
+            .find(|stream| stream.subscribe_seq.get() == seq)
+        else {
+            debug!("no pending trait subscriptions for {seq:?}, {path:?}");
+            return;
+        };
+        let path_kind = subscribed.path_kind.clone();
+        let mut unresolved = vec![];
+        for stream in self.unresolved_stream_handlers.drain(..) {
+            if is_same_trait_path(&path_kind, &stream.path_kind) {
+                debug!("resolved {:?} to {path:?}", stream.path_kind);
+                let listeners = self.stream_handlers.entry(path.clone()).or_default();
+                listeners.push(stream.stream_event_tx);
+            } else {
+                unresolved.push(stream);
+            }
+        }
+        self.unresolved_stream_handlers = unresolved;
+    }
+
     fn cancel_all_requests(&mut self) {
         trace!("canceling all requests");
         for (_, (mut done_tx, _)) in self.response_map.drain() {
@@ -293,5 +498,164 @@ impl RxDispatcher {
                 keep
             });
         }
+        self.unresolved_stream_handlers.retain(|stream| {
+            let keep = stream.stream_event_tx.send(event.clone()).is_ok();
+            if !keep {
+                debug!("dropped subscriber for stream at {:?}", stream.path_kind);
+            }
+            keep
+        });
+    }
+}
+
+fn path_from_trait(path_kind: &PathKindOwned) -> &[UNib32] {
+    match path_kind {
+        PathKindOwned::Absolute { path } => path,
+        PathKindOwned::GlobalCompact {
+            path_from_trait, ..
+        }
+        | PathKindOwned::GlobalFull {
+            path_from_trait, ..
+        } => path_from_trait,
+    }
+}
+
+fn is_same_trait_path(a: &PathKindOwned, b: &PathKindOwned) -> bool {
+    match (a, b) {
+        (
+            PathKindOwned::GlobalCompact {
+                gid: gid_a,
+                path_from_trait: path_a,
+            },
+            PathKindOwned::GlobalCompact {
+                gid: gid_b,
+                path_from_trait: path_b,
+            },
+        ) => gid_a == gid_b && path_a == path_b,
+        (
+            PathKindOwned::GlobalFull {
+                gid: gid_a,
+                path_from_trait: path_a,
+            },
+            PathKindOwned::GlobalFull {
+                gid: gid_b,
+                path_from_trait: path_b,
+            },
+        ) => gid_a == gid_b && path_a == path_b,
+        _ => false,
+    }
+}
+
+/// Find where a trait is implemented in the resource tree and return an absolute path to a stream in it.
+/// Only traits that are not in arrays are considered, as an index is not known. Returns None if a trait is not found
+/// or if it is found in more than one place.
+fn resolve_trait_path(
+    api_bundle: &ApiBundleOwned,
+    path_kind: &PathKindOwned,
+) -> Option<Vec<UNib32>> {
+    let mut found = vec![];
+    find_trait(
+        api_bundle,
+        &api_bundle.root,
+        path_kind,
+        &mut vec![],
+        &mut found,
+    );
+    if found.len() > 1 {
+        warn!("{path_kind:?} is implemented in several places: {found:?}, cannot resolve");
+        return None;
+    }
+    let mut path = found.pop()?;
+    path.extend_from_slice(path_from_trait(path_kind));
+    Some(path)
+}
+
+fn find_trait(
+    api_bundle: &ApiBundleOwned,
+    level: &ApiLevelOwned,
+    path_kind: &PathKindOwned,
+    base: &mut Vec<UNib32>,
+    found: &mut Vec<Vec<UNib32>>,
+) {
+    for item in &level.items {
+        let ApiItemKindOwned::Trait { trait_idx } = &item.kind else {
+            continue;
+        };
+        if item.is_array() {
+            continue;
+        }
+        let Some(location) = api_bundle.traits.get(trait_idx.0 as usize) else {
+            continue;
+        };
+        base.push(item.id);
+        let child_level = match location {
+            ApiLevelLocationOwned::InLine { level, crate_idx } => {
+                let is_compatible = match (path_kind, api_bundle.crate_version(crate_idx.0)) {
+                    (PathKindOwned::GlobalFull { gid, .. }, Ok(version)) => {
+                        version.is_protocol_compatible(gid)
+                    }
+                    (PathKindOwned::GlobalCompact { gid, .. }, Ok(version)) => version
+                        .to_compact()
+                        .is_some_and(|version| version.is_protocol_compatible(gid)),
+                    _ => false,
+                };
+                if is_compatible && is_stream_at(api_bundle, level, path_from_trait(path_kind)) {
+                    found.push(base.clone());
+                }
+                Some(level)
+            }
+            ApiLevelLocationOwned::SkippedFullVersion { crate_idx, .. } => {
+                if let PathKindOwned::GlobalFull { gid, .. } = path_kind
+                    && let Ok(version) = api_bundle.crate_version(crate_idx.0)
+                    && version.is_protocol_compatible(gid)
+                {
+                    found.push(base.clone());
+                }
+                None
+            }
+            ApiLevelLocationOwned::SkippedCompactVersion { version, .. } => {
+                if let PathKindOwned::GlobalCompact { gid, .. } = path_kind
+                    && version == gid
+                {
+                    found.push(base.clone());
+                }
+                None
+            }
+        };
+        // limit depth in case of a malformed bundle
+        if let Some(child_level) = child_level
+            && base.len() < 32
+        {
+            find_trait(api_bundle, child_level, path_kind, base, found);
+        }
+        base.pop();
+    }
+}
+
+fn is_stream_at(api_bundle: &ApiBundleOwned, level: &ApiLevelOwned, path: &[UNib32]) -> bool {
+    let Some((id, rest)) = path.split_first() else {
+        return false;
+    };
+    let Some(item) = level.items.iter().find(|i| i.id == *id) else {
+        return false;
+    };
+    let rest = if item.is_array() {
+        // skip array index
+        let Some((_, rest)) = rest.split_first() else {
+            return false;
+        };
+        rest
+    } else {
+        rest
+    };
+    match &item.kind {
+        ApiItemKindOwned::Stream { .. } => rest.is_empty(),
+        ApiItemKindOwned::Trait { trait_idx } => {
+            let Ok(level) = api_bundle.get_trait(trait_idx.0) else {
+                return false;
+            };
+            is_stream_at(api_bundle, level, rest)
+        }
+        _ => false,
     }
 }
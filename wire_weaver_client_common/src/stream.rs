@@ -1,5 +1,6 @@
 use crate::command_sender::TransportCommander;
-use crate::{Error, StreamEvent, TypedStreamEvent};
+use crate::rx_dispatcher::RequestSeq;
+use crate::{Error, SeqTy, StreamEvent, TypedStreamEvent};
 use std::marker::PhantomData;
 use std::ops::ControlFlow;
 use tokio::sync::mpsc::{error::TryRecvError, UnboundedReceiver};
@@ -13,6 +14,8 @@ use ww_client_server::{PathKindOwned, StreamSidebandCommand, StreamSidebandEvent
 pub struct Stream<T> {
     pub(crate) transport_cmd_tx: TransportCommander,
     pub(crate) path_kind: PathKindOwned,
+    /// Seq number of the Open request for trait-addressed streams, matched with a Subscribed event from a device.
+    pub(crate) subscribe_seq: RequestSeq,
     pub(crate) rx: UnboundedReceiver<StreamEvent>,
     pub(crate) _phantom: PhantomData<T>,
 }
@@ -30,9 +33,24 @@ pub enum StreamError {
 }
 
 impl<T: DeserializeShrinkWrapOwned> Stream<T> {
-    /// Send Open command through sideband channel
+    /// Send Open command through sideband channel.
+    ///
+    /// For trait-addressed streams, a device replies with Subscribed event carrying an absolute path of the stream,
+    /// if it is not yet known from an ApiBundle.
     pub fn open(&self) -> Result<(), StreamError> {
-        self.sideband(StreamSidebandCommand::Open)
+        if matches!(self.path_kind, PathKindOwned::Absolute { .. }) {
+            return self.sideband(StreamSidebandCommand::Open);
+        }
+        // the response itself is not needed, only the seq number assigned to it
+        let _done_rx = self
+            .transport_cmd_tx
+            .send_stream_open(self.path_kind.clone(), self.subscribe_seq.clone())?;
+        Ok(())
+    }
+
+    /// Seq number assigned to the last Open request of a trait-addressed stream, 0 if it was not sent yet.
+    pub fn open_seq(&self) -> SeqTy {
+        self.subscribe_seq.get()
     }
 
     /// Send Close command through sideband channel
@@ -64,8 +82,7 @@ impl<T: DeserializeShrinkWrapOwned> Stream<T> {
             };
             break bytes;
         };
-        let data = T::from_ww_bytes_owned(&bytes)?;
-        Ok(data)
+        self.deserialize(&bytes)
     }
 
     /// Receive one data event in a blocking manner and deserialize it.
@@ -84,8 +101,7 @@ impl<T: DeserializeShrinkWrapOwned> Stream<T> {
             };
             break bytes;
         };
-        let data = T::from_ww_bytes_owned(&bytes)?;
-        Ok(data)
+        self.deserialize(&bytes)
     }
 
     /// Try to receive one data event and deserialize it.
@@ -109,8 +125,7 @@ impl<T: DeserializeShrinkWrapOwned> Stream<T> {
                 Err(TryRecvError::Disconnected) => return Err(StreamError::Closed),
             }
         };
-        let data = T::from_ww_bytes_owned(&bytes)?;
-        Ok(Some(data))
+        self.deserialize(&bytes).map(Some)
     }
 
     /// Receive one event of any kind (data or sideband), deserialize if data is received.
@@ -118,7 +133,7 @@ impl<T: DeserializeShrinkWrapOwned> Stream<T> {
     /// See [Self::recv_any_blocking] for a blocking variant of this method.
     pub async fn recv_any(&mut self) -> Result<TypedStreamEvent<T>, StreamError> {
         let ev = self.rx.recv().await.ok_or(StreamError::Closed)?;
-        to_typed(ev)
+        self.to_typed(ev)
     }
 
     /// Receive one event of any kind (data or sideband), deserialize if data is received.
@@ -126,7 +141,36 @@ impl<T: DeserializeShrinkWrapOwned> Stream<T> {
     /// See [Self::recv_any] for an asynchronous variant of this method.
     pub fn recv_any_blocking(&mut self) -> Result<TypedStreamEvent<T>, StreamError> {
         let ev = self.rx.blocking_recv().ok_or(StreamError::Closed)?;
-        to_typed(ev)
+        self.to_typed(ev)
+    }
+
+    fn to_typed(&self, ev: StreamEvent) -> Result<TypedStreamEvent<T>, StreamError> {
+        match ev {
+            StreamEvent::Data(bytes) => Ok(TypedStreamEvent::Data(self.deserialize(&bytes)?)),
+            StreamEvent::Sideband(s) => Ok(TypedStreamEvent::Sideband(s)),
+            StreamEvent::Connected => Ok(TypedStreamEvent::Connected),
+            StreamEvent::Disconnected => Ok(TypedStreamEvent::Disconnected),
+        }
+    }
+
+    /// Values that failed to deserialize are counted as dropped.
+    fn deserialize(&self, bytes: &[u8]) -> Result<T, StreamError> {
+        let data = T::from_ww_bytes_owned(bytes).inspect_err(|_| {
+            self.transport_cmd_tx.record_stream_dropped(1);
+        })?;
+        Ok(data)
+    }
+}
+
+impl<T> Drop for Stream<T> {
+    fn drop(&mut self) {
+        let mut undelivered = 0;
+        while let Ok(ev) = self.rx.try_recv() {
+            if matches!(ev, StreamEvent::Data(_)) {
+                undelivered += 1;
+            }
+        }
+        self.transport_cmd_tx.record_stream_dropped(undelivered);
     }
 }
 
@@ -175,17 +219,3 @@ fn recv_all_inner(ev: StreamEvent, buf: &mut Vec<u8>) -> Result<ControlFlow<()>,
         e => Err(StreamError::UnexpectedEvent(e)),
     }
 }
-
-fn to_typed<T: DeserializeShrinkWrapOwned>(
-    ev: StreamEvent,
-) -> Result<TypedStreamEvent<T>, StreamError> {
-    match ev {
-        StreamEvent::Data(bytes) => {
-            let data = T::from_ww_bytes_owned(&bytes)?;
-            Ok(TypedStreamEvent::Data(data))
-        }
-        StreamEvent::Sideband(s) => Ok(TypedStreamEvent::Sideband(s)),
-        StreamEvent::Connected => Ok(TypedStreamEvent::Connected),
-        StreamEvent::Disconnected => Ok(TypedStreamEvent::Disconnected),
-    }
-}
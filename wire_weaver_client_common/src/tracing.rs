@@ -1,3 +1,6 @@
+use crate::DeviceInfoBundle;
+use ww_self::ApiBundleOwned;
+
 pub enum TraceEvent {
     /// Request being sent to a remote device
     Request {
@@ -21,6 +24,17 @@ pub enum TraceEvent {
     Error {
         reason: String,
     },
+
+    /// ApiBundle of a connected device, provided through [Command::SetApiBundle](crate::Command::SetApiBundle)
+    ApiBundle {
+        api_bundle: Box<ApiBundleOwned>,
+    },
+
+    /// Link-level packet (e.g., USB packet carrying `wire_weaver_usb_link::Op`'s)
+    LinkPacket {
+        to_device: bool,
+        bytes: Vec<u8>,
+    },
 }
 
 // Ensure the event is not too big as there can be a lot of them
@@ -29,6 +43,8 @@ pub enum TraceEvent {
 // };
 
 pub struct ConnectionInfo {
+    /// Device info, if link provides it
+    pub device_info: Option<DeviceInfoBundle>,
     // /// API model version, e.g., ww_client_server
     // pub remote_api_model: FullVersionOwned,
     // pub local_api_model: FullVersionOwned,
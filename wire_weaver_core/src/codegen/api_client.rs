@@ -329,12 +329,16 @@ fn level_method(
         }
     };
     let ident = Ident::new(&item.ident, Span::call_site());
+    let since = since(item, gid_paths);
+    let deprecated = deprecated(item);
     let lm = match &item.kind {
         ApiItemKindOwned::Method { args, return_ty } => handle_method(
             api_bundle,
             model,
             path_mode,
             gid_paths,
+            &since,
+            &deprecated,
             index_chain_push,
             &ident,
             args,
@@ -350,6 +354,8 @@ fn level_method(
             model,
             path_mode,
             gid_paths,
+            &since,
+            &deprecated,
             index_chain_push,
             access,
             &ident,
@@ -361,6 +367,8 @@ fn level_method(
             model,
             path_mode,
             gid_paths,
+            &since,
+            &deprecated,
             maybe_index_arg,
             index_chain_push,
             &ident,
@@ -373,6 +381,7 @@ fn level_method(
             let mod_name = util::mod_name(level, api_bundle);
             let client_struct_name = client_struct_name(&mod_name.to_string());
             quote! {
+                #deprecated
                 pub fn #level_entry_fn_name(&self #maybe_index_arg) -> #mod_name::#client_struct_name<'_> {
                     #index_chain_push
                     #mod_name::#client_struct_name {
@@ -383,6 +392,24 @@ fn level_method(
             }
         }
     };
+    let lm = if item.since.is_some() {
+        let is_available_fn_name =
+            Ident::new(&format!("{}_is_available", item.ident), Span::call_site());
+        let doc = format!(
+            " Returns false if a connected device implements an older API version without `{}`.",
+            item.ident
+        );
+        quote! {
+            #lm
+            #[doc = #doc]
+            #deprecated
+            pub fn #is_available_fn_name(&self) -> bool {
+                self.cmd_tx.is_available(#since)
+            }
+        }
+    } else {
+        lm
+    };
     if item.multiplicity == Multiplicity::Flat {
         lm
     } else {
@@ -390,20 +417,54 @@ fn level_method(
         let path_kind = path_kind(path_mode, gid_paths);
         quote! {
             #lm
+            #deprecated
             pub fn #read_fn_name(&self) -> wire_weaver_client_common::PreparedRead<ValidIndicesOwned> {
                 #index_chain_push_pre
                 let path_kind = #path_kind;
-                self.cmd_tx.prepare_read(path_kind)
+                self.cmd_tx.prepare_read(path_kind, #since)
             }
         }
     }
 }
 
+/// Expression evaluating to `Option<FullVersion>` with a version from `#[since = "x.y.z"]` and crate name of the trait
+/// that contains the item, used to check it against a connected device API version.
+fn since(item: &ApiItemOwned, gid_paths: &(TokenStream, TokenStream)) -> TokenStream {
+    let Some(since) = &item.since else {
+        return quote! { None };
+    };
+    let full = &gid_paths.0;
+    let (major, minor, patch) = (since.major.0, since.minor.0, since.patch.0);
+    quote! {
+        Some(ww_version::FullVersion::new(#full.crate_id, ww_version::Version::new(#major, #minor, #patch)))
+    }
+}
+
+/// `#[deprecated]` attribute for all client methods of an item, so that users get warnings when using them.
+fn deprecated(item: &ApiItemOwned) -> TokenStream {
+    let Some(deprecated) = &item.deprecated else {
+        return quote! {};
+    };
+    let since = deprecated
+        .since
+        .map(|since| format!("{since:?}"))
+        .map(|since| quote! { since = #since });
+    let note = deprecated.note.as_ref().map(|note| quote! { note = #note });
+    if since.is_none() && note.is_none() {
+        quote! { #[deprecated] }
+    } else {
+        let args = since.into_iter().chain(note);
+        quote! { #[deprecated(#(#args),*)] }
+    }
+}
+
 fn handle_method(
     api_bundle: &ApiBundleOwned,
     model: ClientModel,
     path_mode: ClientPathMode,
     gid_paths: &(TokenStream, TokenStream),
+    since: &TokenStream,
+    deprecated: &TokenStream,
     index_chain_push: TokenStream,
     ident: &Ident,
     args: &[ArgumentOwned],
@@ -422,12 +483,13 @@ fn handle_method(
     let docs = docs.iter().map(|s| quote! { #[doc = #s] });
     quote! {
         #(#docs)*
+        #deprecated
         pub fn #ident(& #maybe_mut self, #args_list) -> wire_weaver_client_common::PreparedCall<#output_ty> {
             let mut args_scratch = [0u8; 128]; // TODO: Vec based writer
             #args_ser
             #index_chain_push
             let path_kind = #path_kind;
-            self.cmd_tx.prepare_call(path_kind, args_bytes)
+            self.cmd_tx.prepare_call(path_kind, #since, args_bytes)
         }
     }
 }
@@ -436,6 +498,8 @@ fn handle_property(
     model: ClientModel,
     path_mode: ClientPathMode,
     gid_paths: &(TokenStream, TokenStream),
+    since: &TokenStream,
+    deprecated: &TokenStream,
     index_chain_push: TokenStream,
     access: &PropertyAccess,
     prop_name: &Ident,
@@ -456,12 +520,13 @@ fn handle_property(
             quote! { () }
         };
         quote! {
+            #deprecated
             pub fn #write_fn_name(&self, #prop_name: #ty) -> wire_weaver_client_common::PreparedWrite<Result<(), #user_result_ty>> {
                 let mut args_scratch = [0u8; 128]; // TODO: Vec based writer
                 let value = #prop_name.to_ww_bytes(&mut args_scratch).map(|b| b.to_vec()).map_err(|e| e.into());
                 #index_chain_push
                 let path_kind = #path_kind;
-                self.cmd_tx.prepare_write(path_kind, value)
+                self.cmd_tx.prepare_write(path_kind, #since, value)
             }
         }
     } else {
@@ -474,10 +539,11 @@ fn handle_property(
     ) {
         let read_fn_name = Ident::new(&format!("read_{}", prop_name), Span::call_site());
         quote! {
+            #deprecated
             pub fn #read_fn_name(&self) -> wire_weaver_client_common::PreparedRead<#ty> {
                 #index_chain_push
                 let path_kind = #path_kind;
-                self.cmd_tx.prepare_read(path_kind)
+                self.cmd_tx.prepare_read(path_kind, #since)
             }
         }
     } else {
@@ -495,6 +561,8 @@ fn handle_stream(
     model: ClientModel,
     path_mode: ClientPathMode,
     gid_paths: &(TokenStream, TokenStream),
+    since: &TokenStream,
+    deprecated: &TokenStream,
     maybe_index_arg: TokenStream,
     index_chain_push: TokenStream,
     ident: &Ident,
@@ -511,20 +579,22 @@ fn handle_stream(
     if is_up {
         // client in
         quote! {
+            #deprecated
             pub fn #ident(&self #maybe_index_arg) -> Result<wire_weaver_client_common::Stream<#ty_def>, wire_weaver_client_common::Error> {
                 #index_chain_push
                 let path_kind = #path_kind;
-                let stream = self.cmd_tx.prepare_stream(path_kind)?;
+                let stream = self.cmd_tx.prepare_stream(path_kind, #since)?;
                 Ok(stream)
             }
         }
     } else {
         // client out
         quote! {
+            #deprecated
             pub fn #ident(&self #maybe_index_arg) -> Result<wire_weaver_client_common::Sink<#ty_def>, wire_weaver_client_common::Error> {
                 #index_chain_push
                 let path_kind = #path_kind;
-                let sink = self.cmd_tx.prepare_sink(path_kind)?;
+                let sink = self.cmd_tx.prepare_sink(path_kind, #since)?;
                 Ok(sink)
             }
         }
@@ -670,12 +740,13 @@ fn connect_disconnect_methods(usb_connect: bool, api_bundle: &ApiBundleOwned) ->
         #usb_connect_fn_raw
         #usb_connect_blocking_raw
 
-        fn introspect_bytes() -> &'static [u8] {
+        /// Serialized ww_self description of the client API, see `CommandSender::set_client_introspect_bytes`.
+        pub fn introspect_bytes() -> &'static [u8] {
             const WW_SELF_BYTES: #ww_self_bytes_const;
             &WW_SELF_BYTES
         }
 
-        fn api_signature() -> &'static [u8] {
+        pub fn api_signature() -> &'static [u8] {
             const WW_API_SIGNATURE_BYTES: #api_signature_bytes;
             &WW_API_SIGNATURE_BYTES
         }
@@ -732,3 +803,78 @@ fn path_kind(path_mode: ClientPathMode, gid_paths: &(TokenStream, TokenStream))
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn deprecated_client_methods() {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/deprecated_api");
+        let api_bundle = crate::load(&crate_path, Some("Sensor".into()), true).unwrap();
+        let client = gen_client(
+            &api_bundle,
+            GenClientConfig {
+                model: ClientModel::StdFullClient,
+                client_struct_path: "crate::SensorClient".into(),
+                usb_connect: false,
+            },
+        )
+        .to_string();
+        assert!(client.contains(
+            "# [deprecated (since = \"0.2.0\" , note = \"use configure instead\")] pub fn set_gain"
+        ));
+        assert!(client.contains("# [deprecated] pub fn write_mode"));
+        assert!(client.contains("# [deprecated] pub fn read_mode"));
+        assert!(client.contains(
+            "# [deprecated (note = \"samples are now sent with timestamps\")] pub fn raw_samples"
+        ));
+        assert!(!client.contains("# [deprecated] pub fn configure"));
+    }
+
+    #[test]
+    fn generic_type_instances() {
+        use ww_self::{FieldsOwned, TypeLocationOwned, TypeOwned};
+
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/generics_api");
+        let api_bundle = crate::load(&crate_path, Some("Telemetry".into()), true).unwrap();
+        let mut names = vec![];
+        for location in &api_bundle.types {
+            let TypeLocationOwned::InLine { ty, .. } = location else {
+                continue;
+            };
+            if let TypeOwned::Struct(item_struct) = ty
+                && item_struct.ident == "Measurement"
+            {
+                let generics = item_struct.generics.as_ref().unwrap();
+                assert_eq!(generics.params, ["T"]);
+                let FieldsOwned::Named(fields) = &item_struct.fields else {
+                    panic!("expected named fields");
+                };
+                assert_eq!(fields[0].ty, generics.args[0]);
+            }
+            names.push(ty.human_name(false, &api_bundle).unwrap());
+        }
+        for name in [
+            "Measurement<f32>",
+            "Measurement<i16>",
+            "Response<String>",
+            "Reply<Measurement<f32>, u8>",
+        ] {
+            assert!(names.iter().any(|n| n == name), "{name} not in {names:?}");
+        }
+
+        let client = gen_client(
+            &api_bundle,
+            GenClientConfig {
+                model: ClientModel::StdFullClient,
+                client_struct_path: "crate::TelemetryClient".into(),
+                usb_connect: false,
+            },
+        )
+        .to_string();
+        assert!(client.contains("generics_api :: Measurement < i16 >"));
+        assert!(client.contains("generics_api :: Response < String >"));
+    }
+}
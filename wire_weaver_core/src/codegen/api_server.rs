@@ -1,6 +1,8 @@
 //! # Implementation details:
 //! * Server's index chain contains only array indices on the way to a resource
 use crate::codegen::index_chain::IndexChain;
+use crate::codegen::server::handler_traits::handler_traits_recursive;
+pub use crate::codegen::server::handlers::{HandlerKind, HandlerSignature, handler_signatures};
 use crate::codegen::server::stream::stream_ser_methods_recursive;
 use crate::codegen::ty_def::ty_def;
 use crate::codegen::util::{ErrorSeq, add_prefix, maybe_quote};
@@ -32,6 +34,11 @@ pub struct GenServerConfig {
     pub server_struct_path: String,
     /// Generate ww_self introspect bytes, fully describing all API methods and data types used.
     pub generate_introspect: bool,
+    /// Generate a Rust trait per API level with default handlers, instead of expecting inherent methods on
+    /// `server_struct_path`, see [gen_server].
+    /// Handlers in traits are not prefixed with level names, but `method_model` is still matched against prefixed
+    /// names (e.g. `gpio_set`), so the same model can be used in both modes.
+    pub handler_traits: bool,
 }
 
 /// API server code generation configuration.
@@ -51,6 +58,11 @@ pub struct GenServerConfigRaw {
     pub server_struct_path: Path,
     /// Generate ww_self introspect bytes, fully describing all API methods and data types used.
     pub generate_introspect: bool,
+    /// Generate a Rust trait per API level with default handlers, instead of expecting inherent methods on
+    /// `server_struct_path`, see [gen_server].
+    /// Handlers in traits are not prefixed with level names, but `method_model` is still matched against prefixed
+    /// names (e.g. `gpio_set`), so the same model can be used in both modes.
+    pub handler_traits: bool,
 }
 
 impl From<GenServerConfig> for GenServerConfigRaw {
@@ -62,6 +74,7 @@ impl From<GenServerConfig> for GenServerConfigRaw {
             property_model: config.property_model,
             server_struct_path: super::util::str_to_path(&config.server_struct_path),
             generate_introspect: config.generate_introspect,
+            handler_traits: config.handler_traits,
         }
     }
 }
@@ -71,6 +84,21 @@ impl From<GenServerConfig> for GenServerConfigRaw {
 /// Pass a [GenServerConfig] or [GenServerConfigRaw] to configure code generation.
 ///
 /// Alternatively, use [wire_weaver_derive::ww_codegen] proc-macro if you do not want to use build.rs.
+///
+/// By default, the dispatcher is implemented on `server_struct_path` and calls user handlers implemented as inherent
+/// methods on the same struct, prefixed with the path to them (e.g. `gpio_set_high`).
+///
+/// If `handler_traits` is set, a Rust trait is generated for each API level instead: `RootTraitNameServer` for the root
+/// level and `PathToLevelServer` for the nested ones (e.g. `GpioServer` for `ww_impl!(gpio[]: Gpio)`).
+/// * Handlers are not prefixed and return `Result<T, ErrorKind<'static>>`, by default all of them return
+///   `Err(ErrorKind::OperationNotImplemented)`, so that API can be implemented incrementally.
+/// * Nested levels are reached through an associated type and an accessor method (e.g. `type Gpio: GpioServer;` and
+///   `fn gpio(&mut self) -> &mut Self::Gpio;`), so that handlers can be split across several types.
+/// * Array resources require `valid_indices_<name>` methods, same as in the default mode.
+/// * Dispatcher (`process_request_bytes`) is a provided method of the root level trait, so any type implementing it
+///   can be used as a server, including mocks.
+/// * `value_on_changed` property model is not supported, properties always use get and set handlers.
+/// * Constants and deferred methods helpers are still implemented on `server_struct_path`.
 pub fn gen_server(
     api_bundle: &ApiBundleOwned,
     config: impl Into<GenServerConfigRaw>,
@@ -80,8 +108,6 @@ pub fn gen_server(
         config.no_alloc,
         quote! { #[allow(unused_imports)] use wire_weaver::shrink_wrap::{RefVec, RefVecIter}; },
     );
-    let maybe_async = maybe_quote(config.use_async, quote! { async });
-    let maybe_await = maybe_quote(config.use_async, quote! { .await });
     let mut error_seq = ErrorSeq::default();
     let api_level = &api_bundle.root;
     let deferred_return_methods = deferred_method_return_ser_methods(
@@ -94,10 +120,12 @@ pub fn gen_server(
     let crate_name = api_level.crate_name(api_bundle).unwrap();
     let cx = ApiServerCGContext {
         ident_prefix: None,
+        level_path: None,
         no_alloc: config.no_alloc,
         use_async: config.use_async,
         method_model: &config.method_model,
         property_model: &config.property_model,
+        handler_traits: config.handler_traits,
     };
     let (handle_introspect, api_signature) = super::server::introspect::introspect(
         api_bundle,
@@ -105,16 +133,33 @@ pub fn gen_server(
         cx.use_async,
         &mut error_seq,
     );
-    let process_request_inner = process_request_inner_recursive(
-        "root".into(),
-        api_bundle,
-        api_level,
-        IndexChain::new(),
-        crate_name,
-        &cx,
-        &mut error_seq,
-        Some(handle_introspect),
-    );
+    let (dispatcher, handler_traits) = if config.handler_traits {
+        let process_request_bytes = process_request_bytes(&cx, &mut error_seq);
+        let handler_traits = handler_traits_recursive(
+            None,
+            api_bundle,
+            api_level,
+            IndexChain::new(),
+            crate_name,
+            &cx,
+            &mut error_seq,
+            Some(handle_introspect),
+            process_request_bytes,
+        );
+        (quote! {}, handler_traits)
+    } else {
+        let process_request_inner = process_request_inner_recursive(
+            "root".into(),
+            api_bundle,
+            api_level,
+            IndexChain::new(),
+            crate_name,
+            &cx,
+            &mut error_seq,
+            Some(handle_introspect),
+        );
+        (process_request_inner, quote! {})
+    };
     let stream_send_methods = stream_ser_methods_recursive(
         api_bundle,
         api_level,
@@ -132,7 +177,16 @@ pub fn gen_server(
         &mut seen,
         &mut args_structs,
     );
-    let es = error_seq.next_err();
+    let max_len_consts = max_len_consts(api_bundle, api_level, config.no_alloc);
+    let dispatcher = if config.handler_traits {
+        dispatcher
+    } else {
+        let process_request_bytes = process_request_bytes(&cx, &mut error_seq);
+        quote! {
+            #process_request_bytes
+            #dispatcher
+        }
+    };
     let server_struct_path = config.server_struct_path;
     quote! {
         #args_structs
@@ -148,69 +202,122 @@ pub fn gen_server(
         #api_signature
 
         impl #server_struct_path {
-            /// Returns an Error only if request deserialization or error serialization failed.
-            /// If there are any other errors, they are returned to the remote caller.
-            pub #maybe_async fn process_request_bytes<'a>(
-                &mut self,
-                bytes: &[u8],
-                scratch_args: &'a mut [u8],
-                scratch_event: &'a mut [u8],
-                scratch_err: &'a mut [u8],
-                msg_tx: &mut impl wire_weaver::MessageSink,
-            ) -> Result<&'a [u8], ShrinkWrapError> {
-                let mut rd = BufReader::new(bytes);
-                let request = Request::des_shrink_wrap(&mut rd)?;
-                // if matches!(request.kind, RequestKind::Read) && request.seq == 0 { // TODO: Move to property read
-                //     return Ok(ser_err_event(scratch_err, request.seq, Error::ReadPropertyWithSeqZero).map_err(|_| Error::ResponseSerFailed)?)
-                // }
-                // TODO: handle trait paths on server side
-                let PathKind::Absolute { path } = &request.path_kind else {
-                    let mut wr = BufWriter::new(scratch_err);
-                    let event = Event { seq: request.seq, result: Err(Error::new(#es, ErrorKind::PathKindNotSupported)) };
-                    event.ser_shrink_wrap(&mut wr)?;
-                    return wr.finish_and_take();
-                };
-                let mut path_iter = path.iter();
-                match self.process_root(path.clone(), &mut path_iter, &request, scratch_args, scratch_event, msg_tx)#maybe_await {
-                    Ok(response_bytes) => Ok(response_bytes),
-                    Err(e) => {
-                        let mut wr = BufWriter::new(scratch_err);
-                        let event = Event {
-                            seq: request.seq,
-                            result: Err(e)
-                        };
-                        event.ser_shrink_wrap(&mut wr)?;
-                        wr.finish_and_take()
-                    }
-                }
-            }
+            #max_len_consts
 
-            #process_request_inner
+            #dispatcher
 
             #deferred_return_methods
         }
 
+        #handler_traits
+
         #stream_send_methods
     }
 }
 
+/// Dispatcher entry point, inherent method on the server struct or provided method of the root handler trait.
+fn process_request_bytes(cx: &ApiServerCGContext<'_>, error_seq: &mut ErrorSeq) -> TokenStream {
+    let maybe_pub = maybe_quote(!cx.handler_traits, quote! { pub });
+    let maybe_async = maybe_quote(cx.use_async, quote! { async });
+    let maybe_await = maybe_quote(cx.use_async, quote! { .await });
+    let es = error_seq.next_err();
+    quote! {
+        /// Returns an Error only if request deserialization or error serialization failed.
+        /// If there are any other errors, they are returned to the remote caller.
+        #maybe_pub #maybe_async fn process_request_bytes<'a>(
+            &mut self,
+            bytes: &[u8],
+            scratch_args: &'a mut [u8],
+            scratch_event: &'a mut [u8],
+            scratch_err: &'a mut [u8],
+            msg_tx: &mut impl wire_weaver::MessageSink,
+        ) -> Result<&'a [u8], ShrinkWrapError> {
+            let mut rd = BufReader::new(bytes);
+            let request = Request::des_shrink_wrap(&mut rd)?;
+            // if matches!(request.kind, RequestKind::Read) && request.seq == 0 { // TODO: Move to property read
+            //     return Ok(ser_err_event(scratch_err, request.seq, Error::ReadPropertyWithSeqZero).map_err(|_| Error::ResponseSerFailed)?)
+            // }
+            // TODO: handle trait paths on server side
+            let PathKind::Absolute { path } = &request.path_kind else {
+                let mut wr = BufWriter::new(scratch_err);
+                let event = Event { seq: request.seq, result: Err(Error::new(#es, ErrorKind::PathKindNotSupported)) };
+                event.ser_shrink_wrap(&mut wr)?;
+                return wr.finish_and_take();
+            };
+            let mut path_iter = path.iter();
+            match self.process_root(path.clone(), &mut path_iter, &request, scratch_args, scratch_event, msg_tx)#maybe_await {
+                Ok(response_bytes) => Ok(response_bytes),
+                Err(e) => {
+                    let mut wr = BufWriter::new(scratch_err);
+                    let event = Event {
+                        seq: request.seq,
+                        result: Err(e)
+                    };
+                    event.ser_shrink_wrap(&mut wr)?;
+                    wr.finish_and_take()
+                }
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
-struct ApiServerCGContext<'i> {
-    ident_prefix: Option<String>,
-    no_alloc: bool,
-    use_async: bool,
-    method_model: &'i MethodModel,
-    property_model: &'i PropertyModel,
+pub(crate) struct ApiServerCGContext<'i> {
+    pub(crate) ident_prefix: Option<String>,
+    /// Names of the levels from the root joined with `_`, same as `ident_prefix`, but also tracked for handler traits
+    pub(crate) level_path: Option<String>,
+    pub(crate) no_alloc: bool,
+    pub(crate) use_async: bool,
+    pub(crate) method_model: &'i MethodModel,
+    pub(crate) property_model: &'i PropertyModel,
+    pub(crate) handler_traits: bool,
 }
 
 impl<'i> ApiServerCGContext<'i> {
-    fn push_suffix(&mut self, suffix: &str) {
+    pub(crate) fn push_suffix(&mut self, suffix: &str) {
         if let Some(prefix) = &self.ident_prefix {
             self.ident_prefix = Some(format!("{}_{}", prefix, suffix));
         } else {
             self.ident_prefix = Some(suffix.to_string());
         }
     }
+
+    /// Enter a nested level, handlers are prefixed with its name, unless handler traits are generated.
+    pub(crate) fn push_level(&mut self, level: &str) {
+        if !self.handler_traits {
+            self.push_suffix(level);
+        }
+        self.level_path = Some(match &self.level_path {
+            Some(path) => format!("{path}_{level}"),
+            None => level.to_string(),
+        });
+    }
+
+    /// Method model is matched against a method name prefixed with the levels it is in (e.g. `gpio_set` for a `set`
+    /// method of a `gpio` level), regardless of whether handler traits are generated.
+    pub(crate) fn method_path(&self, method: &str) -> String {
+        match &self.level_path {
+            Some(path) => format!("{path}_{method}"),
+            None => method.to_string(),
+        }
+    }
+
+    pub(crate) fn method_model(&self, method: &str) -> MethodModelKind {
+        let path = self.method_path(method);
+        self.method_model.pick(&path).unwrap_or_else(|| {
+            panic!("method_model has no match for {path}, add a default, e.g. _=immediate")
+        })
+    }
+
+    /// Handlers from generated traits return `Result<T, ErrorKind>`, forward an error to the remote caller.
+    fn handler_err(&self, error_seq: &mut ErrorSeq) -> TokenStream {
+        if self.handler_traits {
+            let es = error_seq.next_err();
+            quote! { .map_err(|kind| Error::new(#es, kind))? }
+        } else {
+            quote! {}
+        }
+    }
 }
 
 fn process_request_inner_recursive(
@@ -222,12 +329,61 @@ fn process_request_inner_recursive(
     cx: &ApiServerCGContext<'_>,
     error_seq: &mut ErrorSeq,
     introspect: Option<TokenStream>,
+) -> TokenStream {
+    let mut ts = process_level_fn(
+        &level_name_chain,
+        api_bundle,
+        api_level,
+        index_chain,
+        crate_name,
+        cx,
+        error_seq,
+        introspect,
+    );
+
+    for item in &api_level.items {
+        if !matches!(item.kind, ApiItemKindOwned::Trait { .. }) {
+            continue;
+        };
+        let level = item.get_as_level(api_bundle).unwrap();
+        let mut cx = cx.clone();
+        cx.push_level(&item.ident);
+        let mut index_chain = index_chain;
+        if matches!(item.multiplicity, Multiplicity::Array { .. }) {
+            index_chain.increment_length();
+        }
+        let level_name_chain = format!("{}_{}", level_name_chain, item.ident);
+        ts.extend(process_request_inner_recursive(
+            level_name_chain,
+            api_bundle,
+            level,
+            index_chain,
+            level.crate_name(api_bundle).unwrap(),
+            &cx,
+            error_seq,
+            None,
+        ));
+    }
+    ts
+}
+
+/// Generates `process_<level_name_chain>` method, that dispatches requests to one API level.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn process_level_fn(
+    level_name_chain: &str,
+    api_bundle: &ApiBundleOwned,
+    api_level: &ApiLevelOwned,
+    index_chain: IndexChain,
+    crate_name: &str,
+    cx: &ApiServerCGContext<'_>,
+    error_seq: &mut ErrorSeq,
+    introspect: Option<TokenStream>,
 ) -> TokenStream {
     let maybe_async = maybe_quote(cx.use_async, quote! { async });
     let level_matchers = level_matchers(
         api_bundle,
         api_level,
-        &level_name_chain,
+        level_name_chain,
         index_chain,
         crate_name,
         cx,
@@ -240,7 +396,8 @@ fn process_request_inner_recursive(
         Span::call_site(),
     );
     let es = error_seq.next_err();
-    let mut ts = quote! {
+    quote! {
+        #[allow(clippy::too_many_arguments)]
         #maybe_async fn #process_fn_name<'a>(
             &mut self,
             #maybe_index_chain_def
@@ -261,32 +418,7 @@ fn process_request_inner_recursive(
                 }
             }
         }
-    };
-
-    for item in &api_level.items {
-        if !matches!(item.kind, ApiItemKindOwned::Trait { .. }) {
-            continue;
-        };
-        let level = item.get_as_level(api_bundle).unwrap();
-        let mut cx = cx.clone();
-        cx.push_suffix(&item.ident);
-        let mut index_chain = index_chain;
-        if matches!(item.multiplicity, Multiplicity::Array { .. }) {
-            index_chain.increment_length();
-        }
-        let level_name_chain = format!("{}_{}", level_name_chain, item.ident);
-        ts.extend(process_request_inner_recursive(
-            level_name_chain,
-            api_bundle,
-            level,
-            index_chain,
-            level.crate_name(api_bundle).unwrap(),
-            &cx,
-            error_seq,
-            None,
-        ));
     }
-    ts
 }
 
 fn mod_ident(level: &ApiLevelOwned, crate_name: &str) -> Ident {
@@ -343,14 +475,18 @@ fn level_matchers(
                 error_seq,
             );
 
-            let valid_indices = Ident::new(
-                format!(
-                    "valid_indices_{level_name_chain}_{}",
-                    item.ident.to_case(Case::Snake)
+            let valid_indices = if cx.handler_traits {
+                valid_indices_ident(item)
+            } else {
+                Ident::new(
+                    format!(
+                        "valid_indices_{level_name_chain}_{}",
+                        item.ident.to_case(Case::Snake)
+                    )
+                        .as_str(),
+                    Span::call_site(),
                 )
-                    .as_str(),
-                Span::call_site(),
-            );
+            };
             let maybe_index_chain_arg = index_chain.fun_argument_call();
             let es = error_seq.next_err();
             let validate_index = quote! {
@@ -441,8 +577,15 @@ fn level_matcher(
             );
             let maybe_await = maybe_quote(cx.use_async, quote! { .await });
             let maybe_index_chain_arg = index_chain.fun_argument_call();
+            // nested level is implemented by the type returned from an accessor of the current level
+            let level = if cx.handler_traits {
+                let accessor = Ident::new(&api_item.ident, Span::call_site());
+                quote! { self.#accessor() }
+            } else {
+                quote! { self }
+            };
             quote! {
-                Ok(self.#process_fn_name(#maybe_index_chain_arg path, path_iter, request, scratch_event, scratch_args, msg_tx)#maybe_await?)
+                Ok(#level.#process_fn_name(#maybe_index_chain_arg path, path_iter, request, scratch_args, scratch_event, msg_tx)#maybe_await?)
             }
         }
     }
@@ -478,18 +621,20 @@ fn handle_method(
     };
 
     let ser_output_or_unit = ser_method_output(return_type, quote! { request.seq }, error_seq);
+    let method_model = cx.method_model(&ident.to_string());
     let ident = add_prefix(cx.ident_prefix.as_ref(), ident);
-    let call_and_handle_deferred = match cx.method_model.pick(ident.to_string().as_str()).unwrap() {
+    let handler_err = cx.handler_err(error_seq);
+    let call_and_handle_deferred = match method_model {
         MethodModelKind::Immediate => quote! {
-            #maybe_let_output self.#ident(msg_tx, #maybe_index_chain_arg #args_list)#maybe_await;
+            #maybe_let_output self.#ident(msg_tx, #maybe_index_chain_arg #args_list)#maybe_await #handler_err;
             if request.seq != 0 {
                 #ser_output_or_unit
             } else {
                 Ok(&[])
             }
         },
-        MethodModelKind::Deferred => quote! {
-            let output = match self.#ident(msg_tx, #maybe_index_chain_arg request.seq, #args_list)#maybe_await {
+        MethodModelKind::Deferred | MethodModelKind::DeferredCancellable => quote! {
+            let output = match self.#ident(msg_tx, #maybe_index_chain_arg request.seq, #args_list)#maybe_await #handler_err {
                 Some(o) => o,
                 None => {
                     return Ok(&[])
@@ -498,6 +643,21 @@ fn handle_method(
             #ser_output_or_unit
         },
     };
+    // nothing to abort, unless a method is deferred and user handles cancellation
+    let handle_cancel = if method_model == MethodModelKind::DeferredCancellable {
+        let cancel_ident = Ident::new(format!("{ident}_cancel").as_str(), ident.span());
+        let handler_err = cx.handler_err(error_seq);
+        quote! {
+            RequestKind::Cancel { seq } => {
+                self.#cancel_ident(msg_tx, #maybe_index_chain_arg *seq)#maybe_await #handler_err;
+                Ok(&[])
+            }
+        }
+    } else {
+        quote! {
+            RequestKind::Cancel { .. } => Ok(&[]),
+        }
+    };
 
     let es = error_seq.next_err();
     quote! {
@@ -506,6 +666,7 @@ fn handle_method(
                 #args_des
                 #call_and_handle_deferred
             }
+            #handle_cancel
             _ => {
                 Err(Error::not_supported(#es))
             }
@@ -537,7 +698,16 @@ fn handle_property(
     //     &enforce_ty,
     //     &mut des,
     // );
-    let property_model_pick = cx.property_model.pick(ident.to_string().as_str()).unwrap();
+    // handler traits have no fields to store values in, ww_codegen rejects property_model together with handler_traits
+    let property_model_pick = if cx.handler_traits {
+        PropertyModelKind::GetSet
+    } else {
+        cx.property_model
+            .pick(ident.to_string().as_str())
+            .unwrap_or_else(|| {
+                panic!("property_model has no match for {ident}, add a default, e.g. _=get_set")
+            })
+    };
     let prefixed_ident = add_prefix(cx.ident_prefix.as_ref(), ident);
     let maybe_let_user_result = maybe_quote(user_result_ty.is_some(), quote! { let user_result = });
     let (es0, es1, es2, es3) = (
@@ -567,8 +737,9 @@ fn handle_property(
         format!("set_{}", prefixed_ident).as_str(),
         Span::call_site(),
     );
+    let handler_err = cx.handler_err(error_seq);
     let set_property = quote! {
-        #maybe_let_user_result self.#set_property(#maybe_index_chain_arg value)#maybe_await;
+        #maybe_let_user_result self.#set_property(#maybe_index_chain_arg value)#maybe_await #handler_err;
         #maybe_ret_user_result
     };
     let get_and_ser_property = match property_model_pick {
@@ -577,9 +748,10 @@ fn handle_property(
                 format!("get_{}", prefixed_ident).as_str(),
                 Span::call_site(),
             );
+            let handler_err = cx.handler_err(error_seq);
             let es = error_seq.next_err();
             quote! {
-                let value: #enforce_ty = self.#get_property(#maybe_index_chain_arg)#maybe_await;
+                let value: #enforce_ty = self.#get_property(#maybe_index_chain_arg)#maybe_await #handler_err;
                 let mut wr = BufWriter::new(scratch_args);
                 value.ser_shrink_wrap(&mut wr).map_err(|_| Error::new(#es, ErrorKind::ResponseSerFailed))?;
             }
@@ -661,10 +833,11 @@ fn handle_stream(
     let maybe_await = maybe_quote(cx.use_async, quote! { .await });
 
     let sideband_fn = Ident::new(format!("{}_sideband", ident).as_str(), ident.span());
+    let handler_err = cx.handler_err(err_seq);
     let es = err_seq.next_err();
     let handle_sideband_cmd = quote! {
         // user fn returns Option<StreamSidebandEvent>
-        let r = self.#sideband_fn(msg_tx, #maybe_index_chain_call sideband_cmd)#maybe_await;
+        let r = self.#sideband_fn(msg_tx, #maybe_index_chain_call sideband_cmd)#maybe_await #handler_err;
         match r {
             Some(sideband_event) => {
                 let event = Event {
@@ -693,41 +866,26 @@ fn handle_stream(
         }
     } else {
         // sink (device in)
-        let mut other_des = || {
-            let es = err_seq.next_err();
-            let enforce_ty = ty_def(api_bundle, ty, false, true).unwrap();
-            let ts = quote! {
-                let mut rd = BufReader::new(data);
-                let value = #enforce_ty::des_shrink_wrap(&mut rd).map_err(|_e| Error::new(#es, ErrorKind::ArgsDesFailed))?;
-            };
-            (ts, quote! { value })
-        };
         let write = Ident::new(format!("{}_write", ident).as_str(), ident.span());
-        let (des_data, arg) = match ty {
-            TypeOwned::Tuple(elements) => {
-                if elements.is_empty() {
-                    (quote! {}, quote! { () })
-                } else {
-                    other_des()
-                }
-            }
-            TypeOwned::Vec(inner) => {
-                if matches!(
-                    inner.as_ref(),
-                    TypeOwned::NumericAny(NumericAnyTypeOwned::Base(NumericBaseType::U8))
-                ) {
-                    (quote! {}, quote! { data })
-                } else {
-                    other_des()
-                }
+        let handler_err = cx.handler_err(err_seq);
+        let (des_data, arg) = match sink_arg(ty) {
+            SinkArg::Unit => (quote! {}, quote! { () }),
+            SinkArg::Bytes => (quote! {}, quote! { data }),
+            SinkArg::Value => {
+                let es = err_seq.next_err();
+                let enforce_ty = ty_def(api_bundle, ty, false, true).unwrap();
+                let ts = quote! {
+                    let mut rd = BufReader::new(data);
+                    let value = #enforce_ty::des_shrink_wrap(&mut rd).map_err(|_e| Error::new(#es, ErrorKind::ArgsDesFailed))?;
+                };
+                (ts, quote! { value })
             }
-            _ => other_des(),
         };
         // let maybe_comma = maybe_quote(!index_chain.is_empty(), quote! { , });
         quote! {
             RequestKind::Write { data } => {
                 #des_data
-                self.#write(#maybe_index_chain_call #arg)#maybe_await;
+                self.#write(#maybe_index_chain_call #arg)#maybe_await #handler_err;
                 Ok(&[]) // do not send acknowledgements on stream writes
             }
             RequestKind::StreamSideband { sideband_cmd } => {
@@ -745,6 +903,39 @@ fn handle_stream(
     }
 }
 
+/// How data written into a sink is passed to the user handler.
+pub(crate) enum SinkArg {
+    /// `()`
+    Unit,
+    /// Request bytes as is, for `[u8]` sinks
+    Bytes,
+    /// Deserialized value
+    Value,
+}
+
+pub(crate) fn sink_arg(ty: &TypeOwned) -> SinkArg {
+    match ty {
+        TypeOwned::Tuple(elements) if elements.is_empty() => SinkArg::Unit,
+        TypeOwned::Vec(inner)
+            if matches!(
+                inner.as_ref(),
+                TypeOwned::NumericAny(NumericAnyTypeOwned::Base(NumericBaseType::U8))
+            ) =>
+        {
+            SinkArg::Bytes
+        }
+        _ => SinkArg::Value,
+    }
+}
+
+/// `valid_indices_<ident>` handler of an array resource in generated handler traits.
+pub(crate) fn valid_indices_ident(item: &ApiItemOwned) -> Ident {
+    Ident::new(
+        format!("valid_indices_{}", item.ident.to_case(Case::Snake)).as_str(),
+        Span::call_site(),
+    )
+}
+
 fn args_structs_recursive(
     api_bundle: &ApiBundleOwned,
     api_level: &ApiLevelOwned,
@@ -772,6 +963,142 @@ fn args_structs_recursive(
     }
 }
 
+/// Worst-case sizes of each method's request, return value and return value event, and the maximum over all methods.
+/// Firmware can use them to statically size receive buffers, `scratch_args` and `scratch_event`.
+fn max_len_consts(
+    api_bundle: &ApiBundleOwned,
+    api_level: &ApiLevelOwned,
+    no_alloc: bool,
+) -> TokenStream {
+    let mut ts = TokenStream::new();
+    let mut per_method = vec![];
+    max_len_consts_recursive(
+        api_bundle,
+        api_level,
+        None,
+        0,
+        no_alloc,
+        &mut ts,
+        &mut per_method,
+    );
+    let max_over_methods = |suffix: &str| {
+        let consts = per_method
+            .iter()
+            .map(|prefix| Ident::new(format!("{prefix}_{suffix}").as_str(), Span::call_site()));
+        quote! {{
+            let len = Some(0);
+            #(let len = wire_weaver::shrink_wrap::max_len::max(len, Self::#consts);)*
+            len
+        }}
+    };
+    let max_request = max_over_methods("MAX_REQUEST_LEN");
+    let max_return = max_over_methods("MAX_RETURN_LEN");
+    let max_event = max_over_methods("MAX_EVENT_LEN");
+    ts.extend(quote! {
+        /// Worst-case size of a serialized method call request over all methods, None if any of them is unbounded.
+        pub const MAX_CALL_REQUEST_LEN: Option<usize> = #max_request;
+        /// Worst-case size of a serialized return value over all methods (`scratch_args` size), None if any of them is unbounded.
+        pub const MAX_CALL_RETURN_LEN: Option<usize> = #max_return;
+        /// Worst-case size of a serialized return value event over all methods (`scratch_event` size), None if any of them is unbounded.
+        pub const MAX_CALL_EVENT_LEN: Option<usize> = #max_event;
+    });
+    ts
+}
+
+fn max_len_consts_recursive(
+    api_bundle: &ApiBundleOwned,
+    api_level: &ApiLevelOwned,
+    prefix: Option<&str>,
+    path_nibbles: usize,
+    no_alloc: bool,
+    ts: &mut TokenStream,
+    per_method: &mut Vec<String>,
+) {
+    let mod_name = util::mod_name(api_level, api_bundle);
+    for item in &api_level.items {
+        let mut path_nibbles = path_nibbles + item.id.len_nibbles();
+        if matches!(item.multiplicity, Multiplicity::Array { .. }) {
+            path_nibbles += shrink_wrap::max_len::UNIB32_MAX_NIBBLES;
+        }
+        let const_prefix = match prefix {
+            Some(prefix) => format!("{prefix}_{}", item.ident),
+            None => item.ident.clone(),
+        };
+        match &item.kind {
+            ApiItemKindOwned::Method { args, return_ty } => {
+                let const_prefix = const_prefix.to_case(Case::Constant);
+                let args_len = if args.is_empty() {
+                    quote! { Some(0) }
+                } else {
+                    let args_struct_ident = Ident::new(
+                        format!("{}_args", item.ident)
+                            .to_case(Case::Pascal)
+                            .as_str(),
+                        Span::call_site(),
+                    );
+                    let is_lifetime = args
+                        .iter()
+                        .any(|arg| arg.ty.is_lifetime(api_bundle).unwrap());
+                    let maybe_lifetime = maybe_quote(is_lifetime && no_alloc, quote! { <'_> });
+                    quote! { <#mod_name::#args_struct_ident #maybe_lifetime as SerializeShrinkWrap>::MAX_SERIALIZED_LEN }
+                };
+                let (return_len, event_data_len) = match return_ty {
+                    Some(ty) => {
+                        let ty = ty_def(api_bundle, ty, !no_alloc, true).unwrap();
+                        let return_len =
+                            quote! { <#ty as SerializeShrinkWrap>::MAX_SERIALIZED_LEN };
+                        (return_len.clone(), return_len)
+                    }
+                    // see ser_unit_return_event
+                    None => (quote! { Some(0) }, quote! { Some(1) }),
+                };
+                let request_const = Ident::new(
+                    &format!("{const_prefix}_MAX_REQUEST_LEN"),
+                    Span::call_site(),
+                );
+                let return_const =
+                    Ident::new(&format!("{const_prefix}_MAX_RETURN_LEN"), Span::call_site());
+                let event_const =
+                    Ident::new(&format!("{const_prefix}_MAX_EVENT_LEN"), Span::call_site());
+                let request_doc = format!(
+                    " Worst-case size of a serialized `{}` call request, None if unbounded.",
+                    item.ident
+                );
+                let return_doc = format!(
+                    " Worst-case size of a serialized `{}` return value, None if unbounded.",
+                    item.ident
+                );
+                let event_doc = format!(
+                    " Worst-case size of a serialized `{}` return value event, None if unbounded.",
+                    item.ident
+                );
+                ts.extend(quote! {
+                    #[doc = #request_doc]
+                    pub const #request_const: Option<usize> = ww_client_server::util::call_request_max_len(#path_nibbles, #args_len);
+                    #[doc = #return_doc]
+                    pub const #return_const: Option<usize> = #return_len;
+                    #[doc = #event_doc]
+                    pub const #event_const: Option<usize> = ww_client_server::util::value_event_max_len(#event_data_len);
+                });
+                per_method.push(const_prefix);
+            }
+            ApiItemKindOwned::Trait { .. } => {
+                let level = item.get_as_level(api_bundle).unwrap();
+                max_len_consts_recursive(
+                    api_bundle,
+                    level,
+                    Some(&const_prefix),
+                    path_nibbles,
+                    no_alloc,
+                    ts,
+                    per_method,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
 fn ser_method_output(
     return_type: &Option<TypeOwned>,
     seq_path: TokenStream,
@@ -797,12 +1124,12 @@ fn ser_method_output(
                 })
             };
             event.ser_shrink_wrap(&mut event_wr).map_err(|_| Error::response_ser_failed(#es1))?;
-            Ok(event_wr.finish_and_take().map_err(|_| Error::response_ser_failed(#es2))?)
+            event_wr.finish_and_take().map_err(|_| Error::response_ser_failed(#es2))
         }
     } else {
         let es = errors_seq.next_err();
         quote! {
-            Ok(ser_unit_return_event(scratch_event, request.seq).map_err(|_| Error::response_ser_failed(#es))?)
+            ser_unit_return_event(scratch_event, #seq_path).map_err(|_| Error::response_ser_failed(#es))
         }
     }
 }
@@ -855,7 +1182,7 @@ fn deferred_method_return_ser_methods(
         let ApiItemKindOwned::Method { return_ty, .. } = &item.kind else {
             continue;
         };
-        if method_model.pick(&item.ident).unwrap() != MethodModelKind::Deferred {
+        if method_model.pick(&item.ident).unwrap() == MethodModelKind::Immediate {
             continue;
         }
         let fn_name = Ident::new(
@@ -871,7 +1198,7 @@ fn deferred_method_return_ser_methods(
             None => quote! {},
         };
         ts.extend(quote! {
-            pub fn #fn_name<'i>(scratch_args: &'i mut [u8], scratch_event: &'i mut [u8], seq: u16 #maybe_output) -> Result<#byte_return_ty, Error> {
+            pub fn #fn_name<'i>(scratch_args: &'i mut [u8], scratch_event: &'i mut [u8], seq: u16 #maybe_output) -> Result<#byte_return_ty, Error<'static>> {
                 #ser_output_or_unit
             }
         });
@@ -0,0 +1,1757 @@
+// WireWeaver C++17 runtime, generated API types, codecs and clients follow below.
+// Encoding rules mirror shrink_wrap::BufWriter, shrink_wrap::BufReader and ww_self dynamic serializer.
+// Define WW_NO_WEBSOCKET before including this file to leave out the native WebSocket transport.
+
+#ifndef WW_CPP_RUNTIME
+#define WW_CPP_RUNTIME 1
+
+#include <algorithm>
+#include <array>
+#include <chrono>
+#include <condition_variable>
+#include <cstdint>
+#include <cstring>
+#include <functional>
+#include <future>
+#include <initializer_list>
+#include <limits>
+#include <map>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <stdexcept>
+#include <string>
+#include <thread>
+#include <tuple>
+#include <utility>
+#include <variant>
+#include <vector>
+
+#ifndef WW_NO_WEBSOCKET
+#include <random>
+#if defined(_WIN32)
+#include <winsock2.h>
+#include <ws2tcpip.h>
+#else
+#include <netdb.h>
+#include <netinet/in.h>
+#include <netinet/tcp.h>
+#include <sys/socket.h>
+#include <sys/time.h>
+#include <unistd.h>
+#include <cerrno>
+#endif
+#endif
+
+namespace ww {
+
+/// Encoding, decoding or protocol error.
+class Error : public std::runtime_error {
+public:
+    using std::runtime_error::runtime_error;
+};
+
+constexpr uint32_t UNIB32_MORE = 0b1000;
+constexpr uint32_t MAX_U16 = 0xffff;
+constexpr uint32_t MAX_DEPTH = 256;
+
+inline uint32_t unib32_len_nibbles(uint32_t value) {
+    uint32_t len = 1;
+    value >>= 3;
+    while (value != 0) {
+        len += 1;
+        value >>= 3;
+    }
+    return len;
+}
+
+[[noreturn]] inline void unsupported(const char* name) {
+    throw Error(std::string("unsupported type: ") + name);
+}
+
+inline void check_max_len(const char* kind, size_t len, size_t max) {
+    if (len > max) {
+        throw Error(std::string(kind) + " of length " + std::to_string(len) + " exceeds maximum length of " +
+                    std::to_string(max));
+    }
+}
+
+inline bool is_utf8(const uint8_t* bytes, size_t len) {
+    size_t i = 0;
+    while (i < len) {
+        uint8_t b = bytes[i];
+        size_t extra;
+        uint32_t cp;
+        if (b < 0x80) {
+            i += 1;
+            continue;
+        } else if ((b & 0xe0) == 0xc0) {
+            extra = 1;
+            cp = b & 0x1f;
+        } else if ((b & 0xf0) == 0xe0) {
+            extra = 2;
+            cp = b & 0x0f;
+        } else if ((b & 0xf8) == 0xf0) {
+            extra = 3;
+            cp = b & 0x07;
+        } else {
+            return false;
+        }
+        if (len - i <= extra) {
+            return false;
+        }
+        for (size_t j = 1; j <= extra; j++) {
+            uint8_t c = bytes[i + j];
+            if ((c & 0xc0) != 0x80) {
+                return false;
+            }
+            cp = (cp << 6) | (c & 0x3f);
+        }
+        // overlong encodings, surrogates and out of range code points
+        static const uint32_t min_cp[4] = {0, 0x80, 0x800, 0x10000};
+        if (cp < min_cp[extra] || (cp >= 0xd800 && cp <= 0xdfff) || cp > 0x10ffff) {
+            return false;
+        }
+        i += extra + 1;
+    }
+    return true;
+}
+
+// Value types -------------------------------------------------------------------------------------------------------
+
+struct Unsupported {};
+
+inline bool operator==(const Unsupported&, const Unsupported&) { return true; }
+inline bool operator!=(const Unsupported&, const Unsupported&) { return false; }
+
+struct U128 {
+    uint64_t low = 0;
+    uint64_t high = 0;
+};
+
+inline bool operator==(const U128& a, const U128& b) { return a.low == b.low && a.high == b.high; }
+inline bool operator!=(const U128& a, const U128& b) { return !(a == b); }
+
+struct I128 {
+    uint64_t low = 0;
+    int64_t high = 0;
+};
+
+inline bool operator==(const I128& a, const I128& b) { return a.low == b.low && a.high == b.high; }
+inline bool operator!=(const I128& a, const I128& b) { return !(a == b); }
+
+template <typename T>
+struct Range {
+    T start{};
+    T end{};
+};
+
+template <typename T>
+bool operator==(const Range<T>& a, const Range<T>& b) { return a.start == b.start && a.end == b.end; }
+template <typename T>
+bool operator!=(const Range<T>& a, const Range<T>& b) { return !(a == b); }
+
+template <typename T>
+struct RangeInclusive {
+    T start{};
+    T end{};
+};
+
+template <typename T>
+bool operator==(const RangeInclusive<T>& a, const RangeInclusive<T>& b) { return a.start == b.start && a.end == b.end; }
+template <typename T>
+bool operator!=(const RangeInclusive<T>& a, const RangeInclusive<T>& b) { return !(a == b); }
+
+template <typename T>
+struct Ok {
+    T value{};
+};
+
+template <typename E>
+struct Err {
+    E value{};
+};
+
+/// Result<T, E> of the Rust side, holds either Ok<T> or Err<E>.
+template <typename T, typename E>
+class Result {
+public:
+    Result() : inner_(std::in_place_index<0>) {}
+    Result(Ok<T> ok) : inner_(std::in_place_index<0>, std::move(ok)) {}
+    Result(Err<E> err) : inner_(std::in_place_index<1>, std::move(err)) {}
+
+    bool is_ok() const { return inner_.index() == 0; }
+    bool is_err() const { return inner_.index() == 1; }
+
+    /// Throws std::bad_variant_access if this is an error.
+    T& ok() { return std::get<0>(inner_).value; }
+    const T& ok() const { return std::get<0>(inner_).value; }
+
+    /// Throws std::bad_variant_access if this is not an error.
+    E& err() { return std::get<1>(inner_).value; }
+    const E& err() const { return std::get<1>(inner_).value; }
+
+private:
+    std::variant<Ok<T>, Err<E>> inner_;
+};
+
+template <typename T, typename E>
+bool operator==(const Result<T, E>& a, const Result<T, E>& b) {
+    if (a.is_ok() != b.is_ok()) {
+        return false;
+    }
+    return a.is_ok() ? a.ok() == b.ok() : a.err() == b.err();
+}
+
+template <typename T, typename E>
+bool operator!=(const Result<T, E>& a, const Result<T, E>& b) { return !(a == b); }
+
+/// Box<T> of the Rust side, owns a heap allocated value and copies it deeply. Default constructed Box is empty and
+/// cannot be serialized, so that recursive types can be default constructed.
+template <typename T>
+class Box {
+public:
+    Box() = default;
+    Box(T value) : ptr_(std::make_unique<T>(std::move(value))) {}
+    Box(const Box& other) : ptr_(other.ptr_ ? std::make_unique<T>(*other.ptr_) : nullptr) {}
+    Box(Box&&) noexcept = default;
+
+    Box& operator=(const Box& other) {
+        if (this != &other) {
+            ptr_ = other.ptr_ ? std::make_unique<T>(*other.ptr_) : nullptr;
+        }
+        return *this;
+    }
+
+    Box& operator=(Box&&) noexcept = default;
+
+    bool has_value() const { return ptr_ != nullptr; }
+
+    T& emplace() {
+        ptr_ = std::make_unique<T>();
+        return *ptr_;
+    }
+
+    T& operator*() const {
+        if (!ptr_) {
+            throw Error("empty Box");
+        }
+        return *ptr_;
+    }
+
+    T* operator->() const { return &**this; }
+
+private:
+    std::unique_ptr<T> ptr_;
+};
+
+template <typename T>
+bool operator==(const Box<T>& a, const Box<T>& b) {
+    if (!a.has_value() || !b.has_value()) {
+        return a.has_value() == b.has_value();
+    }
+    return *a == *b;
+}
+
+template <typename T>
+bool operator!=(const Box<T>& a, const Box<T>& b) { return !(a == b); }
+
+// Writer ------------------------------------------------------------------------------------------------------------
+
+class BufWriter {
+public:
+    void write_bool(bool value) {
+        reserve(1);
+        if (value) {
+            buf_[byte_idx_] |= static_cast<uint8_t>(1u << bit_idx_);
+        } else {
+            buf_[byte_idx_] &= static_cast<uint8_t>(~(1u << bit_idx_));
+        }
+        if (bit_idx_ == 0) {
+            bit_idx_ = 7;
+            byte_idx_ += 1;
+        } else {
+            bit_idx_ -= 1;
+        }
+    }
+
+    void write_nib(uint8_t value) {
+        if (value > 15) {
+            throw Error(std::to_string(value) + " does not fit into a nibble");
+        }
+        align_nibble();
+        reserve(1);
+        if (bit_idx_ == 7) {
+            buf_[byte_idx_] = static_cast<uint8_t>((buf_[byte_idx_] & 0x0f) | (value << 4));
+            bit_idx_ = 3;
+        } else {
+            buf_[byte_idx_] = static_cast<uint8_t>((buf_[byte_idx_] & 0xf0) | value);
+            bit_idx_ = 7;
+            byte_idx_ += 1;
+        }
+    }
+
+    // Write up to 64 bits without alignment, MSB first
+    void write_un(uint32_t bits, uint64_t value) {
+        for (uint32_t i = bits; i > 0; i--) {
+            write_bool(((value >> (i - 1)) & 1) != 0);
+        }
+    }
+
+    void write_ub(uint32_t bits, uint64_t value) {
+        if (bits < 64 && value >> bits != 0) {
+            throw Error(std::to_string(value) + " is out of u" + std::to_string(bits) + " range");
+        }
+        write_un(bits, value);
+    }
+
+    void write_ib(uint32_t bits, int64_t value) {
+        if (bits < 64) {
+            int64_t max = (int64_t(1) << (bits - 1)) - 1;
+            if (value > max || value < -max - 1) {
+                throw Error(std::to_string(value) + " is out of i" + std::to_string(bits) + " range");
+            }
+        }
+        write_un(bits, static_cast<uint64_t>(value));
+    }
+
+    void write_bytes(const uint8_t* bytes, size_t len) {
+        align_byte();
+        reserve(len);
+        if (len > 0) {
+            std::memcpy(buf_.data() + byte_idx_, bytes, len);
+        }
+        byte_idx_ += len;
+    }
+
+    void write_u8(uint8_t value) { write_le(value, 1); }
+    void write_u16(uint16_t value) { write_le(value, 2); }
+    void write_u32(uint32_t value) { write_le(value, 4); }
+    void write_u64(uint64_t value) { write_le(value, 8); }
+
+    void write_u128(const U128& value) {
+        write_le(value.low, 8);
+        write_le(value.high, 8);
+    }
+
+    void write_i128(const I128& value) {
+        write_le(value.low, 8);
+        write_le(static_cast<uint64_t>(value.high), 8);
+    }
+
+    void write_f32(float value) {
+        uint32_t bits;
+        std::memcpy(&bits, &value, 4);
+        write_le(bits, 4);
+    }
+
+    void write_f64(double value) {
+        uint64_t bits;
+        std::memcpy(&bits, &value, 8);
+        write_le(bits, 8);
+    }
+
+    void write_unib32(uint32_t value) {
+        uint32_t left = unib32_len_nibbles(value);
+        while (left > 0) {
+            uint8_t nib = value & 0b111;
+            write_nib(static_cast<uint8_t>(left > 1 ? nib | UNIB32_MORE : nib));
+            value >>= 3;
+            left -= 1;
+        }
+    }
+
+    // String bytes without size, same as BufWriter::write_raw_str
+    void write_raw_str(const std::string& value) {
+        const auto* bytes = reinterpret_cast<const uint8_t*>(value.data());
+        if (!is_utf8(bytes, value.size())) {
+            throw Error("malformed UTF-8");
+        }
+        write_bytes(bytes, value.size());
+    }
+
+    // Vec<u8> with its length written as reversed UNib32
+    void write_byte_vec(const std::vector<uint8_t>& bytes) {
+        write_u16_rev(len_u16(bytes.size()));
+        write_bytes(bytes.data(), bytes.size());
+    }
+
+    // Returns a slot that can be updated later with update_u16_rev
+    size_t write_u16_rev(uint16_t value) {
+        rev_.push_back(value);
+        return rev_.size() - 1;
+    }
+
+    void update_u16_rev(size_t slot, uint16_t value) { rev_[slot] = value; }
+
+    // Encode all the values written with write_u16_rev starting from the provided slot, most recent first
+    void encode_nib16_rev(size_t from_slot) {
+        if (rev_.size() <= from_slot) {
+            return;
+        }
+        uint32_t total_nibbles = 0;
+        for (size_t i = from_slot; i < rev_.size(); i++) {
+            total_nibbles += unib32_len_nibbles(rev_[i]);
+        }
+        align_nibble();
+        if (bit_idx_ != 7) {
+            total_nibbles += 1;
+        }
+        if (total_nibbles % 2 != 0) {
+            write_nib(0);
+        }
+        for (size_t i = rev_.size(); i > from_slot; i--) {
+            write_unib32_rev(rev_[i - 1]);
+        }
+        rev_.resize(from_slot);
+    }
+
+    // Same as BufWriter::write for objects with ElementSize::Unsized: size in bytes is put in front of the object
+    template <typename F>
+    void write_unsized(F&& write_object) {
+        align_byte();
+        size_t slot = write_u16_rev(0);
+        size_t start = byte_idx_;
+        write_object();
+        encode_nib16_rev(slot + 1);
+        align_byte();
+        size_t size = byte_idx_ - start;
+        if (size > MAX_U16) {
+            throw Error("item too long");
+        }
+        update_u16_rev(slot, static_cast<uint16_t>(size));
+    }
+
+    void align_nibble() {
+        if (bit_idx_ == 7 || bit_idx_ == 3) {
+            return;
+        }
+        if (bit_idx_ > 3) {
+            bit_idx_ = 3;
+        } else {
+            bit_idx_ = 7;
+            byte_idx_ += 1;
+        }
+    }
+
+    void align_byte() {
+        if (bit_idx_ == 7) {
+            return;
+        }
+        bit_idx_ = 7;
+        byte_idx_ += 1;
+    }
+
+    std::vector<uint8_t> finish() {
+        if (!rev_.empty()) {
+            encode_nib16_rev(0);
+        } else {
+            align_byte();
+        }
+        return std::vector<uint8_t>(buf_.begin(), buf_.begin() + static_cast<std::ptrdiff_t>(byte_idx_));
+    }
+
+    static uint16_t len_u16(size_t len) {
+        if (len > MAX_U16) {
+            throw Error("length " + std::to_string(len) + " does not fit into u16");
+        }
+        return static_cast<uint16_t>(len);
+    }
+
+private:
+    void reserve(size_t bytes) {
+        size_t needed = byte_idx_ + bytes + 1;
+        if (needed > buf_.size()) {
+            buf_.resize(needed * 2, 0);
+        }
+    }
+
+    void write_le(uint64_t value, size_t len) {
+        uint8_t bytes[8];
+        for (size_t i = 0; i < len; i++) {
+            bytes[i] = static_cast<uint8_t>(value >> (8 * i));
+        }
+        write_bytes(bytes, len);
+    }
+
+    void write_unib32_rev(uint32_t value) {
+        uint32_t len = unib32_len_nibbles(value);
+        for (uint32_t i = 0; i < len; i++) {
+            uint8_t nib = value & 0b111;
+            write_nib(static_cast<uint8_t>(i == 0 ? nib : nib | UNIB32_MORE));
+            value >>= 3;
+        }
+    }
+
+    std::vector<uint8_t> buf_ = std::vector<uint8_t>(64, 0);
+    size_t byte_idx_ = 0;
+    // next bit to be written, 7 is MSB
+    uint32_t bit_idx_ = 7;
+    // values written with write_u16_rev, most recent last, encoded as reversed UNib32 when an unsized object or the
+    // whole buffer is finished
+    std::vector<uint16_t> rev_;
+};
+
+// Reader ------------------------------------------------------------------------------------------------------------
+
+/// Reads from a borrowed buffer, which must outlive the reader.
+class BufReader {
+public:
+    BufReader(const uint8_t* data, size_t len) : data_(data), end_nibble_(len * 2) {}
+    explicit BufReader(const std::vector<uint8_t>& bytes) : BufReader(bytes.data(), bytes.size()) {}
+
+    size_t bytes_left() const {
+        int64_t left = static_cast<int64_t>(end_nibble_ / 2) - static_cast<int64_t>(byte_idx_) - (bit_idx_ == 7 ? 0 : 1);
+        return left > 0 ? static_cast<size_t>(left) : 0;
+    }
+
+    bool read_bool() {
+        if (bits_left() < 1) {
+            throw Error("out of bounds reading bool");
+        }
+        bool value = (data_[byte_idx_] & (1u << bit_idx_)) != 0;
+        if (bit_idx_ == 0) {
+            bit_idx_ = 7;
+            byte_idx_ += 1;
+        } else {
+            bit_idx_ -= 1;
+        }
+        return value;
+    }
+
+    uint8_t read_nib() {
+        align_nibble();
+        if (bits_left() < 4) {
+            throw Error("out of bounds reading nibble");
+        }
+        if (bit_idx_ == 7) {
+            bit_idx_ = 3;
+            return data_[byte_idx_] >> 4;
+        }
+        uint8_t value = data_[byte_idx_] & 0x0f;
+        bit_idx_ = 7;
+        byte_idx_ += 1;
+        return value;
+    }
+
+    uint64_t read_un(uint32_t bits) {
+        uint64_t value = 0;
+        for (uint32_t i = 0; i < bits; i++) {
+            value = (value << 1) | (read_bool() ? 1 : 0);
+        }
+        return value;
+    }
+
+    const uint8_t* read_bytes(size_t len) {
+        align_byte();
+        if (bytes_left() < len) {
+            throw Error("out of bounds reading " + std::to_string(len) + " bytes");
+        }
+        const uint8_t* bytes = data_ + byte_idx_;
+        byte_idx_ += len;
+        return bytes;
+    }
+
+    uint8_t read_u8() { return static_cast<uint8_t>(read_le(1)); }
+    uint16_t read_u16() { return static_cast<uint16_t>(read_le(2)); }
+    uint32_t read_u32() { return static_cast<uint32_t>(read_le(4)); }
+    uint64_t read_u64() { return read_le(8); }
+
+    U128 read_u128() {
+        U128 value;
+        value.low = read_le(8);
+        value.high = read_le(8);
+        return value;
+    }
+
+    I128 read_i128() {
+        I128 value;
+        value.low = read_le(8);
+        value.high = static_cast<int64_t>(read_le(8));
+        return value;
+    }
+
+    float read_f32() {
+        uint32_t bits = read_u32();
+        float value;
+        std::memcpy(&value, &bits, 4);
+        return value;
+    }
+
+    double read_f64() {
+        uint64_t bits = read_u64();
+        double value;
+        std::memcpy(&value, &bits, 8);
+        return value;
+    }
+
+    uint32_t read_unib32() {
+        uint32_t value = 0;
+        for (uint32_t i = 0; i <= 10; i++) {
+            uint8_t nib = read_nib();
+            if (i == 10 && (nib & UNIB32_MORE) != 0) {
+                throw Error("malformed UNib32");
+            }
+            value |= static_cast<uint32_t>(nib & 0b111) << (3 * i);
+            if ((nib & UNIB32_MORE) == 0) {
+                break;
+            }
+        }
+        return value;
+    }
+
+    uint32_t read_unib32_rev() {
+        uint32_t value = 0;
+        for (uint32_t i = 0; i <= 10; i++) {
+            uint8_t nib = read_nib_rev();
+            if (i == 10 && (nib & UNIB32_MORE) != 0) {
+                throw Error("malformed UNib32");
+            }
+            value |= nib & 0b111;
+            if ((nib & UNIB32_MORE) == 0) {
+                break;
+            }
+            value <<= 3;
+        }
+        return value;
+    }
+
+    // Same as BufReader::split, returns a reader over the next len bytes
+    BufReader split(size_t len) {
+        BufReader rd(read_bytes(len), len);
+        rd.depth_ = depth_;
+        return rd;
+    }
+
+    std::string read_raw_str() {
+        size_t len = bytes_left();
+        const uint8_t* bytes = read_bytes(len);
+        if (!is_utf8(bytes, len)) {
+            throw Error("malformed UTF-8");
+        }
+        return std::string(reinterpret_cast<const char*>(bytes), len);
+    }
+
+    // Vec<u8> with its length written as reversed UNib32
+    std::vector<uint8_t> read_byte_vec() {
+        size_t len = read_unib32_rev();
+        const uint8_t* bytes = read_bytes(len);
+        return std::vector<uint8_t>(bytes, bytes + len);
+    }
+
+    void align_nibble() {
+        if (bit_idx_ == 7 || bit_idx_ == 3) {
+            return;
+        }
+        if (bit_idx_ > 3) {
+            bit_idx_ = 3;
+        } else {
+            bit_idx_ = 7;
+            byte_idx_ += 1;
+        }
+    }
+
+    void align_byte() {
+        if (bit_idx_ == 7) {
+            return;
+        }
+        bit_idx_ = 7;
+        byte_idx_ += 1;
+    }
+
+    // Nesting depth of recursive types is limited, see DepthGuard
+    void enter() {
+        if (depth_ >= MAX_DEPTH) {
+            throw Error("maximum nesting depth exceeded");
+        }
+        depth_ += 1;
+    }
+
+    void leave() { depth_ -= 1; }
+
+private:
+    int64_t bits_left() const {
+        return static_cast<int64_t>(end_nibble_) * 4 - static_cast<int64_t>(byte_idx_ * 8 + 7 - bit_idx_);
+    }
+
+    uint64_t read_le(size_t len) {
+        const uint8_t* bytes = read_bytes(len);
+        uint64_t value = 0;
+        for (size_t i = len; i > 0; i--) {
+            value = (value << 8) | bytes[i - 1];
+        }
+        return value;
+    }
+
+    uint8_t read_nib_rev() {
+        if (byte_idx_ >= (end_nibble_ + 1) / 2) {
+            throw Error("out of bounds reading reversed nibble");
+        }
+        end_nibble_ -= 1;
+        uint8_t byte = data_[end_nibble_ / 2];
+        return end_nibble_ % 2 == 1 ? byte & 0x0f : byte >> 4;
+    }
+
+    const uint8_t* data_;
+    size_t byte_idx_ = 0;
+    uint32_t bit_idx_ = 7;
+    // reversed values are read from the back, one nibble at a time
+    size_t end_nibble_;
+    uint32_t depth_ = 0;
+};
+
+class DepthGuard {
+public:
+    explicit DepthGuard(BufReader& rd) : rd_(rd) { rd_.enter(); }
+    ~DepthGuard() { rd_.leave(); }
+    DepthGuard(const DepthGuard&) = delete;
+    DepthGuard& operator=(const DepthGuard&) = delete;
+
+private:
+    BufReader& rd_;
+};
+
+inline int64_t sign_extend(uint64_t value, uint32_t bits) {
+    if (bits >= 64) {
+        return static_cast<int64_t>(value);
+    }
+    uint64_t sign = uint64_t(1) << (bits - 1);
+    return static_cast<int64_t>(value ^ sign) - static_cast<int64_t>(sign);
+}
+
+enum class Repr { Nib, Bits, UNib32, U8, U16, U32 };
+
+inline void write_discriminant(BufWriter& wr, Repr repr, uint32_t bits, uint32_t discriminant) {
+    switch (repr) {
+    case Repr::Nib:
+        wr.write_nib(static_cast<uint8_t>(discriminant));
+        break;
+    case Repr::Bits:
+        wr.write_ub(bits, discriminant);
+        break;
+    case Repr::UNib32:
+        wr.write_unib32(discriminant);
+        break;
+    case Repr::U8:
+        wr.write_u8(static_cast<uint8_t>(discriminant));
+        break;
+    case Repr::U16:
+        wr.write_u16(static_cast<uint16_t>(discriminant));
+        break;
+    case Repr::U32:
+        wr.write_u32(discriminant);
+        break;
+    }
+}
+
+inline uint32_t read_discriminant(BufReader& rd, Repr repr, uint32_t bits) {
+    switch (repr) {
+    case Repr::Nib:
+        return rd.read_nib();
+    case Repr::Bits:
+        return static_cast<uint32_t>(rd.read_un(bits));
+    case Repr::UNib32:
+        return rd.read_unib32();
+    case Repr::U8:
+        return rd.read_u8();
+    case Repr::U16:
+        return rd.read_u16();
+    case Repr::U32:
+        return rd.read_u32();
+    }
+    throw Error("unknown repr");
+}
+
+// ww_client_server --------------------------------------------------------------------------------------------------
+
+struct ShaperConfig {
+    enum class Kind { NoLimit, MaxBitrate, MaxRate };
+    Kind kind = Kind::NoLimit;
+    // bytes per second for MaxBitrate, events per second for MaxRate
+    uint32_t value = 0;
+};
+
+/// ww_client_server::RequestKind, fields not used by the kind are ignored.
+struct RequestKind {
+    enum class Kind { Call, Read, Write, Subscribe, Unsubscribe, ChangeRate, StreamSideband, Introspect, Cancel };
+    enum class Sideband { Open, Close, FrameSync, ChangeRate, SizeHint, User };
+
+    Kind kind = Kind::Read;
+    // Call arguments or Write data
+    std::vector<uint8_t> data;
+    // ChangeRate and StreamSideband ChangeRate
+    ShaperConfig config;
+    Sideband sideband = Sideband::Open;
+    // StreamSideband SizeHint size or User value, Cancel seq
+    uint32_t value = 0;
+
+    static RequestKind call(std::vector<uint8_t> args) {
+        RequestKind kind;
+        kind.kind = Kind::Call;
+        kind.data = std::move(args);
+        return kind;
+    }
+
+    static RequestKind read() { return RequestKind{}; }
+
+    static RequestKind write(std::vector<uint8_t> data) {
+        RequestKind kind;
+        kind.kind = Kind::Write;
+        kind.data = std::move(data);
+        return kind;
+    }
+
+    static RequestKind stream_sideband(Sideband sideband) {
+        RequestKind kind;
+        kind.kind = Kind::StreamSideband;
+        kind.sideband = sideband;
+        return kind;
+    }
+};
+
+enum class EventKind { ReturnValue, ReadValue, Written, StreamData, StreamSideband, Subscribed, Unsubscribed, RateChanged };
+
+enum class SidebandEvent { Opened, Closed, FrameSync, SizeHint, User };
+
+inline const char* event_kind_name(EventKind kind) {
+    switch (kind) {
+    case EventKind::ReturnValue:
+        return "ReturnValue";
+    case EventKind::ReadValue:
+        return "ReadValue";
+    case EventKind::Written:
+        return "Written";
+    case EventKind::StreamData:
+        return "StreamData";
+    case EventKind::StreamSideband:
+        return "StreamSideband";
+    case EventKind::Subscribed:
+        return "Subscribed";
+    case EventKind::Unsubscribed:
+        return "Unsubscribed";
+    case EventKind::RateChanged:
+        return "RateChanged";
+    }
+    return "Unknown";
+}
+
+inline const char* const ERROR_KINDS[] = {
+    "OperationNotSupported",
+    "BadPath",
+    "BadIndex",
+    "ExpectedArrayIndexGotNone",
+    "ArrayIndexDesFailed",
+    "ArgsDesFailed",
+    "PathDesFailed",
+    "PropertyDesFailed",
+    "ResponseSerFailed",
+    "OperationNotImplemented",
+    "ReadPropertyWithSeqZero",
+    "PathKindNotSupported",
+    "UserBytes",
+    "UserStr",
+};
+
+/// Error reported by the server.
+class RemoteError : public Error {
+public:
+    RemoteError(uint32_t err_seq, std::string kind, std::vector<uint8_t> user_bytes, std::optional<std::string> user_str)
+        : Error(kind + (user_str ? ": " + *user_str : std::string()) + " (err_seq: " + std::to_string(err_seq) + ")"),
+          err_seq(err_seq),
+          kind(std::move(kind)),
+          user_bytes(std::move(user_bytes)),
+          user_str(std::move(user_str)) {}
+
+    uint32_t err_seq;
+    std::string kind;
+    std::vector<uint8_t> user_bytes;
+    std::optional<std::string> user_str;
+};
+
+/// ww_client_server::Event, fields not used by the kind are empty.
+struct Event {
+    uint16_t seq = 0;
+    EventKind kind = EventKind::ReturnValue;
+    // ReturnValue, ReadValue and StreamData
+    std::vector<uint8_t> data;
+    // StreamData, StreamSideband, Subscribed and Unsubscribed
+    std::vector<uint32_t> path;
+    SidebandEvent sideband = SidebandEvent::Opened;
+    // StreamSideband SizeHint size or User value
+    uint32_t sideband_value = 0;
+    // set if the server responded with an error, other fields are not used then
+    std::optional<RemoteError> error;
+};
+
+namespace detail {
+
+inline void write_shaper_config(BufWriter& wr, const ShaperConfig& config) {
+    wr.write_unsized([&] {
+        switch (config.kind) {
+        case ShaperConfig::Kind::NoLimit:
+            wr.write_nib(0);
+            break;
+        case ShaperConfig::Kind::MaxBitrate:
+            wr.write_nib(1);
+            wr.write_u32(config.value);
+            break;
+        case ShaperConfig::Kind::MaxRate:
+            wr.write_nib(2);
+            wr.write_u32(config.value);
+            break;
+        }
+    });
+}
+
+inline std::vector<uint32_t> read_path(BufReader& rd) {
+    uint32_t len = rd.read_unib32_rev();
+    std::vector<uint32_t> path;
+    for (uint32_t i = 0; i < len; i++) {
+        path.push_back(rd.read_unib32());
+    }
+    return path;
+}
+
+inline void read_sideband_event(BufReader& rd, Event& event) {
+    uint8_t discriminant = rd.read_nib();
+    switch (discriminant) {
+    case 0:
+        event.sideband = SidebandEvent::Opened;
+        break;
+    case 1:
+        event.sideband = SidebandEvent::Closed;
+        break;
+    case 2:
+        event.sideband = SidebandEvent::FrameSync;
+        break;
+    case 3:
+        event.sideband = SidebandEvent::SizeHint;
+        event.sideband_value = rd.read_u32();
+        break;
+    case 4:
+        event.sideband = SidebandEvent::User;
+        event.sideband_value = rd.read_u32();
+        break;
+    default:
+        throw Error("unknown StreamSidebandEvent: " + std::to_string(discriminant));
+    }
+}
+
+inline void read_event_kind(BufReader& rd, Event& event) {
+    uint8_t discriminant = rd.read_nib();
+    switch (discriminant) {
+    case 0:
+        event.kind = EventKind::ReturnValue;
+        event.data = rd.read_byte_vec();
+        break;
+    case 1:
+        event.kind = EventKind::ReadValue;
+        event.data = rd.read_byte_vec();
+        break;
+    case 2:
+        event.kind = EventKind::Written;
+        break;
+    case 3:
+        event.kind = EventKind::StreamData;
+        event.path = read_path(rd);
+        event.data = rd.read_byte_vec();
+        break;
+    case 4:
+        event.kind = EventKind::StreamSideband;
+        event.path = read_path(rd);
+        read_sideband_event(rd, event);
+        break;
+    case 5:
+        event.kind = EventKind::Subscribed;
+        event.path = read_path(rd);
+        break;
+    case 6:
+        event.kind = EventKind::Unsubscribed;
+        event.path = read_path(rd);
+        break;
+    case 7:
+        event.kind = EventKind::RateChanged;
+        break;
+    default:
+        throw Error("unknown EventKind: " + std::to_string(discriminant));
+    }
+}
+
+inline RemoteError read_remote_error(BufReader& rd) {
+    uint32_t err_seq = rd.read_u32();
+    BufReader kind_rd = rd.split(rd.read_unib32_rev());
+    uint32_t discriminant = kind_rd.read_unib32();
+    constexpr uint32_t kinds_len = sizeof(ERROR_KINDS) / sizeof(ERROR_KINDS[0]);
+    std::string kind = discriminant < kinds_len ? ERROR_KINDS[discriminant]
+                                                : "Unknown(" + std::to_string(discriminant) + ")";
+    std::vector<uint8_t> user_bytes;
+    std::optional<std::string> user_str;
+    if (kind == "UserBytes") {
+        user_bytes = kind_rd.read_byte_vec();
+    } else if (kind == "UserStr") {
+        user_str = kind_rd.split(kind_rd.read_unib32_rev()).read_raw_str();
+    }
+    return RemoteError(err_seq, std::move(kind), std::move(user_bytes), std::move(user_str));
+}
+
+} // namespace detail
+
+// Serialize ww_client_server::Request with an absolute path
+inline std::vector<uint8_t> encode_request(uint16_t seq, const std::vector<uint32_t>& path, const RequestKind& kind) {
+    BufWriter wr;
+    wr.write_u16(seq);
+    wr.write_nib(0); // PathKind::Absolute
+    wr.write_u16_rev(BufWriter::len_u16(path.size()));
+    for (uint32_t id : path) {
+        wr.write_unib32(id);
+    }
+    switch (kind.kind) {
+    case RequestKind::Kind::Call:
+        wr.write_nib(0);
+        wr.write_byte_vec(kind.data);
+        break;
+    case RequestKind::Kind::Read:
+        wr.write_nib(2);
+        break;
+    case RequestKind::Kind::Write:
+        wr.write_nib(4);
+        wr.write_byte_vec(kind.data);
+        break;
+    case RequestKind::Kind::Subscribe:
+        wr.write_nib(6);
+        break;
+    case RequestKind::Kind::Unsubscribe:
+        wr.write_nib(7);
+        break;
+    case RequestKind::Kind::ChangeRate:
+        wr.write_nib(8);
+        detail::write_shaper_config(wr, kind.config);
+        break;
+    case RequestKind::Kind::StreamSideband:
+        wr.write_nib(9);
+        switch (kind.sideband) {
+        case RequestKind::Sideband::Open:
+            wr.write_nib(0);
+            break;
+        case RequestKind::Sideband::Close:
+            wr.write_nib(1);
+            break;
+        case RequestKind::Sideband::FrameSync:
+            wr.write_nib(2);
+            break;
+        case RequestKind::Sideband::ChangeRate:
+            wr.write_nib(3);
+            detail::write_shaper_config(wr, kind.config);
+            break;
+        case RequestKind::Sideband::SizeHint:
+            wr.write_nib(4);
+            wr.write_u32(kind.value);
+            break;
+        case RequestKind::Sideband::User:
+            wr.write_nib(5);
+            wr.write_u32(kind.value);
+            break;
+        }
+        break;
+    case RequestKind::Kind::Introspect:
+        wr.write_nib(10);
+        break;
+    case RequestKind::Kind::Cancel:
+        wr.write_nib(11);
+        wr.write_u16(static_cast<uint16_t>(kind.value));
+        break;
+    }
+    return wr.finish();
+}
+
+// Deserialize ww_client_server::Event
+inline Event decode_event(const uint8_t* bytes, size_t len) {
+    BufReader rd(bytes, len);
+    Event event;
+    event.seq = rd.read_u16();
+    if (rd.read_bool()) {
+        detail::read_event_kind(rd, event);
+    } else {
+        BufReader err_rd = rd.split(rd.read_unib32_rev());
+        event.error = detail::read_remote_error(err_rd);
+    }
+    return event;
+}
+
+// Client ------------------------------------------------------------------------------------------------------------
+
+/// Crate name and version of an API, passed to transports that check protocol compatibility.
+struct ApiVersion {
+    const char* crate_id;
+    uint32_t major;
+    uint32_t minor;
+    uint32_t patch;
+};
+
+inline std::vector<uint32_t> path(const std::vector<uint32_t>& base, std::initializer_list<uint32_t> ids) {
+    std::vector<uint32_t> path = base;
+    path.insert(path.end(), ids);
+    return path;
+}
+
+// Returns a deferred future that applies f to the result of the provided one
+template <typename T, typename F>
+auto map_future(std::future<T> future, F f) -> std::future<decltype(f(std::declval<T>()))> {
+    return std::async(std::launch::deferred,
+                      [future = std::move(future), f = std::move(f)]() mutable { return f(future.get()); });
+}
+
+using StreamHandler = std::function<void(const std::vector<uint8_t>&)>;
+
+/// Speaks ww_client_server over any message based transport, see WebSocketConnection and ShimConnection.
+/// Responses are matched to requests by seq, requests without a response in time fail with ww::Error.
+class Client {
+public:
+    using SendFn = std::function<void(const std::vector<uint8_t>&)>;
+
+    explicit Client(SendFn send, std::chrono::milliseconds timeout = std::chrono::milliseconds(2000))
+        : send_(std::move(send)), timeout_(timeout), timer_([this] { run_timer(); }) {}
+
+    ~Client() {
+        {
+            std::lock_guard<std::mutex> lock(mutex_);
+            stopping_ = true;
+        }
+        timer_cv_.notify_all();
+        timer_.join();
+        fail_all("client destroyed");
+    }
+
+    Client(const Client&) = delete;
+    Client& operator=(const Client&) = delete;
+
+    /// Called from a transport thread when the connection is lost, must be set before connecting.
+    std::function<void(const std::string&)> on_disconnect;
+
+    /// Feed bytes of one ww_client_server::Event received from the server.
+    void handle_event(const uint8_t* bytes, size_t len) {
+        Event event = decode_event(bytes, len);
+        StreamHandler handler;
+        std::optional<std::promise<Event>> promise;
+        {
+            std::lock_guard<std::mutex> lock(mutex_);
+            if (!event.error && event.kind == EventKind::StreamData) {
+                auto it = streams_.find(event.path);
+                if (it != streams_.end()) {
+                    handler = it->second;
+                }
+            }
+            if (event.seq != 0 || event.error) {
+                auto it = pending_.find(event.seq);
+                if (it != pending_.end()) {
+                    promise = std::move(it->second.promise);
+                    pending_.erase(it);
+                }
+            }
+        }
+        if (handler) {
+            handler(event.data);
+        }
+        if (promise) {
+            if (event.error) {
+                promise->set_exception(std::make_exception_ptr(*event.error));
+            } else {
+                promise->set_value(std::move(event));
+            }
+        }
+    }
+
+    /// Fail all pending requests, must be called by the transport when the connection is closed.
+    void disconnected(const std::string& reason) {
+        fail_all("disconnected: " + reason);
+        if (on_disconnect) {
+            on_disconnect(reason);
+        }
+    }
+
+    /// Send a request and wait for the corresponding event.
+    std::future<Event> request(const std::vector<uint32_t>& path, const RequestKind& kind) {
+        std::promise<Event> promise;
+        std::future<Event> future = promise.get_future();
+        uint16_t seq;
+        {
+            std::lock_guard<std::mutex> lock(mutex_);
+            seq = alloc_seq();
+            pending_.emplace(seq, Pending{std::move(promise), std::chrono::steady_clock::now() + timeout_});
+        }
+        timer_cv_.notify_all();
+        try {
+            send_(encode_request(seq, path, kind));
+        } catch (...) {
+            std::optional<std::promise<Event>> failed = take_pending(seq);
+            if (failed) {
+                failed->set_exception(std::current_exception());
+            }
+        }
+        return future;
+    }
+
+    /// Send a request without waiting for a response (seq = 0).
+    void notify(const std::vector<uint32_t>& path, const RequestKind& kind) { send_(encode_request(0, path, kind)); }
+
+    std::future<std::vector<uint8_t>> call(const std::vector<uint32_t>& path, std::vector<uint8_t> args) {
+        return map_future(request(path, RequestKind::call(std::move(args))), [](Event event) {
+            expect(event, EventKind::ReturnValue);
+            return std::move(event.data);
+        });
+    }
+
+    std::future<std::vector<uint8_t>> read(const std::vector<uint32_t>& path) {
+        return map_future(request(path, RequestKind::read()), [](Event event) {
+            expect(event, EventKind::ReadValue);
+            return std::move(event.data);
+        });
+    }
+
+    std::future<void> write(const std::vector<uint32_t>& path, std::vector<uint8_t> data) {
+        return map_future(request(path, RequestKind::write(std::move(data))),
+                          [](Event event) { expect(event, EventKind::Written); });
+    }
+
+    /// Open a stream and call on_data with bytes of each StreamData event for it, sinks pass an empty handler.
+    /// Handlers are called from the transport thread.
+    std::future<void> open_stream(const std::vector<uint32_t>& path, StreamHandler on_data) {
+        if (on_data) {
+            std::lock_guard<std::mutex> lock(mutex_);
+            streams_[path] = std::move(on_data);
+        }
+        return map_future(request(path, RequestKind::stream_sideband(RequestKind::Sideband::Open)), [](Event) {});
+    }
+
+    std::future<void> close_stream(const std::vector<uint32_t>& path) {
+        {
+            std::lock_guard<std::mutex> lock(mutex_);
+            streams_.erase(path);
+        }
+        return map_future(request(path, RequestKind::stream_sideband(RequestKind::Sideband::Close)), [](Event) {});
+    }
+
+private:
+    struct Pending {
+        std::promise<Event> promise;
+        std::chrono::steady_clock::time_point deadline;
+    };
+
+    static void expect(const Event& event, EventKind kind) {
+        if (event.kind != kind) {
+            throw Error(std::string(event_kind_name(kind)) + " expected, got " + event_kind_name(event.kind));
+        }
+    }
+
+    // must be called with mutex_ locked
+    uint16_t alloc_seq() {
+        for (uint32_t i = 0; i < MAX_U16; i++) {
+            uint16_t seq = next_seq_;
+            next_seq_ = seq == MAX_U16 ? 1 : static_cast<uint16_t>(seq + 1);
+            if (pending_.find(seq) == pending_.end()) {
+                return seq;
+            }
+        }
+        throw Error("no more request IDs available");
+    }
+
+    std::optional<std::promise<Event>> take_pending(uint16_t seq) {
+        std::lock_guard<std::mutex> lock(mutex_);
+        auto it = pending_.find(seq);
+        if (it == pending_.end()) {
+            return std::nullopt;
+        }
+        std::promise<Event> promise = std::move(it->second.promise);
+        pending_.erase(it);
+        return promise;
+    }
+
+    void fail_all(const std::string& reason) {
+        std::map<uint16_t, Pending> pending;
+        {
+            std::lock_guard<std::mutex> lock(mutex_);
+            pending.swap(pending_);
+        }
+        for (auto& entry : pending) {
+            entry.second.promise.set_exception(std::make_exception_ptr(Error(reason)));
+        }
+    }
+
+    void run_timer() {
+        std::unique_lock<std::mutex> lock(mutex_);
+        while (!stopping_) {
+            auto now = std::chrono::steady_clock::now();
+            std::optional<std::chrono::steady_clock::time_point> next;
+            for (auto it = pending_.begin(); it != pending_.end();) {
+                if (it->second.deadline <= now) {
+                    it->second.promise.set_exception(
+                        std::make_exception_ptr(Error("request " + std::to_string(it->first) + " timed out")));
+                    it = pending_.erase(it);
+                } else {
+                    if (!next || it->second.deadline < *next) {
+                        next = it->second.deadline;
+                    }
+                    ++it;
+                }
+            }
+            if (next) {
+                timer_cv_.wait_until(lock, *next);
+            } else {
+                timer_cv_.wait(lock);
+            }
+        }
+    }
+
+    SendFn send_;
+    std::chrono::milliseconds timeout_;
+    std::mutex mutex_;
+    std::condition_variable timer_cv_;
+    bool stopping_ = false;
+    std::map<uint16_t, Pending> pending_;
+    std::map<std::vector<uint32_t>, StreamHandler> streams_;
+    uint16_t next_seq_ = 1;
+    // declared last, so that everything it uses is initialized before it starts
+    std::thread timer_;
+};
+
+// C ABI shim --------------------------------------------------------------------------------------------------------
+
+} // namespace ww
+
+// Implemented by the wire_weaver_c_shim crate, only needed when ww::ShimConnection is used.
+extern "C" {
+typedef struct WwShim WwShim;
+typedef void (*WwShimEventFn)(void* user_data, const uint8_t* bytes, size_t len);
+typedef void (*WwShimDisconnectFn)(void* user_data, const char* reason);
+
+WwShim* ww_shim_connect(const char* filter, const char* crate_id, uint32_t major, uint32_t minor, uint32_t patch,
+                        uint32_t timeout_ms, WwShimEventFn on_event, WwShimDisconnectFn on_disconnect,
+                        void* user_data);
+int32_t ww_shim_send(WwShim* shim, const uint8_t* bytes, size_t len);
+void ww_shim_close(WwShim* shim);
+size_t ww_shim_last_error(char* buf, size_t len);
+}
+
+namespace ww {
+
+/// Connection through any transport supported by wire_weaver_client_common, provided by the wire_weaver_c_shim library.
+/// Filter is "ws://ip:port/path", "usb:vid:pid" or "usb:vid:pid:serial" with hexadecimal vid and pid.
+class ShimConnection {
+public:
+    static std::unique_ptr<ShimConnection> connect(const std::string& filter, const ApiVersion& version,
+                                                   std::chrono::milliseconds timeout = std::chrono::milliseconds(2000)) {
+        std::unique_ptr<ShimConnection> connection(new ShimConnection(timeout));
+        connection->shim_ = ww_shim_connect(filter.c_str(), version.crate_id, version.major, version.minor,
+                                            version.patch, static_cast<uint32_t>(timeout.count()), &on_event,
+                                            &on_disconnect, connection.get());
+        if (connection->shim_ == nullptr) {
+            char message[256];
+            ww_shim_last_error(message, sizeof(message));
+            throw Error(std::string("failed to connect to ") + filter + ": " + message);
+        }
+        return connection;
+    }
+
+    /// Must not be destroyed from a stream handler or on_disconnect.
+    ~ShimConnection() {
+        if (shim_ != nullptr) {
+            ww_shim_close(shim_);
+        }
+    }
+
+    ShimConnection(const ShimConnection&) = delete;
+    ShimConnection& operator=(const ShimConnection&) = delete;
+
+    Client& client() { return client_; }
+
+private:
+    explicit ShimConnection(std::chrono::milliseconds timeout)
+        : client_([this](const std::vector<uint8_t>& bytes) { send(bytes); }, timeout) {}
+
+    void send(const std::vector<uint8_t>& bytes) {
+        if (ww_shim_send(shim_, bytes.data(), bytes.size()) != 0) {
+            char message[256];
+            ww_shim_last_error(message, sizeof(message));
+            throw Error(message);
+        }
+    }
+
+    static void on_event(void* user_data, const uint8_t* bytes, size_t len) {
+        try {
+            static_cast<ShimConnection*>(user_data)->client_.handle_event(bytes, len);
+        } catch (const std::exception&) {
+            // malformed events and exceptions from stream handlers must not unwind into the shim
+        }
+    }
+
+    static void on_disconnect(void* user_data, const char* reason) {
+        try {
+            static_cast<ShimConnection*>(user_data)->client_.disconnected(reason);
+        } catch (const std::exception&) {
+        }
+    }
+
+    WwShim* shim_ = nullptr;
+    Client client_;
+};
+
+// WebSocket ---------------------------------------------------------------------------------------------------------
+
+#ifndef WW_NO_WEBSOCKET
+
+namespace detail {
+
+#if defined(_WIN32)
+using Socket = SOCKET;
+constexpr Socket INVALID_SOCKET_HANDLE = INVALID_SOCKET;
+constexpr int SEND_FLAGS = 0;
+constexpr int SHUTDOWN_BOTH = SD_BOTH;
+
+inline void close_socket(Socket socket) { closesocket(socket); }
+
+inline void init_sockets() {
+    static const bool initialized = [] {
+        WSADATA data;
+        return WSAStartup(MAKEWORD(2, 2), &data) == 0;
+    }();
+    if (!initialized) {
+        throw Error("WSAStartup failed");
+    }
+}
+
+inline void set_recv_timeout(Socket socket, std::chrono::milliseconds timeout) {
+    DWORD ms = static_cast<DWORD>(timeout.count());
+    setsockopt(socket, SOL_SOCKET, SO_RCVTIMEO, reinterpret_cast<const char*>(&ms), sizeof(ms));
+}
+#else
+using Socket = int;
+constexpr Socket INVALID_SOCKET_HANDLE = -1;
+#ifdef MSG_NOSIGNAL
+constexpr int SEND_FLAGS = MSG_NOSIGNAL;
+#else
+constexpr int SEND_FLAGS = 0;
+#endif
+constexpr int SHUTDOWN_BOTH = SHUT_RDWR;
+
+inline void close_socket(Socket socket) { ::close(socket); }
+
+inline void init_sockets() {}
+
+inline void set_recv_timeout(Socket socket, std::chrono::milliseconds timeout) {
+    timeval tv{};
+    tv.tv_sec = static_cast<decltype(tv.tv_sec)>(timeout.count() / 1000);
+    tv.tv_usec = static_cast<decltype(tv.tv_usec)>((timeout.count() % 1000) * 1000);
+    setsockopt(socket, SOL_SOCKET, SO_RCVTIMEO, &tv, sizeof(tv));
+}
+#endif
+
+inline std::string base64(const uint8_t* bytes, size_t len) {
+    static const char alphabet[] = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    std::string out;
+    for (size_t i = 0; i < len; i += 3) {
+        uint32_t chunk = static_cast<uint32_t>(bytes[i]) << 16;
+        if (i + 1 < len) {
+            chunk |= static_cast<uint32_t>(bytes[i + 1]) << 8;
+        }
+        if (i + 2 < len) {
+            chunk |= bytes[i + 2];
+        }
+        out.push_back(alphabet[(chunk >> 18) & 0x3f]);
+        out.push_back(alphabet[(chunk >> 12) & 0x3f]);
+        out.push_back(i + 1 < len ? alphabet[(chunk >> 6) & 0x3f] : '=');
+        out.push_back(i + 2 < len ? alphabet[chunk & 0x3f] : '=');
+    }
+    return out;
+}
+
+} // namespace detail
+
+/// Native WebSocket (RFC 6455) connection to wire_weaver_net_host compatible servers, plain ws:// only.
+class WebSocketConnection {
+public:
+    // Sideband messages are the same as used by wire_weaver_net_host WebSocket client
+    static constexpr const char* VERSIONS_REQUEST = "versions?";
+    static constexpr const char* LINK_SETUP = "link_setup 2048 0 6 0 1 100 0 1";
+    static constexpr uint64_t MAX_MESSAGE_LEN = 16 * 1024 * 1024;
+
+    /// Connect to a server and perform the link setup.
+    static std::unique_ptr<WebSocketConnection> connect(const std::string& host, uint16_t port,
+                                                        const std::string& path = "/",
+                                                        std::chrono::milliseconds timeout = std::chrono::milliseconds(2000)) {
+        detail::init_sockets();
+        addrinfo hints{};
+        hints.ai_family = AF_UNSPEC;
+        hints.ai_socktype = SOCK_STREAM;
+        addrinfo* addresses = nullptr;
+        if (getaddrinfo(host.c_str(), std::to_string(port).c_str(), &hints, &addresses) != 0) {
+            throw Error("cannot resolve " + host);
+        }
+        detail::Socket socket = detail::INVALID_SOCKET_HANDLE;
+        for (addrinfo* address = addresses; address != nullptr; address = address->ai_next) {
+            socket = ::socket(address->ai_family, address->ai_socktype, address->ai_protocol);
+            if (socket == detail::INVALID_SOCKET_HANDLE) {
+                continue;
+            }
+            if (::connect(socket, address->ai_addr, static_cast<int>(address->ai_addrlen)) == 0) {
+                break;
+            }
+            detail::close_socket(socket);
+            socket = detail::INVALID_SOCKET_HANDLE;
+        }
+        freeaddrinfo(addresses);
+        if (socket == detail::INVALID_SOCKET_HANDLE) {
+            throw Error("failed to connect to " + host + ":" + std::to_string(port));
+        }
+        int no_delay = 1;
+        setsockopt(socket, IPPROTO_TCP, TCP_NODELAY, reinterpret_cast<const char*>(&no_delay), sizeof(no_delay));
+
+        std::unique_ptr<WebSocketConnection> connection(new WebSocketConnection(socket, timeout));
+        connection->handshake(host + ":" + std::to_string(port), path.empty() ? "/" : path, timeout);
+        connection->reader_ = std::thread([c = connection.get()] { c->run_reader(); });
+        connection->send_frame(0x1, reinterpret_cast<const uint8_t*>(VERSIONS_REQUEST), std::strlen(VERSIONS_REQUEST));
+        std::unique_lock<std::mutex> lock(connection->link_mutex_);
+        if (!connection->link_cv_.wait_for(lock, timeout, [&] { return connection->link_done_; })) {
+            throw Error("link setup timed out");
+        }
+        if (!connection->link_up_) {
+            throw Error("closed before link setup: " + connection->link_error_);
+        }
+        return connection;
+    }
+
+    /// Must not be destroyed from a stream handler or on_disconnect.
+    ~WebSocketConnection() {
+        try {
+            send_frame(0x8, nullptr, 0);
+        } catch (const std::exception&) {
+        }
+        shutdown(socket_, detail::SHUTDOWN_BOTH);
+        if (reader_.joinable()) {
+            reader_.join();
+        }
+        detail::close_socket(socket_);
+    }
+
+    WebSocketConnection(const WebSocketConnection&) = delete;
+    WebSocketConnection& operator=(const WebSocketConnection&) = delete;
+
+    Client& client() { return client_; }
+
+    /// Crate name and version reported by the device, "crate_id major.minor.patch".
+    const std::string& device_info() const { return device_info_; }
+
+private:
+    WebSocketConnection(detail::Socket socket, std::chrono::milliseconds timeout)
+        : socket_(socket),
+          rng_(std::random_device{}()),
+          client_([this](const std::vector<uint8_t>& bytes) { send_frame(0x2, bytes.data(), bytes.size()); }, timeout) {}
+
+    void handshake(const std::string& host, const std::string& path, std::chrono::milliseconds timeout) {
+        uint8_t key[16];
+        for (auto& byte : key) {
+            byte = static_cast<uint8_t>(rng_());
+        }
+        std::string request = "GET " + path + " HTTP/1.1\r\nHost: " + host +
+                              "\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: " +
+                              detail::base64(key, sizeof(key)) + "\r\nSec-WebSocket-Version: 13\r\n\r\n";
+        send_all(reinterpret_cast<const uint8_t*>(request.data()), request.size());
+        detail::set_recv_timeout(socket_, timeout);
+        std::string response;
+        size_t end;
+        while ((end = response.find("\r\n\r\n")) == std::string::npos) {
+            if (response.size() > 8192) {
+                throw Error("WebSocket handshake response is too long");
+            }
+            char buf[512];
+            auto received = recv(socket_, buf, sizeof(buf), 0);
+            if (received <= 0) {
+                throw Error("WebSocket handshake failed");
+            }
+            response.append(buf, static_cast<size_t>(received));
+        }
+        detail::set_recv_timeout(socket_, std::chrono::milliseconds(0));
+        if (response.compare(0, 12, "HTTP/1.1 101") != 0) {
+            throw Error("WebSocket upgrade rejected: " + response.substr(0, response.find("\r\n")));
+        }
+        leftover_.assign(response.begin() + static_cast<std::ptrdiff_t>(end + 4), response.end());
+    }
+
+    void send_all(const uint8_t* bytes, size_t len) {
+        while (len > 0) {
+            auto sent = send(socket_, reinterpret_cast<const char*>(bytes), static_cast<int>(len), detail::SEND_FLAGS);
+            if (sent <= 0) {
+                throw Error("WebSocket send failed");
+            }
+            bytes += sent;
+            len -= static_cast<size_t>(sent);
+        }
+    }
+
+    void send_frame(uint8_t opcode, const uint8_t* payload, size_t len) {
+        std::vector<uint8_t> frame;
+        frame.reserve(len + 14);
+        frame.push_back(static_cast<uint8_t>(0x80 | opcode));
+        if (len < 126) {
+            frame.push_back(static_cast<uint8_t>(0x80 | len));
+        } else if (len <= 0xffff) {
+            frame.push_back(0x80 | 126);
+            frame.push_back(static_cast<uint8_t>(len >> 8));
+            frame.push_back(static_cast<uint8_t>(len));
+        } else {
+            frame.push_back(0x80 | 127);
+            for (int i = 7; i >= 0; i--) {
+                frame.push_back(static_cast<uint8_t>(static_cast<uint64_t>(len) >> (8 * i)));
+            }
+        }
+        std::lock_guard<std::mutex> lock(send_mutex_);
+        uint8_t mask[4];
+        for (auto& byte : mask) {
+            byte = static_cast<uint8_t>(rng_());
+        }
+        frame.insert(frame.end(), mask, mask + 4);
+        for (size_t i = 0; i < len; i++) {
+            frame.push_back(payload[i] ^ mask[i % 4]);
+        }
+        send_all(frame.data(), frame.size());
+    }
+
+    void read_exact(uint8_t* buf, size_t len) {
+        while (len > 0) {
+            if (!leftover_.empty()) {
+                size_t n = (std::min)(len, leftover_.size());
+                std::memcpy(buf, leftover_.data(), n);
+                leftover_.erase(leftover_.begin(), leftover_.begin() + static_cast<std::ptrdiff_t>(n));
+                buf += n;
+                len -= n;
+                continue;
+            }
+            auto received = recv(socket_, reinterpret_cast<char*>(buf), static_cast<int>(len), 0);
+            if (received == 0) {
+                throw Error("connection closed");
+            }
+            if (received < 0) {
+#if !defined(_WIN32)
+                if (errno == EINTR) {
+                    continue;
+                }
+#endif
+                throw Error("WebSocket receive failed");
+            }
+            buf += received;
+            len -= static_cast<size_t>(received);
+        }
+    }
+
+    void on_text(const std::string& text) {
+        std::string op = text.substr(0, text.find(' '));
+        if (op == "device_info") {
+            device_info_ = text.size() > op.size() ? text.substr(op.size() + 1) : std::string();
+            send_frame(0x1, reinterpret_cast<const uint8_t*>(LINK_SETUP), std::strlen(LINK_SETUP));
+        } else if (op == "link_setup_result") {
+            {
+                std::lock_guard<std::mutex> lock(link_mutex_);
+                link_done_ = true;
+                link_up_ = true;
+            }
+            link_cv_.notify_all();
+        }
+    }
+
+    void run_reader() {
+        std::string reason = "connection closed";
+        std::vector<uint8_t> message;
+        uint8_t message_opcode = 0;
+        try {
+            while (true) {
+                uint8_t header[2];
+                read_exact(header, 2);
+                bool fin = (header[0] & 0x80) != 0;
+                uint8_t opcode = header[0] & 0x0f;
+                bool masked = (header[1] & 0x80) != 0;
+                uint64_t len = header[1] & 0x7f;
+                if (len >= 126) {
+                    uint8_t ext[8];
+                    size_t ext_len = len == 126 ? 2 : 8;
+                    read_exact(ext, ext_len);
+                    len = 0;
+                    for (size_t i = 0; i < ext_len; i++) {
+                        len = (len << 8) | ext[i];
+                    }
+                }
+                if (len + message.size() > MAX_MESSAGE_LEN) {
+                    throw Error("WebSocket message is too long");
+                }
+                uint8_t mask[4] = {0, 0, 0, 0};
+                if (masked) {
+                    read_exact(mask, 4);
+                }
+                std::vector<uint8_t> payload(static_cast<size_t>(len));
+                read_exact(payload.data(), payload.size());
+                for (size_t i = 0; i < payload.size(); i++) {
+                    payload[i] ^= mask[i % 4];
+                }
+                if (opcode == 0x8) {
+                    reason = "closed by server";
+                    send_frame(0x8, nullptr, 0);
+                    break;
+                } else if (opcode == 0x9) {
+                    send_frame(0xa, payload.data(), payload.size());
+                    continue;
+                } else if (opcode >= 0x8) {
+                    continue;
+                }
+                if (opcode != 0) {
+                    message_opcode = opcode;
+                    message.clear();
+                }
+                message.insert(message.end(), payload.begin(), payload.end());
+                if (!fin) {
+                    continue;
+                }
+                if (message_opcode == 0x2) {
+                    try {
+                        client_.handle_event(message.data(), message.size());
+                    } catch (const std::exception&) {
+                        // malformed events and exceptions from stream handlers do not close the connection
+                    }
+                } else if (message_opcode == 0x1) {
+                    on_text(std::string(message.begin(), message.end()));
+                }
+                message.clear();
+            }
+        } catch (const std::exception& e) {
+            reason = e.what();
+        }
+        {
+            std::lock_guard<std::mutex> lock(link_mutex_);
+            if (!link_done_) {
+                link_done_ = true;
+                link_error_ = reason;
+            }
+        }
+        link_cv_.notify_all();
+        try {
+            client_.disconnected(reason);
+        } catch (const std::exception&) {
+        }
+    }
+
+    detail::Socket socket_;
+    std::mt19937 rng_;
+    std::mutex send_mutex_;
+    std::vector<uint8_t> leftover_;
+    std::string device_info_;
+    std::mutex link_mutex_;
+    std::condition_variable link_cv_;
+    bool link_done_ = false;
+    bool link_up_ = false;
+    std::string link_error_;
+    Client client_;
+    std::thread reader_;
+};
+
+#endif // WW_NO_WEBSOCKET
+
+} // namespace ww
+
+#endif // WW_CPP_RUNTIME
@@ -0,0 +1,1853 @@
+//! C++17 types, codec and client generator.
+//!
+//! Structs from an ApiBundle are emitted as plain structs, enums without data as `enum class` and enums with data as
+//! a struct holding a `std::variant` of nested variant structs. Each type gets `ww_write` / `ww_read` overloads
+//! working on the header-only runtime (cpp.hpp), which encodes exactly like shrink_wrap does. Each API level becomes a
+//! client class, with methods returning `std::future`, sending ww_client_server requests over a native WebSocket or
+//! through the wire_weaver_c_shim library.
+//!
+//! Values map as follows: Option<T> is `std::optional<T>`, Result<T, E> is `ww::Result<T, E>`, Vec<T> is
+//! `std::vector<T>`, arrays are `std::array`, tuples are `std::tuple`, Box<T> is `ww::Box<T>`, bit-aligned numbers use
+//! the smallest fitting integer, unnamed fields are named `_0`, `_1`, etc.
+//! Explicit flags are not part of the values, they are derived from the corresponding Option or Result.
+
+use crate::codegen::util::{
+    fields_list, flags, is_bytes, is_option_or_result, numeric_base, unique_name,
+};
+use anyhow::{Result, anyhow};
+use convert_case::{Case, Casing};
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fmt::Write;
+use ww_numeric::{NumericBaseType, NumericValue};
+use ww_self::{
+    ApiBundleOwned, ApiItemKindOwned, ApiItemOwned, ApiLevelLocationOwned, ApiLevelOwned,
+    FieldOwned, FieldsOwned, FieldsValueOwned, ItemEnumOwned, PropertyAccess, Repr,
+    TypeLocationOwned, TypeOwned, ValueOwned,
+};
+
+const RUNTIME: &str = include_str!("cpp.hpp");
+
+const RESERVED: &[&str] = &[
+    "alignas",
+    "alignof",
+    "and",
+    "and_eq",
+    "asm",
+    "auto",
+    "bitand",
+    "bitor",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "compl",
+    "concept",
+    "const",
+    "const_cast",
+    "constexpr",
+    "continue",
+    "decltype",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "dynamic_cast",
+    "else",
+    "enum",
+    "explicit",
+    "export",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "not",
+    "not_eq",
+    "nullptr",
+    "operator",
+    "or",
+    "or_eq",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "reinterpret_cast",
+    "requires",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "static_assert",
+    "static_cast",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "thread_local",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "typeid",
+    "typename",
+    "union",
+    "unsigned",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "wchar_t",
+    "while",
+    "xor",
+    "xor_eq",
+    // array index parameter and locals of the generated methods
+    "index",
+    "wr",
+    "rd",
+    "bytes",
+    "on_value",
+];
+
+/// Generates a single header-only C++17 file with types, encoders, decoders and typed clients for the given API
+/// bundle. ApiBundleOwned can be loaded using [crate::load] or [crate::load_dep].
+///
+/// Resulting file only depends on the standard library and sockets, linking with wire_weaver_c_shim is only needed
+/// when `ww::ShimConnection` is used.
+pub fn gen_cpp(api_bundle: &ApiBundleOwned) -> Result<String> {
+    let root_crate = api_bundle.crate_version(api_bundle.root.crate_idx.0)?;
+    let v = &root_crate.version;
+    let api_name = format!(
+        "{}::{} v{}.{}.{}",
+        root_crate.crate_id, api_bundle.root.trait_name, v.major.0, v.minor.0, v.patch.0
+    );
+    let namespace = ident(&root_crate.crate_id.replace('-', "_"));
+    let cpp_gen = CppGen::new(api_bundle, namespace.clone());
+
+    let mut cpp = String::new();
+    writeln!(
+        cpp,
+        "// C++17 client for {api_name}, generated by wire_weaver_core {}, do not edit.",
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(cpp)?;
+    writeln!(cpp, "#pragma once")?;
+    writeln!(cpp)?;
+    cpp.push_str(RUNTIME);
+    writeln!(cpp)?;
+    writeln!(
+        cpp,
+        "// {api_name} {}",
+        "-".repeat(116usize.saturating_sub(api_name.len()))
+    )?;
+    writeln!(cpp)?;
+    writeln!(cpp, "namespace {namespace} {{")?;
+    writeln!(cpp)?;
+    writeln!(
+        cpp,
+        "inline constexpr const char* WW_API_NAME = {};",
+        cpp_str(&api_name)
+    )?;
+    writeln!(
+        cpp,
+        "inline constexpr ww::ApiVersion WW_API_VERSION{{{}, {}, {}, {}}};",
+        cpp_str(&root_crate.crate_id),
+        v.major.0,
+        v.minor.0,
+        v.patch.0
+    )?;
+    writeln!(cpp)?;
+
+    let order = cpp_gen.definition_order()?;
+    for &idx in &order {
+        let name = cpp_gen.decl_name(idx)?;
+        if cpp_gen.is_unit_enum(idx) {
+            writeln!(cpp, "enum class {name} : uint32_t;")?;
+        } else {
+            writeln!(cpp, "struct {name};")?;
+        }
+    }
+    if !order.is_empty() {
+        writeln!(cpp)?;
+    }
+    for &idx in &order {
+        cpp_gen.type_def(&mut cpp, idx)?;
+    }
+
+    // equality and codec are declared first, so that they are visible in all the definitions regardless of order
+    for &idx in &order {
+        cpp_gen.declarations(&mut cpp, idx)?;
+    }
+    if !order.is_empty() {
+        writeln!(cpp)?;
+    }
+    for &idx in &order {
+        cpp_gen.equality(&mut cpp, idx)?;
+    }
+    for &idx in &order {
+        cpp_gen.codec(&mut cpp, idx)?;
+    }
+    for &idx in &order {
+        let name = cpp_gen.name(idx)?;
+        let snake = cpp_gen.decl_name(idx)?.to_case(Case::Snake);
+        writeln!(
+            cpp,
+            "inline std::vector<uint8_t> encode_{snake}(const {name}& value) {{\n    ww::BufWriter wr;\n    ww_write(wr, value);\n    return wr.finish();\n}}\n"
+        )?;
+        writeln!(
+            cpp,
+            "inline {name} decode_{snake}(const std::vector<uint8_t>& bytes) {{\n    ww::BufReader rd(bytes);\n    {name} value{{}};\n    ww_read(rd, value);\n    return value;\n}}\n"
+        )?;
+    }
+
+    let mut levels = vec![(&api_bundle.root, None)];
+    for (idx, location) in api_bundle.traits.iter().enumerate() {
+        if let ApiLevelLocationOwned::InLine { level, .. } = location {
+            levels.push((level, Some(idx)));
+        }
+    }
+    for (_, trait_idx) in &levels {
+        writeln!(cpp, "class {};", cpp_gen.class_name(*trait_idx)?)?;
+    }
+    writeln!(cpp)?;
+    for (level, trait_idx) in &levels {
+        cpp_gen.client_class(&mut cpp, level, *trait_idx)?;
+    }
+    for (level, trait_idx) in &levels {
+        let class_name = cpp_gen.class_name(*trait_idx)?;
+        for item in &level.items {
+            cpp_gen.client_item(&mut cpp, &class_name, item)?;
+        }
+    }
+    writeln!(cpp, "}} // namespace {namespace}")?;
+    Ok(cpp)
+}
+
+struct CppGen<'a> {
+    api_bundle: &'a ApiBundleOwned,
+    namespace: String,
+    /// C++ names of out-of-line structs and enums, indexed the same as types in the bundle.
+    type_names: Vec<Option<String>>,
+    /// Client class names of out-of-line traits, indexed the same as traits in the bundle.
+    trait_class_names: Vec<Option<String>>,
+    root_class_name: String,
+    /// Names of nested variant structs, type names equal to one of them are qualified with the namespace.
+    variant_names: HashSet<String>,
+    /// Counter for unique local variable names.
+    next_var: Cell<usize>,
+}
+
+impl<'a> CppGen<'a> {
+    fn new(api_bundle: &'a ApiBundleOwned, namespace: String) -> Self {
+        let mut used = HashSet::new();
+        let mut type_names = vec![];
+        let mut variant_names = HashSet::new();
+        for (idx, location) in api_bundle.types.iter().enumerate() {
+            let ident = match location {
+                TypeLocationOwned::InLine {
+                    ty: TypeOwned::Struct(item_struct),
+                    ..
+                } => Some(&item_struct.ident),
+                TypeLocationOwned::InLine {
+                    ty: TypeOwned::Enum(item_enum),
+                    ..
+                } => {
+                    if !is_unit_only(item_enum) {
+                        for variant in &item_enum.variants {
+                            variant_names.insert(variant.ident.clone());
+                        }
+                    }
+                    Some(&item_enum.ident)
+                }
+                _ => None,
+            };
+            type_names.push(ident.map(|ident| unique_name(&mut used, &self::ident(ident), idx)));
+        }
+
+        let root_class_name = unique_name(
+            &mut used,
+            &format!("{}Client", api_bundle.root.trait_name),
+            0,
+        );
+        let mut trait_class_names = vec![];
+        for (idx, location) in api_bundle.traits.iter().enumerate() {
+            let name = match location {
+                ApiLevelLocationOwned::InLine { level, .. } => Some(unique_name(
+                    &mut used,
+                    &format!("{}Client", level.trait_name),
+                    idx,
+                )),
+                _ => None,
+            };
+            trait_class_names.push(name);
+        }
+        CppGen {
+            api_bundle,
+            namespace,
+            type_names,
+            trait_class_names,
+            root_class_name,
+            variant_names,
+            next_var: Cell::new(0),
+        }
+    }
+
+    fn decl_name(&self, idx: usize) -> Result<String> {
+        self.type_names[idx]
+            .clone()
+            .ok_or_else(|| anyhow!("type #{idx} is not a struct or enum"))
+    }
+
+    /// Name to refer to a type with, qualified if a variant struct with the same name could shadow it.
+    fn name(&self, idx: usize) -> Result<String> {
+        let name = self.decl_name(idx)?;
+        if self.variant_names.contains(&name) {
+            Ok(format!("::{}::{name}", self.namespace))
+        } else {
+            Ok(name)
+        }
+    }
+
+    fn class_name(&self, trait_idx: Option<usize>) -> Result<String> {
+        match trait_idx {
+            Some(idx) => self.trait_class_names[idx]
+                .clone()
+                .ok_or_else(|| anyhow!("trait #{idx} is not in-line")),
+            None => Ok(self.root_class_name.clone()),
+        }
+    }
+
+    fn in_line(&self, idx: usize) -> Option<&'a TypeOwned> {
+        match self.api_bundle.types.get(idx) {
+            Some(TypeLocationOwned::InLine { ty, .. }) => Some(ty),
+            _ => None,
+        }
+    }
+
+    fn is_unit_enum(&self, idx: usize) -> bool {
+        matches!(self.in_line(idx), Some(TypeOwned::Enum(item_enum)) if is_unit_only(item_enum))
+    }
+
+    fn var(&self, prefix: &str) -> String {
+        let n = self.next_var.get();
+        self.next_var.set(n + 1);
+        format!("{prefix}{n}")
+    }
+
+    /// Named types in the order they must be defined in: types held by value come before the types holding them.
+    fn definition_order(&self) -> Result<Vec<usize>> {
+        let mut state = vec![0u8; self.type_names.len()];
+        let mut order = vec![];
+        for idx in 0..self.type_names.len() {
+            if self.type_names[idx].is_some() {
+                self.visit(idx, &mut state, &mut order)?;
+            }
+        }
+        Ok(order)
+    }
+
+    fn visit(&self, idx: usize, state: &mut [u8], order: &mut Vec<usize>) -> Result<()> {
+        // cycles are only possible through Box and Vec, which do not need a complete type to be declared
+        if state[idx] != 0 {
+            return Ok(());
+        }
+        state[idx] = 1;
+        let mut deps = vec![];
+        match self.in_line(idx) {
+            Some(TypeOwned::Struct(item_struct)) => {
+                for field in fields_list(&item_struct.fields).unwrap_or_default() {
+                    self.deps(&field.ty, &mut deps);
+                }
+            }
+            Some(TypeOwned::Enum(item_enum)) => {
+                for variant in &item_enum.variants {
+                    for field in fields_list(&variant.fields).unwrap_or_default() {
+                        self.deps(&field.ty, &mut deps);
+                    }
+                }
+            }
+            _ => {}
+        }
+        for dep in deps {
+            self.visit(dep, state, order)?;
+        }
+        state[idx] = 2;
+        order.push(idx);
+        Ok(())
+    }
+
+    fn deps(&self, ty: &TypeOwned, deps: &mut Vec<usize>) {
+        match ty {
+            TypeOwned::OutOfLine { type_idx } => {
+                let idx = type_idx.0 as usize;
+                if self.type_names.get(idx).is_some_and(|name| name.is_some()) {
+                    deps.push(idx);
+                } else if let Some(ty) = self.in_line(idx) {
+                    self.deps(ty, deps);
+                }
+            }
+            TypeOwned::Vec(ty)
+            | TypeOwned::BoundedVec { ty, .. }
+            | TypeOwned::Array { ty, .. }
+            | TypeOwned::Option { some_ty: ty } => self.deps(ty, deps),
+            TypeOwned::Tuple(types) => {
+                for ty in types {
+                    self.deps(ty, deps);
+                }
+            }
+            TypeOwned::Result { ok_ty, err_ty } => {
+                self.deps(ok_ty, deps);
+                self.deps(err_ty, deps);
+            }
+            _ => {}
+        }
+    }
+
+    fn type_def(&self, cpp: &mut String, idx: usize) -> Result<()> {
+        let name = self.decl_name(idx)?;
+        match self.in_line(idx) {
+            Some(TypeOwned::Struct(item_struct)) => {
+                cpp.push_str(&doc_comment(&item_struct.docs, ""));
+                writeln!(cpp, "struct {name} {{")?;
+                self.fields_def(cpp, &item_struct.fields, "    ", true)?;
+                writeln!(cpp, "}};")?;
+            }
+            Some(TypeOwned::Enum(item_enum)) if is_unit_only(item_enum) => {
+                cpp.push_str(&doc_comment(&item_enum.docs, ""));
+                writeln!(cpp, "enum class {name} : uint32_t {{")?;
+                for variant in &item_enum.variants {
+                    cpp.push_str(&doc_comment(&variant.docs, "    "));
+                    writeln!(
+                        cpp,
+                        "    {} = {},",
+                        ident(&variant.ident),
+                        variant.discriminant.0
+                    )?;
+                }
+                writeln!(cpp, "}};")?;
+            }
+            Some(TypeOwned::Enum(item_enum)) => {
+                cpp.push_str(&doc_comment(&item_enum.docs, ""));
+                writeln!(cpp, "struct {name} {{")?;
+                let mut alternatives = vec![];
+                for variant in &item_enum.variants {
+                    let variant_name = variant_name(&item_enum.ident, &variant.ident);
+                    cpp.push_str(&doc_comment(&variant.docs, "    "));
+                    if fields_list(&variant.fields).is_none_or(|fields| fields.is_empty()) {
+                        writeln!(cpp, "    struct {variant_name} {{}};")?;
+                    } else {
+                        writeln!(cpp, "    struct {variant_name} {{")?;
+                        self.fields_def(cpp, &variant.fields, "        ", false)?;
+                        writeln!(cpp, "    }};")?;
+                    }
+                    alternatives.push(variant_name);
+                }
+                writeln!(cpp)?;
+                writeln!(cpp, "    std::variant<{}> value;", alternatives.join(", "))?;
+                writeln!(cpp, "}};")?;
+            }
+            _ => return Err(anyhow!("expected struct or enum at type #{idx}")),
+        }
+        writeln!(cpp)?;
+        Ok(())
+    }
+
+    /// Member initializers are not used in variant structs, std::variant would not see them as default constructible
+    /// inside the enclosing struct, they are value-initialized by it anyway.
+    fn fields_def(
+        &self,
+        cpp: &mut String,
+        fields: &FieldsOwned,
+        indent: &str,
+        init: bool,
+    ) -> Result<()> {
+        let Some(fields) = fields_list(fields) else {
+            return Ok(());
+        };
+        let flags = flags(fields)?;
+        for (idx, field) in fields.iter().enumerate() {
+            if flags.flag_for[idx].is_some() {
+                continue;
+            }
+            cpp.push_str(&doc_comment(&field.docs, indent));
+            writeln!(
+                cpp,
+                "{indent}{} {}{};",
+                self.ty(&field.ty)?,
+                field_name(field, idx),
+                if init { "{}" } else { "" }
+            )?;
+        }
+        Ok(())
+    }
+
+    /// C++ type of values.
+    fn ty(&self, ty: &TypeOwned) -> Result<String> {
+        let cpp = match ty {
+            TypeOwned::Bool => "bool".into(),
+            TypeOwned::NumericAny(numeric) => cpp_num_ty(numeric_base(numeric)),
+            TypeOwned::OutOfLine { type_idx } => {
+                let idx = type_idx.0 as usize;
+                match self.type_names.get(idx) {
+                    Some(Some(_)) => self.name(idx)?,
+                    _ => match self.in_line(idx) {
+                        Some(ty) => self.ty(ty)?,
+                        None => "ww::Unsupported".into(),
+                    },
+                }
+            }
+            TypeOwned::Flag => return Err(anyhow!("Flag type cannot be used on its own")),
+            TypeOwned::String | TypeOwned::BoundedString { .. } => "std::string".into(),
+            TypeOwned::Vec(ty) | TypeOwned::BoundedVec { ty, .. } => {
+                format!("std::vector<{}>", self.ty(ty)?)
+            }
+            TypeOwned::Array { len, ty } => format!("std::array<{}, {}>", self.ty(ty)?, len.0),
+            TypeOwned::Tuple(types) => {
+                let tys = types
+                    .iter()
+                    .map(|ty| self.ty(ty))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("std::tuple<{}>", tys.join(", "))
+            }
+            TypeOwned::Struct(item_struct) => {
+                return Err(anyhow!(
+                    "in-line struct {} is not supported in C++",
+                    item_struct.ident
+                ));
+            }
+            TypeOwned::Enum(item_enum) => {
+                return Err(anyhow!(
+                    "in-line enum {} is not supported in C++",
+                    item_enum.ident
+                ));
+            }
+            TypeOwned::Option { some_ty } => format!("std::optional<{}>", self.ty(some_ty)?),
+            TypeOwned::Result { ok_ty, err_ty } => {
+                format!("ww::Result<{}, {}>", self.ty(ok_ty)?, self.ty(err_ty)?)
+            }
+            TypeOwned::Box(ty) => format!("ww::Box<{}>", self.ty(ty)?),
+            TypeOwned::Range(base) => format!("ww::Range<{}>", cpp_num_ty(base)),
+            TypeOwned::RangeInclusive(base) => {
+                format!("ww::RangeInclusive<{}>", cpp_num_ty(base))
+            }
+        };
+        Ok(cpp)
+    }
+
+    /// Parameter type, small values are passed by value and everything else by const reference.
+    fn param_ty(&self, ty: &TypeOwned) -> Result<String> {
+        let by_value = match ty {
+            TypeOwned::Bool | TypeOwned::NumericAny(_) => true,
+            TypeOwned::OutOfLine { type_idx } => self.is_unit_enum(type_idx.0 as usize),
+            _ => false,
+        };
+        if by_value {
+            self.ty(ty)
+        } else {
+            Ok(format!("const {}&", self.ty(ty)?))
+        }
+    }
+
+    fn is_unsized(&self, ty: &TypeOwned) -> bool {
+        // skipped types are not supported anyway, their size does not matter
+        ty.is_unsized(self.api_bundle).unwrap_or(false)
+    }
+
+    fn declarations(&self, cpp: &mut String, idx: usize) -> Result<()> {
+        let name = self.name(idx)?;
+        match self.in_line(idx) {
+            Some(TypeOwned::Enum(item_enum)) if !is_unit_only(item_enum) => {
+                for variant in &item_enum.variants {
+                    let variant_name = variant_name(&item_enum.ident, &variant.ident);
+                    let ty = format!("{name}::{variant_name}");
+                    writeln!(cpp, "inline bool operator==(const {ty}& a, const {ty}& b);")?;
+                    writeln!(cpp, "inline bool operator!=(const {ty}& a, const {ty}& b);")?;
+                }
+                writeln!(
+                    cpp,
+                    "inline bool operator==(const {name}& a, const {name}& b);"
+                )?;
+                writeln!(
+                    cpp,
+                    "inline bool operator!=(const {name}& a, const {name}& b);"
+                )?;
+            }
+            Some(TypeOwned::Struct(_)) => {
+                writeln!(
+                    cpp,
+                    "inline bool operator==(const {name}& a, const {name}& b);"
+                )?;
+                writeln!(
+                    cpp,
+                    "inline bool operator!=(const {name}& a, const {name}& b);"
+                )?;
+            }
+            _ => {}
+        }
+        writeln!(
+            cpp,
+            "inline void ww_write(ww::BufWriter& wr, const {name}& value);"
+        )?;
+        writeln!(
+            cpp,
+            "inline void ww_read(ww::BufReader& rd, {name}& value);"
+        )?;
+        Ok(())
+    }
+
+    fn equality(&self, cpp: &mut String, idx: usize) -> Result<()> {
+        let name = self.name(idx)?;
+        match self.in_line(idx) {
+            Some(TypeOwned::Struct(item_struct)) => {
+                self.fields_equality(cpp, &name, &item_struct.fields)?;
+            }
+            Some(TypeOwned::Enum(item_enum)) if !is_unit_only(item_enum) => {
+                for variant in &item_enum.variants {
+                    let variant_name = variant_name(&item_enum.ident, &variant.ident);
+                    self.fields_equality(cpp, &format!("{name}::{variant_name}"), &variant.fields)?;
+                }
+                writeln!(
+                    cpp,
+                    "inline bool operator==(const {name}& a, const {name}& b) {{\n    return a.value == b.value;\n}}\n"
+                )?;
+                writeln!(
+                    cpp,
+                    "inline bool operator!=(const {name}& a, const {name}& b) {{\n    return !(a == b);\n}}\n"
+                )?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn fields_equality(&self, cpp: &mut String, ty: &str, fields: &FieldsOwned) -> Result<()> {
+        let mut compared = vec![];
+        if let Some(fields) = fields_list(fields) {
+            let flags = flags(fields)?;
+            for (idx, field) in fields.iter().enumerate() {
+                if flags.flag_for[idx].is_none() {
+                    let name = field_name(field, idx);
+                    compared.push(format!("a.{name} == b.{name}"));
+                }
+            }
+        }
+        if compared.is_empty() {
+            writeln!(
+                cpp,
+                "inline bool operator==(const {ty}&, const {ty}&) {{\n    return true;\n}}\n"
+            )?;
+        } else {
+            writeln!(
+                cpp,
+                "inline bool operator==(const {ty}& a, const {ty}& b) {{\n    return {};\n}}\n",
+                compared.join(" && ")
+            )?;
+        }
+        writeln!(
+            cpp,
+            "inline bool operator!=(const {ty}& a, const {ty}& b) {{\n    return !(a == b);\n}}\n"
+        )?;
+        Ok(())
+    }
+
+    fn codec(&self, cpp: &mut String, idx: usize) -> Result<()> {
+        let name = self.name(idx)?;
+        self.next_var.set(0);
+        let mut write = String::new();
+        let mut read = String::new();
+        match self.in_line(idx) {
+            Some(TypeOwned::Struct(item_struct)) => {
+                if fields_list(&item_struct.fields).is_none_or(|fields| fields.is_empty()) {
+                    write.push_str("    (void)wr;\n    (void)value;\n");
+                    read.push_str("    (void)rd;\n    (void)value;\n");
+                } else {
+                    self.write_fields(&mut write, &item_struct.fields, "value.", 1)?;
+                    read.push_str("    ww::DepthGuard depth(rd);\n");
+                    self.read_fields(&mut read, &item_struct.fields, "value.", 1)?;
+                }
+            }
+            Some(TypeOwned::Enum(item_enum)) if is_unit_only(item_enum) => {
+                let (repr, bits) = cpp_repr(&item_enum.repr);
+                writeln!(write, "    switch (value) {{")?;
+                for variant in &item_enum.variants {
+                    writeln!(write, "    case {name}::{}:", ident(&variant.ident))?;
+                }
+                writeln!(write, "        break;")?;
+                writeln!(write, "    default:")?;
+                writeln!(
+                    write,
+                    "        throw ww::Error(\"invalid {} value\");",
+                    item_enum.ident
+                )?;
+                writeln!(write, "    }}")?;
+                writeln!(
+                    write,
+                    "    ww::write_discriminant(wr, {repr}, {bits}, static_cast<uint32_t>(value));"
+                )?;
+
+                writeln!(
+                    read,
+                    "    uint32_t discriminant = ww::read_discriminant(rd, {repr}, {bits});"
+                )?;
+                writeln!(read, "    switch (discriminant) {{")?;
+                for variant in &item_enum.variants {
+                    writeln!(read, "    case {}:", variant.discriminant.0)?;
+                }
+                writeln!(read, "        value = static_cast<{name}>(discriminant);")?;
+                writeln!(read, "        break;")?;
+                self.unknown_variant(&mut read, &item_enum.ident)?;
+            }
+            Some(TypeOwned::Enum(item_enum)) => {
+                let (repr, bits) = cpp_repr(&item_enum.repr);
+                writeln!(write, "    switch (value.value.index()) {{")?;
+                writeln!(read, "    ww::DepthGuard depth(rd);")?;
+                writeln!(
+                    read,
+                    "    uint32_t discriminant = ww::read_discriminant(rd, {repr}, {bits});"
+                )?;
+                writeln!(read, "    switch (discriminant) {{")?;
+                for (variant_idx, variant) in item_enum.variants.iter().enumerate() {
+                    let variant_name = variant_name(&item_enum.ident, &variant.ident);
+                    let has_fields =
+                        fields_list(&variant.fields).is_some_and(|fields| !fields.is_empty());
+                    writeln!(write, "    case {variant_idx}: {{")?;
+                    if has_fields {
+                        writeln!(
+                            write,
+                            "        [[maybe_unused]] const auto& v = std::get<{variant_idx}>(value.value);"
+                        )?;
+                    }
+                    writeln!(
+                        write,
+                        "        ww::write_discriminant(wr, {repr}, {bits}, {});",
+                        variant.discriminant.0
+                    )?;
+                    self.write_fields(&mut write, &variant.fields, "v.", 2)?;
+                    writeln!(write, "        break;")?;
+                    writeln!(write, "    }}")?;
+
+                    writeln!(read, "    case {}: {{", variant.discriminant.0)?;
+                    writeln!(read, "        {name}::{variant_name} v;")?;
+                    self.read_fields(&mut read, &variant.fields, "v.", 2)?;
+                    writeln!(read, "        value.value = std::move(v);")?;
+                    writeln!(read, "        break;")?;
+                    writeln!(read, "    }}")?;
+                }
+                writeln!(write, "    default:")?;
+                writeln!(
+                    write,
+                    "        throw ww::Error(\"{} is valueless\");",
+                    item_enum.ident
+                )?;
+                writeln!(write, "    }}")?;
+                self.unknown_variant(&mut read, &item_enum.ident)?;
+            }
+            _ => return Err(anyhow!("expected struct or enum at type #{idx}")),
+        }
+        writeln!(
+            cpp,
+            "inline void ww_write(ww::BufWriter& wr, const {name}& value) {{"
+        )?;
+        cpp.push_str(&write);
+        writeln!(cpp, "}}")?;
+        writeln!(cpp)?;
+        writeln!(
+            cpp,
+            "inline void ww_read(ww::BufReader& rd, {name}& value) {{"
+        )?;
+        cpp.push_str(&read);
+        writeln!(cpp, "}}")?;
+        writeln!(cpp)?;
+        Ok(())
+    }
+
+    fn unknown_variant(&self, read: &mut String, enum_ident: &str) -> Result<()> {
+        writeln!(read, "    default:")?;
+        writeln!(
+            read,
+            "        throw ww::Error(\"enum {enum_ident} does not have variant: \" + std::to_string(discriminant));"
+        )?;
+        writeln!(read, "    }}")?;
+        Ok(())
+    }
+
+    fn write_fields(
+        &self,
+        cpp: &mut String,
+        fields: &FieldsOwned,
+        owner: &str,
+        indent: usize,
+    ) -> Result<()> {
+        let Some(fields) = fields_list(fields) else {
+            return Ok(());
+        };
+        let flags = flags(fields)?;
+        for (idx, field) in fields.iter().enumerate() {
+            if let Some(flagged_idx) = flags.flag_for[idx] {
+                let flagged = &fields[flagged_idx];
+                let expr = format!("{owner}{}", field_name(flagged, flagged_idx));
+                let is_some = match flagged.ty {
+                    TypeOwned::Result { .. } => format!("{expr}.is_ok()"),
+                    _ => format!("{expr}.has_value()"),
+                };
+                line(cpp, indent, &format!("wr.write_bool({is_some});"));
+            } else {
+                let expr = format!("{owner}{}", field_name(field, idx));
+                if flags.flagged[idx] {
+                    self.write_flagged(cpp, &field.ty, &expr, indent)?;
+                } else {
+                    self.write_sized(cpp, &field.ty, &expr, indent)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_fields(
+        &self,
+        cpp: &mut String,
+        fields: &FieldsOwned,
+        owner: &str,
+        indent: usize,
+    ) -> Result<()> {
+        let Some(fields) = fields_list(fields) else {
+            return Ok(());
+        };
+        let flags = flags(fields)?;
+        for flagged_idx in flags.flag_for.iter().flatten() {
+            line(cpp, indent, &format!("bool flag{flagged_idx} = false;"));
+        }
+        for (idx, field) in fields.iter().enumerate() {
+            let expr = format!("{owner}{}", field_name(field, idx));
+            let mut body = String::new();
+            let mut fallback = None;
+            if let Some(flagged_idx) = flags.flag_for[idx] {
+                line(
+                    &mut body,
+                    indent + 1,
+                    &format!("flag{flagged_idx} = rd.read_bool();"),
+                );
+                // flag is read as false if the Option it belongs to has a default and there is no more data
+                if fields[flagged_idx].default.is_some() {
+                    fallback = Some(format!("flag{flagged_idx} = false;"));
+                }
+            } else {
+                if flags.flagged[idx] {
+                    self.read_flagged(
+                        &mut body,
+                        &field.ty,
+                        &expr,
+                        &format!("flag{idx}"),
+                        "rd",
+                        indent + 1,
+                    )?;
+                } else {
+                    self.read_sized(&mut body, &field.ty, &expr, "rd", indent + 1)?;
+                }
+                fallback = match (&field.default, &field.ty) {
+                    // #[default = "None"] is stored as is_some flag value
+                    (Some(ValueOwned::Bool(false)), TypeOwned::Option { .. }) => {
+                        Some(format!("{expr}.reset();"))
+                    }
+                    (Some(default), _) => {
+                        Some(format!("{expr} = {};", self.value(default, &field.ty)?))
+                    }
+                    _ => None,
+                };
+            }
+            match fallback {
+                // fields added in later versions fall back to their defaults when reading older data
+                Some(fallback) => {
+                    line(cpp, indent, "try {");
+                    cpp.push_str(&body);
+                    line(cpp, indent, "} catch (const ww::Error&) {");
+                    line(cpp, indent + 1, &fallback);
+                    line(cpp, indent, "}");
+                }
+                None => cpp.push_str(&dedent(&body)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Same as BufWriter::write, size is put in front of unsized objects.
+    fn write_sized(
+        &self,
+        cpp: &mut String,
+        ty: &TypeOwned,
+        expr: &str,
+        indent: usize,
+    ) -> Result<()> {
+        if self.is_unsized(ty) {
+            line(cpp, indent, "wr.write_unsized([&] {");
+            self.write_inner(cpp, ty, expr, indent + 1)?;
+            line(cpp, indent, "});");
+            Ok(())
+        } else {
+            self.write_inner(cpp, ty, expr, indent)
+        }
+    }
+
+    fn write_inner(
+        &self,
+        cpp: &mut String,
+        ty: &TypeOwned,
+        expr: &str,
+        indent: usize,
+    ) -> Result<()> {
+        match ty {
+            TypeOwned::Bool => line(cpp, indent, &format!("wr.write_bool({expr});")),
+            TypeOwned::NumericAny(numeric) => {
+                line(cpp, indent, &write_num(numeric_base(numeric), expr))
+            }
+            TypeOwned::OutOfLine { type_idx } => {
+                let idx = type_idx.0 as usize;
+                match (self.type_names.get(idx), self.api_bundle.types.get(idx)) {
+                    (Some(Some(_)), _) => line(cpp, indent, &format!("ww_write(wr, {expr});")),
+                    (_, Some(TypeLocationOwned::InLine { ty, .. })) => {
+                        self.write_inner(cpp, ty, expr, indent)?
+                    }
+                    (_, Some(TypeLocationOwned::SkippedFullVersion { type_name, .. })) => line(
+                        cpp,
+                        indent,
+                        &format!("ww::unsupported({});", cpp_str(type_name)),
+                    ),
+                    _ => return Err(anyhow!("type #{idx} is not in the bundle")),
+                }
+            }
+            TypeOwned::Flag => return Err(anyhow!("Flag type cannot be used on its own")),
+            TypeOwned::String | TypeOwned::BoundedString { .. } => {
+                if let TypeOwned::BoundedString { max_len } = ty {
+                    line(
+                        cpp,
+                        indent,
+                        &format!(
+                            "ww::check_max_len(\"String\", {expr}.size(), {});",
+                            max_len.0
+                        ),
+                    );
+                }
+                line(cpp, indent, &format!("wr.write_raw_str({expr});"));
+            }
+            TypeOwned::Vec(inner) | TypeOwned::BoundedVec { ty: inner, .. } => {
+                if let TypeOwned::BoundedVec { max_len, .. } = ty {
+                    line(
+                        cpp,
+                        indent,
+                        &format!("ww::check_max_len(\"Vec\", {expr}.size(), {});", max_len.0),
+                    );
+                }
+                if is_bytes(ty) {
+                    line(cpp, indent, &format!("wr.write_byte_vec({expr});"));
+                } else {
+                    line(
+                        cpp,
+                        indent,
+                        &format!("wr.write_u16_rev(ww::BufWriter::len_u16({expr}.size()));"),
+                    );
+                    let item = self.var("item");
+                    line(
+                        cpp,
+                        indent,
+                        &format!("for (const auto& {item} : {expr}) {{"),
+                    );
+                    self.write_sized(cpp, inner, &item, indent + 1)?;
+                    line(cpp, indent, "}");
+                }
+            }
+            TypeOwned::Array { ty: inner, .. } => {
+                let item = self.var("item");
+                line(
+                    cpp,
+                    indent,
+                    &format!("for (const auto& {item} : {expr}) {{"),
+                );
+                self.write_sized(cpp, inner, &item, indent + 1)?;
+                line(cpp, indent, "}");
+            }
+            TypeOwned::Tuple(types) => {
+                for (idx, ty) in types.iter().enumerate() {
+                    self.write_sized(cpp, ty, &format!("std::get<{idx}>({expr})"), indent)?;
+                }
+            }
+            TypeOwned::Struct(_) | TypeOwned::Enum(_) => {
+                self.ty(ty)?;
+            }
+            TypeOwned::Option { .. } => {
+                line(cpp, indent, &format!("wr.write_bool({expr}.has_value());"));
+                self.write_flagged(cpp, ty, expr, indent)?;
+            }
+            TypeOwned::Result { .. } => {
+                line(cpp, indent, &format!("wr.write_bool({expr}.is_ok());"));
+                self.write_flagged(cpp, ty, expr, indent)?;
+            }
+            TypeOwned::Box(inner) => self.write_inner(cpp, inner, &format!("(*{expr})"), indent)?,
+            TypeOwned::Range(base) | TypeOwned::RangeInclusive(base) => {
+                line(cpp, indent, &write_num(base, &format!("{expr}.start")));
+                line(cpp, indent, &write_num(base, &format!("{expr}.end")));
+            }
+        }
+        Ok(())
+    }
+
+    /// Option or Result value without a flag.
+    fn write_flagged(
+        &self,
+        cpp: &mut String,
+        ty: &TypeOwned,
+        expr: &str,
+        indent: usize,
+    ) -> Result<()> {
+        match ty {
+            TypeOwned::Option { some_ty } => {
+                line(cpp, indent, &format!("if ({expr}) {{"));
+                self.write_sized(cpp, some_ty, &format!("(*{expr})"), indent + 1)?;
+                line(cpp, indent, "}");
+            }
+            TypeOwned::Result { ok_ty, err_ty } => {
+                line(cpp, indent, &format!("if ({expr}.is_ok()) {{"));
+                self.write_sized(cpp, ok_ty, &format!("{expr}.ok()"), indent + 1)?;
+                line(cpp, indent, "} else {");
+                self.write_sized(cpp, err_ty, &format!("{expr}.err()"), indent + 1)?;
+                line(cpp, indent, "}");
+            }
+            _ => {
+                return Err(anyhow!(
+                    "flag can only be used with Option or Result, got {ty:?}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Same as BufReader::read, unsized objects are read from a split reader.
+    fn read_sized(
+        &self,
+        cpp: &mut String,
+        ty: &TypeOwned,
+        target: &str,
+        rd: &str,
+        indent: usize,
+    ) -> Result<()> {
+        if self.is_unsized(ty) {
+            let split = self.var("rd");
+            line(cpp, indent, "{");
+            line(
+                cpp,
+                indent + 1,
+                &format!("ww::BufReader {split} = {rd}.split({rd}.read_unib32_rev());"),
+            );
+            self.read_inner(cpp, ty, target, &split, indent + 1)?;
+            line(cpp, indent, "}");
+            Ok(())
+        } else {
+            self.read_inner(cpp, ty, target, rd, indent)
+        }
+    }
+
+    fn read_inner(
+        &self,
+        cpp: &mut String,
+        ty: &TypeOwned,
+        target: &str,
+        rd: &str,
+        indent: usize,
+    ) -> Result<()> {
+        match ty {
+            TypeOwned::Bool => line(cpp, indent, &format!("{target} = {rd}.read_bool();")),
+            TypeOwned::NumericAny(numeric) => {
+                line(cpp, indent, &read_num(numeric_base(numeric), target, rd))
+            }
+            TypeOwned::OutOfLine { type_idx } => {
+                let idx = type_idx.0 as usize;
+                match (self.type_names.get(idx), self.api_bundle.types.get(idx)) {
+                    (Some(Some(_)), _) => line(cpp, indent, &format!("ww_read({rd}, {target});")),
+                    (_, Some(TypeLocationOwned::InLine { ty, .. })) => {
+                        self.read_inner(cpp, ty, target, rd, indent)?
+                    }
+                    (_, Some(TypeLocationOwned::SkippedFullVersion { type_name, .. })) => line(
+                        cpp,
+                        indent,
+                        &format!("ww::unsupported({});", cpp_str(type_name)),
+                    ),
+                    _ => return Err(anyhow!("type #{idx} is not in the bundle")),
+                }
+            }
+            TypeOwned::Flag => return Err(anyhow!("Flag type cannot be used on its own")),
+            TypeOwned::String | TypeOwned::BoundedString { .. } => {
+                line(cpp, indent, &format!("{target} = {rd}.read_raw_str();"));
+                if let TypeOwned::BoundedString { max_len } = ty {
+                    line(
+                        cpp,
+                        indent,
+                        &format!(
+                            "ww::check_max_len(\"String\", {target}.size(), {});",
+                            max_len.0
+                        ),
+                    );
+                }
+            }
+            TypeOwned::Vec(_) | TypeOwned::BoundedVec { .. } if is_bytes(ty) => {
+                line(cpp, indent, &format!("{target} = {rd}.read_byte_vec();"));
+                if let TypeOwned::BoundedVec { max_len, .. } = ty {
+                    line(
+                        cpp,
+                        indent,
+                        &format!(
+                            "ww::check_max_len(\"Vec\", {target}.size(), {});",
+                            max_len.0
+                        ),
+                    );
+                }
+            }
+            TypeOwned::Vec(inner) | TypeOwned::BoundedVec { ty: inner, .. } => {
+                let len = self.var("len");
+                let i = self.var("i");
+                line(cpp, indent, "{");
+                line(
+                    cpp,
+                    indent + 1,
+                    &format!("uint32_t {len} = {rd}.read_unib32_rev();"),
+                );
+                if let TypeOwned::BoundedVec { max_len, .. } = ty {
+                    line(
+                        cpp,
+                        indent + 1,
+                        &format!("ww::check_max_len(\"Vec\", {len}, {});", max_len.0),
+                    );
+                }
+                line(cpp, indent + 1, &format!("{target}.clear();"));
+                line(
+                    cpp,
+                    indent + 1,
+                    &format!("for (uint32_t {i} = 0; {i} < {len}; {i}++) {{"),
+                );
+                line(cpp, indent + 2, &format!("{target}.emplace_back();"));
+                self.read_sized(cpp, inner, &format!("{target}.back()"), rd, indent + 2)?;
+                line(cpp, indent + 1, "}");
+                line(cpp, indent, "}");
+            }
+            TypeOwned::Array { ty: inner, .. } => {
+                let item = self.var("item");
+                line(cpp, indent, &format!("for (auto& {item} : {target}) {{"));
+                self.read_sized(cpp, inner, &item, rd, indent + 1)?;
+                line(cpp, indent, "}");
+            }
+            TypeOwned::Tuple(types) => {
+                for (idx, ty) in types.iter().enumerate() {
+                    self.read_sized(cpp, ty, &format!("std::get<{idx}>({target})"), rd, indent)?;
+                }
+            }
+            TypeOwned::Struct(_) | TypeOwned::Enum(_) => {
+                self.ty(ty)?;
+            }
+            TypeOwned::Option { .. } | TypeOwned::Result { .. } => {
+                self.read_flagged(cpp, ty, target, &format!("{rd}.read_bool()"), rd, indent)?;
+            }
+            TypeOwned::Box(inner) => {
+                line(cpp, indent, &format!("{target}.emplace();"));
+                self.read_inner(cpp, inner, &format!("(*{target})"), rd, indent)?;
+            }
+            TypeOwned::Range(base) | TypeOwned::RangeInclusive(base) => {
+                line(cpp, indent, &read_num(base, &format!("{target}.start"), rd));
+                line(cpp, indent, &read_num(base, &format!("{target}.end"), rd));
+            }
+        }
+        Ok(())
+    }
+
+    /// Option or Result value, with the flag already read or to be read by the provided expression.
+    fn read_flagged(
+        &self,
+        cpp: &mut String,
+        ty: &TypeOwned,
+        target: &str,
+        flag: &str,
+        rd: &str,
+        indent: usize,
+    ) -> Result<()> {
+        match ty {
+            TypeOwned::Option { some_ty } => {
+                line(cpp, indent, &format!("if ({flag}) {{"));
+                line(cpp, indent + 1, &format!("{target}.emplace();"));
+                self.read_sized(cpp, some_ty, &format!("(*{target})"), rd, indent + 1)?;
+                line(cpp, indent, "} else {");
+                line(cpp, indent + 1, &format!("{target}.reset();"));
+                line(cpp, indent, "}");
+            }
+            TypeOwned::Result { ok_ty, err_ty } => {
+                line(cpp, indent, &format!("if ({flag}) {{"));
+                line(
+                    cpp,
+                    indent + 1,
+                    &format!("{target} = ww::Ok<{}>{{}};", self.ty(ok_ty)?),
+                );
+                self.read_sized(cpp, ok_ty, &format!("{target}.ok()"), rd, indent + 1)?;
+                line(cpp, indent, "} else {");
+                line(
+                    cpp,
+                    indent + 1,
+                    &format!("{target} = ww::Err<{}>{{}};", self.ty(err_ty)?),
+                );
+                self.read_sized(cpp, err_ty, &format!("{target}.err()"), rd, indent + 1)?;
+                line(cpp, indent, "}");
+            }
+            _ => {
+                return Err(anyhow!(
+                    "flag can only be used with Option or Result, got {ty:?}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// C++ expression of a value, used for field defaults.
+    fn value(&self, value: &ValueOwned, ty: &TypeOwned) -> Result<String> {
+        let resolved = ty.get_in_line(self.api_bundle)?;
+        let cpp = match (value, resolved) {
+            (_, TypeOwned::Box(inner)) => {
+                format!("{}({})", self.ty(ty)?, self.value(value, inner)?)
+            }
+            (ValueOwned::Bool(b), _) => b.to_string(),
+            (ValueOwned::Numeric(num), _) => cpp_num_value(num)?,
+            (ValueOwned::String(s), _) => format!("std::string({})", cpp_str(s)),
+            (
+                ValueOwned::Vec(items) | ValueOwned::Array(items),
+                TypeOwned::Vec(item_ty)
+                | TypeOwned::BoundedVec { ty: item_ty, .. }
+                | TypeOwned::Array { ty: item_ty, .. },
+            ) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.value(item, item_ty))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("{}{{{}}}", self.ty(ty)?, items.join(", "))
+            }
+            (ValueOwned::Tuple(items), TypeOwned::Tuple(types)) => {
+                let items = items
+                    .iter()
+                    .zip(types)
+                    .map(|(item, ty)| self.value(item, ty))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("{}{{{}}}", self.ty(ty)?, items.join(", "))
+            }
+            (ValueOwned::Struct { fields }, TypeOwned::Struct(item_struct)) => format!(
+                "{}{{{}}}",
+                self.ty(ty)?,
+                self.fields_value(fields, &item_struct.fields)?
+            ),
+            (ValueOwned::Enum { variant, fields }, TypeOwned::Enum(item_enum)) => {
+                let variant_def = item_enum
+                    .variants
+                    .iter()
+                    .find(|v| &v.ident == variant)
+                    .ok_or_else(|| {
+                        anyhow!("enum {} does not have variant {variant}", item_enum.ident)
+                    })?;
+                let enum_ty = self.ty(ty)?;
+                if is_unit_only(item_enum) {
+                    format!("{enum_ty}::{}", ident(variant))
+                } else {
+                    format!(
+                        "{enum_ty}{{{enum_ty}::{}{{{}}}}}",
+                        variant_name(&item_enum.ident, variant),
+                        self.fields_value(fields, &variant_def.fields)?
+                    )
+                }
+            }
+            (ValueOwned::Option(None), _) => "std::nullopt".into(),
+            (ValueOwned::Option(Some(value)), TypeOwned::Option { some_ty }) => {
+                format!("{}({})", self.ty(ty)?, self.value(value, some_ty)?)
+            }
+            (ValueOwned::Result(Ok(value)), TypeOwned::Result { ok_ty, .. }) => format!(
+                "{}(ww::Ok<{}>{{{}}})",
+                self.ty(ty)?,
+                self.ty(ok_ty)?,
+                self.value(value, ok_ty)?
+            ),
+            (ValueOwned::Result(Err(value)), TypeOwned::Result { err_ty, .. }) => format!(
+                "{}(ww::Err<{}>{{{}}})",
+                self.ty(ty)?,
+                self.ty(err_ty)?,
+                self.value(value, err_ty)?
+            ),
+            (ValueOwned::Range(range), _) => format!(
+                "{}{{{}, {}}}",
+                self.ty(ty)?,
+                cpp_num_value(&range.start)?,
+                cpp_num_value(&range.end)?
+            ),
+            (ValueOwned::RangeInclusive(range), _) => format!(
+                "{}{{{}, {}}}",
+                self.ty(ty)?,
+                cpp_num_value(range.start())?,
+                cpp_num_value(range.end())?
+            ),
+            _ => return Err(anyhow!("value {value:?} does not match type {ty:?}")),
+        };
+        Ok(cpp)
+    }
+
+    /// Aggregate initializer of fields, in the order they are declared in.
+    fn fields_value(&self, values: &FieldsValueOwned, fields: &FieldsOwned) -> Result<String> {
+        let Some(defs) = fields_list(fields) else {
+            return Ok(String::new());
+        };
+        let flags = flags(defs)?;
+        let mut items = vec![];
+        for (idx, def) in defs.iter().enumerate() {
+            if flags.flag_for[idx].is_some() {
+                continue;
+            }
+            let value = match values {
+                // flag and the Option or Result it belongs to share the same name, the latter is the one needed
+                FieldsValueOwned::Named(values) => values
+                    .iter()
+                    .filter(|(name, _)| def.ident.as_deref() == Some(name.as_str()))
+                    .map(|(_, value)| value)
+                    .find(|value| {
+                        !(matches!(value, ValueOwned::Bool(_)) && is_option_or_result(&def.ty))
+                    }),
+                FieldsValueOwned::Unnamed(values) => values.get(idx),
+                FieldsValueOwned::Unit => None,
+            };
+            items.push(match value {
+                Some(value) => self.value(value, &def.ty)?,
+                None => "{}".into(),
+            });
+        }
+        Ok(items.join(", "))
+    }
+
+    fn client_class(
+        &self,
+        cpp: &mut String,
+        level: &ApiLevelOwned,
+        trait_idx: Option<usize>,
+    ) -> Result<()> {
+        let class_name = self.class_name(trait_idx)?;
+        cpp.push_str(&doc_comment(&level.docs, ""));
+        writeln!(cpp, "class {class_name} {{")?;
+        writeln!(cpp, "public:")?;
+        writeln!(
+            cpp,
+            "    explicit {class_name}(ww::Client& ww_client, std::vector<uint32_t> ww_path = {{}})"
+        )?;
+        writeln!(
+            cpp,
+            "        : ww_client_(&ww_client), ww_path_(std::move(ww_path)) {{}}"
+        )?;
+        for item in &level.items {
+            writeln!(cpp)?;
+            self.client_item_decl(cpp, item)?;
+        }
+        writeln!(cpp)?;
+        writeln!(cpp, "private:")?;
+        writeln!(cpp, "    ww::Client* ww_client_;")?;
+        writeln!(cpp, "    std::vector<uint32_t> ww_path_;")?;
+        writeln!(cpp, "}};")?;
+        writeln!(cpp)?;
+        Ok(())
+    }
+
+    /// Method signatures of an item: return type, name and parameters.
+    fn item_methods(&self, item: &ApiItemOwned) -> Result<Vec<(String, String, Vec<String>)>> {
+        let name = ident(&item.ident);
+        let with_index = |params: Vec<String>| {
+            let mut all = vec![];
+            if item.is_array() {
+                all.push("uint32_t index".to_string());
+            }
+            all.extend(params);
+            all
+        };
+        let mut methods = vec![];
+        match &item.kind {
+            ApiItemKindOwned::Method { args, return_ty } => {
+                let mut params = vec![];
+                for arg in args {
+                    params.push(format!("{} {}", self.param_ty(&arg.ty)?, ident(&arg.ident)));
+                }
+                let ret_ty = match return_ty {
+                    Some(ty) => self.ty(ty)?,
+                    None => "void".into(),
+                };
+                methods.push((format!("std::future<{ret_ty}>"), name, with_index(params)));
+            }
+            ApiItemKindOwned::Property { ty, access, .. } => {
+                if !matches!(access, PropertyAccess::WriteOnly) {
+                    methods.push((
+                        format!("std::future<{}>", self.ty(ty)?),
+                        format!("read_{}", item.ident),
+                        with_index(vec![]),
+                    ));
+                }
+                if matches!(
+                    access,
+                    PropertyAccess::ReadWrite { .. } | PropertyAccess::WriteOnly
+                ) {
+                    methods.push((
+                        "std::future<void>".into(),
+                        format!("write_{}", item.ident),
+                        with_index(vec![format!("{} value", self.param_ty(ty)?)]),
+                    ));
+                }
+            }
+            ApiItemKindOwned::Stream { ty, is_up } => {
+                if *is_up {
+                    methods.push((
+                        "std::future<void>".into(),
+                        format!("open_stream_{}", item.ident),
+                        with_index(vec![format!(
+                            "std::function<void(const {}&)> on_value",
+                            self.ty(ty)?
+                        )]),
+                    ));
+                    methods.push((
+                        "std::future<void>".into(),
+                        format!("close_stream_{}", item.ident),
+                        with_index(vec![]),
+                    ));
+                } else {
+                    methods.push((
+                        "std::future<void>".into(),
+                        format!("open_sink_{}", item.ident),
+                        with_index(vec![]),
+                    ));
+                    methods.push((
+                        "void".into(),
+                        format!("write_{}", item.ident),
+                        with_index(vec![format!("{} value", self.param_ty(ty)?)]),
+                    ));
+                    methods.push((
+                        "std::future<void>".into(),
+                        format!("close_sink_{}", item.ident),
+                        with_index(vec![]),
+                    ));
+                }
+            }
+            ApiItemKindOwned::Trait { trait_idx } => {
+                if let Some(Some(class_name)) = self.trait_class_names.get(trait_idx.0 as usize) {
+                    let params = if item.is_array() {
+                        vec!["uint32_t index".to_string()]
+                    } else {
+                        vec![]
+                    };
+                    methods.push((class_name.clone(), name, params));
+                }
+            }
+        }
+        Ok(methods)
+    }
+
+    fn client_item_decl(&self, cpp: &mut String, item: &ApiItemOwned) -> Result<()> {
+        let methods = self.item_methods(item)?;
+        if methods.is_empty()
+            && let ApiItemKindOwned::Trait { trait_idx } = &item.kind
+        {
+            writeln!(
+                cpp,
+                "    // {}: trait #{} is not included in the API bundle",
+                item.ident, trait_idx.0
+            )?;
+            return Ok(());
+        }
+        let docs = doc_comment(&item.docs, "    ");
+        for (idx, (ret_ty, name, params)) in methods.iter().enumerate() {
+            if idx > 0 {
+                writeln!(cpp)?;
+            }
+            cpp.push_str(&docs);
+            writeln!(cpp, "    {ret_ty} {name}({}) const;", params.join(", "))?;
+        }
+        Ok(())
+    }
+
+    fn client_item(&self, cpp: &mut String, class_name: &str, item: &ApiItemOwned) -> Result<()> {
+        let index_arg = if item.is_array() { ", index" } else { "" };
+        let path = format!("ww::path(ww_path_, {{{}{index_arg}}})", item.id.0);
+        self.next_var.set(0);
+        let mut bodies = vec![];
+        match &item.kind {
+            ApiItemKindOwned::Method { args, return_ty } => {
+                let mut body = String::new();
+                // arguments are sent as a struct, which is empty when there are none
+                if args.is_empty() {
+                    line(&mut body, 1, "std::vector<uint8_t> args;");
+                } else {
+                    line(&mut body, 1, "ww::BufWriter wr;");
+                    for arg in args {
+                        self.write_sized(&mut body, &arg.ty, &ident(&arg.ident), 1)?;
+                    }
+                    line(&mut body, 1, "std::vector<uint8_t> args = wr.finish();");
+                }
+                match return_ty {
+                    Some(ty) => {
+                        line(
+                            &mut body,
+                            1,
+                            &format!(
+                                "return ww::map_future(ww_client_->call({path}, std::move(args)), [](const std::vector<uint8_t>& bytes) {{"
+                            ),
+                        );
+                        self.decode_value(&mut body, ty, 2)?;
+                        line(&mut body, 2, "return value;");
+                        line(&mut body, 1, "});");
+                    }
+                    None => line(
+                        &mut body,
+                        1,
+                        &format!(
+                            "return ww::map_future(ww_client_->call({path}, std::move(args)), [](std::vector<uint8_t>) {{}});"
+                        ),
+                    ),
+                }
+                bodies.push(body);
+            }
+            ApiItemKindOwned::Property { ty, access, .. } => {
+                if !matches!(access, PropertyAccess::WriteOnly) {
+                    let mut body = String::new();
+                    line(
+                        &mut body,
+                        1,
+                        &format!(
+                            "return ww::map_future(ww_client_->read({path}), [](const std::vector<uint8_t>& bytes) {{"
+                        ),
+                    );
+                    self.decode_value(&mut body, ty, 2)?;
+                    line(&mut body, 2, "return value;");
+                    line(&mut body, 1, "});");
+                    bodies.push(body);
+                }
+                if matches!(
+                    access,
+                    PropertyAccess::ReadWrite { .. } | PropertyAccess::WriteOnly
+                ) {
+                    let mut body = String::new();
+                    line(&mut body, 1, "ww::BufWriter wr;");
+                    self.write_inner(&mut body, ty, "value", 1)?;
+                    line(
+                        &mut body,
+                        1,
+                        &format!("return ww_client_->write({path}, wr.finish());"),
+                    );
+                    bodies.push(body);
+                }
+            }
+            ApiItemKindOwned::Stream { ty, is_up } => {
+                let is_bytes = ty.is_byte_slice(self.api_bundle)?;
+                if *is_up {
+                    let mut body = String::new();
+                    if is_bytes {
+                        line(
+                            &mut body,
+                            1,
+                            &format!(
+                                "return ww_client_->open_stream({path}, std::move(on_value));"
+                            ),
+                        );
+                    } else {
+                        line(
+                            &mut body,
+                            1,
+                            &format!(
+                                "return ww_client_->open_stream({path}, [on_value = std::move(on_value)](const std::vector<uint8_t>& bytes) {{"
+                            ),
+                        );
+                        self.decode_value(&mut body, ty, 2)?;
+                        line(&mut body, 2, "on_value(value);");
+                        line(&mut body, 1, "});");
+                    }
+                    bodies.push(body);
+                } else {
+                    bodies.push(format!(
+                        "    return ww_client_->open_stream({path}, nullptr);\n"
+                    ));
+                    let mut body = String::new();
+                    if is_bytes {
+                        line(
+                            &mut body,
+                            1,
+                            &format!("ww_client_->notify({path}, ww::RequestKind::write(value));"),
+                        );
+                    } else {
+                        line(&mut body, 1, "ww::BufWriter wr;");
+                        self.write_inner(&mut body, ty, "value", 1)?;
+                        line(
+                            &mut body,
+                            1,
+                            &format!(
+                                "ww_client_->notify({path}, ww::RequestKind::write(wr.finish()));"
+                            ),
+                        );
+                    }
+                    bodies.push(body);
+                }
+                bodies.push(format!("    return ww_client_->close_stream({path});\n"));
+            }
+            ApiItemKindOwned::Trait { trait_idx } => {
+                if let Some(Some(trait_class)) = self.trait_class_names.get(trait_idx.0 as usize) {
+                    bodies.push(format!("    return {trait_class}(*ww_client_, {path});\n"));
+                }
+            }
+        }
+        for ((ret_ty, name, params), body) in self.item_methods(item)?.into_iter().zip(bodies) {
+            writeln!(
+                cpp,
+                "inline {ret_ty} {class_name}::{name}({}) const {{",
+                params.join(", ")
+            )?;
+            cpp.push_str(&body);
+            writeln!(cpp, "}}")?;
+            writeln!(cpp)?;
+        }
+        Ok(())
+    }
+
+    /// Declares `value` and decodes it from `bytes`, same as from_ww_bytes() does.
+    fn decode_value(&self, cpp: &mut String, ty: &TypeOwned, indent: usize) -> Result<()> {
+        line(cpp, indent, "ww::BufReader rd(bytes);");
+        line(cpp, indent, &format!("{} value{{}};", self.ty(ty)?));
+        self.read_inner(cpp, ty, "value", "rd", indent)
+    }
+}
+
+fn is_unit_only(item_enum: &ItemEnumOwned) -> bool {
+    item_enum
+        .variants
+        .iter()
+        .all(|variant| fields_list(&variant.fields).is_none_or(|fields| fields.is_empty()))
+}
+
+fn field_name(field: &FieldOwned, idx: usize) -> String {
+    match &field.ident {
+        Some(name) => ident(name),
+        None => format!("_{idx}"),
+    }
+}
+
+/// Nested variant struct name, which cannot be the same as the enclosing struct or its `value` member.
+fn variant_name(enum_ident: &str, variant_ident: &str) -> String {
+    if variant_ident == enum_ident || variant_ident == "value" {
+        format!("{variant_ident}_")
+    } else {
+        ident(variant_ident)
+    }
+}
+
+fn ident(name: &str) -> String {
+    if RESERVED.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+fn line(cpp: &mut String, indent: usize, code: &str) {
+    for _ in 0..indent {
+        cpp.push_str("    ");
+    }
+    cpp.push_str(code);
+    cpp.push('\n');
+}
+
+/// Removes one level of indentation, bodies are generated one level deeper in case they need to be wrapped in try.
+fn dedent(code: &str) -> String {
+    code.lines()
+        .map(|l| format!("{}\n", l.strip_prefix("    ").unwrap_or(l)))
+        .collect()
+}
+
+fn cpp_num_ty(base: &NumericBaseType) -> String {
+    let bits_ty = |prefix: &str, bits: u8| match bits {
+        0..=8 => format!("{prefix}int8_t"),
+        9..=16 => format!("{prefix}int16_t"),
+        17..=32 => format!("{prefix}int32_t"),
+        _ => format!("{prefix}int64_t"),
+    };
+    match base {
+        NumericBaseType::Nibble | NumericBaseType::U8 => "uint8_t".into(),
+        NumericBaseType::U16 => "uint16_t".into(),
+        NumericBaseType::U32 | NumericBaseType::UNib32 => "uint32_t".into(),
+        NumericBaseType::U64 => "uint64_t".into(),
+        NumericBaseType::U128 => "ww::U128".into(),
+        NumericBaseType::I8 => "int8_t".into(),
+        NumericBaseType::I16 => "int16_t".into(),
+        NumericBaseType::I32 => "int32_t".into(),
+        NumericBaseType::I64 => "int64_t".into(),
+        NumericBaseType::I128 => "ww::I128".into(),
+        NumericBaseType::F32 => "float".into(),
+        NumericBaseType::F64 => "double".into(),
+        NumericBaseType::UB(bits) => bits_ty("u", bits.0),
+        NumericBaseType::IB(bits) => bits_ty("", bits.0),
+        _ => "ww::Unsupported".into(),
+    }
+}
+
+fn write_num(base: &NumericBaseType, expr: &str) -> String {
+    match base {
+        NumericBaseType::Nibble => format!("wr.write_nib({expr});"),
+        NumericBaseType::U8 => format!("wr.write_u8({expr});"),
+        NumericBaseType::U16 => format!("wr.write_u16({expr});"),
+        NumericBaseType::U32 => format!("wr.write_u32({expr});"),
+        NumericBaseType::U64 => format!("wr.write_u64({expr});"),
+        NumericBaseType::UNib32 => format!("wr.write_unib32({expr});"),
+        NumericBaseType::U128 => format!("wr.write_u128({expr});"),
+        NumericBaseType::I8 => format!("wr.write_u8(static_cast<uint8_t>({expr}));"),
+        NumericBaseType::I16 => format!("wr.write_u16(static_cast<uint16_t>({expr}));"),
+        NumericBaseType::I32 => format!("wr.write_u32(static_cast<uint32_t>({expr}));"),
+        NumericBaseType::I64 => format!("wr.write_u64(static_cast<uint64_t>({expr}));"),
+        NumericBaseType::I128 => format!("wr.write_i128({expr});"),
+        NumericBaseType::F32 => format!("wr.write_f32({expr});"),
+        NumericBaseType::F64 => format!("wr.write_f64({expr});"),
+        NumericBaseType::UB(bits) => format!("wr.write_ub({}, {expr});", bits.0),
+        NumericBaseType::IB(bits) => format!("wr.write_ib({}, {expr});", bits.0),
+        other => format!("ww::unsupported({});", cpp_str(&format!("{other:?}"))),
+    }
+}
+
+fn read_num(base: &NumericBaseType, target: &str, rd: &str) -> String {
+    match base {
+        NumericBaseType::Nibble => format!("{target} = {rd}.read_nib();"),
+        NumericBaseType::U8 => format!("{target} = {rd}.read_u8();"),
+        NumericBaseType::U16 => format!("{target} = {rd}.read_u16();"),
+        NumericBaseType::U32 => format!("{target} = {rd}.read_u32();"),
+        NumericBaseType::U64 => format!("{target} = {rd}.read_u64();"),
+        NumericBaseType::UNib32 => format!("{target} = {rd}.read_unib32();"),
+        NumericBaseType::U128 => format!("{target} = {rd}.read_u128();"),
+        NumericBaseType::I8 => format!("{target} = static_cast<int8_t>({rd}.read_u8());"),
+        NumericBaseType::I16 => format!("{target} = static_cast<int16_t>({rd}.read_u16());"),
+        NumericBaseType::I32 => format!("{target} = static_cast<int32_t>({rd}.read_u32());"),
+        NumericBaseType::I64 => format!("{target} = static_cast<int64_t>({rd}.read_u64());"),
+        NumericBaseType::I128 => format!("{target} = {rd}.read_i128();"),
+        NumericBaseType::F32 => format!("{target} = {rd}.read_f32();"),
+        NumericBaseType::F64 => format!("{target} = {rd}.read_f64();"),
+        NumericBaseType::UB(bits) => format!(
+            "{target} = static_cast<{}>({rd}.read_un({}));",
+            cpp_num_ty(base),
+            bits.0
+        ),
+        NumericBaseType::IB(bits) => format!(
+            "{target} = static_cast<{}>(ww::sign_extend({rd}.read_un({}), {}));",
+            cpp_num_ty(base),
+            bits.0,
+            bits.0
+        ),
+        other => format!("ww::unsupported({});", cpp_str(&format!("{other:?}"))),
+    }
+}
+
+fn cpp_repr(repr: &Repr) -> (&'static str, u8) {
+    match repr {
+        Repr::Nibble => ("ww::Repr::Nib", 0),
+        Repr::BitAligned(bits) => ("ww::Repr::Bits", *bits),
+        Repr::UNib32 => ("ww::Repr::UNib32", 0),
+        Repr::ByteAlignedU8 => ("ww::Repr::U8", 0),
+        Repr::ByteAlignedU16 => ("ww::Repr::U16", 0),
+        Repr::ByteAlignedU32 => ("ww::Repr::U32", 0),
+    }
+}
+
+fn cpp_num_value(num: &NumericValue) -> Result<String> {
+    let cpp = match num {
+        NumericValue::Nibble(n) => n.value().to_string(),
+        NumericValue::U8(n) => n.to_string(),
+        NumericValue::U16(n) => n.to_string(),
+        NumericValue::U32(n) | NumericValue::UNib32(n) => format!("{n}u"),
+        NumericValue::U64(n) => format!("{n}ull"),
+        NumericValue::I8(n) => n.to_string(),
+        NumericValue::I16(n) => n.to_string(),
+        NumericValue::I32(n) if *n == i32::MIN => "INT32_MIN".into(),
+        NumericValue::I32(n) => n.to_string(),
+        NumericValue::I64(n) if *n == i64::MIN => "INT64_MIN".into(),
+        NumericValue::I64(n) => format!("{n}ll"),
+        NumericValue::U128(n) => format!("ww::U128{{{}ull, {}ull}}", *n as u64, (*n >> 64) as u64),
+        NumericValue::I128(n) => format!(
+            "ww::I128{{{}ull, static_cast<int64_t>({}ull)}}",
+            *n as u64,
+            (*n >> 64) as u64
+        ),
+        NumericValue::F32(n) => cpp_float(*n as f64, "float", "f"),
+        NumericValue::F64(n) => cpp_float(*n, "double", ""),
+        NumericValue::UN(_) | NumericValue::IN(_) => {
+            return Err(anyhow!("{num:?} is not supported in C++"));
+        }
+    };
+    Ok(cpp)
+}
+
+fn cpp_float(n: f64, ty: &str, suffix: &str) -> String {
+    if n.is_nan() {
+        format!("std::numeric_limits<{ty}>::quiet_NaN()")
+    } else if n.is_infinite() {
+        let sign = if n > 0.0 { "" } else { "-" };
+        format!("{sign}std::numeric_limits<{ty}>::infinity()")
+    } else {
+        format!("{n:?}{suffix}")
+    }
+}
+
+/// Line comments with documentation.
+fn doc_comment(docs: &[String], indent: &str) -> String {
+    docs.iter()
+        .map(|line| {
+            let line = line.strip_prefix(' ').unwrap_or(line);
+            if line.is_empty() {
+                format!("{indent}///\n")
+            } else {
+                format!("{indent}/// {line}\n")
+            }
+        })
+        .collect()
+}
+
+/// C++ string literal, bytes outside of printable ASCII are written as octal escapes.
+fn cpp_str(s: &str) -> String {
+    let mut cpp = String::with_capacity(s.len() + 2);
+    cpp.push('"');
+    for b in s.bytes() {
+        match b {
+            b'"' => cpp.push_str("\\\""),
+            b'\\' => cpp.push_str("\\\\"),
+            b'\n' => cpp.push_str("\\n"),
+            b'\r' => cpp.push_str("\\r"),
+            b'\t' => cpp.push_str("\\t"),
+            // trigraphs
+            b'?' => cpp.push_str("\\?"),
+            0x20..=0x7e => cpp.push(b as char),
+            b => cpp.push_str(&format!("\\{b:03o}")),
+        }
+    }
+    cpp.push('"');
+    cpp
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::cpp::gen_cpp;
+    use std::path::Path;
+
+    #[test]
+    fn methods_client() {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/methods_api");
+        let api_bundle = crate::load(&crate_path, Some("Methods".into()), true).unwrap();
+        let cpp = gen_cpp(&api_bundle).unwrap();
+        assert!(cpp.contains("class MethodsClient {"));
+        assert!(cpp.contains("std::future<void> one_plain_arg(uint8_t value) const;"));
+        assert!(cpp.contains(
+            "inline std::vector<uint8_t> encode_user_defined(const UserDefined& value) {"
+        ));
+    }
+}
@@ -0,0 +1,31 @@
+// Search over resources, traits, types, fields and variants, WW_SEARCH_INDEX is defined above.
+(function () {
+    const input = document.getElementById("ww-search");
+    const results = document.getElementById("ww-search-results");
+    const maxResults = 50;
+
+    input.addEventListener("input", function () {
+        const query = input.value.trim().toLowerCase();
+        results.replaceChildren();
+        if (query.length === 0) {
+            return;
+        }
+        for (const entry of WW_SEARCH_INDEX) {
+            const matches = entry.name.toLowerCase().includes(query) || entry.docs.toLowerCase().includes(query);
+            if (!matches) {
+                continue;
+            }
+            const li = document.createElement("li");
+            const a = document.createElement("a");
+            a.href = entry.url;
+            a.textContent = entry.name;
+            li.appendChild(a);
+            const details = entry.docs.length > 0 ? entry.kind + " - " + entry.docs : entry.kind;
+            li.appendChild(document.createTextNode(" " + details));
+            results.appendChild(li);
+            if (results.children.length >= maxResults) {
+                break;
+            }
+        }
+    });
+})();
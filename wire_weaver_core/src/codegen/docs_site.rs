@@ -0,0 +1,1170 @@
+//! Static HTML documentation site generator.
+//!
+//! Every trait level gets a page listing its resources with IDs, signatures, docs, wire format notes and example
+//! request bytes. User-defined types get a page each and are linked from all the signatures they appear in.
+//! Search index is emitted as a script instead of JSON, so that the site also works when opened directly from disk.
+//!
+//! Intended to be generated for each firmware release, either from `build.rs` with [write_docs_site] or with
+//! `ww api docs`, see docs/dev_tool.md.
+
+use crate::codegen::report::{element_size, fields_iter, html_escape, repr};
+use crate::codegen::server::introspect::api_signature;
+use anyhow::{Result, anyhow};
+use shrink_wrap::{ElementSize, SerializeShrinkWrap, UNib32};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use ww_numeric::{NumericAnyTypeOwned, NumericBaseType};
+use ww_self::{
+    ApiBundleOwned, ApiItemKindOwned, ApiItemOwned, ApiLevelLocationOwned, ApiLevelOwned,
+    FieldOwned, FieldsOwned, ItemStructOwned, Multiplicity, PropertyAccess, Repr,
+    TypeLocationOwned, TypeOwned, ValueOwned, VariantOwned,
+};
+
+const STYLE: &str = include_str!("docs_site.css");
+const SEARCH: &str = include_str!("docs_site.js");
+
+/// One file of a generated site, path is relative to the site root.
+#[derive(Clone, Debug)]
+pub struct DocsFile {
+    pub path: String,
+    pub contents: String,
+}
+
+/// Request shown as an example for a resource.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExampleRequest {
+    Call {
+        args: Vec<u8>,
+    },
+    Read,
+    Write {
+        data: Vec<u8>,
+    },
+    Subscribe,
+    /// `StreamSideband` with the `Open` command
+    OpenStream,
+}
+
+/// Serializes `ww_client_server::Request` with `seq` 1 and an absolute `path`.
+/// Provided by the caller, ww_client_server is built with wire_weaver and cannot be a dependency of this crate.
+pub type SerRequest = fn(path: &[u32], request: ExampleRequest) -> Result<Vec<u8>>;
+
+/// Generates a documentation site for the given API bundle, `index.html` is the entry point.
+/// ApiBundleOwned can be loaded using [crate::load] or [crate::load_dep].
+pub fn gen_docs_site(
+    api_bundle: &ApiBundleOwned,
+    ser_request: SerRequest,
+) -> Result<Vec<DocsFile>> {
+    Site::new(api_bundle, ser_request)?.files()
+}
+
+/// Generates a documentation site and writes it into `out_dir`, creating it if needed.
+pub fn write_docs_site(
+    api_bundle: &ApiBundleOwned,
+    ser_request: SerRequest,
+    out_dir: &Path,
+) -> Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    for file in gen_docs_site(api_bundle, ser_request)? {
+        std::fs::write(out_dir.join(&file.path), file.contents)?;
+    }
+    Ok(())
+}
+
+struct Site<'i> {
+    api_bundle: &'i ApiBundleOwned,
+    /// `crate_name::TraitName`
+    api: String,
+    version: String,
+    signature: String,
+    /// Where each inlined trait is implemented, by trait index, root level is stored under None
+    usages: BTreeMap<Option<u32>, Vec<Usage>>,
+    /// User-defined structs and enums in the order of first use
+    types: Vec<&'i TypeOwned>,
+    ser_request: SerRequest,
+}
+
+/// Location of a trait level in the resource tree.
+struct Usage {
+    /// e.g. `periph[].channel[]`
+    path: String,
+    /// Resource IDs with all array indices set to 0
+    ids: Vec<u32>,
+}
+
+struct SearchEntry {
+    name: String,
+    kind: &'static str,
+    url: String,
+    docs: String,
+}
+
+impl<'i> Site<'i> {
+    fn new(api_bundle: &'i ApiBundleOwned, ser_request: SerRequest) -> Result<Self> {
+        let root = &api_bundle.root;
+        let root_crate = api_bundle.crate_version(root.crate_idx.0)?;
+        let mut scratch = vec![0u8; 16_384];
+        let ww_self_bytes = api_bundle
+            .to_ww_bytes(&mut scratch)
+            .map_err(|e| anyhow!("Failed to serialize ApiBundle: {e:?}"))?;
+        let mut site = Site {
+            api_bundle,
+            api: format!("{}::{}", root_crate.crate_id, root.trait_name),
+            version: format!("{:?}", root_crate.version),
+            signature: hex::encode(api_signature(ww_self_bytes)),
+            usages: BTreeMap::new(),
+            types: vec![],
+            ser_request,
+        };
+        site.usages.insert(
+            None,
+            vec![Usage {
+                path: String::new(),
+                ids: vec![],
+            }],
+        );
+        site.walk(root, "", &[])?;
+        for location in &api_bundle.traits {
+            if let ApiLevelLocationOwned::InLine { level, .. } = location {
+                site.collect_level_types(level)?;
+            }
+        }
+        for location in &api_bundle.types {
+            if let TypeLocationOwned::InLine { ty, .. } = location {
+                site.collect_types(ty)?;
+            }
+        }
+        Ok(site)
+    }
+
+    fn walk(&mut self, level: &'i ApiLevelOwned, path: &str, ids: &[u32]) -> Result<()> {
+        self.collect_level_types(level)?;
+        for item in sorted_items(level) {
+            let ApiItemKindOwned::Trait { trait_idx } = &item.kind else {
+                continue;
+            };
+            let item_path = join_path(path, item);
+            let item_ids = item_ids(ids, item);
+            self.usages
+                .entry(Some(trait_idx.0))
+                .or_default()
+                .push(Usage {
+                    path: item_path.clone(),
+                    ids: item_ids.clone(),
+                });
+            let level = item.get_as_level(self.api_bundle)?;
+            self.walk(level, &item_path, &item_ids)?;
+        }
+        Ok(())
+    }
+
+    fn collect_level_types(&mut self, level: &'i ApiLevelOwned) -> Result<()> {
+        for item in &level.items {
+            match &item.kind {
+                ApiItemKindOwned::Method { args, return_ty } => {
+                    for arg in args {
+                        self.collect_types(&arg.ty)?;
+                    }
+                    if let Some(ty) = return_ty {
+                        self.collect_types(ty)?;
+                    }
+                }
+                ApiItemKindOwned::Property {
+                    ty, write_err_ty, ..
+                } => {
+                    self.collect_types(ty)?;
+                    if let Some(ty) = write_err_ty {
+                        self.collect_types(ty)?;
+                    }
+                }
+                ApiItemKindOwned::Stream { ty, .. } => self.collect_types(ty)?,
+                ApiItemKindOwned::Trait { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Collect user-defined structs and enums, including the ones only used in other types.
+    fn collect_types(&mut self, ty: &'i TypeOwned) -> Result<()> {
+        match ty {
+            TypeOwned::Struct(_) | TypeOwned::Enum(_) => {
+                let page = type_page(ty, self.api_bundle)?;
+                for known in &self.types {
+                    if type_page(known, self.api_bundle)? == page {
+                        return Ok(());
+                    }
+                }
+                self.types.push(ty);
+                match ty {
+                    TypeOwned::Struct(item_struct) => {
+                        for field in fields_iter(&item_struct.fields) {
+                            self.collect_types(&field.ty)?;
+                        }
+                    }
+                    TypeOwned::Enum(item_enum) => {
+                        for variant in &item_enum.variants {
+                            for field in fields_iter(&variant.fields) {
+                                self.collect_types(&field.ty)?;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            TypeOwned::OutOfLine { type_idx } => {
+                let (ty, _) = self.api_bundle.get_ty(type_idx.0)?;
+                self.collect_types(ty)?;
+            }
+            TypeOwned::Vec(ty)
+            | TypeOwned::Array { ty, .. }
+            | TypeOwned::BoundedVec { ty, .. }
+            | TypeOwned::Box(ty)
+            | TypeOwned::Option { some_ty: ty } => self.collect_types(ty)?,
+            TypeOwned::Result { ok_ty, err_ty } => {
+                self.collect_types(ok_ty)?;
+                self.collect_types(err_ty)?;
+            }
+            TypeOwned::Tuple(tys) => {
+                for ty in tys {
+                    self.collect_types(ty)?;
+                }
+            }
+            TypeOwned::Bool
+            | TypeOwned::NumericAny(_)
+            | TypeOwned::Flag
+            | TypeOwned::String
+            | TypeOwned::Range(_)
+            | TypeOwned::RangeInclusive(_)
+            | TypeOwned::BoundedString { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn files(&self) -> Result<Vec<DocsFile>> {
+        let mut search = vec![];
+        let mut files = vec![DocsFile {
+            path: "index.html".into(),
+            contents: self.index_page()?,
+        }];
+
+        let root = &self.api_bundle.root;
+        files.push(self.level_page(root, None, &mut search)?);
+        for (trait_idx, location) in self.api_bundle.traits.iter().enumerate() {
+            if let ApiLevelLocationOwned::InLine { level, .. } = location {
+                files.push(self.level_page(level, Some(trait_idx as u32), &mut search)?);
+            }
+        }
+        for ty in &self.types {
+            files.push(self.type_page(ty, &mut search)?);
+        }
+
+        let search: Vec<_> = search
+            .iter()
+            .map(|e| {
+                serde_json::json!({
+                    "name": e.name,
+                    "kind": e.kind,
+                    "url": e.url,
+                    "docs": e.docs,
+                })
+            })
+            .collect();
+        files.push(DocsFile {
+            path: "search.js".into(),
+            contents: format!(
+                "const WW_SEARCH_INDEX = {};\n\n{SEARCH}",
+                serde_json::to_string(&search)?
+            ),
+        });
+        files.push(DocsFile {
+            path: "style.css".into(),
+            contents: STYLE.into(),
+        });
+        Ok(files)
+    }
+
+    fn index_page(&self) -> Result<String> {
+        let root = &self.api_bundle.root;
+        let mut body = String::new();
+        writeln!(
+            body,
+            "<h1>{} <small>v{}</small></h1>",
+            html_escape(&self.api),
+            html_escape(&self.version)
+        )?;
+        writeln!(body, "<table class=\"summary\">")?;
+        writeln!(
+            body,
+            "<tr><td>Root trait</td><td><a href=\"{}\"><code>{}</code></a></td></tr>",
+            level_page(root, self.api_bundle)?,
+            html_escape(&self.api)
+        )?;
+        writeln!(
+            body,
+            "<tr><td>Signature</td><td><code>{}</code></td></tr>",
+            self.signature
+        )?;
+        writeln!(
+            body,
+            "<tr><td>ww_self version</td><td>{:?}</td></tr>",
+            self.api_bundle.ww_self_version
+        )?;
+        writeln!(body, "</table>")?;
+        body.push_str(&docs_html(&root.docs));
+
+        writeln!(body, "<h2>Resources</h2>")?;
+        self.resource_tree(&mut body, root, "")?;
+
+        writeln!(body, "<h2>Traits</h2>\n<ul>")?;
+        writeln!(
+            body,
+            "<li><a href=\"{}\"><code>{}</code></a>{}</li>",
+            level_page(root, self.api_bundle)?,
+            html_escape(&self.api),
+            summary(&root.docs)
+        )?;
+        for location in &self.api_bundle.traits {
+            match location {
+                ApiLevelLocationOwned::InLine { level, .. } => writeln!(
+                    body,
+                    "<li><a href=\"{}\"><code>{}</code></a>{}</li>",
+                    level_page(level, self.api_bundle)?,
+                    html_escape(&level_name(level, self.api_bundle)?),
+                    summary(&level.docs)
+                )?,
+                ApiLevelLocationOwned::SkippedFullVersion {
+                    crate_idx,
+                    trait_name,
+                    ..
+                } => writeln!(
+                    body,
+                    "<li><code>{}::{}</code> (definition not included)</li>",
+                    html_escape(self.api_bundle.crate_name(crate_idx.0)?),
+                    html_escape(trait_name)
+                )?,
+                ApiLevelLocationOwned::SkippedCompactVersion { trait_id, .. } => writeln!(
+                    body,
+                    "<li><code>trait #{}</code> (definition not included)</li>",
+                    trait_id.0
+                )?,
+            }
+        }
+        writeln!(body, "</ul>")?;
+
+        if !self.types.is_empty() {
+            writeln!(body, "<h2>Types</h2>\n<ul>")?;
+            for ty in &self.types {
+                let docs = match ty {
+                    TypeOwned::Struct(item_struct) => &item_struct.docs,
+                    TypeOwned::Enum(item_enum) => &item_enum.docs,
+                    _ => continue,
+                };
+                writeln!(body, "<li>{}{}</li>", self.ty_html(ty)?, summary(docs))?;
+            }
+            writeln!(body, "</ul>")?;
+        }
+        Ok(page(&self.api, &body))
+    }
+
+    /// Nested list of all the resources, each one linking to its definition in a trait page.
+    fn resource_tree(&self, body: &mut String, level: &ApiLevelOwned, path: &str) -> Result<()> {
+        let page = level_page(level, self.api_bundle)?;
+        writeln!(body, "<ul class=\"tree\">")?;
+        for item in sorted_items(level) {
+            let item_path = join_path(path, item);
+            write!(
+                body,
+                "<li><span class=\"id\">{}</span> <a href=\"{page}#{}\"><code>{}</code></a> <span class=\"kind\">{}</span>",
+                item.id.0,
+                html_escape(&item.ident),
+                html_escape(&item_path),
+                item_kind(item)
+            )?;
+            if let ApiItemKindOwned::Trait { .. } = &item.kind {
+                let level = item.get_as_level(self.api_bundle)?;
+                self.resource_tree(body, level, &item_path)?;
+            }
+            writeln!(body, "</li>")?;
+        }
+        writeln!(body, "</ul>")?;
+        Ok(())
+    }
+
+    fn level_page(
+        &self,
+        level: &ApiLevelOwned,
+        trait_idx: Option<u32>,
+        search: &mut Vec<SearchEntry>,
+    ) -> Result<DocsFile> {
+        let name = level_name(level, self.api_bundle)?;
+        let path = level_page(level, self.api_bundle)?;
+        search.push(SearchEntry {
+            name: name.clone(),
+            kind: "trait",
+            url: path.clone(),
+            docs: first_line(&level.docs),
+        });
+
+        let mut body = String::new();
+        writeln!(body, "<h1>trait <code>{}</code></h1>", html_escape(&name))?;
+        body.push_str(&docs_html(&level.docs));
+        let usages = self.usages.get(&trait_idx).map(Vec::as_slice);
+        let usages = usages.unwrap_or_default();
+        if trait_idx.is_some() && !usages.is_empty() {
+            writeln!(body, "<p>Implemented at:</p>\n<ul>")?;
+            for usage in usages {
+                writeln!(
+                    body,
+                    "<li><code>{}</code> <span class=\"id\">{}</span></li>",
+                    html_escape(&usage.path),
+                    ids_str(&usage.ids)
+                )?;
+            }
+            writeln!(body, "</ul>")?;
+        }
+
+        for item in sorted_items(level) {
+            let resource_path = match usages.first() {
+                Some(usage) => join_path(&usage.path, item),
+                None => join_path("", item),
+            };
+            search.push(SearchEntry {
+                name: resource_path,
+                kind: item_kind(item),
+                url: format!("{path}#{}", item.ident),
+                docs: first_line(&item.docs),
+            });
+            self.item_section(&mut body, item, usages.first())?;
+        }
+        Ok(DocsFile {
+            path,
+            contents: page(&name, &body),
+        })
+    }
+
+    fn item_section(
+        &self,
+        body: &mut String,
+        item: &ApiItemOwned,
+        usage: Option<&Usage>,
+    ) -> Result<()> {
+        let ident = html_escape(&item.ident);
+        writeln!(body, "<section class=\"item\" id=\"{ident}\">")?;
+        writeln!(
+            body,
+            "<h3><span class=\"id\">{}</span> <code>{ident}</code> <span class=\"kind\">{}</span></h3>",
+            item.id.0,
+            item_kind(item)
+        )?;
+        let array = match item.multiplicity {
+            Multiplicity::Flat => "",
+            Multiplicity::Array { .. } => "[]",
+        };
+        let mut sizes = vec![];
+        let signature = match &item.kind {
+            ApiItemKindOwned::Method { args, return_ty } => {
+                let mut args_def = vec![];
+                for arg in args {
+                    args_def.push(format!(
+                        "{}: {}",
+                        html_escape(&arg.ident),
+                        self.ty_html(&arg.ty)?
+                    ));
+                    sizes.push((arg.ident.clone(), self.wire_note(&arg.ty)?));
+                }
+                let mut signature = format!("fn {ident}{array}({})", args_def.join(", "));
+                if let Some(ty) = return_ty {
+                    write!(signature, " -&gt; {}", self.ty_html(ty)?)?;
+                    sizes.push(("return value".into(), self.wire_note(ty)?));
+                }
+                signature
+            }
+            ApiItemKindOwned::Property {
+                ty,
+                access,
+                write_err_ty,
+            } => {
+                let (access, observe) = match access {
+                    PropertyAccess::Const => ("const", false),
+                    PropertyAccess::ReadOnly { observe } => ("ro", *observe),
+                    PropertyAccess::ReadWrite { observe } => ("rw", *observe),
+                    PropertyAccess::WriteOnly => ("wo", false),
+                };
+                let mut signature = format!("{access} {ident}{array}: {}", self.ty_html(ty)?);
+                if observe {
+                    signature.push_str(", observe");
+                }
+                sizes.push(("value".into(), self.wire_note(ty)?));
+                if let Some(ty) = write_err_ty {
+                    write!(signature, ", on_set_err: {}", self.ty_html(ty)?)?;
+                    sizes.push(("write error".into(), self.wire_note(ty)?));
+                }
+                signature
+            }
+            ApiItemKindOwned::Stream { ty, is_up } => {
+                let kind = if *is_up { "stream" } else { "sink" };
+                sizes.push(("item".into(), self.wire_note(ty)?));
+                format!("{kind} {ident}{array}: {}", self.ty_html(ty)?)
+            }
+            ApiItemKindOwned::Trait { .. } => {
+                let level = item.get_as_level(self.api_bundle)?;
+                format!(
+                    "impl {ident}{array}: <a href=\"{}\">{}</a>",
+                    level_page(level, self.api_bundle)?,
+                    html_escape(&level_name(level, self.api_bundle)?)
+                )
+            }
+        };
+        writeln!(body, "<pre class=\"signature\">{signature}</pre>")?;
+        if let Some(since) = &item.since {
+            writeln!(body, "<p class=\"since\">Available since {since:?}</p>")?;
+        }
+        body.push_str(&docs_html(&item.docs));
+
+        if !sizes.is_empty() {
+            writeln!(body, "<h4>Wire format</h4>\n<ul class=\"wire\">")?;
+            for (name, note) in sizes {
+                writeln!(
+                    body,
+                    "<li>{}: {}</li>",
+                    html_escape(&name),
+                    html_escape(&note)
+                )?;
+            }
+            writeln!(body, "</ul>")?;
+        }
+
+        if let Some(usage) = usage {
+            let examples = self.examples(item, &item_ids(&usage.ids, item))?;
+            if !examples.is_empty() {
+                writeln!(
+                    body,
+                    "<h4>Example requests</h4>\n<table class=\"examples\">"
+                )?;
+                for (description, bytes) in examples {
+                    writeln!(
+                        body,
+                        "<tr><td>{}</td><td><code>{}</code></td></tr>",
+                        html_escape(&description),
+                        hex_dump(&bytes)
+                    )?;
+                }
+                writeln!(body, "</table>")?;
+            }
+        }
+        writeln!(body, "</section>")?;
+        Ok(())
+    }
+
+    fn type_page(&self, ty: &TypeOwned, search: &mut Vec<SearchEntry>) -> Result<DocsFile> {
+        let name = ty.human_name(true, self.api_bundle)?;
+        let path = type_page(ty, self.api_bundle)?;
+        let (kind, size, docs, members) = match ty {
+            TypeOwned::Struct(item_struct) => (
+                "struct",
+                &item_struct.size,
+                &item_struct.docs,
+                self.fields_rows(&item_struct.fields, &path, &name, search)?,
+            ),
+            TypeOwned::Enum(item_enum) => {
+                let mut rows = String::new();
+                for variant in &item_enum.variants {
+                    self.variant_row(&mut rows, variant, &path, &name, search)?;
+                }
+                ("enum", &item_enum.size, &item_enum.docs, rows)
+            }
+            _ => return Err(anyhow!("Only structs and enums have their own page")),
+        };
+        search.push(SearchEntry {
+            name: name.clone(),
+            kind,
+            url: path.clone(),
+            docs: first_line(docs),
+        });
+
+        let mut body = String::new();
+        writeln!(body, "<h1>{kind} <code>{}</code></h1>", html_escape(&name))?;
+        body.push_str(&docs_html(docs));
+        writeln!(body, "<h4>Wire format</h4>\n<ul class=\"wire\">")?;
+        writeln!(body, "<li>{}</li>", html_escape(&size_note(size)))?;
+        if let TypeOwned::Enum(item_enum) = ty {
+            writeln!(
+                body,
+                "<li>discriminant: {}</li>",
+                html_escape(&repr_note(&item_enum.repr))
+            )?;
+        }
+        let default = ValueOwned::default(ty, self.api_bundle)
+            .and_then(|value| value.ser_shrink_wrap_dyn(ty, self.api_bundle));
+        if let Ok(bytes) = default {
+            writeln!(
+                body,
+                "<li>default value: <code>{}</code></li>",
+                hex_dump(&bytes)
+            )?;
+        }
+        writeln!(body, "</ul>")?;
+        if !members.is_empty() {
+            if kind == "enum" {
+                writeln!(
+                    body,
+                    "<h2>Variants</h2>\n<table class=\"members\">\n<tr><th>Variant</th><th>Discriminant</th><th>Fields</th><th>Since</th><th>Description</th></tr>"
+                )?;
+            } else {
+                writeln!(
+                    body,
+                    "<h2>Fields</h2>\n<table class=\"members\">\n<tr><th>Field</th><th>Type</th><th>Since</th><th>Description</th></tr>"
+                )?;
+            }
+            body.push_str(&members);
+            writeln!(body, "</table>")?;
+        }
+        Ok(DocsFile {
+            path,
+            contents: page(&name, &body),
+        })
+    }
+
+    fn fields_rows(
+        &self,
+        fields: &FieldsOwned,
+        path: &str,
+        type_name: &str,
+        search: &mut Vec<SearchEntry>,
+    ) -> Result<String> {
+        let mut rows = String::new();
+        for (idx, field) in fields_iter(fields).iter().enumerate() {
+            let name = field.ident.clone().unwrap_or_else(|| idx.to_string());
+            search.push(SearchEntry {
+                name: format!("{type_name}::{name}"),
+                kind: "field",
+                url: format!("{path}#{name}"),
+                docs: first_line(&field.docs),
+            });
+            writeln!(
+                rows,
+                "<tr id=\"{0}\"><td><code>{0}</code></td><td><code>{1}</code></td><td>{2}</td><td>{3}</td></tr>",
+                html_escape(&name),
+                self.ty_html(&field.ty)?,
+                since_str(&field.since),
+                docs_inline(&field.docs)
+            )?;
+        }
+        Ok(rows)
+    }
+
+    fn variant_row(
+        &self,
+        rows: &mut String,
+        variant: &VariantOwned,
+        path: &str,
+        type_name: &str,
+        search: &mut Vec<SearchEntry>,
+    ) -> Result<()> {
+        search.push(SearchEntry {
+            name: format!("{type_name}::{}", variant.ident),
+            kind: "variant",
+            url: format!("{path}#{}", variant.ident),
+            docs: first_line(&variant.docs),
+        });
+        let mut fields = vec![];
+        for field in fields_iter(&variant.fields) {
+            let ty = self.ty_html(&field.ty)?;
+            match &field.ident {
+                Some(ident) => fields.push(format!("{}: {ty}", html_escape(ident))),
+                None => fields.push(ty),
+            }
+        }
+        let fields = match &variant.fields {
+            FieldsOwned::Named(_) => format!("{{ {} }}", fields.join(", ")),
+            FieldsOwned::Unnamed(_) => format!("({})", fields.join(", ")),
+            FieldsOwned::Unit => String::new(),
+        };
+        writeln!(
+            rows,
+            "<tr id=\"{0}\"><td><code>{0}</code></td><td>{1}</td><td><code>{2}</code></td><td>{3}</td><td>{4}</td></tr>",
+            html_escape(&variant.ident),
+            variant.discriminant.0,
+            fields,
+            since_str(&variant.since),
+            docs_inline(&variant.docs)
+        )?;
+        Ok(())
+    }
+
+    /// Type name with links to the pages of all user-defined types it consists of.
+    fn ty_html(&self, ty: &TypeOwned) -> Result<String> {
+        let api_bundle = self.api_bundle;
+        let html = match ty {
+            TypeOwned::Struct(_) | TypeOwned::Enum(_) => format!(
+                "<a href=\"{}\" title=\"{}\">{}</a>",
+                type_page(ty, api_bundle)?,
+                html_escape(&ty.human_name(true, api_bundle)?),
+                html_escape(&ty.human_name(false, api_bundle)?)
+            ),
+            TypeOwned::OutOfLine { type_idx } => self.ty_html(api_bundle.get_ty(type_idx.0)?.0)?,
+            TypeOwned::Vec(inner) => format!("Vec&lt;{}&gt;", self.ty_html(inner)?),
+            TypeOwned::Array { len, ty } => format!("[{}; {}]", self.ty_html(ty)?, len.0),
+            TypeOwned::Tuple(types) => {
+                let mut names = vec![];
+                for ty in types {
+                    names.push(self.ty_html(ty)?);
+                }
+                format!("({})", names.join(", "))
+            }
+            TypeOwned::Option { some_ty } => format!("Option&lt;{}&gt;", self.ty_html(some_ty)?),
+            TypeOwned::Result { ok_ty, err_ty } => format!(
+                "Result&lt;{}, {}&gt;",
+                self.ty_html(ok_ty)?,
+                self.ty_html(err_ty)?
+            ),
+            TypeOwned::Box(inner) => format!("Box&lt;{}&gt;", self.ty_html(inner)?),
+            TypeOwned::BoundedVec { max_len, ty } => {
+                format!("BoundedVec&lt;{}, {}&gt;", self.ty_html(ty)?, max_len.0)
+            }
+            _ => html_escape(&ty.human_name(false, api_bundle)?),
+        };
+        Ok(html)
+    }
+
+    /// Short human-readable description of how a value of this type is laid out on the wire.
+    fn wire_note(&self, ty: &TypeOwned) -> Result<String> {
+        let note = match ty {
+            TypeOwned::Bool | TypeOwned::Flag => "1 bit".into(),
+            TypeOwned::NumericAny(NumericAnyTypeOwned::Base(base)) => numeric_note(base),
+            TypeOwned::NumericAny(_) => "numeric".into(),
+            TypeOwned::OutOfLine { type_idx } => {
+                self.wire_note(self.api_bundle.get_ty(type_idx.0)?.0)?
+            }
+            TypeOwned::String | TypeOwned::BoundedString { .. } => {
+                "UTF-8 bytes, length is stored in the back of the buffer".into()
+            }
+            TypeOwned::Vec(inner) | TypeOwned::BoundedVec { ty: inner, .. } => {
+                if ty.is_byte_slice(self.api_bundle)? {
+                    "bytes, length is stored in the back of the buffer".into()
+                } else {
+                    format!(
+                        "items one after another, number of items is stored in the back of the buffer, each item: {}",
+                        self.wire_note(inner)?
+                    )
+                }
+            }
+            TypeOwned::Array { len, ty } => format!("{} x ({})", len.0, self.wire_note(ty)?),
+            TypeOwned::Tuple(_) => "fields one after another".into(),
+            TypeOwned::Struct(item_struct) => size_note(&item_struct.size),
+            TypeOwned::Enum(item_enum) => format!(
+                "{}, discriminant: {}",
+                size_note(&item_enum.size),
+                repr_note(&item_enum.repr)
+            ),
+            TypeOwned::Option { some_ty } => {
+                format!(
+                    "1 bit flag, followed by {} if Some",
+                    self.wire_note(some_ty)?
+                )
+            }
+            TypeOwned::Result { .. } => "1 bit flag, followed by Ok or Err value".into(),
+            TypeOwned::Box(inner) => format!(
+                "{}, size is stored in the back of the buffer",
+                self.wire_note(inner)?
+            ),
+            TypeOwned::Range(base) | TypeOwned::RangeInclusive(base) => {
+                format!("start and end, {} each", numeric_note(base))
+            }
+        };
+        Ok(note)
+    }
+
+    /// Example requests for a resource, with default argument and property values, request ID of 1
+    /// and all array indices set to 0.
+    fn examples(&self, item: &ApiItemOwned, ids: &[u32]) -> Result<Vec<(String, Vec<u8>)>> {
+        let api_bundle = self.api_bundle;
+        let mut examples = vec![];
+        match &item.kind {
+            ApiItemKindOwned::Method { args, .. } => {
+                let args_ty = TypeOwned::Struct(ItemStructOwned {
+                    size: ElementSize::Unsized,
+                    crate_idx: UNib32(0),
+                    docs: vec![],
+                    ident: "Args".into(),
+                    fields: FieldsOwned::Named(
+                        args.iter()
+                            .map(|arg| field(&arg.ident, arg.ty.clone()))
+                            .collect(),
+                    ),
+                    generics: None,
+                });
+                let Ok(args_bytes) = ValueOwned::default(&args_ty, api_bundle)
+                    .and_then(|value| value.ser_shrink_wrap_dyn(&args_ty, api_bundle))
+                else {
+                    return Ok(examples);
+                };
+                let description = if args.is_empty() {
+                    "Call"
+                } else {
+                    "Call with default arguments"
+                };
+                examples.push((
+                    description.into(),
+                    (self.ser_request)(ids, ExampleRequest::Call { args: args_bytes })?,
+                ));
+            }
+            ApiItemKindOwned::Property { ty, access, .. } => {
+                let (read, write, observe) = match access {
+                    PropertyAccess::Const => (true, false, false),
+                    PropertyAccess::ReadOnly { observe } => (true, false, *observe),
+                    PropertyAccess::ReadWrite { observe } => (true, true, *observe),
+                    PropertyAccess::WriteOnly => (false, true, false),
+                };
+                if read {
+                    examples.push((
+                        "Read".into(),
+                        (self.ser_request)(ids, ExampleRequest::Read)?,
+                    ));
+                }
+                if write && let Some(data) = self.default_bytes(ty) {
+                    examples.push((
+                        "Write default value".into(),
+                        (self.ser_request)(ids, ExampleRequest::Write { data })?,
+                    ));
+                }
+                if observe {
+                    let request = (self.ser_request)(ids, ExampleRequest::Subscribe)?;
+                    examples.push(("Subscribe".into(), request));
+                }
+            }
+            ApiItemKindOwned::Stream { ty, is_up } => {
+                let request = (self.ser_request)(ids, ExampleRequest::OpenStream)?;
+                examples.push(("Open".into(), request));
+                if !is_up && let Some(data) = self.default_bytes(ty) {
+                    examples.push((
+                        "Write default value".into(),
+                        (self.ser_request)(ids, ExampleRequest::Write { data })?,
+                    ));
+                }
+            }
+            ApiItemKindOwned::Trait { .. } => {}
+        }
+        Ok(examples)
+    }
+
+    /// Byte slices are sent as is, everything else is serialized with shrink_wrap.
+    fn default_bytes(&self, ty: &TypeOwned) -> Option<Vec<u8>> {
+        if ty.is_byte_slice(self.api_bundle).ok()? {
+            return Some(vec![]);
+        }
+        let value = ValueOwned::default(ty, self.api_bundle).ok()?;
+        value.ser_shrink_wrap_dyn(ty, self.api_bundle).ok()
+    }
+}
+
+fn field(ident: &str, ty: TypeOwned) -> FieldOwned {
+    FieldOwned {
+        ident: Some(ident.into()),
+        default: None,
+        since: None,
+        ty,
+        docs: vec![],
+        deprecated: None,
+    }
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<link rel=\"stylesheet\" href=\"style.css\">
+</head>
+<body>
+<nav><a href=\"index.html\">Index</a> <input id=\"ww-search\" type=\"search\" placeholder=\"Search resources and types\" autocomplete=\"off\"></nav>
+<ul id=\"ww-search-results\"></ul>
+<main>
+{body}</main>
+<script src=\"search.js\"></script>
+</body>
+</html>
+",
+        html_escape(title)
+    )
+}
+
+fn sorted_items(level: &ApiLevelOwned) -> Vec<&ApiItemOwned> {
+    let mut items: Vec<_> = level.items.iter().collect();
+    items.sort_by_key(|item| item.id.0);
+    items
+}
+
+fn join_path(path: &str, item: &ApiItemOwned) -> String {
+    let array = if item.is_array() { "[]" } else { "" };
+    if path.is_empty() {
+        format!("{}{array}", item.ident)
+    } else {
+        format!("{path}.{}{array}", item.ident)
+    }
+}
+
+/// Array index comes right after the ID of an array resource.
+fn item_ids(ids: &[u32], item: &ApiItemOwned) -> Vec<u32> {
+    let mut ids = ids.to_vec();
+    ids.push(item.id.0);
+    if item.is_array() {
+        ids.push(0);
+    }
+    ids
+}
+
+fn ids_str(ids: &[u32]) -> String {
+    let ids: Vec<_> = ids.iter().map(|id| id.to_string()).collect();
+    format!("[{}]", ids.join(", "))
+}
+
+fn item_kind(item: &ApiItemOwned) -> &'static str {
+    match &item.kind {
+        ApiItemKindOwned::Method { .. } => "method",
+        ApiItemKindOwned::Property { .. } => "property",
+        ApiItemKindOwned::Stream { is_up: true, .. } => "stream",
+        ApiItemKindOwned::Stream { is_up: false, .. } => "sink",
+        ApiItemKindOwned::Trait { .. } => "trait",
+    }
+}
+
+fn level_name(level: &ApiLevelOwned, api_bundle: &ApiBundleOwned) -> Result<String> {
+    Ok(format!(
+        "{}::{}",
+        level.crate_name(api_bundle)?,
+        level.trait_name
+    ))
+}
+
+fn level_page(level: &ApiLevelOwned, api_bundle: &ApiBundleOwned) -> Result<String> {
+    Ok(format!(
+        "trait.{}.{}.html",
+        level.crate_name(api_bundle)?,
+        level.trait_name
+    ))
+}
+
+fn type_page(ty: &TypeOwned, api_bundle: &ApiBundleOwned) -> Result<String> {
+    let (crate_idx, ident, generics) = match ty {
+        TypeOwned::Struct(item_struct) => (
+            item_struct.crate_idx.0,
+            &item_struct.ident,
+            &item_struct.generics,
+        ),
+        TypeOwned::Enum(item_enum) => {
+            (item_enum.crate_idx.0, &item_enum.ident, &item_enum.generics)
+        }
+        _ => return Err(anyhow!("Only structs and enums have their own page")),
+    };
+    let ident = if generics.is_some() {
+        // each instance of a generic type gets its own page, e.g. Measurement<f32> -> Measurement_f32
+        let name: String = ty
+            .human_name(false, api_bundle)?
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        name.trim_end_matches('_').to_string()
+    } else {
+        ident.clone()
+    };
+    Ok(format!(
+        "type.{}.{ident}.html",
+        api_bundle.crate_name(crate_idx)?
+    ))
+}
+
+fn numeric_note(base: &NumericBaseType) -> String {
+    let bits = match base {
+        NumericBaseType::Nibble => 4,
+        NumericBaseType::U8 | NumericBaseType::I8 => 8,
+        NumericBaseType::U16 | NumericBaseType::I16 | NumericBaseType::F16 => 16,
+        NumericBaseType::U32 | NumericBaseType::I32 | NumericBaseType::F32 => 32,
+        NumericBaseType::U64 | NumericBaseType::I64 | NumericBaseType::F64 => 64,
+        NumericBaseType::U128 | NumericBaseType::I128 => 128,
+        NumericBaseType::UNib32 => return "1 to 11 nibbles, variable length".into(),
+        _ => return "numeric".into(),
+    };
+    if bits == 4 {
+        "4 bits".into()
+    } else {
+        format!("{bits} bits, little-endian, byte aligned")
+    }
+}
+
+fn size_note(size: &ElementSize) -> String {
+    match size {
+        ElementSize::Unsized => {
+            "Unsized, size is stored in the back of the buffer, can evolve in future versions"
+                .into()
+        }
+        ElementSize::UnsizedFinalStructure => {
+            "UnsizedFinalStructure, cannot evolve in future versions".into()
+        }
+        size => element_size(size),
+    }
+}
+
+fn repr_note(r: &Repr) -> String {
+    match r {
+        Repr::Nibble => "nib (4 bits)".into(),
+        Repr::UNib32 => "unib32 (1 to 11 nibbles)".into(),
+        r => repr(r),
+    }
+}
+
+fn since_str(since: &Option<ww_version::VersionTriplet>) -> String {
+    since.map(|since| format!("{since:?}")).unwrap_or_default()
+}
+
+fn first_line(docs: &[String]) -> String {
+    docs.iter()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// First line of docs prefixed with a space, for lists.
+fn summary(docs: &[String]) -> String {
+    let line = first_line(docs);
+    if line.is_empty() {
+        line
+    } else {
+        format!(" {}", html_escape(&line))
+    }
+}
+
+/// Doc comment lines joined into paragraphs, `code` spans are kept.
+fn docs_html(docs: &[String]) -> String {
+    let mut html = String::new();
+    let mut paragraph = vec![];
+    for line in docs.iter().map(|line| line.trim()).chain([""]) {
+        if line.is_empty() {
+            if !paragraph.is_empty() {
+                html.push_str(&format!("<p>{}</p>\n", code_spans(&paragraph.join(" "))));
+                paragraph.clear();
+            }
+        } else {
+            paragraph.push(line);
+        }
+    }
+    html
+}
+
+fn docs_inline(docs: &[String]) -> String {
+    let docs: Vec<_> = docs
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    code_spans(&docs.join(" "))
+}
+
+fn code_spans(text: &str) -> String {
+    let mut html = String::new();
+    for (idx, part) in text.split('`').enumerate() {
+        if idx % 2 == 1 {
+            html.push_str(&format!("<code>{}</code>", html_escape(part)));
+        } else {
+            html.push_str(&html_escape(part));
+        }
+    }
+    html
+}
+
+fn hex_dump(bytes: &[u8]) -> String {
+    let bytes: Vec<_> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    bytes.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ww_client_server::{PathKindOwned, RequestKindOwned, RequestOwned, StreamSidebandCommand};
+
+    fn ser_request(path: &[u32], request: ExampleRequest) -> Result<Vec<u8>> {
+        let kind = match request {
+            ExampleRequest::Call { args } => RequestKindOwned::Call { args },
+            ExampleRequest::Read => RequestKindOwned::Read,
+            ExampleRequest::Write { data } => RequestKindOwned::Write { data },
+            ExampleRequest::Subscribe => RequestKindOwned::Subscribe,
+            ExampleRequest::OpenStream => RequestKindOwned::StreamSideband {
+                sideband_cmd: StreamSidebandCommand::Open,
+            },
+        };
+        let request = RequestOwned {
+            seq: 1,
+            path_kind: PathKindOwned::Absolute {
+                path: path.iter().map(|id| UNib32(*id)).collect(),
+            },
+            kind,
+        };
+        Ok(shrink_wrap::to_ww_vec(&request, 1024)?)
+    }
+
+    fn load(api: &str, name: &str) -> ApiBundleOwned {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests")
+            .join(api);
+        crate::load(&crate_path, Some(name.into()), true).unwrap()
+    }
+
+    fn file<'a>(files: &'a [DocsFile], path: &str) -> &'a str {
+        &files
+            .iter()
+            .find(|f| f.path == path)
+            .unwrap_or_else(|| panic!("{path} not generated"))
+            .contents
+    }
+
+    #[test]
+    fn methods_site() {
+        let api_bundle = load("methods_api", "Methods");
+        let files = gen_docs_site(&api_bundle, ser_request).unwrap();
+        let index = file(&files, "index.html");
+        assert!(index.contains("<a href=\"trait.methods_api.Methods.html#plain_return\">"));
+
+        let methods = file(&files, "trait.methods_api.Methods.html");
+        assert!(methods.contains("<pre class=\"signature\">fn plain_return() -&gt; u8</pre>"));
+        assert!(methods.contains(
+            "fn user_arg(u: <a href=\"type.methods_api.UserDefined.html\" title=\"methods_api::UserDefined\">UserDefined</a>)"
+        ));
+        assert!(methods.contains("<li>return value: 8 bits, little-endian, byte aligned</li>"));
+        assert!(methods.contains("<p class=\"since\">Available since 0.1.1</p>"));
+
+        let user_defined = file(&files, "type.methods_api.UserDefined.html");
+        assert!(user_defined.contains("<h1>struct <code>methods_api::UserDefined</code></h1>"));
+        assert!(
+            user_defined.contains(
+                "<tr id=\"b\"><td><code>b</code></td><td><code>Vec&lt;u8&gt;</code></td>"
+            )
+        );
+
+        let search = file(&files, "search.js");
+        assert!(search.starts_with("const WW_SEARCH_INDEX = ["));
+        assert!(search.contains("\"name\":\"plain_return\""));
+        assert!(search.contains("\"name\":\"methods_api::UserDefined::b\""));
+    }
+
+    #[test]
+    fn example_requests() {
+        let api_bundle = load("methods_api", "Methods");
+        let files = gen_docs_site(&api_bundle, ser_request).unwrap();
+        let methods = file(&files, "trait.methods_api.Methods.html");
+        // seq = 1, Absolute path [2], Call with no arguments
+        assert!(methods.contains("<tr><td>Call</td><td><code>01 00 02 00 01</code></td></tr>"));
+
+        let api_bundle = load("traits_api", "Traits");
+        let files = gen_docs_site(&api_bundle, ser_request).unwrap();
+        let channel = file(&files, "trait.traits_api.Channel.html");
+        assert!(channel.contains(
+            "<li><code>periph[].channel[]</code> <span class=\"id\">[2, 0, 0, 0]</span></li>"
+        ));
+        // periph[0].channel[0].gain at [2, 0, 0, 0, 0]
+        assert!(channel.contains("<tr><td>Read</td><td><code>01 00 02 00 00 25</code></td></tr>"));
+    }
+}
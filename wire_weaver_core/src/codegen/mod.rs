@@ -1,7 +1,12 @@
 pub mod api_client;
 mod api_common;
 pub mod api_server;
+pub mod cpp;
+pub mod docs_site;
 mod index_chain;
+pub mod report;
 mod server;
 mod ty_def;
-mod util;
+pub mod typescript;
+pub(crate) mod util;
+pub mod wireshark;
@@ -0,0 +1,798 @@
+//! API report generator.
+//!
+//! Report lists every resource with its numeric path, all the traits with their global IDs, user-defined types with
+//! their size and representation, and the API signature. It is meant to be generated for each release and stored
+//! alongside it, so that firmware, test and support teams can look up resource IDs and compare versions.
+//!
+//! Output is deterministic: resources are ordered by ID, types and traits in the order of first use, and no timestamps
+//! are included, so that two reports can be compared with a regular diff tool.
+
+use crate::codegen::server::introspect::api_signature;
+use anyhow::{Result, anyhow};
+use shrink_wrap::{ElementSize, SerializeShrinkWrap};
+use std::fmt::Write;
+use std::str::FromStr;
+use ww_self::{
+    ApiBundleOwned, ApiItemKindOwned, ApiLevelLocationOwned, ApiLevelOwned, FieldsOwned,
+    Multiplicity, PropertyAccess, Repr, TypeLocationOwned, TypeOwned,
+};
+
+/// Output format of [gen_api_report].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            "html" | "htm" => Ok(ReportFormat::Html),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!(
+                "unknown report format '{s}', expected md, html or json"
+            )),
+        }
+    }
+}
+
+/// Generates an API report for the given API bundle.
+/// ApiBundleOwned can be loaded using [crate::load] or [crate::load_dep].
+pub fn gen_api_report(api_bundle: &ApiBundleOwned, format: ReportFormat) -> Result<String> {
+    let report = Report::new(api_bundle)?;
+    match format {
+        ReportFormat::Markdown => report.markdown(),
+        ReportFormat::Html => report.html(),
+        ReportFormat::Json => Ok(serde_json::to_string_pretty(&report.json())? + "\n"),
+    }
+}
+
+struct Report {
+    /// `crate_name::TraitName`
+    api: String,
+    version: String,
+    signature: String,
+    ww_self_version: String,
+    traits: Vec<ReportTrait>,
+    resources: Vec<ReportResource>,
+    types: Vec<ReportType>,
+}
+
+struct ReportTrait {
+    name: String,
+    /// `crate_name@major.minor.patch`
+    full_version: String,
+    compact_version: Option<String>,
+    /// Trait definition is not included into the bundle, only its signature
+    skipped: bool,
+    docs: Vec<String>,
+}
+
+struct ReportResource {
+    /// IDs on the way to a resource, `[]` marks where an array index is inserted, e.g. `2[].0[].1`
+    numeric_path: String,
+    path: String,
+    kind: &'static str,
+    signature: String,
+    since: Option<String>,
+    docs: Vec<String>,
+}
+
+struct ReportType {
+    name: String,
+    kind: &'static str,
+    size: String,
+    repr: Option<String>,
+    docs: Vec<String>,
+    members: Vec<ReportMember>,
+}
+
+/// Struct field or enum variant.
+struct ReportMember {
+    name: String,
+    discriminant: Option<u32>,
+    /// Field type or variant fields
+    ty: String,
+    since: Option<String>,
+    docs: Vec<String>,
+}
+
+impl Report {
+    fn new(api_bundle: &ApiBundleOwned) -> Result<Self> {
+        let root = &api_bundle.root;
+        let root_crate = api_bundle.crate_version(root.crate_idx.0)?;
+        let mut scratch = vec![0u8; 16_384];
+        let ww_self_bytes = api_bundle
+            .to_ww_bytes(&mut scratch)
+            .map_err(|e| anyhow!("Failed to serialize ApiBundle: {e:?}"))?;
+
+        let mut traits = vec![ReportTrait {
+            name: format!("{}::{}", root_crate.crate_id, root.trait_name),
+            full_version: format!("{root_crate:?}"),
+            compact_version: None,
+            skipped: false,
+            docs: root.docs.clone(),
+        }];
+        for location in &api_bundle.traits {
+            traits.push(match location {
+                ApiLevelLocationOwned::InLine { level, crate_idx } => {
+                    let full_version = api_bundle.crate_version(crate_idx.0)?;
+                    ReportTrait {
+                        name: format!("{}::{}", full_version.crate_id, level.trait_name),
+                        full_version: format!("{full_version:?}"),
+                        compact_version: None,
+                        skipped: false,
+                        docs: level.docs.clone(),
+                    }
+                }
+                ApiLevelLocationOwned::SkippedFullVersion {
+                    crate_idx,
+                    trait_name,
+                    ..
+                } => {
+                    let full_version = api_bundle.crate_version(crate_idx.0)?;
+                    ReportTrait {
+                        name: format!("{}::{trait_name}", full_version.crate_id),
+                        full_version: format!("{full_version:?}"),
+                        compact_version: None,
+                        skipped: true,
+                        docs: vec![],
+                    }
+                }
+                ApiLevelLocationOwned::SkippedCompactVersion {
+                    version, trait_id, ..
+                } => ReportTrait {
+                    name: format!("trait #{}", trait_id.0),
+                    full_version: String::new(),
+                    compact_version: Some(format!("{version:?}")),
+                    skipped: true,
+                    docs: vec![],
+                },
+            });
+        }
+
+        let mut resources = vec![];
+        collect_resources(api_bundle, root, "", "", &mut resources)?;
+
+        let mut types = vec![];
+        for location in &api_bundle.types {
+            match location {
+                TypeLocationOwned::InLine { ty, .. } => collect_types(api_bundle, ty, &mut types)?,
+                TypeLocationOwned::SkippedFullVersion {
+                    crate_idx,
+                    type_name,
+                    ..
+                } => types.push(ReportType {
+                    name: format!("{}::{type_name}", api_bundle.crate_name(crate_idx.0)?),
+                    kind: "external",
+                    size: String::new(),
+                    repr: None,
+                    docs: vec![],
+                    members: vec![],
+                }),
+            }
+        }
+
+        Ok(Report {
+            api: format!("{}::{}", root_crate.crate_id, root.trait_name),
+            version: format!("{:?}", root_crate.version),
+            signature: hex::encode(api_signature(ww_self_bytes)),
+            ww_self_version: format!("{:?}", api_bundle.ww_self_version),
+            traits,
+            resources,
+            types,
+        })
+    }
+
+    fn json(&self) -> serde_json::Value {
+        let traits: Vec<_> = self
+            .traits
+            .iter()
+            .map(|t| {
+                serde_json::json!({
+                    "name": t.name,
+                    "full_version": t.full_version,
+                    "compact_version": t.compact_version,
+                    "skipped": t.skipped,
+                    "docs": t.docs,
+                })
+            })
+            .collect();
+        let resources: Vec<_> = self
+            .resources
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "numeric_path": r.numeric_path,
+                    "path": r.path,
+                    "kind": r.kind,
+                    "signature": r.signature,
+                    "since": r.since,
+                    "docs": r.docs,
+                })
+            })
+            .collect();
+        let types: Vec<_> = self
+            .types
+            .iter()
+            .map(|t| {
+                let members: Vec<_> = t
+                    .members
+                    .iter()
+                    .map(|m| {
+                        serde_json::json!({
+                            "name": m.name,
+                            "discriminant": m.discriminant,
+                            "ty": m.ty,
+                            "since": m.since,
+                            "docs": m.docs,
+                        })
+                    })
+                    .collect();
+                serde_json::json!({
+                    "name": t.name,
+                    "kind": t.kind,
+                    "size": t.size,
+                    "repr": t.repr,
+                    "docs": t.docs,
+                    "members": members,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "api": self.api,
+            "version": self.version,
+            "signature": self.signature,
+            "ww_self_version": self.ww_self_version,
+            "traits": traits,
+            "resources": resources,
+            "types": types,
+        })
+    }
+
+    fn markdown(&self) -> Result<String> {
+        let mut md = String::new();
+        writeln!(md, "# API report: {} v{}\n", self.api, self.version)?;
+        writeln!(md, "| | |")?;
+        writeln!(md, "|---|---|")?;
+        writeln!(md, "| API | `{}` |", self.api)?;
+        writeln!(md, "| Version | {} |", self.version)?;
+        writeln!(md, "| Signature | `{}` |", self.signature)?;
+        writeln!(md, "| ww_self version | {} |", self.ww_self_version)?;
+
+        writeln!(md, "\n## Traits\n")?;
+        writeln!(
+            md,
+            "| Trait | Full version | Compact version | Description |"
+        )?;
+        writeln!(md, "|---|---|---|---|")?;
+        for t in &self.traits {
+            writeln!(
+                md,
+                "| `{}` | {} | {} | {} |",
+                t.name,
+                md_code(&t.full_version),
+                md_code(t.compact_version.as_deref().unwrap_or_default()),
+                md_cell(&docs_line(&t.docs, t.skipped))
+            )?;
+        }
+
+        writeln!(md, "\n## Resources\n")?;
+        writeln!(md, "| ID | Path | Kind | Signature | Since | Description |")?;
+        writeln!(md, "|---|---|---|---|---|---|")?;
+        for r in &self.resources {
+            writeln!(
+                md,
+                "| `{}` | `{}` | {} | {} | {} | {} |",
+                r.numeric_path,
+                r.path,
+                r.kind,
+                md_code(&r.signature),
+                r.since.as_deref().unwrap_or_default(),
+                md_cell(&docs_line(&r.docs, false))
+            )?;
+        }
+
+        if !self.types.is_empty() {
+            writeln!(md, "\n## Types")?;
+        }
+        for t in &self.types {
+            writeln!(md, "\n### `{}`\n", t.name)?;
+            write!(md, "{}", t.kind)?;
+            if !t.size.is_empty() {
+                write!(md, ", size: {}", t.size)?;
+            }
+            if let Some(repr) = &t.repr {
+                write!(md, ", repr: {repr}")?;
+            }
+            writeln!(md)?;
+            if !t.docs.is_empty() {
+                writeln!(md, "\n{}", docs_line(&t.docs, false))?;
+            }
+            if t.members.is_empty() {
+                continue;
+            }
+            if t.kind == "enum" {
+                writeln!(
+                    md,
+                    "\n| Variant | Discriminant | Fields | Since | Description |"
+                )?;
+                writeln!(md, "|---|---|---|---|---|")?;
+            } else {
+                writeln!(md, "\n| Field | Type | Since | Description |")?;
+                writeln!(md, "|---|---|---|---|")?;
+            }
+            for m in &t.members {
+                let since = m.since.as_deref().unwrap_or_default();
+                let docs = md_cell(&docs_line(&m.docs, false));
+                match m.discriminant {
+                    Some(discriminant) => writeln!(
+                        md,
+                        "| `{}` | {discriminant} | {} | {since} | {docs} |",
+                        m.name,
+                        md_code(&m.ty)
+                    )?,
+                    None => writeln!(
+                        md,
+                        "| `{}` | {} | {since} | {docs} |",
+                        m.name,
+                        md_code(&m.ty)
+                    )?,
+                }
+            }
+        }
+        Ok(md)
+    }
+
+    fn html(&self) -> Result<String> {
+        let mut html = String::new();
+        let title = format!("API report: {} v{}", self.api, self.version);
+        writeln!(html, "<!DOCTYPE html>")?;
+        writeln!(html, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(html, "<title>{}</title>", html_escape(&title))?;
+        writeln!(
+            html,
+            "<style>body {{ font-family: sans-serif; }} table {{ border-collapse: collapse; }} \
+            th, td {{ border: 1px solid #ccc; padding: 2px 8px; text-align: left; }}</style>"
+        )?;
+        writeln!(html, "</head>\n<body>")?;
+        writeln!(html, "<h1>{}</h1>", html_escape(&title))?;
+        writeln!(html, "<table>")?;
+        html_row(&mut html, "td", &["API", &html_code(&self.api)])?;
+        html_row(&mut html, "td", &["Version", &html_escape(&self.version)])?;
+        html_row(&mut html, "td", &["Signature", &html_code(&self.signature)])?;
+        html_row(
+            &mut html,
+            "td",
+            &["ww_self version", &html_escape(&self.ww_self_version)],
+        )?;
+        writeln!(html, "</table>")?;
+
+        writeln!(html, "<h2>Traits</h2>\n<table>")?;
+        html_row(
+            &mut html,
+            "th",
+            &["Trait", "Full version", "Compact version", "Description"],
+        )?;
+        for t in &self.traits {
+            html_row(
+                &mut html,
+                "td",
+                &[
+                    &html_code(&t.name),
+                    &html_code(&t.full_version),
+                    &html_code(t.compact_version.as_deref().unwrap_or_default()),
+                    &html_escape(&docs_line(&t.docs, t.skipped)),
+                ],
+            )?;
+        }
+        writeln!(html, "</table>")?;
+
+        writeln!(html, "<h2>Resources</h2>\n<table>")?;
+        html_row(
+            &mut html,
+            "th",
+            &["ID", "Path", "Kind", "Signature", "Since", "Description"],
+        )?;
+        for r in &self.resources {
+            html_row(
+                &mut html,
+                "td",
+                &[
+                    &html_code(&r.numeric_path),
+                    &html_code(&r.path),
+                    r.kind,
+                    &html_code(&r.signature),
+                    &html_escape(r.since.as_deref().unwrap_or_default()),
+                    &html_escape(&docs_line(&r.docs, false)),
+                ],
+            )?;
+        }
+        writeln!(html, "</table>")?;
+
+        if !self.types.is_empty() {
+            writeln!(html, "<h2>Types</h2>")?;
+        }
+        for t in &self.types {
+            writeln!(html, "<h3>{}</h3>", html_code(&t.name))?;
+            let mut summary = t.kind.to_string();
+            if !t.size.is_empty() {
+                write!(summary, ", size: {}", t.size)?;
+            }
+            if let Some(repr) = &t.repr {
+                write!(summary, ", repr: {repr}")?;
+            }
+            writeln!(html, "<p>{}</p>", html_escape(&summary))?;
+            if !t.docs.is_empty() {
+                writeln!(html, "<p>{}</p>", html_escape(&docs_line(&t.docs, false)))?;
+            }
+            if t.members.is_empty() {
+                continue;
+            }
+            writeln!(html, "<table>")?;
+            if t.kind == "enum" {
+                html_row(
+                    &mut html,
+                    "th",
+                    &["Variant", "Discriminant", "Fields", "Since", "Description"],
+                )?;
+            } else {
+                html_row(&mut html, "th", &["Field", "Type", "Since", "Description"])?;
+            }
+            for m in &t.members {
+                let since = html_escape(m.since.as_deref().unwrap_or_default());
+                let docs = html_escape(&docs_line(&m.docs, false));
+                match m.discriminant {
+                    Some(discriminant) => html_row(
+                        &mut html,
+                        "td",
+                        &[
+                            &html_code(&m.name),
+                            &discriminant.to_string(),
+                            &html_code(&m.ty),
+                            &since,
+                            &docs,
+                        ],
+                    )?,
+                    None => html_row(
+                        &mut html,
+                        "td",
+                        &[&html_code(&m.name), &html_code(&m.ty), &since, &docs],
+                    )?,
+                }
+            }
+            writeln!(html, "</table>")?;
+        }
+        writeln!(html, "</body>\n</html>")?;
+        Ok(html)
+    }
+}
+
+fn collect_resources(
+    api_bundle: &ApiBundleOwned,
+    level: &ApiLevelOwned,
+    numeric_path: &str,
+    path: &str,
+    resources: &mut Vec<ReportResource>,
+) -> Result<()> {
+    let mut items: Vec<_> = level.items.iter().collect();
+    items.sort_by_key(|item| item.id.0);
+    for item in items {
+        let array = match item.multiplicity {
+            Multiplicity::Flat => "",
+            Multiplicity::Array { .. } => "[]",
+        };
+        let join = |prefix: &str, name: String| {
+            if prefix.is_empty() {
+                format!("{name}{array}")
+            } else {
+                format!("{prefix}.{name}{array}")
+            }
+        };
+        let item_numeric_path = join(numeric_path, item.id.0.to_string());
+        let item_path = join(path, item.ident.clone());
+        let ty_name = |ty: &TypeOwned| ty.human_name(true, api_bundle);
+        let (kind, signature) = match &item.kind {
+            ApiItemKindOwned::Method { args, return_ty } => {
+                let mut args_def = vec![];
+                for arg in args {
+                    args_def.push(format!("{}: {}", arg.ident, ty_name(&arg.ty)?));
+                }
+                let mut signature = format!("fn({})", args_def.join(", "));
+                if let Some(ty) = return_ty {
+                    write!(signature, " -> {}", ty_name(ty)?)?;
+                }
+                ("method", signature)
+            }
+            ApiItemKindOwned::Property {
+                ty,
+                access,
+                write_err_ty,
+            } => {
+                let (access, observe) = match access {
+                    PropertyAccess::Const => ("const", false),
+                    PropertyAccess::ReadOnly { observe } => ("ro", *observe),
+                    PropertyAccess::ReadWrite { observe } => ("rw", *observe),
+                    PropertyAccess::WriteOnly => ("wo", false),
+                };
+                let mut signature = format!("{access} {}", ty_name(ty)?);
+                if observe {
+                    signature.push_str(", observe");
+                }
+                if let Some(ty) = write_err_ty {
+                    write!(signature, ", on_set_err: {}", ty_name(ty)?)?;
+                }
+                ("property", signature)
+            }
+            ApiItemKindOwned::Stream { ty, is_up } => {
+                let kind = if *is_up { "stream" } else { "sink" };
+                (kind, ty_name(ty)?)
+            }
+            ApiItemKindOwned::Trait { .. } => {
+                let level = item.get_as_level(api_bundle)?;
+                let signature = format!(
+                    "impl {}::{}",
+                    level.crate_name(api_bundle)?,
+                    level.trait_name
+                );
+                ("trait", signature)
+            }
+        };
+        resources.push(ReportResource {
+            numeric_path: item_numeric_path.clone(),
+            path: item_path.clone(),
+            kind,
+            signature,
+            since: item.since.map(|since| format!("{since:?}")),
+            docs: item.docs.clone(),
+        });
+        if let ApiItemKindOwned::Trait { .. } = &item.kind {
+            let level = item.get_as_level(api_bundle)?;
+            collect_resources(api_bundle, level, &item_numeric_path, &item_path, resources)?;
+        }
+    }
+    Ok(())
+}
+
+/// Collect user-defined structs and enums, including the ones only used in other types.
+fn collect_types(
+    api_bundle: &ApiBundleOwned,
+    ty: &TypeOwned,
+    types: &mut Vec<ReportType>,
+) -> Result<()> {
+    match ty {
+        TypeOwned::Struct(item_struct) => {
+            let name = ty.human_name(true, api_bundle)?;
+            if types.iter().any(|t| t.name == name) {
+                return Ok(());
+            }
+            types.push(ReportType {
+                name,
+                kind: "struct",
+                size: element_size(&item_struct.size),
+                repr: None,
+                docs: item_struct.docs.clone(),
+                members: vec![],
+            });
+            let idx = types.len() - 1;
+            types[idx].members = fields(api_bundle, &item_struct.fields)?;
+            for field in fields_iter(&item_struct.fields) {
+                collect_types(api_bundle, &field.ty, types)?;
+            }
+        }
+        TypeOwned::Enum(item_enum) => {
+            let name = ty.human_name(true, api_bundle)?;
+            if types.iter().any(|t| t.name == name) {
+                return Ok(());
+            }
+            let mut members = vec![];
+            for variant in &item_enum.variants {
+                let variant_fields = fields(api_bundle, &variant.fields)?
+                    .into_iter()
+                    .map(|f| match &variant.fields {
+                        FieldsOwned::Named(_) => format!("{}: {}", f.name, f.ty),
+                        _ => f.ty,
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let variant_fields = match &variant.fields {
+                    FieldsOwned::Named(_) => format!("{{ {variant_fields} }}"),
+                    FieldsOwned::Unnamed(_) => format!("({variant_fields})"),
+                    FieldsOwned::Unit => String::new(),
+                };
+                members.push(ReportMember {
+                    name: variant.ident.clone(),
+                    discriminant: Some(variant.discriminant.0),
+                    ty: variant_fields,
+                    since: variant.since.map(|since| format!("{since:?}")),
+                    docs: variant.docs.clone(),
+                });
+            }
+            types.push(ReportType {
+                name,
+                kind: "enum",
+                size: element_size(&item_enum.size),
+                repr: Some(repr(&item_enum.repr)),
+                docs: item_enum.docs.clone(),
+                members,
+            });
+            for variant in &item_enum.variants {
+                for field in fields_iter(&variant.fields) {
+                    collect_types(api_bundle, &field.ty, types)?;
+                }
+            }
+        }
+        TypeOwned::OutOfLine { type_idx } => {
+            let (ty, _) = api_bundle.get_ty(type_idx.0)?;
+            collect_types(api_bundle, ty, types)?;
+        }
+        TypeOwned::Vec(ty)
+        | TypeOwned::Array { ty, .. }
+        | TypeOwned::BoundedVec { ty, .. }
+        | TypeOwned::Box(ty)
+        | TypeOwned::Option { some_ty: ty } => collect_types(api_bundle, ty, types)?,
+        TypeOwned::Result { ok_ty, err_ty } => {
+            collect_types(api_bundle, ok_ty, types)?;
+            collect_types(api_bundle, err_ty, types)?;
+        }
+        TypeOwned::Tuple(tys) => {
+            for ty in tys {
+                collect_types(api_bundle, ty, types)?;
+            }
+        }
+        TypeOwned::Bool
+        | TypeOwned::NumericAny(_)
+        | TypeOwned::Flag
+        | TypeOwned::String
+        | TypeOwned::Range(_)
+        | TypeOwned::RangeInclusive(_)
+        | TypeOwned::BoundedString { .. } => {}
+    }
+    Ok(())
+}
+
+pub(super) fn fields_iter(fields: &FieldsOwned) -> &[ww_self::FieldOwned] {
+    match fields {
+        FieldsOwned::Named(fields) | FieldsOwned::Unnamed(fields) => fields,
+        FieldsOwned::Unit => &[],
+    }
+}
+
+fn fields(api_bundle: &ApiBundleOwned, fields: &FieldsOwned) -> Result<Vec<ReportMember>> {
+    let mut members = vec![];
+    for (idx, field) in fields_iter(fields).iter().enumerate() {
+        members.push(ReportMember {
+            name: field.ident.clone().unwrap_or_else(|| idx.to_string()),
+            discriminant: None,
+            ty: field.ty.human_name(true, api_bundle)?,
+            since: field.since.map(|since| format!("{since:?}")),
+            docs: field.docs.clone(),
+        });
+    }
+    Ok(members)
+}
+
+pub(super) fn element_size(size: &ElementSize) -> String {
+    match size {
+        ElementSize::Unsized => "Unsized".into(),
+        ElementSize::UnsizedFinalStructure => "UnsizedFinalStructure".into(),
+        ElementSize::SelfDescribing => "SelfDescribing".into(),
+        ElementSize::Sized { size_bits } => format!("Sized ({size_bits} bits)"),
+    }
+}
+
+/// Same as in `#[ww_repr(..)]` attribute.
+pub(super) fn repr(repr: &Repr) -> String {
+    match repr {
+        Repr::Nibble => "nib".into(),
+        Repr::BitAligned(bits) => format!("u{bits}"),
+        Repr::UNib32 => "unib32".into(),
+        Repr::ByteAlignedU8 => "u8".into(),
+        Repr::ByteAlignedU16 => "u16".into(),
+        Repr::ByteAlignedU32 => "u32".into(),
+    }
+}
+
+fn docs_line(docs: &[String], skipped: bool) -> String {
+    let docs = docs
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if skipped {
+        format!("(definition not included) {docs}")
+            .trim_end()
+            .to_string()
+    } else {
+        docs
+    }
+}
+
+fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn md_code(text: &str) -> String {
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("`{}`", md_cell(text))
+    }
+}
+
+pub(super) fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_code(text: &str) -> String {
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("<code>{}</code>", html_escape(text))
+    }
+}
+
+fn html_row(html: &mut String, cell: &str, cells: &[&str]) -> Result<()> {
+    write!(html, "<tr>")?;
+    for c in cells {
+        write!(html, "<{cell}>{c}</{cell}>")?;
+    }
+    writeln!(html, "</tr>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn methods_report() {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/methods_api");
+        let api_bundle = crate::load(&crate_path, Some("Methods".into()), true).unwrap();
+        let md = gen_api_report(&api_bundle, ReportFormat::Markdown).unwrap();
+        assert!(md.contains("| `2` | `plain_return` | method | `fn() -> u8` |  |  |"));
+        assert!(md.contains("| `5` | `added_later` | method | `fn()` | 0.1.1 |  |"));
+        assert!(md.contains("### `methods_api::UserDefined`\n\nstruct, size: Unsized"));
+        assert!(md.contains("| `b` | `Vec<u8>` |  |  |"));
+        assert_eq!(
+            md,
+            gen_api_report(&api_bundle, ReportFormat::Markdown).unwrap()
+        );
+
+        let html = gen_api_report(&api_bundle, ReportFormat::Html).unwrap();
+        assert!(html.contains("<td><code>fn() -&gt; u8</code></td>"));
+
+        let json = gen_api_report(&api_bundle, ReportFormat::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["api"], "methods_api::Methods");
+        assert_eq!(json["traits"][0]["full_version"], "methods_api@0.1.1");
+        assert_eq!(
+            json["resources"][3]["signature"],
+            "fn(u: methods_api::UserDefined)"
+        );
+        assert_eq!(json["types"][0]["members"][0]["ty"], "u8");
+    }
+
+    #[test]
+    fn nested_resources_numeric_paths() {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/traits_api");
+        let api_bundle = crate::load(&crate_path, Some("Traits".into()), true).unwrap();
+        let md = gen_api_report(&api_bundle, ReportFormat::Markdown).unwrap();
+        assert!(md.contains(
+            "| `2[].0[]` | `periph[].channel[]` | trait | `impl traits_api::Channel` |  |  |"
+        ));
+        assert!(
+            md.contains("| `2[].0[].0` | `periph[].channel[].gain` | property | `rw f32` |  |  |")
+        );
+        assert!(md.contains("| `traits_api::Subgroup` | `traits_api@0.1.0` |  |  |"));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::codegen::api_server::{ApiServerCGContext, process_level_fn};
+use crate::codegen::index_chain::IndexChain;
+use crate::codegen::server::handlers::{HandlerFnKind, level_handlers};
+use crate::codegen::util::ErrorSeq;
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use ww_self::{ApiBundleOwned, ApiItemKindOwned, ApiLevelOwned, Multiplicity};
+
+/// Generates a Rust trait for the provided API level and all the nested ones.
+/// Each trait contains user handlers with default bodies, accessors to nested levels and a dispatcher as provided methods.
+///
+/// `name_chain` is None for the root level and `a_b_c` for nested ones, where a, b and c are resource names.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn handler_traits_recursive(
+    name_chain: Option<String>,
+    api_bundle: &ApiBundleOwned,
+    api_level: &ApiLevelOwned,
+    index_chain: IndexChain,
+    crate_name: &str,
+    cx: &ApiServerCGContext<'_>,
+    error_seq: &mut ErrorSeq,
+    introspect: Option<TokenStream>,
+    process_request_bytes: TokenStream,
+) -> TokenStream {
+    let trait_ident = match &name_chain {
+        Some(name_chain) => handler_trait_ident(name_chain),
+        None => handler_trait_ident(&api_level.trait_name),
+    };
+    let level_name_chain = match &name_chain {
+        Some(name_chain) => format!("root_{name_chain}"),
+        None => "root".to_string(),
+    };
+    let process_fn = process_level_fn(
+        &level_name_chain,
+        api_bundle,
+        api_level,
+        index_chain,
+        crate_name,
+        cx,
+        error_seq,
+        introspect,
+    );
+    let handlers = handlers(api_bundle, api_level, &level_name_chain, index_chain, cx);
+
+    let mut nested_levels = TokenStream::new();
+    let mut nested_traits = TokenStream::new();
+    for item in &api_level.items {
+        if !matches!(item.kind, ApiItemKindOwned::Trait { .. }) {
+            continue;
+        };
+        let level = item.get_as_level(api_bundle).unwrap();
+        let nested_name_chain = match &name_chain {
+            Some(name_chain) => format!("{name_chain}_{}", item.ident),
+            None => item.ident.clone(),
+        };
+        let nested_trait_ident = handler_trait_ident(&nested_name_chain);
+        let assoc_ty = Ident::new(item.ident.to_case(Case::Pascal).as_str(), Span::call_site());
+        let accessor = Ident::new(&item.ident, Span::call_site());
+        let doc = format!(" Implementation of `{}` resources.", item.ident);
+        nested_levels.extend(quote! {
+            #[doc = #doc]
+            type #assoc_ty: #nested_trait_ident;
+            #[doc = #doc]
+            fn #accessor(&mut self) -> &mut Self::#assoc_ty;
+        });
+
+        let mut cx = cx.clone();
+        cx.push_level(&item.ident);
+        let mut index_chain = index_chain;
+        if matches!(item.multiplicity, Multiplicity::Array { .. }) {
+            index_chain.increment_length();
+        }
+        nested_traits.extend(handler_traits_recursive(
+            Some(nested_name_chain),
+            api_bundle,
+            level,
+            index_chain,
+            level.crate_name(api_bundle).unwrap(),
+            &cx,
+            error_seq,
+            None,
+            quote! {},
+        ));
+    }
+
+    let docs = api_level.docs.iter().map(|d| quote! { #[doc = #d] });
+    quote! {
+        #(#docs)*
+        #[allow(async_fn_in_trait)]
+        #[allow(unused_variables)]
+        pub trait #trait_ident {
+            #nested_levels
+            #handlers
+            #process_request_bytes
+            #[doc(hidden)]
+            #process_fn
+        }
+
+        #nested_traits
+    }
+}
+
+pub(crate) fn handler_trait_ident(name: &str) -> Ident {
+    Ident::new(
+        format!("{}_server", name.to_case(Case::Snake))
+            .to_case(Case::Pascal)
+            .as_str(),
+        Span::call_site(),
+    )
+}
+
+/// User handlers of one API level, defaulting to OperationNotImplemented error.
+fn handlers(
+    api_bundle: &ApiBundleOwned,
+    api_level: &ApiLevelOwned,
+    level_name_chain: &str,
+    index_chain: IndexChain,
+    cx: &ApiServerCGContext<'_>,
+) -> TokenStream {
+    let not_implemented = quote! {{ Err(ErrorKind::OperationNotImplemented) }};
+    let mut ts = TokenStream::new();
+    for handler in level_handlers(api_bundle, api_level, level_name_chain, index_chain, cx) {
+        let docs = handler.docs.iter().map(|d| quote! { #[doc = #d] });
+        let sig = handler.sig;
+        let body = match handler.kind {
+            HandlerFnKind::Handler => not_implemented.clone(),
+            HandlerFnKind::Required | HandlerFnKind::Field => quote! { ; },
+        };
+        ts.extend(quote! {
+            #(#docs)*
+            #sig #body
+        });
+    }
+    ts
+}
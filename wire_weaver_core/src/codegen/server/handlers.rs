@@ -0,0 +1,615 @@
+use crate::codegen::api_server::{
+    ApiServerCGContext, GenServerConfig, SinkArg, sink_arg, valid_indices_ident,
+};
+use crate::codegen::index_chain::IndexChain;
+use crate::codegen::server::handler_traits::handler_trait_ident;
+use crate::codegen::ty_def::ty_def;
+use crate::codegen::util::{add_prefix, maybe_quote};
+use crate::method_model::MethodModelKind;
+use crate::property_model::PropertyModelKind;
+use anyhow::{Result, anyhow};
+use convert_case::{Case, Casing};
+use proc_macro2::{Delimiter, Ident, Spacing, Span, TokenStream, TokenTree};
+use quote::quote;
+use ww_self::{
+    ApiBundleOwned, ApiItemKindOwned, ApiItemOwned, ApiLevelOwned, Multiplicity, PropertyAccess,
+};
+
+/// User handler called by the server dispatcher.
+pub(crate) struct HandlerFn {
+    /// Name of the API resource this handler belongs to
+    pub(crate) resource: String,
+    pub(crate) docs: Vec<String>,
+    /// `fn name(&mut self, ..) -> T` or `name: T` for value_on_changed properties
+    pub(crate) sig: TokenStream,
+    pub(crate) kind: HandlerFnKind,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum HandlerFnKind {
+    /// Has to be implemented, no meaningful default is possible (e.g., valid indices or accessors)
+    Required,
+    /// Defaults to an OperationNotImplemented error in generated handler traits
+    Handler,
+    /// Struct field instead of a method, for value_on_changed properties
+    Field,
+}
+
+/// Handlers of all the resources of one API level, excluding nested levels, exactly as dispatcher code calls them.
+/// In handler traits mode, names are not prefixed and all the handlers return `Result<T, ErrorKind>`.
+pub(crate) fn level_handlers(
+    api_bundle: &ApiBundleOwned,
+    api_level: &ApiLevelOwned,
+    level_name_chain: &str,
+    index_chain: IndexChain,
+    cx: &ApiServerCGContext<'_>,
+) -> Vec<HandlerFn> {
+    let maybe_async = maybe_quote(cx.use_async, quote! { async });
+    let msg_tx = quote! { msg_tx: &mut impl wire_weaver::MessageSink };
+    let returns = |ty: TokenStream| {
+        if cx.handler_traits {
+            quote! { -> Result<#ty, ww_client_server::ErrorKind<'static>> }
+        } else if ty.to_string() == "()" {
+            quote! {}
+        } else {
+            quote! { -> #ty }
+        }
+    };
+    let mut handlers = vec![];
+    for item in &api_level.items {
+        let mut item_index_chain = index_chain;
+        if matches!(item.multiplicity, Multiplicity::Array { .. }) {
+            let valid_indices = if cx.handler_traits {
+                valid_indices_ident(item)
+            } else {
+                Ident::new(
+                    format!(
+                        "valid_indices_{level_name_chain}_{}",
+                        item.ident.to_case(Case::Snake)
+                    )
+                    .as_str(),
+                    Span::call_site(),
+                )
+            };
+            let index_chain_def = index_chain.fun_argument_def();
+            handlers.push(HandlerFn {
+                resource: item.ident.clone(),
+                docs: vec![format!(
+                    " Indices of `{}` resources that can be accessed.",
+                    item.ident
+                )],
+                sig: quote! {
+                    fn #valid_indices(&mut self, #index_chain_def) -> wire_weaver::ValidIndices<'_>
+                },
+                kind: HandlerFnKind::Required,
+            });
+            item_index_chain.increment_length();
+        }
+        let index_chain_def = item_index_chain.fun_argument_def();
+        let ident = Ident::new(&item.ident, Span::call_site());
+        let prefixed_ident = add_prefix(cx.ident_prefix.as_ref(), &ident);
+        let mut push = |sig: TokenStream, kind: HandlerFnKind, extra_doc: Option<&str>| {
+            let mut docs = item.docs.clone();
+            docs.extend(extra_doc.map(String::from));
+            handlers.push(HandlerFn {
+                resource: item.ident.clone(),
+                docs,
+                sig,
+                kind,
+            })
+        };
+        match &item.kind {
+            ApiItemKindOwned::Method { args, return_ty } => {
+                let args = args.iter().map(|arg| {
+                    let ident = Ident::new(&arg.ident, Span::call_site());
+                    let ty = ty_def(api_bundle, &arg.ty, !cx.no_alloc, true).unwrap();
+                    quote! { #ident: #ty }
+                });
+                let output_ty = match return_ty {
+                    Some(ty) => ty_def(api_bundle, ty, false, true).unwrap(),
+                    None => quote! { () },
+                };
+                let method_model = cx.method_model(&item.ident);
+                match method_model {
+                    MethodModelKind::Immediate => {
+                        let ret = returns(output_ty);
+                        push(
+                            quote! { #maybe_async fn #prefixed_ident(&mut self, #msg_tx, #index_chain_def #(#args),*) #ret },
+                            HandlerFnKind::Handler,
+                            None,
+                        );
+                    }
+                    MethodModelKind::Deferred | MethodModelKind::DeferredCancellable => {
+                        let ret = returns(quote! { Option<#output_ty> });
+                        push(
+                            quote! { #maybe_async fn #prefixed_ident(&mut self, #msg_tx, #index_chain_def seq: u16, #(#args),*) #ret },
+                            HandlerFnKind::Handler,
+                            Some(" Return None to answer later using request `seq`."),
+                        );
+                    }
+                }
+                if method_model == MethodModelKind::DeferredCancellable {
+                    let cancel_ident = Ident::new(
+                        format!("{prefixed_ident}_cancel").as_str(),
+                        Span::call_site(),
+                    );
+                    let ret = returns(quote! { () });
+                    push(
+                        quote! { #maybe_async fn #cancel_ident(&mut self, #msg_tx, #index_chain_def seq: u16) #ret },
+                        HandlerFnKind::Handler,
+                        None,
+                    );
+                }
+            }
+            ApiItemKindOwned::Property {
+                ty,
+                access,
+                write_err_ty,
+            } => {
+                let ty = ty_def(api_bundle, ty, false, true).unwrap();
+                // property_model does not apply to handler traits, see handle_property
+                let property_model = if cx.handler_traits {
+                    PropertyModelKind::GetSet
+                } else {
+                    cx.property_model.pick(item.ident.as_str()).unwrap()
+                };
+                let readable = matches!(
+                    access,
+                    PropertyAccess::Const
+                        | PropertyAccess::ReadOnly { .. }
+                        | PropertyAccess::ReadWrite { .. }
+                );
+                match property_model {
+                    PropertyModelKind::GetSet if readable => {
+                        let get_ident =
+                            Ident::new(format!("get_{prefixed_ident}").as_str(), Span::call_site());
+                        let ret = returns(ty.clone());
+                        push(
+                            quote! { #maybe_async fn #get_ident(&mut self, #index_chain_def) #ret },
+                            HandlerFnKind::Handler,
+                            None,
+                        );
+                    }
+                    PropertyModelKind::ValueOnChanged if readable => {
+                        let ty = if item_index_chain.is_empty() {
+                            ty.clone()
+                        } else {
+                            // indexed with array_indices()
+                            quote! { Vec<#ty> }
+                        };
+                        push(quote! { #prefixed_ident: #ty }, HandlerFnKind::Field, None);
+                    }
+                    _ => {}
+                }
+                if matches!(
+                    access,
+                    PropertyAccess::WriteOnly | PropertyAccess::ReadWrite { .. }
+                ) {
+                    let set_ident =
+                        Ident::new(format!("set_{prefixed_ident}").as_str(), Span::call_site());
+                    let ret = match write_err_ty {
+                        Some(err_ty) => {
+                            let err_ty = ty_def(api_bundle, err_ty, false, true).unwrap();
+                            returns(quote! { Result<(), #err_ty> })
+                        }
+                        None => returns(quote! { () }),
+                    };
+                    push(
+                        quote! { #maybe_async fn #set_ident(&mut self, #index_chain_def value: #ty) #ret },
+                        HandlerFnKind::Handler,
+                        None,
+                    );
+                }
+            }
+            ApiItemKindOwned::Stream { ty, is_up } => {
+                // stream handlers are not prefixed in the dispatcher
+                let sideband_ident =
+                    Ident::new(format!("{ident}_sideband").as_str(), Span::call_site());
+                let ret = returns(quote! { Option<ww_client_server::StreamSidebandEvent> });
+                push(
+                    quote! { #maybe_async fn #sideband_ident(&mut self, #msg_tx, #index_chain_def cmd: ww_client_server::StreamSidebandCommand) #ret },
+                    HandlerFnKind::Handler,
+                    None,
+                );
+                if !*is_up {
+                    let write_ident =
+                        Ident::new(format!("{ident}_write").as_str(), Span::call_site());
+                    let value_ty = match sink_arg(ty) {
+                        SinkArg::Unit => quote! { () },
+                        SinkArg::Bytes => quote! { &wire_weaver::shrink_wrap::RefVec<'_, u8> },
+                        SinkArg::Value => ty_def(api_bundle, ty, false, true).unwrap(),
+                    };
+                    let ret = returns(quote! { () });
+                    push(
+                        quote! { #maybe_async fn #write_ident(&mut self, #index_chain_def value: #value_ty) #ret },
+                        HandlerFnKind::Handler,
+                        None,
+                    );
+                }
+            }
+            ApiItemKindOwned::Trait { .. } => {}
+        }
+    }
+    handlers
+}
+
+/// User code expected by a generated server, see [handler_signatures].
+#[derive(Debug, Clone)]
+pub struct HandlerSignature {
+    /// Absolute path to the API resource, e.g. `gpio[].set_high`, empty for the root level
+    pub resource_path: String,
+    /// Trait to implement when `handler_traits` is used, `server_struct_path` otherwise
+    pub impl_target: String,
+    pub kind: HandlerKind,
+    /// Handler name, struct field name or associated type name
+    pub name: String,
+    /// Rust declaration without a body, e.g. `fn get_speed(&mut self) -> u32`
+    pub signature: String,
+    pub docs: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HandlerKind {
+    /// User handler, has a default implementation when `handler_traits` is used
+    Handler,
+    /// `valid_indices_..` method of an array resource
+    ValidIndices,
+    /// Struct field of a `value_on_changed` property
+    Field,
+    /// Associated type implementing a nested level trait, when `handler_traits` is used
+    LevelType,
+    /// Accessor to a nested level implementation, when `handler_traits` is used
+    LevelAccessor,
+}
+
+/// Returns all the handlers that user code needs to provide for [gen_server](crate::gen_server) to compile,
+/// in the same order as API resources are defined.
+///
+/// Useful to bootstrap a server implementation or to check what changed after switching method or property models.
+/// Returns an error if a model has no match for some of the methods or properties, e.g., when there is no `_=` default.
+pub fn handler_signatures(
+    api_bundle: &ApiBundleOwned,
+    config: &GenServerConfig,
+) -> Result<Vec<HandlerSignature>> {
+    let cx = ApiServerCGContext {
+        ident_prefix: None,
+        level_path: None,
+        no_alloc: config.no_alloc,
+        use_async: config.use_async,
+        method_model: &config.method_model,
+        property_model: &config.property_model,
+        handler_traits: config.handler_traits,
+    };
+    check_models(api_bundle, &api_bundle.root, &cx)?;
+    let mut signatures = vec![];
+    handler_signatures_recursive(
+        api_bundle,
+        &api_bundle.root,
+        None,
+        "",
+        IndexChain::new(),
+        &cx,
+        &config.server_struct_path,
+        &mut signatures,
+    );
+    Ok(signatures)
+}
+
+/// Check that the method and property models are matched with every resource, as [level_handlers] expects.
+fn check_models(
+    api_bundle: &ApiBundleOwned,
+    api_level: &ApiLevelOwned,
+    cx: &ApiServerCGContext<'_>,
+) -> Result<()> {
+    for item in &api_level.items {
+        match &item.kind {
+            ApiItemKindOwned::Method { .. } => {
+                let path = cx.method_path(&item.ident);
+                if cx.method_model.pick(&path).is_none() {
+                    return Err(anyhow!(
+                        "method model has no match for {path}, add a default, e.g. _=immediate"
+                    ));
+                }
+            }
+            ApiItemKindOwned::Property { .. } => {
+                if !cx.handler_traits && cx.property_model.pick(&item.ident).is_none() {
+                    return Err(anyhow!(
+                        "property model has no match for {}, add a default, e.g. _=get_set",
+                        item.ident
+                    ));
+                }
+            }
+            ApiItemKindOwned::Trait { .. } => {
+                let mut cx = cx.clone();
+                cx.push_level(&item.ident);
+                check_models(api_bundle, item.get_as_level(api_bundle)?, &cx)?;
+            }
+            ApiItemKindOwned::Stream { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handler_signatures_recursive(
+    api_bundle: &ApiBundleOwned,
+    api_level: &ApiLevelOwned,
+    name_chain: Option<String>,
+    path: &str,
+    index_chain: IndexChain,
+    cx: &ApiServerCGContext<'_>,
+    server_struct_path: &str,
+    signatures: &mut Vec<HandlerSignature>,
+) {
+    let level_name_chain = match &name_chain {
+        Some(name_chain) => format!("root_{name_chain}"),
+        None => "root".to_string(),
+    };
+    let impl_target = if cx.handler_traits {
+        match &name_chain {
+            Some(name_chain) => handler_trait_ident(name_chain),
+            None => handler_trait_ident(&api_level.trait_name),
+        }
+        .to_string()
+    } else {
+        server_struct_path.to_string()
+    };
+    let resource_path = |item: &ApiItemOwned| {
+        let multiplicity = match item.multiplicity {
+            Multiplicity::Flat => "",
+            Multiplicity::Array { .. } => "[]",
+        };
+        if path.is_empty() {
+            format!("{}{multiplicity}", item.ident)
+        } else {
+            format!("{path}.{}{multiplicity}", item.ident)
+        }
+    };
+
+    for handler in level_handlers(api_bundle, api_level, &level_name_chain, index_chain, cx) {
+        let item = api_level
+            .items
+            .iter()
+            .find(|item| item.ident == handler.resource)
+            .unwrap();
+        let name = match handler.sig.clone().into_iter().find_map(|tt| match tt {
+            TokenTree::Ident(ident) if ident != "async" && ident != "fn" => Some(ident),
+            _ => None,
+        }) {
+            Some(name) => name.to_string(),
+            None => handler.resource.clone(),
+        };
+        let kind = match handler.kind {
+            HandlerFnKind::Required => HandlerKind::ValidIndices,
+            HandlerFnKind::Handler => HandlerKind::Handler,
+            HandlerFnKind::Field => HandlerKind::Field,
+        };
+        signatures.push(HandlerSignature {
+            resource_path: resource_path(item),
+            impl_target: impl_target.clone(),
+            kind,
+            name,
+            signature: tokens_to_string(handler.sig),
+            docs: handler.docs,
+        });
+    }
+
+    for item in &api_level.items {
+        if !matches!(item.kind, ApiItemKindOwned::Trait { .. }) {
+            continue;
+        };
+        let level = item.get_as_level(api_bundle).unwrap();
+        let nested_name_chain = match &name_chain {
+            Some(name_chain) => format!("{name_chain}_{}", item.ident),
+            None => item.ident.clone(),
+        };
+        if cx.handler_traits {
+            let assoc_ty = item.ident.to_case(Case::Pascal);
+            let nested_trait = handler_trait_ident(&nested_name_chain);
+            signatures.push(HandlerSignature {
+                resource_path: resource_path(item),
+                impl_target: impl_target.clone(),
+                kind: HandlerKind::LevelType,
+                name: assoc_ty.clone(),
+                signature: format!("type {assoc_ty}: {nested_trait}"),
+                docs: vec![],
+            });
+            signatures.push(HandlerSignature {
+                resource_path: resource_path(item),
+                impl_target: impl_target.clone(),
+                kind: HandlerKind::LevelAccessor,
+                name: item.ident.clone(),
+                signature: format!("fn {}(&mut self) -> &mut Self::{assoc_ty}", item.ident),
+                docs: vec![],
+            });
+        }
+        let mut cx = cx.clone();
+        cx.push_level(&item.ident);
+        let mut index_chain = index_chain;
+        if matches!(item.multiplicity, Multiplicity::Array { .. }) {
+            index_chain.increment_length();
+        }
+        handler_signatures_recursive(
+            api_bundle,
+            level,
+            Some(nested_name_chain),
+            &resource_path(item),
+            index_chain,
+            &cx,
+            server_struct_path,
+            signatures,
+        );
+    }
+}
+
+/// Prints Rust tokens the way rustfmt would for a single-line declaration, dropping trailing commas.
+fn tokens_to_string(ts: TokenStream) -> String {
+    let mut pieces: Vec<String> = vec![];
+    flatten(ts, &mut pieces);
+    let mut out = String::new();
+    let mut prev: Option<&str> = None;
+    for (i, piece) in pieces.iter().enumerate() {
+        let next = pieces.get(i + 1).map(|s| s.as_str());
+        if piece == "," && matches!(next, Some(")" | "]") | None) {
+            continue;
+        }
+        if let Some(prev) = prev
+            && needs_space(prev, piece)
+        {
+            out.push(' ');
+        }
+        out.push_str(piece);
+        prev = Some(piece);
+    }
+    out
+}
+
+fn flatten(ts: TokenStream, pieces: &mut Vec<String>) {
+    // previous token is a joint punct that starts `::`, `->` or a lifetime
+    let mut glue = false;
+    for tt in ts {
+        let piece = match tt {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::None => ("", ""),
+                };
+                pieces.push(open.to_string());
+                flatten(group.stream(), pieces);
+                pieces.push(close.to_string());
+                glue = false;
+                continue;
+            }
+            TokenTree::Ident(ident) => ident.to_string(),
+            TokenTree::Literal(literal) => {
+                // array lengths are generated as `2usize`
+                let literal = literal.to_string();
+                match literal.strip_suffix("usize") {
+                    Some(n) if n.chars().all(|c| c.is_ascii_digit()) => n.to_string(),
+                    _ => literal,
+                }
+            }
+            TokenTree::Punct(punct) => {
+                let piece = punct.as_char().to_string();
+                let next_glue = punct.spacing() == Spacing::Joint
+                    && matches!(punct.as_char(), ':' | '-' | '\'')
+                    && !glue;
+                match pieces.last_mut() {
+                    Some(last) if glue => last.push_str(&piece),
+                    _ => pieces.push(piece),
+                }
+                glue = next_glue;
+                continue;
+            }
+        };
+        match pieces.last_mut() {
+            Some(last) if glue => last.push_str(&piece),
+            _ => pieces.push(piece),
+        }
+        glue = false;
+    }
+}
+
+fn needs_space(prev: &str, next: &str) -> bool {
+    let after_ident = prev
+        .chars()
+        .last()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let no_space_after = matches!(prev, "(" | "[" | "&" | "<" | "::" | "!");
+    let no_space_before = matches!(next, "," | ";" | ")" | "]" | "." | "?" | ":" | "::")
+        || (next.starts_with('>') && next != "->")
+        || next == "<"
+        || (matches!(next, "(" | "[") && after_ident && !matches!(prev, "mut" | "impl" | "dyn"));
+    !no_space_after && !no_space_before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::method_model::MethodModel;
+    use crate::property_model::PropertyModel;
+    use std::path::Path;
+
+    fn signatures(handler_traits: bool) -> Vec<HandlerSignature> {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/traits_api");
+        let api_bundle = crate::load(&crate_path, Some("Traits".into()), true).unwrap();
+        let config = GenServerConfig {
+            no_alloc: false,
+            use_async: false,
+            method_model: MethodModel::parse(
+                "periph_channel_run=deferred+cancellable, _=immediate",
+            )
+            .unwrap(),
+            property_model: PropertyModel::parse("_=value_on_changed").unwrap(),
+            server_struct_path: "Device".into(),
+            generate_introspect: false,
+            handler_traits,
+        };
+        handler_signatures(&api_bundle, &config).unwrap()
+    }
+
+    fn find<'a>(signatures: &'a [HandlerSignature], name: &str) -> &'a HandlerSignature {
+        signatures.iter().find(|s| s.name == name).unwrap()
+    }
+
+    #[test]
+    fn inherent_handler_signatures() {
+        let signatures = signatures(false);
+        assert!(signatures.iter().all(|s| s.impl_target == "Device"));
+        let s = find(&signatures, "valid_indices_root_gpio");
+        assert_eq!(s.kind, HandlerKind::ValidIndices);
+        assert_eq!(
+            s.signature,
+            "fn valid_indices_root_gpio(&mut self) -> wire_weaver::ValidIndices<'_>"
+        );
+        let s = find(&signatures, "gpio_set_high");
+        assert_eq!(s.resource_path, "gpio[].set_high");
+        assert_eq!(
+            s.signature,
+            "fn gpio_set_high(&mut self, msg_tx: &mut impl wire_weaver::MessageSink, index_chain: [UNib32; 1])"
+        );
+        let s = find(&signatures, "periph_channel_gain");
+        assert_eq!(s.kind, HandlerKind::Field);
+        assert_eq!(s.signature, "periph_channel_gain: Vec<f32>");
+        assert_eq!(
+            find(&signatures, "set_periph_channel_gain").signature,
+            "fn set_periph_channel_gain(&mut self, index_chain: [UNib32; 2], value: f32)"
+        );
+        assert_eq!(
+            find(&signatures, "periph_channel_run").signature,
+            "fn periph_channel_run(&mut self, msg_tx: &mut impl wire_weaver::MessageSink, index_chain: [UNib32; 2], seq: u16) -> Option<()>"
+        );
+        assert_eq!(
+            find(&signatures, "periph_channel_run_cancel").signature,
+            "fn periph_channel_run_cancel(&mut self, msg_tx: &mut impl wire_weaver::MessageSink, index_chain: [UNib32; 2], seq: u16)"
+        );
+    }
+
+    #[test]
+    fn handler_traits_signatures() {
+        let signatures = signatures(true);
+        let s = find(&signatures, "Periph");
+        assert_eq!(s.kind, HandlerKind::LevelType);
+        assert_eq!(s.impl_target, "TraitsServer");
+        assert_eq!(s.signature, "type Periph: PeriphServer");
+        assert_eq!(
+            find(&signatures, "periph").signature,
+            "fn periph(&mut self) -> &mut Self::Periph"
+        );
+        let s = find(&signatures, "get_gain");
+        assert_eq!(s.impl_target, "PeriphChannelServer");
+        assert_eq!(s.resource_path, "periph[].channel[].gain");
+        assert_eq!(
+            s.signature,
+            "fn get_gain(&mut self, index_chain: [UNib32; 2]) -> Result<f32, ww_client_server::ErrorKind<'static>>"
+        );
+        // method model is matched against the same prefixed name as for inherent handlers
+        assert!(find(&signatures, "run").signature.contains("seq: u16"));
+        assert_eq!(
+            find(&signatures, "run_cancel").impl_target,
+            "PeriphChannelServer"
+        );
+    }
+}
@@ -67,15 +67,22 @@ pub(crate) fn introspect_prepare(api_bundle: &ApiBundleOwned) -> (TokenStream, T
         [u8; #bytes_len] = [ #(#bytes),* ]
     };
 
-    // TODO: calculate api signature properly
-    let sha256 = sha2::Sha256::digest(bytes);
-    let short_hash = &sha256[..8];
+    let short_hash = api_signature(bytes);
     let api_signature = quote! { [u8; 8] = [ #(#short_hash),* ]};
-    crate::local_registry::cache_api_bundle(api_bundle, short_hash);
+    crate::local_registry::cache_api_bundle(api_bundle, &short_hash);
 
     (ww_self_bytes_const, api_signature)
 }
 
+/// Short hash of the serialized ApiBundle, emitted as `WW_API_SIGNATURE` into generated code.
+pub(crate) fn api_signature(ww_self_bytes: &[u8]) -> [u8; 8] {
+    // TODO: calculate api signature properly
+    let sha256 = sha2::Sha256::digest(ww_self_bytes);
+    let mut short_hash = [0u8; 8];
+    short_hash.copy_from_slice(&sha256[..8]);
+    short_hash
+}
+
 // struct DropDocs {}
 //
 // impl ww_self::visitor::VisitMut for DropDocs {
@@ -1,2 +1,4 @@
+pub(crate) mod handler_traits;
+pub(crate) mod handlers;
 pub(crate) mod introspect;
 pub(crate) mod stream;
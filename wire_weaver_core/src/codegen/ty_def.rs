@@ -3,7 +3,7 @@ use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 use syn::{Lit, LitInt};
 use ww_numeric::{NumericAnyTypeOwned, NumericBaseType};
-use ww_self::{ApiBundleOwned, TypeOwned};
+use ww_self::{ApiBundleOwned, GenericInstanceOwned, TypeOwned};
 
 pub(crate) fn ty_def(
     api_bundle: &ApiBundleOwned,
@@ -74,6 +74,7 @@ fn ty_def_inner(
             crate_idx,
             &item_struct.ident,
             item_struct.is_lifetime(api_bundle)?,
+            &item_struct.generics,
             alloc,
             arg_pos,
             api_bundle,
@@ -82,6 +83,7 @@ fn ty_def_inner(
             crate_idx,
             &item_enum.ident,
             item_enum.is_lifetime(api_bundle)?,
+            &item_enum.generics,
             alloc,
             arg_pos,
             api_bundle,
@@ -112,6 +114,21 @@ fn ty_def_inner(
             let numeric_base = ty_def_numeric_base(numeric_base);
             Ok(quote! { core::ops::RangeInclusive<#numeric_base> })
         }
+        TypeOwned::BoundedVec { max_len, ty } => {
+            let ty = ty_def_inner(api_bundle, ty, alloc, arg_pos, None)?;
+            let max_len = Lit::Int(LitInt::new(
+                format!("{}", max_len.0).as_str(),
+                Span::call_site(),
+            ));
+            Ok(quote! { shrink_wrap::BoundedVec<#ty, #max_len> })
+        }
+        TypeOwned::BoundedString { max_len } => {
+            let max_len = Lit::Int(LitInt::new(
+                format!("{}", max_len.0).as_str(),
+                Span::call_site(),
+            ));
+            Ok(quote! { shrink_wrap::BoundedString<#max_len> })
+        }
     }
 }
 
@@ -119,6 +136,7 @@ fn user_ty_def(
     crate_idx: Option<u32>,
     ty_name: &str,
     is_lifetime: bool,
+    generics: &Option<GenericInstanceOwned>,
     alloc: bool,
     arg_pos: bool,
     api_bundle: &ApiBundleOwned,
@@ -130,24 +148,27 @@ fn user_ty_def(
     } else {
         quote! {}
     };
+    // fields of a generic type instance can borrow because of the arguments, while the definition might not
+    let is_lifetime = generics.as_ref().map(|g| g.lifetime).unwrap_or(is_lifetime);
+    let mut args = vec![];
+    if is_lifetime && !alloc {
+        args.push(lifetime(arg_pos));
+    }
+    for arg in generics.iter().flat_map(|g| &g.args) {
+        args.push(ty_def_inner(api_bundle, arg, alloc, arg_pos, None)?);
+    }
+    let args = if args.is_empty() {
+        quote! {}
+    } else {
+        quote! { <#(#args),*> }
+    };
 
-    if alloc {
-        let ty_name = if is_lifetime {
-            Ident::new(&format!("{ty_name}Owned"), Span::call_site())
-        } else {
-            Ident::new(ty_name, Span::call_site())
-        };
-        Ok(quote! { #source_crate::#ty_name })
+    let ty_name = if alloc && is_lifetime {
+        Ident::new(&format!("{ty_name}Owned"), Span::call_site())
     } else {
-        // no_std
-        let ty_name = Ident::new(ty_name, Span::call_site());
-        if is_lifetime {
-            let l = lifetime(arg_pos);
-            Ok(quote! { #source_crate::#ty_name<#l> })
-        } else {
-            Ok(quote! { #source_crate::#ty_name })
-        }
-    }
+        Ident::new(ty_name, Span::call_site())
+    };
+    Ok(quote! { #source_crate::#ty_name #args })
 }
 
 fn ty_def_numeric_any(numeric_any: &NumericAnyTypeOwned) -> TokenStream {
@@ -0,0 +1,1005 @@
+//! TypeScript types, codec and client generator.
+//!
+//! Structs from an ApiBundle are emitted as interfaces and enums as discriminated unions tagged with `kind`, together
+//! with type descriptions that a fixed pure-TS runtime (typescript.ts) uses to encode and decode values exactly like
+//! shrink_wrap does. Each API level becomes a client class, sending ww_client_server requests over a WebSocket.
+//!
+//! Values map as follows: Option<T> is `T | null`, Result<T, E> is `{ ok: T } | { err: E }`, Vec<u8> is `Uint8Array`,
+//! tuples are arrays, 64 and 128-bit numbers are `bigint`, unnamed fields are named `_0`, `_1`, etc.
+//! Explicit flags are not part of the values, they are derived from the corresponding Option or Result.
+
+use crate::codegen::util::{
+    fields_list, flags, is_bytes, is_option_or_result, numeric_base, unique_name,
+};
+use anyhow::{Result, anyhow};
+use convert_case::{Case, Casing};
+use std::collections::HashSet;
+use std::fmt::Write;
+use ww_numeric::{NumericBaseType, NumericValue};
+use ww_self::{
+    ApiBundleOwned, ApiItemKindOwned, ApiItemOwned, ApiLevelLocationOwned, ApiLevelOwned,
+    FieldOwned, FieldsOwned, FieldsValueOwned, PropertyAccess, Repr, TypeLocationOwned, TypeOwned,
+    ValueOwned,
+};
+
+const RUNTIME: &str = include_str!("typescript.ts");
+
+/// Name of the property holding enum variant name, must match WW_ENUM_TAG in the runtime.
+const ENUM_TAG: &str = "kind";
+
+const RESERVED: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+    // array index parameter of the generated methods
+    "index",
+];
+
+/// Generates a single TypeScript module with types, encoders, decoders and a typed WebSocket client for the given
+/// API bundle. ApiBundleOwned can be loaded using [crate::load] or [crate::load_dep].
+///
+/// Resulting file has no dependencies and works in browsers and Node.js (WebSocket is only needed for `connect`).
+pub fn gen_typescript(api_bundle: &ApiBundleOwned) -> Result<String> {
+    let root_crate = api_bundle.crate_version(api_bundle.root.crate_idx.0)?;
+    let v = &root_crate.version;
+    let api_name = format!(
+        "{}::{} v{}.{}.{}",
+        root_crate.crate_id, api_bundle.root.trait_name, v.major.0, v.minor.0, v.patch.0
+    );
+    let ts_gen = TsGen::new(api_bundle);
+
+    let mut ts = String::new();
+    writeln!(
+        ts,
+        "// TypeScript client for {api_name}, generated by wire_weaver_core {}, do not edit.",
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(ts)?;
+    ts.push_str(RUNTIME);
+    writeln!(ts)?;
+    writeln!(
+        ts,
+        "// {api_name} {}",
+        "-".repeat(116usize.saturating_sub(api_name.len()))
+    )?;
+    writeln!(ts)?;
+    writeln!(ts, "export const WW_API_NAME = {};", ts_str(&api_name))?;
+    writeln!(ts)?;
+
+    for (idx, location) in api_bundle.types.iter().enumerate() {
+        let TypeLocationOwned::InLine { ty, .. } = location else {
+            continue;
+        };
+        let Some(name) = &ts_gen.type_names[idx] else {
+            continue;
+        };
+        ts_gen.type_def(&mut ts, name, ty)?;
+    }
+
+    writeln!(ts, "export const WW_TYPES: WwTy[] = [")?;
+    for (idx, location) in api_bundle.types.iter().enumerate() {
+        let desc = match location {
+            TypeLocationOwned::InLine { ty, .. } => ts_gen.desc(ty)?,
+            TypeLocationOwned::SkippedFullVersion { type_name, .. } => {
+                format!("{{ k: \"unsupported\", name: {} }}", ts_str(type_name))
+            }
+        };
+        writeln!(ts, "    /* {idx} */ {desc},")?;
+    }
+    writeln!(ts, "];")?;
+    writeln!(ts)?;
+    writeln!(ts, "export const WW_CODEC = new WwCodec(WW_TYPES);")?;
+    writeln!(ts)?;
+
+    for (idx, name) in ts_gen.type_names.iter().enumerate() {
+        let Some(name) = name else {
+            continue;
+        };
+        writeln!(
+            ts,
+            "export function encode{name}(value: {name}): Uint8Array {{\n    return WW_CODEC.encode(WW_TYPES[{idx}], value);\n}}\n"
+        )?;
+        writeln!(
+            ts,
+            "export function decode{name}(bytes: Uint8Array): {name} {{\n    return WW_CODEC.decode(WW_TYPES[{idx}], bytes) as {name};\n}}\n"
+        )?;
+    }
+
+    ts_gen.client_class(&mut ts, &api_bundle.root, None, true)?;
+    for (idx, location) in api_bundle.traits.iter().enumerate() {
+        if let ApiLevelLocationOwned::InLine { level, .. } = location {
+            ts_gen.client_class(&mut ts, level, Some(idx), false)?;
+        }
+    }
+    Ok(ts)
+}
+
+struct TsGen<'a> {
+    api_bundle: &'a ApiBundleOwned,
+    /// TypeScript names of out-of-line structs and enums, indexed the same as types in the bundle.
+    type_names: Vec<Option<String>>,
+    /// Client class names of out-of-line traits, indexed the same as traits in the bundle.
+    trait_class_names: Vec<Option<String>>,
+    root_class_name: String,
+}
+
+impl<'a> TsGen<'a> {
+    fn new(api_bundle: &'a ApiBundleOwned) -> Self {
+        let mut used = HashSet::new();
+        let mut type_names = vec![];
+        for (idx, location) in api_bundle.types.iter().enumerate() {
+            let ident = match location {
+                TypeLocationOwned::InLine {
+                    ty: TypeOwned::Struct(item_struct),
+                    ..
+                } => Some(&item_struct.ident),
+                TypeLocationOwned::InLine {
+                    ty: TypeOwned::Enum(item_enum),
+                    ..
+                } => Some(&item_enum.ident),
+                _ => None,
+            };
+            type_names.push(ident.map(|ident| unique_name(&mut used, ident, idx)));
+        }
+
+        let root_class_name = unique_name(
+            &mut used,
+            &format!("{}Client", api_bundle.root.trait_name),
+            0,
+        );
+        let mut trait_class_names = vec![];
+        for (idx, location) in api_bundle.traits.iter().enumerate() {
+            let name = match location {
+                ApiLevelLocationOwned::InLine { level, .. } => Some(unique_name(
+                    &mut used,
+                    &format!("{}Client", level.trait_name),
+                    idx,
+                )),
+                _ => None,
+            };
+            trait_class_names.push(name);
+        }
+        TsGen {
+            api_bundle,
+            type_names,
+            trait_class_names,
+            root_class_name,
+        }
+    }
+
+    fn type_def(&self, ts: &mut String, name: &str, ty: &TypeOwned) -> Result<()> {
+        match ty {
+            TypeOwned::Struct(item_struct) => {
+                ts.push_str(&jsdoc(&item_struct.docs, ""));
+                writeln!(ts, "export interface {name} {{")?;
+                if let Some(fields) = fields_list(&item_struct.fields) {
+                    let flags = flags(fields)?;
+                    for (idx, field) in fields.iter().enumerate() {
+                        if flags.flag_for[idx].is_some() {
+                            continue;
+                        }
+                        ts.push_str(&jsdoc(&field.docs, "    "));
+                        writeln!(
+                            ts,
+                            "    {}: {};",
+                            field_key(field, idx),
+                            self.ty(&field.ty)?
+                        )?;
+                    }
+                }
+                writeln!(ts, "}}")?;
+            }
+            TypeOwned::Enum(item_enum) => {
+                ts.push_str(&jsdoc(&item_enum.docs, ""));
+                writeln!(ts, "export type {name} =")?;
+                for variant in &item_enum.variants {
+                    ts.push_str(&jsdoc(&variant.docs, "    "));
+                    writeln!(
+                        ts,
+                        "    | {}",
+                        self.variant_ty(&variant.ident, &variant.fields)?
+                    )?;
+                }
+                if item_enum.variants.is_empty() {
+                    writeln!(ts, "    never")?;
+                }
+                let _ = ts.pop();
+                writeln!(ts, ";")?;
+            }
+            _ => return Err(anyhow!("expected struct or enum, got {ty:?}")),
+        }
+        writeln!(ts)?;
+        Ok(())
+    }
+
+    /// TypeScript type of values.
+    fn ty(&self, ty: &TypeOwned) -> Result<String> {
+        let ts = match ty {
+            TypeOwned::Bool => "boolean".into(),
+            TypeOwned::NumericAny(numeric) => ts_num_ty(numeric_base(numeric)).into(),
+            TypeOwned::OutOfLine { type_idx } => match self.type_names.get(type_idx.0 as usize) {
+                Some(Some(name)) => name.clone(),
+                _ => match self.api_bundle.types.get(type_idx.0 as usize) {
+                    Some(TypeLocationOwned::InLine { ty, .. }) => self.ty(ty)?,
+                    _ => "unknown".into(),
+                },
+            },
+            TypeOwned::Flag => return Err(anyhow!("Flag type cannot be used on its own")),
+            TypeOwned::String | TypeOwned::BoundedString { .. } => "string".into(),
+            TypeOwned::Vec(_) | TypeOwned::BoundedVec { .. } if is_bytes(ty) => "Uint8Array".into(),
+            TypeOwned::Vec(ty) | TypeOwned::BoundedVec { ty, .. } | TypeOwned::Array { ty, .. } => {
+                format!("{}[]", self.parenthesized_ty(ty)?)
+            }
+            TypeOwned::Tuple(types) => {
+                let tys = types
+                    .iter()
+                    .map(|ty| self.ty(ty))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("[{}]", tys.join(", "))
+            }
+            TypeOwned::Struct(item_struct) => {
+                let mut props = vec![];
+                if let Some(fields) = fields_list(&item_struct.fields) {
+                    let flags = flags(fields)?;
+                    for (idx, field) in fields.iter().enumerate() {
+                        if flags.flag_for[idx].is_none() {
+                            props.push(format!(
+                                "{}: {}",
+                                field_key(field, idx),
+                                self.ty(&field.ty)?
+                            ));
+                        }
+                    }
+                }
+                if props.is_empty() {
+                    "Record<string, never>".into()
+                } else {
+                    format!("{{ {} }}", props.join("; "))
+                }
+            }
+            TypeOwned::Enum(item_enum) => {
+                let variants = item_enum
+                    .variants
+                    .iter()
+                    .map(|variant| self.variant_ty(&variant.ident, &variant.fields))
+                    .collect::<Result<Vec<_>>>()?;
+                if variants.is_empty() {
+                    "never".into()
+                } else {
+                    variants.join(" | ")
+                }
+            }
+            TypeOwned::Option { some_ty } => format!("{} | null", self.ty(some_ty)?),
+            TypeOwned::Result { ok_ty, err_ty } => {
+                format!(
+                    "{{ ok: {} }} | {{ err: {} }}",
+                    self.ty(ok_ty)?,
+                    self.ty(err_ty)?
+                )
+            }
+            TypeOwned::Box(ty) => self.ty(ty)?,
+            TypeOwned::Range(base) | TypeOwned::RangeInclusive(base) => {
+                let num = ts_num_ty(base);
+                format!("{{ start: {num}; end: {num} }}")
+            }
+        };
+        Ok(ts)
+    }
+
+    fn parenthesized_ty(&self, ty: &TypeOwned) -> Result<String> {
+        let ts = self.ty(ty)?;
+        if ts.contains(' ') && !ts.starts_with('{') && !ts.starts_with('[') {
+            Ok(format!("({ts})"))
+        } else {
+            Ok(ts)
+        }
+    }
+
+    fn variant_ty(&self, ident: &str, fields: &FieldsOwned) -> Result<String> {
+        let mut props = vec![format!("{ENUM_TAG}: {}", ts_str(ident))];
+        if let Some(fields) = fields_list(fields) {
+            let flags = flags(fields)?;
+            for (idx, field) in fields.iter().enumerate() {
+                let key = field_key(field, idx);
+                if key == ENUM_TAG {
+                    return Err(anyhow!(
+                        "enum variant {ident} has a field named '{ENUM_TAG}', which is used as a tag in TypeScript"
+                    ));
+                }
+                if flags.flag_for[idx].is_none() {
+                    props.push(format!("{key}: {}", self.ty(&field.ty)?));
+                }
+            }
+        }
+        Ok(format!("{{ {} }}", props.join("; ")))
+    }
+
+    /// Type description used by the runtime codec (WwTy).
+    fn desc(&self, ty: &TypeOwned) -> Result<String> {
+        let ts = match ty {
+            TypeOwned::Bool => "{ k: \"bool\" }".into(),
+            TypeOwned::NumericAny(numeric) => ts_num_desc(numeric_base(numeric)),
+            TypeOwned::OutOfLine { type_idx } => format!("{{ k: \"ref\", idx: {} }}", type_idx.0),
+            TypeOwned::Flag => return Err(anyhow!("Flag type cannot be used on its own")),
+            TypeOwned::String => "{ k: \"string\" }".into(),
+            TypeOwned::BoundedString { max_len } => {
+                format!("{{ k: \"string\", max: {} }}", max_len.0)
+            }
+            TypeOwned::Vec(_) if is_bytes(ty) => "{ k: \"bytes\" }".into(),
+            TypeOwned::BoundedVec { max_len, .. } if is_bytes(ty) => {
+                format!("{{ k: \"bytes\", max: {} }}", max_len.0)
+            }
+            TypeOwned::Vec(ty) => format!("{{ k: \"vec\", ty: {} }}", self.desc(ty)?),
+            TypeOwned::BoundedVec { max_len, ty } => format!(
+                "{{ k: \"vec\", ty: {}, max: {} }}",
+                self.desc(ty)?,
+                max_len.0
+            ),
+            TypeOwned::Array { len, ty } => {
+                format!("{{ k: \"array\", len: {}, ty: {} }}", len.0, self.desc(ty)?)
+            }
+            TypeOwned::Tuple(types) => {
+                let tys = types
+                    .iter()
+                    .map(|ty| self.desc(ty))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("{{ k: \"tuple\", tys: [{}] }}", tys.join(", "))
+            }
+            TypeOwned::Struct(item_struct) => format!(
+                "{{ k: \"struct\", name: {}, unsized: {}, fields: {} }}",
+                ts_str(&item_struct.ident),
+                item_struct.is_unsized(),
+                self.fields_desc(&item_struct.fields)?
+            ),
+            TypeOwned::Enum(item_enum) => {
+                let repr = match item_enum.repr {
+                    Repr::Nibble => "repr: \"nib\"".into(),
+                    Repr::BitAligned(bits) => format!("repr: \"bits\", bits: {bits}"),
+                    Repr::UNib32 => "repr: \"unib32\"".into(),
+                    Repr::ByteAlignedU8 => "repr: \"u8\"".into(),
+                    Repr::ByteAlignedU16 => "repr: \"u16\"".into(),
+                    Repr::ByteAlignedU32 => "repr: \"u32\"".into(),
+                };
+                let variants = item_enum
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        Ok(format!(
+                            "{{ name: {}, discriminant: {}, fields: {} }}",
+                            ts_str(&variant.ident),
+                            variant.discriminant.0,
+                            self.fields_desc(&variant.fields)?
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                format!(
+                    "{{ k: \"enum\", name: {}, unsized: {}, {repr}, variants: [{}] }}",
+                    ts_str(&item_enum.ident),
+                    item_enum.is_unsized(),
+                    variants.join(", ")
+                )
+            }
+            TypeOwned::Option { some_ty } => {
+                format!("{{ k: \"option\", ty: {} }}", self.desc(some_ty)?)
+            }
+            TypeOwned::Result { ok_ty, err_ty } => format!(
+                "{{ k: \"result\", ok: {}, err: {} }}",
+                self.desc(ok_ty)?,
+                self.desc(err_ty)?
+            ),
+            TypeOwned::Box(ty) => format!("{{ k: \"box\", ty: {} }}", self.desc(ty)?),
+            TypeOwned::Range(base) => format!("{{ k: \"range\", num: {} }}", ts_num_desc(base)),
+            TypeOwned::RangeInclusive(base) => {
+                format!("{{ k: \"range_incl\", num: {} }}", ts_num_desc(base))
+            }
+        };
+        Ok(ts)
+    }
+
+    fn fields_desc(&self, fields: &FieldsOwned) -> Result<String> {
+        let Some(fields) = fields_list(fields) else {
+            return Ok("[]".into());
+        };
+        let flags = flags(fields)?;
+        let mut descs = vec![];
+        for (idx, field) in fields.iter().enumerate() {
+            let mut desc = format!("{{ key: {}, ", ts_str(&field_key(field, idx)));
+            if let Some(flagged_idx) = flags.flag_for[idx] {
+                write!(desc, "ty: {{ k: \"bool\" }}, flagFor: {flagged_idx}")?;
+                // flag is read as false if the Option it belongs to has a default and there is no more data
+                if fields[flagged_idx].default.is_some() {
+                    desc.push_str(", default: false");
+                }
+            } else {
+                write!(desc, "ty: {}", self.desc(&field.ty)?)?;
+                if flags.flagged[idx] {
+                    desc.push_str(", flagged: true");
+                }
+            }
+            match (&field.default, &field.ty) {
+                // #[default = "None"] is stored as is_some flag value
+                (Some(ValueOwned::Bool(false)), TypeOwned::Option { .. }) => {
+                    desc.push_str(", default: null");
+                }
+                (Some(default), _) if flags.flag_for[idx].is_none() => {
+                    write!(desc, ", default: {}", self.value(default, &field.ty)?)?;
+                }
+                _ => {}
+            }
+            desc.push_str(" }");
+            descs.push(desc);
+        }
+        Ok(format!("[{}]", descs.join(", ")))
+    }
+
+    /// TypeScript literal of a value, used for field defaults.
+    fn value(&self, value: &ValueOwned, ty: &TypeOwned) -> Result<String> {
+        let ty = ty.get_in_line(self.api_bundle)?;
+        let ts = match (value, ty) {
+            (_, TypeOwned::Box(ty)) => self.value(value, ty)?,
+            (ValueOwned::Bool(b), _) => b.to_string(),
+            (ValueOwned::Numeric(num), _) => ts_num_value(num)?,
+            (ValueOwned::String(s), _) => ts_str(s),
+            (ValueOwned::Vec(items), _) if is_bytes(ty) => {
+                let bytes = items
+                    .iter()
+                    .map(ts_num_value_of)
+                    .collect::<Result<Vec<_>>>()?;
+                format!("Uint8Array.of({})", bytes.join(", "))
+            }
+            (
+                ValueOwned::Vec(items) | ValueOwned::Array(items),
+                TypeOwned::Vec(ty) | TypeOwned::BoundedVec { ty, .. } | TypeOwned::Array { ty, .. },
+            ) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.value(item, ty))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("[{}]", items.join(", "))
+            }
+            (ValueOwned::Tuple(items), TypeOwned::Tuple(types)) => {
+                let items = items
+                    .iter()
+                    .zip(types)
+                    .map(|(item, ty)| self.value(item, ty))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("[{}]", items.join(", "))
+            }
+            (ValueOwned::Struct { fields }, TypeOwned::Struct(item_struct)) => {
+                self.fields_value(None, fields, &item_struct.fields)?
+            }
+            (ValueOwned::Enum { variant, fields }, TypeOwned::Enum(item_enum)) => {
+                let variant_def = item_enum
+                    .variants
+                    .iter()
+                    .find(|v| &v.ident == variant)
+                    .ok_or_else(|| {
+                        anyhow!("enum {} does not have variant {variant}", item_enum.ident)
+                    })?;
+                self.fields_value(Some(variant), fields, &variant_def.fields)?
+            }
+            (ValueOwned::Option(None), _) => "null".into(),
+            (ValueOwned::Option(Some(value)), TypeOwned::Option { some_ty }) => {
+                self.value(value, some_ty)?
+            }
+            (ValueOwned::Result(Ok(value)), TypeOwned::Result { ok_ty, .. }) => {
+                format!("{{ ok: {} }}", self.value(value, ok_ty)?)
+            }
+            (ValueOwned::Result(Err(value)), TypeOwned::Result { err_ty, .. }) => {
+                format!("{{ err: {} }}", self.value(value, err_ty)?)
+            }
+            (ValueOwned::Range(range), _) => format!(
+                "{{ start: {}, end: {} }}",
+                ts_num_value(&range.start)?,
+                ts_num_value(&range.end)?
+            ),
+            (ValueOwned::RangeInclusive(range), _) => format!(
+                "{{ start: {}, end: {} }}",
+                ts_num_value(range.start())?,
+                ts_num_value(range.end())?
+            ),
+            _ => return Err(anyhow!("value {value:?} does not match type {ty:?}")),
+        };
+        Ok(ts)
+    }
+
+    fn fields_value(
+        &self,
+        variant: Option<&str>,
+        values: &FieldsValueOwned,
+        fields: &FieldsOwned,
+    ) -> Result<String> {
+        let mut props = vec![];
+        if let Some(variant) = variant {
+            props.push(format!("{ENUM_TAG}: {}", ts_str(variant)));
+        }
+        let defs = fields_list(fields).unwrap_or_default();
+        match values {
+            FieldsValueOwned::Named(values) => {
+                for (name, value) in values {
+                    // flag and the Option or Result it belongs to share the same name, the latter is the one needed
+                    let field = defs
+                        .iter()
+                        .rev()
+                        .find(|f| f.ident.as_deref() == Some(name.as_str()))
+                        .ok_or_else(|| anyhow!("unknown field {name}"))?;
+                    if matches!(value, ValueOwned::Bool(_)) && is_option_or_result(&field.ty) {
+                        continue;
+                    }
+                    props.push(format!("{name}: {}", self.value(value, &field.ty)?));
+                }
+            }
+            FieldsValueOwned::Unnamed(values) => {
+                for (idx, (value, field)) in values.iter().zip(defs).enumerate() {
+                    props.push(format!("_{idx}: {}", self.value(value, &field.ty)?));
+                }
+            }
+            FieldsValueOwned::Unit => {}
+        }
+        Ok(format!("{{ {} }}", props.join(", ")))
+    }
+
+    fn client_class(
+        &self,
+        ts: &mut String,
+        level: &ApiLevelOwned,
+        trait_idx: Option<usize>,
+        is_root: bool,
+    ) -> Result<()> {
+        let class_name = match trait_idx {
+            Some(idx) => self.trait_class_names[idx]
+                .clone()
+                .ok_or_else(|| anyhow!("trait #{idx} is not in-line"))?,
+            None => self.root_class_name.clone(),
+        };
+        ts.push_str(&jsdoc(&level.docs, ""));
+        writeln!(ts, "export class {class_name} {{")?;
+        writeln!(ts, "    readonly wwClient: WwClient;")?;
+        writeln!(ts, "    readonly wwPath: number[];")?;
+        writeln!(ts)?;
+        writeln!(
+            ts,
+            "    constructor(wwClient: WwClient, wwPath: number[] = []) {{"
+        )?;
+        writeln!(ts, "        this.wwClient = wwClient;")?;
+        writeln!(ts, "        this.wwPath = wwPath;")?;
+        writeln!(ts, "    }}")?;
+        if is_root {
+            writeln!(ts)?;
+            writeln!(
+                ts,
+                "    static async connect(url: string, options: WwClientOptions = {{}}): Promise<{class_name}> {{"
+            )?;
+            writeln!(
+                ts,
+                "        return new {class_name}(await WwClient.connect(url, options));"
+            )?;
+            writeln!(ts, "    }}")?;
+        }
+        for item in &level.items {
+            writeln!(ts)?;
+            self.client_item(ts, item)?;
+        }
+        writeln!(ts, "}}")?;
+        writeln!(ts)?;
+        Ok(())
+    }
+
+    fn client_item(&self, ts: &mut String, item: &ApiItemOwned) -> Result<()> {
+        let docs = jsdoc(&item.docs, "    ");
+        let (index_param, index_arg) = if item.is_array() {
+            ("index: number", ", index")
+        } else {
+            ("", "")
+        };
+        let path = format!("[...this.wwPath, {}{index_arg}]", item.id.0);
+        let camel = item.ident.to_case(Case::Camel);
+        let pascal = item.ident.to_case(Case::Pascal);
+        let with_index = |params: &[String]| {
+            let mut all = vec![];
+            if item.is_array() {
+                all.push(index_param.to_string());
+            }
+            all.extend_from_slice(params);
+            all.join(", ")
+        };
+        match &item.kind {
+            ApiItemKindOwned::Method { args, return_ty } => {
+                let mut params = vec![];
+                let mut props = vec![];
+                let mut fields = vec![];
+                for arg in args {
+                    let param = param_name(&arg.ident);
+                    params.push(format!("{param}: {}", self.ty(&arg.ty)?));
+                    props.push(if param == arg.ident {
+                        param
+                    } else {
+                        format!("{}: {param}", arg.ident)
+                    });
+                    fields.push(format!(
+                        "{{ key: {}, ty: {} }}",
+                        ts_str(&arg.ident),
+                        self.desc(&arg.ty)?
+                    ));
+                }
+                let ret_ty = match return_ty {
+                    Some(ty) => self.ty(ty)?,
+                    None => "void".into(),
+                };
+                ts.push_str(&docs);
+                writeln!(
+                    ts,
+                    "    async {camel}({}): Promise<{ret_ty}> {{",
+                    with_index(&params)
+                )?;
+                // arguments are sent as a struct, which is empty when there are none
+                if args.is_empty() {
+                    writeln!(ts, "        const args = new Uint8Array(0);")?;
+                } else {
+                    writeln!(
+                        ts,
+                        "        const args = WW_CODEC.encode(\n            {{ k: \"struct\", name: \"Args\", unsized: true, fields: [{}] }},\n            {{ {} }},\n        );",
+                        fields.join(", "),
+                        props.join(", ")
+                    )?;
+                }
+                match return_ty {
+                    Some(ty) => {
+                        writeln!(
+                            ts,
+                            "        const data = await this.wwClient.call({path}, args);"
+                        )?;
+                        writeln!(
+                            ts,
+                            "        return WW_CODEC.decode({}, data) as {ret_ty};",
+                            self.desc(ty)?
+                        )?;
+                    }
+                    None => {
+                        writeln!(ts, "        await this.wwClient.call({path}, args);")?;
+                    }
+                }
+                writeln!(ts, "    }}")?;
+            }
+            ApiItemKindOwned::Property { ty, access, .. } => {
+                let value_ty = self.ty(ty)?;
+                let desc = self.desc(ty)?;
+                let readable = !matches!(access, PropertyAccess::WriteOnly);
+                let writable = matches!(
+                    access,
+                    PropertyAccess::ReadWrite { .. } | PropertyAccess::WriteOnly
+                );
+                if readable {
+                    ts.push_str(&docs);
+                    writeln!(
+                        ts,
+                        "    async read{pascal}({}): Promise<{value_ty}> {{",
+                        with_index(&[])
+                    )?;
+                    writeln!(ts, "        const data = await this.wwClient.read({path});")?;
+                    writeln!(
+                        ts,
+                        "        return WW_CODEC.decode({desc}, data) as {value_ty};"
+                    )?;
+                    writeln!(ts, "    }}")?;
+                }
+                if readable && writable {
+                    writeln!(ts)?;
+                }
+                if writable {
+                    ts.push_str(&docs);
+                    writeln!(
+                        ts,
+                        "    async write{pascal}({}): Promise<void> {{",
+                        with_index(&[format!("value: {value_ty}")])
+                    )?;
+                    writeln!(
+                        ts,
+                        "        await this.wwClient.write({path}, WW_CODEC.encode({desc}, value));"
+                    )?;
+                    writeln!(ts, "    }}")?;
+                }
+            }
+            ApiItemKindOwned::Stream { ty, is_up } => {
+                let is_bytes = ty.is_byte_slice(self.api_bundle)?;
+                let value_ty = self.ty(ty)?;
+                let desc = self.desc(ty)?;
+                ts.push_str(&docs);
+                if *is_up {
+                    writeln!(
+                        ts,
+                        "    openStream{pascal}({}): Promise<void> {{",
+                        with_index(&[format!("onValue: (value: {value_ty}) => void")])
+                    )?;
+                    if is_bytes {
+                        writeln!(
+                            ts,
+                            "        return this.wwClient.openStream({path}, onValue);"
+                        )?;
+                    } else {
+                        writeln!(
+                            ts,
+                            "        return this.wwClient.openStream({path}, (data) => onValue(WW_CODEC.decode({desc}, data) as {value_ty}));"
+                        )?;
+                    }
+                    writeln!(ts, "    }}")?;
+                } else {
+                    writeln!(
+                        ts,
+                        "    openSink{pascal}({}): Promise<void> {{",
+                        with_index(&[])
+                    )?;
+                    writeln!(ts, "        return this.wwClient.openStream({path}, null);")?;
+                    writeln!(ts, "    }}")?;
+                    writeln!(ts)?;
+                    writeln!(
+                        ts,
+                        "    write{pascal}({}): void {{",
+                        with_index(&[format!("value: {value_ty}")])
+                    )?;
+                    let data = if is_bytes {
+                        "value".to_string()
+                    } else {
+                        format!("WW_CODEC.encode({desc}, value)")
+                    };
+                    writeln!(
+                        ts,
+                        "        this.wwClient.notify({path}, {{ kind: \"Write\", data: {data} }});"
+                    )?;
+                    writeln!(ts, "    }}")?;
+                }
+                writeln!(ts)?;
+                let close = if *is_up { "closeStream" } else { "closeSink" };
+                writeln!(
+                    ts,
+                    "    {close}{pascal}({}): Promise<void> {{",
+                    with_index(&[])
+                )?;
+                writeln!(ts, "        return this.wwClient.closeStream({path});")?;
+                writeln!(ts, "    }}")?;
+            }
+            ApiItemKindOwned::Trait { trait_idx } => {
+                let Some(Some(class_name)) = self.trait_class_names.get(trait_idx.0 as usize)
+                else {
+                    writeln!(
+                        ts,
+                        "    // {}: trait #{} is not included in the API bundle",
+                        item.ident, trait_idx.0
+                    )?;
+                    return Ok(());
+                };
+                ts.push_str(&docs);
+                if item.is_array() {
+                    writeln!(ts, "    {camel}(index: number): {class_name} {{")?;
+                } else {
+                    writeln!(ts, "    get {camel}(): {class_name} {{")?;
+                }
+                writeln!(
+                    ts,
+                    "        return new {class_name}(this.wwClient, {path});"
+                )?;
+                writeln!(ts, "    }}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn field_key(field: &FieldOwned, idx: usize) -> String {
+    match &field.ident {
+        Some(ident) => ident.clone(),
+        None => format!("_{idx}"),
+    }
+}
+
+fn param_name(ident: &str) -> String {
+    let name = ident.to_case(Case::Camel);
+    if RESERVED.contains(&name.as_str()) {
+        format!("{name}_")
+    } else {
+        name
+    }
+}
+
+fn ts_num_ty(base: &NumericBaseType) -> &'static str {
+    match base {
+        NumericBaseType::U64
+        | NumericBaseType::I64
+        | NumericBaseType::U128
+        | NumericBaseType::I128 => "bigint",
+        NumericBaseType::UB(bits) if bits.0 > 32 => "unknown",
+        NumericBaseType::IB(bits) if bits.0 > 32 => "unknown",
+        NumericBaseType::Nibble
+        | NumericBaseType::U8
+        | NumericBaseType::U16
+        | NumericBaseType::U32
+        | NumericBaseType::UNib32
+        | NumericBaseType::I32
+        | NumericBaseType::F32
+        | NumericBaseType::I8
+        | NumericBaseType::I16
+        | NumericBaseType::F64
+        | NumericBaseType::UB(_)
+        | NumericBaseType::IB(_) => "number",
+        _ => "unknown",
+    }
+}
+
+fn ts_num_desc(base: &NumericBaseType) -> String {
+    let b = match base {
+        NumericBaseType::Nibble => "nib",
+        NumericBaseType::U8 => "u8",
+        NumericBaseType::U16 => "u16",
+        NumericBaseType::U32 => "u32",
+        NumericBaseType::UNib32 => "unib32",
+        NumericBaseType::U64 => "u64",
+        NumericBaseType::I32 => "i32",
+        NumericBaseType::F32 => "f32",
+        NumericBaseType::U128 => "u128",
+        NumericBaseType::I8 => "i8",
+        NumericBaseType::I16 => "i16",
+        NumericBaseType::I64 => "i64",
+        NumericBaseType::I128 => "i128",
+        NumericBaseType::F64 => "f64",
+        // bit-aligned numbers are written bit by bit, up to 32 bits are supported by the runtime
+        NumericBaseType::UB(bits) if bits.0 <= 32 => {
+            return format!("{{ k: \"num\", b: \"ub\", bits: {} }}", bits.0);
+        }
+        NumericBaseType::IB(bits) if bits.0 <= 32 => {
+            return format!("{{ k: \"num\", b: \"ib\", bits: {} }}", bits.0);
+        }
+        other => {
+            return format!(
+                "{{ k: \"unsupported\", name: {} }}",
+                ts_str(&format!("{other:?}"))
+            );
+        }
+    };
+    format!("{{ k: \"num\", b: \"{b}\" }}")
+}
+
+fn ts_num_value(num: &NumericValue) -> Result<String> {
+    let ts = match num {
+        NumericValue::Nibble(n) => n.value().to_string(),
+        NumericValue::U8(n) => n.to_string(),
+        NumericValue::U16(n) => n.to_string(),
+        NumericValue::U32(n) | NumericValue::UNib32(n) => n.to_string(),
+        NumericValue::U64(n) => format!("{n}n"),
+        NumericValue::I32(n) => n.to_string(),
+        NumericValue::F32(n) => ts_float(*n as f64),
+        NumericValue::U128(n) => format!("{n}n"),
+        NumericValue::I8(n) => n.to_string(),
+        NumericValue::I16(n) => n.to_string(),
+        NumericValue::I64(n) => format!("{n}n"),
+        NumericValue::I128(n) => format!("{n}n"),
+        NumericValue::F64(n) => ts_float(*n),
+        NumericValue::UN(_) | NumericValue::IN(_) => {
+            return Err(anyhow!("{num:?} is not supported in TypeScript"));
+        }
+    };
+    Ok(ts)
+}
+
+fn ts_num_value_of(value: &ValueOwned) -> Result<String> {
+    match value {
+        ValueOwned::Numeric(num) => ts_num_value(num),
+        _ => Err(anyhow!("expected number, got {value:?}")),
+    }
+}
+
+fn ts_float(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".into()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.into()
+    } else {
+        format!("{n:?}")
+    }
+}
+
+/// Block comment with documentation, `*/` in the docs is escaped.
+fn jsdoc(docs: &[String], indent: &str) -> String {
+    if docs.is_empty() {
+        return String::new();
+    }
+    let lines: Vec<String> = docs
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line).replace("*/", "*\\/"))
+        .collect();
+    if lines.len() == 1 {
+        return format!("{indent}/** {} */\n", lines[0]);
+    }
+    let mut ts = format!("{indent}/**\n");
+    for line in lines {
+        if line.is_empty() {
+            ts.push_str(&format!("{indent} *\n"));
+        } else {
+            ts.push_str(&format!("{indent} * {line}\n"));
+        }
+    }
+    ts.push_str(&format!("{indent} */\n"));
+    ts
+}
+
+/// Double-quoted JavaScript string literal.
+fn ts_str(s: &str) -> String {
+    let mut ts = String::with_capacity(s.len() + 2);
+    ts.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => ts.push_str("\\\""),
+            '\\' => ts.push_str("\\\\"),
+            '\n' => ts.push_str("\\n"),
+            '\r' => ts.push_str("\\r"),
+            '\t' => ts.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{2028}' || c == '\u{2029}' => {
+                ts.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => ts.push(c),
+        }
+    }
+    ts.push('"');
+    ts
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::typescript::gen_typescript;
+    use std::path::Path;
+
+    #[test]
+    fn methods_client() {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/methods_api");
+        let api_bundle = crate::load(&crate_path, Some("Methods".into()), true).unwrap();
+        let ts = gen_typescript(&api_bundle).unwrap();
+        assert!(ts.contains("export class MethodsClient {"));
+        assert!(ts.contains("async onePlainArg(value: number): Promise<void> {"));
+        assert!(ts.contains("{ key: \"value\", ty: { k: \"num\", b: \"u8\" } }"));
+        assert!(ts.contains("export function encodeUserDefined(value: UserDefined): Uint8Array {"));
+    }
+}
@@ -0,0 +1,1360 @@
+// WireWeaver TypeScript runtime, generated API types, codecs and clients follow below.
+// Encoding rules mirror shrink_wrap::BufWriter, shrink_wrap::BufReader and ww_self dynamic serializer.
+
+export class WwError extends Error {
+    constructor(message: string) {
+        super(message);
+        this.name = "WwError";
+    }
+}
+
+const UNIB32_MORE = 0b1000;
+const MAX_U16 = 0xffff;
+const MAX_DEPTH = 256;
+
+function unib32LenNibbles(value: number): number {
+    let len = 1;
+    let rest = value >>> 3;
+    while (rest !== 0) {
+        len += 1;
+        rest = rest >>> 3;
+    }
+    return len;
+}
+
+// Writer ------------------------------------------------------------------------------------------------------------
+
+export class WwBufWriter {
+    private buf: Uint8Array = new Uint8Array(64);
+    private byteIdx = 0;
+    // next bit to be written, 7 is MSB
+    private bitIdx = 7;
+    // values written with writeU16Rev, most recent last, encoded as reversed UNib32 when an unsized object or the whole
+    // buffer is finished
+    private rev: number[] = [];
+
+    private reserve(bytes: number): void {
+        const needed = this.byteIdx + bytes + 1;
+        if (needed <= this.buf.length) {
+            return;
+        }
+        let len = this.buf.length * 2;
+        while (len < needed) {
+            len *= 2;
+        }
+        const buf = new Uint8Array(len);
+        buf.set(this.buf);
+        this.buf = buf;
+    }
+
+    writeBool(value: boolean): void {
+        this.reserve(1);
+        if (value) {
+            this.buf[this.byteIdx] |= 1 << this.bitIdx;
+        } else {
+            this.buf[this.byteIdx] &= ~(1 << this.bitIdx);
+        }
+        if (this.bitIdx === 0) {
+            this.bitIdx = 7;
+            this.byteIdx += 1;
+        } else {
+            this.bitIdx -= 1;
+        }
+    }
+
+    writeNib(value: number): void {
+        this.alignNibble();
+        this.reserve(1);
+        if (this.bitIdx === 7) {
+            this.buf[this.byteIdx] = (this.buf[this.byteIdx] & 0x0f) | ((value & 0x0f) << 4);
+            this.bitIdx = 3;
+        } else {
+            this.buf[this.byteIdx] = (this.buf[this.byteIdx] & 0xf0) | (value & 0x0f);
+            this.bitIdx = 7;
+            this.byteIdx += 1;
+        }
+    }
+
+    // Write up to 32 bits without alignment, MSB first
+    writeUn(bits: number, value: number): void {
+        for (let i = bits - 1; i >= 0; i--) {
+            this.writeBool(Math.floor(value / 2 ** i) % 2 === 1);
+        }
+    }
+
+    writeBytes(bytes: Uint8Array): void {
+        this.alignByte();
+        this.reserve(bytes.length);
+        this.buf.set(bytes, this.byteIdx);
+        this.byteIdx += bytes.length;
+    }
+
+    writeU8(value: number): void {
+        this.writeBytes(Uint8Array.of(value));
+    }
+
+    writeU16(value: number): void {
+        this.writeBytes(Uint8Array.of(value & 0xff, value >>> 8));
+    }
+
+    writeU32(value: number): void {
+        const bytes = new Uint8Array(4);
+        new DataView(bytes.buffer).setUint32(0, value, true);
+        this.writeBytes(bytes);
+    }
+
+    writeUNib32(value: number): void {
+        let rest = value;
+        let left = unib32LenNibbles(value);
+        while (left > 0) {
+            const nib = rest & 0b111;
+            this.writeNib(left > 1 ? nib | UNIB32_MORE : nib);
+            rest = rest >>> 3;
+            left -= 1;
+        }
+    }
+
+    private writeUNib32Rev(value: number): void {
+        let rest = value;
+        const len = unib32LenNibbles(value);
+        for (let i = 0; i < len; i++) {
+            const nib = rest & 0b111;
+            this.writeNib(i === 0 ? nib : nib | UNIB32_MORE);
+            rest = rest >>> 3;
+        }
+    }
+
+    // Returns a slot that can be updated later with updateU16Rev
+    writeU16Rev(value: number): number {
+        if (value > MAX_U16) {
+            throw new WwError(`value ${value} does not fit into u16`);
+        }
+        this.rev.push(value);
+        return this.rev.length - 1;
+    }
+
+    updateU16Rev(slot: number, value: number): void {
+        this.rev[slot] = value;
+    }
+
+    // Encode all the values written with writeU16Rev starting from the provided slot, most recent first
+    encodeNib16Rev(fromSlot: number): void {
+        if (this.rev.length <= fromSlot) {
+            return;
+        }
+        let totalNibbles = 0;
+        for (let i = fromSlot; i < this.rev.length; i++) {
+            totalNibbles += unib32LenNibbles(this.rev[i]);
+        }
+        this.alignNibble();
+        if (this.bitIdx !== 7) {
+            totalNibbles += 1;
+        }
+        if (totalNibbles % 2 !== 0) {
+            this.writeNib(0);
+        }
+        for (let i = this.rev.length - 1; i >= fromSlot; i--) {
+            this.writeUNib32Rev(this.rev[i]);
+        }
+        this.rev.length = fromSlot;
+    }
+
+    // Same as BufWriter::write for objects with ElementSize::Unsized: size in bytes is put in front of the object
+    writeUnsized(writeObject: () => void): void {
+        this.alignByte();
+        const slot = this.writeU16Rev(0);
+        const start = this.byteIdx;
+        writeObject();
+        this.encodeNib16Rev(slot + 1);
+        this.alignByte();
+        const size = this.byteIdx - start;
+        if (size > MAX_U16) {
+            throw new WwError("item too long");
+        }
+        this.updateU16Rev(slot, size);
+    }
+
+    alignNibble(): void {
+        if (this.bitIdx === 7 || this.bitIdx === 3) {
+            return;
+        }
+        if (this.bitIdx > 3) {
+            this.bitIdx = 3;
+        } else {
+            this.bitIdx = 7;
+            this.byteIdx += 1;
+        }
+    }
+
+    alignByte(): void {
+        if (this.bitIdx === 7) {
+            return;
+        }
+        this.bitIdx = 7;
+        this.byteIdx += 1;
+    }
+
+    finish(): Uint8Array {
+        if (this.rev.length > 0) {
+            this.encodeNib16Rev(0);
+        } else {
+            this.alignByte();
+        }
+        return this.buf.slice(0, this.byteIdx);
+    }
+}
+
+// Reader ------------------------------------------------------------------------------------------------------------
+
+export class WwBufReader {
+    private readonly buf: Uint8Array;
+    private byteIdx = 0;
+    private bitIdx = 7;
+    // reversed values are read from the back, one nibble at a time
+    private endNibble: number;
+
+    constructor(buf: Uint8Array) {
+        this.buf = buf;
+        this.endNibble = buf.length * 2;
+    }
+
+    private bitsLeft(): number {
+        return this.endNibble * 4 - (this.byteIdx * 8 + 7 - this.bitIdx);
+    }
+
+    bytesLeft(): number {
+        const left = Math.floor(this.endNibble / 2) - this.byteIdx - (this.bitIdx === 7 ? 0 : 1);
+        return Math.max(left, 0);
+    }
+
+    readBool(): boolean {
+        if (this.bitsLeft() < 1) {
+            throw new WwError("out of bounds reading bool");
+        }
+        const value = (this.buf[this.byteIdx] & (1 << this.bitIdx)) !== 0;
+        if (this.bitIdx === 0) {
+            this.bitIdx = 7;
+            this.byteIdx += 1;
+        } else {
+            this.bitIdx -= 1;
+        }
+        return value;
+    }
+
+    readNib(): number {
+        this.alignNibble();
+        if (this.bitsLeft() < 4) {
+            throw new WwError("out of bounds reading nibble");
+        }
+        if (this.bitIdx === 7) {
+            this.bitIdx = 3;
+            return this.buf[this.byteIdx] >>> 4;
+        }
+        const value = this.buf[this.byteIdx] & 0x0f;
+        this.bitIdx = 7;
+        this.byteIdx += 1;
+        return value;
+    }
+
+    readUn(bits: number): number {
+        let value = 0;
+        for (let i = 0; i < bits; i++) {
+            value = value * 2 + (this.readBool() ? 1 : 0);
+        }
+        return value;
+    }
+
+    readBytes(len: number): Uint8Array {
+        this.alignByte();
+        if (this.bytesLeft() < len) {
+            throw new WwError(`out of bounds reading ${len} bytes`);
+        }
+        const bytes = this.buf.subarray(this.byteIdx, this.byteIdx + len);
+        this.byteIdx += len;
+        return bytes;
+    }
+
+    readU8(): number {
+        return this.readBytes(1)[0];
+    }
+
+    readU16(): number {
+        const bytes = this.readBytes(2);
+        return bytes[0] | (bytes[1] << 8);
+    }
+
+    readU32(): number {
+        const bytes = this.readBytes(4);
+        return new DataView(bytes.buffer, bytes.byteOffset, 4).getUint32(0, true);
+    }
+
+    readUNib32(): number {
+        let value = 0;
+        for (let i = 0; i <= 10; i++) {
+            const nib = this.readNib();
+            if (i === 10 && (nib & UNIB32_MORE) !== 0) {
+                throw new WwError("malformed UNib32");
+            }
+            value = (value | ((nib & 0b111) << (3 * i))) >>> 0;
+            if ((nib & UNIB32_MORE) === 0) {
+                break;
+            }
+        }
+        return value;
+    }
+
+    private readNibRev(): number {
+        if (this.byteIdx >= Math.ceil(this.endNibble / 2)) {
+            throw new WwError("out of bounds reading reversed nibble");
+        }
+        this.endNibble -= 1;
+        const byte = this.buf[this.endNibble >>> 1];
+        return this.endNibble % 2 === 1 ? byte & 0x0f : byte >>> 4;
+    }
+
+    readUNib32Rev(): number {
+        let value = 0;
+        for (let i = 0; i <= 10; i++) {
+            const nib = this.readNibRev();
+            if (i === 10 && (nib & UNIB32_MORE) !== 0) {
+                throw new WwError("malformed UNib32");
+            }
+            value = (value | (nib & 0b111)) >>> 0;
+            if ((nib & UNIB32_MORE) === 0) {
+                break;
+            }
+            value = (value << 3) >>> 0;
+        }
+        return value;
+    }
+
+    // Same as BufReader::split, returns a reader over the next len bytes
+    split(len: number): WwBufReader {
+        return new WwBufReader(this.readBytes(len));
+    }
+
+    readRawStr(): string {
+        const bytes = this.readBytes(this.bytesLeft());
+        try {
+            return new TextDecoder("utf-8", { fatal: true }).decode(bytes);
+        } catch {
+            throw new WwError("malformed UTF-8");
+        }
+    }
+
+    alignNibble(): void {
+        if (this.bitIdx === 7 || this.bitIdx === 3) {
+            return;
+        }
+        if (this.bitIdx > 3) {
+            this.bitIdx = 3;
+        } else {
+            this.bitIdx = 7;
+            this.byteIdx += 1;
+        }
+    }
+
+    alignByte(): void {
+        if (this.bitIdx === 7) {
+            return;
+        }
+        this.bitIdx = 7;
+        this.byteIdx += 1;
+    }
+}
+
+// Type descriptions -------------------------------------------------------------------------------------------------
+
+export type WwNum =
+    | "nib"
+    | "u8"
+    | "u16"
+    | "u32"
+    | "unib32"
+    | "u64"
+    | "u128"
+    | "i8"
+    | "i16"
+    | "i32"
+    | "i64"
+    | "i128"
+    | "f32"
+    | "f64"
+    | "ub"
+    | "ib";
+
+export type WwRepr = "nib" | "bits" | "unib32" | "u8" | "u16" | "u32";
+
+export type WwTy =
+    | { k: "bool" }
+    | { k: "num"; b: WwNum; bits?: number }
+    | { k: "ref"; idx: number }
+    | { k: "string"; max?: number }
+    | { k: "bytes"; max?: number }
+    | { k: "vec"; ty: WwTy; max?: number }
+    | { k: "array"; len: number; ty: WwTy }
+    | { k: "tuple"; tys: WwTy[] }
+    | { k: "struct"; name: string; unsized: boolean; fields: WwField[] }
+    | { k: "enum"; name: string; unsized: boolean; repr: WwRepr; bits?: number; variants: WwVariant[] }
+    | { k: "option"; ty: WwTy }
+    | { k: "result"; ok: WwTy; err: WwTy }
+    | { k: "box"; ty: WwTy }
+    | { k: "range"; num: WwTy }
+    | { k: "range_incl"; num: WwTy }
+    | { k: "unsupported"; name: string };
+
+// Flag fields (explicit `#[flag]` or Flag type) have flagFor set to the index of the Option or Result field they belong to,
+// which is marked as flagged and does not carry its own flag.
+export interface WwField {
+    key: string;
+    ty: WwTy;
+    flagFor?: number;
+    flagged?: boolean;
+    default?: unknown;
+}
+
+export interface WwVariant {
+    name: string;
+    discriminant: number;
+    fields: WwField[];
+}
+
+// Name of the property holding enum variant name
+export const WW_ENUM_TAG = "kind";
+
+type WwObject = Record<string, unknown>;
+
+function numRange(b: WwNum, bits: number): [number, number] {
+    switch (b) {
+        case "nib":
+            return [0, 15];
+        case "u8":
+            return [0, 0xff];
+        case "u16":
+            return [0, 0xffff];
+        case "u32":
+        case "unib32":
+            return [0, 0xffff_ffff];
+        case "i8":
+            return [-0x80, 0x7f];
+        case "i16":
+            return [-0x8000, 0x7fff];
+        case "i32":
+            return [-0x8000_0000, 0x7fff_ffff];
+        case "ub":
+            return [0, 2 ** bits - 1];
+        case "ib":
+            return [-(2 ** (bits - 1)), 2 ** (bits - 1) - 1];
+        default:
+            return [-Infinity, Infinity];
+    }
+}
+
+function writeNum(wr: WwBufWriter, ty: WwTy, value: unknown): void {
+    if (ty.k !== "num") {
+        throw new WwError(`numeric type expected, got ${ty.k}`);
+    }
+    const bits = ty.bits ?? 0;
+    if (ty.b === "u64" || ty.b === "i64" || ty.b === "u128" || ty.b === "i128") {
+        if (typeof value !== "bigint") {
+            throw new WwError(`bigint expected for ${ty.b}, got ${typeof value}`);
+        }
+        const len = ty.b === "u64" || ty.b === "i64" ? 8 : 16;
+        const signed = ty.b === "i64" || ty.b === "i128";
+        const width = BigInt(len * 8);
+        const min = signed ? -(1n << (width - 1n)) : 0n;
+        const max = signed ? (1n << (width - 1n)) - 1n : (1n << width) - 1n;
+        if (value < min || value > max) {
+            throw new WwError(`${value} is out of ${ty.b} range`);
+        }
+        const bytes = new Uint8Array(len);
+        let rest = BigInt.asUintN(len * 8, value);
+        for (let i = 0; i < len; i++) {
+            bytes[i] = Number(rest & 0xffn);
+            rest >>= 8n;
+        }
+        wr.writeBytes(bytes);
+        return;
+    }
+    if (typeof value !== "number") {
+        throw new WwError(`number expected for ${ty.b}, got ${typeof value}`);
+    }
+    if (ty.b === "f32" || ty.b === "f64") {
+        const bytes = new Uint8Array(ty.b === "f32" ? 4 : 8);
+        const view = new DataView(bytes.buffer);
+        if (ty.b === "f32") {
+            view.setFloat32(0, value, true);
+        } else {
+            view.setFloat64(0, value, true);
+        }
+        wr.writeBytes(bytes);
+        return;
+    }
+    const [min, max] = numRange(ty.b, bits);
+    if (!Number.isInteger(value) || value < min || value > max) {
+        throw new WwError(`${value} is out of ${ty.b}${bits > 0 ? bits : ""} range`);
+    }
+    switch (ty.b) {
+        case "nib":
+            wr.writeNib(value);
+            break;
+        case "u8":
+        case "i8":
+            wr.writeU8(value & 0xff);
+            break;
+        case "u16":
+        case "i16":
+            wr.writeU16(value & 0xffff);
+            break;
+        case "u32":
+        case "i32":
+            wr.writeU32(value >>> 0);
+            break;
+        case "unib32":
+            wr.writeUNib32(value);
+            break;
+        case "ub":
+            wr.writeUn(bits, value);
+            break;
+        case "ib":
+            wr.writeUn(bits, value < 0 ? value + 2 ** bits : value);
+            break;
+    }
+}
+
+function readNum(rd: WwBufReader, ty: WwTy): number | bigint {
+    if (ty.k !== "num") {
+        throw new WwError(`numeric type expected, got ${ty.k}`);
+    }
+    const bits = ty.bits ?? 0;
+    switch (ty.b) {
+        case "nib":
+            return rd.readNib();
+        case "u8":
+            return rd.readU8();
+        case "i8":
+            return (rd.readU8() << 24) >> 24;
+        case "u16":
+            return rd.readU16();
+        case "i16":
+            return (rd.readU16() << 16) >> 16;
+        case "u32":
+            return rd.readU32();
+        case "i32":
+            return rd.readU32() | 0;
+        case "unib32":
+            return rd.readUNib32();
+        case "ub":
+            return rd.readUn(bits);
+        case "ib": {
+            const value = rd.readUn(bits);
+            return value >= 2 ** (bits - 1) ? value - 2 ** bits : value;
+        }
+        case "f32":
+        case "f64": {
+            const len = ty.b === "f32" ? 4 : 8;
+            const bytes = rd.readBytes(len);
+            const view = new DataView(bytes.buffer, bytes.byteOffset, len);
+            return ty.b === "f32" ? view.getFloat32(0, true) : view.getFloat64(0, true);
+        }
+        default: {
+            const len = ty.b === "u64" || ty.b === "i64" ? 8 : 16;
+            const bytes = rd.readBytes(len);
+            let value = 0n;
+            for (let i = len - 1; i >= 0; i--) {
+                value = (value << 8n) | BigInt(bytes[i]);
+            }
+            return ty.b === "i64" || ty.b === "i128" ? BigInt.asIntN(len * 8, value) : value;
+        }
+    }
+}
+
+function isNone(value: unknown): boolean {
+    return value === null || value === undefined;
+}
+
+function isOk(value: unknown): boolean {
+    if (typeof value !== "object" || value === null) {
+        throw new WwError("Result value must be { ok: T } or { err: E }");
+    }
+    return "ok" in value;
+}
+
+function checkMaxLen(kind: string, len: number, max: number | undefined): void {
+    if (max !== undefined && len > max) {
+        throw new WwError(`${kind} of length ${len} exceeds maximum length of ${max}`);
+    }
+}
+
+// Encodes and decodes values according to type descriptions, out-of-line types are looked up in the provided table.
+export class WwCodec {
+    private readonly types: WwTy[];
+
+    constructor(types: WwTy[]) {
+        this.types = types;
+    }
+
+    // Serialize a value the same way as to_ww_bytes() does, without size in front
+    encode(ty: WwTy, value: unknown): Uint8Array {
+        const wr = new WwBufWriter();
+        this.writeInner(wr, ty, value, 0);
+        return wr.finish();
+    }
+
+    // Deserialize a value the same way as from_ww_bytes() does
+    decode(ty: WwTy, bytes: Uint8Array): unknown {
+        return this.readInner(new WwBufReader(bytes), ty, 0);
+    }
+
+    resolve(ty: WwTy): WwTy {
+        if (ty.k !== "ref") {
+            return ty;
+        }
+        const resolved = this.types[ty.idx];
+        if (resolved === undefined) {
+            throw new WwError(`type #${ty.idx} is not in the table`);
+        }
+        return resolved;
+    }
+
+    isUnsized(ty: WwTy): boolean {
+        const resolved = this.resolve(ty);
+        switch (resolved.k) {
+            case "string":
+            case "box":
+                return true;
+            case "struct":
+            case "enum":
+                return resolved.unsized;
+            default:
+                return false;
+        }
+    }
+
+    write(wr: WwBufWriter, ty: WwTy, value: unknown, depth: number): void {
+        if (this.isUnsized(ty)) {
+            wr.writeUnsized(() => this.writeInner(wr, ty, value, depth));
+        } else {
+            this.writeInner(wr, ty, value, depth);
+        }
+    }
+
+    read(rd: WwBufReader, ty: WwTy, depth: number): unknown {
+        if (this.isUnsized(ty)) {
+            const len = rd.readUNib32Rev();
+            return this.readInner(rd.split(len), ty, depth);
+        }
+        return this.readInner(rd, ty, depth);
+    }
+
+    private writeInner(wr: WwBufWriter, ty: WwTy, value: unknown, depth: number): void {
+        if (depth > MAX_DEPTH) {
+            throw new WwError("maximum nesting depth exceeded");
+        }
+        const resolved = this.resolve(ty);
+        switch (resolved.k) {
+            case "bool":
+                if (typeof value !== "boolean") {
+                    throw new WwError(`boolean expected, got ${typeof value}`);
+                }
+                wr.writeBool(value);
+                break;
+            case "num":
+                writeNum(wr, resolved, value);
+                break;
+            case "string": {
+                if (typeof value !== "string") {
+                    throw new WwError(`string expected, got ${typeof value}`);
+                }
+                const bytes = new TextEncoder().encode(value);
+                checkMaxLen("String", bytes.length, resolved.max);
+                wr.writeBytes(bytes);
+                break;
+            }
+            case "bytes":
+                if (!(value instanceof Uint8Array)) {
+                    throw new WwError("Uint8Array expected");
+                }
+                checkMaxLen("Vec", value.length, resolved.max);
+                wr.writeU16Rev(value.length);
+                wr.writeBytes(value);
+                break;
+            case "vec":
+                if (!Array.isArray(value)) {
+                    throw new WwError("array expected");
+                }
+                checkMaxLen("Vec", value.length, resolved.max);
+                wr.writeU16Rev(value.length);
+                for (const item of value) {
+                    this.write(wr, resolved.ty, item, depth + 1);
+                }
+                break;
+            case "array":
+                if (!Array.isArray(value) || value.length !== resolved.len) {
+                    throw new WwError(`array of length ${resolved.len} expected`);
+                }
+                for (const item of value) {
+                    this.write(wr, resolved.ty, item, depth + 1);
+                }
+                break;
+            case "tuple":
+                if (!Array.isArray(value) || value.length !== resolved.tys.length) {
+                    throw new WwError(`tuple of length ${resolved.tys.length} expected`);
+                }
+                resolved.tys.forEach((itemTy, i) => this.write(wr, itemTy, value[i], depth + 1));
+                break;
+            case "struct":
+                this.writeFields(wr, resolved.fields, value, depth);
+                break;
+            case "enum": {
+                const variantName = typeof value === "object" && value !== null ? (value as WwObject)[WW_ENUM_TAG] : undefined;
+                const variant = resolved.variants.find((v) => v.name === variantName);
+                if (variant === undefined) {
+                    throw new WwError(`enum ${resolved.name} does not have variant ${String(variantName)}`);
+                }
+                writeDiscriminant(wr, resolved.repr, resolved.bits ?? 0, variant.discriminant);
+                this.writeFields(wr, variant.fields, value, depth);
+                break;
+            }
+            case "option":
+                wr.writeBool(!isNone(value));
+                this.writeFlagged(wr, resolved, value, depth);
+                break;
+            case "result":
+                wr.writeBool(isOk(value));
+                this.writeFlagged(wr, resolved, value, depth);
+                break;
+            case "box":
+                this.writeInner(wr, resolved.ty, value, depth + 1);
+                break;
+            case "range":
+            case "range_incl": {
+                const range = value as { start: unknown; end: unknown };
+                writeNum(wr, resolved.num, range.start);
+                writeNum(wr, resolved.num, range.end);
+                break;
+            }
+            case "unsupported":
+                throw new WwError(`unsupported type: ${resolved.name}`);
+        }
+    }
+
+    // Option or Result value without a flag
+    private writeFlagged(wr: WwBufWriter, ty: WwTy, value: unknown, depth: number): void {
+        const resolved = this.resolve(ty);
+        if (resolved.k === "option") {
+            if (!isNone(value)) {
+                this.write(wr, resolved.ty, value, depth + 1);
+            }
+        } else if (resolved.k === "result") {
+            const result = value as WwObject;
+            if (isOk(value)) {
+                this.write(wr, resolved.ok, result.ok, depth + 1);
+            } else {
+                this.write(wr, resolved.err, result.err, depth + 1);
+            }
+        } else {
+            throw new WwError(`flag can only be used with Option or Result, got ${resolved.k}`);
+        }
+    }
+
+    private writeFields(wr: WwBufWriter, fields: WwField[], value: unknown, depth: number): void {
+        if (fields.length === 0) {
+            return;
+        }
+        if (typeof value !== "object" || value === null) {
+            throw new WwError("object expected");
+        }
+        const obj = value as WwObject;
+        for (const field of fields) {
+            if (field.flagFor !== undefined) {
+                const flagged = fields[field.flagFor];
+                const flaggedTy = this.resolve(flagged.ty);
+                const flaggedValue = obj[flagged.key];
+                wr.writeBool(flaggedTy.k === "result" ? isOk(flaggedValue) : !isNone(flaggedValue));
+            } else if (field.flagged) {
+                this.writeFlagged(wr, field.ty, obj[field.key], depth);
+            } else {
+                this.write(wr, field.ty, obj[field.key], depth + 1);
+            }
+        }
+    }
+
+    private readInner(rd: WwBufReader, ty: WwTy, depth: number): unknown {
+        if (depth > MAX_DEPTH) {
+            throw new WwError("maximum nesting depth exceeded");
+        }
+        const resolved = this.resolve(ty);
+        switch (resolved.k) {
+            case "bool":
+                return rd.readBool();
+            case "num":
+                return readNum(rd, resolved);
+            case "string": {
+                const value = rd.readRawStr();
+                checkMaxLen("String", new TextEncoder().encode(value).length, resolved.max);
+                return value;
+            }
+            case "bytes": {
+                const len = rd.readUNib32Rev();
+                checkMaxLen("Vec", len, resolved.max);
+                return rd.readBytes(len).slice();
+            }
+            case "vec": {
+                const len = rd.readUNib32Rev();
+                checkMaxLen("Vec", len, resolved.max);
+                return this.readItems(rd, resolved.ty, len, depth);
+            }
+            case "array":
+                return this.readItems(rd, resolved.ty, resolved.len, depth);
+            case "tuple":
+                return resolved.tys.map((itemTy) => this.read(rd, itemTy, depth + 1));
+            case "struct":
+                return this.readFields(rd, resolved.fields, {}, depth);
+            case "enum": {
+                const discriminant = readDiscriminant(rd, resolved.repr, resolved.bits ?? 0);
+                const variant = resolved.variants.find((v) => v.discriminant === discriminant);
+                if (variant === undefined) {
+                    throw new WwError(`enum ${resolved.name} does not have variant: ${discriminant}`);
+                }
+                const obj: WwObject = {};
+                obj[WW_ENUM_TAG] = variant.name;
+                return this.readFields(rd, variant.fields, obj, depth);
+            }
+            case "option":
+            case "result":
+                return this.readFlagged(rd, resolved, rd.readBool(), depth);
+            case "box":
+                return this.readInner(rd, resolved.ty, depth + 1);
+            case "range":
+            case "range_incl": {
+                const start = readNum(rd, resolved.num);
+                const end = readNum(rd, resolved.num);
+                return { start, end };
+            }
+            case "unsupported":
+                throw new WwError(`unsupported type: ${resolved.name}`);
+        }
+    }
+
+    private readItems(rd: WwBufReader, ty: WwTy, len: number, depth: number): unknown[] {
+        const items = [];
+        for (let i = 0; i < len; i++) {
+            items.push(this.read(rd, ty, depth + 1));
+        }
+        return items;
+    }
+
+    private readFlagged(rd: WwBufReader, ty: WwTy, flag: boolean, depth: number): unknown {
+        const resolved = this.resolve(ty);
+        if (resolved.k === "option") {
+            return flag ? this.read(rd, resolved.ty, depth + 1) : null;
+        } else if (resolved.k === "result") {
+            return flag ? { ok: this.read(rd, resolved.ok, depth + 1) } : { err: this.read(rd, resolved.err, depth + 1) };
+        }
+        throw new WwError(`flag can only be used with Option or Result, got ${resolved.k}`);
+    }
+
+    private readFields(rd: WwBufReader, fields: WwField[], obj: WwObject, depth: number): WwObject {
+        const flags: Map<number, boolean> = new Map();
+        fields.forEach((field, i) => {
+            try {
+                if (field.flagFor !== undefined) {
+                    flags.set(field.flagFor, rd.readBool());
+                } else if (field.flagged) {
+                    obj[field.key] = this.readFlagged(rd, field.ty, flags.get(i) ?? false, depth);
+                } else {
+                    obj[field.key] = this.read(rd, field.ty, depth + 1);
+                }
+            } catch (e) {
+                // fields added in later versions fall back to their defaults when reading older data
+                if (field.default === undefined) {
+                    throw e;
+                }
+                if (field.flagFor !== undefined) {
+                    flags.set(field.flagFor, field.default === true);
+                } else {
+                    obj[field.key] = field.default;
+                }
+            }
+        });
+        return obj;
+    }
+}
+
+function writeDiscriminant(wr: WwBufWriter, repr: WwRepr, bits: number, discriminant: number): void {
+    switch (repr) {
+        case "nib":
+            wr.writeNib(discriminant);
+            break;
+        case "bits":
+            wr.writeUn(bits, discriminant);
+            break;
+        case "unib32":
+            wr.writeUNib32(discriminant);
+            break;
+        case "u8":
+            wr.writeU8(discriminant);
+            break;
+        case "u16":
+            wr.writeU16(discriminant);
+            break;
+        case "u32":
+            wr.writeU32(discriminant);
+            break;
+    }
+}
+
+function readDiscriminant(rd: WwBufReader, repr: WwRepr, bits: number): number {
+    switch (repr) {
+        case "nib":
+            return rd.readNib();
+        case "bits":
+            return rd.readUn(bits);
+        case "unib32":
+            return rd.readUNib32();
+        case "u8":
+            return rd.readU8();
+        case "u16":
+            return rd.readU16();
+        case "u32":
+            return rd.readU32();
+    }
+}
+
+// ww_client_server --------------------------------------------------------------------------------------------------
+
+export type WwShaperConfig = { kind: "NoLimit" } | { kind: "MaxBitrate"; bytesPerS: number } | { kind: "MaxRate"; eventsPerS: number };
+
+export type WwStreamSidebandCommand =
+    | { kind: "Open" }
+    | { kind: "Close" }
+    | { kind: "FrameSync" }
+    | { kind: "ChangeRate"; config: WwShaperConfig }
+    | { kind: "SizeHint"; size: number }
+    | { kind: "User"; value: number };
+
+export type WwRequestKind =
+    | { kind: "Call"; args: Uint8Array }
+    | { kind: "Read" }
+    | { kind: "Write"; data: Uint8Array }
+    | { kind: "Subscribe" }
+    | { kind: "Unsubscribe" }
+    | { kind: "ChangeRate"; config: WwShaperConfig }
+    | { kind: "StreamSideband"; cmd: WwStreamSidebandCommand }
+    | { kind: "Introspect" }
+    | { kind: "Cancel"; seq: number };
+
+export type WwStreamSidebandEvent =
+    | { kind: "Opened" }
+    | { kind: "Closed" }
+    | { kind: "FrameSync" }
+    | { kind: "SizeHint"; size: number }
+    | { kind: "User"; value: number };
+
+export type WwEventKind =
+    | { kind: "ReturnValue"; data: Uint8Array }
+    | { kind: "ReadValue"; data: Uint8Array }
+    | { kind: "Written" }
+    | { kind: "StreamData"; path: number[]; data: Uint8Array }
+    | { kind: "StreamSideband"; path: number[]; event: WwStreamSidebandEvent }
+    | { kind: "Subscribed"; path: number[] }
+    | { kind: "Unsubscribed"; path: number[] }
+    | { kind: "RateChanged" };
+
+export const WW_ERROR_KINDS = [
+    "OperationNotSupported",
+    "BadPath",
+    "BadIndex",
+    "ExpectedArrayIndexGotNone",
+    "ArrayIndexDesFailed",
+    "ArgsDesFailed",
+    "PathDesFailed",
+    "PropertyDesFailed",
+    "ResponseSerFailed",
+    "OperationNotImplemented",
+    "ReadPropertyWithSeqZero",
+    "PathKindNotSupported",
+    "UserBytes",
+    "UserStr",
+];
+
+// Error reported by the server
+export class WwRemoteError extends WwError {
+    readonly errSeq: number;
+    readonly kind: string;
+    readonly userBytes: Uint8Array | null;
+    readonly userStr: string | null;
+
+    constructor(errSeq: number, kind: string, userBytes: Uint8Array | null, userStr: string | null) {
+        super(`${kind}${userStr !== null ? ": " + userStr : ""} (err_seq: ${errSeq})`);
+        this.name = "WwRemoteError";
+        this.errSeq = errSeq;
+        this.kind = kind;
+        this.userBytes = userBytes;
+        this.userStr = userStr;
+    }
+}
+
+export interface WwEvent {
+    seq: number;
+    result: { ok: WwEventKind } | { err: WwRemoteError };
+}
+
+function writeByteVec(wr: WwBufWriter, bytes: Uint8Array): void {
+    wr.writeU16Rev(bytes.length);
+    wr.writeBytes(bytes);
+}
+
+function writeShaperConfig(wr: WwBufWriter, config: WwShaperConfig): void {
+    wr.writeUnsized(() => {
+        switch (config.kind) {
+            case "NoLimit":
+                wr.writeNib(0);
+                break;
+            case "MaxBitrate":
+                wr.writeNib(1);
+                wr.writeU32(config.bytesPerS);
+                break;
+            case "MaxRate":
+                wr.writeNib(2);
+                wr.writeU32(config.eventsPerS);
+                break;
+        }
+    });
+}
+
+// Serialize ww_client_server::Request with an absolute path
+export function wwEncodeRequest(seq: number, path: number[], kind: WwRequestKind): Uint8Array {
+    const wr = new WwBufWriter();
+    wr.writeU16(seq);
+    wr.writeNib(0); // PathKind::Absolute
+    wr.writeU16Rev(path.length);
+    for (const id of path) {
+        wr.writeUNib32(id);
+    }
+    switch (kind.kind) {
+        case "Call":
+            wr.writeNib(0);
+            writeByteVec(wr, kind.args);
+            break;
+        case "Read":
+            wr.writeNib(2);
+            break;
+        case "Write":
+            wr.writeNib(4);
+            writeByteVec(wr, kind.data);
+            break;
+        case "Subscribe":
+            wr.writeNib(6);
+            break;
+        case "Unsubscribe":
+            wr.writeNib(7);
+            break;
+        case "ChangeRate":
+            wr.writeNib(8);
+            writeShaperConfig(wr, kind.config);
+            break;
+        case "StreamSideband": {
+            wr.writeNib(9);
+            const cmd = kind.cmd;
+            switch (cmd.kind) {
+                case "Open":
+                    wr.writeNib(0);
+                    break;
+                case "Close":
+                    wr.writeNib(1);
+                    break;
+                case "FrameSync":
+                    wr.writeNib(2);
+                    break;
+                case "ChangeRate":
+                    wr.writeNib(3);
+                    writeShaperConfig(wr, cmd.config);
+                    break;
+                case "SizeHint":
+                    wr.writeNib(4);
+                    wr.writeU32(cmd.size);
+                    break;
+                case "User":
+                    wr.writeNib(5);
+                    wr.writeU32(cmd.value);
+                    break;
+            }
+            break;
+        }
+        case "Introspect":
+            wr.writeNib(10);
+            break;
+        case "Cancel":
+            wr.writeNib(11);
+            wr.writeU16(kind.seq);
+            break;
+    }
+    return wr.finish();
+}
+
+function readByteVec(rd: WwBufReader): Uint8Array {
+    const len = rd.readUNib32Rev();
+    return rd.readBytes(len).slice();
+}
+
+function readPath(rd: WwBufReader): number[] {
+    const len = rd.readUNib32Rev();
+    const path = [];
+    for (let i = 0; i < len; i++) {
+        path.push(rd.readUNib32());
+    }
+    return path;
+}
+
+function readSidebandEvent(rd: WwBufReader): WwStreamSidebandEvent {
+    const discriminant = rd.readNib();
+    switch (discriminant) {
+        case 0:
+            return { kind: "Opened" };
+        case 1:
+            return { kind: "Closed" };
+        case 2:
+            return { kind: "FrameSync" };
+        case 3:
+            return { kind: "SizeHint", size: rd.readU32() };
+        case 4:
+            return { kind: "User", value: rd.readU32() };
+        default:
+            throw new WwError(`unknown StreamSidebandEvent: ${discriminant}`);
+    }
+}
+
+function readEventKind(rd: WwBufReader): WwEventKind {
+    const discriminant = rd.readNib();
+    switch (discriminant) {
+        case 0:
+            return { kind: "ReturnValue", data: readByteVec(rd) };
+        case 1:
+            return { kind: "ReadValue", data: readByteVec(rd) };
+        case 2:
+            return { kind: "Written" };
+        case 3: {
+            const path = readPath(rd);
+            return { kind: "StreamData", path, data: readByteVec(rd) };
+        }
+        case 4: {
+            const path = readPath(rd);
+            return { kind: "StreamSideband", path, event: readSidebandEvent(rd) };
+        }
+        case 5:
+            return { kind: "Subscribed", path: readPath(rd) };
+        case 6:
+            return { kind: "Unsubscribed", path: readPath(rd) };
+        case 7:
+            return { kind: "RateChanged" };
+        default:
+            throw new WwError(`unknown EventKind: ${discriminant}`);
+    }
+}
+
+function readRemoteError(rd: WwBufReader): WwRemoteError {
+    const errSeq = rd.readU32();
+    const kindRd = rd.split(rd.readUNib32Rev());
+    const discriminant = kindRd.readUNib32();
+    const kind = WW_ERROR_KINDS[discriminant] ?? `Unknown(${discriminant})`;
+    let userBytes = null;
+    let userStr = null;
+    if (kind === "UserBytes") {
+        userBytes = readByteVec(kindRd);
+    } else if (kind === "UserStr") {
+        userStr = kindRd.split(kindRd.readUNib32Rev()).readRawStr();
+    }
+    return new WwRemoteError(errSeq, kind, userBytes, userStr);
+}
+
+// Deserialize ww_client_server::Event
+export function wwDecodeEvent(bytes: Uint8Array): WwEvent {
+    const rd = new WwBufReader(bytes);
+    const seq = rd.readU16();
+    if (rd.readBool()) {
+        return { seq, result: { ok: readEventKind(rd) } };
+    }
+    const errRd = rd.split(rd.readUNib32Rev());
+    return { seq, result: { err: readRemoteError(errRd) } };
+}
+
+// Client ------------------------------------------------------------------------------------------------------------
+
+// Sideband messages are the same as used by wire_weaver_net_host WebSocket client
+const WS_VERSIONS_REQUEST = "versions?";
+const WS_LINK_SETUP = "link_setup 2048 0 6 0 1 100 0 1";
+
+interface WwPending {
+    resolve: (kind: WwEventKind) => void;
+    reject: (e: Error) => void;
+    timer: ReturnType<typeof setTimeout>;
+}
+
+export interface WwClientOptions {
+    // How long to wait for a response, in milliseconds
+    timeoutMs?: number;
+}
+
+// Speaks ww_client_server over any message based transport, see WwClient.connect for WebSocket.
+export class WwClient {
+    private readonly send: (bytes: Uint8Array) => void;
+    private readonly timeoutMs: number;
+    private readonly pending: Map<number, WwPending> = new Map();
+    private readonly streams: Map<string, (data: Uint8Array) => void> = new Map();
+    private nextSeq = 1;
+    // crate name and version reported by the device, "crate_id major.minor.patch"
+    deviceInfo: string | null = null;
+    onDisconnect: (() => void) | null = null;
+
+    constructor(send: (bytes: Uint8Array) => void, options: WwClientOptions = {}) {
+        this.send = send;
+        this.timeoutMs = options.timeoutMs ?? 2000;
+    }
+
+    // Connect to a WebSocket server and perform the link setup
+    static connect(url: string, options: WwClientOptions = {}): Promise<WwClient> {
+        return new Promise((resolve, reject) => {
+            const ws = new WebSocket(url);
+            ws.binaryType = "arraybuffer";
+            const client = new WwClient((bytes) => ws.send(bytes), options);
+            let linkUp = false;
+            ws.onopen = () => ws.send(WS_VERSIONS_REQUEST);
+            ws.onmessage = (message) => {
+                if (typeof message.data === "string") {
+                    const op = message.data.split(" ")[0];
+                    if (op === "device_info") {
+                        client.deviceInfo = message.data.substring(op.length + 1);
+                        ws.send(WS_LINK_SETUP);
+                    } else if (op === "link_setup_result") {
+                        linkUp = true;
+                        resolve(client);
+                    }
+                } else {
+                    client.handleEvent(new Uint8Array(message.data));
+                }
+            };
+            ws.onerror = () => {
+                if (!linkUp) {
+                    reject(new WwError(`failed to connect to ${url}`));
+                }
+            };
+            ws.onclose = () => {
+                if (!linkUp) {
+                    reject(new WwError(`${url} closed before link setup`));
+                }
+                client.disconnected();
+            };
+        });
+    }
+
+    // Feed bytes of one ww_client_server::Event received from the server
+    handleEvent(bytes: Uint8Array): void {
+        const event = wwDecodeEvent(bytes);
+        if ("ok" in event.result) {
+            const kind = event.result.ok;
+            if (kind.kind === "StreamData") {
+                this.streams.get(kind.path.join("."))?.(kind.data);
+            }
+            if (event.seq !== 0) {
+                const pending = this.takePending(event.seq);
+                pending?.resolve(kind);
+            }
+        } else {
+            this.takePending(event.seq)?.reject(event.result.err);
+        }
+    }
+
+    // Reject all pending requests, must be called when the transport is closed
+    disconnected(): void {
+        for (const seq of [...this.pending.keys()]) {
+            this.takePending(seq)?.reject(new WwError("disconnected"));
+        }
+        this.onDisconnect?.();
+    }
+
+    private takePending(seq: number): WwPending | undefined {
+        const pending = this.pending.get(seq);
+        if (pending !== undefined) {
+            clearTimeout(pending.timer);
+            this.pending.delete(seq);
+        }
+        return pending;
+    }
+
+    private allocSeq(): number {
+        for (let i = 0; i < MAX_U16; i++) {
+            const seq = this.nextSeq;
+            this.nextSeq = seq === MAX_U16 ? 1 : seq + 1;
+            if (!this.pending.has(seq)) {
+                return seq;
+            }
+        }
+        throw new WwError("no more request IDs available");
+    }
+
+    // Send a request and wait for the corresponding event
+    request(path: number[], kind: WwRequestKind): Promise<WwEventKind> {
+        const seq = this.allocSeq();
+        return new Promise((resolve, reject) => {
+            const timer = setTimeout(() => {
+                this.takePending(seq);
+                reject(new WwError(`request ${seq} timed out`));
+            }, this.timeoutMs);
+            this.pending.set(seq, { resolve, reject, timer });
+            try {
+                this.send(wwEncodeRequest(seq, path, kind));
+            } catch (e) {
+                this.takePending(seq);
+                reject(e);
+            }
+        });
+    }
+
+    // Send a request without waiting for a response (seq = 0)
+    notify(path: number[], kind: WwRequestKind): void {
+        this.send(wwEncodeRequest(0, path, kind));
+    }
+
+    async call(path: number[], args: Uint8Array): Promise<Uint8Array> {
+        const kind = await this.request(path, { kind: "Call", args });
+        if (kind.kind !== "ReturnValue") {
+            throw new WwError(`ReturnValue expected, got ${kind.kind}`);
+        }
+        return kind.data;
+    }
+
+    async read(path: number[]): Promise<Uint8Array> {
+        const kind = await this.request(path, { kind: "Read" });
+        if (kind.kind !== "ReadValue") {
+            throw new WwError(`ReadValue expected, got ${kind.kind}`);
+        }
+        return kind.data;
+    }
+
+    async write(path: number[], data: Uint8Array): Promise<void> {
+        const kind = await this.request(path, { kind: "Write", data });
+        if (kind.kind !== "Written") {
+            throw new WwError(`Written expected, got ${kind.kind}`);
+        }
+    }
+
+    // Open a stream and call onData with bytes of each StreamData event for it, sinks do not receive any data
+    async openStream(path: number[], onData: ((data: Uint8Array) => void) | null): Promise<void> {
+        if (onData !== null) {
+            this.streams.set(path.join("."), onData);
+        }
+        try {
+            await this.request(path, { kind: "StreamSideband", cmd: { kind: "Open" } });
+        } catch (e) {
+            this.streams.delete(path.join("."));
+            throw e;
+        }
+    }
+
+    async closeStream(path: number[]): Promise<void> {
+        this.streams.delete(path.join("."));
+        await this.request(path, { kind: "StreamSideband", cmd: { kind: "Close" } });
+    }
+}
@@ -1,8 +1,11 @@
+use anyhow::{Result, anyhow};
 use convert_case::{Case, Casing};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
+use std::collections::HashSet;
 use syn::{Path, PathArguments, PathSegment};
-use ww_self::{ApiBundleOwned, ApiLevelOwned};
+use ww_numeric::{NumericAnyTypeOwned, NumericBaseType};
+use ww_self::{ApiBundleOwned, ApiLevelOwned, FieldOwned, FieldsOwned, TypeOwned};
 
 pub fn maybe_quote(condition: bool, tokens_if_true: TokenStream) -> TokenStream {
     if condition {
@@ -69,3 +72,81 @@ pub(crate) fn str_to_path(path: &str) -> Path {
             .collect(),
     }
 }
+
+/// Flag pairing of struct or variant fields: explicit flags (bool or Flag) are serialized in their own position
+/// and carry the is_some/is_ok state of a later Option or Result field with the same name.
+pub(crate) struct Flags {
+    pub(crate) flag_for: Vec<Option<usize>>,
+    pub(crate) flagged: Vec<bool>,
+}
+
+pub(crate) fn flags(fields: &[FieldOwned]) -> Result<Flags> {
+    let mut flag_for = vec![None; fields.len()];
+    let mut flagged = vec![false; fields.len()];
+    for (idx, field) in fields.iter().enumerate() {
+        if !matches!(field.ty, TypeOwned::Bool | TypeOwned::Flag) {
+            continue;
+        }
+        let target = field.ident.as_ref().and_then(|ident| {
+            fields
+                .iter()
+                .enumerate()
+                .skip(idx + 1)
+                .find(|(_, f)| f.ident.as_ref() == Some(ident) && is_option_or_result(&f.ty))
+                .map(|(target, _)| target)
+        });
+        match target {
+            Some(target) => {
+                flag_for[idx] = Some(target);
+                flagged[target] = true;
+            }
+            None if matches!(field.ty, TypeOwned::Flag) => {
+                return Err(anyhow!(
+                    "flag {:?} is not followed by an Option or Result with the same name",
+                    field.ident
+                ));
+            }
+            None => {}
+        }
+    }
+    Ok(Flags { flag_for, flagged })
+}
+
+pub(crate) fn is_option_or_result(ty: &TypeOwned) -> bool {
+    matches!(ty, TypeOwned::Option { .. } | TypeOwned::Result { .. })
+}
+
+pub(crate) fn is_bytes(ty: &TypeOwned) -> bool {
+    match ty {
+        TypeOwned::Vec(inner) | TypeOwned::BoundedVec { ty: inner, .. } => matches!(
+            inner.as_ref(),
+            TypeOwned::NumericAny(NumericAnyTypeOwned::Base(NumericBaseType::U8))
+        ),
+        _ => false,
+    }
+}
+
+pub(crate) fn fields_list(fields: &FieldsOwned) -> Option<&[FieldOwned]> {
+    match fields {
+        FieldsOwned::Named(fields) | FieldsOwned::Unnamed(fields) => Some(fields),
+        FieldsOwned::Unit => None,
+    }
+}
+
+pub(crate) fn unique_name(used: &mut HashSet<String>, name: &str, idx: usize) -> String {
+    let name = if used.contains(name) {
+        format!("{name}{idx}")
+    } else {
+        name.to_string()
+    };
+    used.insert(name.clone());
+    name
+}
+
+pub(crate) fn numeric_base(numeric: &NumericAnyTypeOwned) -> &NumericBaseType {
+    match numeric {
+        NumericAnyTypeOwned::Base(base) => base,
+        NumericAnyTypeOwned::SubType { base, .. } => base,
+        NumericAnyTypeOwned::ShiftScale { base, .. } => base,
+    }
+}
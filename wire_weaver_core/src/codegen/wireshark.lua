@@ -0,0 +1,896 @@
+-- WireWeaver dissector runtime, appended after generated API tables (ww_types, ww_traits, ww_root, ww_api_name).
+-- Decoding rules mirror shrink_wrap::BufReader and ww_self dynamic decoder.
+
+local POW2 = {}
+do
+    local p = 1
+    for i = 0, 32 do
+        POW2[i] = p
+        p = p * 2
+    end
+end
+
+local MAX_DEPTH = 64
+local MAX_ITEMS = 65535
+local TEXT_LIMIT = 96
+
+local function short(s)
+    if #s > TEXT_LIMIT then
+        return s:sub(1, TEXT_LIMIT - 3) .. "..."
+    end
+    return s
+end
+
+-- Reader ------------------------------------------------------------------------------------------------------------
+
+local Reader = {}
+Reader.__index = Reader
+
+function Reader.new(tvb, offset, len)
+    return setmetatable({
+        tvb = tvb,
+        offset = offset,
+        len_bytes = len,
+        byte_idx = 0,
+        bit_idx = 7,
+        -- true when low nibble of the last byte was already read from the back
+        at_bit7_rev = false,
+    }, Reader)
+end
+
+function Reader:byte(idx)
+    return self.tvb(self.offset + idx, 1):uint()
+end
+
+function Reader:bytes_left()
+    if self.byte_idx >= self.len_bytes then
+        return 0
+    end
+    local left = self.len_bytes - self.byte_idx
+    if self.bit_idx ~= 7 then
+        left = left - 1
+    end
+    if left == 0 then
+        return 0
+    end
+    if self.at_bit7_rev then
+        return left - 1
+    end
+    return left
+end
+
+function Reader:bits_in_byte_left()
+    if self.byte_idx >= self.len_bytes then
+        return 0
+    end
+    if self.byte_idx + 1 == self.len_bytes and self.at_bit7_rev then
+        if self.bit_idx >= 3 then
+            return self.bit_idx - 3
+        end
+        return 0
+    end
+    return self.bit_idx + 1
+end
+
+function Reader:nibbles_in_byte_left()
+    if self.byte_idx >= self.len_bytes then
+        return 0
+    end
+    if self.byte_idx + 1 == self.len_bytes then
+        local n = 2
+        if self.bit_idx ~= 7 then
+            n = n - 1
+        end
+        if self.at_bit7_rev then
+            n = n - 1
+        end
+        return n
+    end
+    if self.bit_idx == 7 then
+        return 2
+    elseif self.bit_idx == 3 then
+        return 1
+    end
+    return 0
+end
+
+function Reader:align_nibble()
+    if self.bit_idx == 7 or self.bit_idx == 3 then
+        return
+    end
+    if self.bit_idx > 3 then
+        self.bit_idx = 3
+    else
+        self.bit_idx = 7
+        self.byte_idx = self.byte_idx + 1
+    end
+end
+
+function Reader:align_byte()
+    if self.bit_idx ~= 7 then
+        self.bit_idx = 7
+        self.byte_idx = self.byte_idx + 1
+    end
+end
+
+function Reader:read_bool()
+    if self:bits_in_byte_left() == 0 then
+        error("out of bounds reading bool", 0)
+    end
+    local val = math.floor(self:byte(self.byte_idx) / POW2[self.bit_idx]) % 2 == 1
+    if self.bit_idx == 0 then
+        self.bit_idx = 7
+        self.byte_idx = self.byte_idx + 1
+    else
+        self.bit_idx = self.bit_idx - 1
+    end
+    return val
+end
+
+function Reader:read_un(bit_count)
+    local result = 0
+    local left = bit_count
+    while left > 0 do
+        if self:bits_in_byte_left() == 0 then
+            error("out of bounds reading " .. bit_count .. " bits", 0)
+        end
+        local n = math.min(left, self.bit_idx + 1)
+        local shift = self.bit_idx + 1 - n
+        local bits = math.floor(self:byte(self.byte_idx) / POW2[shift]) % POW2[n]
+        result = result * POW2[n] + bits
+        self.bit_idx = self.bit_idx - n
+        if self.bit_idx < 0 then
+            self.bit_idx = 7
+            self.byte_idx = self.byte_idx + 1
+        end
+        left = left - n
+    end
+    return result
+end
+
+function Reader:read_nib()
+    self:align_nibble()
+    if self:nibbles_in_byte_left() == 0 then
+        error("out of bounds reading nibble", 0)
+    end
+    local b = self:byte(self.byte_idx)
+    if self.bit_idx == 7 then
+        self.bit_idx = 3
+        return math.floor(b / 16)
+    end
+    self.bit_idx = 7
+    self.byte_idx = self.byte_idx + 1
+    return b % 16
+end
+
+function Reader:read_unib32()
+    local num, mult = 0, 1
+    for _ = 0, 10 do
+        local nib = self:read_nib()
+        num = num + (nib % 8) * mult
+        if nib < 8 then
+            return num
+        end
+        mult = mult * 8
+    end
+    error("malformed UNib32", 0)
+end
+
+function Reader:read_u4_rev()
+    if self.byte_idx >= self.len_bytes then
+        error("out of bounds reading from the back", 0)
+    end
+    if self.at_bit7_rev then
+        self.at_bit7_rev = false
+        self.len_bytes = self.len_bytes - 1
+        return math.floor(self:byte(self.len_bytes) / 16)
+    end
+    self.at_bit7_rev = true
+    return self:byte(self.len_bytes - 1) % 16
+end
+
+function Reader:read_unib32_rev()
+    local num = 0
+    for _ = 0, 10 do
+        local nib = self:read_u4_rev()
+        num = num + nib % 8
+        if nib < 8 then
+            return num
+        end
+        num = num * 8
+    end
+    error("malformed reverse UNib32", 0)
+end
+
+-- Returns start index of len bytes, relative to this reader
+function Reader:read_raw(len)
+    self:align_byte()
+    if self:bytes_left() < len then
+        error("out of bounds reading " .. len .. " bytes", 0)
+    end
+    local start = self.byte_idx
+    self.byte_idx = self.byte_idx + len
+    return start
+end
+
+function Reader:range(start, len)
+    local abs = self.offset + start
+    if len == 0 then
+        abs = math.max(math.min(abs, self.tvb:len() - 1), 0)
+    end
+    return self.tvb(abs, len)
+end
+
+function Reader:read_range(len)
+    return self:range(self:read_raw(len), len)
+end
+
+function Reader:len_from(start)
+    local stop = self.byte_idx
+    if self.bit_idx ~= 7 then
+        stop = stop + 1
+    end
+    return math.max(stop - start, 0)
+end
+
+function Reader:split(len)
+    local start = self:read_raw(len)
+    return Reader.new(self.tvb, self.offset + start, len)
+end
+
+function Reader:rest()
+    return self:split(self:bytes_left())
+end
+
+-- Type decoder ------------------------------------------------------------------------------------------------------
+
+local function sign(value, bits)
+    if value >= POW2[bits - 1] then
+        return value - POW2[bits]
+    end
+    return value
+end
+
+local NUM = {
+    nibble = function(rd) return rd:read_nib() end,
+    unib32 = function(rd) return rd:read_unib32() end,
+    u8 = function(rd) return rd:read_range(1):uint() end,
+    u16 = function(rd) return rd:read_range(2):le_uint() end,
+    u32 = function(rd) return rd:read_range(4):le_uint() end,
+    u64 = function(rd) return rd:read_range(8):le_uint64() end,
+    u128 = function(rd) return "0x" .. rd:read_range(16):bytes():tohex() end,
+    i8 = function(rd) return rd:read_range(1):int() end,
+    i16 = function(rd) return rd:read_range(2):le_int() end,
+    i32 = function(rd) return rd:read_range(4):le_int() end,
+    i64 = function(rd) return rd:read_range(8):le_int64() end,
+    i128 = function(rd) return "0x" .. rd:read_range(16):bytes():tohex() end,
+    f32 = function(rd) return rd:read_range(4):le_float() end,
+    f64 = function(rd) return rd:read_range(8):le_float() end,
+    ub = function(rd, desc) return rd:read_un(desc.bits) end,
+    ib = function(rd, desc) return sign(rd:read_un(desc.bits), desc.bits) end,
+}
+
+local function read_num(rd, desc)
+    local reader = NUM[desc.b]
+    if reader == nil then
+        error("unsupported numeric type: " .. tostring(desc.b), 0)
+    end
+    return reader(rd, desc)
+end
+
+local function resolve(ty)
+    local depth = 0
+    while ty.k == "ref" do
+        local next_ty = ww_types[ty.idx]
+        if next_ty == nil then
+            error("no type with index " .. ty.idx, 0)
+        end
+        ty = next_ty
+        depth = depth + 1
+        if depth > MAX_DEPTH then
+            error("type references form a loop", 0)
+        end
+    end
+    return ty
+end
+
+local function is_unsized(ty)
+    ty = resolve(ty)
+    if ty.k == "string" or ty.k == "box" then
+        return true
+    end
+    if ty.k == "struct" or ty.k == "enum" then
+        return ty.unsized
+    end
+    return false
+end
+
+local function is_byte_slice(ty)
+    ty = resolve(ty)
+    return ty.k == "vec" and ty.ty.k == "num" and ty.ty.b == "u8"
+end
+
+local decode_inner
+
+-- Read a value as a field, unsized ones are prefixed with their size
+local function decode(rd, ty, tree, label, depth, flag)
+    if is_unsized(ty) then
+        local len = rd:read_unib32_rev()
+        local sub = rd:split(len)
+        return decode_inner(sub, ty, tree, label, depth, flag)
+    end
+    return decode_inner(rd, ty, tree, label, depth, flag)
+end
+
+local function decode_fields(rd, fields, tree, depth)
+    local values, texts, flags = {}, {}, {}
+    local named = false
+    for i, field in ipairs(fields) do
+        local name = field.name or tostring(i - 1)
+        if resolve(field.ty).k == "flag" then
+            flags[name] = rd:read_bool()
+        else
+            local value, text = decode(rd, field.ty, tree, name, depth + 1, flags[name])
+            values[name] = value
+            if field.name then
+                named = true
+                texts[#texts + 1] = name .. ": " .. text
+            else
+                texts[#texts + 1] = text
+            end
+        end
+    end
+    if #fields == 0 then
+        return values, ""
+    elseif named then
+        return values, " { " .. table.concat(texts, ", ") .. " }"
+    end
+    return values, "(" .. table.concat(texts, ", ") .. ")"
+end
+
+local function decode_items(rd, len, ty, tree, depth)
+    if len > MAX_ITEMS then
+        error("too many items: " .. len, 0)
+    end
+    local values, texts = {}, {}
+    for i = 1, len do
+        values[i], texts[i] = decode(rd, ty, tree, "[" .. (i - 1) .. "]", depth + 1)
+    end
+    return values, "[" .. table.concat(texts, ", ") .. "]"
+end
+
+local function read_repr(rd, ty)
+    if ty.repr == "nib" then
+        return rd:read_nib()
+    elseif ty.repr == "bits" then
+        return rd:read_un(ty.bits)
+    elseif ty.repr == "unib32" then
+        return rd:read_unib32()
+    end
+    return read_num(rd, { b = ty.repr })
+end
+
+local function decode_value(rd, ty, tree, depth, flag)
+    if depth > MAX_DEPTH then
+        error("maximum nesting depth exceeded", 0)
+    end
+    ty = resolve(ty)
+    local k = ty.k
+    if k == "bool" then
+        local value = rd:read_bool()
+        return value, tostring(value)
+    elseif k == "num" then
+        local value = read_num(rd, ty)
+        return value, tostring(value)
+    elseif k == "string" then
+        local value = rd:read_range(rd:bytes_left()):string(ENC_UTF_8)
+        return value, string.format("%q", value)
+    elseif k == "vec" then
+        local len = rd:read_unib32_rev()
+        if is_byte_slice(ty) then
+            local start = rd:read_raw(len)
+            return { bytes = true, tvb = rd.tvb, offset = rd.offset + start, len = len }, len .. " bytes"
+        end
+        return decode_items(rd, len, ty.ty, tree, depth)
+    elseif k == "array" then
+        return decode_items(rd, ty.len, ty.ty, tree, depth)
+    elseif k == "tuple" then
+        local values, texts = {}, {}
+        for i, item_ty in ipairs(ty.tys) do
+            values[i], texts[i] = decode(rd, item_ty, tree, tostring(i - 1), depth + 1)
+        end
+        return values, "(" .. table.concat(texts, ", ") .. ")"
+    elseif k == "struct" then
+        local values, text = decode_fields(rd, ty.fields, tree, depth)
+        return values, ty.name .. text
+    elseif k == "enum" then
+        local discriminant = read_repr(rd, ty)
+        local variant = ty.variants[discriminant]
+        if variant == nil then
+            error("enum " .. ty.name .. " does not have variant " .. discriminant, 0)
+        end
+        local values, text = decode_fields(rd, variant.fields, tree, depth)
+        return { variant = variant.name, fields = values }, variant.name .. text
+    elseif k == "option" then
+        local is_some = flag
+        if is_some == nil then
+            is_some = rd:read_bool()
+        end
+        if is_some then
+            local value, text = decode(rd, ty.ty, tree, "Some", depth + 1)
+            return { some = value }, "Some(" .. text .. ")"
+        end
+        return nil, "None"
+    elseif k == "result" then
+        local is_ok = flag
+        if is_ok == nil then
+            is_ok = rd:read_bool()
+        end
+        if is_ok then
+            local value, text = decode(rd, ty.ok, tree, "Ok", depth + 1)
+            return { ok = value }, "Ok(" .. text .. ")"
+        end
+        local value, text = decode(rd, ty.err, tree, "Err", depth + 1)
+        return { err = value }, "Err(" .. text .. ")"
+    elseif k == "box" then
+        return decode_value(rd, ty.ty, tree, depth + 1)
+    elseif k == "range" or k == "range_incl" then
+        local start = read_num(rd, ty.base)
+        local stop = read_num(rd, ty.base)
+        return { start, stop }, tostring(start) .. (k == "range" and ".." or "..=") .. tostring(stop)
+    elseif k == "flag" then
+        error("flag cannot be read on its own", 0)
+    end
+    error("unsupported type: " .. tostring(k), 0)
+end
+
+-- Read a value without size prefix, adding a tree item for it
+decode_inner = function(rd, ty, tree, label, depth, flag)
+    local start = rd.byte_idx
+    local item = tree:add(rd:range(start, 0), label)
+    local value, text = decode_value(rd, ty, item, depth or 0, flag)
+    text = short(text)
+    item:set_len(rd:len_from(start))
+    item:append_text(": " .. text)
+    return value, text
+end
+
+-- Decode whole tvb range as a value of the provided type
+local function decode_bytes(bytes, ty, tree, label)
+    local rd = Reader.new(bytes.tvb, bytes.offset, bytes.len)
+    return decode_inner(rd, ty, tree, label, 0)
+end
+
+local function decode_args(bytes, args, tree)
+    local rd = Reader.new(bytes.tvb, bytes.offset, bytes.len)
+    local item = tree:add(rd:range(0, bytes.len), "Arguments")
+    local _, text = decode_fields(rd, args, item, 0)
+    item:append_text(": " .. short(text))
+    return text
+end
+
+-- Envelopes ---------------------------------------------------------------------------------------------------------
+
+local UNIB32 = { k = "num", b = "unib32" }
+local U32 = { k = "num", b = "u32" }
+local BYTES = { k = "vec", ty = { k = "num", b = "u8" } }
+local PATH = { k = "vec", ty = UNIB32 }
+local STR = { k = "string" }
+
+local VERSION = { k = "struct", name = "Version", unsized = false, fields = {
+    { name = "major", ty = UNIB32 },
+    { name = "minor", ty = UNIB32 },
+    { name = "patch", ty = UNIB32 },
+    { name = "build", ty = { k = "flag" } },
+    { name = "pre", ty = { k = "option", ty = STR } },
+    { name = "build", ty = { k = "option", ty = STR } },
+} }
+local FULL_VERSION = { k = "struct", name = "FullVersion", unsized = false, fields = {
+    { name = "crate_id", ty = STR },
+    { name = "version", ty = VERSION },
+} }
+local COMPACT_VERSION = { k = "struct", name = "CompactVersion", unsized = false, fields = {
+    { name = "gid", ty = { k = "struct", name = "GlobalTypeId", unsized = false, fields = { { name = "id", ty = UNIB32 } } } },
+    { name = "major", ty = UNIB32 },
+    { name = "minor", ty = UNIB32 },
+    { name = "patch", ty = UNIB32 },
+} }
+local SHAPER_CONFIG = { k = "enum", name = "ShaperConfig", unsized = true, repr = "nib", variants = {
+    [0] = { name = "NoLimit", fields = {} },
+    [1] = { name = "MaxBitrate", fields = { { name = "bytes_per_s", ty = U32 } } },
+    [2] = { name = "MaxRate", fields = { { name = "events_per_s", ty = U32 } } },
+} }
+local MULTI_INDEX = { k = "enum", name = "MultiIndex", unsized = true, repr = "bits", bits = 2, variants = {
+    [0] = { name = "All", fields = {} },
+    [1] = { name = "Range", fields = { { ty = { k = "range", base = U32 } } } },
+    [2] = { name = "List", fields = { { ty = { k = "vec", ty = U32 } } } },
+    [3] = { name = "Mask32", fields = { { ty = U32 } } },
+} }
+local MULTI_ARGS = { k = "enum", name = "MultiArgs", unsized = true, repr = "bits", bits = 1, variants = {
+    [0] = { name = "Same", fields = { { ty = BYTES } } },
+    [1] = { name = "Different", fields = { { ty = BYTES } } },
+} }
+local SIDEBAND_COMMAND = { k = "enum", name = "StreamSidebandCommand", unsized = false, repr = "nib", variants = {
+    [0] = { name = "Open", fields = {} },
+    [1] = { name = "Close", fields = {} },
+    [2] = { name = "FrameSync", fields = {} },
+    [3] = { name = "ChangeRate", fields = { { ty = SHAPER_CONFIG } } },
+    [4] = { name = "SizeHint", fields = { { ty = U32 } } },
+    [5] = { name = "User", fields = { { ty = U32 } } },
+} }
+local SIDEBAND_EVENT = { k = "enum", name = "StreamSidebandEvent", unsized = false, repr = "nib", variants = {
+    [0] = { name = "Opened", fields = {} },
+    [1] = { name = "Closed", fields = {} },
+    [2] = { name = "FrameSync", fields = {} },
+    [3] = { name = "SizeHint", fields = { { ty = U32 } } },
+    [4] = { name = "User", fields = { { ty = U32 } } },
+} }
+local REQUEST = { k = "struct", name = "Request", unsized = true, fields = {
+    { name = "seq", ty = { k = "num", b = "u16" } },
+    { name = "path_kind", ty = { k = "enum", name = "PathKind", unsized = false, repr = "nib", variants = {
+        [0] = { name = "Absolute", fields = { { name = "path", ty = PATH } } },
+        [1] = { name = "GlobalCompact", fields = { { name = "gid", ty = COMPACT_VERSION }, { name = "path_from_trait", ty = PATH } } },
+        [2] = { name = "GlobalFull", fields = { { name = "gid", ty = FULL_VERSION }, { name = "path_from_trait", ty = PATH } } },
+    } } },
+    { name = "kind", ty = { k = "enum", name = "RequestKind", unsized = false, repr = "nib", variants = {
+        [0] = { name = "Call", fields = { { name = "args", ty = BYTES } } },
+        [1] = { name = "MultiCall", fields = { { name = "multi_idx", ty = MULTI_INDEX }, { name = "resource_id", ty = { k = "option", ty = UNIB32 } }, { name = "multi_args", ty = MULTI_ARGS } } },
+        [2] = { name = "Read", fields = {} },
+        [3] = { name = "MultiRead", fields = { { name = "multi_idx", ty = MULTI_INDEX }, { name = "resource_id", ty = { k = "option", ty = UNIB32 } } } },
+        [4] = { name = "Write", fields = { { name = "data", ty = BYTES } } },
+        [5] = { name = "MultiWrite", fields = { { name = "multi_idx", ty = MULTI_INDEX }, { name = "resource_id", ty = { k = "option", ty = UNIB32 } }, { name = "multi_data", ty = MULTI_ARGS } } },
+        [6] = { name = "Subscribe", fields = {} },
+        [7] = { name = "Unsubscribe", fields = {} },
+        [8] = { name = "ChangeRate", fields = { { name = "shaper_config", ty = SHAPER_CONFIG } } },
+        [9] = { name = "StreamSideband", fields = { { name = "sideband_cmd", ty = SIDEBAND_COMMAND } } },
+        [10] = { name = "Introspect", fields = {} },
+        [11] = { name = "Cancel", fields = { { name = "seq", ty = { k = "num", b = "u16" } } } },
+    } } },
+} }
+local ERROR_KINDS = { "OperationNotSupported", "BadPath", "BadIndex", "ExpectedArrayIndexGotNone", "ArrayIndexDesFailed",
+    "ArgsDesFailed", "PathDesFailed", "PropertyDesFailed", "ResponseSerFailed", "OperationNotImplemented",
+    "ReadPropertyWithSeqZero", "PathKindNotSupported" }
+local ERROR_KIND = { k = "enum", name = "ErrorKind", unsized = true, repr = "unib32", variants = {
+    [12] = { name = "UserBytes", fields = { { ty = BYTES } } },
+    [13] = { name = "UserStr", fields = { { ty = STR } } },
+} }
+for i, name in ipairs(ERROR_KINDS) do
+    ERROR_KIND.variants[i - 1] = { name = name, fields = {} }
+end
+local EVENT = { k = "struct", name = "Event", unsized = true, fields = {
+    { name = "seq", ty = { k = "num", b = "u16" } },
+    { name = "result", ty = { k = "result",
+        ok = { k = "enum", name = "EventKind", unsized = false, repr = "nib", variants = {
+            [0] = { name = "ReturnValue", fields = { { name = "data", ty = BYTES } } },
+            [1] = { name = "ReadValue", fields = { { name = "data", ty = BYTES } } },
+            [2] = { name = "Written", fields = {} },
+            [3] = { name = "StreamData", fields = { { name = "path", ty = PATH }, { name = "data", ty = BYTES } } },
+            [4] = { name = "StreamSideband", fields = { { name = "path", ty = PATH }, { name = "sideband_event", ty = SIDEBAND_EVENT } } },
+            [5] = { name = "Subscribed", fields = { { name = "path", ty = PATH } } },
+            [6] = { name = "Unsubscribed", fields = { { name = "path", ty = PATH } } },
+            [7] = { name = "RateChanged", fields = {} },
+        } },
+        err = { k = "struct", name = "Error", unsized = true, fields = {
+            { name = "err_seq", ty = U32 },
+            { name = "kind", ty = ERROR_KIND },
+        } },
+    } },
+} }
+local DEVICE_INFO = { k = "struct", name = "DeviceInfo", unsized = true, fields = {
+    { name = "dev_link_version", ty = COMPACT_VERSION },
+    { name = "api_model_version", ty = COMPACT_VERSION },
+    { name = "user_api_version", ty = FULL_VERSION },
+    { name = "user_api_signature", ty = BYTES },
+    { name = "dev_max_message_len", ty = U32 },
+    { name = "packet_accumulation_time_us", ty = { k = "num", b = "u16" } },
+} }
+local LINK_SETUP = { k = "struct", name = "LinkSetup", unsized = true, fields = {
+    { name = "host_user_version", ty = FULL_VERSION },
+    { name = "host_max_message_len", ty = U32 },
+} }
+local DISCONNECT_REASON = { k = "enum", name = "DisconnectReason", unsized = true, repr = "u8", variants = {
+    [0] = { name = "ApplicationCrash", fields = {} },
+    [1] = { name = "RequestByUser", fields = {} },
+    [2] = { name = "IncompatibleVersion", fields = {} },
+    [3] = { name = "Other", fields = { { ty = { k = "num", b = "u8" } } } },
+    [4] = { name = "Unknown", fields = {} },
+} }
+
+-- Resource paths ----------------------------------------------------------------------------------------------------
+
+-- Returns API item at the provided path (if found) and its human-readable name
+local function resolve_path(level, ids)
+    local names = {}
+    local item
+    local i = 1
+    while i <= #ids do
+        if level == nil or level.items == nil then
+            names[#names + 1] = "?"
+            return nil, table.concat(names, "/")
+        end
+        item = level.items[ids[i]]
+        if item == nil then
+            names[#names + 1] = "#" .. ids[i]
+            return nil, table.concat(names, "/")
+        end
+        local name = item.ident
+        if item.array then
+            i = i + 1
+            name = name .. "[" .. (ids[i] ~= nil and tostring(ids[i]) or "") .. "]"
+        end
+        names[#names + 1] = name
+        i = i + 1
+        if item.kind == "trait" then
+            level = ww_traits[item.trait]
+        else
+            level = nil
+        end
+    end
+    return item, table.concat(names, "/")
+end
+
+local function find_trait(crate_id)
+    if ww_root.crate == crate_id then
+        return ww_root
+    end
+    for _, level in pairs(ww_traits) do
+        if level.crate == crate_id then
+            return level
+        end
+    end
+    return nil
+end
+
+local function resolve_path_kind(path_kind)
+    local f = path_kind.fields
+    if path_kind.variant == "Absolute" then
+        return resolve_path(ww_root, f.path)
+    elseif path_kind.variant == "GlobalFull" then
+        local crate_id = f.gid.crate_id
+        local item, name = resolve_path(find_trait(crate_id), f.path_from_trait)
+        return item, crate_id .. "::" .. name
+    end
+    local _, name = resolve_path(nil, f.path_from_trait)
+    return nil, "gid " .. f.gid.gid.id .. "::" .. name
+end
+
+-- Protocols ---------------------------------------------------------------------------------------------------------
+
+local ww_link = Proto("ww_link", "WireWeaver USB link")
+local ww_link_h2d = Proto("ww_link_h2d", "WireWeaver USB link, host to device")
+local ww_link_d2h = Proto("ww_link_d2h", "WireWeaver USB link, device to host")
+local ww = Proto("wire_weaver", "WireWeaver " .. ww_api_name)
+local ww_req = Proto("wire_weaver_req", "WireWeaver requests")
+local ww_evt = Proto("wire_weaver_evt", "WireWeaver events")
+
+local LINK_OPS = {
+    [0] = "Nop", [1] = "GetDeviceInfo", [2] = "DeviceInfo", [3] = "LinkSetup", [4] = "LinkReady",
+    [5] = "MessageStart", [6] = "MessageContinue", [7] = "MessageEnd", [8] = "MessageStartEnd",
+    [9] = "Ping", [10] = "GetStats", [11] = "Stats", [12] = "Loopback", [15] = "Disconnect",
+}
+
+local f_link_op = ProtoField.uint8("ww_link.op", "Op", base.DEC, LINK_OPS, 0xF0)
+local f_link_len = ProtoField.uint16("ww_link.len", "Length", base.DEC, nil, 0x0FFF)
+local f_link_data = ProtoField.bytes("ww_link.data", "Data")
+local f_link_crc = ProtoField.uint16("ww_link.crc", "CRC", base.HEX)
+local f_link_repeat = ProtoField.uint32("ww_link.loopback.repeat", "Repeat", base.DEC)
+local f_link_loopback_seq = ProtoField.uint32("ww_link.loopback.seq", "Seq", base.DEC)
+ww_link.fields = { f_link_op, f_link_len, f_link_data, f_link_crc, f_link_repeat, f_link_loopback_seq }
+
+local f_seq = ProtoField.uint16("wire_weaver.seq", "Seq", base.DEC)
+local f_kind = ProtoField.string("wire_weaver.kind", "Kind")
+local f_path = ProtoField.string("wire_weaver.path", "Path")
+local f_resource = ProtoField.string("wire_weaver.resource", "Resource")
+local f_data = ProtoField.bytes("wire_weaver.data", "Data")
+local f_request_frame = ProtoField.framenum("wire_weaver.request_frame", "Request in frame", base.NONE, frametype.REQUEST)
+ww.fields = { f_seq, f_kind, f_path, f_resource, f_data, f_request_frame }
+
+local e_malformed = ProtoExpert.new("wire_weaver.malformed", "Malformed message", expert.group.MALFORMED, expert.severity.ERROR)
+local e_error = ProtoExpert.new("wire_weaver.error", "Error response", expert.group.RESPONSE_CODE, expert.severity.WARN)
+ww.experts = { e_malformed, e_error }
+
+-- seq -> request info, filled on the first pass, so that responses can be decoded with the right types
+local pending_requests = {}
+-- "frame:message" -> request info, found on the first pass
+local matched_requests = {}
+
+function ww.init()
+    pending_requests = {}
+    matched_requests = {}
+end
+
+local function add_bytes(tree, field, bytes)
+    local rd = Reader.new(bytes.tvb, bytes.offset, bytes.len)
+    return tree:add(field, rd:range(0, bytes.len))
+end
+
+local function dissect_value(tree, bytes, ty, label)
+    if ty == nil or is_byte_slice(ty) then
+        add_bytes(tree, f_data, bytes)
+        return bytes.len .. " bytes"
+    end
+    local _, text = decode_bytes(bytes, ty, tree, label)
+    return text
+end
+
+local function dissect_request(tvb, pinfo, tree, msg_key)
+    local raw = tree:add(tvb(), "Envelope")
+    local req = decode_bytes({ tvb = tvb, offset = 0, len = tvb:len() }, REQUEST, raw, "Request")
+    local kind = req.kind.variant
+    local item, path = resolve_path_kind(req.path_kind)
+    tree:add(f_seq, tvb(0, 2), req.seq)
+    tree:add(f_kind, kind)
+    tree:add(f_path, path)
+    if item ~= nil then
+        tree:add(f_resource, item.kind)
+    end
+
+    local summary = kind .. " " .. path
+    local f = req.kind.fields
+    if kind == "Call" and item ~= nil and item.kind == "method" then
+        summary = summary .. decode_args(f.args, item.args, tree)
+    elseif kind == "Write" and item ~= nil and item.ty ~= nil then
+        summary = summary .. " = " .. dissect_value(tree, f.data, item.ty, "Value")
+    elseif f.args ~= nil then
+        add_bytes(tree, f_data, f.args)
+    elseif f.data ~= nil then
+        add_bytes(tree, f_data, f.data)
+    end
+    if not pinfo.visited and req.seq ~= 0 then
+        pending_requests[req.seq] = { item = item, path = path, frame = pinfo.number }
+    end
+    tree:append_text(", " .. short(summary))
+    return summary
+end
+
+local function dissect_event(tvb, pinfo, tree, msg_key)
+    local raw = tree:add(tvb(), "Envelope")
+    local ev = decode_bytes({ tvb = tvb, offset = 0, len = tvb:len() }, EVENT, raw, "Event")
+    tree:add(f_seq, tvb(0, 2), ev.seq)
+    if not pinfo.visited and ev.seq ~= 0 then
+        matched_requests[msg_key] = pending_requests[ev.seq]
+    end
+    local request = matched_requests[msg_key]
+    if request ~= nil then
+        tree:add(f_request_frame, request.frame)
+        tree:add(f_path, request.path)
+    end
+
+    if ev.result.err ~= nil then
+        local kind = ev.result.err.kind.variant
+        tree:add(f_kind, "Error")
+        tree:add_proto_expert_info(e_error, kind)
+        tree:append_text(", Error " .. kind)
+        return "Error " .. kind
+    end
+    local event = ev.result.ok
+    local kind = event.variant
+    local f = event.fields
+    tree:add(f_kind, kind)
+    local summary = kind
+    local item = request and request.item
+    if kind == "ReturnValue" then
+        summary = summary .. " " .. dissect_value(tree, f.data, item and item.ret, "Return value")
+    elseif kind == "ReadValue" then
+        summary = summary .. " " .. dissect_value(tree, f.data, item and item.ty, "Value")
+    elseif kind == "StreamData" or kind == "StreamSideband" or kind == "Subscribed" or kind == "Unsubscribed" then
+        local stream, path = resolve_path(ww_root, f.path)
+        tree:add(f_path, path)
+        summary = summary .. " " .. path
+        if kind == "StreamData" then
+            summary = summary .. " " .. dissect_value(tree, f.data, stream and stream.ty, "Value")
+        end
+    end
+    tree:append_text(", " .. short(summary))
+    return summary
+end
+
+local function dissect_message(tvb, pinfo, tree, is_request, msg_key)
+    local subtree = tree:add(ww, tvb(), is_request and "WireWeaver Request" or "WireWeaver Event")
+    pinfo.cols.protocol = "WireWeaver"
+    local ok, result = pcall(is_request and dissect_request or dissect_event, tvb, pinfo, subtree, msg_key)
+    if ok then
+        pinfo.cols.info:append(short(result) .. "; ")
+    else
+        subtree:add_proto_expert_info(e_malformed, tostring(result))
+        pinfo.cols.info:append("[Malformed] ")
+    end
+end
+
+local function dissect_link(tvb, pinfo, tree, to_device)
+    pinfo.cols.protocol = "WW link"
+    pinfo.cols.info:set(to_device and "host -> device: " or "device -> host: ")
+    local subtree = tree:add(ww_link, tvb())
+    local offset = 0
+    local msg_idx = 0
+    while tvb:len() - offset >= 2 do
+        local b0 = tvb(offset, 1):uint()
+        local op = math.floor(b0 / 16)
+        local len = (b0 % 16) * 256 + tvb(offset + 1, 1):uint()
+        local op_name = LINK_OPS[op] or ("Unknown(" .. op .. ")")
+        local rest = tvb:len() - offset - 2
+        local op_len = 2
+        if op >= 5 and op <= 8 then
+            op_len = op_len + len
+            if op == 7 then
+                op_len = op_len + 2
+            end
+        elseif op ~= 0 then
+            op_len = op_len + rest
+        end
+        if offset + op_len > tvb:len() then
+            subtree:add_proto_expert_info(e_malformed, op_name .. " is longer than the packet")
+            break
+        end
+        if op ~= 0 then
+            local op_tree = subtree:add(tvb(offset, op_len), op_name)
+            op_tree:add(f_link_op, tvb(offset, 1))
+            local data_offset = offset + 2
+            if op >= 5 and op <= 8 then
+                op_tree:add(f_link_len, tvb(offset, 2))
+                local data = tvb(data_offset, len)
+                if op == 8 then
+                    dissect_message(data:tvb(), pinfo, tree, to_device, pinfo.number .. ":" .. msg_idx)
+                    msg_idx = msg_idx + 1
+                else
+                    op_tree:add(f_link_data, data)
+                    op_tree:append_text(" (fragment, not reassembled)")
+                    if op == 7 then
+                        op_tree:add_le(f_link_crc, tvb(data_offset + len, 2))
+                    end
+                    pinfo.cols.info:append(op_name .. "; ")
+                end
+            elseif rest > 0 then
+                local data = { tvb = tvb, offset = data_offset, len = rest }
+                local ok, err = pcall(function()
+                    if op == 2 then
+                        decode_bytes(data, DEVICE_INFO, op_tree, "DeviceInfo")
+                    elseif op == 3 then
+                        decode_bytes(data, LINK_SETUP, op_tree, "LinkSetup")
+                    elseif op == 15 then
+                        decode_bytes(data, DISCONNECT_REASON, op_tree, "Reason")
+                    elseif op == 12 and rest >= 8 then
+                        op_tree:add_le(f_link_repeat, tvb(data_offset, 4))
+                        op_tree:add_le(f_link_loopback_seq, tvb(data_offset + 4, 4))
+                        if rest > 8 then
+                            op_tree:add(f_link_data, tvb(data_offset + 8, rest - 8))
+                        end
+                    else
+                        op_tree:add(f_link_data, tvb(data_offset, rest))
+                    end
+                end)
+                if not ok then
+                    op_tree:add_proto_expert_info(e_malformed, tostring(err))
+                end
+                pinfo.cols.info:append(op_name .. "; ")
+            else
+                pinfo.cols.info:append(op_name .. "; ")
+            end
+        end
+        offset = offset + op_len
+    end
+end
+
+function ww_link_h2d.dissector(tvb, pinfo, tree)
+    dissect_link(tvb, pinfo, tree, true)
+end
+
+function ww_link_d2h.dissector(tvb, pinfo, tree)
+    dissect_link(tvb, pinfo, tree, false)
+end
+
+function ww_req.dissector(tvb, pinfo, tree)
+    pinfo.cols.info:clear()
+    dissect_message(tvb, pinfo, tree, true, pinfo.number .. ":0")
+end
+
+function ww_evt.dissector(tvb, pinfo, tree)
+    pinfo.cols.info:clear()
+    dissect_message(tvb, pinfo, tree, false, pinfo.number .. ":0")
+end
+
+local wtap_encap = DissectorTable.get("wtap_encap")
+wtap_encap:add(wtap.USER0, ww_link_h2d)
+wtap_encap:add(wtap.USER1, ww_link_d2h)
+wtap_encap:add(wtap.USER2, ww_req)
+wtap_encap:add(wtap.USER3, ww_evt)
@@ -0,0 +1,329 @@
+//! Wireshark Lua dissector generator.
+//!
+//! API tree and all the types from an ApiBundle are emitted as Lua tables, followed by a fixed runtime (wireshark.lua),
+//! that decodes USB link ops, Request/Event envelopes and then arguments, return values, properties and stream data
+//! using these tables. Resource paths are shown as names instead of numeric IDs.
+//!
+//! Dissector is registered for LINKTYPE_USER0..3, see `wire_weaver_client_common::pcapng`.
+
+use anyhow::Result;
+use std::fmt::Write;
+use ww_numeric::{NumericAnyTypeOwned, NumericBaseType};
+use ww_self::{
+    ApiBundleOwned, ApiItemKindOwned, ApiLevelLocationOwned, ApiLevelOwned, FieldsOwned,
+    PropertyAccess, Repr, TypeLocationOwned, TypeOwned,
+};
+
+const RUNTIME: &str = include_str!("wireshark.lua");
+
+/// Generates a Wireshark Lua dissector for the given API bundle.
+/// ApiBundleOwned can be loaded using [crate::load] or [crate::load_dep].
+///
+/// Resulting file can be put into Wireshark personal plugins folder or loaded with `wireshark -X lua_script:<file>`.
+pub fn gen_wireshark_dissector(api_bundle: &ApiBundleOwned) -> Result<String> {
+    let root_crate = api_bundle.crate_version(api_bundle.root.crate_idx.0)?;
+    let v = &root_crate.version;
+    let api_name = format!(
+        "{}::{} v{}.{}.{}",
+        root_crate.crate_id, api_bundle.root.trait_name, v.major.0, v.minor.0, v.patch.0
+    );
+
+    let mut lua = String::new();
+    writeln!(
+        lua,
+        "-- Wireshark dissector for {api_name}, generated by wire_weaver_core {}, do not edit.",
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(lua)?;
+    writeln!(lua, "local ww_api_name = {}", lua_str(&api_name))?;
+
+    writeln!(lua, "local ww_types = {{}}")?;
+    for (idx, location) in api_bundle.types.iter().enumerate() {
+        let ty = match location {
+            TypeLocationOwned::InLine { ty, .. } => lua_ty(ty)?,
+            TypeLocationOwned::SkippedFullVersion { type_name, .. } => {
+                format!("{{ k = \"unsupported\", name = {} }}", lua_str(type_name))
+            }
+        };
+        writeln!(lua, "ww_types[{idx}] = {ty}")?;
+    }
+
+    writeln!(lua, "local ww_traits = {{}}")?;
+    for (idx, location) in api_bundle.traits.iter().enumerate() {
+        let level = match location {
+            ApiLevelLocationOwned::InLine { level, .. } => lua_level(level, api_bundle)?,
+            ApiLevelLocationOwned::SkippedFullVersion { trait_name, .. } => {
+                format!("{{ name = {} }}", lua_str(trait_name))
+            }
+            ApiLevelLocationOwned::SkippedCompactVersion { trait_id, .. } => {
+                format!("{{ name = \"trait #{}\" }}", trait_id.0)
+            }
+        };
+        writeln!(lua, "ww_traits[{idx}] = {level}")?;
+    }
+    writeln!(
+        lua,
+        "local ww_root = {}",
+        lua_level(&api_bundle.root, api_bundle)?
+    )?;
+    writeln!(lua)?;
+    lua.push_str(RUNTIME);
+    Ok(lua)
+}
+
+fn lua_level(level: &ApiLevelOwned, api_bundle: &ApiBundleOwned) -> Result<String> {
+    let mut items = String::new();
+    for item in &level.items {
+        let kind = match &item.kind {
+            ApiItemKindOwned::Method { args, return_ty } => {
+                let mut args_lua = String::new();
+                for arg in args {
+                    write!(
+                        args_lua,
+                        "{{ name = {}, ty = {} }}, ",
+                        lua_str(&arg.ident),
+                        lua_ty(&arg.ty)?
+                    )?;
+                }
+                let ret = match return_ty {
+                    Some(ty) => format!(", ret = {}", lua_ty(ty)?),
+                    None => String::new(),
+                };
+                format!("kind = \"method\", args = {{ {args_lua}}}{ret}")
+            }
+            ApiItemKindOwned::Property { ty, access, .. } => {
+                let access = match access {
+                    PropertyAccess::Const => "const",
+                    PropertyAccess::ReadOnly { .. } => "ro",
+                    PropertyAccess::ReadWrite { .. } => "rw",
+                    PropertyAccess::WriteOnly => "wo",
+                };
+                format!(
+                    "kind = \"property\", access = \"{access}\", ty = {}",
+                    lua_ty(ty)?
+                )
+            }
+            ApiItemKindOwned::Stream { ty, is_up } => {
+                format!("kind = \"stream\", up = {is_up}, ty = {}", lua_ty(ty)?)
+            }
+            ApiItemKindOwned::Trait { trait_idx } => {
+                format!("kind = \"trait\", trait = {}", trait_idx.0)
+            }
+        };
+        writeln!(
+            items,
+            "    [{}] = {{ ident = {}, array = {}, {kind} }},",
+            item.id.0,
+            lua_str(&item.ident),
+            item.is_array()
+        )?;
+    }
+    Ok(format!(
+        "{{ name = {}, crate = {}, items = {{\n{items}}} }}",
+        lua_str(&level.trait_name),
+        lua_str(level.crate_name(api_bundle)?)
+    ))
+}
+
+fn lua_ty(ty: &TypeOwned) -> Result<String> {
+    let lua = match ty {
+        TypeOwned::Bool => "{ k = \"bool\" }".into(),
+        TypeOwned::NumericAny(numeric) => {
+            let base = match numeric {
+                NumericAnyTypeOwned::Base(base) => base,
+                NumericAnyTypeOwned::SubType { base, .. } => base,
+                NumericAnyTypeOwned::ShiftScale { base, .. } => base,
+            };
+            lua_num(base)
+        }
+        TypeOwned::OutOfLine { type_idx } => format!("{{ k = \"ref\", idx = {} }}", type_idx.0),
+        TypeOwned::Flag => "{ k = \"flag\" }".into(),
+        TypeOwned::String | TypeOwned::BoundedString { .. } => "{ k = \"string\" }".into(),
+        TypeOwned::Vec(ty) | TypeOwned::BoundedVec { ty, .. } => {
+            format!("{{ k = \"vec\", ty = {} }}", lua_ty(ty)?)
+        }
+        TypeOwned::Array { len, ty } => {
+            format!("{{ k = \"array\", len = {}, ty = {} }}", len.0, lua_ty(ty)?)
+        }
+        TypeOwned::Tuple(types) => {
+            let mut tys = String::new();
+            for ty in types {
+                write!(tys, "{}, ", lua_ty(ty)?)?;
+            }
+            format!("{{ k = \"tuple\", tys = {{ {tys}}} }}")
+        }
+        TypeOwned::Struct(item_struct) => format!(
+            "{{ k = \"struct\", name = {}, unsized = {}, fields = {} }}",
+            lua_str(&item_struct.ident),
+            item_struct.is_unsized(),
+            lua_fields(&item_struct.fields)?
+        ),
+        TypeOwned::Enum(item_enum) => {
+            let repr = match item_enum.repr {
+                Repr::Nibble => "repr = \"nib\"".into(),
+                Repr::BitAligned(bits) => format!("repr = \"bits\", bits = {bits}"),
+                Repr::UNib32 => "repr = \"unib32\"".into(),
+                Repr::ByteAlignedU8 => "repr = \"u8\"".into(),
+                Repr::ByteAlignedU16 => "repr = \"u16\"".into(),
+                Repr::ByteAlignedU32 => "repr = \"u32\"".into(),
+            };
+            let mut variants = String::new();
+            for variant in &item_enum.variants {
+                write!(
+                    variants,
+                    "[{}] = {{ name = {}, fields = {} }}, ",
+                    variant.discriminant.0,
+                    lua_str(&variant.ident),
+                    lua_fields(&variant.fields)?
+                )?;
+            }
+            format!(
+                "{{ k = \"enum\", name = {}, unsized = {}, {repr}, variants = {{ {variants}}} }}",
+                lua_str(&item_enum.ident),
+                item_enum.is_unsized()
+            )
+        }
+        TypeOwned::Option { some_ty } => format!("{{ k = \"option\", ty = {} }}", lua_ty(some_ty)?),
+        TypeOwned::Result { ok_ty, err_ty } => format!(
+            "{{ k = \"result\", ok = {}, err = {} }}",
+            lua_ty(ok_ty)?,
+            lua_ty(err_ty)?
+        ),
+        TypeOwned::Box(ty) => format!("{{ k = \"box\", ty = {} }}", lua_ty(ty)?),
+        TypeOwned::Range(base) => format!("{{ k = \"range\", base = {} }}", lua_num(base)),
+        TypeOwned::RangeInclusive(base) => {
+            format!("{{ k = \"range_incl\", base = {} }}", lua_num(base))
+        }
+    };
+    Ok(lua)
+}
+
+fn lua_fields(fields: &FieldsOwned) -> Result<String> {
+    let fields = match fields {
+        FieldsOwned::Named(fields) | FieldsOwned::Unnamed(fields) => fields,
+        FieldsOwned::Unit => return Ok("{}".into()),
+    };
+    let mut lua = String::new();
+    for field in fields {
+        let name = match &field.ident {
+            Some(ident) => format!("name = {}, ", lua_str(ident)),
+            None => String::new(),
+        };
+        write!(lua, "{{ {name}ty = {} }}, ", lua_ty(&field.ty)?)?;
+    }
+    Ok(format!("{{ {lua}}}"))
+}
+
+fn lua_num(base: &NumericBaseType) -> String {
+    let b = match base {
+        NumericBaseType::Nibble => "nibble",
+        NumericBaseType::U8 => "u8",
+        NumericBaseType::U16 => "u16",
+        NumericBaseType::U32 => "u32",
+        NumericBaseType::UNib32 => "unib32",
+        NumericBaseType::U64 => "u64",
+        NumericBaseType::I32 => "i32",
+        NumericBaseType::F32 => "f32",
+        NumericBaseType::U128 => "u128",
+        NumericBaseType::I8 => "i8",
+        NumericBaseType::I16 => "i16",
+        NumericBaseType::I64 => "i64",
+        NumericBaseType::I128 => "i128",
+        NumericBaseType::F64 => "f64",
+        NumericBaseType::UB(bits) => {
+            return format!("{{ k = \"num\", b = \"ub\", bits = {} }}", bits.0);
+        }
+        NumericBaseType::IB(bits) => {
+            return format!("{{ k = \"num\", b = \"ib\", bits = {} }}", bits.0);
+        }
+        other => return format!("{{ k = \"num\", b = {} }}", lua_str(&format!("{other:?}"))),
+    };
+    format!("{{ k = \"num\", b = \"{b}\" }}")
+}
+
+/// Double-quoted Lua string literal, non-printable characters are escaped as decimal bytes.
+fn lua_str(s: &str) -> String {
+    let mut lua = String::with_capacity(s.len() + 2);
+    lua.push('"');
+    for b in s.bytes() {
+        match b {
+            b'"' => lua.push_str("\\\""),
+            b'\\' => lua.push_str("\\\\"),
+            0x20..=0x7e => lua.push(b as char),
+            _ => lua.push_str(&format!("\\{b:03}")),
+        }
+    }
+    lua.push('"');
+    lua
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::wireshark::gen_wireshark_dissector;
+    use std::path::Path;
+
+    #[test]
+    fn methods_dissector() {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/methods_api");
+        let api_bundle = crate::load(&crate_path, Some("Methods".into()), true).unwrap();
+        let lua = gen_wireshark_dissector(&api_bundle).unwrap();
+        assert!(lua.contains("ident = \"one_plain_arg\""));
+        assert!(lua.contains("args = { { name = \"value\", ty = { k = \"num\", b = \"u8\" } }, }"));
+        assert!(lua.contains("name = \"UserDefined\""));
+        assert!(lua.contains("wtap_encap:add(wtap.USER3, ww_evt)"));
+    }
+
+    /// Decodes captured frames with the generated dissector, Wireshark API is stubbed in wireshark_test_harness.lua.
+    #[test]
+    #[ignore = "requires a Lua interpreter, set WW_LUA to its path (default: lua) and run with --ignored"]
+    fn methods_dissector_decodes_frames() {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/methods_api");
+        let api_bundle = crate::load(&crate_path, Some("Methods".into()), true).unwrap();
+        let lua = gen_wireshark_dissector(&api_bundle).unwrap();
+        let dir = std::env::temp_dir().join(format!("ww_dissector_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dissector_path = dir.join("methods.lua");
+        let harness_path = dir.join("harness.lua");
+        std::fs::write(&dissector_path, lua).unwrap();
+        std::fs::write(&harness_path, include_str!("wireshark_test_harness.lua")).unwrap();
+
+        let frames = [
+            // LINKTYPE_USER0: MessageStartEnd from host, carrying Call one_plain_arg(0xCC) with seq 1
+            ("147", "800601000100cc11"),
+            // LINKTYPE_USER2: Call plain_return with seq 2
+            ("149", "0200020001"),
+            // LINKTYPE_USER3: ReturnValue 0xAA for seq 2
+            ("150", "020080aa01"),
+        ];
+        let lua_bin = std::env::var("WW_LUA").unwrap_or_else(|_| "lua".into());
+        let output = std::process::Command::new(&lua_bin)
+            .arg(&harness_path)
+            .arg(&dissector_path)
+            .args(
+                frames
+                    .iter()
+                    .flat_map(|(link_type, frame)| [link_type, frame]),
+            )
+            .output()
+            .unwrap_or_else(|e| panic!("failed to run {lua_bin}: {e}"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let info: Vec<&str> = stdout
+            .lines()
+            .filter_map(|line| line.strip_prefix("Info: "))
+            .collect();
+        assert_eq!(
+            info,
+            [
+                "host -> device: Call one_plain_arg { value: 204 }; ",
+                "Call plain_return; ",
+                "ReturnValue 170; ",
+            ]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+-- Minimal stand-ins for the Wireshark Lua API used by the generated dissector, so that frames can be decoded in tests.
+-- Usage: lua wireshark_test_harness.lua <dissector.lua> <link type> <frame hex> [<link type> <frame hex> ...]
+-- Prints Info column and protocol tree of each frame.
+
+local function int_pow(base, exp)
+    local result = 1
+    for _ = 1, exp do
+        result = result * base
+    end
+    return result
+end
+
+local function named_enum()
+    return setmetatable({}, { __index = function(_, key) return key end })
+end
+
+base = named_enum()
+frametype = named_enum()
+expert = { group = named_enum(), severity = named_enum() }
+ENC_UTF_8 = 0
+wtap = { USER0 = 147, USER1 = 148, USER2 = 149, USER3 = 150 }
+
+function Proto(name, description)
+    return { name = description }
+end
+
+ProtoField = setmetatable({}, {
+    __index = function(_, _)
+        return function(_, name)
+            return { name = name }
+        end
+    end,
+})
+
+ProtoExpert = {
+    new = function(_, name)
+        return { name = name }
+    end,
+}
+
+local dissectors = {}
+DissectorTable = {
+    get = function(_)
+        return {
+            add = function(_, link_type, proto)
+                dissectors[link_type] = proto
+            end,
+        }
+    end,
+}
+
+-- Tvb and TvbRange ------------------------------------------------------------------------------------------------
+
+local ByteArray = {}
+ByteArray.__index = ByteArray
+
+function ByteArray:tohex()
+    local hex = {}
+    for i, b in ipairs(self.data) do
+        hex[i] = string.format("%02X", b)
+    end
+    return table.concat(hex)
+end
+
+local TvbRange = {}
+TvbRange.__index = TvbRange
+
+local Tvb = {}
+Tvb.__index = Tvb
+
+local function new_tvb(data)
+    return setmetatable({ data = data }, Tvb)
+end
+
+Tvb.__call = function(self, offset, len)
+    offset = offset or 0
+    len = len or (#self.data - offset)
+    if offset < 0 or len < 0 or offset + len > #self.data then
+        error("tvb range out of bounds: " .. offset .. "+" .. len, 0)
+    end
+    return setmetatable({ data = self.data, offset = offset, length = len }, TvbRange)
+end
+
+function Tvb:len()
+    return #self.data
+end
+
+function TvbRange:len()
+    return self.length
+end
+
+function TvbRange:byte_at(idx)
+    return self.data[self.offset + idx + 1]
+end
+
+function TvbRange:tvb()
+    local data = {}
+    for i = 0, self.length - 1 do
+        data[#data + 1] = self:byte_at(i)
+    end
+    return new_tvb(data)
+end
+
+function TvbRange:uint()
+    local value = 0
+    for i = 0, self.length - 1 do
+        value = value * 256 + self:byte_at(i)
+    end
+    return value
+end
+
+function TvbRange:le_uint()
+    local value = 0
+    for i = self.length - 1, 0, -1 do
+        value = value * 256 + self:byte_at(i)
+    end
+    return value
+end
+
+TvbRange.le_uint64 = TvbRange.le_uint
+
+local function signed(value, len)
+    if value >= int_pow(2, len * 8 - 1) then
+        return value - int_pow(2, len * 8)
+    end
+    return value
+end
+
+function TvbRange:int()
+    return signed(self:uint(), self.length)
+end
+
+function TvbRange:le_int()
+    return signed(self:le_uint(), self.length)
+end
+
+TvbRange.le_int64 = TvbRange.le_int
+
+function TvbRange:raw()
+    local chars = {}
+    for i = 0, self.length - 1 do
+        chars[#chars + 1] = string.char(self:byte_at(i))
+    end
+    return table.concat(chars)
+end
+
+function TvbRange:le_float()
+    return (string.unpack(self.length == 4 and "<f" or "<d", self:raw()))
+end
+
+function TvbRange:string()
+    return self:raw()
+end
+
+function TvbRange:bytes()
+    local data = {}
+    for i = 0, self.length - 1 do
+        data[#data + 1] = self:byte_at(i)
+    end
+    return setmetatable({ data = data }, ByteArray)
+end
+
+-- Protocol tree and columns ---------------------------------------------------------------------------------------
+
+local TreeItem = {}
+TreeItem.__index = TreeItem
+
+local function new_item(text)
+    return setmetatable({ text = text, children = {} }, TreeItem)
+end
+
+function TreeItem:add(...)
+    local parts = {}
+    for i = 1, select("#", ...) do
+        local arg = select(i, ...)
+        if type(arg) == "table" and getmetatable(arg) ~= TvbRange then
+            parts[#parts + 1] = arg.name
+        elseif type(arg) ~= "table" and arg ~= nil then
+            parts[#parts + 1] = tostring(arg)
+        end
+    end
+    local item = new_item(table.concat(parts, ": "))
+    self.children[#self.children + 1] = item
+    return item
+end
+
+TreeItem.add_le = TreeItem.add
+
+function TreeItem:add_proto_expert_info(expert_info, text)
+    return self:add("Expert", expert_info.name, text)
+end
+
+function TreeItem:append_text(text)
+    self.text = self.text .. text
+end
+
+function TreeItem:set_len() end
+
+local Column = {}
+Column.__index = Column
+
+function Column:set(text)
+    self.text = text
+end
+
+function Column:append(text)
+    self.text = self.text .. text
+end
+
+function Column:clear()
+    self.text = ""
+end
+
+local function print_tree(item, depth)
+    print(string.rep("  ", depth) .. item.text)
+    for _, child in ipairs(item.children) do
+        print_tree(child, depth + 1)
+    end
+end
+
+-- Decode frames ---------------------------------------------------------------------------------------------------
+
+dofile(arg[1])
+
+local frame_number = 0
+for i = 2, #arg, 2 do
+    local link_type = tonumber(arg[i])
+    local data = {}
+    for hex in arg[i + 1]:gmatch("%x%x") do
+        data[#data + 1] = tonumber(hex, 16)
+    end
+    frame_number = frame_number + 1
+    local pinfo = {
+        number = frame_number,
+        visited = false,
+        cols = { protocol = "", info = setmetatable({ text = "" }, Column) },
+    }
+    local root = new_item("Frame " .. frame_number)
+    dissectors[link_type].dissector(new_tvb(data), pinfo, root)
+    print("Info: " .. pinfo.cols.info.text)
+    print_tree(root, 0)
+end
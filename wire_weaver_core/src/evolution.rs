@@ -0,0 +1,466 @@
+//! API evolution checks between two versions of an API, see docs/evolution/checker_tool.md.
+//!
+//! Resources are matched by their IDs, at each level of the API tree. A resource that is present in the old version,
+//! but not in the new one, must have been replaced with `reserved!`, which is only allowed for items that were marked
+//! `#[deprecated]` in the old version and only on a major version bump. Same goes for struct fields and enum variants of
+//! the types used by resources present in both versions, fields are matched by name and variants by discriminant.
+
+use crate::codegen::util::flags;
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use ww_self::{
+    ApiBundleOwned, ApiItemKindOwned, ApiLevelOwned, DeprecatedOwned, FieldsOwned, TypeOwned,
+};
+use ww_version::FullVersionOwned;
+
+/// Evolution rule violation found by [check_api_evolution].
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvolutionIssue {
+    /// Path of the resource in the old API, e.g. `periph[].channel[].gain`, or of a field or variant, e.g.
+    /// `Settings.gain` or `Mode::Triggered`.
+    pub path: String,
+    pub kind: EvolutionIssueKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EvolutionIssueKind {
+    /// Item, field or variant was removed without being marked `#[deprecated]` in the old version first.
+    RemovedWithoutDeprecation,
+    /// Item, field or variant was removed, but the new version is compatible with the old one (not a major bump).
+    RemovedWithoutMajorBump {
+        old: FullVersionOwned,
+        new: FullVersionOwned,
+    },
+}
+
+impl Display for EvolutionIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            EvolutionIssueKind::RemovedWithoutDeprecation => write!(
+                f,
+                "{}: removed without being marked #[deprecated] in a previous version",
+                self.path
+            ),
+            EvolutionIssueKind::RemovedWithoutMajorBump { old, new } => write!(
+                f,
+                "{}: removed in {new:?}, which is not a major version bump from {old:?}",
+                self.path
+            ),
+        }
+    }
+}
+
+/// Compare two versions of an API and return all the evolution rule violations, an empty list means that the new
+/// version can be released.
+pub fn check_api_evolution(
+    old: &ApiBundleOwned,
+    new: &ApiBundleOwned,
+) -> Result<Vec<EvolutionIssue>> {
+    let mut checker = Checker {
+        old,
+        new,
+        checked_types: HashSet::new(),
+        issues: vec![],
+    };
+    checker.check_level(&old.root, &new.root, "")?;
+    Ok(checker.issues)
+}
+
+struct Checker<'a> {
+    old: &'a ApiBundleOwned,
+    new: &'a ApiBundleOwned,
+    /// Names of the types already compared, so that each issue is reported once, no matter how many resources use it.
+    checked_types: HashSet<String>,
+    issues: Vec<EvolutionIssue>,
+}
+
+impl Checker<'_> {
+    fn check_level(
+        &mut self,
+        old_level: &ApiLevelOwned,
+        new_level: &ApiLevelOwned,
+        path: &str,
+    ) -> Result<()> {
+        for old_item in &old_level.items {
+            let item_path = if path.is_empty() {
+                old_item.ident.clone()
+            } else {
+                format!("{path}.{}", old_item.ident)
+            };
+            let item_path = if old_item.is_array() {
+                format!("{item_path}[]")
+            } else {
+                item_path
+            };
+            let Some(new_item) = new_level.items.iter().find(|i| i.id == old_item.id) else {
+                self.check_removal(
+                    item_path,
+                    old_item.deprecated.as_ref(),
+                    old_level.crate_idx.0,
+                    new_level.crate_idx.0,
+                )?;
+                continue;
+            };
+            match (&old_item.kind, &new_item.kind) {
+                (ApiItemKindOwned::Trait { .. }, ApiItemKindOwned::Trait { .. }) => {
+                    let old_trait = old_item.get_as_level(self.old)?;
+                    let new_trait = new_item.get_as_level(self.new)?;
+                    let same_crate = self.old.crate_name(old_trait.crate_idx.0)?
+                        == self.new.crate_name(new_trait.crate_idx.0)?;
+                    // different trait implemented at the same ID, nothing to compare with
+                    if same_crate && old_trait.trait_name == new_trait.trait_name {
+                        self.check_level(old_trait, new_trait, &item_path)?;
+                    }
+                }
+                (
+                    ApiItemKindOwned::Method {
+                        args: old_args,
+                        return_ty: old_return_ty,
+                    },
+                    ApiItemKindOwned::Method {
+                        args: new_args,
+                        return_ty: new_return_ty,
+                    },
+                ) => {
+                    for (old_arg, new_arg) in old_args.iter().zip(new_args) {
+                        self.check_type(&old_arg.ty, &new_arg.ty)?;
+                    }
+                    if let (Some(old_ty), Some(new_ty)) = (old_return_ty, new_return_ty) {
+                        self.check_type(old_ty, new_ty)?;
+                    }
+                }
+                (
+                    ApiItemKindOwned::Property {
+                        ty: old_ty,
+                        write_err_ty: old_err_ty,
+                        ..
+                    },
+                    ApiItemKindOwned::Property {
+                        ty: new_ty,
+                        write_err_ty: new_err_ty,
+                        ..
+                    },
+                ) => {
+                    self.check_type(old_ty, new_ty)?;
+                    if let (Some(old_ty), Some(new_ty)) = (old_err_ty, new_err_ty) {
+                        self.check_type(old_ty, new_ty)?;
+                    }
+                }
+                (
+                    ApiItemKindOwned::Stream { ty: old_ty, .. },
+                    ApiItemKindOwned::Stream { ty: new_ty, .. },
+                ) => {
+                    self.check_type(old_ty, new_ty)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Resource, struct field or enum variant is present in the old version, but not in the new one.
+    fn check_removal(
+        &mut self,
+        path: String,
+        deprecated: Option<&DeprecatedOwned>,
+        old_crate_idx: u32,
+        new_crate_idx: u32,
+    ) -> Result<()> {
+        let old_version = self.old.crate_version(old_crate_idx)?;
+        let new_version = self.new.crate_version(new_crate_idx)?;
+        let major_bump = !old_version
+            .version
+            .as_ref()
+            .is_protocol_compatible(&new_version.version.as_ref());
+        if deprecated.is_none() {
+            self.issues.push(EvolutionIssue {
+                path: path.clone(),
+                kind: EvolutionIssueKind::RemovedWithoutDeprecation,
+            });
+        }
+        if !major_bump {
+            self.issues.push(EvolutionIssue {
+                path,
+                kind: EvolutionIssueKind::RemovedWithoutMajorBump {
+                    old: old_version.clone(),
+                    new: new_version.clone(),
+                },
+            });
+        }
+        Ok(())
+    }
+
+    /// Compare types used in the same position, structs and enums are matched by name, fields by name and variants by
+    /// discriminant.
+    fn check_type(&mut self, old_ty: &TypeOwned, new_ty: &TypeOwned) -> Result<()> {
+        let old_ty = old_ty.get_in_line(self.old)?;
+        let new_ty = new_ty.get_in_line(self.new)?;
+        match (old_ty, new_ty) {
+            (TypeOwned::Struct(old_struct), TypeOwned::Struct(new_struct)) => {
+                let Some(name) = self.first_check(old_ty, new_ty)? else {
+                    return Ok(());
+                };
+                let crates = (old_struct.crate_idx.0, new_struct.crate_idx.0);
+                self.check_fields(&name, &old_struct.fields, &new_struct.fields, crates)?;
+            }
+            (TypeOwned::Enum(old_enum), TypeOwned::Enum(new_enum)) => {
+                let Some(name) = self.first_check(old_ty, new_ty)? else {
+                    return Ok(());
+                };
+                let crates = (old_enum.crate_idx.0, new_enum.crate_idx.0);
+                for old_variant in &old_enum.variants {
+                    let variant_path = format!("{name}::{}", old_variant.ident);
+                    match new_enum
+                        .variants
+                        .iter()
+                        .find(|v| v.discriminant == old_variant.discriminant)
+                    {
+                        Some(new_variant) => self.check_fields(
+                            &variant_path,
+                            &old_variant.fields,
+                            &new_variant.fields,
+                            crates,
+                        )?,
+                        None => self.check_removal(
+                            variant_path,
+                            old_variant.deprecated.as_ref(),
+                            crates.0,
+                            crates.1,
+                        )?,
+                    }
+                }
+            }
+            (TypeOwned::Vec(old_inner), TypeOwned::Vec(new_inner))
+            | (TypeOwned::Box(old_inner), TypeOwned::Box(new_inner))
+            | (
+                TypeOwned::Option { some_ty: old_inner },
+                TypeOwned::Option { some_ty: new_inner },
+            )
+            | (TypeOwned::Array { ty: old_inner, .. }, TypeOwned::Array { ty: new_inner, .. })
+            | (
+                TypeOwned::BoundedVec { ty: old_inner, .. },
+                TypeOwned::BoundedVec { ty: new_inner, .. },
+            ) => self.check_type(old_inner, new_inner)?,
+            (
+                TypeOwned::Result {
+                    ok_ty: old_ok,
+                    err_ty: old_err,
+                },
+                TypeOwned::Result {
+                    ok_ty: new_ok,
+                    err_ty: new_err,
+                },
+            ) => {
+                self.check_type(old_ok, new_ok)?;
+                self.check_type(old_err, new_err)?;
+            }
+            (TypeOwned::Tuple(old_types), TypeOwned::Tuple(new_types)) => {
+                for (old_ty, new_ty) in old_types.iter().zip(new_types) {
+                    self.check_type(old_ty, new_ty)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Returns type name, if both types are the same user type and it wasn't compared yet.
+    fn first_check(&mut self, old_ty: &TypeOwned, new_ty: &TypeOwned) -> Result<Option<String>> {
+        let name = old_ty.human_name(false, self.old)?;
+        // different type used at the same place, nothing to compare with
+        if name != new_ty.human_name(false, self.new)? || !self.checked_types.insert(name.clone()) {
+            return Ok(None);
+        }
+        Ok(Some(name))
+    }
+
+    fn check_fields(
+        &mut self,
+        path: &str,
+        old_fields: &FieldsOwned,
+        new_fields: &FieldsOwned,
+        (old_crate_idx, new_crate_idx): (u32, u32),
+    ) -> Result<()> {
+        match (old_fields, new_fields) {
+            (FieldsOwned::Named(old_fields), FieldsOwned::Named(new_fields)) => {
+                let old_flags = flags(old_fields)?;
+                let new_flags = flags(new_fields)?;
+                for (idx, old_field) in old_fields.iter().enumerate() {
+                    // explicit flags are removed together with the Option or Result they belong to
+                    if old_flags.flag_for[idx].is_some() {
+                        continue;
+                    }
+                    let new_field = new_fields.iter().enumerate().find(|(idx, f)| {
+                        new_flags.flag_for[*idx].is_none() && f.ident == old_field.ident
+                    });
+                    match new_field {
+                        Some((_, new_field)) => self.check_type(&old_field.ty, &new_field.ty)?,
+                        None => {
+                            // #[deprecated] can also be put on an explicit flag
+                            let deprecated = old_field.deprecated.as_ref().or_else(|| {
+                                let flag_idx =
+                                    old_flags.flag_for.iter().position(|f| *f == Some(idx))?;
+                                old_fields[flag_idx].deprecated.as_ref()
+                            });
+                            let field_path = format!(
+                                "{path}.{}",
+                                old_field.ident.as_deref().unwrap_or_default()
+                            );
+                            self.check_removal(
+                                field_path,
+                                deprecated,
+                                old_crate_idx,
+                                new_crate_idx,
+                            )?;
+                        }
+                    }
+                }
+            }
+            (FieldsOwned::Unnamed(old_fields), FieldsOwned::Unnamed(new_fields)) => {
+                for (idx, old_field) in old_fields.iter().enumerate() {
+                    match new_fields.get(idx) {
+                        Some(new_field) => self.check_type(&old_field.ty, &new_field.ty)?,
+                        None => self.check_removal(
+                            format!("{path}.{idx}"),
+                            old_field.deprecated.as_ref(),
+                            old_crate_idx,
+                            new_crate_idx,
+                        )?,
+                    }
+                }
+            }
+            // changed between named, unnamed and unit, fields cannot be matched
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use ww_self::DeprecatedOwned;
+    use ww_version::VersionTriplet;
+
+    fn load_deprecated_api() -> ApiBundleOwned {
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/deprecated_api");
+        crate::load(&crate_path, Some("Sensor".into()), true).unwrap()
+    }
+
+    fn set_version(api_bundle: &mut ApiBundleOwned, major: u32, minor: u32) {
+        let crate_idx = api_bundle.root.crate_idx.0 as usize;
+        let version = &mut api_bundle.ext_crates[crate_idx].version;
+        version.major.0 = major;
+        version.minor.0 = minor;
+    }
+
+    #[test]
+    fn deprecated_recorded_in_ww_self() {
+        let api_bundle = load_deprecated_api();
+        let items = &api_bundle.root.items;
+        assert_eq!(items[0].deprecated, None);
+        assert_eq!(
+            items[1].deprecated,
+            Some(DeprecatedOwned {
+                since: Some(VersionTriplet::new(0, 2, 0)),
+                note: Some("use configure instead".into()),
+            })
+        );
+        assert_eq!(
+            items[2].deprecated,
+            Some(DeprecatedOwned {
+                since: None,
+                note: None
+            })
+        );
+        assert_eq!(
+            items[3].deprecated.as_ref().unwrap().note.as_deref(),
+            Some("samples are now sent with timestamps")
+        );
+
+        let ApiItemKindOwned::Method { args, .. } = &items[0].kind else {
+            panic!("expected method");
+        };
+        let settings = args[0].ty.get_in_line(&api_bundle).unwrap();
+        let ww_self::TypeOwned::Struct(settings) = settings else {
+            panic!("expected struct");
+        };
+        let ww_self::FieldsOwned::Named(fields) = &settings.fields else {
+            panic!("expected named fields");
+        };
+        assert_eq!(fields[0].deprecated, None);
+        assert_eq!(
+            fields[1].deprecated.as_ref().unwrap().note.as_deref(),
+            Some("gain is set per channel")
+        );
+
+        let ApiItemKindOwned::Property { ty, .. } = &items[2].kind else {
+            panic!("expected property");
+        };
+        let ww_self::TypeOwned::Enum(mode) = ty.get_in_line(&api_bundle).unwrap() else {
+            panic!("expected enum");
+        };
+        assert_eq!(mode.variants[0].deprecated, None);
+        assert_eq!(
+            mode.variants[1]
+                .deprecated
+                .as_ref()
+                .unwrap()
+                .note
+                .as_deref(),
+            Some("devices ignore it")
+        );
+    }
+
+    #[test]
+    fn removal_rules() {
+        let old = load_deprecated_api();
+        assert_eq!(check_api_evolution(&old, &old).unwrap(), vec![]);
+
+        // deprecated_api::next replaces deprecated resources with reserved! and drops deprecated fields and variants,
+        // along with a method, a field and a variant that were not deprecated
+        let crate_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/deprecated_api");
+        let mut new = crate::load(&crate_path, Some("SensorNext".into()), true).unwrap();
+        set_version(&mut new, 1, 0);
+        let issues = check_api_evolution(&old, &new).unwrap();
+        assert_eq!(
+            issues,
+            vec![
+                EvolutionIssue {
+                    path: "Settings.channel".into(),
+                    kind: EvolutionIssueKind::RemovedWithoutDeprecation,
+                },
+                EvolutionIssue {
+                    path: "calibrate".into(),
+                    kind: EvolutionIssueKind::RemovedWithoutDeprecation,
+                },
+            ]
+        );
+
+        set_version(&mut new, 0, 2);
+        let issues = check_api_evolution(&old, &new).unwrap();
+        let not_major: Vec<&str> = issues
+            .iter()
+            .filter(|i| matches!(i.kind, EvolutionIssueKind::RemovedWithoutMajorBump { .. }))
+            .map(|i| i.path.as_str())
+            .collect();
+        assert_eq!(
+            not_major,
+            [
+                "Settings.gain",
+                "Settings.channel",
+                "Settings.offset",
+                "set_gain",
+                "Mode::Triggered",
+                "raw_samples",
+                "calibrate"
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "Settings.gain: removed in deprecated_api@0.2.0, which is not a major version bump from deprecated_api@0.2.0"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 pub mod codegen;
 // pub mod eval;
+pub mod evolution;
 pub mod layout;
 mod local_registry;
 pub mod method_model;
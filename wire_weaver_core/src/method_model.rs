@@ -27,6 +27,8 @@ pub struct MethodModelItem {
 pub enum MethodModelKind {
     Immediate,
     Deferred,
+    /// Deferred, with a user handler called when a client cancels a call (`deferred+cancellable`)
+    DeferredCancellable,
 }
 
 impl MethodModel {
@@ -41,10 +43,18 @@ impl MethodModel {
             let mut item = item.into_inner();
             let regex = item.next().unwrap().as_str();
             let model = item.next().unwrap().into_inner().next().unwrap();
-            let model = match model.as_rule() {
-                Rule::immediate => MethodModelKind::Immediate,
-                Rule::deferred => MethodModelKind::Deferred,
-                _ => unreachable!(),
+            let modifier = item
+                .next()
+                .map(|m| m.into_inner().next().unwrap().as_rule());
+            let model = match (model.as_rule(), modifier) {
+                (Rule::immediate, None) => MethodModelKind::Immediate,
+                (Rule::deferred, None) => MethodModelKind::Deferred,
+                (Rule::deferred, Some(Rule::cancellable)) => MethodModelKind::DeferredCancellable,
+                _ => {
+                    return Err(Error::msg(format!(
+                        "Unsupported method model for '{regex}', expected immediate, deferred or deferred+cancellable"
+                    )));
+                }
             };
             if regex == "_" {
                 if property_model.default.is_some() {
@@ -72,11 +82,20 @@ impl MethodModel {
 
 #[cfg(test)]
 mod tests {
-    use crate::method_model::MethodModel;
+    use crate::method_model::{MethodModel, MethodModelKind};
 
     #[test]
     fn property_model_parse() {
         let m = MethodModel::parse(".*move=deferred, _=immediate").unwrap();
         println!("{:?}", m);
     }
+
+    #[test]
+    fn cancellable_model_parse() {
+        let m = MethodModel::parse("move=deferred+cancellable, _=immediate").unwrap();
+        assert_eq!(m.pick("move"), Some(MethodModelKind::DeferredCancellable));
+        assert_eq!(m.pick("stop"), Some(MethodModelKind::Immediate));
+        assert!(MethodModel::parse("move=cancellable").is_err());
+        assert!(MethodModel::parse("move=immediate+cancellable").is_err());
+    }
 }
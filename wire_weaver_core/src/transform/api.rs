@@ -1,7 +1,7 @@
 use super::{
-    crate_walker::{CrateContext, Scratch},
+    crate_walker::{CrateContext, Resolved, Scratch},
     ty::{convert_ty, convert_ty_path, convert_ty_path_segment},
-    util::{collect_docs, get_since_attr},
+    util::{collect_docs, get_deprecated_attr, get_since_attr},
 };
 use anyhow::{Context, Result, anyhow};
 use proc_macro2::Ident;
@@ -106,6 +106,7 @@ pub(crate) fn convert_api_items(
                     since,
                     ident: trait_item_const.ident.to_string(),
                     docs,
+                    deprecated: get_deprecated_attr(&trait_item_const.attrs, current_crate)?,
                 });
             }
             i => {
@@ -154,6 +155,7 @@ fn convert_api_item_fn(
         since,
         ident: item_fn.sig.ident.to_string(),
         docs: collect_docs(&item_fn.attrs),
+        deprecated: get_deprecated_attr(&item_fn.attrs, current_crate)?,
     })
 }
 
@@ -183,6 +185,7 @@ fn convert_api_item_stream(
         since,
         ident: args.resource_name.to_string(),
         docs,
+        deprecated: get_deprecated_attr(&item_macro.attrs, current_crate)?,
     })
 }
 
@@ -217,6 +220,7 @@ fn convert_api_item_property(
         since,
         ident: args.resource_name.to_string(),
         docs,
+        deprecated: get_deprecated_attr(&item_macro.attrs, current_crate)?,
     })
 }
 
@@ -239,21 +243,13 @@ fn convert_api_item_impl(
             args.type_or_trait
         ));
     };
-    let len = type_path.path.segments.len();
-    let kind = if len == 1 {
-        let trait_name = &type_path.path.segments[0];
-        find_and_convert_trait(trait_name, current_crate, scratch)?
-    } else if len == 2 {
-        let dep_crate_name = type_path.path.segments[0].ident.to_string();
-        let dependent_crate = current_crate.load_dependent_crate(&dep_crate_name, scratch)?;
-        let trait_name = &type_path.path.segments[1];
-        find_and_convert_trait(trait_name, &dependent_crate, scratch)?
-    } else {
-        return Err(
-            anyhow!("Only support `MyTrait` and `ext_crate::MyTrait` for now")
-                .context(current_crate.err_context()),
-        );
-    };
+    let path: Vec<String> = type_path
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+    let kind = find_and_convert_trait(&path, current_crate, scratch)?;
     let multiplicity = convert_multiplicity(&args.multiplicity, current_crate, scratch)?;
     let since = get_since_attr(&item_macro.attrs, current_crate)?;
     Ok(ApiItemOwned {
@@ -263,41 +259,50 @@ fn convert_api_item_impl(
         since,
         ident: args.resource_name.to_string(),
         docs: collect_docs(&item_macro.attrs),
+        deprecated: get_deprecated_attr(&item_macro.attrs, current_crate)?,
     })
 }
 
 fn find_and_convert_trait(
-    trait_name: &PathSegment,
+    path: &[String],
     current_crate: &CrateContext,
     scratch: &mut Scratch,
 ) -> Result<ApiItemKindOwned> {
-    for item in &current_crate.lib_rs_ast.items {
-        let Item::Trait(item_trait) = item else {
-            continue;
-        };
-        if item_trait.ident != trait_name.ident {
-            continue;
+    let (trait_crate, item_idx) = match current_crate.resolve(path)? {
+        Some(Resolved::Local(cx, item_idx)) => (cx, item_idx),
+        Some(Resolved::External { crate_name, path }) => {
+            let dependent_crate = current_crate.load_dependent_crate(&crate_name, scratch)?;
+            return find_and_convert_trait(&path, &dependent_crate, scratch);
+        }
+        None => {
+            return Err(
+                anyhow!("Trait {} not found", path.join("::")).context(current_crate.err_context())
+            );
         }
-        let crate_idx = scratch.root_bundle.find_crate_or_create(current_crate);
-        let items = convert_api_items(item_trait, current_crate, scratch)?;
-        let trait_idx = scratch.root_bundle.traits.len() as u32;
-        scratch
-            .root_bundle
-            .traits
-            .push(ApiLevelLocationOwned::InLine {
-                level: ApiLevelOwned {
-                    docs: collect_docs(&item_trait.attrs),
-                    crate_idx,
-                    trait_name: trait_name.ident.to_string(),
-                    items,
-                },
+    };
+    let Item::Trait(item_trait) = trait_crate.item(item_idx) else {
+        return Err(
+            anyhow!("{} is not a trait", path.join("::")).context(trait_crate.err_context())
+        );
+    };
+    let crate_idx = scratch.root_bundle.find_crate_or_create(&trait_crate);
+    let items = convert_api_items(item_trait, &trait_crate, scratch)?;
+    let trait_idx = scratch.root_bundle.traits.len() as u32;
+    scratch
+        .root_bundle
+        .traits
+        .push(ApiLevelLocationOwned::InLine {
+            level: ApiLevelOwned {
+                docs: collect_docs(&item_trait.attrs),
                 crate_idx,
-            });
-        return Ok(ApiItemKindOwned::Trait {
-            trait_idx: trait_idx.into(),
+                trait_name: item_trait.ident.to_string(),
+                items,
+            },
+            crate_idx,
         });
-    }
-    Err(anyhow!("Trait {trait_name:?} not found").context(current_crate.err_context()))
+    Ok(ApiItemKindOwned::Trait {
+        trait_idx: trait_idx.into(),
+    })
 }
 
 /// ww_impl!(gpio: Gpio) or stream!(data: Packet)
@@ -0,0 +1,567 @@
+//! Locating registry and git dependencies of API crates in the Cargo home directory, or through `cargo metadata`.
+use anyhow::{Context, Result, anyhow};
+use semver::{Version, VersionReq};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where a dependency comes from, according to Cargo.toml.
+pub(crate) enum DepSource<'i> {
+    /// crates.io, or an alternative registry with the provided index URL (`registry = "name"` or `registry-index = "url"`)
+    Registry {
+        req: VersionReq,
+        index: Option<String>,
+    },
+    /// `git = "url"` with an optional `rev`
+    Git { url: &'i str, rev: Option<&'i str> },
+}
+
+impl DepSource<'_> {
+    /// Whether a package `source` from Cargo.lock or `cargo metadata` output satisfies this dependency.
+    fn matches(&self, source: &str, version: Option<&Version>) -> bool {
+        match self {
+            DepSource::Registry { req, index } => {
+                let Some(index_url) = registry_index_url(source) else {
+                    return false;
+                };
+                let same_registry = match index {
+                    Some(index) => same_index(index, index_url),
+                    None => is_crates_io(index_url),
+                };
+                same_registry && version.is_some_and(|v| req.matches(v))
+            }
+            DepSource::Git { url, rev } => {
+                let Some(git) = GitSource::parse(source) else {
+                    return false;
+                };
+                same_repository(git.url, url)
+                    && rev.is_none_or(|rev| {
+                        git.commit.starts_with(rev)
+                            || git
+                                .query
+                                .split('&')
+                                .any(|q| q.strip_prefix("rev=") == Some(rev))
+                    })
+            }
+        }
+    }
+}
+
+/// `$CARGO_HOME` or `~/.cargo`.
+pub(crate) fn cargo_home() -> Option<PathBuf> {
+    if let Some(home) = std::env::var_os("CARGO_HOME") {
+        return Some(PathBuf::from(home));
+    }
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".cargo"))
+}
+
+/// Same as Cargo `--offline` flag, can also be set with `CARGO_NET_OFFLINE=true`.
+pub(crate) fn is_cargo_offline() -> bool {
+    std::env::var("CARGO_NET_OFFLINE").is_ok_and(|v| v == "true" || v == "1")
+}
+
+/// Index URL of an alternative registry named in Cargo.toml (`registry = "name"`).
+///
+/// Resolved the same way as Cargo does: `CARGO_REGISTRIES_<NAME>_INDEX` environment variable first, then
+/// `registries.<name>.index` in `.cargo/config.toml` of `crate_path` or one of its parent directories (the closest one
+/// wins), and finally in `$CARGO_HOME/config.toml`.
+pub(crate) fn registry_index(crate_path: &Path, name: &str) -> Option<String> {
+    let env_name = format!(
+        "CARGO_REGISTRIES_{}_INDEX",
+        name.to_uppercase().replace('-', "_")
+    );
+    if let Ok(index) = std::env::var(env_name) {
+        return Some(index);
+    }
+    let config_dirs = crate_path
+        .ancestors()
+        .map(|dir| dir.join(".cargo"))
+        .chain(cargo_home());
+    for config_dir in config_dirs {
+        // config without extension is still supported by Cargo
+        for file in ["config.toml", "config"] {
+            let Ok(contents) = fs::read_to_string(config_dir.join(file)) else {
+                continue;
+            };
+            let Ok(config) = contents.parse::<toml::Table>() else {
+                continue;
+            };
+            if let Some(index) = config
+                .get("registries")
+                .and_then(|r| r.get(name))
+                .and_then(|r| r.get("index"))
+                .and_then(|i| i.as_str())
+            {
+                return Some(index.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Find a crate unpacked by Cargo into `$CARGO_HOME/registry/src/<index host>-<hash>/<package>-<version>/`.
+/// Only the directory of the registry with `index_url` is searched.
+pub(crate) fn find_registry_crate(
+    cargo_home: &Path,
+    index_url: &str,
+    package: &str,
+    version: &Version,
+) -> Option<PathBuf> {
+    let hosts = registry_hosts(index_url);
+    read_dirs(&cargo_home.join("registry").join("src"))
+        .into_iter()
+        .filter(|index_dir| {
+            strip_hash_suffix(&file_name(index_dir)).is_some_and(|host| hosts.contains(&host))
+        })
+        .map(|index_dir| index_dir.join(format!("{package}-{version}")))
+        .find(|crate_dir| crate_dir.join("Cargo.toml").is_file())
+}
+
+/// Find a crate in a repository checked out by Cargo into `$CARGO_HOME/git/checkouts/<repo>-<hash>/<short commit>/`.
+/// The crate can be located anywhere inside a repository, e.g., in a workspace member directory.
+pub(crate) fn find_git_crate(
+    cargo_home: &Path,
+    package: &str,
+    url: &str,
+    commit: &str,
+) -> Option<PathBuf> {
+    let repo_name = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit('/')
+        .next()?;
+    read_dirs(&cargo_home.join("git").join("checkouts"))
+        .into_iter()
+        .filter(|repo_dir| strip_hash_suffix(&file_name(repo_dir)) == Some(repo_name))
+        .flat_map(|repo_dir| read_dirs(&repo_dir))
+        .filter(|checkout| {
+            let short_commit = file_name(checkout);
+            short_commit.len() >= 7 && commit.starts_with(short_commit.as_str())
+        })
+        .find_map(|checkout| find_package_dir(&checkout, package, 3))
+}
+
+/// Packages locked in Cargo.lock of the root crate or its workspace.
+pub(crate) struct CargoLock {
+    packages: Vec<LockedPackage>,
+}
+
+pub(crate) struct LockedPackage {
+    name: String,
+    version: Option<Version>,
+    source: String,
+}
+
+impl CargoLock {
+    /// Load Cargo.lock from `crate_path` or one of its parent directories, `None` is returned if there is none.
+    pub(crate) fn find(crate_path: &Path) -> Result<Option<Self>> {
+        let Some(lock_path) = crate_path
+            .ancestors()
+            .map(|dir| dir.join("Cargo.lock"))
+            .find(|path| path.is_file())
+        else {
+            return Ok(None);
+        };
+        let contents = fs::read_to_string(&lock_path)
+            .context(format!("Failed to read {}", lock_path.display()))?;
+        Self::parse(&contents)
+            .context(format!("Failed to parse {}", lock_path.display()))
+            .map(Some)
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let lock: toml::Table = contents.parse()?;
+        let packages = lock
+            .get("package")
+            .and_then(|packages| packages.as_array())
+            .map(|packages| {
+                packages
+                    .iter()
+                    .filter_map(|p| {
+                        Some(LockedPackage {
+                            name: p.get("name")?.as_str()?.to_string(),
+                            version: p
+                                .get("version")
+                                .and_then(|v| v.as_str())
+                                .and_then(|v| Version::parse(v).ok()),
+                            // local packages have no source
+                            source: p.get("source")?.as_str()?.to_string(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(CargoLock { packages })
+    }
+
+    /// Returns the locked package matching the provided source.
+    pub(crate) fn find_package(&self, package: &str, source: &DepSource) -> Option<&LockedPackage> {
+        self.packages
+            .iter()
+            .filter(|p| p.name == package)
+            .find(|p| source.matches(&p.source, p.version.as_ref()))
+    }
+}
+
+impl LockedPackage {
+    /// Returns the directory of this exact package version or git commit, if Cargo already downloaded it.
+    pub(crate) fn find_in_cargo_home(&self, cargo_home: &Path) -> Option<PathBuf> {
+        if let Some(index_url) = registry_index_url(&self.source) {
+            find_registry_crate(cargo_home, index_url, &self.name, self.version.as_ref()?)
+        } else {
+            let git = GitSource::parse(&self.source)?;
+            find_git_crate(cargo_home, &self.name, git.url, git.commit)
+        }
+    }
+}
+
+/// Packages resolved by `cargo metadata`, used when a dependency is not yet downloaded.
+pub(crate) struct CargoMetadata {
+    packages: Vec<MetadataPackage>,
+}
+
+struct MetadataPackage {
+    name: String,
+    version: Option<Version>,
+    source: String,
+    manifest_path: PathBuf,
+}
+
+impl CargoMetadata {
+    /// Run `cargo metadata` for a crate or workspace at `manifest_dir`, Cargo will download missing dependencies
+    /// unless `offline` is true.
+    pub(crate) fn load(manifest_dir: &Path, offline: bool) -> Result<Self> {
+        let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let mut command = Command::new(cargo);
+        command.args(["metadata", "--format-version", "1", "--manifest-path"]);
+        command.arg(manifest_dir.join("Cargo.toml"));
+        if offline {
+            command.arg("--offline");
+        }
+        let output = command.output().context("Failed to run cargo metadata")?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "cargo metadata failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        let metadata: serde_json::Value =
+            serde_json::from_slice(&output.stdout).context("Failed to parse cargo metadata")?;
+        let packages = metadata["packages"]
+            .as_array()
+            .map(|packages| {
+                packages
+                    .iter()
+                    .filter_map(|p| {
+                        Some(MetadataPackage {
+                            name: p["name"].as_str()?.to_string(),
+                            version: p["version"].as_str().and_then(|v| Version::parse(v).ok()),
+                            source: p["source"].as_str().unwrap_or_default().to_string(),
+                            manifest_path: PathBuf::from(p["manifest_path"].as_str()?),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(CargoMetadata { packages })
+    }
+
+    /// Returns the directory of a package matching the provided source.
+    pub(crate) fn find(&self, package: &str, source: &DepSource) -> Option<PathBuf> {
+        self.packages
+            .iter()
+            .filter(|p| p.name == package)
+            .find(|p| source.matches(&p.source, p.version.as_ref()))
+            .and_then(|p| p.manifest_path.parent().map(Path::to_path_buf))
+    }
+}
+
+/// `git+<url>?<query>#<commit>` source of a git dependency.
+struct GitSource<'i> {
+    url: &'i str,
+    /// `rev=..`, `branch=..` or `tag=..`, empty if the default branch is used
+    query: &'i str,
+    commit: &'i str,
+}
+
+impl<'i> GitSource<'i> {
+    fn parse(source: &'i str) -> Option<Self> {
+        let (url, commit) = source.strip_prefix("git+")?.split_once('#')?;
+        let (url, query) = url.split_once('?').unwrap_or((url, ""));
+        Some(GitSource { url, query, commit })
+    }
+}
+
+/// Index URL of a `registry+<url>` or `sparse+<url>` source.
+fn registry_index_url(source: &str) -> Option<&str> {
+    source
+        .strip_prefix("registry+")
+        .or_else(|| source.strip_prefix("sparse+"))
+}
+
+/// Compare an index URL from Cargo config or Cargo.toml with the one from a package source, protocol prefix is optional
+/// in the former.
+fn same_index(configured: &str, index_url: &str) -> bool {
+    let configured = registry_index_url(configured).unwrap_or(configured);
+    configured.trim_end_matches('/') == index_url.trim_end_matches('/')
+}
+
+fn is_crates_io(index_url: &str) -> bool {
+    let index_url = index_url.trim_end_matches('/');
+    index_url == "https://github.com/rust-lang/crates.io-index"
+        || index_url == "https://index.crates.io"
+}
+
+/// Hosts used by Cargo to name the `registry/src/<host>-<hash>` directory of a registry.
+fn registry_hosts(index_url: &str) -> Vec<&str> {
+    if is_crates_io(index_url) {
+        // github.com was used before the sparse protocol became the default
+        return vec!["index.crates.io", "github.com"];
+    }
+    let without_scheme = index_url
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(index_url);
+    let host = without_scheme
+        .split(['/', ':'])
+        .next()
+        .unwrap_or(without_scheme);
+    vec![host]
+}
+
+fn same_repository(a: &str, b: &str) -> bool {
+    let normalize = |url: &str| {
+        url.trim_end_matches('/')
+            .trim_end_matches(".git")
+            .to_lowercase()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// `name-0123456789abcdef` -> `name`, Cargo appends a 16-digit hash of the source URL to registry and repository
+/// directory names.
+fn strip_hash_suffix(dir_name: &str) -> Option<&str> {
+    let (name, hash) = dir_name.rsplit_once('-')?;
+    (hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some(name)
+}
+
+/// Look for a directory with Cargo.toml of the provided package name, up to `depth` levels deep.
+fn find_package_dir(dir: &Path, package: &str, depth: usize) -> Option<PathBuf> {
+    if let Ok(contents) = fs::read_to_string(dir.join("Cargo.toml"))
+        && let Ok(manifest) = cargo_toml::Manifest::from_str(&contents)
+        && manifest.package.is_some_and(|p| p.name == package)
+    {
+        return Some(dir.to_path_buf());
+    }
+    if depth == 0 {
+        return None;
+    }
+    read_dirs(dir)
+        .into_iter()
+        .filter(|d| {
+            let name = file_name(d);
+            !name.starts_with('.') && name != "target"
+        })
+        .find_map(|d| find_package_dir(&d, package, depth - 1))
+}
+
+fn read_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn manifest(name: &str, version: &str) -> String {
+        format!("[package]\nname = \"{name}\"\nversion = \"{version}\"\n")
+    }
+
+    #[test]
+    fn locked_registry_and_git_crates_are_found_in_cargo_home() {
+        let cargo_home = std::env::temp_dir().join(format!("ww_cargo_home_{}", std::process::id()));
+        _ = fs::remove_dir_all(&cargo_home);
+        let crates_io = cargo_home.join("registry/src/index.crates.io-1949cf8c6b5b557f");
+        let alt = cargo_home.join("registry/src/registry.example.com-0123456789abcdef");
+        for (dir, version) in [
+            (&crates_io, "0.1.0"),
+            (&crates_io, "0.2.1"),
+            (&alt, "0.2.3"),
+        ] {
+            write(
+                &dir.join(format!("gpio_api-{version}/Cargo.toml")),
+                &manifest("gpio_api", version),
+            );
+        }
+        let checkouts = cargo_home.join("git/checkouts/traits-a1b2c3d4e5f60718");
+        write(
+            &checkouts.join("5e825ac/Cargo.toml"),
+            "[workspace]\nmembers = [\"can_api\"]\n",
+        );
+        write(
+            &checkouts.join("5e825ac/can_api/Cargo.toml"),
+            &manifest("can_api", "1.0.0"),
+        );
+        // another repository which name starts with the same prefix
+        let ext_checkouts = cargo_home.join("git/checkouts/traits-ext-0123456789abcdef");
+        write(
+            &ext_checkouts.join("9f00d11/can_api/Cargo.toml"),
+            &manifest("can_api", "2.0.0"),
+        );
+
+        let lock = CargoLock::parse(
+            r#"
+version = 4
+
+[[package]]
+name = "api"
+version = "0.1.0"
+
+[[package]]
+name = "gpio_api"
+version = "0.2.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "gpio_api"
+version = "0.2.3"
+source = "sparse+https://registry.example.com/index/"
+
+[[package]]
+name = "can_api"
+version = "1.0.0"
+source = "git+https://github.com/vhrdtech/traits.git?rev=5e825ac#5e825ac1f0e7d2b4a6c8e0f1a2b3c4d5e6f7a8b9"
+
+[[package]]
+name = "can_api"
+version = "2.0.0"
+source = "git+https://github.com/vhrdtech/traits-ext#9f00d117c2d4e6f8a0b1c2d3e4f5a6b7c8d9e0f1"
+"#,
+        )
+        .unwrap();
+        let find = |package: &str, source: DepSource| {
+            lock.find_package(package, &source)?
+                .find_in_cargo_home(&cargo_home)
+        };
+
+        // locked version is used, not the highest one matching the requirement
+        let req = VersionReq::parse("0.2").unwrap();
+        let source = DepSource::Registry {
+            req: req.clone(),
+            index: None,
+        };
+        assert_eq!(
+            find("gpio_api", source),
+            Some(crates_io.join("gpio_api-0.2.1"))
+        );
+        let source = DepSource::Registry {
+            req: req.clone(),
+            index: Some("sparse+https://registry.example.com/index".into()),
+        };
+        assert_eq!(find("gpio_api", source), Some(alt.join("gpio_api-0.2.3")));
+        // another alternative registry, gpio_api is not locked from it
+        let source = DepSource::Registry {
+            req,
+            index: Some("sparse+https://other.example.com/index/".into()),
+        };
+        assert_eq!(find("gpio_api", source), None);
+        let source = DepSource::Registry {
+            req: VersionReq::parse("0.1").unwrap(),
+            index: None,
+        };
+        assert_eq!(find("gpio_api", source), None);
+
+        // only the directory of the locked registry is searched
+        let version = Version::new(0, 2, 3);
+        let crates_io_index = "https://github.com/rust-lang/crates.io-index";
+        assert_eq!(
+            find_registry_crate(&cargo_home, crates_io_index, "gpio_api", &version),
+            None
+        );
+
+        let url = "https://github.com/vhrdtech/traits.git";
+        let source = DepSource::Git {
+            url,
+            rev: Some("5e825ac"),
+        };
+        assert_eq!(
+            find("can_api", source),
+            Some(checkouts.join("5e825ac/can_api"))
+        );
+        let source = DepSource::Git { url, rev: None };
+        assert_eq!(
+            find("can_api", source),
+            Some(checkouts.join("5e825ac/can_api"))
+        );
+        let source = DepSource::Git {
+            url,
+            rev: Some("0000000"),
+        };
+        assert_eq!(find("can_api", source), None);
+        let source = DepSource::Git {
+            url: "https://github.com/vhrdtech/traits-ext",
+            rev: None,
+        };
+        assert_eq!(
+            find("can_api", source),
+            Some(ext_checkouts.join("9f00d11/can_api"))
+        );
+
+        // `traits` repository does not match `traits-ext-<hash>` checkouts
+        let commit = "9f00d117c2d4e6f8a0b1c2d3e4f5a6b7c8d9e0f1";
+        assert_eq!(find_git_crate(&cargo_home, "can_api", url, commit), None);
+
+        _ = fs::remove_dir_all(&cargo_home);
+    }
+
+    #[test]
+    fn registry_index_is_resolved_from_closest_config() {
+        let root = std::env::temp_dir().join(format!("ww_cargo_config_{}", std::process::id()));
+        _ = fs::remove_dir_all(&root);
+        write(
+            &root.join(".cargo/config.toml"),
+            "[registries.example]\nindex = \"sparse+https://registry.example.com/index/\"\n\n\
+            [registries.other]\nindex = \"https://other.example.com/index.git\"\n",
+        );
+        write(
+            &root.join("workspace/.cargo/config"),
+            "[registries.example]\nindex = \"sparse+https://mirror.example.com/index/\"\n",
+        );
+        let crate_path = root.join("workspace/api");
+
+        assert_eq!(
+            registry_index(&crate_path, "example").as_deref(),
+            Some("sparse+https://mirror.example.com/index/")
+        );
+        assert_eq!(
+            registry_index(&crate_path, "other").as_deref(),
+            Some("https://other.example.com/index.git")
+        );
+        assert_eq!(
+            registry_index(&crate_path, "ww-test-unknown-registry"),
+            None
+        );
+
+        _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,13 +1,16 @@
+use super::cargo_cache::{
+    CargoLock, CargoMetadata, DepSource, cargo_home, is_cargo_offline, registry_index,
+};
 use super::{api::convert_api_items, util::collect_docs};
 use anyhow::{Context, Result, anyhow};
 use cargo_toml::{Dependency, DepsSet, Inheritable, Manifest};
-use semver::Version;
+use semver::{Version, VersionReq};
 use shrink_wrap::UNib32;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use syn::{File, Item};
+use syn::{Expr, Item, ItemTrait, Lit, Meta, UseTree};
 use ww_self::{ApiBundleOwned, ApiLevelLocationOwned, ApiLevelOwned, TypeLocationOwned, TypeOwned};
 use ww_version::{FullVersionOwned, VersionOwned};
 
@@ -15,30 +18,46 @@ use ww_version::{FullVersionOwned, VersionOwned};
 ///
 /// This method will recursively walk (and download if necessary) all the referenced crates:
 /// 1. Load `crate_path/Cargo.toml` and parse into cargo_toml::[Manifest].
-/// 2. Load `crate_path/src/lib.rs` and parse into syn::[File].
+/// 2. Load `crate_path/src/lib.rs` and all the modules declared with `mod name;` (`name.rs`, `name/mod.rs` or
+///    `#[path = "file.rs"]`) and parse them into syn::[File]'s.
 /// 3. Find the trait marked with `#[ww_trait]` and named `trait_name`.
-///     3.1 If provided, otherwise the first and only `#[ww_api_root]` or `#[ww_trait]` is used or an error is returned.
+///    3.1 If provided, otherwise the first and only `#[ww_api_root]` or `#[ww_trait]` is used or an error is returned.
 /// 4. For each user-defined type referenced, find its definition:
-///     4.1 Defined in the same file
-///     4.2 Imported using `use another_crate::Ty`
-///         4.2.1 Load `another_crate` starting from step #1, skipping #3.
-///     4.3 Convert into [ww_self::Type]
+///    4.1 Defined in the same module
+///    4.2 Imported using `use crate::a::b::Ty`, `use super::Ty`, `pub use` re-exports or glob imports
+///    4.3 Referenced by path, e.g. `a::b::Ty` or `crate::a::Ty`
+///    4.4 Imported using `use another_crate::Ty` or referenced as `another_crate::Ty`
+///    4.4.1 Load `another_crate` starting from step #1, skipping #3.
+///    4.5 Convert into [ww_self::Type]
 /// 5. For each trait referenced via `ww_impl` do similar steps as for types.
 /// 6. Assemble all data into [ww_self::ApiBundle]
 /// 7. Cache in `~/.wire_weaver/crate_name-sha.ron` if not already.
 ///
-/// If `offline_mode` is true, no attempts to download any crates will be made. Only the ones already
-/// found in the local Cargo registry will be used, or an error is returned if they are not found.
+/// Dependencies are resolved as follows:
+/// * `path = ".."` and `dep.workspace = true` are loaded directly.
+/// * crates.io, alternative registry and git dependencies are resolved to the version or commit locked in Cargo.lock
+///   of `crate_path` (or its workspace) and looked up in the Cargo home (`~/.cargo/registry/src` and
+///   `~/.cargo/git/checkouts`). If not locked or not downloaded yet, `cargo metadata` of `crate_path` is used,
+///   which resolves and downloads them if needed.
+///
+/// If `offline_mode` is true or `CARGO_NET_OFFLINE=true` is set, no attempts to download any crates will be made.
+/// `cargo metadata --offline` is used instead, and an error is returned if a dependency is not downloaded.
 ///
 /// Limitations:
-/// * Only types and ww_trait's defined in `src/lib.rs` are supported.
-/// * Only crates.io and path dependencies are supported.
+/// * `#[cfg(..)]` attributes on modules and items are ignored.
+/// * Generated code refers to types and traits as `crate_name::Ty`, so the ones defined in submodules must be
+///   re-exported from the crate root.
 pub fn load(
     crate_path: &Path,
     trait_name: Option<String>,
-    _offline_mode: bool, // TODO: try CARGO_NET_OFFLINE instead
+    offline_mode: bool,
 ) -> Result<ApiBundleOwned> {
-    let mut scratch = Scratch::default();
+    let mut scratch = Scratch {
+        root_path: crate_path.to_path_buf(),
+        cargo_lock: CargoLock::find(crate_path)?,
+        offline: offline_mode || is_cargo_offline(),
+        ..Default::default()
+    };
     load_inner(crate_path, trait_name, &mut scratch)
 }
 
@@ -48,11 +67,16 @@ pub fn load_dep(
     dep_name: impl AsRef<str>,
     trait_name: Option<impl AsRef<str>>,
 ) -> Result<ApiBundleOwned> {
-    let mut scratch = Scratch::default();
     let current_crate_path = std::env::var("CARGO_MANIFEST_DIR").map_err(|_|
         anyhow!("CARGO_MANIFEST_DIR not set, cannot determine current crate path. Consider calling this method from a build.rs script.")
     )?;
     let current_crate_path = PathBuf::from(current_crate_path);
+    let mut scratch = Scratch {
+        root_path: current_crate_path.clone(),
+        cargo_lock: CargoLock::find(&current_crate_path)?,
+        offline: is_cargo_offline(),
+        ..Default::default()
+    };
     let current_crate_manifest = ManifestContext::load(&current_crate_path)?;
     let dep_manifest =
         current_crate_manifest.load_dependent_manifest(dep_name.as_ref(), &mut scratch)?;
@@ -69,14 +93,14 @@ fn load_inner(
     scratch: &mut Scratch,
 ) -> Result<ApiBundleOwned> {
     let entry = CrateContext::load(crate_path, scratch)?;
-    let trait_name = find_trait_if_none(trait_name, &entry.lib_rs_ast)?;
-    let item_trait = get_trait(&entry.lib_rs_ast, &trait_name)?;
-    let items = convert_api_items(item_trait, &entry, scratch)?;
+    let (api_module, item_trait) = find_trait(&entry, trait_name)?;
+    let item_trait = api_module.item_trait(item_trait);
+    let items = convert_api_items(item_trait, &api_module, scratch)?;
 
     let root = ApiLevelOwned {
         docs: collect_docs(&item_trait.attrs),
         crate_idx: 0.into(),
-        trait_name,
+        trait_name: item_trait.ident.to_string(),
         items,
     };
     Ok(ApiBundleOwned {
@@ -96,6 +120,14 @@ pub(crate) struct Scratch {
     crates: HashMap<FullVersionOwned, Rc<CrateContext>>,
     /// Cached manifests
     manifests: HashMap<PathBuf, Rc<ManifestContext>>,
+    /// Crate being loaded (or the current crate for `load_dep`), all dependencies are resolved as Cargo does for it
+    root_path: PathBuf,
+    /// Cargo.lock of the root crate or its workspace
+    cargo_lock: Option<CargoLock>,
+    /// Cached `cargo metadata` output of the root crate, loaded if a dependency is not locked or not downloaded
+    metadata: Option<Rc<CargoMetadata>>,
+    /// Run `cargo metadata` with `--offline`, only use crates already in the Cargo home
+    offline: bool,
     /// Scratch space with types and traits
     pub(crate) root_bundle: ApiBundleScratch,
 }
@@ -160,14 +192,51 @@ impl ApiBundleScratch {
     }
 }
 
+/// Crate being converted, together with one of its modules that is used to resolve names.
+#[derive(Clone)]
 pub(crate) struct CrateContext {
     manifest: Rc<ManifestContext>,
     version: FullVersionOwned,
-    pub(crate) lib_rs_ast: File,
-    // crate_path: PathBuf,
-    // lib_rs_source: String,
+    /// All the modules of a crate, starting from `src/lib.rs`
+    modules: Rc<Vec<Module>>,
+    /// Index into `modules`
+    module_idx: usize,
+    /// Concrete types in place of type parameters, while converting fields of a generic struct or enum instance
+    generic_args: Rc<Vec<(String, TypeOwned)>>,
+}
+
+/// File or inline module
+struct Module {
+    /// Empty for the crate root
+    name: String,
+    parent: Option<usize>,
+    /// File in which module items are located, inline modules point to the file they are declared in
+    file_path: PathBuf,
+    items: Vec<Item>,
+}
+
+/// Definition that a name or a path refers to.
+pub(crate) enum Resolved {
+    /// Item defined in the same crate, CrateContext points to the module with the definition.
+    Local(CrateContext, usize),
+    /// Item defined in another crate, `path` is relative to its root.
+    External {
+        crate_name: String,
+        path: Vec<String>,
+    },
 }
 
+/// What a `use` item brings into scope.
+enum UseEntry {
+    /// `use a::b::Name` or `use a::b::Other as Name`
+    Named { name: String, path: Vec<String> },
+    /// `use a::b::*`
+    Glob { path: Vec<String> },
+}
+
+/// Maximum number of `use` and re-export hops when resolving a name, guards against glob import cycles.
+const MAX_RESOLVE_DEPTH: usize = 32;
+
 /// Cargo.toml manifest together with its file path
 struct ManifestContext {
     crate_path: PathBuf,
@@ -188,12 +257,13 @@ impl CrateContext {
         if let Some(crate_cx) = scratch.crates.get(&version) {
             return Ok(crate_cx.clone());
         }
-        let lib_rs = load_lib_rs(&manifest.crate_path)?;
+        let modules = load_modules(&manifest.crate_path)?;
         let crate_cx = Self {
-            // crate_path: manifest.crate_path.to_path_buf(),
             manifest,
             version,
-            lib_rs_ast: lib_rs,
+            modules: Rc::new(modules),
+            module_idx: 0,
+            generic_args: Rc::new(vec![]),
         };
         scratch.root_bundle.find_crate_or_create(&crate_cx); // ensure crate name is in ext_crates
         Ok(Rc::new(crate_cx))
@@ -209,7 +279,166 @@ impl CrateContext {
     }
 
     pub(crate) fn err_context(&self) -> String {
-        format!("{:?}", self.version)
+        format!("{:?} {}", self.version, self.module().file_path.display())
+    }
+
+    pub(crate) fn item(&self, idx: usize) -> &Item {
+        &self.module().items[idx]
+    }
+
+    pub(crate) fn item_trait(&self, idx: usize) -> &ItemTrait {
+        match &self.module().items[idx] {
+            Item::Trait(item_trait) => item_trait,
+            _ => unreachable!("not a trait"),
+        }
+    }
+
+    fn module(&self) -> &Module {
+        &self.modules[self.module_idx]
+    }
+
+    fn in_module(&self, module_idx: usize) -> CrateContext {
+        CrateContext {
+            module_idx,
+            generic_args: Rc::new(vec![]),
+            ..self.clone()
+        }
+    }
+
+    /// Same module with type parameters of a generic item substituted with `generic_args`.
+    pub(crate) fn with_generic_args(&self, generic_args: Vec<(String, TypeOwned)>) -> CrateContext {
+        CrateContext {
+            generic_args: Rc::new(generic_args),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn generic_arg(&self, param: &str) -> Option<&TypeOwned> {
+        self.generic_args
+            .iter()
+            .find(|(name, _)| name == param)
+            .map(|(_, ty)| ty)
+    }
+
+    /// Find the definition of a struct, enum or trait referred to by `path` from the current module.
+    /// Follows child modules, `crate::`, `self::` and `super::` paths, `use` items (including `pub use` re-exports
+    /// and glob imports) within the crate. Paths starting with an unknown name are assumed to point into another crate.
+    pub(crate) fn resolve(&self, path: &[String]) -> Result<Option<Resolved>> {
+        self.resolve_in(self.module_idx, path, 0)
+    }
+
+    fn resolve_in(
+        &self,
+        module_idx: usize,
+        path: &[String],
+        depth: usize,
+    ) -> Result<Option<Resolved>> {
+        if depth > MAX_RESOLVE_DEPTH {
+            return Ok(None);
+        }
+        let Some((first, rest)) = path.split_first() else {
+            return Ok(None);
+        };
+        let module = &self.modules[module_idx];
+        if rest.is_empty() {
+            if let Some(idx) = module
+                .items
+                .iter()
+                .position(|item| item_ident(item).is_some_and(|ident| ident == first))
+            {
+                return Ok(Some(Resolved::Local(self.in_module(module_idx), idx)));
+            }
+        } else {
+            let next_module = match first.as_str() {
+                "crate" => Some(0),
+                "self" => Some(module_idx),
+                "super" => Some(
+                    module.parent.ok_or(
+                        anyhow!("super:: used at the crate root")
+                            .context(self.in_module(module_idx).err_context()),
+                    )?,
+                ),
+                name => self.child_module(module_idx, name),
+            };
+            if let Some(next_module) = next_module {
+                return self.resolve_in(next_module, rest, depth + 1);
+            }
+        }
+
+        let mut globs = vec![];
+        for entry in module.items.iter().flat_map(use_entries) {
+            match entry {
+                UseEntry::Named {
+                    name,
+                    path: use_path,
+                } if &name == first => {
+                    let mut full_path = use_path;
+                    full_path.extend_from_slice(rest);
+                    return self.resolve_use_path(module_idx, &full_path, depth + 1);
+                }
+                UseEntry::Glob { path } => globs.push(path),
+                _ => {}
+            }
+        }
+        // glob imports from other crates (e.g. `use wire_weaver::prelude::*`) are not followed
+        for glob in globs {
+            let Some(glob_module) = self.resolve_module(module_idx, &glob) else {
+                continue;
+            };
+            if let Some(resolved) = self.resolve_in(glob_module, path, depth + 1)? {
+                return Ok(Some(resolved));
+            }
+        }
+
+        if rest.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(Resolved::External {
+                crate_name: first.clone(),
+                path: rest.to_vec(),
+            }))
+        }
+    }
+
+    /// Paths in `use` items start with `crate`, `self`, `super`, a child module name or a crate name.
+    fn resolve_use_path(
+        &self,
+        module_idx: usize,
+        path: &[String],
+        depth: usize,
+    ) -> Result<Option<Resolved>> {
+        let Some((first, rest)) = path.split_first() else {
+            return Ok(None);
+        };
+        let is_local = matches!(first.as_str(), "crate" | "self" | "super")
+            || self.child_module(module_idx, first).is_some();
+        if is_local || rest.is_empty() {
+            self.resolve_in(module_idx, path, depth)
+        } else {
+            Ok(Some(Resolved::External {
+                crate_name: first.clone(),
+                path: rest.to_vec(),
+            }))
+        }
+    }
+
+    fn resolve_module(&self, module_idx: usize, path: &[String]) -> Option<usize> {
+        let mut idx = module_idx;
+        for (i, segment) in path.iter().enumerate() {
+            idx = match segment.as_str() {
+                "crate" if i == 0 => 0,
+                "self" if i == 0 => idx,
+                "super" => self.modules[idx].parent?,
+                name => self.child_module(idx, name)?,
+            };
+        }
+        Some(idx)
+    }
+
+    fn child_module(&self, module_idx: usize, name: &str) -> Option<usize> {
+        self.modules
+            .iter()
+            .position(|m| m.parent == Some(module_idx) && m.name == name)
     }
 }
 
@@ -222,6 +451,15 @@ impl Scratch {
         self.manifests.insert(crate_path, manifest_cx.clone());
         Ok(manifest_cx)
     }
+
+    fn get_or_load_metadata(&mut self) -> Result<Rc<CargoMetadata>> {
+        if let Some(metadata) = &self.metadata {
+            return Ok(metadata.clone());
+        }
+        let metadata = Rc::new(CargoMetadata::load(&self.root_path, self.offline)?);
+        self.metadata = Some(metadata.clone());
+        Ok(metadata)
+    }
 }
 
 impl ManifestContext {
@@ -304,9 +542,11 @@ impl ManifestContext {
                 .context(self.err_context()),
         )?;
         match dep {
-            Dependency::Simple(_version) => {
+            Dependency::Simple(version) => {
                 // crate_name = "version"
-                todo!()
+                let req = self.parse_version_req(crate_name, version)?;
+                let source = DepSource::Registry { req, index: None };
+                self.load_from_cargo_cache(crate_name, source, scratch)
             }
             Dependency::Inherited(_) => {
                 // crate_name.workspace = true
@@ -334,71 +574,328 @@ impl ManifestContext {
                     };
                     return Ok(scratch.get_or_load_manifest(dep_crate_path)?);
                 }
-                // crate_name = { git = "" }
-                todo!();
-                // crate_name = { version = "" }
-                // todo!();
+                // crate_name = { package = "actual_name", .. }
+                let package = detailed.package.as_deref().unwrap_or(crate_name);
+                let source = if let Some(url) = &detailed.git {
+                    // crate_name = { git = "" }
+                    DepSource::Git {
+                        url,
+                        rev: detailed.rev.as_deref(),
+                    }
+                } else if let Some(version) = &detailed.version {
+                    // crate_name = { version = "", registry = "" }
+                    let req = self.parse_version_req(crate_name, version)?;
+                    let index = match (&detailed.registry_index, &detailed.registry) {
+                        (Some(index), _) => Some(index.clone()),
+                        (None, Some(name)) => Some(self.registry_index(crate_name, name)?),
+                        (None, None) => None,
+                    };
+                    DepSource::Registry { req, index }
+                } else {
+                    return Err(anyhow!(
+                        "Dependency {crate_name} has no path, git or version specified"
+                    )
+                    .context(self.err_context()));
+                };
+                self.load_from_cargo_cache(package, source, scratch)
             }
         }
     }
 
+    /// Find a registry or git dependency locked in Cargo.lock in the Cargo home, or with `cargo metadata`.
+    fn load_from_cargo_cache(
+        &self,
+        package: &str,
+        source: DepSource,
+        scratch: &mut Scratch,
+    ) -> Result<Rc<ManifestContext>> {
+        let cached = scratch
+            .cargo_lock
+            .as_ref()
+            .and_then(|lock| lock.find_package(package, &source))
+            .zip(cargo_home())
+            .and_then(|(locked, cargo_home)| locked.find_in_cargo_home(&cargo_home));
+        if let Some(dep_crate_path) = cached {
+            return scratch.get_or_load_manifest(dep_crate_path);
+        }
+        let metadata = scratch.get_or_load_metadata().map_err(|e| {
+            if scratch.offline {
+                e.context(format!(
+                    "Dependency {package} is not found in the Cargo home and offline mode is enabled, \
+                    run `cargo fetch` or vendor it first"
+                ))
+            } else {
+                e
+            }
+        });
+        let metadata = metadata.context(self.err_context())?;
+        let dep_crate_path = metadata.find(package, &source).ok_or(
+            anyhow!("Dependency {package} is not found in cargo metadata output")
+                .context(self.err_context()),
+        )?;
+        scratch.get_or_load_manifest(dep_crate_path)
+    }
+
+    /// Index URL of an alternative registry, from Cargo config.
+    fn registry_index(&self, crate_name: &str, registry: &str) -> Result<String> {
+        registry_index(&self.crate_path, registry).ok_or(
+            anyhow!(
+                "Registry {registry} of dependency {crate_name} is not found in .cargo/config.toml"
+            )
+            .context(self.err_context()),
+        )
+    }
+
+    fn parse_version_req(&self, crate_name: &str, version: &str) -> Result<VersionReq> {
+        VersionReq::parse(version).context(format!(
+            "Failed to parse version requirement of {crate_name} in {}",
+            self.err_context()
+        ))
+    }
+
     fn err_context(&self) -> String {
         format!("{}/Cargo.toml", self.crate_path.display())
     }
 }
 
-fn load_lib_rs(crate_path: &Path) -> Result<File> {
-    let mut lib_rs_path = crate_path.canonicalize()?;
-    lib_rs_path.push("src");
-    lib_rs_path.push("lib.rs");
-    let contents = fs::read_to_string(&lib_rs_path).context(format!(
-        "Failed to read lib.rs from {}",
-        lib_rs_path.display()
-    ))?;
-    syn::parse_file(&contents).context(format!(
-        "Failed to parse lib.rs from {}",
-        lib_rs_path.display()
-    ))
+fn load_modules(crate_path: &Path) -> Result<Vec<Module>> {
+    let src_path = crate_path.canonicalize()?.join("src");
+    let lib_rs_path = src_path.join("lib.rs");
+    let items = parse_rs_file(&lib_rs_path)?;
+    let mut modules = vec![];
+    push_module(
+        &mut modules,
+        ModuleLocation {
+            name: String::new(),
+            parent: None,
+            file_path: lib_rs_path,
+            dir: src_path.clone(),
+            path_attr_dir: src_path,
+        },
+        items,
+    )?;
+    Ok(modules)
+}
+
+struct ModuleLocation {
+    name: String,
+    parent: Option<usize>,
+    file_path: PathBuf,
+    /// Directory with the files of child modules (`dir/child.rs` or `dir/child/mod.rs`)
+    dir: PathBuf,
+    /// Directory that `#[path = ".."]` attributes on child modules are relative to
+    path_attr_dir: PathBuf,
 }
 
-fn find_trait_if_none(trait_name: Option<String>, lib_rs: &File) -> Result<String> {
-    if let Some(name) = trait_name {
-        return Ok(name.to_string());
-    }
-    let mut ww_api_root = vec![];
-    let mut ww_trait = vec![];
-    for item in &lib_rs.items {
-        let Item::Trait(item_trait) = item else {
+fn push_module(
+    modules: &mut Vec<Module>,
+    location: ModuleLocation,
+    items: Vec<Item>,
+) -> Result<()> {
+    let idx = modules.len();
+    let child_mods: Vec<_> = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Mod(item_mod) => Some(item_mod.clone()),
+            _ => None,
+        })
+        .collect();
+    modules.push(Module {
+        name: location.name,
+        parent: location.parent,
+        file_path: location.file_path.clone(),
+        items,
+    });
+    for item_mod in child_mods {
+        let name = item_mod.ident.to_string();
+        let path_attr = get_path_attr(&item_mod.attrs);
+        if let Some((_, items)) = item_mod.content {
+            // mod name { .. }
+            let dir = match &path_attr {
+                Some(path) => location.path_attr_dir.join(path),
+                None => location.dir.join(&name),
+            };
+            let child = ModuleLocation {
+                name,
+                parent: Some(idx),
+                file_path: location.file_path.clone(),
+                path_attr_dir: dir.clone(),
+                dir,
+            };
+            push_module(modules, child, items)?;
             continue;
+        }
+        // mod name;
+        let file_path = if let Some(path) = &path_attr {
+            location.path_attr_dir.join(path)
+        } else {
+            let name_rs = location.dir.join(format!("{name}.rs"));
+            if name_rs.exists() {
+                name_rs
+            } else {
+                location.dir.join(&name).join("mod.rs")
+            }
+        };
+        // files loaded through #[path] and mod.rs files hold their children next to them, name.rs files in name/
+        let dir = if path_attr.is_some() || file_path.ends_with("mod.rs") {
+            file_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default()
+        } else {
+            location.dir.join(&name)
+        };
+        let items = parse_rs_file(&file_path).context(format!(
+            "mod {name} declared in {}",
+            location.file_path.display()
+        ))?;
+        let child = ModuleLocation {
+            name,
+            parent: Some(idx),
+            path_attr_dir: file_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            file_path,
+            dir,
         };
-        let attrs = &item_trait.attrs;
-        if attrs.iter().any(|attr| attr.path().is_ident("ww_api_root")) {
-            ww_api_root.push(item_trait.ident.to_string());
+        push_module(modules, child, items)?;
+    }
+    Ok(())
+}
+
+fn parse_rs_file(path: &Path) -> Result<Vec<Item>> {
+    let contents =
+        fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+    let file = syn::parse_file(&contents).context(format!("Failed to parse {}", path.display()))?;
+    Ok(file.items)
+}
+
+/// `#[path = "file.rs"]`
+fn get_path_attr(attrs: &[syn::Attribute]) -> Option<String> {
+    let attr = attrs.iter().find(|a| a.path().is_ident("path"))?;
+    if let Meta::NameValue(name_value) = &attr.meta
+        && let Expr::Lit(expr_lit) = &name_value.value
+        && let Lit::Str(lit_str) = &expr_lit.lit
+    {
+        Some(lit_str.value())
+    } else {
+        None
+    }
+}
+
+fn item_ident(item: &Item) -> Option<&syn::Ident> {
+    match item {
+        Item::Struct(item_struct) => Some(&item_struct.ident),
+        Item::Enum(item_enum) => Some(&item_enum.ident),
+        Item::Trait(item_trait) => Some(&item_trait.ident),
+        _ => None,
+    }
+}
+
+fn use_entries(item: &Item) -> Vec<UseEntry> {
+    let mut entries = vec![];
+    if let Item::Use(item_use) = item {
+        collect_use_entries(&item_use.tree, vec![], &mut entries);
+    }
+    entries
+}
+
+fn collect_use_entries(tree: &UseTree, prefix: Vec<String>, entries: &mut Vec<UseEntry>) {
+    match tree {
+        UseTree::Path(use_path) => {
+            let mut prefix = prefix;
+            prefix.push(use_path.ident.to_string());
+            collect_use_entries(&use_path.tree, prefix, entries);
         }
-        if attrs.iter().any(|attr| attr.path().is_ident("ww_trait")) {
-            ww_trait.push(item_trait.ident.to_string());
+        UseTree::Name(use_name) => {
+            // use a::b::{self} imports b
+            let (name, path) = if use_name.ident == "self" {
+                (prefix.last().cloned().unwrap_or_default(), prefix)
+            } else {
+                let mut path = prefix;
+                path.push(use_name.ident.to_string());
+                (use_name.ident.to_string(), path)
+            };
+            entries.push(UseEntry::Named { name, path });
+        }
+        UseTree::Rename(use_rename) => {
+            let mut path = prefix;
+            if use_rename.ident != "self" {
+                path.push(use_rename.ident.to_string());
+            }
+            entries.push(UseEntry::Named {
+                name: use_rename.rename.to_string(),
+                path,
+            });
+        }
+        UseTree::Glob(_) => entries.push(UseEntry::Glob { path: prefix }),
+        UseTree::Group(use_group) => {
+            for tree in &use_group.items {
+                collect_use_entries(tree, prefix.clone(), entries);
+            }
         }
-    }
-    match (ww_api_root.len(), ww_trait.len()) {
-        (0, 0) => Err(anyhow!(
-            "No #[ww_api_root] or #[ww_trait] marked traits found in lib.rs"
-        )),
-        (0, 1) => Ok(ww_trait[0].clone()),
-        (1, _) => Ok(ww_api_root[0].clone()),
-        _ => Err(anyhow!(
-            "Multiple #[ww_api_root] or #[ww_trait] marked traits found in lib.rs"
-        )),
     }
 }
 
-fn get_trait<'i>(lib_rs: &'i File, trait_name: &str) -> Result<&'i syn::ItemTrait> {
-    for item in &lib_rs.items {
-        let Item::Trait(item_trait) = item else {
-            continue;
-        };
-        if item_trait.ident == trait_name {
-            return Ok(item_trait);
+/// Find a trait with the provided name or the only one marked with `#[ww_api_root]` or `#[ww_trait]`, in any module.
+/// Returns module context and item index.
+fn find_trait(entry: &CrateContext, trait_name: Option<String>) -> Result<(CrateContext, usize)> {
+    let mut ww_api_root = vec![];
+    let mut ww_trait = vec![];
+    let mut named = vec![];
+    for (module_idx, module) in entry.modules.iter().enumerate() {
+        for (item_idx, item) in module.items.iter().enumerate() {
+            let Item::Trait(item_trait) = item else {
+                continue;
+            };
+            let location = (module_idx, item_idx);
+            if let Some(name) = &trait_name {
+                if item_trait.ident == name {
+                    named.push(location);
+                }
+                continue;
+            }
+            let attrs = &item_trait.attrs;
+            if attrs.iter().any(|attr| attr.path().is_ident("ww_api_root")) {
+                ww_api_root.push(location);
+            }
+            if attrs.iter().any(|attr| attr.path().is_ident("ww_trait")) {
+                ww_trait.push(location);
+            }
         }
     }
-    Err(anyhow!("Trait with name {} not found", trait_name))
+    let (module_idx, item_idx) = if let Some(name) = trait_name {
+        match named.len() {
+            0 => return Err(anyhow!("Trait with name {} not found", name)),
+            1 => named[0],
+            _ => {
+                // prefer the one reachable from the crate root
+                let Some(Resolved::Local(cx, item_idx)) =
+                    entry.resolve(std::slice::from_ref(&name))?
+                else {
+                    return Err(anyhow!(
+                        "Multiple traits with name {name} found, re-export one of them from the crate root"
+                    ));
+                };
+                (cx.module_idx, item_idx)
+            }
+        }
+    } else {
+        match (ww_api_root.len(), ww_trait.len()) {
+            (0, 0) => {
+                return Err(anyhow!(
+                    "No #[ww_api_root] or #[ww_trait] marked traits found"
+                ));
+            }
+            (0, 1) => ww_trait[0],
+            (1, _) => ww_api_root[0],
+            _ => {
+                return Err(anyhow!(
+                    "Multiple #[ww_api_root] or #[ww_trait] marked traits found"
+                ));
+            }
+        }
+    };
+    Ok((entry.in_module(module_idx), item_idx))
 }
@@ -1,4 +1,5 @@
 mod api;
+mod cargo_cache;
 mod crate_walker;
 mod ty;
 mod util;
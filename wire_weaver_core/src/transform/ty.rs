@@ -1,17 +1,17 @@
 use super::{
-    crate_walker::{CrateContext, Scratch},
-    util::{collect_docs, get_since_attr, use_tree_has_type},
+    crate_walker::{CrateContext, Resolved, Scratch},
+    util::{collect_docs, get_deprecated_attr, get_since_attr},
 };
 use anyhow::{anyhow, Context, Result};
 use shrink_wrap::{ElementSize, UNib32};
 use syn::{
-    parse_str, Attribute, Expr, Fields, GenericArgument, Item, ItemEnum, ItemStruct, Lit, Meta,
-    PathArguments, PathSegment, Type, TypePath, UseTree,
+    parse_str, Attribute, Expr, Fields, GenericArgument, Generics, Item, ItemEnum, ItemStruct, Lit,
+    Meta, PathArguments, PathSegment, Type, TypeParam, TypePath,
 };
 use ww_numeric::{IBits, NumericAnyTypeOwned, UBits};
 use ww_self::{
-    FieldOwned, FieldsOwned, ItemEnumOwned, ItemStructOwned, NumericBaseType, Repr, TypeOwned,
-    ValueOwned, VariantOwned,
+    FieldOwned, FieldsOwned, GenericInstanceOwned, ItemEnumOwned, ItemStructOwned, NumericBaseType,
+    Repr, TypeOwned, ValueOwned, VariantOwned,
 };
 
 pub(crate) fn convert_ty(
@@ -60,19 +60,48 @@ pub(crate) fn convert_ty_path(
     current_crate: &CrateContext,
     scratch: &mut Scratch,
 ) -> Result<TypeOwned> {
-    let len = ty_path.path.segments.len();
-    if len == 1 {
-        let segment = &ty_path.path.segments[0];
-        convert_ty_path_segment(segment, current_crate, scratch)
-    } else if len == 2 {
-        let dep_crate_name = ty_path.path.segments[0].ident.to_string();
-        let dependent_crate = current_crate.load_dependent_crate(&dep_crate_name, scratch)?;
-        let segment = &ty_path.path.segments[1];
-        convert_ty_path_segment(segment, &dependent_crate, scratch)
+    let segments = &ty_path.path.segments;
+    if segments.len() == 1 {
+        convert_ty_path_segment(&segments[0], current_crate, scratch)
     } else {
-        Err(anyhow!(
-            "Only support `MyType` and `ext_crate::MyType` for now"
-        ))
+        let path: Vec<String> = segments.iter().map(|s| s.ident.to_string()).collect();
+        let args = convert_generic_args(segments.last().unwrap(), current_crate, scratch)?;
+        convert_user_ty(&path, args, current_crate, scratch)
+    }
+}
+
+/// Find a struct or enum definition referred to by `path` from the current module and convert it.
+/// Generic types are converted into an instance with type parameters substituted with `args`.
+fn convert_user_ty(
+    path: &[String],
+    args: Vec<TypeOwned>,
+    current_crate: &CrateContext,
+    scratch: &mut Scratch,
+) -> Result<TypeOwned> {
+    match current_crate.resolve(path)? {
+        Some(Resolved::Local(cx, item_idx)) => {
+            match cx.item(item_idx) {
+                Item::Enum(item_enum) => {
+                    convert_item_enum(&cx, scratch, item_enum.ident.to_string(), item_enum, args)
+                }
+                Item::Struct(item_struct) => convert_item_struct(
+                    &cx,
+                    scratch,
+                    item_struct.ident.to_string(),
+                    item_struct,
+                    args,
+                ),
+                _ => Err(anyhow!("{} is not a struct or enum", path.join("::"))
+                    .context(cx.err_context())),
+            }
+        }
+        Some(Resolved::External { crate_name, path }) => {
+            let dependent_crate = current_crate.load_dependent_crate(&crate_name, scratch)?;
+            convert_user_ty(&path, args, &dependent_crate, scratch)
+        }
+        None => {
+            Err(anyhow!("Type {} not found", path.join("::")).context(current_crate.err_context()))
+        }
     }
 }
 
@@ -82,6 +111,11 @@ pub(crate) fn convert_ty_path_segment(
     scratch: &mut Scratch,
 ) -> Result<TypeOwned> {
     let ty_name = segment.ident.to_string();
+    if segment.arguments.is_none()
+        && let Some(ty) = current_crate.generic_arg(&ty_name)
+    {
+        return Ok(ty.clone());
+    }
     match ty_name.as_str() {
         "bool" => Ok(TypeOwned::Bool),
         "Nibble" | "nib" => Ok(numeric_base(NumericBaseType::Nibble)),
@@ -109,6 +143,8 @@ pub(crate) fn convert_ty_path_segment(
         "ILeb128" | "ileb128" => Ok(numeric_base(NumericBaseType::ILeb128)),
         "String" | "str" => Ok(TypeOwned::String),
         "Vec" | "RefVec" => convert_ty_vec(segment, current_crate, scratch),
+        "BoundedVec" => convert_ty_bounded_vec(segment, current_crate, scratch),
+        "BoundedString" => convert_ty_bounded_string(segment),
         "Option" => convert_ty_option(segment, current_crate, scratch),
         "Result" => convert_ty_result(segment, current_crate, scratch),
         "Range" => convert_ty_range(segment, current_crate, scratch),
@@ -119,34 +155,83 @@ pub(crate) fn convert_ty_path_segment(
                 return Ok(ty);
             }
 
-            for item in &current_crate.lib_rs_ast.items {
-                match item {
-                    Item::Enum(item_enum) if item_enum.ident == user_ty => {
-                        return convert_item_enum(current_crate, scratch, ty_name, &item_enum);
-                    }
-                    Item::Struct(item_struct) if item_struct.ident == user_ty => {
-                        return convert_item_struct(current_crate, scratch, ty_name, &item_struct);
-                    }
-                    Item::Use(item_use) => {
-                        if !use_tree_has_type(&item_use.tree, user_ty) {
-                            continue;
-                        }
-                        let UseTree::Path(use_path) = &item_use.tree else {
-                            continue;
-                        };
-                        // only supporting `use ext_crate::Type` for now
-                        let dep_crate_name = use_path.ident.to_string();
-                        let dependent_crate =
-                            current_crate.load_dependent_crate(&dep_crate_name, scratch)?;
-                        let ty: Type = parse_str(&format!("{user_ty}"))?;
-                        return convert_ty(&ty, &dependent_crate, scratch);
-                    }
-                    _ => {}
-                }
+            let args = convert_generic_args(segment, current_crate, scratch)?;
+            convert_user_ty(std::slice::from_ref(&ty_name), args, current_crate, scratch)
+        }
+    }
+}
+
+/// Convert type arguments of a user-defined type where it is used, lifetimes are skipped.
+fn convert_generic_args(
+    segment: &PathSegment,
+    current_crate: &CrateContext,
+    scratch: &mut Scratch,
+) -> Result<Vec<TypeOwned>> {
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return Ok(vec![]);
+    };
+    let mut types = vec![];
+    for arg in &args.args {
+        match arg {
+            GenericArgument::Lifetime(_) => {}
+            GenericArgument::Type(ty) => types.push(convert_ty(ty, current_crate, scratch)?),
+            u => {
+                return Err(anyhow!(
+                    "only lifetimes and types are supported as generic arguments, got {u:?}"
+                )
+                .context(current_crate.err_context()));
             }
-            Err(anyhow!("Type {segment:?} not found").context(current_crate.err_context()))
         }
     }
+    Ok(types)
+}
+
+/// Pair type parameters of a generic struct or enum with the arguments it is used with. Returned context is used to
+/// convert fields of the instance, so that the parameters are replaced with concrete types.
+fn generic_instance(
+    current_crate: &CrateContext,
+    scratch: &mut Scratch,
+    ty_name: &str,
+    generics: &Generics,
+    args: Vec<TypeOwned>,
+) -> Result<(CrateContext, Option<GenericInstanceOwned>)> {
+    let params: Vec<&TypeParam> = generics.type_params().collect();
+    if params.is_empty() && args.is_empty() {
+        return Ok((current_crate.clone(), None));
+    }
+    if args.len() > params.len() {
+        return Err(anyhow!(
+            "{ty_name} has {} type parameters, but is used with {} arguments",
+            params.len(),
+            args.len()
+        )
+        .context(current_crate.err_context()));
+    }
+    let mut args = args.into_iter();
+    let mut substituted = vec![];
+    for param in params {
+        let ty = match (args.next(), &param.default) {
+            (Some(ty), _) => ty,
+            // default can refer to the preceding parameters
+            (None, Some(default)) => {
+                let cx = current_crate.with_generic_args(substituted.clone());
+                convert_ty(default, &cx, scratch)?
+            }
+            (None, None) => {
+                return Err(
+                    anyhow!("{ty_name}: missing type argument for {}", param.ident)
+                        .context(current_crate.err_context()),
+                );
+            }
+        };
+        substituted.push((param.ident.to_string(), ty));
+    }
+    let instance = GenericInstanceOwned {
+        params: substituted.iter().map(|(name, _)| name.clone()).collect(),
+        args: substituted.iter().map(|(_, ty)| ty.clone()).collect(),
+        lifetime: generics.lifetimes().next().is_some(),
+    };
+    Ok((current_crate.with_generic_args(substituted), Some(instance)))
 }
 
 fn convert_ub_ib(user_ty: &str) -> Option<TypeOwned> {
@@ -181,11 +266,14 @@ fn convert_item_enum(
     scratch: &mut Scratch,
     ty_name: String,
     item_enum: &ItemEnum,
+    args: Vec<TypeOwned>,
 ) -> Result<TypeOwned> {
+    let (cx, generics) =
+        generic_instance(current_crate, scratch, &ty_name, &item_enum.generics, args)?;
     let mut variants = vec![];
     let mut discriminant = 0;
     for variant in &item_enum.variants {
-        let fields = convert_fields(&variant.fields, current_crate, scratch)?;
+        let fields = convert_fields(&variant.fields, &cx, scratch)?;
         if let Some((_, explicit_discriminant)) = &variant.discriminant {
             if let Expr::Lit(expr_lit) = explicit_discriminant
                 && let Lit::Int(lit_int) = &expr_lit.lit
@@ -200,12 +288,14 @@ fn convert_item_enum(
             }
         }
         let since = get_since_attr(&variant.attrs, current_crate)?;
+        let deprecated = get_deprecated_attr(&variant.attrs, current_crate)?;
         variants.push(VariantOwned {
             docs: collect_docs(&variant.attrs),
             ident: variant.ident.to_string(),
             fields,
             discriminant: UNib32(discriminant),
             since,
+            deprecated,
         });
         discriminant += 1;
     }
@@ -218,6 +308,7 @@ fn convert_item_enum(
         docs: collect_docs(&item_enum.attrs),
         ident: ty_name,
         variants,
+        generics,
     });
     if let Some(type_idx) = scratch.root_bundle.find_type(&ty) {
         return Ok(TypeOwned::OutOfLine { type_idx });
@@ -232,8 +323,16 @@ fn convert_item_struct(
     scratch: &mut Scratch,
     ty_name: String,
     item_struct: &ItemStruct,
+    args: Vec<TypeOwned>,
 ) -> Result<TypeOwned> {
-    let fields = convert_fields(&item_struct.fields, current_crate, scratch)?;
+    let (cx, generics) = generic_instance(
+        current_crate,
+        scratch,
+        &ty_name,
+        &item_struct.generics,
+        args,
+    )?;
+    let fields = convert_fields(&item_struct.fields, &cx, scratch)?;
     let size = get_size_assumption(&item_struct.attrs);
     let ty = TypeOwned::Struct(ItemStructOwned {
         size,
@@ -241,6 +340,7 @@ fn convert_item_struct(
         docs: collect_docs(&item_struct.attrs),
         ident: ty_name,
         fields,
+        generics,
     });
     if let Some(type_idx) = scratch.root_bundle.find_type(&ty) {
         return Ok(TypeOwned::OutOfLine { type_idx });
@@ -261,12 +361,14 @@ fn convert_fields(
             for f in fields {
                 let since = get_since_attr(&f.attrs, current_crate)?;
                 let default = get_default_attr(&f.attrs, current_crate)?;
+                let deprecated = get_deprecated_attr(&f.attrs, current_crate)?;
                 owned.push(FieldOwned {
                     docs: collect_docs(&f.attrs),
                     ident: f.ident.as_ref().map(|i| i.to_string()),
                     ty: convert_ty(&f.ty, current_crate, scratch)?,
                     default,
                     since,
+                    deprecated,
                 });
             }
             if matches!(fields, Fields::Unnamed(_)) {
@@ -289,6 +391,53 @@ fn convert_ty_vec(
     Ok(TypeOwned::Vec(Box::new(inner_ty)))
 }
 
+fn convert_ty_bounded_vec(
+    segment: &PathSegment,
+    current_crate: &CrateContext,
+    scratch: &mut Scratch,
+) -> Result<TypeOwned> {
+    let PathArguments::AngleBracketed(arg) = &segment.arguments else {
+        return Err(anyhow!(
+            "expected BoundedVec<T, N>, got BoundedVec or BoundedVec()"
+        ));
+    };
+    let mut args = arg.args.iter();
+    let (Some(GenericArgument::Type(inner_ty)), Some(len_arg)) = (args.next(), args.next()) else {
+        return Err(anyhow!("expected BoundedVec<T, N>, got {arg:?}"));
+    };
+    let inner_ty = convert_ty(inner_ty, current_crate, scratch)?;
+    Ok(TypeOwned::BoundedVec {
+        max_len: UNib32(const_generic_len(len_arg)?),
+        ty: Box::new(inner_ty),
+    })
+}
+
+fn convert_ty_bounded_string(segment: &PathSegment) -> Result<TypeOwned> {
+    let PathArguments::AngleBracketed(arg) = &segment.arguments else {
+        return Err(anyhow!(
+            "expected BoundedString<N>, got BoundedString or BoundedString()"
+        ));
+    };
+    let Some(len_arg) = arg.args.first() else {
+        return Err(anyhow!("expected BoundedString<N>"));
+    };
+    Ok(TypeOwned::BoundedString {
+        max_len: UNib32(const_generic_len(len_arg)?),
+    })
+}
+
+fn const_generic_len(arg: &GenericArgument) -> Result<u32> {
+    let GenericArgument::Const(Expr::Lit(lit)) = arg else {
+        return Err(anyhow!(
+            "only literals supported as max length, got {arg:?}"
+        ));
+    };
+    let Lit::Int(lit_int) = &lit.lit else {
+        return Err(anyhow!("only integers supported as max length"));
+    };
+    lit_int.base10_parse().context("parsing max length")
+}
+
 fn convert_ty_option(
     segment: &PathSegment,
     current_crate: &CrateContext,
@@ -1,7 +1,8 @@
 use super::crate_walker::CrateContext;
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result, anyhow};
 use semver::Version;
-use syn::{Attribute, Expr, Lit, Meta, UseTree};
+use syn::{Attribute, Expr, Lit, LitStr, Meta};
+use ww_self::DeprecatedOwned;
 use ww_version::VersionTriplet;
 
 pub(crate) fn collect_docs(attrs: &[Attribute]) -> Vec<String> {
@@ -57,19 +58,51 @@ pub(crate) fn get_since_attr(
     }
 }
 
-pub(crate) fn use_tree_has_type(tree: &UseTree, type_name: &str) -> bool {
-    match tree {
-        UseTree::Path(use_path) => use_tree_has_type(&use_path.tree, type_name),
-        UseTree::Name(use_name) => use_name.ident == type_name,
-        UseTree::Rename(_) => false,
-        UseTree::Glob(_) => false,
-        UseTree::Group(use_group) => {
-            for item in &use_group.items {
-                if use_tree_has_type(item, type_name) {
-                    return true;
-                }
+/// `#[deprecated]`, `#[deprecated = "note"]` or `#[deprecated(since = "x.y.z", note = "...")]`.
+pub(crate) fn get_deprecated_attr(
+    attrs: &[Attribute],
+    current_crate: &CrateContext,
+) -> Result<Option<DeprecatedOwned>> {
+    let Some(attr) = attrs.iter().find(|a| a.path().is_ident("deprecated")) else {
+        return Ok(None);
+    };
+    let mut deprecated = DeprecatedOwned {
+        since: None,
+        note: None,
+    };
+    match &attr.meta {
+        Meta::Path(_) => {}
+        Meta::NameValue(name_value) => {
+            if let Expr::Lit(expr_lit) = &name_value.value
+                && let Lit::Str(lit_str) = &expr_lit.lit
+            {
+                deprecated.note = Some(lit_str.value());
+            } else {
+                return Err(anyhow!("expected #[deprecated = \"note\"]")
+                    .context(current_crate.err_context()));
             }
-            false
+        }
+        Meta::List(_) => {
+            attr.parse_nested_meta(|meta| {
+                let value: LitStr = meta.value()?.parse()?;
+                if meta.path.is_ident("since") {
+                    let version = Version::parse(&value.value())
+                        .map_err(|e| meta.error(format!("since must be x.y.z: {e}")))?;
+                    deprecated.since = Some(VersionTriplet::new(
+                        version.major as u32,
+                        version.minor as u32,
+                        version.patch as u32,
+                    ));
+                } else if meta.path.is_ident("note") {
+                    deprecated.note = Some(value.value());
+                } else {
+                    return Err(meta.error("expected since or note"));
+                }
+                Ok(())
+            })
+            .context("expected #[deprecated(since = \"x.y.z\", note = \"...\")]")
+            .context(current_crate.err_context())?;
         }
     }
+    Ok(Some(deprecated))
 }
@@ -27,9 +27,12 @@ pub fn ww_api(args: TokenStream) -> TokenStream {
 /// * server = true/false - whether to generate server code or not.
 /// * no_alloc = true/false - whether to use std types or RefVec for strings, vectors. Lifetime will be added automatically if no_alloc = true.
 /// * use_async - whether to generate async-aware code.
+/// * handler_traits = true/false - generate a Rust trait per API level with default handlers and a generic dispatcher,
+///   instead of expecting handlers as inherent methods of the server struct (see wire_weaver_core::gen_server).
 /// * debug_to_file = "path to an output file" - save generated code to a file for debug purposes.
 /// * derive = "A, B, C" - put additional derives on generated types definitions.
 /// * method_model = "move_*=deferred, rotate_*=deferred, _=immediate" - list of comma separated regex expressions and deferred or immediate keywords.
+///   Regexes are matched against method names prefixed with nested level names (e.g. `gpio_set`), also with handler_traits = true.
 ///   Deferred methods can answer right away or later with a provided request id.
 ///   Immediate methods have to answer right away and ideally do not block.
 ///   Underscore captures all the unmatched methods.
@@ -37,6 +40,7 @@ pub fn ww_api(args: TokenStream) -> TokenStream {
 ///   Depending on the application, it might be more convenient to store property directly as a context struct member and
 ///   use value_on_changed, so that generated code directly reads and writes to it. Notification method is called when the value is changed.
 ///   In other cases, get_set is more useful, allowing to represent GPIO pin as a bool property, for example.
+///   Not supported with handler_traits = true, properties always use get and set handlers then.
 #[proc_macro]
 pub fn ww_codegen(args: TokenStream) -> TokenStream {
     let args = parse_macro_input!(args as ww_impl_args::ApiArgs);
@@ -54,7 +58,7 @@ pub fn ww_impl(args: TokenStream) -> TokenStream {
 
 /// Define a ww_trait, this macro is only a marker and produces no Rust code. All the work is done inside ww_impl! macro, which
 /// loads the appropriate .rs file again through a file system or from crates.io, finds this marker and parses the trait definition.
-/// TODO: transform ww_trait into valid Rust trait?
+/// Server side Rust traits can be generated from it with `handler_traits = true`, see [ww_codegen].
 /// TODO: emit unit constant to check for name collisions
 ///
 /// Example:
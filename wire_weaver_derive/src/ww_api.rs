@@ -28,6 +28,12 @@ fn api_inner(args: ApiArgs) -> Result<TokenStream, String> {
     let api_bundle = load_dep(args.dep_name.to_string(), Some(args.trait_name.to_string()))
         .map_err(|e| format!("{e:?}"))?;
 
+    if args.ext.handler_traits && !args.ext.property_model.is_empty() {
+        return Err(
+            "property_model is not supported with handler_traits = true, properties always use get and set handlers"
+                .into(),
+        );
+    }
     let property_model = if args.ext.property_model.is_empty() {
         PropertyModel {
             default: Some(PropertyModelKind::GetSet),
@@ -70,6 +76,7 @@ fn api_inner(args: ApiArgs) -> Result<TokenStream, String> {
                 property_model,
                 server_struct_path: args.context_ident.clone(),
                 generate_introspect: args.ext.introspect,
+                handler_traits: args.ext.handler_traits,
             },
         );
         codegen_ts.append_all(ts);
@@ -37,6 +37,9 @@ pub(crate) struct ImplExtArgs {
 
     #[darling(default)]
     pub(crate) introspect: bool,
+
+    #[darling(default)]
+    pub(crate) handler_traits: bool,
 }
 
 impl Parse for ApiArgs {
@@ -0,0 +1,18 @@
+//! Headless mock device server, generated at runtime from an `ApiBundleOwned`.
+//!
+//! Answers every method, property and stream of an API using default values, scripted responses (RON or JSON file)
+//! or a user callback. Reachable through the in-process [Command](wire_weaver_client_common::Command) channel
+//! ([mock_worker]) or over WebSocket ([serve_ws]), so that host software can be developed and tested before hardware exists.
+//!
+//! API can be loaded from a crate source ([MockServer::from_crate]) or from the local registry
+//! (`~/.wire_weaver/*.ron`, see [find_in_registry] and [MockServer::from_ron_file]).
+
+mod script;
+mod server;
+mod worker;
+mod ws;
+
+pub use script::{MockResponse, Script, ScriptedResponse, ScriptedStream};
+pub use server::{MockRequest, MockRequestKind, MockServer, find_in_registry};
+pub use worker::mock_worker;
+pub use ws::serve_ws;
@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use ww_self::ValueOwned;
+
+/// Prerecorded answers, loaded from a RON or JSON file.
+///
+/// Resource paths are written with names, separated by `/`, e.g. `gpio[1]/set_level`.
+/// Array indices can be omitted or replaced with `[*]` to match any element: `gpio/set_level` or `gpio[*]/set_level`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Script {
+    pub responses: Vec<ScriptedResponse>,
+    pub streams: Vec<ScriptedStream>,
+}
+
+/// Responses to a method call or property read, given out in order, the last one is then repeated.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptedResponse {
+    pub path: String,
+    pub responses: Vec<MockResponse>,
+}
+
+/// Values sent after a stream is opened by a client.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptedStream {
+    pub path: String,
+    pub values: Vec<ValueOwned>,
+    /// Delay between consecutive values
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    /// Start from the first value again after the last one was sent
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+fn default_interval_ms() -> u64 {
+    100
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MockResponse {
+    /// Method return value or property value
+    Value(ValueOwned),
+    /// Respond with `ErrorKind::UserStr`
+    Error(String),
+    /// Do not respond at all, e.g., to test timeouts on a client side
+    Ignore,
+}
+
+impl Script {
+    /// Load script from a file, JSON is used if file extension is `.json` and RON otherwise.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading script {}", path.display()))?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json_str(&contents)
+        } else {
+            Self::from_ron_str(&contents)
+        }
+    }
+
+    pub fn from_ron_str(s: &str) -> Result<Self> {
+        Ok(ron::from_str(s)?)
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    pub(crate) fn response(&self, path: &str) -> Option<&ScriptedResponse> {
+        self.responses.iter().find(|r| path_matches(&r.path, path))
+    }
+
+    pub(crate) fn stream(&self, path: &str) -> Option<&ScriptedStream> {
+        self.streams.iter().find(|s| path_matches(&s.path, path))
+    }
+}
+
+/// Match `gpio[1]/set_level` against `gpio[1]/set_level`, `gpio[*]/set_level` or `gpio/set_level`.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern_segments = pattern.trim_matches('/').split('/');
+    let mut path_segments = path.split('/');
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            (None, None) => return true,
+            (Some(pattern), Some(segment)) => {
+                let (pattern_name, pattern_idx) = split_index(pattern);
+                let (name, idx) = split_index(segment);
+                if pattern_name != name {
+                    return false;
+                }
+                match pattern_idx {
+                    None | Some("*") => {}
+                    Some(pattern_idx) => {
+                        if Some(pattern_idx) != idx {
+                            return false;
+                        }
+                    }
+                }
+            }
+            _ => return false,
+        }
+    }
+}
+
+fn split_index(segment: &str) -> (&str, Option<&str>) {
+    match segment.split_once('[') {
+        Some((name, idx)) => (name, Some(idx.trim_end_matches(']'))),
+        None => (segment, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::path_matches;
+
+    #[test]
+    fn array_index_patterns() {
+        assert!(path_matches("gpio[1]/set_level", "gpio[1]/set_level"));
+        assert!(path_matches("gpio[*]/set_level", "gpio[1]/set_level"));
+        assert!(path_matches("gpio/set_level", "gpio[1]/set_level"));
+        assert!(path_matches("/plain_return", "plain_return"));
+        assert!(!path_matches("gpio[2]/set_level", "gpio[1]/set_level"));
+        assert!(!path_matches("gpio", "gpio[1]/set_level"));
+        assert!(!path_matches("gpio[1]/set_level", "gpio[1]"));
+    }
+}
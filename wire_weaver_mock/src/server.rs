@@ -0,0 +1,603 @@
+use crate::script::{MockResponse, Script, ScriptedStream};
+use anyhow::{Context, Result, anyhow};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{debug, trace, warn};
+use wire_weaver::shrink_wrap::{
+    self, DeserializeShrinkWrap, ElementSize, RefVec, SerializeShrinkWrap, UNib32,
+};
+use ww_client_server::{
+    Error, ErrorKind, Event, EventKind, PathKind, Request, RequestKind, StreamSidebandCommand,
+    StreamSidebandEvent,
+};
+use ww_numeric::NumericValue;
+use ww_self::{
+    ApiBundleOwned, ApiItemKindOwned, FieldOwned, FieldsOwned, FieldsValueOwned, ItemStructOwned,
+    PropertyAccess, TypeOwned, ValueOwned,
+};
+use ww_version::FullVersionOwned;
+
+/// Same chunk size as used by generated servers.
+const INTROSPECT_CHUNK_LEN: usize = 128;
+
+/// Request as seen by a [MockServer::on_request] callback, with arguments and values already deserialized.
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    /// Resource path with names, e.g. `gpio[1]/set_level`
+    pub path: String,
+    /// Resource path as sent by a client
+    pub path_ids: Vec<u32>,
+    pub kind: MockRequestKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum MockRequestKind {
+    Call { args: Vec<(String, ValueOwned)> },
+    Read,
+    Write { value: ValueOwned },
+    StreamWrite { value: ValueOwned },
+    StreamSideband(StreamSidebandCommand),
+}
+
+type OnRequest = Box<dyn FnMut(&MockRequest) -> Option<MockResponse> + Send>;
+
+/// Headless mock of a device, answering requests using only an [ApiBundleOwned].
+///
+/// Responses are picked in the following order:
+/// * user callback, see [on_request](Self::on_request), if it returns Some;
+/// * scripted response for a resource path, see [Script];
+/// * last written value (properties only);
+/// * [ValueOwned::default] of the return or property type.
+///
+/// Transport agnostic, see [mock_worker](crate::mock_worker) and [serve_ws](crate::serve_ws).
+pub struct MockServer {
+    api_bundle: ApiBundleOwned,
+    ww_self_bytes: Vec<u8>,
+    script: Script,
+    /// How many scripted responses were already given out for each path
+    script_progress: HashMap<String, usize>,
+    properties: HashMap<Vec<u32>, ValueOwned>,
+    streams: Vec<OpenStream>,
+    on_request: Option<OnRequest>,
+}
+
+struct OpenStream {
+    path_ids: Vec<u32>,
+    ty: TypeOwned,
+    stream: ScriptedStream,
+    next_idx: usize,
+    next_due: Instant,
+}
+
+struct ResolvedItem {
+    path: String,
+    kind: ApiItemKindOwned,
+}
+
+impl MockServer {
+    pub fn new(api_bundle: ApiBundleOwned) -> Result<Self> {
+        let ww_self_bytes = to_ww_vec(&api_bundle)?;
+        Ok(MockServer {
+            api_bundle,
+            ww_self_bytes,
+            script: Script::default(),
+            script_progress: HashMap::new(),
+            properties: HashMap::new(),
+            streams: vec![],
+            on_request: None,
+        })
+    }
+
+    /// Load API from a crate source, see `wire_weaver_core::load`.
+    pub fn from_crate(crate_path: impl AsRef<Path>, trait_name: Option<String>) -> Result<Self> {
+        let api_bundle = wire_weaver_core::load(crate_path.as_ref(), trait_name, true)?;
+        Self::new(api_bundle)
+    }
+
+    /// Load API from a RON file, as stored in the local registry (`~/.wire_weaver`).
+    pub fn from_ron_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading API bundle {}", path.display()))?;
+        let api_bundle: ApiBundleOwned = ron::from_str(&contents)?;
+        Self::new(api_bundle)
+    }
+
+    pub fn with_script(mut self, script: Script) -> Self {
+        self.script = script;
+        self.script_progress.clear();
+        self
+    }
+
+    /// Called for every request to a method, property or stream, before scripted or default response is picked.
+    /// Return None to fall back to those.
+    pub fn on_request(
+        mut self,
+        f: impl FnMut(&MockRequest) -> Option<MockResponse> + Send + 'static,
+    ) -> Self {
+        self.on_request = Some(Box::new(f));
+        self
+    }
+
+    pub fn api_bundle(&self) -> &ApiBundleOwned {
+        &self.api_bundle
+    }
+
+    /// Crate name and version of the root API level.
+    pub fn user_api_version(&self) -> Result<&FullVersionOwned> {
+        self.api_bundle
+            .crate_version(self.api_bundle.root.crate_idx.0)
+    }
+
+    /// Process one serialized [Request] and return serialized [Event]s to be sent back, if any.
+    pub fn process_request(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let request = match Request::from_ww_bytes(bytes) {
+            Ok(r) => r,
+            Err(e) => {
+                warn!("failed to deserialize request: {e:?}");
+                return vec![];
+            }
+        };
+        trace!("{request:?}");
+        let seq = request.seq;
+        let PathKind::Absolute { path } = &request.path_kind else {
+            return err_event(seq, ErrorKind::PathKindNotSupported)
+                .into_iter()
+                .collect();
+        };
+        let Ok(path_ids) = path
+            .iter()
+            .map(|id| id.map(|id| id.0))
+            .collect::<Result<Vec<u32>, _>>()
+        else {
+            return err_event(seq, ErrorKind::PathDesFailed)
+                .into_iter()
+                .collect();
+        };
+        if matches!(request.kind, RequestKind::Introspect) {
+            return self.introspect(seq);
+        }
+        match self.process_resource(seq, &path_ids, &request.kind) {
+            Ok(events) => events,
+            Err(MockError::Kind(kind)) => err_event(seq, kind).into_iter().collect(),
+            Err(MockError::User(msg)) => err_event(seq, ErrorKind::UserStr(&msg))
+                .into_iter()
+                .collect(),
+            Err(MockError::Ignore) => vec![],
+        }
+    }
+
+    /// Serialized stream data events that are due at the provided instant.
+    pub fn poll_streams(&mut self, now: Instant) -> Vec<Vec<u8>> {
+        let mut events = vec![];
+        for open in &mut self.streams {
+            while open.next_due <= now && open.next_idx < open.stream.values.len() {
+                let value = &open.stream.values[open.next_idx];
+                match value_bytes(value, &open.ty, &self.api_bundle) {
+                    Ok(data) => {
+                        let path = to_unib32(&open.path_ids);
+                        let kind = EventKind::StreamData {
+                            path: RefVec::Slice { slice: &path },
+                            data: RefVec::Slice { slice: &data },
+                        };
+                        events.extend(ok_event(0, kind));
+                    }
+                    Err(e) => warn!("skipping scripted value for {}: {e}", open.stream.path),
+                }
+                open.next_idx += 1;
+                if open.stream.repeat && open.next_idx >= open.stream.values.len() {
+                    open.next_idx = 0;
+                }
+                // at least 1ms, so that a repeating stream cannot stall the event loop
+                open.next_due += Duration::from_millis(open.stream.interval_ms.max(1));
+            }
+        }
+        self.streams.retain(|s| s.next_idx < s.stream.values.len());
+        events
+    }
+
+    /// Instant at which the next scripted stream value is due, if any stream is open.
+    pub fn next_stream_due(&self) -> Option<Instant> {
+        self.streams.iter().map(|s| s.next_due).min()
+    }
+
+    /// Close all the streams, e.g., when a client disconnects.
+    pub fn reset_streams(&mut self) {
+        self.streams.clear();
+    }
+
+    fn introspect(&self, seq: u16) -> Vec<Vec<u8>> {
+        let mut events = vec![];
+        for chunk in self.ww_self_bytes.chunks(INTROSPECT_CHUNK_LEN) {
+            let kind = EventKind::StreamData {
+                path: RefVec::Slice { slice: &[] },
+                data: RefVec::new_bytes(chunk),
+            };
+            events.extend(ok_event(seq, kind));
+        }
+        let kind = EventKind::StreamSideband {
+            path: RefVec::Slice { slice: &[] },
+            sideband_event: StreamSidebandEvent::Closed,
+        };
+        events.extend(ok_event(seq, kind));
+        events
+    }
+
+    fn process_resource(
+        &mut self,
+        seq: u16,
+        path_ids: &[u32],
+        kind: &RequestKind,
+    ) -> Result<Vec<Vec<u8>>, MockError> {
+        let item = self.resolve(path_ids)?;
+        let events = match (&item.kind, kind) {
+            (ApiItemKindOwned::Method { args, return_ty }, RequestKind::Call { args: data }) => {
+                let args_ty = args_struct(args.iter().map(|a| (a.ident.clone(), a.ty.clone())));
+                let args = match ValueOwned::des_shrink_wrap_dyn(
+                    data.as_slice(),
+                    &args_ty,
+                    &self.api_bundle,
+                ) {
+                    Ok(ValueOwned::Struct {
+                        fields: FieldsValueOwned::Named(args),
+                    }) => args,
+                    Ok(ValueOwned::Struct { .. }) => vec![],
+                    _ => return Err(ErrorKind::ArgsDesFailed.into()),
+                };
+                let request = MockRequest {
+                    path: item.path,
+                    path_ids: path_ids.to_vec(),
+                    kind: MockRequestKind::Call { args },
+                };
+                let value = match self.pick_response(&request)? {
+                    Some(value) => Some(value),
+                    None => match return_ty {
+                        Some(ty) => Some(self.default_value(ty)?),
+                        None => None,
+                    },
+                };
+                if seq == 0 {
+                    return Ok(vec![]);
+                }
+                let data = match (value, return_ty) {
+                    (Some(value), Some(ty)) => value_bytes(&value, ty, &self.api_bundle)
+                        .map_err(|_| ErrorKind::ResponseSerFailed)?,
+                    // same as ser_unit_return_event
+                    _ => vec![0x00],
+                };
+                ok_event(
+                    seq,
+                    EventKind::ReturnValue {
+                        data: RefVec::Slice { slice: &data },
+                    },
+                )
+                .into_iter()
+                .collect()
+            }
+            (ApiItemKindOwned::Property { ty, access, .. }, RequestKind::Read) => {
+                if matches!(access, PropertyAccess::WriteOnly) {
+                    return Err(ErrorKind::OperationNotSupported.into());
+                }
+                if seq == 0 {
+                    return Err(ErrorKind::ReadPropertyWithSeqZero.into());
+                }
+                let request = MockRequest {
+                    path: item.path,
+                    path_ids: path_ids.to_vec(),
+                    kind: MockRequestKind::Read,
+                };
+                let value = match self.pick_response(&request)? {
+                    Some(value) => value,
+                    None => match self.properties.get(path_ids) {
+                        Some(value) => value.clone(),
+                        None => self.default_value(ty)?,
+                    },
+                };
+                let data = value_bytes(&value, ty, &self.api_bundle)
+                    .map_err(|_| ErrorKind::ResponseSerFailed)?;
+                ok_event(
+                    seq,
+                    EventKind::ReadValue {
+                        data: RefVec::Slice { slice: &data },
+                    },
+                )
+                .into_iter()
+                .collect()
+            }
+            (ApiItemKindOwned::Property { ty, access, .. }, RequestKind::Write { data }) => {
+                if matches!(
+                    access,
+                    PropertyAccess::Const | PropertyAccess::ReadOnly { .. }
+                ) {
+                    return Err(ErrorKind::OperationNotSupported.into());
+                }
+                let value = ValueOwned::des_shrink_wrap_dyn(data.as_slice(), ty, &self.api_bundle)
+                    .map_err(|_| ErrorKind::PropertyDesFailed)?;
+                let request = MockRequest {
+                    path: item.path,
+                    path_ids: path_ids.to_vec(),
+                    kind: MockRequestKind::Write {
+                        value: value.clone(),
+                    },
+                };
+                self.pick_response(&request)?;
+                self.properties.insert(path_ids.to_vec(), value);
+                if seq == 0 {
+                    return Ok(vec![]);
+                }
+                ok_event(seq, EventKind::Written).into_iter().collect()
+            }
+            (ApiItemKindOwned::Stream { ty, is_up: false }, RequestKind::Write { data }) => {
+                let value = if ty.is_byte_slice(&self.api_bundle).unwrap_or(false) {
+                    bytes_value(data.as_slice())
+                } else {
+                    ValueOwned::des_shrink_wrap_dyn(data.as_slice(), ty, &self.api_bundle)
+                        .map_err(|_| ErrorKind::ArgsDesFailed)?
+                };
+                let request = MockRequest {
+                    path: item.path,
+                    path_ids: path_ids.to_vec(),
+                    kind: MockRequestKind::StreamWrite { value },
+                };
+                self.pick_response(&request)?;
+                // do not send acknowledgements on stream writes
+                vec![]
+            }
+            (
+                ApiItemKindOwned::Stream { ty, is_up },
+                RequestKind::StreamSideband { sideband_cmd },
+            ) => {
+                let request = MockRequest {
+                    path: item.path,
+                    path_ids: path_ids.to_vec(),
+                    kind: MockRequestKind::StreamSideband(*sideband_cmd),
+                };
+                self.pick_response(&request)?;
+                let sideband_event = match sideband_cmd {
+                    StreamSidebandCommand::Open => {
+                        if *is_up {
+                            self.open_stream(&request.path, path_ids, ty);
+                        }
+                        StreamSidebandEvent::Opened
+                    }
+                    StreamSidebandCommand::Close => {
+                        self.streams.retain(|s| s.path_ids != path_ids);
+                        StreamSidebandEvent::Closed
+                    }
+                    _ => return Ok(vec![]),
+                };
+                let path = to_unib32(path_ids);
+                ok_event(
+                    seq,
+                    EventKind::StreamSideband {
+                        path: RefVec::Slice { slice: &path },
+                        sideband_event,
+                    },
+                )
+                .into_iter()
+                .collect()
+            }
+            (ApiItemKindOwned::Stream { .. }, RequestKind::ChangeRate { .. }) => vec![],
+            // calls are answered right away, nothing to abort
+            (ApiItemKindOwned::Method { .. }, RequestKind::Cancel { .. }) => vec![],
+            (ApiItemKindOwned::Trait { .. }, _) => {
+                return Err(ErrorKind::OperationNotSupported.into());
+            }
+            _ => return Err(ErrorKind::OperationNotImplemented.into()),
+        };
+        Ok(events)
+    }
+
+    /// Walk the API tree following resource IDs, array items consume one more ID as an index.
+    fn resolve(&self, path_ids: &[u32]) -> Result<ResolvedItem, MockError> {
+        let mut level = &self.api_bundle.root;
+        let mut names = vec![];
+        let mut ids = path_ids.iter();
+        while let Some(id) = ids.next() {
+            let item = level
+                .items
+                .iter()
+                .find(|item| item.id.0 == *id)
+                .ok_or(ErrorKind::BadPath)?;
+            if item.is_array() {
+                let index = ids.next().ok_or(ErrorKind::ExpectedArrayIndexGotNone)?;
+                names.push(format!("{}[{index}]", item.ident));
+            } else {
+                names.push(item.ident.clone());
+            }
+            if let ApiItemKindOwned::Trait { trait_idx } = &item.kind
+                && ids.len() > 0
+            {
+                level = self
+                    .api_bundle
+                    .get_trait(trait_idx.0)
+                    .map_err(|_| ErrorKind::BadPath)?;
+                continue;
+            }
+            if ids.len() > 0 {
+                return Err(ErrorKind::BadPath.into());
+            }
+            return Ok(ResolvedItem {
+                path: names.join("/"),
+                kind: item.kind.clone(),
+            });
+        }
+        Err(ErrorKind::BadPath.into())
+    }
+
+    /// User callback first, then script. Ok(None) if neither of them provided a value.
+    fn pick_response(&mut self, request: &MockRequest) -> Result<Option<ValueOwned>, MockError> {
+        let mut response = None;
+        if let Some(on_request) = &mut self.on_request {
+            response = on_request(request);
+        }
+        if response.is_none()
+            && matches!(
+                request.kind,
+                MockRequestKind::Call { .. } | MockRequestKind::Read
+            )
+            && let Some(scripted) = self.script.response(&request.path)
+        {
+            let given_out = self
+                .script_progress
+                .entry(request.path.clone())
+                .or_default();
+            let idx = (*given_out).min(scripted.responses.len().saturating_sub(1));
+            response = scripted.responses.get(idx).cloned();
+            *given_out += 1;
+        }
+        debug!("{} {:?} -> {response:?}", request.path, request.kind);
+        match response {
+            Some(MockResponse::Value(value)) => Ok(Some(value)),
+            Some(MockResponse::Error(msg)) => Err(MockError::User(msg)),
+            Some(MockResponse::Ignore) => Err(MockError::Ignore),
+            None => Ok(None),
+        }
+    }
+
+    fn open_stream(&mut self, path: &str, path_ids: &[u32], ty: &TypeOwned) {
+        let Some(stream) = self.script.stream(path) else {
+            return;
+        };
+        self.streams.retain(|s| s.path_ids != path_ids);
+        self.streams.push(OpenStream {
+            path_ids: path_ids.to_vec(),
+            ty: ty.clone(),
+            stream: stream.clone(),
+            next_idx: 0,
+            next_due: Instant::now(),
+        });
+    }
+
+    fn default_value(&self, ty: &TypeOwned) -> Result<ValueOwned, MockError> {
+        ValueOwned::default(ty, &self.api_bundle).map_err(|e| MockError::User(e.to_string()))
+    }
+}
+
+enum MockError {
+    Kind(ErrorKind<'static>),
+    User(String),
+    Ignore,
+}
+
+impl From<ErrorKind<'static>> for MockError {
+    fn from(kind: ErrorKind<'static>) -> Self {
+        MockError::Kind(kind)
+    }
+}
+
+/// Arguments are put into a struct and serialized, same as generated client does.
+fn args_struct(args: impl Iterator<Item = (String, TypeOwned)>) -> TypeOwned {
+    let fields = args
+        .map(|(ident, ty)| FieldOwned {
+            ident: Some(ident),
+            default: None,
+            since: None,
+            ty,
+            docs: vec![],
+            deprecated: None,
+        })
+        .collect();
+    TypeOwned::Struct(ItemStructOwned {
+        size: ElementSize::Unsized,
+        crate_idx: UNib32(0),
+        docs: vec![],
+        ident: "Args".into(),
+        fields: FieldsOwned::Named(fields),
+        generics: None,
+    })
+}
+
+/// Byte slices are sent as is, everything else is serialized with shrink_wrap.
+fn value_bytes(value: &ValueOwned, ty: &TypeOwned, api_bundle: &ApiBundleOwned) -> Result<Vec<u8>> {
+    if ty.is_byte_slice(api_bundle)? {
+        let ValueOwned::Vec(items) = value else {
+            return Err(anyhow!("expected Vec<u8>, got {value:?}"));
+        };
+        return items
+            .iter()
+            .map(|item| match item {
+                ValueOwned::Numeric(NumericValue::U8(b)) => Ok(*b),
+                _ => Err(anyhow!("expected u8, got {item:?}")),
+            })
+            .collect();
+    }
+    value.ser_shrink_wrap_dyn(ty, api_bundle)
+}
+
+fn bytes_value(bytes: &[u8]) -> ValueOwned {
+    ValueOwned::Vec(
+        bytes
+            .iter()
+            .map(|b| ValueOwned::Numeric(NumericValue::U8(*b)))
+            .collect(),
+    )
+}
+
+fn to_unib32(path_ids: &[u32]) -> Vec<UNib32> {
+    path_ids.iter().map(|id| UNib32(*id)).collect()
+}
+
+fn ok_event(seq: u16, kind: EventKind<'_>) -> Option<Vec<u8>> {
+    ser_event(&Event {
+        seq,
+        result: Ok(kind),
+    })
+}
+
+fn err_event(seq: u16, kind: ErrorKind<'_>) -> Option<Vec<u8>> {
+    ser_event(&Event {
+        seq,
+        result: Err(Error::new(0, kind)),
+    })
+}
+
+fn ser_event(event: &Event<'_>) -> Option<Vec<u8>> {
+    match to_ww_vec(event) {
+        Ok(bytes) => Some(bytes),
+        Err(e) => {
+            warn!("failed to serialize event: {e}");
+            None
+        }
+    }
+}
+
+/// Serialize into a Vec, growing it if a value does not fit.
+fn to_ww_vec<T: SerializeShrinkWrap>(value: &T) -> Result<Vec<u8>> {
+    shrink_wrap::to_ww_vec(value, 16 * 1024 * 1024).map_err(|e| anyhow!("{e:?}"))
+}
+
+/// Find the most recently cached API bundle of the provided crate in the local registry (`~/.wire_weaver`).
+pub fn find_in_registry(crate_name: &str) -> Result<PathBuf> {
+    let registry = std::env::home_dir()
+        .ok_or(anyhow!("no home directory"))?
+        .join(".wire_weaver");
+    let prefix = format!("{crate_name}-");
+    let mut newest: Option<(std::time::SystemTime, PathBuf)> = None;
+    for entry in std::fs::read_dir(&registry)
+        .with_context(|| format!("reading local registry {}", registry.display()))?
+    {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        let Some(hash) = file_name
+            .strip_prefix(&prefix)
+            .and_then(|s| s.strip_suffix(".ron"))
+            .map(|s| s.trim_end_matches("+docs"))
+        else {
+            continue;
+        };
+        if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            // other crate with the same prefix, e.g. `crate_name-ext`
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        if newest.as_ref().is_none_or(|(m, _)| modified > *m) {
+            newest = Some((modified, entry.path()));
+        }
+    }
+    newest
+        .map(|(_, path)| path)
+        .ok_or(anyhow!("{crate_name} not found in {}", registry.display()))
+}
@@ -0,0 +1,189 @@
+use crate::MockServer;
+use std::ops::ControlFlow;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+use wire_weaver_client_common::event_loop_state::CommonState;
+use wire_weaver_client_common::rx_dispatcher::{
+    DispatcherCommand, DispatcherMessage, RxDispatcher,
+};
+use wire_weaver_client_common::{Command, DeviceInfoBundle, Error, TestProgress};
+use ww_client_server::Request;
+
+struct State {
+    common: CommonState,
+    server: MockServer,
+}
+
+/// Run mock device event loop until [Command::DisconnectAndExit] is received or all the command senders are dropped.
+///
+/// Can be used instead of a USB or WebSocket worker, generated clients work with it as if a real device was connected.
+pub async fn mock_worker(mut cmd_rx: mpsc::UnboundedReceiver<Command>, server: MockServer) {
+    let mut state = State {
+        common: CommonState::default(),
+        server,
+    };
+    let mut rx_dispatcher = RxDispatcher::default();
+    loop {
+        if state.common.link_up {
+            for event in state.server.poll_streams(Instant::now()) {
+                state.deliver(&event, &mut rx_dispatcher);
+            }
+        }
+        let prune_in = rx_dispatcher.prune_next_timeout();
+        let sleep_for = match state.server.next_stream_due() {
+            Some(due) if state.common.link_up => {
+                due.saturating_duration_since(Instant::now()).min(prune_in)
+            }
+            _ => prune_in,
+        };
+        tokio::select! {
+            cmd = cmd_rx.recv() => {
+                let Some(cmd) = cmd else {
+                    debug!("mock worker exiting, because all command senders were dropped");
+                    break;
+                };
+                if state.handle_command(cmd, &mut rx_dispatcher).is_break() {
+                    break;
+                }
+            }
+            _ = tokio::time::sleep(sleep_for) => {}
+        }
+    }
+    debug!("mock worker exited");
+}
+
+impl State {
+    fn handle_command(
+        &mut self,
+        cmd: Command,
+        rx_dispatcher: &mut RxDispatcher,
+    ) -> ControlFlow<()> {
+        match cmd {
+            Command::Connect {
+                filter,
+                on_error,
+                connected_tx,
+                client_version,
+            } => {
+                if self.common.link_up {
+                    warn!("Ignoring Connect while already connected");
+                    return ControlFlow::Continue(());
+                }
+                let mut device_info = DeviceInfoBundle::empty();
+                device_info.api_model_version = ww_client_server::FULL_VERSION.make_owned();
+                if let Ok(version) = self.server.user_api_version() {
+                    device_info.user_api_version = version.clone();
+                }
+                info!("Mocking: {device_info:?}");
+                self.common
+                    .on_connect(&filter, on_error, connected_tx, *client_version.clone());
+                if !client_version.crate_id.is_empty()
+                    && !client_version.is_protocol_compatible(&device_info.user_api_version)
+                {
+                    if let Some(tx) = self.common.connected_tx.take() {
+                        _ = tx.send(Err(Error::IncompatibleDeviceProtocol));
+                    }
+                    return ControlFlow::Continue(());
+                }
+                self.common.device_info = Some(device_info.clone());
+                rx_dispatcher.handle_msg(DispatcherMessage::Connected);
+                if let Some(tx) = self.common.connected_tx.take() {
+                    _ = tx.send(Ok(device_info));
+                }
+                for request in self.common.on_link_up() {
+                    self.common.trace_request(&request);
+                    for event in self.server.process_request(&request) {
+                        self.deliver(&event, rx_dispatcher);
+                    }
+                }
+            }
+            Command::RegisterTracer { trace_event_tx } => {
+                self.common.tracers.push(trace_event_tx);
+            }
+            Command::DisconnectKeepStreams { disconnected_tx } => {
+                self.common.trace_disconnect("client request", true);
+                self.common.on_disconnect();
+                self.server.reset_streams();
+                rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+                if let Some(tx) = disconnected_tx {
+                    _ = tx.send(());
+                }
+            }
+            Command::DisconnectAndExit { disconnected_tx } => {
+                self.common.trace_disconnect("client request", false);
+                rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+                if let Some(tx) = disconnected_tx {
+                    _ = tx.send(());
+                }
+                return ControlFlow::Break(());
+            }
+            Command::SendMessage {
+                mut bytes,
+                mut done_tx,
+            } => {
+                if let Some((done_tx, timeout)) = done_tx.take() {
+                    if let Some(seq) = rx_dispatcher.next_seq() {
+                        Request::set_seq(&mut bytes, seq);
+                        rx_dispatcher.handle_cmd(DispatcherCommand::OnReturn {
+                            seq,
+                            done_tx,
+                            timeout,
+                        });
+                    } else {
+                        _ = done_tx.send(Err(Error::Other("No more request IDs available".into())));
+                    }
+                }
+                if !self.common.link_up {
+                    warn!("ignoring send message while disconnected");
+                    return ControlFlow::Continue(());
+                }
+                self.common.on_request(&bytes);
+                for event in self.server.process_request(&bytes) {
+                    self.deliver(&event, rx_dispatcher);
+                }
+            }
+            Command::Cancel { request } => {
+                rx_dispatcher.cancel(request.seq.get());
+                if !self.common.link_up {
+                    return ControlFlow::Continue(());
+                }
+                if let Some(bytes) = request.notification() {
+                    self.common.on_request(&bytes);
+                    for event in self.server.process_request(&bytes) {
+                        self.deliver(&event, rx_dispatcher);
+                    }
+                }
+            }
+            Command::OnStreamEvent {
+                path_kind,
+                stream_event_tx,
+                subscribe_seq,
+            } => {
+                rx_dispatcher.handle_cmd(DispatcherCommand::OnStreamEvent {
+                    path_kind: *path_kind,
+                    stream_event_tx,
+                    subscribe_seq,
+                });
+            }
+            Command::SetApiBundle { api_bundle } => {
+                self.common.trace_api_bundle(&api_bundle);
+                rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle { api_bundle });
+            }
+            Command::OnConnectionEvent { event_tx } => {
+                self.common.add_connection_event_tx(event_tx);
+            }
+            Command::LoopbackTest { progress_tx, .. } => {
+                _ = progress_tx.send(TestProgress::FatalError(
+                    "Not supported by mock device".into(),
+                ));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn deliver(&mut self, event: &[u8], rx_dispatcher: &mut RxDispatcher) {
+        self.common.trace_event(event);
+        rx_dispatcher.handle_msg(DispatcherMessage::MessageBytes(event));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::MockServer;
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+/// How often to check for scripted stream updates when no stream is open.
+const IDLE_POLL: Duration = Duration::from_secs(1);
+
+/// Accept WebSocket connections one at a time and answer them with the provided mock server.
+///
+/// Speaks the same sideband text protocol as `wire_weaver_net_host::ws_worker` expects from a device
+/// (`versions?` -> `device_info`, `link_setup` -> `link_setup_result`), requests and events are sent as binary messages.
+/// Runs until the listener fails.
+pub async fn serve_ws(listener: TcpListener, mut server: MockServer) -> Result<()> {
+    info!("mock device listening on {}", listener.local_addr()?);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        info!("client connected: {peer}");
+        if let Err(e) = serve_connection(stream, &mut server).await {
+            warn!("client {peer}: {e}");
+        }
+        server.reset_streams();
+        info!("client disconnected: {peer}");
+    }
+}
+
+async fn serve_connection(stream: TcpStream, server: &mut MockServer) -> Result<()> {
+    let ws = tokio_tungstenite::accept_async(stream).await?;
+    let (mut tx, mut rx) = ws.split();
+    loop {
+        let sleep_for = server
+            .next_stream_due()
+            .map(|due| due.saturating_duration_since(Instant::now()))
+            .unwrap_or(IDLE_POLL);
+        tokio::select! {
+            message = rx.next() => {
+                let Some(message) = message else {
+                    return Ok(());
+                };
+                match message? {
+                    Message::Binary(data) => {
+                        for event in server.process_request(&data) {
+                            tx.send(Message::Binary(event.into())).await?;
+                        }
+                    }
+                    Message::Text(text) => {
+                        let op = text.split(' ').next().unwrap_or_default();
+                        let response = match op {
+                            "versions?" => {
+                                let version = server.user_api_version()?;
+                                let v = &version.version;
+                                format!(
+                                    "device_info {} {}.{}.{}",
+                                    version.crate_id, v.major.0, v.minor.0, v.patch.0
+                                )
+                            }
+                            "link_setup" => "link_setup_result ok".into(),
+                            _ => {
+                                warn!("unexpected sideband message: {text}");
+                                continue;
+                            }
+                        };
+                        debug!("{op} -> {response}");
+                        tx.send(Message::Text(response.into())).await?;
+                    }
+                    Message::Close(_) => return Ok(()),
+                    _ => {}
+                }
+            }
+            _ = tokio::time::sleep(sleep_for) => {
+                for event in server.poll_streams(Instant::now()) {
+                    tx.send(Message::Binary(event.into())).await?;
+                }
+            }
+        }
+    }
+}
@@ -11,8 +11,12 @@ use wire_weaver_client_common::event_loop_state::CommonState;
 use wire_weaver_client_common::rx_dispatcher::{
     DispatcherCommand, DispatcherMessage, RxDispatcher,
 };
+use wire_weaver_client_common::ww_version::{FullVersionOwned, VersionOwned};
 use wire_weaver_client_common::{Command, DeviceInfoBundle, Error, ww_client_server};
 
+/// Delay between connection attempts after a link was lost with [OnError::KeepRetrying](wire_weaver_client_common::OnError::KeepRetrying).
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
 pub struct WsTarget {
     pub addr: IpAddr,
     pub port: u16,
@@ -75,6 +79,8 @@ pub async fn ws_worker(mut cmd_rx: mpsc::UnboundedReceiver<Command>) {
                         info!("loop (inner) exited with {:?}", r);
                         if r == EventLoopResult::Exit {
                             break;
+                        } else if r == EventLoopResult::DisconnectKeepStreams {
+                            state.common.stop_reconnecting();
                         }
                     }
                     Err(e) => error!("loop (inner) exited with {:?}", e),
@@ -83,6 +89,9 @@ pub async fn ws_worker(mut cmd_rx: mpsc::UnboundedReceiver<Command>) {
                     break;
                 } else {
                     info!("will try to reconnect");
+                    if state.common.link_up {
+                        rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+                    }
                     state.common.on_disconnect();
                     link = None;
                     continue;
@@ -178,7 +187,30 @@ async fn wait_for_connection_and_queue_commands(
     state: &mut State,
 ) -> Result<Option<Link>, WsError> {
     loop {
-        let Some(cmd) = cmd_rx.recv().await else {
+        let reconnect_target = state
+            .common
+            .reconnect_filter()
+            .and_then(|filter| filter.as_web_socket());
+        let cmd = match reconnect_target {
+            Some((addr, port, path)) => {
+                tokio::select! {
+                    link = open_link(addr, port, path) => match link {
+                        Ok(link) => {
+                            info!("reconnected to ws://{addr}:{port}");
+                            return Ok(Some(link));
+                        }
+                        Err(e) => {
+                            debug!("reconnect failed: {e}, retrying in {RECONNECT_INTERVAL:?}");
+                            tokio::time::sleep(RECONNECT_INTERVAL).await;
+                            continue;
+                        }
+                    },
+                    cmd = cmd_rx.recv() => cmd,
+                }
+            }
+            None => cmd_rx.recv().await,
+        };
+        let Some(cmd) = cmd else {
             debug!("ws worker exiting, because all command senders were dropped");
             return Err(WsError::CmdTxDropped);
         };
@@ -196,16 +228,15 @@ async fn wait_for_connection_and_queue_commands(
                 };
                 state
                     .common
-                    .on_connect(on_error, connected_tx, *client_version.clone()); // TODO: use user protocol version
-                let (ws, _response) =
-                    tokio_tungstenite::connect_async(format!("ws://{}:{}/{}", addr, port, path))
-                        .await?;
-                let (tx, rx) = ws.split();
-                return Ok(Some(Link {
-                    _target: WsTarget { addr, port, path },
-                    tx,
-                    rx,
-                }));
+                    .on_connect(&filter, on_error, connected_tx, *client_version.clone()); // TODO: use user protocol version
+                match open_link(addr, port, path).await {
+                    Ok(link) => return Ok(Some(link)),
+                    Err(e) if !state.common.exit_on_error => {
+                        warn!("connection failed: {e}, will keep retrying");
+                        return Ok(None);
+                    }
+                    Err(e) => return Err(e),
+                }
             }
             Command::RegisterTracer { trace_event_tx } => {
                 state.common.tracers.push(trace_event_tx);
@@ -223,18 +254,41 @@ async fn wait_for_connection_and_queue_commands(
                 state.common.exit_on_error = true;
                 return Err(WsError::ExitRequested);
             }
-            Command::SendMessage { .. } => {
+            Command::SendMessage { bytes, .. } => {
                 warn!("ignoring send message while disconnected");
+                // streams opened while disconnected are opened after connection
+                state.common.active_resources.on_request(&bytes);
+            }
+            Command::Cancel { .. } => {
+                // all requests were already cancelled on disconnect
             }
             Command::OnStreamEvent { .. } => {
                 // TODO: do not ignore OnStreamEvent when disconnected
                 warn!("ignoring on stream event while disconnected for now");
             }
+            Command::SetApiBundle { api_bundle } => {
+                state.common.trace_api_bundle(&api_bundle);
+                state.common.pending_api_bundle = Some(api_bundle);
+            }
+            Command::OnConnectionEvent { event_tx } => {
+                state.common.add_connection_event_tx(event_tx);
+            }
             Command::LoopbackTest { .. } => {}
         }
     }
 }
 
+async fn open_link(addr: IpAddr, port: u16, path: String) -> Result<Link, WsError> {
+    let (ws, _response) =
+        tokio_tungstenite::connect_async(format!("ws://{}:{}/{}", addr, port, path)).await?;
+    let (tx, rx) = ws.split();
+    Ok(Link {
+        _target: WsTarget { addr, port, path },
+        tx,
+        rx,
+    })
+}
+
 enum EventLoopSpinResult {
     Continue,
     DisconnectKeepStreams,
@@ -271,6 +325,14 @@ async fn handle_message(
             }
             let op = pieces[0];
             if op == "device_info" {
+                if let [_, crate_id, version, ..] = pieces.as_slice()
+                    && let Some(version) = parse_version(version)
+                {
+                    let mut device_info = DeviceInfoBundle::empty();
+                    device_info.user_api_version =
+                        FullVersionOwned::new(crate_id.to_string(), version);
+                    state.common.device_info = Some(device_info);
+                }
                 tx.send(Message::Text("link_setup 2048 0 6 0 1 100 0 1".into()))
                     .await?; // TODO: send proper versions
             } else if op == "link_setup_result" {
@@ -284,10 +346,20 @@ async fn handle_message(
                 }
                 info!("LinkSetup complete");
                 rx_dispatcher.handle_msg(DispatcherMessage::Connected);
+                if let Some(api_bundle) = state.common.pending_api_bundle.take() {
+                    rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle { api_bundle });
+                }
                 if let Some(tx) = state.common.connected_tx.take() {
-                    _ = tx.send(Ok(DeviceInfoBundle::empty())); // TODO: ws: device info bundle
+                    _ = tx.send(Ok(state
+                        .common
+                        .device_info
+                        .clone()
+                        .unwrap_or_else(DeviceInfoBundle::empty))); // TODO: ws: full device info bundle
+                }
+                for request in state.common.on_link_up() {
+                    state.common.trace_request(&request);
+                    tx.send(Message::Binary(request.into())).await?;
                 }
-                state.common.link_up = true;
             } else {
                 error!("Unexpected sideband message received: {op}");
                 return Err(WsError::LinkSetupError);
@@ -350,22 +422,47 @@ async fn handle_command(
                     _ = done_tx.send(Err(Error::Other("No more request IDs available".into())));
                 }
             }
-            state.common.trace_request(&bytes);
+            state.common.on_request(&bytes);
             tx.send(Message::Binary(bytes.into())).await?;
             // TODO: check in WireShark whether messages batch together or else force send on timer
         }
+        Command::Cancel { request } => {
+            rx_dispatcher.cancel(request.seq.get());
+            if let Some(bytes) = request.notification() {
+                state.common.on_request(&bytes);
+                tx.send(Message::Binary(bytes.into())).await?;
+            }
+        }
         Command::OnStreamEvent {
             path_kind,
             stream_event_tx,
+            subscribe_seq,
         } => {
             rx_dispatcher.handle_cmd(DispatcherCommand::OnStreamEvent {
                 path_kind: *path_kind,
                 stream_event_tx,
+                subscribe_seq,
             });
         }
+        Command::SetApiBundle { api_bundle } => {
+            state.common.trace_api_bundle(&api_bundle);
+            rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle { api_bundle });
+        }
+        Command::OnConnectionEvent { event_tx } => {
+            state.common.add_connection_event_tx(event_tx);
+        }
         Command::LoopbackTest { .. } => {
             todo!()
         }
     }
     Ok(EventLoopSpinResult::Continue)
 }
+
+/// Parse `major.minor.patch` as sent in `device_info` sideband message.
+fn parse_version(version: &str) -> Option<VersionOwned> {
+    let mut numbers = version.split('.').map(|n| n.parse::<u32>().ok());
+    let major = numbers.next()??;
+    let minor = numbers.next()??;
+    let patch = numbers.next()??;
+    Some(VersionOwned::new(major, minor, patch))
+}
@@ -19,6 +19,9 @@ use wire_weaver_usb_link::{
     WireWeaverUsbLink,
 };
 
+/// Delay between connection attempts after a device was lost with [OnError::KeepRetrying].
+const RECONNECT_INTERVAL: Duration = Duration::from_millis(500);
+
 struct State {
     common: CommonState,
     message_rx: [u8; MAX_MESSAGE_SIZE],
@@ -45,7 +48,16 @@ impl State {
     }
 }
 
-pub async fn usb_worker(mut cmd_rx: mpsc::UnboundedReceiver<Command>) {
+/// USB host event loop. Connects through `ww daemon` instead, if [DeviceFilter::allow_ipc](wire_weaver_client_common::DeviceFilter::allow_ipc)
+/// is set and a daemon serving the same filter is running.
+pub async fn usb_worker(cmd_rx: mpsc::UnboundedReceiver<Command>) {
+    #[cfg(unix)]
+    wire_weaver_client_common::ipc::prefer_ipc(cmd_rx, usb_event_loop).await;
+    #[cfg(not(unix))]
+    usb_event_loop(cmd_rx).await;
+}
+
+async fn usb_event_loop(mut cmd_rx: mpsc::UnboundedReceiver<Command>) {
     let mut state = State::new();
     let mut rx_dispatcher = RxDispatcher::default();
 
@@ -63,6 +75,8 @@ pub async fn usb_worker(mut cmd_rx: mpsc::UnboundedReceiver<Command>) {
                         info!("usb event loop (inner) exited with {:?}", r);
                         if r == EventLoopResult::Exit {
                             break;
+                        } else if r == EventLoopResult::DisconnectKeepStreams {
+                            state.common.stop_reconnecting();
                         }
                     }
                     Err(e) => error!("usb event loop (inner) exited with {:?}", e),
@@ -71,6 +85,9 @@ pub async fn usb_worker(mut cmd_rx: mpsc::UnboundedReceiver<Command>) {
                     break;
                 } else {
                     info!("will try to reconnect");
+                    if state.common.link_up {
+                        rx_dispatcher.handle_msg(DispatcherMessage::Disconnected);
+                    }
                     state.on_disconnect();
                     link = None;
                     continue;
@@ -126,7 +143,29 @@ async fn wait_for_connection_and_queue_commands(
     state: &mut State,
 ) -> Result<Option<(Interface, DeviceInfo, TransferType, usize, FullVersionOwned)>, ()> {
     loop {
-        let Some(cmd) = cmd_rx.recv().await else {
+        let cmd = match state.common.reconnect_filter() {
+            Some(filter) => {
+                tokio::select! {
+                    r = crate::connection::connect(&filter, OnError::KeepRetrying) => match r {
+                        Ok((interface, di, transfer_type, max_packet_size)) => {
+                            info!("reconnected to {di:?}");
+                            let client_version = state.common.client_version.clone().unwrap_or_else(|| {
+                                FullVersionOwned::new("".into(), VersionOwned::new(0, 0, 0))
+                            });
+                            return Ok(Some((interface, di, transfer_type, max_packet_size, client_version)));
+                        }
+                        Err(e) => {
+                            debug!("reconnect failed: {e:?}, retrying in {RECONNECT_INTERVAL:?}");
+                            tokio::time::sleep(RECONNECT_INTERVAL).await;
+                            continue;
+                        }
+                    },
+                    cmd = cmd_rx.recv() => cmd,
+                }
+            }
+            None => cmd_rx.recv().await,
+        };
+        let Some(cmd) = cmd else {
             // all senders have been dropped
             debug!("usb worker exiting, because all command senders were dropped");
             return Err(());
@@ -144,6 +183,13 @@ async fn wait_for_connection_and_queue_commands(
                         Err(e) => {
                             // TODO: drop requests if any
                             return if on_error == OnError::KeepRetrying {
+                                warn!("connection failed: {e:?}, will keep retrying");
+                                state.common.on_connect(
+                                    &filter,
+                                    on_error,
+                                    connected_tx,
+                                    *client_version,
+                                );
                                 Ok(None)
                             } else {
                                 if let Some(tx) = connected_tx {
@@ -155,7 +201,7 @@ async fn wait_for_connection_and_queue_commands(
                     };
                 state
                     .common
-                    .on_connect(on_error, connected_tx, *client_version.clone());
+                    .on_connect(&filter, on_error, connected_tx, *client_version.clone());
                 return Ok(Some((
                     interface,
                     di,
@@ -180,13 +226,25 @@ async fn wait_for_connection_and_queue_commands(
                 state.common.exit_on_error = true;
                 return Err(());
             }
-            Command::SendMessage { .. } => {
+            Command::SendMessage { bytes, .. } => {
                 warn!("ignoring send message while disconnected");
+                // streams opened while disconnected are opened after connection
+                state.common.active_resources.on_request(&bytes);
+            }
+            Command::Cancel { .. } => {
+                // all requests were already cancelled on disconnect
             }
             Command::OnStreamEvent { .. } => {
                 // TODO: do not ignore OnStreamEvent when disconnected
                 warn!("ignoring on stream event while disconnected for now");
             }
+            Command::SetApiBundle { api_bundle } => {
+                state.common.trace_api_bundle(&api_bundle);
+                state.common.pending_api_bundle = Some(api_bundle);
+            }
+            Command::OnConnectionEvent { event_tx } => {
+                state.common.add_connection_event_tx(event_tx);
+            }
             Command::LoopbackTest { progress_tx, .. } => {
                 _ = progress_tx.send(TestProgress::FatalError("Not connected".into()));
             }
@@ -433,6 +491,9 @@ where
             info!("LinkSetup complete");
             state.max_protocol_mismatched_messages = 10;
             rx_dispatcher.handle_msg(DispatcherMessage::Connected);
+            if let Some(api_bundle) = state.common.pending_api_bundle.take() {
+                rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle { api_bundle });
+            }
             if let Some(tx) = state.common.connected_tx.take() {
                 _ = tx.send(Ok(state
                     .common
@@ -440,7 +501,15 @@ where
                     .clone()
                     .unwrap_or(DeviceInfoBundle::empty())));
             }
-            state.common.on_link_up();
+            for request in state.common.on_link_up() {
+                state.common.trace_request(&request);
+                link.send_message(&request)
+                    .await
+                    .map_err(|e| Error::Transport(format!("{:?}", e)))?;
+            }
+            if !link.is_tx_queue_empty() && state.common.packet_started_instant.is_none() {
+                state.common.packet_started_instant = Some(Instant::now());
+            }
         }
         Ok(MessageKind::IncompatibleVersion) => {
             // device only message, ignore to pass cargo check --all-features
@@ -532,7 +601,7 @@ where
                     _ = done_tx.send(Err(Error::Other("No more request IDs available".into())));
                 }
             }
-            state.common.trace_request(&bytes);
+            state.common.on_request(&bytes);
             link.send_message(&bytes)
                 .await
                 .map_err(|e| Error::Transport(format!("{:?}", e)))?;
@@ -542,15 +611,33 @@ where
                 state.common.packet_started_instant = Some(Instant::now());
             }
         }
+        Command::Cancel { request } => {
+            rx_dispatcher.cancel(request.seq.get());
+            if let Some(bytes) = request.notification() {
+                state.common.on_request(&bytes);
+                link.send_message(&bytes)
+                    .await
+                    .map_err(|e| Error::Transport(format!("{:?}", e)))?;
+            }
+        }
         Command::OnStreamEvent {
             path_kind,
             stream_event_tx,
+            subscribe_seq,
         } => {
             rx_dispatcher.handle_cmd(DispatcherCommand::OnStreamEvent {
                 path_kind: *path_kind,
                 stream_event_tx,
+                subscribe_seq,
             });
         }
+        Command::SetApiBundle { api_bundle } => {
+            state.common.trace_api_bundle(&api_bundle);
+            rx_dispatcher.handle_cmd(DispatcherCommand::SetApiBundle { api_bundle });
+        }
+        Command::OnConnectionEvent { event_tx } => {
+            state.common.add_connection_event_tx(event_tx);
+        }
         Command::LoopbackTest {
             test_duration,
             packet_size,
@@ -2,6 +2,7 @@ use iceoryx2::port::publisher::Publisher;
 use iceoryx2::prelude::ZeroCopySend;
 use iceoryx2::service::ipc_threadsafe::Service;
 use iceoryx2_bb_container::vector::{StaticVec, Vector};
+use wire_weaver_client_common::TraceEvent;
 use wire_weaver_usb_link::{PacketSink, PacketSource};
 
 #[derive(ZeroCopySend, Debug)]
@@ -11,6 +12,17 @@ pub struct UsbPacket {
     pub data: StaticVec<u8, 1024>,
 }
 
+impl UsbPacket {
+    /// Convert a packet received from `.../tx` (`to_device` = true) or `.../rx` service into a trace event,
+    /// so that it can be recorded into a capture file and exported to pcapng later.
+    pub fn to_trace_event(&self, to_device: bool) -> TraceEvent {
+        TraceEvent::LinkPacket {
+            to_device,
+            bytes: self.data[..self.data.len()].to_vec(),
+        }
+    }
+}
+
 pub(crate) struct SinkTrace<B> {
     publisher: Publisher<Service, UsbPacket, ()>,
     inner: B,
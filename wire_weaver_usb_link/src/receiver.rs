@@ -59,8 +59,12 @@ impl<T: PacketSink, R: PacketSource> WireWeaverUsbLink<'_, T, R> {
     ) -> Result<MessageKind, Error<T::Error, R::Error>> {
         'next_message: loop {
             let (packet, is_new_frame) = if self.rx_left_bytes > 0 {
+                let left_bytes = self.rx_left_bytes;
+                // set again in adjust_read_pos() if there are more messages left,
+                // rest of the packet is skipped on malformed data, instead of parsing it again and again
+                self.rx_left_bytes = 0;
                 (
-                    &self.rx_packet_buf[self.rx_start_pos..self.rx_start_pos + self.rx_left_bytes],
+                    &self.rx_packet_buf[self.rx_start_pos..self.rx_start_pos + left_bytes],
                     false,
                 )
             } else {
@@ -312,7 +316,7 @@ impl<T: PacketSink, R: PacketSource> WireWeaverUsbLink<'_, T, R> {
             }
             (false, true) => {
                 self.rx_start_pos += read_bytes;
-                self.rx_left_bytes -= read_bytes;
+                self.rx_left_bytes = rd_bytes_left;
             }
             _ => {
                 self.continue_with_new_packet();
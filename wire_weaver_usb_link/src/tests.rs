@@ -275,6 +275,39 @@ mod link_tests {
         );
     }
 
+    #[test]
+    fn unknown_op_in_the_rest_of_packet_is_skipped() {
+        let mut tx_buf = [0u8; 8];
+        let mut rx_buf = [0u8; 8];
+        let mut rx = VecSink::new();
+        // Ping followed by Stats, which is not expected on host
+        rx.packets.push_back(vec![
+            (Op::Ping as u8) << 4,
+            0x00,
+            (Op::Stats as u8) << 4,
+            0x00,
+        ]);
+        rx.packets
+            .push_back(vec![(Op::MessageStartEnd as u8) << 4, 0x02, 1, 2]);
+        let mut link = WireWeaverUsbLink::new_host(
+            FullVersionOwned::new("test".into(), VersionOwned::new(0, 0, 0)),
+            VecSink::new(),
+            &mut tx_buf,
+            rx,
+            &mut rx_buf,
+        );
+        link.is_link_up = true;
+
+        let mut receive = [0u8; 8];
+        let kind = block_on(link.receive_message(&mut receive)).unwrap();
+        assert!(matches!(kind, MessageKind::Ping));
+        let kind = block_on(link.receive_message(&mut receive)).unwrap();
+        let MessageKind::Data(len) = kind else {
+            panic!("Expected data packet");
+        };
+        assert_eq!(&receive[..len], &[1, 2]);
+    }
+
     #[test]
     fn receive_is_cancel_safe() {
         let mut tx_buf = [0u8; 8];
@@ -133,6 +133,10 @@ pub enum RequestKind<'i> {
     /// Send serialized AST describing a resource and all related types, see `ww_self` for format.
     /// Optional, for simplicity can be implemented only at root level, sending all API tree.
     Introspect,
+
+    /// Client is no longer interested in the result of a request with provided ID, sent with request ID 0 and the same path.
+    /// Optional, server can use it to abort deferred work. Nothing is sent back.
+    Cancel { seq: u16 },
     // Get [ValidIndices] for an array resource.
     // ValidIndices, -> requested as Read
 
@@ -257,6 +261,7 @@ pub enum EventKind<'i> {
     },
 
     /// Sent in response to RequestKind::Subscribe for properties. Optional.
+    /// Also sent in response to StreamSidebandCommand::Open for a trait-addressed stream, with seq of that request.
     Subscribed {
         /// When subscribing through trait interface, this path is used later to match stream updates to an original request.
         path: RefVec<'i, UNib32>,
@@ -530,6 +535,7 @@ impl RequestKind<'_> {
                 shaper_config: *shaper_config,
             },
             RequestKind::Introspect => RequestKindOwned::Introspect,
+            RequestKind::Cancel { seq } => RequestKindOwned::Cancel { seq: *seq },
         };
         Ok(req)
     }
@@ -541,6 +547,22 @@ impl Request<'_> {
         bytes[0] = seq_le[0];
         bytes[1] = seq_le[1];
     }
+
+    pub fn seq(bytes: &[u8]) -> Option<u16> {
+        Some(u16::from_le_bytes([*bytes.first()?, *bytes.get(1)?]))
+    }
+}
+
+impl Event<'_> {
+    pub fn set_seq(bytes: &mut [u8], seq: u16) {
+        let seq_le = seq.to_le_bytes();
+        bytes[0] = seq_le[0];
+        bytes[1] = seq_le[1];
+    }
+
+    pub fn seq(bytes: &[u8]) -> Option<u16> {
+        Some(u16::from_le_bytes([*bytes.first()?, *bytes.get(1)?]))
+    }
 }
 
 #[cfg(feature = "std")]
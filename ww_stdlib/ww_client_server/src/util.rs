@@ -1,5 +1,5 @@
 use super::{Event, EventKind};
-use wire_weaver::shrink_wrap::{ref_vec::RefVec, BufWriter, Error, SerializeShrinkWrap};
+use wire_weaver::shrink_wrap::{max_len, ref_vec::RefVec, BufWriter, Error, SerializeShrinkWrap};
 
 pub fn ser_ok_event<'a>(
     scratch: &'a mut [u8],
@@ -44,3 +44,86 @@ pub fn ser_unit_return_event(scratch: &mut [u8], seq: u16) -> Result<&[u8], Erro
     event.ser_shrink_wrap(&mut wr)?;
     wr.finish_and_take()
 }
+
+/// Upper bound of a serialized [Request](super::Request) with [RequestKind::Call](super::RequestKind::Call),
+/// addressing a method through [PathKind::Absolute](super::PathKind::Absolute) path of `path_nibbles` UNib32 nibbles in total
+/// and carrying at most `args_len` bytes of serialized arguments.
+/// None if arguments are unbounded.
+pub const fn call_request_max_len(path_nibbles: usize, args_len: Option<usize>) -> Option<usize> {
+    let Some(args_len) = args_len else {
+        return None;
+    };
+    let seq_bits = 16;
+    let path_kind_bits = 4 + 3 + path_nibbles * 4 + max_len::unib32_nibbles(path_nibbles) * 4;
+    let request_kind_bits = 4 + 3 + 7 + args_len * 8 + max_len::unib32_nibbles(args_len) * 4;
+    max_len::root_len(Some(seq_bits + path_kind_bits + request_kind_bits))
+}
+
+/// Upper bound of a serialized [Event] with [EventKind::ReturnValue] or [EventKind::ReadValue],
+/// carrying at most `data_len` bytes of serialized value.
+/// None if the value is unbounded.
+pub const fn value_event_max_len(data_len: Option<usize>) -> Option<usize> {
+    let Some(data_len) = data_len else {
+        return None;
+    };
+    let seq_bits = 16;
+    let is_ok_bits = 1;
+    let event_kind_bits = 3 + 4 + 7 + data_len * 8 + max_len::unib32_nibbles(data_len) * 4;
+    max_len::root_len(Some(seq_bits + is_ok_bits + event_kind_bits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PathKind, Request, RequestKind};
+    use wire_weaver::shrink_wrap::UNib32;
+
+    #[test]
+    fn call_request_within_bound() {
+        let args = [0xAAu8; 300];
+        for path in [
+            &[UNib32(0)][..],
+            &[UNib32(1000), UNib32(7)],
+            &[UNib32(u32::MAX); 3],
+        ] {
+            let path_nibbles = path.iter().map(|n| n.len_nibbles()).sum();
+            for args_len in [0, 1, 7, 8, 255, 300] {
+                let request = Request {
+                    seq: u16::MAX,
+                    path_kind: PathKind::Absolute {
+                        path: RefVec::Slice { slice: path },
+                    },
+                    kind: RequestKind::Call {
+                        args: RefVec::Slice {
+                            slice: &args[..args_len],
+                        },
+                    },
+                };
+                let mut buf = [0u8; 512];
+                let bytes = request.to_ww_bytes(&mut buf).unwrap();
+                let max_len = call_request_max_len(path_nibbles, Some(args_len)).unwrap();
+                assert!(bytes.len() <= max_len, "{} > {max_len}", bytes.len());
+            }
+        }
+    }
+
+    #[test]
+    fn return_event_within_bound() {
+        let data = [0xAAu8; 300];
+        for data_len in [0, 1, 7, 8, 255, 300] {
+            let mut buf = [0u8; 512];
+            let bytes = ser_ok_event(
+                &mut buf,
+                u16::MAX,
+                EventKind::ReturnValue {
+                    data: RefVec::Slice {
+                        slice: &data[..data_len],
+                    },
+                },
+            )
+            .unwrap();
+            let max_len = value_event_max_len(Some(data_len)).unwrap();
+            assert!(bytes.len() <= max_len, "{} > {max_len}", bytes.len());
+        }
+    }
+}
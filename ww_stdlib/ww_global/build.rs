@@ -11,7 +11,7 @@ fn main() {
     write!(&mut wr, "#![no_std]\n\n").unwrap();
     write!(&mut wr, "mod gid;\n\n").unwrap();
     write!(&mut wr, "pub use gid::GlobalTypeId;\n\n").unwrap();
-    for (crate_name, gid) in ids {
+    for (crate_name, gid) in &ids {
         let crate_name = crate_name.to_case(Case::Constant);
         writeln!(
             &mut wr,
@@ -19,6 +19,21 @@ fn main() {
         )
         .unwrap();
     }
+    writeln!(&mut wr).unwrap();
+    writeln!(&mut wr, "/// Global type id assigned to a crate, if any.").unwrap();
+    writeln!(
+        &mut wr,
+        "pub fn by_crate_name(crate_name: &str) -> Option<GlobalTypeId> {{"
+    )
+    .unwrap();
+    writeln!(&mut wr, "    match crate_name {{").unwrap();
+    for (crate_name, _) in &ids {
+        let const_name = crate_name.to_case(Case::Constant);
+        writeln!(&mut wr, "        \"{crate_name}\" => Some({const_name}),").unwrap();
+    }
+    writeln!(&mut wr, "        _ => None,").unwrap();
+    writeln!(&mut wr, "    }}").unwrap();
+    writeln!(&mut wr, "}}").unwrap();
     std::fs::write("src/lib.rs", wr).unwrap();
 
     println!("cargo:rerun-if-changed=wire_weaver_gid.json");
@@ -7,3 +7,13 @@ pub use gid::GlobalTypeId;
 pub const WW_LOG_BARE_METAL: GlobalTypeId = GlobalTypeId::new(0);
 pub const WIRE_WEAVER_USB_LINK: GlobalTypeId = GlobalTypeId::new(512);
 pub const WW_CLIENT_SERVER: GlobalTypeId = GlobalTypeId::new(513);
+
+/// Global type id assigned to a crate, if any.
+pub fn by_crate_name(crate_name: &str) -> Option<GlobalTypeId> {
+    match crate_name {
+        "ww_log_bare_metal" => Some(WW_LOG_BARE_METAL),
+        "wire_weaver_usb_link" => Some(WIRE_WEAVER_USB_LINK),
+        "ww_client_server" => Some(WW_CLIENT_SERVER),
+        _ => None,
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     ApiBundleOwned, ApiItemKindOwned, ApiItemOwned, ApiLevelLocationOwned, ApiLevelOwned,
-    FieldsOwned, ItemEnumOwned, ItemStructOwned, Multiplicity, Repr, TypeLocationOwned, TypeOwned,
+    FieldsOwned, GenericInstanceOwned, ItemEnumOwned, ItemStructOwned, Multiplicity, Repr,
+    TypeLocationOwned, TypeOwned,
 };
 use anyhow::{anyhow, Result};
 use shrink_wrap::ElementSize;
@@ -77,6 +78,13 @@ impl ApiBundleOwned {
             .get(type_idx as usize)
             .ok_or(anyhow!("Bad ApiBundle: no type with index: {}", type_idx))?;
         if let TypeLocationOwned::InLine { ty, crate_idx } = location {
+            if let TypeOwned::OutOfLine { .. } = ty {
+                // could form a loop, resolving it forever
+                return Err(anyhow!(
+                    "Bad ApiBundle: type with index: {} refers to another out of line type",
+                    type_idx
+                ));
+            }
             Ok((ty, crate_idx.0))
         } else {
             Err(anyhow!(
@@ -146,6 +154,8 @@ impl TypeOwned {
             TypeOwned::Box(_) => Ok(true),
             TypeOwned::Range(_) => Ok(false),
             TypeOwned::RangeInclusive(_) => Ok(false),
+            TypeOwned::BoundedVec { ty, .. } => ty.is_lifetime(api_bundle),
+            TypeOwned::BoundedString { .. } => Ok(false),
         }
     }
 
@@ -160,27 +170,17 @@ impl TypeOwned {
             TypeOwned::Flag => Ok(false),
             TypeOwned::String => Ok(true),
             TypeOwned::Vec(_) => Ok(false), // Vec is UnsizedFinalStructure, see shrink_wrap::ElementSize
-            TypeOwned::Array { ty, .. } => ty.is_unsized(api_bundle),
-            TypeOwned::Tuple(types) => {
-                for ty in types {
-                    if ty.is_unsized(api_bundle)? {
-                        return Ok(true);
-                    }
-                }
-                Ok(false)
-            }
+            TypeOwned::Array { .. } => Ok(false), // UnsizedFinalStructure as well
+            TypeOwned::Tuple(_) => Ok(false), // UnsizedFinalStructure as well
             TypeOwned::Struct(item_struct) => Ok(item_struct.is_unsized()),
             TypeOwned::Enum(item_enum) => Ok(item_enum.is_unsized()),
-            TypeOwned::Option { some_ty } => some_ty.is_unsized(api_bundle),
-            TypeOwned::Result { ok_ty, err_ty } => {
-                if ok_ty.is_unsized(api_bundle)? {
-                    return Ok(true);
-                }
-                err_ty.is_unsized(api_bundle)
-            }
+            TypeOwned::Option { .. } => Ok(false), // SelfDescribing, see shrink_wrap::ElementSize
+            TypeOwned::Result { .. } => Ok(false), // SelfDescribing as well
             TypeOwned::Box(_) => Ok(true),
             TypeOwned::Range(_) => Ok(false),
             TypeOwned::RangeInclusive(_) => Ok(false),
+            TypeOwned::BoundedVec { .. } => Ok(false), // same as Vec
+            TypeOwned::BoundedString { .. } => Ok(true),
         }
     }
 
@@ -234,26 +234,28 @@ impl TypeOwned {
                 Ok(format!("({})", names.join(", ")))
             }
             TypeOwned::Struct(item_struct) => {
-                if show_crate_name {
-                    Ok(format!(
+                let name = if show_crate_name {
+                    format!(
                         "{}::{}",
                         api_bundle.crate_name(item_struct.crate_idx.0)?,
                         item_struct.ident
-                    ))
+                    )
                 } else {
-                    Ok(item_struct.ident.to_string())
-                }
+                    item_struct.ident.to_string()
+                };
+                with_generic_args(name, &item_struct.generics, show_crate_name, api_bundle)
             }
             TypeOwned::Enum(item_enum) => {
-                if show_crate_name {
-                    Ok(format!(
+                let name = if show_crate_name {
+                    format!(
                         "{}::{}",
                         api_bundle.crate_name(item_enum.crate_idx.0)?,
                         item_enum.ident
-                    ))
+                    )
                 } else {
-                    Ok(item_enum.ident.to_string())
-                }
+                    item_enum.ident.to_string()
+                };
+                with_generic_args(name, &item_enum.generics, show_crate_name, api_bundle)
             }
             TypeOwned::Option { some_ty } => Ok(format!(
                 "Option<{}>",
@@ -270,6 +272,12 @@ impl TypeOwned {
             )),
             TypeOwned::Range(base) => Ok(format!("Range<{}>", base.name())),
             TypeOwned::RangeInclusive(base) => Ok(format!("RangeInclusive<{}>", base.name())),
+            TypeOwned::BoundedVec { max_len, ty } => Ok(format!(
+                "BoundedVec<{}, {}>",
+                ty.human_name(show_crate_name, api_bundle)?,
+                max_len.0
+            )),
+            TypeOwned::BoundedString { max_len } => Ok(format!("BoundedString<{}>", max_len.0)),
         }
     }
 
@@ -304,11 +312,7 @@ impl TypeOwned {
                 Ok(format!("({})", names.join(", ")))
             }
             TypeOwned::Struct(item_struct) => {
-                let mut s = format!(
-                    "struct {}::{} {{",
-                    api_bundle.crate_name(item_struct.crate_idx.0)?,
-                    item_struct.ident
-                );
+                let mut s = format!("struct {} {{", self.human_name(true, api_bundle)?);
                 s +=
                     fields_human_definition(&item_struct.fields, api_bundle, single_line)?.as_str();
                 Ok(s)
@@ -322,11 +326,7 @@ impl TypeOwned {
                     Repr::ByteAlignedU16 => "u16".to_string(),
                     Repr::ByteAlignedU32 => "u32".to_string(),
                 };
-                let mut s = format!(
-                    "enum {repr} {}::{} {{",
-                    api_bundle.crate_name(item_enum.crate_idx.0)?,
-                    item_enum.ident
-                );
+                let mut s = format!("enum {repr} {} {{", self.human_name(true, api_bundle)?);
                 for (idx, variant) in item_enum.variants.iter().enumerate() {
                     s += &variant.ident;
                     s +=
@@ -353,10 +353,33 @@ impl TypeOwned {
             )),
             TypeOwned::Range(base) => Ok(format!("Range<{}>", base.name())),
             TypeOwned::RangeInclusive(base) => Ok(format!("RangeInclusive<{}>", base.name())),
+            TypeOwned::BoundedVec { max_len, ty } => Ok(format!(
+                "BoundedVec<{}, {}>",
+                ty.human_definition(api_bundle, single_line)?,
+                max_len.0
+            )),
+            TypeOwned::BoundedString { max_len } => Ok(format!("BoundedString<{}>", max_len.0)),
         }
     }
 }
 
+/// `name<arg1, arg2>` for instances of generic types, `name` otherwise.
+fn with_generic_args(
+    name: String,
+    generics: &Option<GenericInstanceOwned>,
+    show_crate_name: bool,
+    api_bundle: &ApiBundleOwned,
+) -> Result<String> {
+    let Some(generics) = generics else {
+        return Ok(name);
+    };
+    let mut args = Vec::with_capacity(generics.args.len());
+    for arg in &generics.args {
+        args.push(arg.human_name(show_crate_name, api_bundle)?);
+    }
+    Ok(format!("{name}<{}>", args.join(", ")))
+}
+
 fn fields_human_definition(
     fields: &FieldsOwned,
     api_bundle: &ApiBundleOwned,
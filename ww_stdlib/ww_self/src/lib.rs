@@ -18,7 +18,7 @@ use ww_numeric::NumericValue;
 use ww_version::FullVersionOwned;
 
 pub const MAGIC: u32 = 0xA91B_14F0;
-pub const VERSION: VersionTriplet = VersionTriplet::new(0, 1, 1); // TODO: Fill properly
+pub const VERSION: VersionTriplet = VersionTriplet::new(0, 2, 0); // keep in sync with the crate version
 
 // TODO: add doc
 // TODO: add ufs
@@ -108,6 +108,18 @@ pub struct ApiItem<'i> {
     pub since: Option<VersionTriplet>,
     pub ident: &'i str,
     pub docs: RefVec<'i, &'i str>,
+    pub deprecated: Option<Deprecated<'i>>,
+}
+
+/// From `#[deprecated(since = "x.y.z", note = "...")]` on an item, field or variant. Deprecated items can only be
+/// removed (replaced with `reserved!`) in a next major version.
+#[derive_shrink_wrap]
+#[derive(Clone, Debug, PartialEq)]
+#[owned = "std"]
+#[serde = "serde"]
+pub struct Deprecated<'i> {
+    pub since: Option<VersionTriplet>,
+    pub note: Option<&'i str>,
 }
 
 #[derive_shrink_wrap]
@@ -215,6 +227,13 @@ pub enum Type<'i> {
     Range(RefBox<'i, NumericBaseType>),
     /// Closed range `start..=end`
     RangeInclusive(RefBox<'i, NumericBaseType>),
+    /// `Vec<T>` with at most `max_len` elements, serialized in the same way
+    BoundedVec {
+        max_len: UNib32,
+        ty: RefBox<'i, Type<'i>>,
+    },
+    /// Unicode string with at most `max_len` bytes, serialized in the same way as `String`
+    BoundedString { max_len: UNib32 },
 }
 
 #[derive_shrink_wrap]
@@ -263,6 +282,7 @@ pub struct ItemStruct<'i> {
     pub docs: RefVec<'i, &'i str>,
     pub ident: &'i str,
     pub fields: Fields<'i>,
+    pub generics: Option<GenericInstance<'i>>,
 }
 
 #[derive_shrink_wrap]
@@ -280,6 +300,7 @@ pub struct Field<'i> {
     pub since: Option<VersionTriplet>,
     pub ty: Type<'i>,
     pub docs: RefVec<'i, &'i str>,
+    pub deprecated: Option<Deprecated<'i>>,
 }
 
 #[derive_shrink_wrap]
@@ -293,6 +314,22 @@ pub struct ItemEnum<'i> {
     pub docs: RefVec<'i, &'i str>,
     pub ident: &'i str,
     pub variants: RefVec<'i, Variant<'i>>,
+    pub generics: Option<GenericInstance<'i>>,
+}
+
+/// Instance of a generic struct or enum used in an API, e.g. `Measurement<f32>`. Each instance is a separate type,
+/// fields and variants of which already have all the type parameters substituted with the concrete types.
+#[derive_shrink_wrap]
+#[derive(Clone, Debug, PartialEq)]
+#[owned = "std"]
+#[serde = "serde"]
+pub struct GenericInstance<'i> {
+    /// Type parameter names in the definition order, e.g. `T`
+    pub params: RefVec<'i, &'i str>,
+    /// Concrete types used in place of the parameters, e.g. `f32`
+    pub args: RefVec<'i, Type<'i>>,
+    /// Whether the definition has a lifetime parameter (and an owned version), regardless of the arguments
+    pub lifetime: bool,
 }
 
 #[derive_shrink_wrap]
@@ -325,6 +362,7 @@ pub struct Variant<'i> {
     pub fields: Fields<'i>,
     pub discriminant: UNib32,
     pub since: Option<VersionTriplet>,
+    pub deprecated: Option<Deprecated<'i>>,
 }
 
 #[derive_shrink_wrap]
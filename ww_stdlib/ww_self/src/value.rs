@@ -1,6 +1,6 @@
 use crate::{ApiBundleOwned, FieldsOwned, FieldsValueOwned, Repr, TypeOwned, ValueOwned};
 use anyhow::{anyhow, Result};
-use shrink_wrap::{BufReader, BufWriter, Nibble};
+use shrink_wrap::{BufReader, BufWriter, Error as ShrinkWrapError, Nibble};
 use ww_numeric::{NumericAnyTypeOwned, NumericBaseType, NumericValue};
 
 impl ValueOwned {
@@ -10,7 +10,7 @@ impl ValueOwned {
         api_bundle: &ApiBundleOwned,
     ) -> Result<Self> {
         let mut rd = BufReader::new(bytes);
-        from_shrink_wrap_inner(&mut rd, ty, api_bundle)
+        from_shrink_wrap_inner(&mut rd, ty, api_bundle, 0)
     }
 
     pub fn ser_shrink_wrap_dyn(
@@ -35,7 +35,8 @@ impl ValueOwned {
             let ty = types
                 .next()
                 .ok_or(anyhow!("values and types must have the same length"))?;
-            to_shrink_wrap_inner(&mut wr, value, ty, api_bundle)?;
+            // serialized in the same way as fields of a struct
+            write(&mut wr, value, ty, api_bundle)?;
         }
         Ok(wr.finish_and_take()?.to_vec())
     }
@@ -84,6 +85,8 @@ impl ValueOwned {
             TypeOwned::RangeInclusive(base) => {
                 Ok(ValueOwned::RangeInclusive(base.default()..=base.default()))
             }
+            TypeOwned::BoundedVec { .. } => Ok(ValueOwned::Vec(vec![])),
+            TypeOwned::BoundedString { .. } => Ok(ValueOwned::String(String::new())),
         }
     }
 
@@ -114,13 +117,21 @@ impl ValueOwned {
     }
 }
 
-fn read(rd: &mut BufReader, ty: &TypeOwned, api_bundle: &ApiBundleOwned) -> Result<ValueOwned> {
+/// Limits nesting of values when deserializing dynamically.
+const MAX_DEPTH: u32 = 256;
+
+fn read(
+    rd: &mut BufReader,
+    ty: &TypeOwned,
+    api_bundle: &ApiBundleOwned,
+    depth: u32,
+) -> Result<ValueOwned> {
     if ty.is_unsized(api_bundle)? {
         let len = rd.read_unib32_rev()?;
         let mut rd = rd.split(len as usize)?;
-        from_shrink_wrap_inner(&mut rd, ty, api_bundle)
+        from_shrink_wrap_inner(&mut rd, ty, api_bundle, depth)
     } else {
-        from_shrink_wrap_inner(rd, ty, api_bundle)
+        from_shrink_wrap_inner(rd, ty, api_bundle, depth)
     }
 }
 
@@ -128,7 +139,12 @@ fn from_shrink_wrap_inner(
     rd: &mut BufReader,
     ty: &TypeOwned,
     api_bundle: &ApiBundleOwned,
+    depth: u32,
 ) -> Result<ValueOwned> {
+    if depth > MAX_DEPTH {
+        // malformed ApiBundle can contain self-referential types that do not consume any input
+        return Err(anyhow!("Maximum nesting depth of {MAX_DEPTH} exceeded"));
+    }
     match ty {
         TypeOwned::Bool => Ok(ValueOwned::Bool(rd.read_bool()?)),
         TypeOwned::NumericAny(numeric_any) => {
@@ -136,37 +152,36 @@ fn from_shrink_wrap_inner(
         }
         TypeOwned::OutOfLine { type_idx } => {
             let ty = api_bundle.get_ty(type_idx.0)?.0;
-            from_shrink_wrap_inner(rd, ty, api_bundle)
+            from_shrink_wrap_inner(rd, ty, api_bundle, depth + 1)
         }
-        // TypeOwned::Flag => {}
         TypeOwned::String => Ok(ValueOwned::String(rd.read_raw_str()?.to_string())),
-        TypeOwned::Vec(inner_ty) => {
+        TypeOwned::BoundedString { max_len } => {
+            let s = rd.read_raw_str()?;
+            check_max_len("String", s.len(), max_len.0)?;
+            Ok(ValueOwned::String(s.to_string()))
+        }
+        TypeOwned::Vec(inner_ty) | TypeOwned::BoundedVec { ty: inner_ty, .. } => {
             let len = rd.read_unib32_rev()?;
-            let mut items = vec![];
-            for _ in 0..len {
-                let value = read(rd, inner_ty, api_bundle)?;
-                items.push(value);
+            if let TypeOwned::BoundedVec { max_len, .. } = ty {
+                check_max_len("Vec", len as usize, max_len.0)?;
             }
+            let items = read_items(rd, len, inner_ty, api_bundle, depth + 1)?;
             Ok(ValueOwned::Vec(items))
         }
         TypeOwned::Array { len, ty } => {
-            let mut items = vec![];
-            for _ in 0..len.0 {
-                let value = read(rd, ty, api_bundle)?;
-                items.push(value);
-            }
+            let items = read_items(rd, len.0, ty, api_bundle, depth + 1)?;
             Ok(ValueOwned::Array(items))
         }
         TypeOwned::Tuple(types) => {
             let mut values = vec![];
             for ty in types {
-                let value = read(rd, ty, api_bundle)?;
+                let value = read(rd, ty, api_bundle, depth + 1)?;
                 values.push(value);
             }
             Ok(ValueOwned::Tuple(values))
         }
         TypeOwned::Struct(struct_def) => {
-            let fields = process_fields(rd, &struct_def.fields, api_bundle)?;
+            let fields = process_fields(rd, &struct_def.fields, api_bundle, depth + 1)?;
             Ok(ValueOwned::Struct { fields })
         }
         TypeOwned::Enum(enum_def) => {
@@ -178,14 +193,18 @@ fn from_shrink_wrap_inner(
                 Repr::ByteAlignedU16 => rd.read_u16()? as u32,
                 Repr::ByteAlignedU32 => rd.read_u32()?,
             };
-            let Some(variant) = enum_def.variants.get(discriminant as usize) else {
+            let Some(variant) = enum_def
+                .variants
+                .iter()
+                .find(|v| v.discriminant.0 == discriminant)
+            else {
                 return Err(anyhow!(
                     "Enum '{}' does not have variant: {}",
                     enum_def.ident,
                     discriminant
                 ));
             };
-            let fields = process_fields(rd, &variant.fields, api_bundle)?;
+            let fields = process_fields(rd, &variant.fields, api_bundle, depth + 1)?;
 
             Ok(ValueOwned::Enum {
                 variant: variant.ident.to_string(),
@@ -195,7 +214,7 @@ fn from_shrink_wrap_inner(
         TypeOwned::Option { some_ty } => {
             let is_some = rd.read_bool()?;
             if is_some {
-                let value = read(rd, some_ty, api_bundle)?;
+                let value = read(rd, some_ty, api_bundle, depth + 1)?;
                 Ok(ValueOwned::Option(Some(Box::new(value))))
             } else {
                 Ok(ValueOwned::Option(None))
@@ -204,17 +223,80 @@ fn from_shrink_wrap_inner(
         TypeOwned::Result { ok_ty, err_ty } => {
             let is_ok = rd.read_bool()?;
             if is_ok {
-                let value = read(rd, ok_ty, api_bundle)?;
+                let value = read(rd, ok_ty, api_bundle, depth + 1)?;
                 Ok(ValueOwned::Result(Ok(Box::new(value))))
             } else {
-                let value = read(rd, err_ty, api_bundle)?;
+                let value = read(rd, err_ty, api_bundle, depth + 1)?;
                 Ok(ValueOwned::Result(Err(Box::new(value))))
             }
         }
-        // TypeOwned::Box(_) => {}
-        // TypeOwned::Range(_) => {}
-        // TypeOwned::RangeInclusive(_) => {}
-        u => Err(anyhow::anyhow!("Unsupported type: {:?}", u)),
+        // Box<T> is serialized exactly as T, size is already handled by read()
+        TypeOwned::Box(inner_ty) => from_shrink_wrap_inner(rd, inner_ty, api_bundle, depth + 1),
+        TypeOwned::Range(base_ty) => {
+            let start = from_numeric_base(rd, base_ty)?;
+            let end = from_numeric_base(rd, base_ty)?;
+            Ok(ValueOwned::Range(start..end))
+        }
+        TypeOwned::RangeInclusive(base_ty) => {
+            let start = from_numeric_base(rd, base_ty)?;
+            let end = from_numeric_base(rd, base_ty)?;
+            Ok(ValueOwned::RangeInclusive(start..=end))
+        }
+        TypeOwned::Flag => Err(anyhow::anyhow!("Flag type cannot be read on its own")),
+    }
+}
+
+fn read_items(
+    rd: &mut BufReader,
+    len: u32,
+    ty: &TypeOwned,
+    api_bundle: &ApiBundleOwned,
+    depth: u32,
+) -> Result<Vec<ValueOwned>> {
+    if len > 1 && is_zero_sized(ty, api_bundle, 0)? {
+        // otherwise, few bytes of malformed input could produce billions of empty values
+        return Err(anyhow!("Vec or array of zero sized items is not supported"));
+    }
+    let mut items = vec![];
+    for _ in 0..len {
+        let value = read(rd, ty, api_bundle, depth)?;
+        items.push(value);
+    }
+    Ok(items)
+}
+
+/// Returns true if values of this type are serialized into zero bits.
+fn is_zero_sized(ty: &TypeOwned, api_bundle: &ApiBundleOwned, depth: u32) -> Result<bool> {
+    if depth > MAX_DEPTH {
+        return Err(anyhow!("Maximum nesting depth of {MAX_DEPTH} exceeded"));
+    }
+    match ty {
+        TypeOwned::OutOfLine { type_idx } => {
+            let ty = api_bundle.get_ty(type_idx.0)?.0;
+            is_zero_sized(ty, api_bundle, depth + 1)
+        }
+        TypeOwned::Array { len, ty } => Ok(len.0 == 0 || is_zero_sized(ty, api_bundle, depth + 1)?),
+        TypeOwned::Tuple(types) => {
+            for ty in types {
+                if !is_zero_sized(ty, api_bundle, depth + 1)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        // Unsized structs always have a size written
+        TypeOwned::Struct(item_struct) if !item_struct.is_unsized() => match &item_struct.fields {
+            FieldsOwned::Named(fields) | FieldsOwned::Unnamed(fields) => {
+                for field in fields {
+                    if !is_zero_sized(&field.ty, api_bundle, depth + 1)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            FieldsOwned::Unit => Ok(true),
+        },
+        _ => Ok(false),
     }
 }
 
@@ -222,13 +304,14 @@ fn process_fields(
     rd: &mut BufReader,
     fields: &FieldsOwned,
     api_bundle: &ApiBundleOwned,
+    depth: u32,
 ) -> Result<FieldsValueOwned> {
     let fields = match fields {
         FieldsOwned::Named(fields_def) | FieldsOwned::Unnamed(fields_def) => {
             let mut fields_named = vec![];
             let mut fields_unnamed = vec![];
             for field_def in fields_def {
-                let value = read(rd, &field_def.ty, api_bundle)?;
+                let value = read(rd, &field_def.ty, api_bundle, depth)?;
                 if let Some(name) = &field_def.ident {
                     fields_named.push((name.clone(), value));
                 } else {
@@ -248,45 +331,74 @@ fn process_fields(
 
 fn from_numeric_any(rd: &mut BufReader, numeric_ty: &NumericAnyTypeOwned) -> Result<NumericValue> {
     match numeric_ty {
-        NumericAnyTypeOwned::Base(base_ty) => match base_ty {
-            NumericBaseType::Nibble => {
-                let nib: Nibble = rd.read()?;
-                Ok(NumericValue::Nibble(nib))
-            }
-            NumericBaseType::U8 => Ok(NumericValue::U8(rd.read_u8()?)),
-            NumericBaseType::U16 => Ok(NumericValue::U16(rd.read_u16()?)),
-            NumericBaseType::U32 => Ok(NumericValue::U32(rd.read_u32()?)),
-            NumericBaseType::UNib32 => Ok(NumericValue::UNib32(rd.read_unib32()?)),
-            NumericBaseType::U64 => Ok(NumericValue::U64(rd.read_u64()?)),
-            NumericBaseType::I32 => Ok(NumericValue::I32(rd.read_i32()?)),
-            NumericBaseType::F32 => Ok(NumericValue::F32(rd.read_f32()?)),
-            NumericBaseType::U128 => Ok(NumericValue::U128(rd.read_u128()?)),
-            NumericBaseType::I8 => Ok(NumericValue::I8(rd.read_i8()?)),
-            NumericBaseType::I16 => Ok(NumericValue::I16(rd.read_i16()?)),
-            NumericBaseType::I64 => Ok(NumericValue::I64(rd.read_i64()?)),
-            NumericBaseType::I128 => Ok(NumericValue::I128(rd.read_i128()?)),
-            // NumericBaseType::F16 => Ok(NumericValue::F16(rd.read_u8()?)),
-            NumericBaseType::F64 => Ok(NumericValue::F64(rd.read_f64()?)),
-            // NumericBaseType::UB(bits) => Ok(NumericValue::UB)
-            // NumericBaseType::IB(bits) => {}
-            // NumericBaseType::UN => {}
-            // NumericBaseType::IN => {}
-            // NumericBaseType::ULeb32 => {}
-            // NumericBaseType::ULeb64 => {}
-            // NumericBaseType::ULeb128 => {}
-            // NumericBaseType::ILeb32 => {}
-            // NumericBaseType::ILeb64 => {}
-            // NumericBaseType::ILeb128 => {}
-            // NumericBaseType::UQ { .. } => {}
-            // NumericBaseType::IQ { .. } => {}
-            u => Err(anyhow::anyhow!("Unsupported numeric base type: {:?}", u)),
-        },
+        NumericAnyTypeOwned::Base(base_ty) => from_numeric_base(rd, base_ty),
         // NumericAnyTypeOwned::SubType { .. } => {}
         // NumericAnyTypeOwned::ShiftScale { .. } => {}
         u => Err(anyhow::anyhow!("Unsupported numeric type: {:?}", u)),
     }
 }
 
+fn from_numeric_base(rd: &mut BufReader, base_ty: &NumericBaseType) -> Result<NumericValue> {
+    match base_ty {
+        NumericBaseType::Nibble => {
+            let nib: Nibble = rd.read()?;
+            Ok(NumericValue::Nibble(nib))
+        }
+        NumericBaseType::U8 => Ok(NumericValue::U8(rd.read_u8()?)),
+        NumericBaseType::U16 => Ok(NumericValue::U16(rd.read_u16()?)),
+        NumericBaseType::U32 => Ok(NumericValue::U32(rd.read_u32()?)),
+        NumericBaseType::UNib32 => Ok(NumericValue::UNib32(rd.read_unib32()?)),
+        NumericBaseType::U64 => Ok(NumericValue::U64(rd.read_u64()?)),
+        NumericBaseType::I32 => Ok(NumericValue::I32(rd.read_i32()?)),
+        NumericBaseType::F32 => Ok(NumericValue::F32(rd.read_f32()?)),
+        NumericBaseType::U128 => Ok(NumericValue::U128(rd.read_u128()?)),
+        NumericBaseType::I8 => Ok(NumericValue::I8(rd.read_i8()?)),
+        NumericBaseType::I16 => Ok(NumericValue::I16(rd.read_i16()?)),
+        NumericBaseType::I64 => Ok(NumericValue::I64(rd.read_i64()?)),
+        NumericBaseType::I128 => Ok(NumericValue::I128(rd.read_i128()?)),
+        // NumericBaseType::F16 => Ok(NumericValue::F16(rd.read_u8()?)),
+        NumericBaseType::F64 => Ok(NumericValue::F64(rd.read_f64()?)),
+        // NumericBaseType::UB(bits) => Ok(NumericValue::UB)
+        // NumericBaseType::IB(bits) => {}
+        // NumericBaseType::UN => {}
+        // NumericBaseType::IN => {}
+        // NumericBaseType::ULeb32 => {}
+        // NumericBaseType::ULeb64 => {}
+        // NumericBaseType::ULeb128 => {}
+        // NumericBaseType::ILeb32 => {}
+        // NumericBaseType::ILeb64 => {}
+        // NumericBaseType::ILeb128 => {}
+        // NumericBaseType::UQ { .. } => {}
+        // NumericBaseType::IQ { .. } => {}
+        u => Err(anyhow::anyhow!("Unsupported numeric base type: {:?}", u)),
+    }
+}
+
+fn write(
+    wr: &mut BufWriter,
+    value: &ValueOwned,
+    ty: &TypeOwned,
+    api_bundle: &ApiBundleOwned,
+) -> Result<()> {
+    if ty.is_unsized(api_bundle)? {
+        // same as BufWriter::write
+        wr.align_byte();
+        let size_slot_pos = wr.write_u16_rev(0)?;
+        let unsized_start_idx = wr.pos().0;
+        to_shrink_wrap_inner(wr, value, ty, api_bundle)?;
+        wr.encode_nib16_rev(wr.u16_rev_pos(), size_slot_pos)?;
+        wr.align_byte();
+        let size_bytes = wr.pos().0 - unsized_start_idx;
+        let Ok(size_bytes) = u16::try_from(size_bytes) else {
+            return Err(ShrinkWrapError::ItemTooLong.into());
+        };
+        wr.update_u16_rev(size_slot_pos, size_bytes)?;
+        Ok(())
+    } else {
+        to_shrink_wrap_inner(wr, value, ty, api_bundle)
+    }
+}
+
 fn to_shrink_wrap_inner(
     wr: &mut BufWriter,
     value: &ValueOwned,
@@ -294,15 +406,80 @@ fn to_shrink_wrap_inner(
     api_bundle: &ApiBundleOwned,
 ) -> Result<()> {
     let ty = ty.get_in_line(api_bundle)?;
+    if let TypeOwned::Box(inner_ty) = ty {
+        // Box<T> is serialized exactly as T
+        return to_shrink_wrap_inner(wr, value, inner_ty, api_bundle);
+    }
     match value {
         ValueOwned::Bool(value) => {
+            let TypeOwned::Bool = ty else {
+                return Err(anyhow::anyhow!("Bool type expected"));
+            };
             wr.write_bool(*value)?;
         }
-        ValueOwned::Numeric(_) => {}
-        ValueOwned::String(_) => {}
-        ValueOwned::Vec(_) => {}
-        ValueOwned::Array(_) => {}
-        ValueOwned::Tuple(_) => {}
+        ValueOwned::Numeric(value) => {
+            let TypeOwned::NumericAny(numeric_ty) = ty else {
+                return Err(anyhow::anyhow!("Numeric type expected"));
+            };
+            to_numeric_any(wr, value, numeric_ty)?;
+        }
+        ValueOwned::String(s) => {
+            match ty {
+                TypeOwned::String => {}
+                TypeOwned::BoundedString { max_len } => {
+                    check_max_len("String", s.len(), max_len.0)?;
+                }
+                _ => return Err(anyhow::anyhow!("String type expected")),
+            }
+            wr.write_raw_str(s)?;
+        }
+        ValueOwned::Vec(items) => {
+            let inner_ty = match ty {
+                TypeOwned::Vec(inner_ty) => inner_ty,
+                TypeOwned::BoundedVec { max_len, ty } => {
+                    check_max_len("Vec", items.len(), max_len.0)?;
+                    ty
+                }
+                _ => return Err(anyhow::anyhow!("Vec type expected")),
+            };
+            let Ok(len) = u16::try_from(items.len()) else {
+                return Err(ShrinkWrapError::VecTooLong.into());
+            };
+            wr.write_u16_rev(len)?;
+            for item in items {
+                write(wr, item, inner_ty, api_bundle)?;
+            }
+        }
+        ValueOwned::Array(items) => {
+            let TypeOwned::Array { len, ty } = ty else {
+                return Err(anyhow::anyhow!("Array type expected"));
+            };
+            if items.len() != len.0 as usize {
+                return Err(anyhow::anyhow!(
+                    "Array of length {} expected, got {}",
+                    len.0,
+                    items.len()
+                ));
+            }
+            for item in items {
+                write(wr, item, ty, api_bundle)?;
+            }
+        }
+        ValueOwned::Tuple(values) => {
+            let TypeOwned::Tuple(types) = ty else {
+                return Err(anyhow::anyhow!("Tuple type expected"));
+            };
+            if values.len() != types.len() {
+                return Err(anyhow::anyhow!(
+                    "Tuple of length {} expected, got {}",
+                    types.len(),
+                    values.len()
+                ));
+            }
+            for (value, ty) in values.iter().zip(types) {
+                write(wr, value, ty, api_bundle)?;
+            }
+        }
         ValueOwned::Struct { fields } => {
             let TypeOwned::Struct(item_struct) = ty else {
                 return Err(anyhow::anyhow!("Struct type expected"));
@@ -373,12 +550,38 @@ fn to_shrink_wrap_inner(
             };
             wr.write_bool(value.is_some())?;
             if let Some(value) = value {
-                to_shrink_wrap_inner(wr, value, some_ty, api_bundle)?;
+                write(wr, value, some_ty, api_bundle)?;
             }
         }
-        ValueOwned::Result(_) => {}
-        ValueOwned::Range(_) => {}
-        ValueOwned::RangeInclusive(_) => {}
+        ValueOwned::Result(value) => {
+            let TypeOwned::Result { ok_ty, err_ty } = ty else {
+                return Err(anyhow::anyhow!("Result type expected"));
+            };
+            match value {
+                Ok(value) => {
+                    wr.write_bool(true)?;
+                    write(wr, value, ok_ty, api_bundle)?;
+                }
+                Err(value) => {
+                    wr.write_bool(false)?;
+                    write(wr, value, err_ty, api_bundle)?;
+                }
+            }
+        }
+        ValueOwned::Range(range) => {
+            let TypeOwned::Range(base) = ty else {
+                return Err(anyhow::anyhow!("Range type expected"));
+            };
+            to_numeric_base(wr, &range.start, base)?;
+            to_numeric_base(wr, &range.end, base)?;
+        }
+        ValueOwned::RangeInclusive(range) => {
+            let TypeOwned::RangeInclusive(base) = ty else {
+                return Err(anyhow::anyhow!("RangeInclusive type expected"));
+            };
+            to_numeric_base(wr, range.start(), base)?;
+            to_numeric_base(wr, range.end(), base)?;
+        }
     }
     Ok(())
 }
@@ -396,8 +599,13 @@ fn to_shrink_wrap_fields(
                     "Fields type mismatch between definition and value"
                 ));
             };
+            if fields_def.len() != named.len() {
+                return Err(anyhow::anyhow!(
+                    "Fields count mismatch between definition and value"
+                ));
+            }
             for (def, (_name, value)) in fields_def.iter().zip(named) {
-                to_shrink_wrap_inner(wr, value, &def.ty, api_bundle)?;
+                write(wr, value, &def.ty, api_bundle)?;
             }
         }
         FieldsValueOwned::Unnamed(unnamed) => {
@@ -406,11 +614,68 @@ fn to_shrink_wrap_fields(
                     "Fields type mismatch between definition and value"
                 ));
             };
+            if fields_def.len() != unnamed.len() {
+                return Err(anyhow::anyhow!(
+                    "Fields count mismatch between definition and value"
+                ));
+            }
             for (def, value) in fields_def.iter().zip(unnamed) {
-                to_shrink_wrap_inner(wr, value, &def.ty, api_bundle)?;
+                write(wr, value, &def.ty, api_bundle)?;
             }
         }
         FieldsValueOwned::Unit => {}
     }
     Ok(())
 }
+
+fn to_numeric_any(
+    wr: &mut BufWriter,
+    value: &NumericValue,
+    numeric_ty: &NumericAnyTypeOwned,
+) -> Result<()> {
+    match numeric_ty {
+        NumericAnyTypeOwned::Base(base_ty) => to_numeric_base(wr, value, base_ty),
+        u => Err(anyhow::anyhow!("Unsupported numeric type: {:?}", u)),
+    }
+}
+
+fn to_numeric_base(
+    wr: &mut BufWriter,
+    value: &NumericValue,
+    base_ty: &NumericBaseType,
+) -> Result<()> {
+    match (base_ty, value) {
+        (NumericBaseType::Nibble, NumericValue::Nibble(value)) => wr.write_nib(*value)?,
+        (NumericBaseType::U8, NumericValue::U8(value)) => wr.write_u8(*value)?,
+        (NumericBaseType::U16, NumericValue::U16(value)) => wr.write_u16(*value)?,
+        (NumericBaseType::U32, NumericValue::U32(value)) => wr.write_u32(*value)?,
+        (NumericBaseType::UNib32, NumericValue::UNib32(value)) => wr.write_unib32(*value)?,
+        (NumericBaseType::U64, NumericValue::U64(value)) => wr.write_u64(*value)?,
+        (NumericBaseType::I32, NumericValue::I32(value)) => wr.write_i32(*value)?,
+        (NumericBaseType::F32, NumericValue::F32(value)) => wr.write_f32(*value)?,
+        (NumericBaseType::U128, NumericValue::U128(value)) => wr.write_u128(*value)?,
+        (NumericBaseType::I8, NumericValue::I8(value)) => wr.write_i8(*value)?,
+        (NumericBaseType::I16, NumericValue::I16(value)) => wr.write_i16(*value)?,
+        (NumericBaseType::I64, NumericValue::I64(value)) => wr.write_i64(*value)?,
+        (NumericBaseType::I128, NumericValue::I128(value)) => wr.write_i128(*value)?,
+        (NumericBaseType::F64, NumericValue::F64(value)) => wr.write_f64(*value)?,
+        (base_ty, value) => {
+            return Err(anyhow::anyhow!(
+                "Unsupported numeric value {:?} for type {:?}",
+                value,
+                base_ty
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check String or Vec length against BoundedString or BoundedVec bound.
+fn check_max_len(kind: &str, len: usize, max_len: u32) -> Result<()> {
+    if len > max_len as usize {
+        return Err(anyhow!(
+            "{kind} of length {len} exceeds maximum length of {max_len}"
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,742 @@
+use core::ops::Range;
+use proptest::prelude::*;
+use shrink_wrap::prelude::*;
+use ww_numeric::{NumericAnyTypeOwned, NumericBaseType, NumericValue};
+use ww_self::{
+    ApiBundleOwned, ApiLevelOwned, FieldOwned, FieldsOwned, FieldsValueOwned, ItemEnumOwned,
+    ItemStructOwned, MAGIC, Repr, TypeLocationOwned, TypeOwned, VERSION, ValueOwned, VariantOwned,
+};
+
+fn empty_bundle() -> ApiBundleOwned {
+    ApiBundleOwned {
+        magic: MAGIC,
+        ww_self_version: VERSION,
+        root: ApiLevelOwned {
+            docs: vec![],
+            crate_idx: UNib32(0),
+            trait_name: String::new(),
+            items: vec![],
+        },
+        types: vec![],
+        traits: vec![],
+        ext_crates: vec![],
+    }
+}
+
+fn numeric(base: NumericBaseType) -> TypeOwned {
+    TypeOwned::NumericAny(NumericAnyTypeOwned::Base(base))
+}
+
+fn field(ident: Option<String>, ty: TypeOwned) -> FieldOwned {
+    FieldOwned {
+        ident,
+        default: None,
+        since: None,
+        ty,
+        docs: vec![],
+        deprecated: None,
+    }
+}
+
+fn named_fields(types: Vec<TypeOwned>) -> FieldsOwned {
+    FieldsOwned::Named(
+        types
+            .into_iter()
+            .enumerate()
+            .map(|(idx, ty)| field(Some(format!("f{idx}")), ty))
+            .collect(),
+    )
+}
+
+fn item_struct(size: ElementSize, fields: FieldsOwned) -> TypeOwned {
+    TypeOwned::Struct(ItemStructOwned {
+        size,
+        crate_idx: UNib32(0),
+        docs: vec![],
+        ident: "S".into(),
+        fields,
+        generics: None,
+    })
+}
+
+fn variant(ident: &str, discriminant: u32, fields: FieldsOwned) -> VariantOwned {
+    VariantOwned {
+        docs: vec![],
+        ident: ident.into(),
+        fields,
+        discriminant: UNib32(discriminant),
+        since: None,
+        deprecated: None,
+    }
+}
+
+fn item_enum(repr: Repr, variants: Vec<VariantOwned>) -> TypeOwned {
+    TypeOwned::Enum(ItemEnumOwned {
+        size: ElementSize::Unsized,
+        repr,
+        crate_idx: UNib32(0),
+        docs: vec![],
+        ident: "E".into(),
+        variants,
+        generics: None,
+    })
+}
+
+/// Type description and dynamic value of a statically known type.
+trait Described {
+    fn ty() -> TypeOwned;
+    fn to_value(&self) -> ValueOwned;
+}
+
+macro_rules! described_numeric {
+    ($($ty:ty => $base:ident),*) => {
+        $(
+            impl Described for $ty {
+                fn ty() -> TypeOwned {
+                    numeric(NumericBaseType::$base)
+                }
+
+                fn to_value(&self) -> ValueOwned {
+                    ValueOwned::Numeric(NumericValue::$base(*self))
+                }
+            }
+        )*
+    };
+}
+
+described_numeric!(u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128, i8 => I8, i16 => I16,
+    i32 => I32, i64 => I64, i128 => I128, f32 => F32, f64 => F64, Nibble => Nibble);
+
+impl Described for UNib32 {
+    fn ty() -> TypeOwned {
+        numeric(NumericBaseType::UNib32)
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Numeric(NumericValue::UNib32(self.0))
+    }
+}
+
+impl Described for bool {
+    fn ty() -> TypeOwned {
+        TypeOwned::Bool
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Bool(*self)
+    }
+}
+
+impl Described for String {
+    fn ty() -> TypeOwned {
+        TypeOwned::String
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::String(self.clone())
+    }
+}
+
+impl<T: Described> Described for Vec<T> {
+    fn ty() -> TypeOwned {
+        TypeOwned::Vec(Box::new(T::ty()))
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Vec(self.iter().map(|v| v.to_value()).collect())
+    }
+}
+
+impl<T: Described, const N: usize> Described for [T; N] {
+    fn ty() -> TypeOwned {
+        TypeOwned::Array {
+            len: UNib32(N as u32),
+            ty: Box::new(T::ty()),
+        }
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Array(self.iter().map(|v| v.to_value()).collect())
+    }
+}
+
+impl<T: Described> Described for Option<T> {
+    fn ty() -> TypeOwned {
+        TypeOwned::Option {
+            some_ty: Box::new(T::ty()),
+        }
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Option(self.as_ref().map(|v| Box::new(v.to_value())))
+    }
+}
+
+impl<T: Described, E: Described> Described for Result<T, E> {
+    fn ty() -> TypeOwned {
+        TypeOwned::Result {
+            ok_ty: Box::new(T::ty()),
+            err_ty: Box::new(E::ty()),
+        }
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Result(match self {
+            Ok(v) => Ok(Box::new(v.to_value())),
+            Err(e) => Err(Box::new(e.to_value())),
+        })
+    }
+}
+
+impl<A: Described, B: Described> Described for (A, B) {
+    fn ty() -> TypeOwned {
+        TypeOwned::Tuple(vec![A::ty(), B::ty()])
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Tuple(vec![self.0.to_value(), self.1.to_value()])
+    }
+}
+
+impl Described for Range<u16> {
+    fn ty() -> TypeOwned {
+        TypeOwned::Range(Box::new(NumericBaseType::U16))
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Range(NumericValue::U16(self.start)..NumericValue::U16(self.end))
+    }
+}
+
+impl<const N: usize> Described for BoundedString<N> {
+    fn ty() -> TypeOwned {
+        TypeOwned::BoundedString {
+            max_len: UNib32(N as u32),
+        }
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::String(self.as_str().to_string())
+    }
+}
+
+impl<T: Described + Default, const N: usize> Described for BoundedVec<T, N> {
+    fn ty() -> TypeOwned {
+        TypeOwned::BoundedVec {
+            max_len: UNib32(N as u32),
+            ty: Box::new(T::ty()),
+        }
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Vec(self.iter().map(|v| v.to_value()).collect())
+    }
+}
+
+#[derive_shrink_wrap]
+#[derive(Debug, Clone, PartialEq)]
+struct Inner {
+    label: String,
+    enabled: bool,
+}
+
+impl Described for Inner {
+    fn ty() -> TypeOwned {
+        item_struct(
+            ElementSize::Unsized,
+            named_fields(vec![String::ty(), bool::ty()]),
+        )
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        ValueOwned::Struct {
+            fields: FieldsValueOwned::Named(vec![
+                ("f0".into(), self.label.to_value()),
+                ("f1".into(), self.enabled.to_value()),
+            ]),
+        }
+    }
+}
+
+#[derive_shrink_wrap]
+#[ww_repr(unib32)]
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Stop,
+    Move { x: i32, y: Option<i32> },
+    Configure(Inner, Vec<u8>),
+}
+
+impl Described for Command {
+    fn ty() -> TypeOwned {
+        item_enum(
+            Repr::UNib32,
+            vec![
+                variant("Stop", 0, FieldsOwned::Unit),
+                variant(
+                    "Move",
+                    1,
+                    named_fields(vec![i32::ty(), Option::<i32>::ty()]),
+                ),
+                variant(
+                    "Configure",
+                    2,
+                    FieldsOwned::Unnamed(vec![
+                        field(None, Inner::ty()),
+                        field(None, Vec::<u8>::ty()),
+                    ]),
+                ),
+            ],
+        )
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        let (variant, fields) = match self {
+            Command::Stop => ("Stop", FieldsValueOwned::Unit),
+            Command::Move { x, y } => (
+                "Move",
+                FieldsValueOwned::Named(vec![
+                    ("f0".into(), x.to_value()),
+                    ("f1".into(), y.to_value()),
+                ]),
+            ),
+            Command::Configure(inner, data) => (
+                "Configure",
+                FieldsValueOwned::Unnamed(vec![inner.to_value(), data.to_value()]),
+            ),
+        };
+        ValueOwned::Enum {
+            variant: variant.into(),
+            fields,
+        }
+    }
+}
+
+#[derive_shrink_wrap]
+#[derive(Debug, Clone, PartialEq)]
+struct Everything {
+    a: u8,
+    flag: bool,
+    nib: Nibble,
+    b: UNib32,
+    c: Option<f32>,
+    name: String,
+    status: Result<u16, String>,
+    array: [Option<i8>; 3],
+    pair: (i64, String),
+    list: Vec<Inner>,
+    range: Range<u16>,
+    command: Command,
+    bounded_name: BoundedString<8>,
+    bounded_list: BoundedVec<u16, 4>,
+    wide: (u128, i128),
+    double: f64,
+    small: (i16, u64),
+}
+
+impl Described for Everything {
+    fn ty() -> TypeOwned {
+        item_struct(
+            ElementSize::Unsized,
+            named_fields(vec![
+                u8::ty(),
+                bool::ty(),
+                Nibble::ty(),
+                UNib32::ty(),
+                Option::<f32>::ty(),
+                String::ty(),
+                Result::<u16, String>::ty(),
+                <[Option<i8>; 3]>::ty(),
+                <(i64, String)>::ty(),
+                Vec::<Inner>::ty(),
+                Range::<u16>::ty(),
+                Command::ty(),
+                BoundedString::<8>::ty(),
+                BoundedVec::<u16, 4>::ty(),
+                <(u128, i128)>::ty(),
+                f64::ty(),
+                <(i16, u64)>::ty(),
+            ]),
+        )
+    }
+
+    fn to_value(&self) -> ValueOwned {
+        let values = vec![
+            self.a.to_value(),
+            self.flag.to_value(),
+            self.nib.to_value(),
+            self.b.to_value(),
+            self.c.to_value(),
+            self.name.to_value(),
+            self.status.to_value(),
+            self.array.to_value(),
+            self.pair.to_value(),
+            self.list.to_value(),
+            self.range.to_value(),
+            self.command.to_value(),
+            self.bounded_name.to_value(),
+            self.bounded_list.to_value(),
+            self.wide.to_value(),
+            self.double.to_value(),
+            self.small.to_value(),
+        ];
+        ValueOwned::Struct {
+            fields: FieldsValueOwned::Named(
+                values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, v)| (format!("f{idx}"), v))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn nibble() -> impl Strategy<Value = Nibble> {
+    (0u8..=15).prop_map(Nibble::new_masked)
+}
+
+fn inner() -> impl Strategy<Value = Inner> {
+    (".{0,8}", any::<bool>()).prop_map(|(label, enabled)| Inner { label, enabled })
+}
+
+fn command() -> impl Strategy<Value = Command> {
+    prop_oneof![
+        Just(Command::Stop),
+        (any::<i32>(), any::<Option<i32>>()).prop_map(|(x, y)| Command::Move { x, y }),
+        (inner(), prop::collection::vec(any::<u8>(), 0..8))
+            .prop_map(|(inner, data)| Command::Configure(inner, data)),
+    ]
+}
+
+fn everything() -> impl Strategy<Value = Everything> {
+    (
+        (
+            any::<u8>(),
+            any::<bool>(),
+            nibble(),
+            any::<u32>(),
+            proptest::option::of(-1.0e6f32..1.0e6),
+            ".{0,16}",
+            prop_oneof![any::<u16>().prop_map(Ok), ".{0,4}".prop_map(Err)],
+            any::<[Option<i8>; 3]>(),
+            (any::<i64>(), ".{0,4}"),
+        ),
+        (
+            prop::collection::vec(inner(), 0..4),
+            any::<u16>(),
+            command(),
+            "[a-z]{0,8}",
+            prop::collection::vec(any::<u16>(), 0..=4),
+            any::<(u128, i128)>(),
+            -1.0e12f64..1.0e12,
+            any::<(i16, u64)>(),
+        ),
+    )
+        .prop_map(
+            |(
+                (a, flag, nib, b, c, name, status, array, pair),
+                (list, range_end, command, bounded_name, bounded_list, wide, double, small),
+            )| Everything {
+                a,
+                flag,
+                nib,
+                b: UNib32(b),
+                c,
+                name,
+                status,
+                array,
+                pair,
+                list,
+                range: 0..range_end,
+                command,
+                bounded_name: BoundedString::try_from(bounded_name.as_str()).unwrap(),
+                bounded_list: BoundedVec::from_slice(&bounded_list).unwrap(),
+                wide,
+                double,
+                small,
+            },
+        )
+}
+
+fn numeric_base() -> impl Strategy<Value = NumericBaseType> {
+    prop_oneof![
+        Just(NumericBaseType::Nibble),
+        Just(NumericBaseType::U8),
+        Just(NumericBaseType::U16),
+        Just(NumericBaseType::U32),
+        Just(NumericBaseType::UNib32),
+        Just(NumericBaseType::U64),
+        Just(NumericBaseType::U128),
+        Just(NumericBaseType::I8),
+        Just(NumericBaseType::I16),
+        Just(NumericBaseType::I32),
+        Just(NumericBaseType::I64),
+        Just(NumericBaseType::I128),
+        Just(NumericBaseType::F32),
+        Just(NumericBaseType::F64),
+    ]
+}
+
+fn repr() -> impl Strategy<Value = Repr> {
+    prop_oneof![
+        Just(Repr::Nibble),
+        (3u8..=8).prop_map(Repr::BitAligned),
+        Just(Repr::UNib32),
+        Just(Repr::ByteAlignedU8),
+    ]
+}
+
+fn element_size() -> impl Strategy<Value = ElementSize> {
+    prop_oneof![
+        Just(ElementSize::Unsized),
+        Just(ElementSize::UnsizedFinalStructure),
+    ]
+}
+
+/// Random type description, up to a few levels deep. Zero sized types are not generated, as they are not supported
+/// in collections by the dynamic deserializer.
+fn ty() -> impl Strategy<Value = TypeOwned> {
+    let leaf = prop_oneof![
+        Just(TypeOwned::Bool),
+        numeric_base().prop_map(numeric),
+        Just(TypeOwned::String),
+        (0u32..8).prop_map(|max_len| TypeOwned::BoundedString {
+            max_len: UNib32(max_len)
+        }),
+        numeric_base().prop_map(|base| TypeOwned::Range(Box::new(base))),
+        numeric_base().prop_map(|base| TypeOwned::RangeInclusive(Box::new(base))),
+    ];
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        prop_oneof![
+            inner.clone().prop_map(|ty| TypeOwned::Vec(Box::new(ty))),
+            (0u32..4, inner.clone()).prop_map(|(max_len, ty)| TypeOwned::BoundedVec {
+                max_len: UNib32(max_len),
+                ty: Box::new(ty)
+            }),
+            (1u32..4, inner.clone()).prop_map(|(len, ty)| TypeOwned::Array {
+                len: UNib32(len),
+                ty: Box::new(ty)
+            }),
+            prop::collection::vec(inner.clone(), 1..4).prop_map(TypeOwned::Tuple),
+            inner.clone().prop_map(|ty| TypeOwned::Option {
+                some_ty: Box::new(ty)
+            }),
+            (inner.clone(), inner.clone()).prop_map(|(ok_ty, err_ty)| TypeOwned::Result {
+                ok_ty: Box::new(ok_ty),
+                err_ty: Box::new(err_ty)
+            }),
+            inner.clone().prop_map(|ty| TypeOwned::Box(Box::new(ty))),
+            (element_size(), prop::collection::vec(inner.clone(), 1..4))
+                .prop_map(|(size, types)| item_struct(size, named_fields(types))),
+            (repr(), prop::collection::vec(inner, 0..3)).prop_map(|(repr, types)| {
+                let mut variants = vec![variant("Unit", 0, FieldsOwned::Unit)];
+                for (idx, ty) in types.into_iter().enumerate() {
+                    let fields = FieldsOwned::Unnamed(vec![field(None, ty)]);
+                    // discriminants do not have to be sequential
+                    variants.push(variant(&format!("V{idx}"), idx as u32 * 3 + 1, fields));
+                }
+                item_enum(repr, variants)
+            }),
+        ]
+    })
+}
+
+fn numeric_value(base: &NumericBaseType) -> BoxedStrategy<NumericValue> {
+    match base {
+        NumericBaseType::Nibble => nibble().prop_map(NumericValue::Nibble).boxed(),
+        NumericBaseType::U8 => any::<u8>().prop_map(NumericValue::U8).boxed(),
+        NumericBaseType::U16 => any::<u16>().prop_map(NumericValue::U16).boxed(),
+        NumericBaseType::U32 => any::<u32>().prop_map(NumericValue::U32).boxed(),
+        NumericBaseType::UNib32 => any::<u32>().prop_map(NumericValue::UNib32).boxed(),
+        NumericBaseType::U64 => any::<u64>().prop_map(NumericValue::U64).boxed(),
+        NumericBaseType::U128 => any::<u128>().prop_map(NumericValue::U128).boxed(),
+        NumericBaseType::I8 => any::<i8>().prop_map(NumericValue::I8).boxed(),
+        NumericBaseType::I16 => any::<i16>().prop_map(NumericValue::I16).boxed(),
+        NumericBaseType::I32 => any::<i32>().prop_map(NumericValue::I32).boxed(),
+        NumericBaseType::I64 => any::<i64>().prop_map(NumericValue::I64).boxed(),
+        NumericBaseType::I128 => any::<i128>().prop_map(NumericValue::I128).boxed(),
+        // NaN != NaN, so only finite values are generated
+        NumericBaseType::F32 => (-1.0e6f32..1.0e6).prop_map(NumericValue::F32).boxed(),
+        NumericBaseType::F64 => (-1.0e12f64..1.0e12).prop_map(NumericValue::F64).boxed(),
+        u => unreachable!("{u:?} is not generated"),
+    }
+}
+
+fn fields_value(fields: &FieldsOwned) -> BoxedStrategy<FieldsValueOwned> {
+    match fields {
+        FieldsOwned::Named(fields) => {
+            let values: Vec<_> = fields
+                .iter()
+                .map(|f| {
+                    let name = f.ident.clone().unwrap_or_default();
+                    value(&f.ty).prop_map(move |v| (name.clone(), v))
+                })
+                .collect();
+            values.prop_map(FieldsValueOwned::Named).boxed()
+        }
+        FieldsOwned::Unnamed(fields) => {
+            let values: Vec<_> = fields.iter().map(|f| value(&f.ty)).collect();
+            values.prop_map(FieldsValueOwned::Unnamed).boxed()
+        }
+        FieldsOwned::Unit => Just(FieldsValueOwned::Unit).boxed(),
+    }
+}
+
+/// Random value of the provided type.
+fn value(ty: &TypeOwned) -> BoxedStrategy<ValueOwned> {
+    match ty {
+        TypeOwned::Bool => any::<bool>().prop_map(ValueOwned::Bool).boxed(),
+        TypeOwned::NumericAny(NumericAnyTypeOwned::Base(base)) => {
+            numeric_value(base).prop_map(ValueOwned::Numeric).boxed()
+        }
+        TypeOwned::String => ".{0,8}".prop_map(ValueOwned::String).boxed(),
+        TypeOwned::BoundedString { max_len } => {
+            let regex = format!("[a-z]{{0,{}}}", max_len.0);
+            proptest::string::string_regex(&regex)
+                .unwrap()
+                .prop_map(ValueOwned::String)
+                .boxed()
+        }
+        TypeOwned::Vec(ty) => prop::collection::vec(value(ty), 0..4)
+            .prop_map(ValueOwned::Vec)
+            .boxed(),
+        TypeOwned::BoundedVec { max_len, ty } => {
+            prop::collection::vec(value(ty), 0..=max_len.0 as usize)
+                .prop_map(ValueOwned::Vec)
+                .boxed()
+        }
+        TypeOwned::Array { len, ty } => prop::collection::vec(value(ty), len.0 as usize)
+            .prop_map(ValueOwned::Array)
+            .boxed(),
+        TypeOwned::Tuple(types) => {
+            let values: Vec<_> = types.iter().map(value).collect();
+            values.prop_map(ValueOwned::Tuple).boxed()
+        }
+        TypeOwned::Struct(item_struct) => fields_value(&item_struct.fields)
+            .prop_map(|fields| ValueOwned::Struct { fields })
+            .boxed(),
+        TypeOwned::Enum(item_enum) => {
+            let variants: Vec<_> = item_enum
+                .variants
+                .iter()
+                .map(|v| {
+                    let ident = v.ident.clone();
+                    fields_value(&v.fields).prop_map(move |fields| ValueOwned::Enum {
+                        variant: ident.clone(),
+                        fields,
+                    })
+                })
+                .collect();
+            proptest::strategy::Union::new(variants).boxed()
+        }
+        TypeOwned::Option { some_ty } => proptest::option::of(value(some_ty))
+            .prop_map(|v| ValueOwned::Option(v.map(Box::new)))
+            .boxed(),
+        TypeOwned::Result { ok_ty, err_ty } => prop_oneof![
+            value(ok_ty).prop_map(|v| ValueOwned::Result(Ok(Box::new(v)))),
+            value(err_ty).prop_map(|v| ValueOwned::Result(Err(Box::new(v)))),
+        ]
+        .boxed(),
+        TypeOwned::Box(ty) => value(ty),
+        TypeOwned::Range(base) => (numeric_value(base), numeric_value(base))
+            .prop_map(|(start, end)| ValueOwned::Range(start..end))
+            .boxed(),
+        TypeOwned::RangeInclusive(base) => (numeric_value(base), numeric_value(base))
+            .prop_map(|(start, end)| ValueOwned::RangeInclusive(start..=end))
+            .boxed(),
+        u => unreachable!("{u:?} is not generated"),
+    }
+}
+
+fn ty_and_value() -> impl Strategy<Value = (TypeOwned, ValueOwned)> {
+    ty().prop_flat_map(|ty| {
+        let value = value(&ty);
+        (Just(ty), value)
+    })
+}
+
+proptest! {
+    #[test]
+    fn static_and_dynamic_are_equivalent(everything in everything()) {
+        let bundle = empty_bundle();
+        let mut buf = [0u8; 2048];
+        let static_bytes = everything.to_ww_bytes(&mut buf).unwrap();
+
+        let ty = Everything::ty();
+        let value = everything.to_value();
+        let dynamic_bytes = value.ser_shrink_wrap_dyn(&ty, &bundle).unwrap();
+        prop_assert_eq!(static_bytes, dynamic_bytes.as_slice());
+
+        let des = ValueOwned::des_shrink_wrap_dyn(static_bytes, &ty, &bundle).unwrap();
+        prop_assert_eq!(des, value);
+    }
+
+    #[test]
+    fn dynamic_round_trip((ty, value) in ty_and_value()) {
+        let bundle = empty_bundle();
+        let bytes = value.ser_shrink_wrap_dyn(&ty, &bundle).unwrap();
+        let des = ValueOwned::des_shrink_wrap_dyn(&bytes, &ty, &bundle).unwrap();
+        prop_assert_eq!(des, value);
+    }
+
+    #[test]
+    fn malformed_input_does_not_panic(ty in ty(), bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        let bundle = empty_bundle();
+        let _ = ValueOwned::des_shrink_wrap_dyn(&bytes, &ty, &bundle);
+    }
+
+    #[test]
+    fn static_des_does_not_panic(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        let _ = Everything::from_ww_bytes(&bytes);
+        let _ = Command::from_ww_bytes(&bytes);
+    }
+}
+
+#[test]
+fn zero_sized_items_are_rejected() {
+    let bundle = empty_bundle();
+    let ty = TypeOwned::Vec(Box::new(item_struct(
+        ElementSize::UnsizedFinalStructure,
+        FieldsOwned::Unit,
+    )));
+    // u16_rev Vec length of 0xFFFF, followed by nothing
+    let bytes = [0xFF, 0xFF, 0xFF, 0xFF, 0x0F];
+    assert!(ValueOwned::des_shrink_wrap_dyn(&bytes, &ty, &bundle).is_err());
+}
+
+#[test]
+fn self_referential_types_are_rejected() {
+    let mut bundle = empty_bundle();
+    let ty = TypeOwned::Tuple(vec![TypeOwned::OutOfLine {
+        type_idx: UNib32(0),
+    }]);
+    bundle.types.push(TypeLocationOwned::InLine {
+        ty: ty.clone(),
+        crate_idx: UNib32(0),
+    });
+    assert!(ValueOwned::des_shrink_wrap_dyn(&[], &ty, &bundle).is_err());
+
+    bundle.types[0] = TypeLocationOwned::InLine {
+        ty: TypeOwned::OutOfLine {
+            type_idx: UNib32(0),
+        },
+        crate_idx: UNib32(0),
+    };
+    assert!(ValueOwned::des_shrink_wrap_dyn(&[], &ty, &bundle).is_err());
+}
+
+#[test]
+fn bounds_are_checked() {
+    let bundle = empty_bundle();
+    let ty = BoundedVec::<u8, 2>::ty();
+    let value = vec![1u8, 2, 3].to_value();
+    assert!(value.ser_shrink_wrap_dyn(&ty, &bundle).is_err());
+
+    let ty = BoundedString::<2>::ty();
+    let bytes = "abc".to_string().to_value();
+    assert!(bytes.ser_shrink_wrap_dyn(&ty, &bundle).is_err());
+    let mut buf = [0u8; 8];
+    let bytes = "abc".to_ww_bytes(&mut buf).unwrap();
+    assert!(ValueOwned::des_shrink_wrap_dyn(bytes, &ty, &bundle).is_err());
+}
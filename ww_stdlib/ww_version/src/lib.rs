@@ -134,6 +134,14 @@ impl CompactVersion {
             patch: UNib32(patch),
         }
     }
+
+    pub fn is_protocol_compatible(&self, other: &Self) -> bool {
+        if self.gid != other.gid {
+            return false;
+        }
+        Version::new(self.major.0, self.minor.0, self.patch.0)
+            .is_protocol_compatible(&Version::new(other.major.0, other.minor.0, other.patch.0))
+    }
 }
 
 impl VersionTriplet {
@@ -209,6 +217,17 @@ impl FullVersionOwned {
             .as_ref()
             .is_protocol_compatible(&other.version.as_ref())
     }
+
+    /// Compact version of this crate, if it has a global type id assigned in [ww_global].
+    pub fn to_compact(&self) -> Option<CompactVersion> {
+        let gid = ww_global::by_crate_name(&self.crate_id)?;
+        Some(CompactVersion {
+            gid,
+            major: self.version.major,
+            minor: self.version.minor,
+            patch: self.version.patch,
+        })
+    }
 }
 
 #[cfg(feature = "std")]